@@ -0,0 +1,13 @@
+/**
+ * Sets the `has_discovery` cfg when a transport that looks devices up by name or id is compiled
+ * in, so items used by those transports share one condition instead of the feature list.
+ */
+fn main() {
+    let enabled = |feature: &str| std::env::var_os(format!("CARGO_FEATURE_{}", feature.to_uppercase())).is_some();
+    println!("cargo::rustc-check-cfg=cfg(has_discovery)");
+    if ["usb", "hid", "gpib", "ble", "audio"].iter().any(|feature| enabled(feature))
+        || (enabled("network") && enabled("modbus"))
+    {
+        println!("cargo::rustc-cfg=has_discovery");
+    }
+}
@@ -1,98 +1,1331 @@
-use clap::{Parser, ValueEnum};
+use std::collections::HashMap;
+
+use clap::{CommandFactory, Parser, Subcommand, ValueEnum};
+use clap_complete::Shell;
+use serde::Deserialize;
+
+use crate::{
+    error::EXIT_CONFIG,
+    instruments::reading::{Calibration, ModbusRegister},
+};
 
 /// Hardware measurement arguments
 #[derive(Parser, Debug, Clone, PartialEq)]
 #[command(author, version, about, long_about = None)]
+// Only the flat form requires the device here, the subcommands may take it from a profile or need none
+#[command(mut_arg("device", |arg| {
+    arg.required_unless_present_any(["profiles", "groups", "validate", "discover", "completions", "man"])
+}))]
+// Conflicts between option groups are declared where the groups are combined, as the subcommands take only some of them
+#[command(mut_arg("samples", |arg| arg.conflicts_with_all(["stdin", "script", "scan_channels"])))]
+#[command(mut_arg("average", |arg| arg.conflicts_with("stdin")))]
+#[command(mut_arg("decimate", |arg| arg.conflicts_with("stdin")))]
+#[command(mut_arg("prompt_dut_id", |arg| arg.conflicts_with("stdin")))]
+#[command(mut_arg("interval_ms", |arg| {
+    arg.conflicts_with_all(["stdin", "sweep_start", "profile_file", "discharge_current"])
+}))]
+#[command(mut_arg("record", |arg| {
+    arg.conflicts_with_all([
+        "stdin", "script", "scan_channels", "prompt_dut_id", "samples", "average", "decimate", "sweep_start", "profile_file",
+        "discharge_current",
+    ])
+}))]
 pub struct Args {
-    /// Measurement device
+    #[command(flatten)]
+    pub common: CommonOptions,
+
+    #[command(flatten)]
+    pub connection: ConnectionOptions,
+
+    #[command(flatten)]
+    pub control: ControlOptions,
+
+    #[command(flatten)]
+    pub readings: ReadingOptions,
+
+    #[command(flatten)]
+    pub printing: PrintOptions,
+
+    #[command(flatten)]
+    pub measurement: MeasureOptions,
+
+    #[command(flatten)]
+    pub sequence: SequenceOptions,
+
+    #[command(flatten)]
+    pub sinks: SinkOptions,
+
+    #[command(flatten)]
+    pub monitoring: MonitorOptions,
+
+    #[command(flatten)]
+    pub discovery: DiscoverOptions,
+
+    /// Open the device, print what it needs and can do, the manufacturer, product and serial number of the
+    /// hardware and its *IDN? identity, and exit. The same as the info subcommand.
     #[arg(long)]
-    pub device: Device,
+    pub info: bool,
 
-    /// HID device path
+    /// Decode a file of frames or responses captured earlier, one per line as written by --format=raw
+    /// or as hex, with the reading of --device or the --reader, and print the readings without
+    /// opening the device.
+    #[arg(long, value_name = "FILE")]
+    pub decode: Option<String>,
+
+    /// Benchmark the connection: send the first --command, or *IDN? or Measure, this many times back to back
+    /// and print the round trip percentiles and the sample rate the device sustains, then exit.
+    #[arg(long, value_name = "COUNT")]
+    pub bench: Option<usize>,
+
+    /// Print the completion script of this shell to stdout and exit, e.g. to
+    /// /usr/share/bash-completion/completions/hardware-measurement.
+    #[arg(long, value_name = "SHELL")]
+    pub completions: Option<Shell>,
+
+    /// Print the man page in roff format to stdout and exit, e.g. for `man -l -`.
+    #[arg(long)]
+    pub man: bool,
+
+    /// Verify the hash chain of a log written with --chain-log and exit.
+    #[arg(long)]
+    pub verify_log: Option<String>,
+
+    /// Check configuration files (.toml) and scripts for errors and exit, without opening a device.
+    /// Script commands are checked against the device of --device or --profile.
+    #[arg(long, num_args=1..)]
+    pub validate: Vec<String>,
+
+    /// Daemon mode: serve readings over HTTP on this address, e.g. 127.0.0.1:8080.
+    /// GET /devices/<profile>/reading?max_age=2s returns cached readings up to that age.
+    #[arg(long)]
+    pub serve: Option<String>,
+
+    /// Daemon mode: keep the devices open and answer newline-delimited JSON requests on this Unix domain
+    /// socket, e.g. {"op":"command","commands":["Measure"]}, {"op":"reading"} or {"op":"subscribe","interval":"1s"}.
+    #[arg(long, conflicts_with = "serve")]
+    pub socket: Option<String>,
+
+    /// List the supported devices and the profiles and groups of the configuration file, set by
+    /// the list subcommand.
+    #[arg(skip)]
+    pub list: bool,
+
+    /// Calibration entries of the device, from the `calibration` of its profile.
+    #[arg(skip)]
+    pub calibrations: Vec<Calibration>,
+
+    /// Register map of a Modbus TCP device, from the `registers` of its profile.
+    #[arg(skip)]
+    pub registers: Vec<ModbusRegister>,
+}
+
+/**
+ * Options of every subcommand.
+ */
+#[derive(clap::Args, Debug, Clone, PartialEq)]
+pub struct CommonOptions {
+    /// Configuration file. The default is ~/.config/hardware-measurement/config.toml if it exists.
+    #[arg(long)]
+    pub config: Option<String>,
+
+    /// Named device profile from the configuration file. Repeat to measure with several devices;
+    /// readings are then labelled with the profile name.
+    #[arg(long = "profile")]
+    pub profiles: Vec<String>,
+
+    /// Device group from the configuration file. The commands are sent to every device of the group concurrently.
+    #[arg(long = "group")]
+    pub groups: Vec<String>,
+
+    /// Command macro, e.g. --macro setup-sine="APPL:SIN 1000,1;OUTP ON". Overrides macros in the configuration file.
+    #[arg(long = "macro")]
+    pub macros: Vec<String>,
+
+    /// Log to stderr what happens on the device: -v for the open and claim steps, -vv also for the bytes
+    /// written and read as hex and the checksum validation, -vvv for everything.
+    #[arg(short, long, action = clap::ArgAction::Count)]
+    pub verbose: u8,
+}
+
+/**
+ * Options selecting and opening a device.
+ */
+#[derive(clap::Args, Debug, Clone, PartialEq)]
+pub struct ConnectionOptions {
+    /// Measurement device. Required unless given by the profile.
+    #[arg(long)]
+    pub device: Option<Device>,
+
+    /// HID device path, e.g. /dev/hidraw6. UNI-T meters can also be given as vendor_id:product_id[:serial] in hex.
     #[arg(long)]
     pub hid: Option<String>,
 
-    /// USB device path (vendor_id:product_id)
+    /// USB device: vendor_id:product_id in hex with an optional :serial to choose between identical
+    /// instruments, or a device node such as /dev/bus/usb/001/005, which is opened without enumerating the bus
     #[arg(long)]
     pub usb: Option<String>,
 
+    /// Remember where USB devices were found in this file, so later runs open them without enumerating the bus
+    #[arg(long)]
+    pub device_cache: Option<String>,
+
+    /// Serial port path, e.g. /dev/ttyACM0
+    #[arg(long)]
+    pub serial: Option<String>,
+
+    /// Wait up to this many seconds for the USB, HID or serial device to be plugged in, instead of failing
+    /// at once when it is not present
+    #[arg(long, value_name = "SECONDS")]
+    pub wait_for_device: Option<u64>,
+
+    /// Bluetooth LE address (e.g. A4:C1:38:12:34:56) or advertised name of the meter
+    #[arg(long)]
+    pub ble: Option<String>,
+
+    /// GPIB address of the instrument as board:primary[:secondary], e.g. 0:22 (linux-gpib)
+    #[arg(long, value_name = "ADDRESS")]
+    pub gpib: Option<String>,
+
+    /// Host name or IP address of a network device, with the port if not the default of the protocol,
+    /// e.g. 192.168.1.50 or meter.lan:1502
+    #[arg(long, value_name = "HOST[:PORT]")]
+    pub tcp: Option<String>,
+
+    /// USB interface number.
+    #[arg(long)]
+    pub interface_number: Option<u8>,
+
+    /// USB Bulk IN endpoint address.
+    #[arg(long)]
+    pub bulk_in_address: Option<u8>,
+    /// USB Bulk OUT endpoint address.
+    #[arg(long)]
+    pub bulk_out_address: Option<u8>,
+    /// USB488 Interrupt IN endpoint address. Enables the WaitSrq command.
+    #[arg(long)]
+    pub interrupt_in_address: Option<u8>,
+
+    /// HID feature report (hex, starting with the report id) sent to initialize the meter, e.g. "41 01". Can be repeated.
+    #[arg(long = "hid-feature-report")]
+    pub hid_feature_reports: Vec<String>,
+
+    /// HID-UART bridge chip of the meter cable.
+    #[arg(long)]
+    pub bridge: Option<Bridge>,
+
+    /// Serial baud rate of the serial port or HID-UART bridge.
+    #[arg(long, default_value_t = 9600)]
+    pub baud_rate: u32,
+
+    /// Serial data bits of the serial port or HID-UART bridge.
+    #[arg(long, default_value_t = 8)]
+    pub data_bits: u8,
+
+    /// Serial parity of the serial port or HID-UART bridge. The default is none.
+    #[arg(long)]
+    pub parity: Option<Parity>,
+
+    /// Serial stop bits of the serial port or HID-UART bridge.
+    #[arg(long, default_value_t = 1)]
+    pub stop_bits: u8,
+
+    /// Modbus unit address of the device, 1 to 247. The default is 1.
+    #[arg(long)]
+    pub modbus_address: Option<u8>,
+
+    /// Output channel of typed function generator commands without channel number, e.g. Apply:Sin. The default is 1.
+    #[arg(long)]
+    pub channel: Option<u8>,
+
+    /// Sound card input device name. The default is the system default input.
+    #[arg(long)]
+    pub audio_device: Option<String>,
+
+    /// Terminator appended to text commands sent to SCPI instruments. The default is lf.
+    #[arg(long)]
+    pub terminator: Option<Terminator>,
+
+    /// Terminator ending responses of SCPI instruments. The default is lf; with none a response
+    /// ends at the read timeout or the maximum response size.
+    #[arg(long)]
+    pub response_terminator: Option<Terminator>,
+
+    /// Read a response to every text command sent to SCPI instruments, not only to commands containing `?`.
+    #[arg(long, conflicts_with = "no_response")]
+    pub expect_response: bool,
+
+    /// Never read a response to text commands sent to SCPI instruments, even if they contain `?`.
+    #[arg(long)]
+    pub no_response: bool,
+
+    /// Time to wait for more response data from SCPI instruments. The default is 10000 ms.
+    #[arg(long)]
+    pub read_timeout_ms: Option<u64>,
+
+    /// Maximum size of a SCPI response in bytes. The default is 2000000.
+    #[arg(long)]
+    pub max_response_size: Option<usize>,
+
+    /// Remote mode handling of SCPI instruments. The default is off.
+    #[arg(long)]
+    pub remote: Option<RemoteMode>,
+
+    /// Command returning SCPI instruments to local mode. The default is SYST:LOC.
+    #[arg(long)]
+    pub local_command: Option<String>,
+
+    /// Do not identify SCPI instruments with *IDN? to apply known firmware workarounds.
+    #[arg(long)]
+    pub no_quirks: bool,
+
+    /// Initialization commands run when the device is opened, e.g. --init '*RST' '*CLS'. Responses are discarded.
+    #[arg(long="init", num_args=1..)]
+    pub init_commands: Vec<String>,
+
+    /// Record every byte written to and read from the device in this file, one line per transfer with the
+    /// time, the direction and the bytes as hex, for debugging protocols and bug reports.
+    #[arg(long, value_name = "FILE")]
+    pub capture: Option<String>,
+}
+
+/**
+ * Options sending commands safely.
+ */
+#[derive(clap::Args, Debug, Clone, PartialEq)]
+pub struct ControlOptions {
     /// Send commands to the instrument
     /// Suppported commands are instrument specific.
     ///
+    /// All SCPI devices and Unit161d
+    /// Indicate:Pass|Fail|Attention (1, 3 or 2 beeps with SYST:BEEP; blinks the backlight on the Unit161d)
     /// Unit161d
     /// MinMax, NotMinMax, Range, Auto, Rel, Select2, Hold, Lamp, Select1, PMinMax, NotPeak, Measure
-    /// GenericScpiUsb (Peaktech4055mv)
-    /// Apply:Waveform [Frequency, Amplitude, Offset]
+    /// Peaktech4055mvUsb
+    /// Apply:Waveform [Frequency, Amplitude, Offset] (Waveform: Sin, Square, Ramp, Pulse, Noise, Dc), Reset, Raw:Scpi
+    /// Output:On|Off, Frequency:Hz, Amplitude:Vpp, Offset:Volts, Duty:Percent, Phase:Degrees, and the queries Output?, Frequency?, Amplitude?, Offset?, Duty?, Phase?
+    /// RigolScopeUsb, SiglentScopeUsb
+    /// Measure:Item, Source (Item: Vpp, Vmax, Vmin, Vavg, Vrms, Frequency, Period, RiseTime, FallTime, DutyCycle; Source: CHAN1-4, MATH)
+    /// Segments:Source, First, Last (downloads each segment of the segmented/history memory)
+    /// Waveform:Source (Rigol only; reads the raw waveform memory in chunks, stop the scope first)
+    /// ScpiSwitchUsb, HidRelay
+    /// Close:Channel[,Channel...], Open:Channel[,Channel...], Open:all
+    /// AudioIn
+    /// Measure
+    /// KoradPsu
+    /// Voltage:Volts, Current:Amperes, Output:On|Off, Ocp:On|Off, Ovp:On|Off, Measure, Setpoints, Status, Identify
+    /// RidenPsu
+    /// Voltage:Volts, Current:Amperes, Output:On|Off, Measure, Setpoints, Identify
+    /// Ut71Serial, OwonBle, BrymenHid, Tc66Serial
+    /// Measure
+    /// FlukeSerial
+    /// Measure, Display, Identify, Sessions, Session:Index
+    /// ModbusTcp
+    /// Measure (all registers of the map), Read:Name[,Name...]
+    /// OwonXdmSerial
+    /// Function:VoltageDc|VoltageAc|CurrentDc|CurrentAc|Resistance|Capacitance|Frequency|Period|Diode|Continuity|Temperature, Rate:Slow|Medium|Fast, Measure, Identify
+    /// KeysightDmmUsb, KeysightDmmSerial
+    /// Configure:Function[:Range[:Resolution]], Measure:Function[:Range[:Resolution]], Read, Identify (Function: VoltageDc, VoltageAc, CurrentDc, CurrentAc, Resistance, Resistance4W, Frequency, Period, Diode, Continuity, Capacitance, Temperature)
+    /// AtorchDl24
+    /// Output:On|Off, Current:Amperes, Cutoff:Volts, Reset, Measure, Setpoints
+    /// GenericSerialText
+    /// Measure (reads the next printed line), other text is sent as is with the terminator
+    /// Ut325Serial
+    /// Measure[:T1|T2|T1-T2], Download[:T1|T2|T1-T2] (reads the logged records; the channel is the typed measurement)
     #[arg(long="command", num_args=1..)]
     pub commands: Vec<String>,
 
+    /// File with one command per line, sent after the --command list. Empty lines and lines starting with # are skipped.
+    #[arg(long, conflicts_with_all = ["script", "stdin"])]
+    pub commands_file: Option<String>,
+
+    /// Open the device once and send every line read from stdin as a command, printing its readings
+    /// before the next line is read, until stdin is closed.
+    #[arg(long, conflicts_with_all = ["commands", "script"])]
+    pub stdin: bool,
+
+    /// Measurement script with commands and delay, repeat, expect and log directives.
+    #[arg(long, conflicts_with = "commands")]
+    pub script: Option<String>,
+
+    /// Macros of the configuration file and --macro, expanded in the commands of the --script as it runs.
+    #[arg(skip)]
+    pub script_macros: HashMap<String, Vec<String>>,
+
+    /// Highest voltage in volts any command may set on the device. Commands above it are rejected before anything is sent.
+    #[arg(long)]
+    pub max_voltage: Option<f64>,
+
+    /// Highest current in amperes any command may set on the device. Commands above it are rejected before anything is sent.
+    #[arg(long)]
+    pub max_current: Option<f64>,
+
+    /// Ask for confirmation before sending commands flagged dangerous. Dangerous commands are refused without a terminal.
+    #[arg(long)]
+    pub confirm_dangerous: bool,
+
+    /// Command flagged dangerous, e.g. "CAL*" for every calibration command. A trailing * matches any suffix. Can be repeated.
+    #[arg(long = "dangerous")]
+    pub dangerous_commands: Vec<String>,
+
+    /// Enabling the output is flagged dangerous when the voltage set is above this threshold in volts.
+    #[arg(long)]
+    pub dangerous_output_voltage: Option<f64>,
+
+    /// Read back every setting after it is sent, e.g. FREQ? after FREQ 1000, Setpoints after Voltage:5 or
+    /// Measure after Hold on the UT161D, and fail if the instrument reports a different value.
+    /// Commands are sent one at a time.
+    #[arg(long)]
+    pub verify: bool,
+
+    /// Check every command before it is sent: typed commands are parsed and SCPI commands are checked against the
+    /// grammar of the instrument, if it has one, e.g. the Peaktech 4055MV. A batch with an invalid command is not sent.
+    #[arg(long)]
+    pub check_commands: bool,
+
+    /// Check the SCPI error queue with SYST:ERR? after each command or at the end of each command batch.
+    /// Reported errors fail the batch.
+    #[arg(long)]
+    pub check_errors: Option<CheckErrors>,
+
+    /// Wait with *OPC? after each SCPI configuration command until the instrument has finished processing it.
+    #[arg(long)]
+    pub opc_sync: bool,
+
+    /// Wait this many milliseconds between consecutive SCPI commands, for instruments dropping commands sent back to back.
+    #[arg(long)]
+    pub delay_ms: Option<u64>,
+
+    /// Only send commands that read from the device: SCPI queries and typed reads such as Measure.
+    /// Batches with any other command are refused before anything is written.
+    #[arg(long)]
+    pub read_only: bool,
+
+    /// Number of times a failed command batch is retried, reopening the device each time.
+    #[arg(long, default_value_t = 0)]
+    pub retries: u32,
+
+    /// Emit errors, retries and reconnects as records of type event in the output.
+    #[arg(long)]
+    pub events: bool,
+}
+
+/**
+ * Options choosing how responses are read.
+ */
+#[derive(clap::Args, Debug, Clone, PartialEq)]
+pub struct ReadingOptions {
     /// Reader type for interpreting instrument responses.. For scpi devices the default is ScpiRawReader.
+    /// Analysis computes fundamental frequency, RMS and THD from ASCII waveform data or sound card captures.
+    /// ScpiBlockReader reads IEEE 488.2 binary blocks (#<n><length><payload>) of any size and outputs the payload.
+    /// ScreenshotReader reads the binary block of display queries (:DISP:DATA?, HCOPy:SDUMp:DATA?) and writes the image to --output.
+    /// ScpiIdnReader splits *IDN? responses into manufacturer, model, serial number and firmware.
+    /// KeyValueReader splits key=value or comma separated lines into fields; the default for GenericSerialText.
     #[arg(long)]
     pub reader: Option<Reader>,
 
+    /// Names of the columns of comma separated lines read with the KeyValueReader, e.g. temperature,humidity.
+    /// Columns without a name are called field1, field2, ...
+    #[arg(long, value_delimiter = ',', value_name = "NAMES")]
+    pub text_fields: Vec<String>,
+
+    /// Sample rate in Hz of waveform data, used by the Analysis reader for SCPI devices.
+    #[arg(long)]
+    pub sample_rate: Option<f64>,
+}
+
+/**
+ * Options formatting and writing the readings.
+ */
+#[derive(clap::Args, Debug, Clone, PartialEq)]
+pub struct PrintOptions {
+    /// Output file: the image for the ScreenshotReader, otherwise the printed records. Files ending
+    /// in .gz or .zst are compressed.
+    #[arg(long)]
+    pub output: Option<String>,
+
     /// Output format. The default is Raw.
     #[arg(long)]
     pub format: Option<Format>,
 
-    /// USB interface number.
+    /// Language of mode, flag and field names in the pretty format. Other formats always use
+    /// the canonical names.
     #[arg(long)]
-    pub interface_number: Option<u8>,
+    pub language: Option<Language>,
 
-    /// USB Bulk IN endpoint address.
+    /// Notation of non-integer numbers in the CSV, JSON and pretty formats. The default is Plain.
     #[arg(long)]
-    pub bulk_in_address: Option<u8>,
-    /// USB Bulk OUT endpoint address.
+    pub notation: Option<Notation>,
+
+    /// Color the pretty format: values bold, units dimmed, overload and hardware warnings red,
+    /// held readings yellow. The default auto colors only terminals and respects NO_COLOR.
     #[arg(long)]
-    pub bulk_out_address: Option<u8>,
+    pub color: Option<ColorChoice>,
+
+    /// Field delimiter for CSV output.
+    #[arg(long, default_value_t = ',')]
+    pub csv_delimiter: char,
+
+    /// Decimal separator of numbers in CSV output. With comma the default field delimiter
+    /// becomes `;`, for spreadsheets in locales using a decimal comma. The default is dot.
+    #[arg(long)]
+    pub decimal_separator: Option<DecimalSeparator>,
+
+    /// Write a header row before the first CSV record.
+    #[arg(long)]
+    pub csv_header: bool,
+}
+
+/**
+ * Options taking measurements.
+ */
+#[derive(clap::Args, Debug, Clone, PartialEq)]
+pub struct MeasureOptions {
+    /// Duration of each sound card capture in milliseconds.
+    #[arg(long, default_value_t = 1000)]
+    pub duration_ms: u64,
+
+    /// Factor converting a full scale sound card sample to volts.
+    #[arg(long, default_value_t = 1.0)]
+    pub calibration: f64,
+
+    /// Send the commands this many times back to back over the open connection and write every reading,
+    /// numbered in a `sample` field, e.g. for repeatability checks without monitor mode.
+    #[arg(long)]
+    pub samples: Option<usize>,
+
+    /// Write a moving average over this many readings of each device, channel and quantity instead of the
+    /// readings, in an `average` field and as the typed measurement.
+    #[arg(long)]
+    pub average: Option<usize>,
+
+    /// Kind of the moving average of --average.
+    #[arg(long, requires = "average")]
+    pub average_kind: Option<AverageKind>,
+
+    /// Write only every Nth reading of each device, channel and quantity, or every Nth average with
+    /// --average, e.g. to poll fast and log slowly.
+    #[arg(long)]
+    pub decimate: Option<usize>,
+
+    /// Also write the readings as taken with --average, with a `stream` field of `raw` or `filtered`.
+    #[arg(long, requires = "average")]
+    pub keep_raw: bool,
+
+    /// DUT identifier (e.g. serial number) included in every record.
+    #[arg(long)]
+    pub dut_id: Option<String>,
+
+    /// Include the manufacturer, model and serial number of the device, the reader and the
+    /// commands in every record.
+    #[arg(long)]
+    pub metadata: bool,
+
+    /// Read a DUT identifier from stdin before each measurement cycle, e.g. from a barcode scanner.
+    /// Runs until stdin is closed.
+    #[arg(long, conflicts_with = "dut_id")]
+    pub prompt_dut_id: bool,
+
+    /// Pair each setpoint command with the readings of the queries after it into one record with
+    /// set_<name> columns followed by the measured columns, e.g. for sweeps. Commands are sent one at a time.
+    #[arg(long)]
+    pub pair: bool,
+
+    /// Record the round trip time of every command, and for USB instruments the time until the command was
+    /// sent and until the first response byte arrived, as fields of its readings. A summary is printed at exit.
+    #[arg(long)]
+    pub latency: bool,
+
+    /// Fail with exit code 7 when a reading violates a bound, e.g. "value within 4.95..5.05". `value` is the
+    /// typed measurement value in the base unit, other names are CSV fields. Either end of the range may be left out.
+    #[arg(long = "assert", value_name = "BOUND")]
+    pub assertions: Vec<String>,
+
+    /// Shell command run when an --assert bound is violated, with the violation in HM_ALARM
+    #[arg(long)]
+    pub alarm_hook: Option<String>,
+}
+
+/**
+ * Options of scans, sweeps, ramps and discharge tests.
+ */
+#[derive(clap::Args, Debug, Clone, PartialEq)]
+pub struct SequenceOptions {
+    /// Scan mode: channels closed one at a time on the switch device before the commands are sent to the measurement device.
+    #[arg(long, value_delimiter = ',', conflicts_with_all = ["stdin", "script"])]
+    pub scan_channels: Vec<u16>,
+
+    /// Time in milliseconds to wait after closing a scan channel before measuring.
+    #[arg(long, default_value_t = 0)]
+    pub settle_ms: u64,
+
+    /// Switch device used for scanning.
+    #[arg(long)]
+    pub switch_device: Option<Device>,
+
+    /// HID device path of the switch device.
+    #[arg(long)]
+    pub switch_hid: Option<String>,
+
+    /// USB device of the switch device (vendor_id:product_id).
+    #[arg(long)]
+    pub switch_usb: Option<String>,
+
+    /// Sweep mode: start frequency in Hz. The first device is the generator and the other
+    /// profiles measure at every frequency.
+    #[arg(long, requires = "sweep_stop", conflicts_with_all = ["stdin", "script", "scan_channels"])]
+    pub sweep_start: Option<f64>,
+
+    /// Stop frequency of the sweep in Hz.
+    #[arg(long, requires = "sweep_start")]
+    pub sweep_stop: Option<f64>,
+
+    /// Number of frequencies of the sweep, including start and stop.
+    #[arg(long, default_value_t = 10)]
+    pub sweep_points: u32,
+
+    /// Space the sweep frequencies logarithmically, as for Bode plots, instead of linearly.
+    #[arg(long)]
+    pub sweep_log: bool,
+
+    /// Time in milliseconds to wait after setting a sweep frequency before measuring.
+    #[arg(long, default_value_t = 0)]
+    pub dwell_ms: u64,
+
+    /// Generator command setting the sweep frequency, with {frequency} replaced by the frequency in Hz.
+    #[arg(long, default_value = "FREQ {frequency}")]
+    pub sweep_command: String,
+
+    /// Setpoint profile of a power supply, e.g. `0s 0V; 10s 5V; 60s 5V; 65s 0V`, ramped linearly
+    /// between the points while the commands read back the output at every step.
+    #[arg(long, conflicts_with_all = ["stdin", "script", "scan_channels", "sweep_start"])]
+    pub profile_file: Option<String>,
+
+    /// Interval in milliseconds of the steps of a setpoint profile.
+    #[arg(long, default_value_t = 100)]
+    pub ramp_step_ms: u64,
+
+    /// Command setting the voltage of a setpoint profile, with {value} replaced by the voltage in V.
+    #[arg(long, default_value = "Voltage:{value}")]
+    pub voltage_command: String,
+
+    /// Command setting the current of a setpoint profile or a discharge test, with {value} replaced
+    /// by the current in A.
+    #[arg(long, default_value = "Current:{value}")]
+    pub current_command: String,
+
+    /// Battery discharge test: constant current in A drawn by the first device, the load. The other
+    /// profiles, e.g. a DMM, measure the battery voltage.
+    #[arg(
+        long,
+        requires = "cutoff_voltage",
+        conflicts_with_all = ["stdin", "script", "scan_channels", "sweep_start", "profile_file"]
+    )]
+    pub discharge_current: Option<f64>,
+
+    /// Battery voltage in V ending a discharge test.
+    #[arg(long, requires = "discharge_current")]
+    pub cutoff_voltage: Option<f64>,
+
+    /// Interval in milliseconds of the readings of a discharge test.
+    #[arg(long, default_value_t = 1000)]
+    pub discharge_interval_ms: u64,
+
+    /// Command switching the load on at the start of a discharge test.
+    #[arg(long, default_value = "Output:On")]
+    pub load_on_command: String,
+
+    /// Command switching the load off at the end of a discharge test.
+    #[arg(long, default_value = "Output:Off")]
+    pub load_off_command: String,
+}
+
+/**
+ * Options of the outputs besides the printed readings.
+ */
+#[derive(clap::Args, Debug, Clone, PartialEq)]
+pub struct SinkOptions {
+    /// Start a new output file every this many seconds. The start time is inserted in the file
+    /// name of each segment, e.g. capture-1700000000.csv.gz.
+    #[arg(long, requires = "output")]
+    pub rotate_seconds: Option<u64>,
+
+    /// Upload finished output segments to an S3-compatible bucket, http[s]://host[:port]/bucket[/prefix].
+    /// Segments are spooled locally and retried while the bucket is unreachable.
+    #[arg(long, requires = "rotate_seconds")]
+    pub s3_url: Option<String>,
+
+    /// Signing region of the S3 bucket.
+    #[arg(long, default_value = "us-east-1")]
+    pub s3_region: String,
+
+    /// S3 access key id. The default is the AWS_ACCESS_KEY_ID environment variable.
+    #[arg(long)]
+    pub s3_access_key: Option<String>,
+
+    /// S3 secret access key. The default is the AWS_SECRET_ACCESS_KEY environment variable.
+    #[arg(long)]
+    pub s3_secret_key: Option<String>,
+
+    /// Directory of segments waiting for upload. The default is spool next to the output file.
+    #[arg(long)]
+    pub spool_dir: Option<String>,
+
+    /// Append readings to a tamper-evident log where every record is hash chained to the previous one.
+    #[arg(long)]
+    pub chain_log: Option<String>,
+
+    /// Also send readings and errors to syslog (RFC 5424) or journald with the reading fields as structured data.
+    #[arg(long)]
+    pub log_to: Option<LogTo>,
+
+    /// Syslog socket path or UDP host:port. The default is /dev/log.
+    #[arg(long, requires = "log_to")]
+    pub syslog_address: Option<String>,
+
+    /// Application name of the syslog and journald messages and the OTLP service.name.
+    #[arg(long, default_value = "hardware-measurement")]
+    pub log_identifier: String,
+
+    /// Export measurements as OpenTelemetry metrics to an OTLP/HTTP collector, http://host[:port][/path].
+    /// The path defaults to /v1/metrics.
+    #[arg(long)]
+    pub otlp_endpoint: Option<String>,
+
+    /// Time between OTLP exports in milliseconds.
+    #[arg(long, default_value_t = 10000)]
+    pub otlp_interval_ms: u64,
+
+    /// Number of readings queued for the OTLP export task before --sink-overflow applies.
+    #[arg(long, default_value_t = 10000)]
+    pub otlp_queue: usize,
+
+    /// What the OTLP and WebSocket sinks do with a reading when their queue is full.
+    #[arg(long, value_enum, default_value_t = SinkOverflow::DropOldest)]
+    pub sink_overflow: SinkOverflow,
+
+    /// Persist records the syslog and OTLP sinks could not deliver in this directory, so they are
+    /// backfilled with their original timestamps also after a restart. Memory only by default.
+    #[arg(long)]
+    pub buffer_dir: Option<String>,
+
+    /// Largest number of undelivered records kept per sink; the oldest are dropped beyond it.
+    #[arg(long, default_value_t = 100000)]
+    pub buffer_limit: usize,
 }
 
+/**
+ * Options of repeated measurements.
+ */
+#[derive(clap::Args, Debug, Clone, PartialEq)]
+pub struct MonitorOptions {
+    /// Monitor mode: repeat the commands with this interval in milliseconds until interrupted.
+    #[arg(long)]
+    pub interval_ms: Option<u64>,
+
+    /// Record mode for the UT161D: keep the device open and measure in a tight loop until
+    /// interrupted, skipping responses with a bad checksum instead of failing.
+    #[arg(long, conflicts_with = "interval_ms")]
+    pub record: bool,
+
+    /// Serve readings as JSON messages to WebSocket clients on this address (e.g. 127.0.0.1:9001).
+    #[arg(long)]
+    pub websocket: Option<String>,
+
+    /// Number of messages buffered per WebSocket client before --sink-overflow applies.
+    #[arg(long, default_value_t = 64)]
+    pub websocket_buffer: usize,
+
+    /// Largest number of messages sent to each WebSocket client per second; messages beyond it
+    /// wait in the client's buffer.
+    #[arg(long)]
+    pub websocket_max_rate: Option<f64>,
+}
+
+/**
+ * Options discovering network instruments.
+ */
+#[derive(clap::Args, Debug, Clone, PartialEq)]
+pub struct DiscoverOptions {
+    /// Browse the local network for LXI and raw SCPI instruments (mDNS _lxi._tcp and _scpi-raw._tcp), identify
+    /// them with *IDN? and print their address, model and serial number. Also taken by the list subcommand.
+    #[arg(long)]
+    pub discover: bool,
+
+    /// How long to collect mDNS responses when discovering instruments, in milliseconds.
+    #[arg(long, default_value_t = 2000)]
+    pub discover_ms: u64,
+}
+
+
 impl Args {
     /**
-     * Parses command-line arguments and returns an Args instance.
+     * Parses command-line arguments and returns an Args instance. Invalid arguments exit with
+     * the exit code of configuration errors rather than clap's 2, which means device not found.
      *
      * # Returns
      * An Args instance containing the parsed arguments.
      */
     pub fn parse_args() -> Self {
-        Args::parse()
+        Args::try_parse_modes(std::env::args_os()).unwrap_or_else(|e| {
+            let _ = e.print();
+            // Help and version are not errors
+            std::process::exit(match e.use_stderr() {
+                true => EXIT_CONFIG as i32,
+                false => 0,
+            })
+        })
+    }
+}
+
+/// Hardware measurement arguments
+///
+/// The flat options or a subcommand with its options.
+#[derive(Parser)]
+#[command(author, version, about, long_about = None)]
+#[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
+struct Cli {
+    #[command(flatten)]
+    args: Args,
+
+    #[command(subcommand)]
+    mode: Option<Mode>,
+}
+
+impl Args {
+    /**
+     * Builds the command line with the flat options and the subcommands, for the shell
+     * completions and man page.
+     *
+     * # Returns
+     * The command.
+     */
+    pub fn command_with_modes() -> clap::Command {
+        Cli::command()
+    }
+
+    /**
+     * Parses the arguments, either the flat options or a subcommand with its options. The options
+     * of a subcommand are moved into the Args of the flat form, so the rest of the application
+     * does not depend on how they were given.
+     *
+     * # Arguments
+     * `args` - The command line, starting with the program name.
+     *
+     * # Returns
+     * A Result containing the Args or the clap error.
+     */
+    pub fn try_parse_modes<I, T>(args: I) -> Result<Self, clap::Error>
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<std::ffi::OsString> + Clone,
+    {
+        let Cli { mut args, mode } = Cli::try_parse_from(args)?;
+        if let Some(mode) = mode {
+            mode.apply(&mut args);
+        }
+        Ok(args)
+    }
+}
+
+/**
+ * Enum representing the subcommands, each with only the options that apply to it.
+ */
+#[derive(Subcommand, Debug, Clone, PartialEq)]
+pub enum Mode {
+    /// Measure once and exit.
+    Measure(MeasureArgs),
+    /// Send setpoints and other commands, with the safety limits, and exit.
+    Control(ControlArgs),
+    /// Measure every --interval-ms until stopped.
+    Monitor(Box<MonitorArgs>),
+    /// List the supported devices and the profiles and groups of the configuration file.
+    List(ListArgs),
+    /// Show what a device needs and can do, and its identity if it is connected.
+    Info(InfoArgs),
+    /// Serve readings of the profiles over HTTP.
+    Serve(ServeArgs),
+    /// Decode captured frames or responses with the reading of a device, without the device.
+    Decode(DecodeArgs),
+    /// Measure the round trip latency and the sample rate a device sustains.
+    Bench(BenchArgs),
+    /// Print the completion script of a shell.
+    Completions(CompletionsArgs),
+    /// Print the man page.
+    Man(ManArgs),
+}
+
+impl Mode {
+    /**
+     * Moves the options of the subcommand into the Args of the flat form, whose other options
+     * keep their defaults.
+     *
+     * # Arguments
+     * `args` - The Args parsed next to the subcommand.
+     */
+    fn apply(self, args: &mut Args) {
+        match self {
+            Mode::Measure(measure) => measure.apply(args),
+            Mode::Control(control) => {
+                args.common = control.common;
+                args.connection = control.connection;
+                args.control = control.control;
+            }
+            Mode::Monitor(monitor) => {
+                let monitor = *monitor;
+                monitor.measure.apply(args);
+                args.sinks = monitor.sinks;
+                args.monitoring = monitor.monitoring;
+            }
+            Mode::List(list) => {
+                args.common = list.common;
+                args.discovery = list.discovery;
+                args.list = true;
+            }
+            Mode::Info(info) => {
+                args.common = info.common;
+                args.connection = info.connection;
+                args.info = true;
+            }
+            Mode::Serve(serve) => {
+                args.common = serve.common;
+                args.connection = serve.connection;
+                args.serve = serve.listen;
+                args.socket = serve.socket;
+            }
+            Mode::Decode(decode) => {
+                args.common = decode.common;
+                args.connection.device = decode.device;
+                args.decode = Some(decode.input);
+                args.readings = decode.readings;
+                args.printing = decode.printing;
+            }
+            Mode::Bench(bench) => {
+                args.common = bench.common;
+                args.connection = bench.connection;
+                args.control.commands = bench.commands;
+                args.bench = Some(bench.count);
+            }
+            Mode::Completions(completions) => {
+                args.common = completions.common;
+                args.completions = Some(completions.shell);
+            }
+            Mode::Man(man) => {
+                args.common = man.common;
+                args.man = true;
+            }
+        }
+    }
+}
+
+/**
+ * Options of the measure subcommand.
+ */
+#[derive(clap::Args, Debug, Clone, PartialEq)]
+pub struct MeasureArgs {
+    #[command(flatten)]
+    pub common: CommonOptions,
+
+    #[command(flatten)]
+    pub connection: ConnectionOptions,
+
+    /// Query sent to the device, e.g. Measure or MEAS:VOLT?. Can be repeated.
+    #[arg(long = "command", num_args = 1..)]
+    pub commands: Vec<String>,
+
+    #[command(flatten)]
+    pub readings: ReadingOptions,
+
+    #[command(flatten)]
+    pub printing: PrintOptions,
+
+    #[command(flatten)]
+    pub measurement: MeasureOptions,
+}
+
+impl MeasureArgs {
+    /**
+     * Moves the options into the Args of the flat form.
+     *
+     * # Arguments
+     * `args` - The Args parsed next to the subcommand.
+     */
+    fn apply(self, args: &mut Args) {
+        args.common = self.common;
+        args.connection = self.connection;
+        args.control.commands = self.commands;
+        args.readings = self.readings;
+        args.printing = self.printing;
+        args.measurement = self.measurement;
     }
 }
 
+/**
+ * Options of the control subcommand.
+ */
+#[derive(clap::Args, Debug, Clone, PartialEq)]
+pub struct ControlArgs {
+    #[command(flatten)]
+    pub common: CommonOptions,
+
+    #[command(flatten)]
+    pub connection: ConnectionOptions,
+
+    #[command(flatten)]
+    pub control: ControlOptions,
+}
+
+/**
+ * Options of the monitor subcommand, which needs --interval-ms.
+ */
+#[derive(clap::Args, Debug, Clone, PartialEq)]
+#[command(mut_arg("interval_ms", |arg| arg.required(true)))]
+#[command(mut_arg("record", |arg| arg.conflicts_with_all(["prompt_dut_id", "samples", "average", "decimate"])))]
+pub struct MonitorArgs {
+    #[command(flatten)]
+    pub measure: MeasureArgs,
+
+    #[command(flatten)]
+    pub sinks: SinkOptions,
+
+    #[command(flatten)]
+    pub monitoring: MonitorOptions,
+}
+
+/**
+ * Options of the list subcommand.
+ */
+#[derive(clap::Args, Debug, Clone, PartialEq)]
+pub struct ListArgs {
+    #[command(flatten)]
+    pub common: CommonOptions,
+
+    #[command(flatten)]
+    pub discovery: DiscoverOptions,
+}
+
+/**
+ * Options of the info subcommand.
+ */
+#[derive(clap::Args, Debug, Clone, PartialEq)]
+pub struct InfoArgs {
+    #[command(flatten)]
+    pub common: CommonOptions,
+
+    #[command(flatten)]
+    pub connection: ConnectionOptions,
+}
+
+/**
+ * Options of the serve subcommand.
+ */
+#[derive(clap::Args, Debug, Clone, PartialEq)]
+pub struct ServeArgs {
+    #[command(flatten)]
+    pub common: CommonOptions,
+
+    #[command(flatten)]
+    pub connection: ConnectionOptions,
+
+    /// Serve readings over HTTP on this address, e.g. 127.0.0.1:8080.
+    /// GET /devices/<profile>/reading?max_age=2s returns cached readings up to that age.
+    #[arg(long, value_name = "ADDRESS", required_unless_present = "socket")]
+    pub listen: Option<String>,
+
+    /// Keep the devices open and answer newline-delimited JSON requests on this Unix domain socket
+    /// instead, e.g. {"op":"command","commands":["Measure"]}, {"op":"reading"} or {"op":"subscribe","interval":"1s"}.
+    #[arg(long, conflicts_with = "listen")]
+    pub socket: Option<String>,
+}
+
+/**
+ * Options of the decode subcommand.
+ */
+#[derive(clap::Args, Debug, Clone, PartialEq)]
+pub struct DecodeArgs {
+    #[command(flatten)]
+    pub common: CommonOptions,
+
+    /// Device whose reading decodes the file. Required unless given by the profile or the --reader.
+    #[arg(long)]
+    pub device: Option<Device>,
+
+    /// File of frames or responses captured earlier, one per line as written by --format=raw or as hex.
+    #[arg(long, value_name = "FILE")]
+    pub input: String,
+
+    #[command(flatten)]
+    pub readings: ReadingOptions,
+
+    #[command(flatten)]
+    pub printing: PrintOptions,
+}
+
+/**
+ * Options of the bench subcommand.
+ */
+#[derive(clap::Args, Debug, Clone, PartialEq)]
+pub struct BenchArgs {
+    #[command(flatten)]
+    pub common: CommonOptions,
+
+    #[command(flatten)]
+    pub connection: ConnectionOptions,
+
+    /// Command sent back to back, the first one if repeated. The default is *IDN? or Measure.
+    #[arg(long = "command", num_args = 1..)]
+    pub commands: Vec<String>,
+
+    /// Number of round trips.
+    #[arg(long, default_value_t = 100)]
+    pub count: usize,
+}
+
+/**
+ * Options of the completions subcommand.
+ */
+#[derive(clap::Args, Debug, Clone, PartialEq)]
+pub struct CompletionsArgs {
+    #[command(flatten)]
+    pub common: CommonOptions,
+
+    /// Shell of the completion script.
+    #[arg(long)]
+    pub shell: Shell,
+}
+
+/**
+ * Options of the man subcommand.
+ */
+#[derive(clap::Args, Debug, Clone, PartialEq)]
+pub struct ManArgs {
+    #[command(flatten)]
+    pub common: CommonOptions,
+}
+
 /**
  * Enum representing supported measurement devices.
  */
-#[derive(Debug, Clone, ValueEnum, PartialEq, Eq)]
+#[derive(Debug, Clone, ValueEnum, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
 pub enum Device {
     Unit161d,
     GenericScpiUsb,
-    Peaktech4055mvUsb
+    Peaktech4055mvUsb,
+    RigolScopeUsb,
+    SiglentScopeUsb,
+    ScpiSwitchUsb,
+    KeysightDmmUsb,
+    HidRelay,
+    HidUart,
+    KoradPsu,
+    RidenPsu,
+    Ut71Serial,
+    OwonBle,
+    BrymenHid,
+    FlukeSerial,
+    Tc66Serial,
+    OwonXdmSerial,
+    KeysightDmmSerial,
+    AtorchDl24,
+    GenericSerialText,
+    Ut325Serial,
+    AudioIn,
+    GenericScpiGpib,
+    ModbusTcp,
 }
 /**
  * Enum representing supported reader types.
  */
-#[derive(Debug, Clone, ValueEnum, PartialEq, Eq)]
+#[derive(Debug, Clone, ValueEnum, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+#[allow(clippy::enum_variant_names)]
 pub enum Reader {
-    ScpiRawReader,    
+    ScpiRawReader,
+    Analysis,
+    ScreenshotReader,
+    ScpiBlockReader,
+    ScpiIdnReader,
+    KeyValueReader,
+}
+
+/**
+ * Enum representing HID-UART bridge chips used in meter cables.
+ */
+#[derive(Debug, Clone, ValueEnum, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Bridge {
+    Cp2110,
+    Ch9325,
+}
+
+/**
+ * Enum representing serial parity settings.
+ */
+#[derive(Debug, Clone, ValueEnum, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Parity {
+    None,
+    Odd,
+    Even,
+}
+
+/**
+ * Enum representing remote/local mode handling of SCPI instruments.
+ */
+#[derive(Debug, Clone, ValueEnum, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum RemoteMode {
+    /// Remote and local mode are left to the instrument.
+    Off,
+    /// SYST:REM is sent on connect.
+    Remote,
+    /// SYST:REM is sent on connect and the local command when the connection is closed, also after errors.
+    RemoteLocal,
+}
+
+/**
+ * Enum representing when the SCPI error queue is checked.
+ */
+#[derive(Debug, Clone, Copy, ValueEnum, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CheckErrors {
+    /// SYST:ERR? is sent after each command.
+    Command,
+    /// SYST:ERR? is sent after the last command of the batch.
+    Batch,
+}
+
+/**
+ * Enum representing command and response terminators of the raw command paths.
+ */
+#[derive(Debug, Clone, ValueEnum, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Terminator {
+    Lf,
+    Cr,
+    Crlf,
+    None,
 }
 
 /**
  * Enum representing supported output formats.
  */
-#[derive(Debug, Clone, ValueEnum, PartialEq, Eq)]
+#[derive(Debug, Clone, ValueEnum, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
 pub enum Format {
     Csv,
+    Json,
     Raw,
-    RawString
+    RawString,
+    /// Raw bytes as hex and ASCII dump in the layout of xxd, for reverse engineering protocols.
+    Hex,
+    /// Human readable, with translated mode, flag and field names.
+    Pretty,
+    /// Long format CSV with one (timestamp, device, channel, field, value) record per field.
+    Tidy,
+    /// Every field of a reading as labelled name=value pairs, including inactive flags.
+    KeyValue,
+    /// Value Change Dump of the numeric and boolean fields, for sigrok and PulseView.
+    Vcd,
+    /// Samples of waveform readings as (time, value) CSV records.
+    WaveformCsv,
+    /// Samples of a waveform reading as 16-bit WAV file, with the sample rate of the preamble.
+    Wav,
+    /// Apache Parquet file with timestamp, device, mode, value, unit and flags columns, for long captures.
+    Parquet,
+}
+
+/**
+ * Enum representing the system logs readings can be sent to.
+ */
+#[derive(Debug, Clone, Copy, ValueEnum, PartialEq, Eq)]
+pub enum LogTo {
+    Syslog,
+    Journald,
+}
+
+/**
+ * Enum representing notations of numbers in the output, e.g. 0.00047 as `0.00047`,
+ * `4.7e-4` or `470e-6`.
+ */
+#[derive(Debug, Clone, Copy, ValueEnum, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Notation {
+    #[default]
+    Plain,
+    Scientific,
+    Engineering,
+}
+
+/**
+ * Enum representing what the streaming sinks do with a reading when their queue is full.
+ */
+#[derive(Debug, Clone, Copy, ValueEnum, PartialEq, Eq, Default)]
+pub enum SinkOverflow {
+    /// Drop the oldest queued reading.
+    #[default]
+    DropOldest,
+    /// Drop the queued readings and keep only the newest, so a consumer catching up gets the current value.
+    KeepLatest,
+    /// Wait for the consumer, delaying acquisition but dropping nothing.
+    Block,
+}
+
+/**
+ * Enum representing the moving averages of --average.
+ */
+#[derive(Debug, Clone, Copy, ValueEnum, PartialEq, Eq, Default)]
+pub enum AverageKind {
+    /// Mean of the last N readings.
+    #[default]
+    Boxcar,
+    /// Exponential moving average weighting the newest reading by 2 / (N + 1).
+    Exponential,
+}
+
+/**
+ * Enum representing decimal separators of numbers in CSV output.
+ */
+#[derive(Debug, Clone, Copy, ValueEnum, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum DecimalSeparator {
+    #[default]
+    Dot,
+    Comma,
+}
+
+/**
+ * Enum representing when the pretty output format is colored.
+ */
+#[derive(Debug, Clone, Copy, ValueEnum, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ColorChoice {
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+/**
+ * Enum representing languages of the pretty output format.
+ */
+#[derive(Debug, Clone, Copy, ValueEnum, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Language {
+    #[default]
+    En,
+    De,
+    Nb,
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
-    use clap::Parser;
+    use clap::{error::ErrorKind, Parser};
 
     #[test]
     fn test_parse_args() {
-        let args = Args::parse_from(&[
+        let args = Args::parse_from([
             "test_program",
             "--device",
             "unit161d",
@@ -104,14 +1337,77 @@ mod test {
             "Hold",
         ]);
 
-        assert_eq!(args.device, Device::Unit161d);
-        assert_eq!(args.hid, Some("/dev/hidraw0".to_string()));
-        assert_eq!(args.commands, vec!["Measure".to_string(), "Hold".to_string()]);
+        assert_eq!(args.connection.device, Some(Device::Unit161d));
+        assert_eq!(args.connection.hid, Some("/dev/hidraw0".to_string()));
+        assert_eq!(args.control.commands, vec!["Measure".to_string(), "Hold".to_string()]);
+    }
+
+    #[test]
+    fn test_command() {
+        Args::command_with_modes().debug_assert();
+    }
+
+    #[test]
+    fn test_parse_modes() {
+        let flat = ["--device=korad-psu", "--serial=/dev/ttyACM0", "--command=Measure", "--format=csv", "--samples=3"];
+        let args = Args::try_parse_modes(["test_program", "measure"].into_iter().chain(flat)).unwrap();
+        assert_eq!(args.connection.serial, Some("/dev/ttyACM0".to_string()));
+        // Flat options still work, with the same result
+        assert_eq!(args, Args::try_parse_modes(["test_program"].into_iter().chain(flat)).unwrap());
+        let args = Args::try_parse_modes(["test_program", "monitor", "--profile=psu", "--interval-ms=100", "--websocket=127.0.0.1:9001"]).unwrap();
+        assert_eq!((args.monitoring.interval_ms, args.common.profiles), (Some(100), vec!["psu".to_string()]));
+        let args = Args::try_parse_modes(["test_program", "serve", "--profile=psu", "--listen=127.0.0.1:8080"]).unwrap();
+        assert_eq!(args.serve, Some("127.0.0.1:8080".to_string()));
+        let args = Args::try_parse_modes(["test_program", "serve", "--profile=psu", "--socket=/run/hm.sock"]).unwrap();
+        assert_eq!(args.socket, Some("/run/hm.sock".to_string()));
+        assert!(Args::try_parse_modes(["test_program", "serve", "--profile=psu"]).is_err());
+        assert!(Args::try_parse_modes(["test_program", "list"]).unwrap().list);
+        assert!(Args::try_parse_modes(["test_program", "list", "--discover", "--discover-ms=500"]).unwrap().discovery.discover);
+        assert!(Args::try_parse_modes(["test_program", "info", "--discover"]).is_err());
+        let args = Args::try_parse_modes(["test_program", "decode", "--device=unit161d", "--input=capture.txt", "--format=csv"]).unwrap();
+        assert_eq!((args.connection.device, args.decode), (Some(Device::Unit161d), Some("capture.txt".to_string())));
+        assert!(Args::try_parse_modes(["test_program", "decode", "--device=unit161d"]).is_err());
+        assert!(Args::try_parse_modes(["test_program", "decode", "--device=unit161d", "--input=capture.txt", "--hid=/dev/hidraw0"]).is_err());
+        let args = Args::try_parse_modes(["test_program", "bench", "--device=generic-scpi-usb", "--usb=1ab1:0e11"]).unwrap();
+        assert_eq!(args.bench, Some(100));
+        let args = Args::try_parse_modes(["test_program", "completions", "--shell=zsh"]).unwrap();
+        assert_eq!(args.completions, Some(Shell::Zsh));
+        assert!(Args::try_parse_modes(["test_program", "completions"]).is_err());
+        assert!(Args::try_parse_modes(["test_program", "man"]).unwrap().man);
+        assert!(Args::try_parse_modes(["test_program", "man", "--device=unit161d"]).is_err());
+        assert!(Args::try_parse_modes(["test_program", "--completions=fish"]).is_ok());
+        let args = Args::try_parse_modes(["test_program", "bench", "--device=korad-psu", "--count=10", "--command=Measure"]).unwrap();
+        assert_eq!((args.bench, args.control.commands), (Some(10), vec!["Measure".to_string()]));
+        assert!(Args::try_parse_modes(["test_program", "bench", "--device=korad-psu", "--format=csv"]).is_err());
+
+        let control = ["test_program", "control", "--device=korad-psu", "--command", "Voltage:5", "Output:On", "--max-voltage=6"];
+        let args = Args::try_parse_modes(control).unwrap();
+        assert_eq!((args.control.commands.len(), args.control.max_voltage), (2, Some(6.0)));
+
+        for (mode, option) in [
+            ("measure", "--websocket=127.0.0.1:9001"),
+            ("measure", "--max-voltage=6"),
+            ("measure", "--scan-channels=1"),
+            ("measure", "--s3-url=http://localhost:9000/bucket"),
+            ("control", "--format=csv"),
+            ("control", "--interval-ms=100"),
+        ] {
+            let error = Args::try_parse_modes(["test_program", mode, "--device=korad-psu", option]).unwrap_err();
+            assert_eq!(error.kind(), ErrorKind::UnknownArgument, "{} {}", mode, option);
+        }
+        // Conflicts between the option groups of a subcommand still hold
+        let error = Args::try_parse_modes(["test_program", "monitor", "--device=korad-psu", "--interval-ms=100", "--record"]).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::ArgumentConflict);
+        let error = Args::try_parse_modes(["test_program", "control", "--device=korad-psu", "--commands-file=setup.txt", "--stdin"]).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::ArgumentConflict);
+        assert!(Args::try_parse_modes(["test_program", "measure", "--device=korad-psu", "--samples=3", "--average=2"]).is_ok());
+        assert!(Args::try_parse_modes(["test_program", "monitor", "--device=korad-psu"]).is_err());
+        assert!(Args::try_parse_modes(["test_program", "--device=korad-psu", "measure"]).is_err());
     }
 
     #[test]
     fn test_parse_args_peaktech() {
-        let args = Args::parse_from(&[
+        let args = Args::parse_from([
             "test_program",
             "--device",
             "generic-scpi-usb",
@@ -121,8 +1417,184 @@ mod test {
             "Apply:Waveform 1000, 5, 0"
         ]);
 
-        assert_eq!(args.device, Device::GenericScpiUsb);
-        assert_eq!(args.usb, Some("1234:5678".to_string()));
-        assert_eq!(args.commands, vec!["Apply:Waveform 1000, 5, 0".to_string()]);
+        assert_eq!(args.connection.device, Some(Device::GenericScpiUsb));
+        assert_eq!(args.connection.usb, Some("1234:5678".to_string()));
+        assert_eq!(args.control.commands, vec!["Apply:Waveform 1000, 5, 0".to_string()]);
+    }
+
+    #[test]
+    fn test_parse_args_audio_in() {
+        let args = Args::parse_from([
+            "test_program",
+            "--device",
+            "audio-in",
+            "--calibration",
+            "2.5",
+            "--duration-ms",
+            "250",
+            "--command",
+            "Measure",
+        ]);
+
+        assert_eq!(args.connection.device, Some(Device::AudioIn));
+        assert_eq!(args.connection.audio_device, None);
+        assert_eq!(args.measurement.calibration, 2.5);
+        assert_eq!(args.measurement.duration_ms, 250);
+    }
+
+    #[test]
+    fn test_parse_args_analysis() {
+        let args = Args::parse_from([
+            "test_program",
+            "--device",
+            "generic-scpi-usb",
+            "--usb",
+            "1ab1:4ce",
+            "--reader",
+            "analysis",
+            "--sample-rate",
+            "1e6",
+            "--command",
+            ":WAV:DATA?",
+        ]);
+
+        assert_eq!(args.readings.reader, Some(Reader::Analysis));
+        assert_eq!(args.readings.sample_rate, Some(1e6));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_parse_args_monitor_websocket() {
+        let args = Args::parse_from([
+            "test_program",
+            "--device",
+            "unit161d",
+            "--interval-ms",
+            "500",
+            "--websocket",
+            "127.0.0.1:9001",
+        ]);
+
+        assert_eq!(args.monitoring.interval_ms, Some(500));
+        assert_eq!(args.monitoring.websocket, Some("127.0.0.1:9001".to_string()));
+        assert_eq!(args.monitoring.websocket_buffer, 64);
+    }
+
+    #[test]
+    fn test_parse_args_scan() {
+        let args = Args::parse_from([
+            "test_program",
+            "--device",
+            "unit161d",
+            "--hid",
+            "/dev/hidraw0",
+            "--switch-device",
+            "hid-relay",
+            "--switch-hid",
+            "/dev/hidraw1",
+            "--scan-channels",
+            "1,2,4",
+            "--settle-ms",
+            "200",
+            "--command",
+            "Measure",
+        ]);
+
+        assert_eq!(args.sequence.scan_channels, vec![1, 2, 4]);
+        assert_eq!(args.sequence.settle_ms, 200);
+        assert_eq!(args.sequence.switch_device, Some(Device::HidRelay));
+        assert_eq!(args.sequence.switch_hid, Some("/dev/hidraw1".to_string()));
+    }
+
+    #[test]
+    fn test_parse_args_csv() {
+        let args = Args::parse_from([
+            "test_program",
+            "--device",
+            "unit161d",
+            "--format",
+            "csv",
+            "--csv-delimiter",
+            ";",
+            "--csv-header",
+        ]);
+
+        assert_eq!(args.printing.format, Some(Format::Csv));
+        assert_eq!(args.printing.csv_delimiter, ';');
+        assert!(args.printing.csv_header);
+    }
+
+    #[test]
+    fn test_parse_args_dut_id() {
+        let args = Args::parse_from(["test_program", "--device", "unit161d", "--prompt-dut-id"]);
+        assert!(args.measurement.prompt_dut_id);
+        let result = Args::try_parse_from([
+            "test_program",
+            "--device",
+            "unit161d",
+            "--prompt-dut-id",
+            "--dut-id",
+            "SN1",
+        ]);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_parse_args_otlp() {
+        let args = Args::parse_from([
+            "test_program",
+            "--device",
+            "korad-psu",
+            "--otlp-endpoint",
+            "http://collector:4318",
+        ]);
+        assert_eq!(args.sinks.otlp_endpoint.as_deref(), Some("http://collector:4318"));
+        assert_eq!(args.sinks.otlp_interval_ms, 10000);
+        assert_eq!(args.sinks.otlp_queue, 10000);
+        assert_eq!(args.sinks.sink_overflow, SinkOverflow::DropOldest);
+    }
+
+    #[test]
+    fn test_parse_args_sink_overflow() {
+        let args = Args::parse_from([
+            "test_program",
+            "--device",
+            "unit161d",
+            "--interval-ms",
+            "100",
+            "--websocket",
+            "127.0.0.1:9001",
+            "--websocket-max-rate",
+            "2.5",
+            "--sink-overflow",
+            "keep-latest",
+        ]);
+        assert_eq!(args.sinks.sink_overflow, SinkOverflow::KeepLatest);
+        assert_eq!(args.monitoring.websocket_max_rate, Some(2.5));
+        assert!(Args::try_parse_from(["test_program", "--device", "unit161d", "--sink-overflow", "newest"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_args_s3() {
+        let args = Args::parse_from([
+            "test_program",
+            "--device",
+            "unit161d",
+            "--output",
+            "capture.csv.gz",
+            "--rotate-seconds",
+            "3600",
+            "--s3-url",
+            "http://minio.local:9000/bench",
+        ]);
+        assert_eq!(args.sinks.rotate_seconds, Some(3600));
+        assert_eq!(args.sinks.s3_region, "us-east-1");
+        let result = Args::try_parse_from([
+            "test_program",
+            "--device",
+            "unit161d",
+            "--s3-url",
+            "http://minio.local:9000/bench",
+        ]);
+        assert!(result.is_err());
+    }
+}
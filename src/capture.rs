@@ -0,0 +1,166 @@
+use std::{
+    fs::File,
+    io::{LineWriter, Write},
+    sync::{Mutex, OnceLock},
+};
+#[cfg(any(feature = "usb", feature = "hid", feature = "serial", feature = "gpib", feature = "ble", all(feature = "network", feature = "modbus")))]
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[cfg(any(feature = "usb", feature = "hid", feature = "serial", feature = "gpib", feature = "ble", all(feature = "network", feature = "modbus")))]
+use tracing::warn;
+
+use crate::error::ApplicationError;
+#[cfg(any(feature = "usb", feature = "hid", feature = "serial", feature = "gpib", feature = "ble", all(feature = "network", feature = "modbus")))]
+use crate::{logging::hex_dump, output::time::utc};
+
+/**
+ * The capture file of --capture, opened once at startup.
+ */
+static CAPTURE: OnceLock<Mutex<LineWriter<File>>> = OnceLock::new();
+
+/**
+ * Direction of captured bytes.
+ */
+#[cfg(any(feature = "usb", feature = "hid", feature = "serial", feature = "gpib", feature = "ble", all(feature = "network", feature = "modbus")))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Direction {
+    /// Written to the device.
+    #[cfg(any(feature = "usb", feature = "hid", feature = "serial", feature = "gpib", all(feature = "network", feature = "modbus")))]
+    Written,
+    /// Read from the device.
+    Read,
+}
+
+#[cfg(any(feature = "usb", feature = "hid", feature = "serial", feature = "gpib", feature = "ble", all(feature = "network", feature = "modbus")))]
+impl Direction {
+    /**
+     * Gets the marker of the direction in the capture file.
+     *
+     * # Returns
+     * `>` for bytes written to the device, `<` for bytes read from it.
+     */
+    fn marker(&self) -> &'static str {
+        match self {
+            #[cfg(any(feature = "usb", feature = "hid", feature = "serial", feature = "gpib", all(feature = "network", feature = "modbus")))]
+            Direction::Written => ">",
+            Direction::Read => "<",
+        }
+    }
+}
+
+/**
+ * Opens the capture file every byte written to and read from the devices is recorded in, one
+ * line per transfer with the UTC time, the direction, the transport and the bytes as hex, e.g.
+ * `2024-02-29T12:34:56.123456Z > usb 2a 49 44 4e 3f 0a`.
+ *
+ * # Arguments
+ * `path` - The capture file.
+ *
+ * # Returns
+ * A Result indicating success or an ApplicationError if the file cannot be created.
+ */
+pub fn open(path: &str) -> Result<(), ApplicationError> {
+    let mut file = File::create(path)
+        .map(LineWriter::new)
+        .map_err(|e| ApplicationError::Io(format!("Could not create {}", path), e))?;
+    writeln!(file, "# hardware-measurement {} capture, > written to and < read from the device", env!("CARGO_PKG_VERSION"))
+        .map_err(|e| ApplicationError::Io(format!("Could not write {}", path), e))?;
+    if CAPTURE.set(Mutex::new(file)).is_err() {
+        return Err(ApplicationError::General("The capture is already open".to_string()));
+    }
+    Ok(())
+}
+
+/**
+ * Records bytes written to a device, if capturing.
+ *
+ * # Arguments
+ * `transport` - The transport the bytes are written to, e.g. `usb` or the serial port.
+ * `bytes` - The bytes.
+ */
+#[cfg(any(feature = "usb", feature = "hid", feature = "serial", feature = "gpib", all(feature = "network", feature = "modbus")))]
+pub fn written(transport: &str, bytes: &[u8]) {
+    record(Direction::Written, transport, bytes);
+}
+
+/**
+ * Records bytes read from a device, if capturing.
+ *
+ * # Arguments
+ * `transport` - The transport the bytes are read from, e.g. `usb` or the serial port.
+ * `bytes` - The bytes.
+ */
+#[cfg(any(feature = "usb", feature = "hid", feature = "serial", feature = "gpib", feature = "ble", all(feature = "network", feature = "modbus")))]
+pub fn read(transport: &str, bytes: &[u8]) {
+    record(Direction::Read, transport, bytes);
+}
+
+/**
+ * Appends a transfer to the capture file. A failing capture is logged and does not stop the
+ * measurement.
+ *
+ * # Arguments
+ * `direction` - Whether the bytes are written or read.
+ * `transport` - The transport.
+ * `bytes` - The bytes.
+ */
+#[cfg(any(feature = "usb", feature = "hid", feature = "serial", feature = "gpib", feature = "ble", all(feature = "network", feature = "modbus")))]
+fn record(direction: Direction, transport: &str, bytes: &[u8]) {
+    let (Some(capture), false) = (CAPTURE.get(), bytes.is_empty()) else {
+        return;
+    };
+    let since_epoch = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+    let line = format_line(since_epoch.as_secs(), since_epoch.subsec_micros(), direction, transport, bytes);
+    let mut file = capture.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    if let Err(e) = writeln!(file, "{}", line) {
+        warn!("Could not write the capture: {}", e);
+    }
+}
+
+/**
+ * Formats a captured transfer.
+ *
+ * # Arguments
+ * `seconds` - Seconds since the Unix epoch.
+ * `micros` - Microseconds into the second.
+ * `direction` - Whether the bytes are written or read.
+ * `transport` - The transport.
+ * `bytes` - The bytes.
+ *
+ * # Returns
+ * The line, without newline.
+ */
+#[cfg(any(feature = "usb", feature = "hid", feature = "serial", feature = "gpib", feature = "ble", all(feature = "network", feature = "modbus")))]
+fn format_line(seconds: u64, micros: u32, direction: Direction, transport: &str, bytes: &[u8]) -> String {
+    let time = utc(seconds);
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:06}Z {} {} {}",
+        time.year,
+        time.month,
+        time.day,
+        time.hour,
+        time.minute,
+        time.second,
+        micros,
+        direction.marker(),
+        transport.replace(' ', "_"),
+        hex_dump(bytes)
+    )
+}
+
+#[cfg(all(test, any(feature = "usb", feature = "hid", feature = "serial", feature = "gpib", all(feature = "network", feature = "modbus"))))]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_format_line() {
+        assert_eq!(
+            format_line(1709210096, 123, Direction::Written, "usb", b"*IDN?\n"),
+            "2024-02-29T12:34:56.000123Z > usb 2a 49 44 4e 3f 0a"
+        );
+        assert_eq!(
+            format_line(0, 0, Direction::Read, "/dev/tty USB0", &[0x41]),
+            "1970-01-01T00:00:00.000000Z < /dev/tty_USB0 41"
+        );
+    }
+}
@@ -0,0 +1,740 @@
+use std::{collections::HashMap, path::PathBuf};
+
+use serde::Deserialize;
+
+use crate::{
+    arguments::{Args, CheckErrors, ColorChoice, DecimalSeparator, Device, Format, Language, Notation, Reader, RemoteMode, Terminator},
+    error::ApplicationError,
+    instruments::reading::{Calibration, ModbusRegister},
+    output::shape::SinkShapes,
+};
+
+/**
+ * Configuration file contents.
+ */
+#[derive(Debug, Default, Deserialize)]
+pub struct Config {
+    /**
+     * Named device profiles.
+     */
+    #[serde(default)]
+    pub profiles: HashMap<String, Profile>,
+    /**
+     * Command macros. Each name expands to a sequence of commands.
+     */
+    #[serde(default)]
+    pub macros: HashMap<String, Vec<String>>,
+    /**
+     * Device groups. Each name lists the profiles of the group.
+     */
+    #[serde(default)]
+    pub groups: HashMap<String, Vec<String>>,
+    /**
+     * Field selection and renaming of each sink.
+     */
+    #[serde(default)]
+    pub sinks: SinkShapes,
+}
+
+/**
+ * A named device profile. Every value is a default that command line arguments override.
+ */
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Profile {
+    pub device: Option<Device>,
+    pub hid: Option<String>,
+    pub usb: Option<String>,
+    /**
+     * File remembering where USB devices were found.
+     */
+    pub device_cache: Option<String>,
+    pub serial: Option<String>,
+    /**
+     * Seconds to wait for the device to be plugged in.
+     */
+    pub wait_for_device: Option<u64>,
+    pub ble: Option<String>,
+    pub gpib: Option<String>,
+    pub tcp: Option<String>,
+    pub modbus_address: Option<u8>,
+    pub channel: Option<u8>,
+    pub interface_number: Option<u8>,
+    pub bulk_in_address: Option<u8>,
+    pub bulk_out_address: Option<u8>,
+    pub interrupt_in_address: Option<u8>,
+    #[serde(default)]
+    pub hid_feature_reports: Vec<String>,
+    pub reader: Option<Reader>,
+    #[serde(default)]
+    pub text_fields: Vec<String>,
+    pub format: Option<Format>,
+    pub language: Option<Language>,
+    pub notation: Option<Notation>,
+    pub color: Option<ColorChoice>,
+    pub decimal_separator: Option<DecimalSeparator>,
+    pub terminator: Option<Terminator>,
+    pub response_terminator: Option<Terminator>,
+    /**
+     * Whether text commands are answered, overriding the detection of queries by `?`.
+     */
+    pub expect_response: Option<bool>,
+    pub read_timeout_ms: Option<u64>,
+    pub max_response_size: Option<usize>,
+    pub remote: Option<RemoteMode>,
+    pub local_command: Option<String>,
+    pub check_errors: Option<CheckErrors>,
+    /**
+     * Whether SCPI configuration commands are followed by `*OPC?`.
+     */
+    #[serde(default)]
+    pub opc_sync: bool,
+    /**
+     * Milliseconds waited between consecutive SCPI commands.
+     */
+    pub delay_ms: Option<u64>,
+    /**
+     * Whether only commands reading from the device are sent.
+     */
+    #[serde(default)]
+    pub read_only: bool,
+    /**
+     * Whether every record carries the provenance of its device.
+     */
+    #[serde(default)]
+    pub metadata: bool,
+    /**
+     * Number of times the commands are sent back to back per measurement.
+     */
+    pub samples: Option<usize>,
+    /**
+     * Highest voltage any command may set on the device.
+     */
+    pub max_voltage: Option<f64>,
+    /**
+     * Highest current any command may set on the device.
+     */
+    pub max_current: Option<f64>,
+    /**
+     * Whether commands flagged dangerous must be confirmed.
+     */
+    #[serde(default)]
+    pub confirm_dangerous: bool,
+    /**
+     * Whether settings are read back after they are sent.
+     */
+    #[serde(default)]
+    pub verify: bool,
+    /**
+     * Whether commands are checked by the driver before they are sent.
+     */
+    #[serde(default)]
+    pub check_commands: bool,
+    /**
+     * Commands flagged dangerous.
+     */
+    #[serde(default)]
+    pub dangerous: Vec<String>,
+    /**
+     * Enabling the output is flagged dangerous above this voltage.
+     */
+    pub dangerous_output_voltage: Option<f64>,
+    /**
+     * Initialization commands run when the device is opened.
+     */
+    #[serde(default)]
+    pub init: Vec<String>,
+    /**
+     * Corrections of the decoded values, per mode.
+     */
+    #[serde(default)]
+    pub calibration: Vec<Calibration>,
+    /**
+     * Register map of a Modbus TCP device.
+     */
+    #[serde(default)]
+    pub registers: Vec<ModbusRegister>,
+    /**
+     * Commands run on the device when several profiles are given.
+     */
+    #[serde(default)]
+    pub commands: Vec<String>,
+}
+
+impl Config {
+    /**
+     * Returns the default configuration file path, `$XDG_CONFIG_HOME/hardware-measurement/config.toml`
+     * or `~/.config/hardware-measurement/config.toml`.
+     *
+     * # Returns
+     * The path, or None if neither XDG_CONFIG_HOME nor HOME is set.
+     */
+    pub fn default_path() -> Option<PathBuf> {
+        std::env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+            .map(|config_dir| config_dir.join("hardware-measurement").join("config.toml"))
+    }
+
+    /**
+     * Parses a configuration file.
+     *
+     * # Arguments
+     * `content` - The TOML content.
+     *
+     * # Returns
+     * A Result containing the Config or an ApplicationError.
+     */
+    pub fn parse(content: &str) -> Result<Self, ApplicationError> {
+        Ok(toml::from_str(content)?)
+    }
+
+    /**
+     * Loads the configuration file given on the command line, or the default file if it exists.
+     *
+     * # Arguments
+     * `args` - The command line arguments.
+     *
+     * # Returns
+     * A Result containing the Config (empty if no file is used) or an ApplicationError.
+     */
+    pub fn load(args: &Args) -> Result<Self, ApplicationError> {
+        let path = match &args.common.config {
+            Some(path) => PathBuf::from(path),
+            None => match Self::default_path() {
+                Some(path) if path.exists() => path,
+                _ => return Ok(Self::default()),
+            },
+        };
+        let content = std::fs::read_to_string(&path).map_err(|e| {
+            ApplicationError::Config(format!("Could not read {}: {}", path.display(), e))
+        })?;
+        Self::parse(&content)
+    }
+
+    /**
+     * Resolves the devices of an invocation. Without `--profile` or with a single profile the
+     * command line describes one device. With several profiles every profile is a device labelled
+     * with the profile name, running the `commands` of the profile or else the `--command` list.
+     * The `--command` list, with the commands of `--commands-file`, is broadcast to every member of a `--group`.
+     * Macros are expanded for every device.
+     *
+     * # Arguments
+     * `args` - The command line arguments.
+     *
+     * # Returns
+     * A Result containing the device labels (None for a single device) with their arguments,
+     * at least one, or an ApplicationError if the groups have no profiles.
+     */
+    pub fn devices(&self, args: &Args) -> Result<Vec<(Option<String>, Args)>, ApplicationError> {
+        let args = &read_commands_file(args)?;
+        // Profile names with whether they take the command line commands
+        let mut members: Vec<(String, bool)> = Vec::new();
+        for name in &args.common.profiles {
+            members.push((name.clone(), false));
+        }
+        for group in &args.common.groups {
+            let profiles = self
+                .groups
+                .get(group)
+                .ok_or_else(|| ApplicationError::Config(format!("Unknown group {}", group)))?;
+            members.extend(profiles.iter().map(|name| (name.clone(), true)));
+        }
+        let mut seen = Vec::new();
+        members.retain(|(name, _)| {
+            let first = !seen.contains(name);
+            seen.push(name.clone());
+            first
+        });
+        if members.is_empty() && !args.common.groups.is_empty() {
+            return Err(ApplicationError::Config(format!("Group {} has no profiles", args.common.groups.join(", "))));
+        }
+        if members.len() <= 1 && args.common.groups.is_empty() {
+            let mut device_args = args.clone();
+            if let Some(name) = args.common.profiles.first() {
+                self.apply_profile(name, &mut device_args)?;
+            }
+            self.expand_macros(&mut device_args)?;
+            return Ok(vec![(None, device_args)]);
+        }
+        members
+            .into_iter()
+            .map(|(name, broadcast)| {
+                let mut device_args = args.clone();
+                device_args.common.profiles = vec![name.clone()];
+                device_args.common.groups = Vec::new();
+                self.apply_profile(&name, &mut device_args)?;
+                let broadcast = broadcast && !args.control.commands.is_empty();
+                if let Some(commands) = self.profiles.get(&name).map(|profile| &profile.commands) {
+                    if !commands.is_empty() && !broadcast {
+                        device_args.control.commands = commands.clone();
+                    }
+                }
+                self.expand_macros(&mut device_args)?;
+                Ok((Some(name), device_args))
+            })
+            .collect()
+    }
+
+    /**
+     * Fills unset arguments from a profile.
+     *
+     * # Arguments
+     * `name` - The profile name.
+     * `args` - The command line arguments to complete.
+     */
+    pub fn apply_profile(&self, name: &str, args: &mut Args) -> Result<(), ApplicationError> {
+        let profile = self
+            .profiles
+            .get(name)
+            .ok_or_else(|| ApplicationError::Config(format!("Unknown profile {}", name)))?
+            .clone();
+        args.connection.device = args.connection.device.take().or(profile.device);
+        args.connection.hid = args.connection.hid.take().or(profile.hid);
+        args.connection.usb = args.connection.usb.take().or(profile.usb);
+        args.connection.device_cache = args.connection.device_cache.take().or(profile.device_cache);
+        args.connection.serial = args.connection.serial.take().or(profile.serial);
+        args.connection.wait_for_device = args.connection.wait_for_device.or(profile.wait_for_device);
+        args.connection.ble = args.connection.ble.take().or(profile.ble);
+        args.connection.gpib = args.connection.gpib.take().or(profile.gpib);
+        args.connection.tcp = args.connection.tcp.take().or(profile.tcp);
+        args.connection.modbus_address = args.connection.modbus_address.or(profile.modbus_address);
+        args.connection.channel = args.connection.channel.or(profile.channel);
+        args.connection.interface_number = args.connection.interface_number.or(profile.interface_number);
+        args.connection.bulk_in_address = args.connection.bulk_in_address.or(profile.bulk_in_address);
+        args.connection.bulk_out_address = args.connection.bulk_out_address.or(profile.bulk_out_address);
+        args.connection.interrupt_in_address = args.connection.interrupt_in_address.or(profile.interrupt_in_address);
+        if args.connection.hid_feature_reports.is_empty() {
+            args.connection.hid_feature_reports = profile.hid_feature_reports;
+        }
+        if args.connection.init_commands.is_empty() {
+            args.connection.init_commands = profile.init;
+        }
+        if args.calibrations.is_empty() {
+            args.calibrations = profile.calibration;
+        }
+        if args.registers.is_empty() {
+            args.registers = profile.registers;
+        }
+        args.readings.reader = args.readings.reader.take().or(profile.reader);
+        if args.readings.text_fields.is_empty() {
+            args.readings.text_fields = profile.text_fields;
+        }
+        args.printing.format = args.printing.format.take().or(profile.format);
+        args.printing.language = args.printing.language.or(profile.language);
+        args.printing.notation = args.printing.notation.or(profile.notation);
+        args.printing.color = args.printing.color.or(profile.color);
+        args.printing.decimal_separator = args.printing.decimal_separator.or(profile.decimal_separator);
+        args.connection.terminator = args.connection.terminator.take().or(profile.terminator);
+        args.connection.response_terminator =
+            args.connection.response_terminator.take().or(profile.response_terminator);
+        if !args.connection.expect_response && !args.connection.no_response {
+            args.connection.expect_response = profile.expect_response == Some(true);
+            args.connection.no_response = profile.expect_response == Some(false);
+        }
+        args.connection.read_timeout_ms = args.connection.read_timeout_ms.or(profile.read_timeout_ms);
+        args.connection.max_response_size = args.connection.max_response_size.or(profile.max_response_size);
+        args.connection.remote = args.connection.remote.take().or(profile.remote);
+        args.connection.local_command = args.connection.local_command.take().or(profile.local_command);
+        args.control.check_errors = args.control.check_errors.or(profile.check_errors);
+        args.control.opc_sync = args.control.opc_sync || profile.opc_sync;
+        args.control.delay_ms = args.control.delay_ms.or(profile.delay_ms);
+        args.control.read_only = args.control.read_only || profile.read_only;
+        args.measurement.metadata = args.measurement.metadata || profile.metadata;
+        args.measurement.samples = args.measurement.samples.or(profile.samples);
+        args.control.max_voltage = lowest(args.control.max_voltage, profile.max_voltage);
+        args.control.max_current = lowest(args.control.max_current, profile.max_current);
+        args.control.confirm_dangerous = args.control.confirm_dangerous || profile.confirm_dangerous;
+        args.control.verify = args.control.verify || profile.verify;
+        args.control.check_commands = args.control.check_commands || profile.check_commands;
+        args.control.dangerous_commands.extend(profile.dangerous);
+        args.control.dangerous_output_voltage =
+            lowest(args.control.dangerous_output_voltage, profile.dangerous_output_voltage);
+        Ok(())
+    }
+
+    /**
+     * Expands command macros in the commands and initialization commands, and keeps the macros for the script.
+     * Macros given with `--macro name="cmd1;cmd2"` take precedence over macros in the configuration file. Macros may
+     * use other macros.
+     *
+     * # Arguments
+     * `args` - The command line arguments whose commands are expanded.
+     *
+     * # Returns
+     * A Result indicating success or an ApplicationError if a macro is invalid or recursive.
+     */
+    pub fn expand_macros(&self, args: &mut Args) -> Result<(), ApplicationError> {
+        let mut macros = self.macros.clone();
+        for definition in &args.common.macros {
+            let (name, commands) = definition.split_once('=').ok_or_else(|| {
+                ApplicationError::Config(format!("Expected --macro name=\"cmd1;cmd2\", got {}", definition))
+            })?;
+            macros.insert(
+                name.trim().to_string(),
+                commands
+                    .split(';')
+                    .map(|command| command.trim().to_string())
+                    .filter(|command| !command.is_empty())
+                    .collect(),
+            );
+        }
+        for commands in [&mut args.control.commands, &mut args.connection.init_commands] {
+            let mut expanded = Vec::new();
+            for command in commands.iter() {
+                expand(&macros, command, &mut Vec::new(), &mut expanded)?;
+            }
+            *commands = expanded;
+        }
+        args.control.script_macros = macros;
+        Ok(())
+    }
+}
+
+/**
+ * Appends the commands of the `--commands-file`, if given, to the `--command` list.
+ *
+ * # Arguments
+ * `args` - The command line arguments.
+ *
+ * # Returns
+ * A Result containing the arguments with the commands or an ApplicationError if the file could not be read.
+ */
+fn read_commands_file(args: &Args) -> Result<Args, ApplicationError> {
+    let mut args = args.clone();
+    if let Some(path) = &args.control.commands_file {
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| ApplicationError::Config(format!("Could not read {}: {}", path, e)))?;
+        args.control.commands.extend(parse_commands_file(&content));
+    }
+    Ok(args)
+}
+
+/**
+ * Parses a commands file: one command per line, without empty lines and `#` comment lines.
+ *
+ * # Arguments
+ * `content` - The file content.
+ *
+ * # Returns
+ * The commands.
+ */
+pub fn parse_commands_file(content: &str) -> Vec<String> {
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(str::to_string)
+        .collect()
+}
+
+/**
+ * Combines two optional limits; the stricter one applies.
+ *
+ * # Arguments
+ * `a` - The first limit.
+ * `b` - The second limit.
+ *
+ * # Returns
+ * The lower of the limits, or the one that is set.
+ */
+fn lowest(a: Option<f64>, b: Option<f64>) -> Option<f64> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(a.min(b)),
+        (a, b) => a.or(b),
+    }
+}
+
+/**
+ * Expands a single command recursively.
+ *
+ * # Arguments
+ * `macros` - The macro definitions.
+ * `command` - The command or macro name.
+ * `stack` - The macros currently being expanded, used to detect recursion.
+ * `expanded` - The resulting commands.
+ *
+ * # Returns
+ * A Result indicating success or an ApplicationError if a macro is recursive.
+ */
+pub fn expand(
+    macros: &HashMap<String, Vec<String>>,
+    command: &str,
+    stack: &mut Vec<String>,
+    expanded: &mut Vec<String>,
+) -> Result<(), ApplicationError> {
+    let Some(commands) = macros.get(command) else {
+        expanded.push(command.to_string());
+        return Ok(());
+    };
+    if stack.iter().any(|name| name == command) {
+        return Err(ApplicationError::Config(format!(
+            "Recursive macro {} -> {}",
+            stack.join(" -> "),
+            command
+        )));
+    }
+    stack.push(command.to_string());
+    for command in commands {
+        expand(macros, command, stack, expanded)?;
+    }
+    stack.pop();
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use clap::{Parser, ValueEnum};
+
+    const CONFIG: &str = r#"
+        [profiles.bench-dmm]
+        device = "unit161d"
+        hid = "/dev/hidraw6"
+        format = "csv"
+
+        [profiles.bench-dmm-2]
+        device = "unit161d"
+        hid = "/dev/hidraw7"
+        commands = ["Hold"]
+
+        [profiles.generator]
+        device = "peaktech4055mv-usb"
+        usb = "5345:1234"
+        bulk_in_address = 0x82
+        terminator = "crlf"
+        remote = "remote-local"
+        init = ["*RST", "*CLS", "output-on"]
+
+        [profiles.bench-psu]
+        device = "korad-psu"
+        serial = "/dev/ttyACM0"
+        max_voltage = 6.0
+        max_current = 1.0
+        dangerous = ["OCP0", "OVP0"]
+        dangerous_output_voltage = 5.0
+
+        [profiles.calibrated-dmm]
+        device = "unit161d"
+        calibration = [{ mode = "DCV", gain = 0.997 }, { offset = -0.01 }]
+
+        [groups]
+        all-dmms = ["bench-dmm", "bench-dmm-2"]
+
+        [sinks.websocket]
+        fields = ["display_value", "display_unit"]
+
+        [sinks.output.rename]
+        display_value = "reading"
+
+        [macros]
+        setup-sine = ["APPL:SIN 1000,1", "output-on"]
+        output-on = ["OUTP ON"]
+        loop-a = ["loop-b"]
+        loop-b = ["loop-a"]
+    "#;
+
+    #[test]
+    fn test_apply_profile() {
+        let config = Config::parse(CONFIG).unwrap();
+        let mut args = Args::parse_from(["test_program", "--profile", "bench-dmm", "--command", "Measure", "--format", "json"]);
+        config.apply_profile("bench-dmm", &mut args).unwrap();
+        assert_eq!(args.connection.device, Some(Device::Unit161d));
+        assert_eq!(args.connection.hid, Some("/dev/hidraw6".to_string()));
+        assert_eq!(args.printing.format, Some(Format::Json));
+
+        let mut args = Args::parse_from(["test_program", "--profile", "generator"]);
+        config.apply_profile("generator", &mut args).unwrap();
+        assert_eq!(args.connection.device, Some(Device::Peaktech4055mvUsb));
+        assert_eq!(args.connection.bulk_in_address, Some(0x82));
+        assert_eq!(args.connection.terminator, Some(Terminator::Crlf));
+        assert_eq!(args.connection.remote, Some(RemoteMode::RemoteLocal));
+        assert_eq!(args.connection.init_commands, vec!["*RST", "*CLS", "output-on"]);
+        config.expand_macros(&mut args).unwrap();
+        assert_eq!(args.connection.init_commands, vec!["*RST", "*CLS", "OUTP ON"]);
+
+        let args = Args::parse_from(["test_program", "--profile", "generator", "--init", "SYST:REM"]);
+        let devices = config.devices(&args).unwrap();
+        assert_eq!(devices.len(), 1);
+        assert_eq!(devices[0].0, None);
+        assert_eq!(devices[0].1.connection.init_commands, vec!["SYST:REM"]);
+    }
+
+    #[test]
+    fn test_multiple_devices() {
+        let config = Config::parse(CONFIG).unwrap();
+        let args = Args::parse_from([
+            "test_program",
+            "--profile",
+            "bench-dmm",
+            "--profile",
+            "bench-dmm-2",
+            "--command",
+            "Measure",
+        ]);
+        let devices = config.devices(&args).unwrap();
+        assert_eq!(devices.len(), 2);
+        assert_eq!(devices[0].0, Some("bench-dmm".to_string()));
+        assert_eq!(devices[0].1.control.commands, vec!["Measure"]);
+        assert_eq!(devices[1].0, Some("bench-dmm-2".to_string()));
+        assert_eq!(devices[1].1.control.commands, vec!["Hold"]);
+    }
+
+    #[test]
+    fn test_limits_take_the_stricter_value() {
+        let config = Config::parse(CONFIG).unwrap();
+        let mut args = Args::parse_from(["test_program", "--profile", "bench-psu", "--max-voltage", "5", "--max-current", "3"]);
+        config.apply_profile("bench-psu", &mut args).unwrap();
+        assert_eq!(args.connection.serial, Some("/dev/ttyACM0".to_string()));
+        assert_eq!(args.control.max_voltage, Some(5.0));
+        assert_eq!(args.control.max_current, Some(1.0));
+        assert!(!args.control.confirm_dangerous);
+        assert_eq!(args.control.dangerous_commands, vec!["OCP0", "OVP0"]);
+        assert_eq!(args.control.dangerous_output_voltage, Some(5.0));
+    }
+
+    #[test]
+    fn test_calibration() {
+        let config = Config::parse(CONFIG).unwrap();
+        let mut args = Args::parse_from(["test_program", "--profile", "calibrated-dmm"]);
+        config.apply_profile("calibrated-dmm", &mut args).unwrap();
+        assert_eq!(
+            args.calibrations,
+            [
+                Calibration { mode: Some("DCV".into()), gain: 0.997, offset: 0.0 },
+                Calibration { mode: None, gain: 1.0, offset: -0.01 },
+            ]
+        );
+        assert!(Config::parse("[profiles.x]\ncalibration = [{ scale = 2.0 }]").is_err());
+    }
+
+    #[test]
+    fn test_registers() {
+        let config = Config::parse(
+            "[profiles.meter]\ndevice = \"modbus-tcp\"\ntcp = \"192.168.1.50\"\nregisters = [{ name = \"voltage\", address = 0, table = \"input\", type = \"f32\", unit = \"V\" }]\n",
+        )
+        .unwrap();
+        let mut args = Args::parse_from(["test_program", "--profile", "meter"]);
+        config.apply_profile("meter", &mut args).unwrap();
+        assert_eq!(args.connection.device, Some(Device::ModbusTcp));
+        assert_eq!(args.connection.tcp.as_deref(), Some("192.168.1.50"));
+        assert_eq!(args.registers.len(), 1);
+        #[cfg(all(feature = "network", feature = "modbus"))]
+        assert_eq!(args.registers[0].words(), 2);
+        assert!(Config::parse("[profiles.x]\nregisters = [{ name = \"x\", address = 0, kind = \"f32\" }]").is_err());
+    }
+
+    #[test]
+    fn test_text_fields() {
+        let config = Config::parse("[profiles.sensor]\ndevice = \"generic-serial-text\"\ntext_fields = [\"temperature\", \"humidity\"]\n").unwrap();
+        let mut args = Args::parse_from(["test_program", "--profile", "sensor"]);
+        config.apply_profile("sensor", &mut args).unwrap();
+        assert_eq!(args.connection.device, Some(Device::GenericSerialText));
+        assert_eq!(args.readings.text_fields, ["temperature", "humidity"]);
+    }
+
+    #[test]
+    fn test_expect_response() {
+        let config = Config::parse("[profiles.meter]\ndevice = \"generic-scpi-usb\"\nexpect_response = true\n").unwrap();
+        let mut args = Args::parse_from(["test_program", "--profile", "meter"]);
+        config.apply_profile("meter", &mut args).unwrap();
+        assert!(args.connection.expect_response);
+        assert!(!args.connection.no_response);
+        let mut args = Args::parse_from(["test_program", "--profile", "meter", "--no-response"]);
+        config.apply_profile("meter", &mut args).unwrap();
+        assert!(!args.connection.expect_response);
+        assert!(args.connection.no_response);
+    }
+
+    #[test]
+    fn test_unknown_profile_and_field() {
+        let config = Config::parse(CONFIG).unwrap();
+        let args = Args::parse_from(["test_program", "--profile", "missing"]);
+        assert!(config.devices(&args).is_err());
+        assert!(Config::parse("[profiles.x]\nvendor = 1").is_err());
+        assert!(Config::parse("[sinks.mqtt]\nfields = []").is_err());
+    }
+
+    #[test]
+    fn test_sink_shapes() {
+        let config = Config::parse(CONFIG).unwrap();
+        assert_eq!(config.sinks.websocket.unwrap().fields, vec!["display_value", "display_unit"]);
+        assert_eq!(config.sinks.output.unwrap().rename["display_value"], "reading");
+        assert!(config.sinks.syslog.is_none());
+    }
+
+    #[test]
+    fn test_group_broadcast() {
+        let config = Config::parse(CONFIG).unwrap();
+        let args = Args::parse_from(["test_program", "--group", "all-dmms", "--profile", "bench-dmm", "--command", "Hold"]);
+        let devices = config.devices(&args).unwrap();
+        assert_eq!(devices.len(), 2);
+        assert_eq!(devices[1].0, Some("bench-dmm-2".to_string()));
+        assert_eq!(devices[1].1.control.commands, vec!["Hold"]);
+        assert_eq!(devices[1].1.connection.hid, Some("/dev/hidraw7".to_string()));
+
+        let args = Args::parse_from(["test_program", "--group", "missing"]);
+        assert!(config.devices(&args).is_err());
+
+        let config = Config::parse("[groups]\nempty = []").unwrap();
+        let args = Args::parse_from(["test_program", "--group", "empty"]);
+        assert!(matches!(config.devices(&args), Err(ApplicationError::Config(message)) if message.contains("no profiles")));
+    }
+
+    #[test]
+    fn test_expand_macros() {
+        let config = Config::parse(CONFIG).unwrap();
+        let mut args = Args::parse_from([
+            "test_program",
+            "--device",
+            "peaktech4055mv-usb",
+            "--macro",
+            "measure=MEAS:VOLT?; MEAS:CURR?",
+            "--command",
+            "setup-sine",
+            "measure",
+            "*IDN?",
+        ]);
+        config.expand_macros(&mut args).unwrap();
+        assert_eq!(
+            args.control.commands,
+            vec!["APPL:SIN 1000,1", "OUTP ON", "MEAS:VOLT?", "MEAS:CURR?", "*IDN?"]
+        );
+
+        let mut args = Args::parse_from(["test_program", "--device", "unit161d", "--command", "loop-a"]);
+        assert!(config.expand_macros(&mut args).is_err());
+        let mut args = Args::parse_from(["test_program", "--device", "unit161d", "--macro", "broken"]);
+        assert!(config.expand_macros(&mut args).is_err());
+    }
+
+    #[test]
+    fn test_commands_file() {
+        assert_eq!(
+            parse_commands_file("# Setup\n*RST\n\n  APPL:SIN 1000,1  \n# Output\nOUTP ON\n"),
+            vec!["*RST", "APPL:SIN 1000,1", "OUTP ON"]
+        );
+        let path = std::env::temp_dir().join(format!("commands-{}.txt", std::process::id()));
+        std::fs::write(&path, "setup-sine\nMEAS:VOLT?\n").unwrap();
+        let config = Config::parse(CONFIG).unwrap();
+        let args = Args::parse_from([
+            "test_program",
+            "--device=peaktech4055mv-usb",
+            "--command=*RST",
+            &format!("--commands-file={}", path.display()),
+        ]);
+        let devices = config.devices(&args).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(devices[0].1.control.commands, vec!["*RST", "APPL:SIN 1000,1", "OUTP ON", "MEAS:VOLT?"]);
+        let args = Args::parse_from(["test_program", "--device=unit161d", "--commands-file=/nonexistent/commands.txt"]);
+        assert!(config.devices(&args).is_err());
+    }
+
+    #[test]
+    fn test_device_names_match_command_line() {
+        for device in Device::value_variants() {
+            let name = device.to_possible_value().unwrap().get_name().to_string();
+            let profile = Config::parse(&format!("[profiles.p]\ndevice = \"{}\"", name)).unwrap();
+            assert_eq!(profile.profiles["p"].device.as_ref(), Some(device));
+        }
+    }
+}
@@ -0,0 +1,81 @@
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+/**
+ * A cached acquisition of a device.
+ */
+#[derive(Debug, Clone)]
+pub struct CachedReadings {
+    /**
+     * Time of the acquisition.
+     */
+    pub taken: Instant,
+    /**
+     * The readings formatted as JSON objects.
+     */
+    pub readings: Vec<String>,
+}
+
+/**
+ * Cache of the last acquisition per device.
+ */
+#[derive(Debug, Default)]
+pub struct ReadingCache {
+    entries: HashMap<String, CachedReadings>,
+}
+
+impl ReadingCache {
+    /**
+     * Returns the last acquisition of a device if it is not older than the maximum age.
+     *
+     * # Arguments
+     * `device` - The device label.
+     * `max_age` - The accepted age. None never accepts a cached acquisition.
+     * `now` - The current time.
+     *
+     * # Returns
+     * The cached acquisition, or None if there is none or it is stale.
+     */
+    pub fn get(&self, device: &str, max_age: Option<Duration>, now: Instant) -> Option<&CachedReadings> {
+        let max_age = max_age?;
+        self.entries
+            .get(device)
+            .filter(|entry| now.saturating_duration_since(entry.taken) <= max_age)
+    }
+
+    /**
+     * Stores the latest acquisition of a device.
+     *
+     * # Arguments
+     * `device` - The device label.
+     * `entry` - The acquisition.
+     */
+    pub fn insert(&mut self, device: &str, entry: CachedReadings) {
+        self.entries.insert(device.to_string(), entry);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_cache_max_age() {
+        let mut cache = ReadingCache::default();
+        let taken = Instant::now();
+        cache.insert(
+            "dmm",
+            CachedReadings {
+                taken,
+                readings: vec!["{}".into()],
+            },
+        );
+        let later = taken + Duration::from_secs(3);
+        assert!(cache.get("dmm", Some(Duration::from_secs(5)), later).is_some());
+        assert!(cache.get("dmm", Some(Duration::from_secs(2)), later).is_none());
+        assert!(cache.get("dmm", None, taken).is_none());
+        assert!(cache.get("psu", Some(Duration::from_secs(5)), later).is_none());
+    }
+}
@@ -0,0 +1,311 @@
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    rc::Rc,
+    time::{Duration, Instant},
+};
+
+use serde_json::json;
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+    sync::Mutex,
+    task::{spawn_local, LocalSet},
+};
+
+use crate::{
+    arguments::Args,
+    daemon::cache::{CachedReadings, ReadingCache},
+    error::ApplicationError,
+    output::json,
+    shutdown,
+    workflow::{
+        acquire::{acquire, OpenDevices},
+        script::parse_duration,
+    },
+};
+
+/**
+ * Label of the device when the daemon serves a single device without profile.
+ */
+const DEFAULT_DEVICE: &str = "default";
+
+/**
+ * Largest accepted request head.
+ */
+const MAX_REQUEST_SIZE: usize = 8192;
+
+/**
+ * A parsed HTTP request line.
+ */
+#[derive(Debug, PartialEq, Eq)]
+pub struct Request {
+    pub method: String,
+    pub path: String,
+    pub query: HashMap<String, String>,
+}
+
+impl Request {
+    /**
+     * Parses the request line of an HTTP request, e.g. `GET /devices/dmm/reading?max_age=2s HTTP/1.1`.
+     *
+     * # Arguments
+     * `head` - The request head.
+     *
+     * # Returns
+     * Some(Request), or None if the request line is malformed.
+     */
+    pub fn parse(head: &str) -> Option<Self> {
+        let mut parts = head.lines().next()?.split_whitespace();
+        let method = parts.next()?.to_string();
+        let target = parts.next()?;
+        let (path, query) = target.split_once('?').unwrap_or((target, ""));
+        let query = query
+            .split('&')
+            .filter_map(|pair| pair.split_once('='))
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect();
+        Some(Self {
+            method,
+            path: path.to_string(),
+            query,
+        })
+    }
+}
+
+/**
+ * A device served by the daemon.
+ */
+struct ServedDevice {
+    args: Args,
+    /**
+     * The devices, opened by the first request and kept open. Held while the hardware is accessed
+     * so concurrent requests do not contend for the device.
+     */
+    opened: Mutex<OpenDevices>,
+}
+
+/**
+ * HTTP daemon serving readings of the configured devices. The last acquisition of every device
+ * is cached; clients that tolerate stale values pass `max_age` and get the cached readings
+ * without touching the hardware.
+ *
+ * Routes:
+ * - `GET /devices` lists the device labels.
+ * - `GET /devices/<label>/reading[?max_age=2s]` returns the readings of a device.
+ */
+pub struct Daemon {
+    devices: HashMap<String, ServedDevice>,
+    cache: RefCell<ReadingCache>,
+}
+
+impl Daemon {
+    /**
+     * Creates a daemon for the devices.
+     *
+     * # Arguments
+     * `devices` - The device labels (None for a single device) with their arguments.
+     *
+     * # Returns
+     * A new Daemon instance.
+     */
+    pub fn new(devices: &[(Option<String>, Args)]) -> Self {
+        Self {
+            devices: devices
+                .iter()
+                .map(|(label, args)| {
+                    (
+                        label.clone().unwrap_or_else(|| DEFAULT_DEVICE.to_string()),
+                        ServedDevice {
+                            args: args.clone(),
+                            opened: Mutex::new(OpenDevices::default()),
+                        },
+                    )
+                })
+                .collect(),
+            cache: RefCell::new(ReadingCache::default()),
+        }
+    }
+
+    /**
+     * Listens on the address and serves clients until a shutdown is requested.
+     *
+     * # Arguments
+     * `address` - The address to listen on, e.g. `127.0.0.1:8080`.
+     */
+    pub async fn serve(self, address: &str) -> Result<(), ApplicationError> {
+        let listener = TcpListener::bind(address)
+            .await
+            .map_err(|e| ApplicationError::Io(format!("Could not listen on {}", address), e))?;
+        let daemon = Rc::new(self);
+        // Devices are not Send, so clients are served on the local task set
+        LocalSet::new()
+            .run_until(async move {
+                loop {
+                    let (stream, _) = tokio::select! {
+                        biased;
+                        _ = shutdown::requested() => return Ok(()),
+                        accepted = listener.accept() => {
+                            accepted.map_err(|e| ApplicationError::Io("Could not accept client".into(), e))?
+                        }
+                    };
+                    spawn_local(daemon.clone().handle(stream));
+                }
+            })
+            .await
+    }
+
+    /**
+     * Reads a request from the client and writes the response.
+     *
+     * # Arguments
+     * `stream` - The client connection.
+     */
+    async fn handle(self: Rc<Self>, mut stream: TcpStream) {
+        let mut head = Vec::new();
+        let mut buffer = [0u8; 1024];
+        while !head.windows(4).any(|window| window == b"\r\n\r\n") && head.len() < MAX_REQUEST_SIZE {
+            match stream.read(&mut buffer).await {
+                Ok(0) | Err(_) => break,
+                Ok(length) => head.extend_from_slice(&buffer[..length]),
+            }
+        }
+        let (status, body) = match Request::parse(&String::from_utf8_lossy(&head)) {
+            Some(request) => self.route(&request).await,
+            None => ("400 Bad Request", json!({"error": "Malformed request"})),
+        };
+        let body = body.to_string();
+        let response = format!(
+            "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            status,
+            body.len(),
+            body
+        );
+        let _ = stream.write_all(response.as_bytes()).await;
+    }
+
+    /**
+     * Routes a request.
+     *
+     * # Arguments
+     * `request` - The request.
+     *
+     * # Returns
+     * The HTTP status and the JSON body.
+     */
+    async fn route(&self, request: &Request) -> (&'static str, serde_json::Value) {
+        if request.method != "GET" {
+            return ("405 Method Not Allowed", json!({"error": "Only GET is supported"}));
+        }
+        let segments: Vec<&str> = request.path.trim_matches('/').split('/').collect();
+        match segments[..] {
+            ["devices"] => {
+                let mut labels: Vec<&String> = self.devices.keys().collect();
+                labels.sort();
+                ("200 OK", json!(labels))
+            }
+            ["devices", label, "reading"] => {
+                let max_age = match request.query.get("max_age").map(|value| parse_duration(value)) {
+                    Some(Ok(max_age)) => Some(max_age),
+                    Some(Err(e)) => return ("400 Bad Request", json!({"error": e.to_string()})),
+                    None => None,
+                };
+                match self.reading(label, max_age).await {
+                    Ok(Some(body)) => ("200 OK", body),
+                    Ok(None) => ("404 Not Found", json!({"error": format!("Unknown device {}", label)})),
+                    Err(e) => ("502 Bad Gateway", json!({"error": e.to_string()})),
+                }
+            }
+            _ => ("404 Not Found", json!({"error": "Unknown path"})),
+        }
+    }
+
+    /**
+     * Returns the readings of a device, from the cache if they are fresh enough.
+     * Requests for the same device wait for each other, so a request arriving while the hardware
+     * is read gets the new cached value.
+     *
+     * # Arguments
+     * `label` - The device label.
+     * `max_age` - The accepted age of cached readings.
+     *
+     * # Returns
+     * A Result containing the response body, None for unknown devices, or an ApplicationError.
+     */
+    async fn reading(&self, label: &str, max_age: Option<Duration>) -> Result<Option<serde_json::Value>, ApplicationError> {
+        let Some(device) = self.devices.get(label) else {
+            return Ok(None);
+        };
+        let mut opened = device.opened.lock().await;
+        if let Some(entry) = self.cache.borrow().get(label, max_age, Instant::now()) {
+            return Ok(Some(Self::body(label, entry, true)));
+        }
+        let readings = acquire(&device.args, &mut opened, &mut Vec::new())
+            .await?
+            .iter()
+            .map(|reading| json::format_reading(reading.as_ref(), device.args.printing.notation.unwrap_or_default()))
+            .collect::<Result<Vec<String>, ApplicationError>>()?;
+        let entry = CachedReadings {
+            taken: Instant::now(),
+            readings,
+        };
+        let body = Self::body(label, &entry, false);
+        self.cache.borrow_mut().insert(label, entry);
+        Ok(Some(body))
+    }
+
+    /**
+     * Builds the response body for an acquisition.
+     *
+     * # Arguments
+     * `label` - The device label.
+     * `entry` - The acquisition.
+     * `cached` - Whether the acquisition came from the cache.
+     *
+     * # Returns
+     * The JSON body.
+     */
+    fn body(label: &str, entry: &CachedReadings, cached: bool) -> serde_json::Value {
+        let readings: Vec<serde_json::Value> = entry
+            .readings
+            .iter()
+            .filter_map(|reading| serde_json::from_str(reading).ok())
+            .collect();
+        json!({
+            "device": label,
+            "cached": cached,
+            "age_ms": entry.taken.elapsed().as_millis() as u64,
+            "readings": readings,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    #[cfg(all(feature = "network", feature = "modbus"))]
+    use crate::instruments::communication::LoopbackModbus;
+
+    #[test]
+    fn test_parse_request() {
+        let request = Request::parse("GET /devices/dmm/reading?max_age=2s&x=1 HTTP/1.1\r\nHost: localhost\r\n\r\n").unwrap();
+        assert_eq!(request.method, "GET");
+        assert_eq!(request.path, "/devices/dmm/reading");
+        assert_eq!(request.query.get("max_age"), Some(&"2s".to_string()));
+        assert_eq!(Request::parse("GET /devices HTTP/1.1").unwrap().query.len(), 0);
+        assert_eq!(Request::parse(""), None);
+    }
+
+    #[cfg(all(feature = "network", feature = "modbus"))]
+    #[tokio::test]
+    async fn test_device_kept_open() {
+        let server = LoopbackModbus::start();
+        let daemon = Daemon::new(&[(Some("meter".into()), server.args(&["--command", "Measure"]))]);
+        for _ in 0..3 {
+            let body = daemon.reading("meter", None).await.unwrap().unwrap();
+            assert_eq!(body["cached"], false);
+        }
+        assert_eq!(server.connections(), 1);
+        assert_eq!(server.requests(), 3);
+    }
+}
@@ -0,0 +1,6 @@
+mod cache;
+mod http;
+mod socket;
+
+pub use http::Daemon;
+pub use socket::SocketDaemon;
@@ -0,0 +1,367 @@
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    os::unix::fs::FileTypeExt,
+    rc::Rc,
+    time::{Duration, Instant},
+};
+
+use serde::Deserialize;
+use serde_json::json;
+use tokio::{
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+    net::{unix::OwnedWriteHalf, UnixListener, UnixStream},
+    sync::Mutex,
+    task::{spawn_local, LocalSet},
+};
+
+use crate::{
+    arguments::Args,
+    daemon::cache::{CachedReadings, ReadingCache},
+    error::ApplicationError,
+    instruments::{
+        communication::{get_communication_device, Communication},
+        reading::Reading,
+    },
+    output::json,
+    shutdown,
+    workflow::script::parse_duration,
+};
+
+/**
+ * Label of the device when the daemon serves a single device without profile.
+ */
+const DEFAULT_DEVICE: &str = "default";
+
+/**
+ * A request of a socket client, one JSON object per line. The device may be left out when the
+ * daemon serves a single device.
+ */
+#[derive(Debug, PartialEq, Deserialize)]
+#[serde(tag = "op", rename_all = "kebab-case", deny_unknown_fields)]
+pub enum SocketRequest {
+    /**
+     * Lists the device labels.
+     */
+    Devices,
+    /**
+     * Sends commands to a device and returns their readings.
+     */
+    Command { device: Option<String>, commands: Vec<String> },
+    /**
+     * Runs the commands of the device, or returns cached readings up to `max_age` old.
+     */
+    Reading { device: Option<String>, max_age: Option<String> },
+    /**
+     * Runs the commands of the device every `interval` until the client disconnects.
+     */
+    Subscribe { device: Option<String>, interval: String },
+}
+
+/**
+ * A device kept open by the daemon.
+ */
+struct OpenDevice {
+    args: Args,
+    /**
+     * Held while the hardware is accessed so clients take turns on the device.
+     */
+    device: Mutex<Box<dyn Communication>>,
+}
+
+/**
+ * Daemon keeping the configured devices open and serving newline-delimited JSON requests on a
+ * Unix domain socket, so measurements do not pay for opening and claiming the device and local
+ * clients can share an instrument. Every request is answered with one JSON line, either
+ * `{"device": ..., "readings": [...]}` or `{"error": ...}`; a subscription answers once per
+ * interval.
+ */
+pub struct SocketDaemon {
+    devices: HashMap<String, OpenDevice>,
+    cache: RefCell<ReadingCache>,
+}
+
+impl SocketDaemon {
+    /**
+     * Opens the devices.
+     *
+     * # Arguments
+     * `devices` - The device labels (None for a single device) with their arguments.
+     *
+     * # Returns
+     * A Result containing the daemon or the ApplicationError of a device that could not be opened.
+     */
+    pub async fn open(devices: &[(Option<String>, Args)]) -> Result<Self, ApplicationError> {
+        let mut open = HashMap::new();
+        for (label, args) in devices {
+            let device = get_communication_device(args).await?;
+            open.insert(
+                label.clone().unwrap_or_else(|| DEFAULT_DEVICE.to_string()),
+                OpenDevice {
+                    args: args.clone(),
+                    device: Mutex::new(device),
+                },
+            );
+        }
+        Ok(Self {
+            devices: open,
+            cache: RefCell::new(ReadingCache::default()),
+        })
+    }
+
+    /**
+     * Listens on the socket and serves clients until a shutdown is requested, then removes the
+     * socket. A socket left behind by an earlier daemon is replaced; other files are not.
+     *
+     * # Arguments
+     * `path` - The path of the socket.
+     */
+    pub async fn serve(self, path: &str) -> Result<(), ApplicationError> {
+        if std::fs::symlink_metadata(path).is_ok_and(|metadata| metadata.file_type().is_socket()) {
+            std::fs::remove_file(path).map_err(|e| ApplicationError::Io(format!("Could not remove {}", path), e))?;
+        }
+        let listener =
+            UnixListener::bind(path).map_err(|e| ApplicationError::Io(format!("Could not listen on {}", path), e))?;
+        let daemon = Rc::new(self);
+        // Devices are not Send, so clients are served on the local task set
+        let result = LocalSet::new()
+            .run_until(async move {
+                loop {
+                    let (stream, _) = tokio::select! {
+                        biased;
+                        _ = shutdown::requested() => return Ok(()),
+                        accepted = listener.accept() => {
+                            accepted.map_err(|e| ApplicationError::Io("Could not accept client".into(), e))?
+                        }
+                    };
+                    spawn_local(daemon.clone().handle(stream));
+                }
+            })
+            .await;
+        let _ = std::fs::remove_file(path);
+        result
+    }
+
+    /**
+     * Answers the requests of a client until it disconnects.
+     *
+     * # Arguments
+     * `stream` - The client connection.
+     */
+    async fn handle(self: Rc<Self>, stream: UnixStream) {
+        let (reader, mut writer) = stream.into_split();
+        let mut lines = BufReader::new(reader).lines();
+        while let Ok(Some(line)) = lines.next_line().await {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let result = match serde_json::from_str::<SocketRequest>(&line) {
+                Ok(SocketRequest::Subscribe { device, interval }) => {
+                    self.subscribe(device.as_deref(), &interval, &mut writer).await;
+                    return;
+                }
+                Ok(request) => self.answer(request).await,
+                Err(e) => Err(ApplicationError::Command(format!("Invalid request: {}", e))),
+            };
+            if !send(&mut writer, result).await {
+                return;
+            }
+        }
+    }
+
+    /**
+     * Answers a request other than a subscription.
+     *
+     * # Arguments
+     * `request` - The request.
+     *
+     * # Returns
+     * A Result containing the response or an ApplicationError.
+     */
+    async fn answer(&self, request: SocketRequest) -> Result<serde_json::Value, ApplicationError> {
+        match request {
+            SocketRequest::Devices => {
+                let mut labels: Vec<&String> = self.devices.keys().collect();
+                labels.sort();
+                Ok(json!({ "devices": labels }))
+            }
+            SocketRequest::Command { device, commands } => {
+                let (label, device) = self.device(device.as_deref())?;
+                let formatted = self.command(device, commands).await?;
+                Ok(json!({ "device": label, "readings": readings(&formatted) }))
+            }
+            SocketRequest::Reading { device, max_age } => {
+                let max_age = max_age.as_deref().map(parse_duration).transpose()?;
+                let (label, device) = self.device(device.as_deref())?;
+                let communication = device.device.lock().await;
+                if let Some(entry) = self.cache.borrow().get(label, max_age, Instant::now()) {
+                    return Ok(body(label, entry, true));
+                }
+                let entry = CachedReadings {
+                    readings: format(&device.args, communication.command(device.args.control.commands.clone()).await?)?,
+                    taken: Instant::now(),
+                };
+                let body = body(label, &entry, false);
+                self.cache.borrow_mut().insert(label, entry);
+                Ok(body)
+            }
+            SocketRequest::Subscribe { .. } => Err(ApplicationError::Command("Subscriptions are answered per interval".into())),
+        }
+    }
+
+    /**
+     * Runs the commands of a device every interval and sends the readings until the client
+     * disconnects.
+     *
+     * # Arguments
+     * `device` - The device label, if given.
+     * `interval` - The interval, e.g. `1s`.
+     * `writer` - The client connection.
+     */
+    async fn subscribe(&self, device: Option<&str>, interval: &str, writer: &mut OwnedWriteHalf) {
+        let opened = parse_duration(interval).and_then(|interval| Ok((interval, self.device(device)?)));
+        let (interval, (label, device)) = match opened {
+            Ok(opened) => opened,
+            Err(e) => {
+                send(writer, Err(e)).await;
+                return;
+            }
+        };
+        let mut ticks = tokio::time::interval(interval.max(Duration::from_millis(1)));
+        loop {
+            ticks.tick().await;
+            let result = self
+                .command(device, device.args.control.commands.clone())
+                .await
+                .map(|formatted| json!({ "device": label, "readings": readings(&formatted) }));
+            if !send(writer, result).await {
+                return;
+            }
+        }
+    }
+
+    /**
+     * Finds a device by label, or the only device if no label is given.
+     *
+     * # Arguments
+     * `label` - The device label, if given.
+     *
+     * # Returns
+     * A Result containing the label and the device or an ApplicationError.
+     */
+    fn device(&self, label: Option<&str>) -> Result<(&str, &OpenDevice), ApplicationError> {
+        match label {
+            Some(label) => self.devices.get_key_value(label),
+            None if self.devices.len() == 1 => self.devices.iter().next(),
+            None => return Err(ApplicationError::Command("The device must be given when several are served".into())),
+        }
+        .map(|(label, device)| (label.as_str(), device))
+        .ok_or_else(|| ApplicationError::Command(format!("Unknown device {}", label.unwrap_or_default())))
+    }
+
+    /**
+     * Sends commands to an open device, waiting for other clients of the device.
+     *
+     * # Arguments
+     * `device` - The device.
+     * `commands` - The commands.
+     *
+     * # Returns
+     * A Result containing the readings formatted as JSON objects or an ApplicationError.
+     */
+    async fn command(&self, device: &OpenDevice, commands: Vec<String>) -> Result<Vec<String>, ApplicationError> {
+        let readings = device.device.lock().await.command(commands).await?;
+        format(&device.args, readings)
+    }
+}
+
+/**
+ * Formats readings as JSON objects.
+ *
+ * # Arguments
+ * `args` - The arguments of the device.
+ * `readings` - The readings.
+ *
+ * # Returns
+ * A Result containing the formatted readings or an ApplicationError.
+ */
+fn format(args: &Args, readings: Option<Vec<Box<dyn Reading>>>) -> Result<Vec<String>, ApplicationError> {
+    readings
+        .unwrap_or_default()
+        .iter()
+        .map(|reading| json::format_reading(reading.as_ref(), args.printing.notation.unwrap_or_default()))
+        .collect()
+}
+
+/**
+ * Builds the response for readings of a device.
+ *
+ * # Arguments
+ * `label` - The device label.
+ * `entry` - The readings.
+ * `cached` - Whether the readings came from the cache.
+ *
+ * # Returns
+ * The JSON response.
+ */
+fn body(label: &str, entry: &CachedReadings, cached: bool) -> serde_json::Value {
+    json!({
+        "device": label,
+        "cached": cached,
+        "age_ms": entry.taken.elapsed().as_millis() as u64,
+        "readings": readings(&entry.readings),
+    })
+}
+
+/**
+ * Parses readings formatted as JSON objects.
+ *
+ * # Arguments
+ * `readings` - The formatted readings.
+ *
+ * # Returns
+ * The readings as JSON values.
+ */
+fn readings(readings: &[String]) -> Vec<serde_json::Value> {
+    readings.iter().filter_map(|reading| serde_json::from_str(reading).ok()).collect()
+}
+
+/**
+ * Writes a response line to the client.
+ *
+ * # Arguments
+ * `writer` - The client connection.
+ * `result` - The response or the error to report.
+ *
+ * # Returns
+ * A boolean indicating whether the client is still connected.
+ */
+async fn send(writer: &mut OwnedWriteHalf, result: Result<serde_json::Value, ApplicationError>) -> bool {
+    let response = match result {
+        Ok(response) => response,
+        Err(e) => json!({ "error": e.to_string() }),
+    };
+    writer.write_all(format!("{}\n", response).as_bytes()).await.is_ok()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_requests() {
+        let request: SocketRequest = serde_json::from_str(r#"{"op":"command","device":"psu","commands":["Measure"]}"#).unwrap();
+        assert_eq!(
+            request,
+            SocketRequest::Command {
+                device: Some("psu".into()),
+                commands: vec!["Measure".into()]
+            }
+        );
+        let request: SocketRequest = serde_json::from_str(r#"{"op":"subscribe","interval":"1s"}"#).unwrap();
+        assert_eq!(request, SocketRequest::Subscribe { device: None, interval: "1s".into() });
+        assert_eq!(serde_json::from_str::<SocketRequest>(r#"{"op":"devices"}"#).unwrap(), SocketRequest::Devices);
+        assert!(serde_json::from_str::<SocketRequest>(r#"{"op":"reading","devise":"psu"}"#).is_err());
+        assert!(serde_json::from_str::<SocketRequest>(r#"{"op":"restart"}"#).is_err());
+    }
+}
@@ -1,55 +1,258 @@
-use std::fmt::Debug;
+use std::num::{ParseFloatError, ParseIntError};
+
+use thiserror::Error;
+
+/**
+ * Exit code of errors without a more specific code.
+ */
+const EXIT_GENERAL: u8 = 1;
+/**
+ * Exit code when the device is not found.
+ */
+const EXIT_NOT_FOUND: u8 = 2;
+/**
+ * Exit code when a command is invalid or the instrument rejects it.
+ */
+const EXIT_COMMAND: u8 = 3;
+/**
+ * Exit code when the device does not answer in time.
+ */
+const EXIT_TIMEOUT: u8 = 4;
+/**
+ * Exit code for invalid arguments, configuration files and scripts.
+ */
+pub const EXIT_CONFIG: u8 = 5;
+/**
+ * Exit code when communicating with the device fails.
+ */
+const EXIT_DEVICE: u8 = 6;
+/**
+ * Exit code when a reading violates an --assert bound.
+ */
+const EXIT_ASSERTION: u8 = 7;
 
 /**
  * Enum representing application-level errors.
  */
+#[derive(Debug, Error)]
 pub enum ApplicationError {
     /// Error related to USB device operations
+    #[error("USB Error: {0}")]
     Usb(String),
     /// Error related to HID device operations
+    #[error("HID Error: {0}")]
     Hid(String),
     /// Error related to command execution
+    #[error("Command Error: {0}")]
     Command(String),
+    /// Error related to sound card input
+    #[error("Audio Error: {0}")]
+    Audio(String),
+    /// Error related to serial port operations
+    #[error("Serial Error: {0}")]
+    Serial(String),
+    /// Error related to Bluetooth LE operations
+    #[error("Bluetooth Error: {0}")]
+    Bluetooth(String),
+    /// Error related to GPIB operations
+    #[error("GPIB Error: {0}")]
+    Gpib(String),
+    /// Error related to network connections
+    #[error("Network Error: {0}")]
+    Network(String),
+    /// Error related to the configuration file
+    #[error("Config Error: {0}")]
+    Config(String),
     /// General application error
+    #[error("General Error: {0}")]
     General(String),
+    /// The device was not found
+    #[cfg(has_discovery)]
+    #[error("Not Found Error: {0}")]
+    NotFound(String),
+    /// The device did not answer in time
+    #[error("Timeout Error: {0}")]
+    Timeout(String),
+    /// Error reading or writing files and sockets, with the underlying error
+    #[error("IO Error: {0}: {1}")]
+    Io(String, #[source] std::io::Error),
+    /// Error of the USB library, with the underlying error
+    #[cfg(feature = "usb")]
+    #[error("USB Error: {0}: {1}")]
+    Nusb(String, #[source] nusb::Error),
+    /// A USB transfer of a command or its response failed, with the underlying error
+    #[cfg(feature = "usb")]
+    #[error("Command Error: {0}: {1}")]
+    Transfer(String, #[source] nusb::transfer::TransferError),
+    /// Error of the HID library, with the underlying error
+    #[cfg(feature = "hid")]
+    #[error("HID Error: {0}: {1}")]
+    HidApi(String, #[source] hidapi::HidError),
+    /// Error of the serial port, with the underlying error
+    #[cfg(feature = "serial")]
+    #[error("Serial Error: {0}: {1}")]
+    SerialPort(String, #[source] serialport::Error),
+    /// The configuration file is not valid TOML or does not match the expected fields
+    #[error("Config Error: Invalid configuration: {0}")]
+    Toml(#[from] toml::de::Error),
+    /// An integer in a command could not be parsed, with the parse error
+    #[error("Command Error: {0}: {1}")]
+    ParseInt(String, #[source] ParseIntError),
+    /// A decimal number in a command or response could not be parsed, with the parse error
+    #[error("Command Error: {0}: {1}")]
+    ParseFloat(String, #[source] ParseFloatError),
+    /// A reading violated an --assert bound
+    #[error("Assertion Error: {0}")]
+    Assertion(String),
 }
 
-impl Debug for ApplicationError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl ApplicationError {
+    /**
+     * Checks if the device was not found, e.g. because it is not plugged in.
+     *
+     * # Returns
+     * A boolean indicating whether the device was not found.
+     */
+    pub fn is_not_found(&self) -> bool {
+        self.exit_code() == EXIT_NOT_FOUND
+    }
+
+    /**
+     * Returns the process exit code of the error, so scripts can tell failure modes apart.
+     *
+     * # Returns
+     * 2 if the device was not found, 3 for command errors, 4 for timeouts, 5 for configuration
+     * errors, 6 for other device communication errors, 7 for violated assertions and 1 otherwise.
+     */
+    pub fn exit_code(&self) -> u8 {
         match self {
-            ApplicationError::Usb(msg) => write!(f, "USB Error: {}", msg),
-            ApplicationError::Hid(msg) => write!(f, "HID Error: {}", msg),
-            ApplicationError::Command(msg) => write!(f, "Command Error: {}", msg),
-            ApplicationError::General(msg) => write!(f, "General Error: {}", msg),
+            #[cfg(has_discovery)]
+            ApplicationError::NotFound(_) => EXIT_NOT_FOUND,
+            ApplicationError::Command(_) | ApplicationError::ParseInt(..) | ApplicationError::ParseFloat(..) => EXIT_COMMAND,
+            #[cfg(feature = "usb")]
+            ApplicationError::Transfer(..) => EXIT_COMMAND,
+            ApplicationError::Timeout(_) => EXIT_TIMEOUT,
+            ApplicationError::Config(_) | ApplicationError::Toml(_) => EXIT_CONFIG,
+            ApplicationError::Assertion(_) => EXIT_ASSERTION,
+            ApplicationError::Usb(_)
+            | ApplicationError::Hid(_)
+            | ApplicationError::Audio(_)
+            | ApplicationError::Serial(_)
+            | ApplicationError::Bluetooth(_)
+            | ApplicationError::Gpib(_)
+            | ApplicationError::Network(_) => EXIT_DEVICE,
+            #[cfg(feature = "usb")]
+            ApplicationError::Nusb(..) => EXIT_DEVICE,
+            #[cfg(feature = "hid")]
+            ApplicationError::HidApi(..) => EXIT_DEVICE,
+            #[cfg(feature = "serial")]
+            ApplicationError::SerialPort(..) => EXIT_DEVICE,
+            ApplicationError::General(_) | ApplicationError::Io(..) => EXIT_GENERAL,
         }
     }
 }
 
 #[cfg(test)]
 mod test {
+    use std::num::ParseIntError;
+
     use super::ApplicationError;
 
     #[test]
-    fn test_debug_usb_error() {
+    fn test_display_usb_error() {
         let error = ApplicationError::Usb("Device not found".into());
-        assert_eq!(format!("{:?}", error), "USB Error: Device not found");
+        assert_eq!(error.to_string(), "USB Error: Device not found");
     }
 
     #[test]
-    fn test_debug_hid_error() {
+    fn test_display_hid_error() {
         let error = ApplicationError::Hid("Failed to open HID device".into());
-        assert_eq!(format!("{:?}", error), "HID Error: Failed to open HID device");
+        assert_eq!(error.to_string(), "HID Error: Failed to open HID device");
     }
 
     #[test]
-    fn test_debug_command_error() {
+    fn test_display_command_error() {
         let error = ApplicationError::Command("Invalid command".into());
-        assert_eq!(format!("{:?}", error), "Command Error: Invalid command");
+        assert_eq!(error.to_string(), "Command Error: Invalid command");
+    }
+
+    #[test]
+    fn test_display_audio_error() {
+        let error = ApplicationError::Audio("No default input device".into());
+        assert_eq!(error.to_string(), "Audio Error: No default input device");
     }
 
     #[test]
-    fn test_debug_general_error() {
+    fn test_display_serial_error() {
+        let error = ApplicationError::Serial("No response from /dev/ttyACM0".into());
+        assert_eq!(error.to_string(), "Serial Error: No response from /dev/ttyACM0");
+    }
+
+    #[test]
+    fn test_display_bluetooth_error() {
+        let error = ApplicationError::Bluetooth("No adapter found".into());
+        assert_eq!(error.to_string(), "Bluetooth Error: No adapter found");
+    }
+
+    #[test]
+    fn test_display_network_error() {
+        let error = ApplicationError::Network("Modbus TCP connection failed: connection reset".into());
+        assert_eq!(error.to_string(), "Network Error: Modbus TCP connection failed: connection reset");
+    }
+
+    #[test]
+    fn test_display_gpib_error() {
+        let error = ApplicationError::Gpib("Writing \"*IDN?\": ENOL no listeners".into());
+        assert_eq!(error.to_string(), "GPIB Error: Writing \"*IDN?\": ENOL no listeners");
+    }
+
+    #[test]
+    fn test_display_config_error() {
+        let error = ApplicationError::Config("Unknown profile x".into());
+        assert_eq!(error.to_string(), "Config Error: Unknown profile x");
+    }
+
+    #[test]
+    fn test_display_general_error() {
         let error = ApplicationError::General("An unknown error occurred".into());
-        assert_eq!(format!("{:?}", error), "General Error: An unknown error occurred");
+        assert_eq!(error.to_string(), "General Error: An unknown error occurred");
+    }
+
+    #[test]
+    fn test_display_and_source() {
+        let error = ApplicationError::Io(
+            "Could not open log x.log".into(),
+            std::io::Error::new(std::io::ErrorKind::NotFound, "No such file"),
+        );
+        assert_eq!(error.to_string(), "IO Error: Could not open log x.log: No such file");
+        assert_eq!(std::error::Error::source(&error).map(|source| source.to_string()), Some("No such file".into()));
+        assert!(std::error::Error::source(&ApplicationError::Usb("Busy".into())).is_none());
+        assert_eq!(format!("{:?}", ApplicationError::Usb("Busy".into())), "Usb(\"Busy\")");
+    }
+
+    #[test]
+    fn test_parse_error_sources() {
+        let error = ApplicationError::ParseInt("Invalid channel x".into(), "x".parse::<u16>().unwrap_err());
+        assert_eq!(error.to_string(), "Command Error: Invalid channel x: invalid digit found in string");
+        assert_eq!(error.exit_code(), 3);
+        let source = std::error::Error::source(&error).and_then(|source| source.downcast_ref::<ParseIntError>());
+        assert_eq!(source, Some(&"x".parse::<u16>().unwrap_err()));
+
+        let error = ApplicationError::from(toml::from_str::<toml::Table>("x =").unwrap_err());
+        assert!(error.to_string().starts_with("Config Error: Invalid configuration: "));
+        assert_eq!(error.exit_code(), 5);
+        assert!(std::error::Error::source(&error).is_some_and(|source| source.is::<toml::de::Error>()));
+    }
+
+    #[test]
+    fn test_exit_codes() {
+        #[cfg(has_discovery)]
+        assert_eq!(ApplicationError::NotFound("USB device 1ab1:4ce".into()).exit_code(), 2);
+        assert_eq!(ApplicationError::Command("Unknown command".into()).exit_code(), 3);
+        assert_eq!(ApplicationError::Timeout("No response".into()).exit_code(), 4);
+        assert_eq!(ApplicationError::Config("Unknown profile".into()).exit_code(), 5);
+        assert_eq!(ApplicationError::Serial("Failed to open".into()).exit_code(), 6);
+        assert_eq!(ApplicationError::Assertion("value 5.12 violates value within 4.95..5.05".into()).exit_code(), 7);
+        assert_eq!(ApplicationError::General("Failed".into()).exit_code(), 1);
     }
 }
\ No newline at end of file
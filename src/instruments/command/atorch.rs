@@ -0,0 +1,229 @@
+use crate::{
+    error::ApplicationError,
+    instruments::command::readback::{Expected, Readback},
+};
+
+// First two and last byte of a command frame
+#[cfg(feature = "serial")]
+const FRAME_START: [u8; 2] = [0xB1, 0xB2];
+#[cfg(feature = "serial")]
+const FRAME_END: u8 = 0xB6;
+
+// Setting commands, answered with a single acknowledge byte
+#[cfg(feature = "serial")]
+const SET_OUTPUT: u8 = 0x01;
+#[cfg(feature = "serial")]
+const SET_CURRENT: u8 = 0x02;
+#[cfg(feature = "serial")]
+const SET_CUTOFF: u8 = 0x03;
+#[cfg(feature = "serial")]
+const RESET_COUNTERS: u8 = 0x05;
+
+// Queries, answered with a value frame
+#[cfg(feature = "serial")]
+pub const QUERY_OUTPUT: u8 = 0x10;
+#[cfg(feature = "serial")]
+pub const QUERY_VOLTAGE: u8 = 0x11;
+#[cfg(feature = "serial")]
+pub const QUERY_CURRENT: u8 = 0x12;
+#[cfg(feature = "serial")]
+pub const QUERY_TIME: u8 = 0x13;
+#[cfg(feature = "serial")]
+pub const QUERY_CAPACITY: u8 = 0x14;
+#[cfg(feature = "serial")]
+pub const QUERY_ENERGY: u8 = 0x15;
+#[cfg(feature = "serial")]
+pub const QUERY_TEMPERATURE: u8 = 0x16;
+#[cfg(feature = "serial")]
+pub const QUERY_CURRENT_SET: u8 = 0x17;
+#[cfg(feature = "serial")]
+pub const QUERY_CUTOFF_SET: u8 = 0x18;
+
+/**
+ * Enum representing the commands of Atorch DL24 / PX100 style electronic loads.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub enum AtorchCommand {
+    /// Switches the load on or off.
+    Output(bool),
+    /// Sets the load current in amperes, in steps of 10 mA.
+    Current(f64),
+    /// Sets the cutoff voltage in volts, in steps of 10 mV. The load switches off below it.
+    Cutoff(f64),
+    /// Resets the capacity, energy and time counters.
+    Reset,
+    /// Reads the voltage, current, time, capacity, energy, temperature and load state.
+    Measure,
+    /// Reads the current and cutoff voltage setpoints.
+    Setpoints,
+}
+
+/**
+ * Builds a command frame.
+ *
+ * # Arguments
+ * `command` - The command byte.
+ * `data` - The two data bytes.
+ *
+ * # Returns
+ * The frame.
+ */
+#[cfg(feature = "serial")]
+fn frame(command: u8, data: [u8; 2]) -> [u8; 6] {
+    [FRAME_START[0], FRAME_START[1], command, data[0], data[1], FRAME_END]
+}
+
+/**
+ * Splits a setpoint into the whole and hundredth parts sent in the data bytes.
+ *
+ * # Arguments
+ * `value` - The setpoint, below 256.
+ *
+ * # Returns
+ * The data bytes.
+ */
+#[cfg(feature = "serial")]
+fn hundredths(value: f64) -> [u8; 2] {
+    let hundredths = (value * 100.0).round() as u32;
+    [(hundredths / 100) as u8, (hundredths % 100) as u8]
+}
+
+impl AtorchCommand {
+    /**
+     * Serializes the command into the frames sent to the load. Frames with a command byte of
+     * 0x10 and above are queries answered with a value frame, the others are acknowledged.
+     *
+     * # Returns
+     * The frames in order.
+     */
+    #[cfg(feature = "serial")]
+    pub fn to_wire(&self) -> Vec<[u8; 6]> {
+        let queries = |commands: &[u8]| commands.iter().map(|command| frame(*command, [0, 0])).collect();
+        match self {
+            AtorchCommand::Output(on) => vec![frame(SET_OUTPUT, [*on as u8, 0])],
+            AtorchCommand::Current(amperes) => vec![frame(SET_CURRENT, hundredths(*amperes))],
+            AtorchCommand::Cutoff(volts) => vec![frame(SET_CUTOFF, hundredths(*volts))],
+            AtorchCommand::Reset => vec![frame(RESET_COUNTERS, [0, 0])],
+            AtorchCommand::Measure => queries(&[
+                QUERY_OUTPUT,
+                QUERY_VOLTAGE,
+                QUERY_CURRENT,
+                QUERY_TIME,
+                QUERY_CAPACITY,
+                QUERY_ENERGY,
+                QUERY_TEMPERATURE,
+            ]),
+            AtorchCommand::Setpoints => queries(&[QUERY_CURRENT_SET, QUERY_CUTOFF_SET]),
+        }
+    }
+
+    /**
+     * Gets the query reading back a setpoint or the load state. Setpoints are compared at the
+     * resolution they are sent with.
+     *
+     * # Returns
+     * The readback, or None if the command is not a setting.
+     */
+    pub fn readback(&self) -> Option<Readback> {
+        let setpoint = |value: f64| Expected::Number((value * 100.0).round() / 100.0, 0.01);
+        match self {
+            AtorchCommand::Output(on) => Some(Readback::field("Measure", "output", Expected::Switch(*on))),
+            AtorchCommand::Current(amperes) => Some(Readback::field("Setpoints", "current", setpoint(*amperes))),
+            AtorchCommand::Cutoff(volts) => Some(Readback::field("Setpoints", "voltage", setpoint(*volts))),
+            _ => None,
+        }
+    }
+}
+
+/**
+ * Parses an On or Off argument.
+ *
+ * # Arguments
+ * `command` - The full command, used in error messages.
+ * `value` - The argument.
+ *
+ * # Returns
+ * A Result containing true for On, false for Off or an ApplicationError.
+ */
+fn parse_switch(command: &str, value: &str) -> Result<bool, ApplicationError> {
+    match value.trim() {
+        "On" => Ok(true),
+        "Off" => Ok(false),
+        _ => Err(ApplicationError::Command(format!("Expected On or Off, got {}", command))),
+    }
+}
+
+/**
+ * Parses a setpoint argument, which must fit the whole part in a byte.
+ *
+ * # Arguments
+ * `command` - The full command, used in error messages.
+ * `value` - The argument.
+ *
+ * # Returns
+ * A Result containing the number or an ApplicationError.
+ */
+fn parse_setpoint(command: &str, value: &str) -> Result<f64, ApplicationError> {
+    value
+        .trim()
+        .parse::<f64>()
+        .ok()
+        .filter(|number| (0.0..255.995).contains(number))
+        .ok_or_else(|| ApplicationError::Command(format!("Invalid value in {}", command)))
+}
+
+impl TryFrom<&str> for AtorchCommand {
+    type Error = ApplicationError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        let (name, argument) = match value.split_once(':') {
+            Some((name, argument)) => (name, Some(argument)),
+            None => (value, None),
+        };
+        match (name, argument) {
+            ("Output", Some(argument)) => Ok(AtorchCommand::Output(parse_switch(value, argument)?)),
+            ("Current", Some(argument)) => Ok(AtorchCommand::Current(parse_setpoint(value, argument)?)),
+            ("Cutoff", Some(argument)) => Ok(AtorchCommand::Cutoff(parse_setpoint(value, argument)?)),
+            ("Reset", None) => Ok(AtorchCommand::Reset),
+            ("Measure", None) => Ok(AtorchCommand::Measure),
+            ("Setpoints", None) => Ok(AtorchCommand::Setpoints),
+            _ => Err(ApplicationError::Command(format!("Unknown command: {}", value))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_try_from_command() {
+        assert_eq!(AtorchCommand::try_from("Current:1.5").unwrap(), AtorchCommand::Current(1.5));
+        assert_eq!(AtorchCommand::try_from("Output:On").unwrap(), AtorchCommand::Output(true));
+        assert_eq!(AtorchCommand::try_from("Cutoff:2.9").unwrap(), AtorchCommand::Cutoff(2.9));
+        assert_eq!(AtorchCommand::try_from("Measure").unwrap(), AtorchCommand::Measure);
+        assert!(AtorchCommand::try_from("Current:-1").is_err());
+        assert!(AtorchCommand::try_from("Current:256").is_err());
+        assert!(AtorchCommand::try_from("Voltage:5").is_err());
+        assert!(AtorchCommand::try_from("Reset:1").is_err());
+    }
+
+    #[cfg(feature = "serial")]
+    #[test]
+    fn test_to_wire() {
+        assert_eq!(AtorchCommand::Output(true).to_wire(), vec![[0xB1, 0xB2, 0x01, 0x01, 0x00, 0xB6]]);
+        assert_eq!(AtorchCommand::Current(1.25).to_wire(), vec![[0xB1, 0xB2, 0x02, 0x01, 0x19, 0xB6]]);
+        assert_eq!(AtorchCommand::Cutoff(2.999).to_wire(), vec![[0xB1, 0xB2, 0x03, 0x03, 0x00, 0xB6]]);
+        assert_eq!(AtorchCommand::Measure.to_wire().len(), 7);
+        assert_eq!(AtorchCommand::Setpoints.to_wire()[1], [0xB1, 0xB2, 0x18, 0x00, 0x00, 0xB6]);
+    }
+
+    #[test]
+    fn test_readback() {
+        assert_eq!(
+            AtorchCommand::Current(1.234).readback(),
+            Some(Readback::field("Setpoints", "current", Expected::Number(1.23, 0.01)))
+        );
+        assert_eq!(AtorchCommand::Reset.readback(), None);
+    }
+}
@@ -0,0 +1,20 @@
+use crate::instruments::command::ScopeDialect;
+
+/**
+ * Enum representing how typed commands are translated to SCPI for a device.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommandSet {
+    /// Commands are sent as is, apart from RawHex payloads.
+    Raw,
+    /// Oscilloscope Measure and Segments commands are translated to the dialect.
+    Scope(ScopeDialect),
+    /// Close and Open commands are translated to SCPI route commands.
+    Switch,
+    /// Typed Peaktech 4055MV generator commands are checked and translated to SCPI, addressed
+    /// to the given output channel unless they have a channel number.
+    Generator(u8),
+    /// Typed Keysight multimeter commands are translated to `CONF:`, `MEAS:` and `READ?`, and
+    /// their values read with the unit of the configured function.
+    Dmm,
+}
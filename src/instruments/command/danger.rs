@@ -0,0 +1,122 @@
+use crate::instruments::command::limits::voltage_setpoint;
+
+/**
+ * Rules flagging commands as dangerous, e.g. calibration commands or enabling an output set
+ * above a voltage threshold.
+ */
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DangerRules {
+    /**
+     * Dangerous commands, matched case insensitively. A trailing `*` matches any command
+     * starting with the pattern, e.g. `CAL*`.
+     */
+    pub patterns: Vec<String>,
+    /**
+     * Enabling the output is dangerous when the last voltage set is above this threshold.
+     */
+    pub output_voltage: Option<f64>,
+}
+
+/**
+ * Checks if a command enables an output: `Output:On`, Korad `OUT1` or SCPI `OUTP ON`.
+ *
+ * # Arguments
+ * `command` - The command string.
+ *
+ * # Returns
+ * A boolean indicating whether the command enables an output.
+ */
+fn is_output_on(command: &str) -> bool {
+    let command = command.trim().to_ascii_uppercase();
+    if command == "OUTPUT:ON" || command == "OUT1" {
+        return true;
+    }
+    match command.split_once(char::is_whitespace) {
+        Some((header, argument)) => {
+            let header = header.trim_start_matches(':');
+            matches!(header, "OUTP" | "OUTPUT" | "OUTP:STAT" | "OUTPUT:STATE")
+                && matches!(argument.trim(), "ON" | "1")
+        }
+        None => false,
+    }
+}
+
+impl DangerRules {
+    /**
+     * Checks if no rule is set.
+     *
+     * # Returns
+     * A boolean indicating whether no rule is set.
+     */
+    pub fn is_empty(&self) -> bool {
+        self.patterns.is_empty() && self.output_voltage.is_none()
+    }
+
+    /**
+     * Checks a command against the rules. Commands must be checked in the order they are sent,
+     * as the voltage set by earlier commands is tracked.
+     *
+     * # Arguments
+     * `command` - The command string.
+     * `voltage` - The last voltage set on the device, updated by the command.
+     *
+     * # Returns
+     * Some(reason) if the command is dangerous, otherwise None.
+     */
+    pub fn check(&self, command: &str, voltage: &mut Option<f64>) -> Option<String> {
+        if let Some(setpoint) = voltage_setpoint(command) {
+            *voltage = Some(setpoint);
+        }
+        let upper = command.trim().to_ascii_uppercase();
+        for pattern in &self.patterns {
+            let pattern = pattern.trim().to_ascii_uppercase();
+            let matched = match pattern.strip_suffix('*') {
+                Some(prefix) => upper.starts_with(prefix),
+                None => upper == pattern,
+            };
+            if matched {
+                return Some(format!("matches {}", pattern));
+            }
+        }
+        match (self.output_voltage, *voltage) {
+            (Some(threshold), Some(voltage)) if voltage > threshold && is_output_on(command) => Some(format!(
+                "enables the output at {} V, above {} V",
+                voltage, threshold
+            )),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn rules() -> DangerRules {
+        DangerRules {
+            patterns: vec!["CAL*".into(), "*RST".into()],
+            output_voltage: Some(30.0),
+        }
+    }
+
+    #[test]
+    fn test_patterns() {
+        let mut voltage = None;
+        assert!(rules().check("cal:volt 1", &mut voltage).is_some());
+        assert!(rules().check("*RST", &mut voltage).is_some());
+        assert!(rules().check("*RST;*CLS", &mut voltage).is_none());
+        assert!(rules().check("MEAS:VOLT?", &mut voltage).is_none());
+    }
+
+    #[test]
+    fn test_output_above_threshold() {
+        let mut voltage = None;
+        assert!(rules().check("OUTP ON", &mut voltage).is_none());
+        assert!(rules().check("VOLT 48", &mut voltage).is_none());
+        assert_eq!(voltage, Some(48.0));
+        assert!(rules().check("OUTP ON", &mut voltage).is_some());
+        assert!(rules().check("Output:Off", &mut voltage).is_none());
+        assert!(rules().check("Voltage:12", &mut voltage).is_none());
+        assert!(rules().check("Output:On", &mut voltage).is_none());
+    }
+}
@@ -0,0 +1,81 @@
+use std::time::Duration;
+
+use crate::error::ApplicationError;
+
+/**
+ * Represents a pause between two commands, e.g. `Delay:200` waiting 200 ms.
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Delay {
+    pub duration: Duration,
+}
+
+impl Delay {
+    /**
+     * Parses a Delay command.
+     *
+     * # Arguments
+     * `command` - The command string.
+     *
+     * # Returns
+     * A Result containing Some(Delay) for Delay commands, None for other commands, or an
+     * ApplicationError if the duration is invalid.
+     */
+    pub fn parse(command: &str) -> Result<Option<Self>, ApplicationError> {
+        let Some(duration) = command.strip_prefix("Delay:") else {
+            return Ok(None);
+        };
+        let duration = duration
+            .trim()
+            .parse::<u64>()
+            .map_err(|e| ApplicationError::ParseInt(format!("Invalid delay {}", duration), e))?;
+        Ok(Some(Self {
+            duration: Duration::from_millis(duration),
+        }))
+    }
+}
+
+/**
+ * Gets the pause before a command with `--delay-ms`. The first command of a batch is sent right
+ * away, and no pause is added next to a Delay command, which sets the pause itself.
+ *
+ * # Arguments
+ * `previous` - The command sent before, if any.
+ * `command` - The command about to be sent.
+ * `delay` - The delay between commands, if any.
+ *
+ * # Returns
+ * The time to wait before sending the command, if any.
+ */
+pub fn pause_before(previous: Option<&str>, command: &str, delay: Option<Duration>) -> Option<Duration> {
+    let previous = previous?;
+    let is_delay = |command: &str| command.starts_with("Delay:");
+    (!is_delay(previous) && !is_delay(command)).then_some(delay?)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            Delay::parse("Delay:250").unwrap(),
+            Some(Delay {
+                duration: Duration::from_millis(250)
+            })
+        );
+        assert_eq!(Delay::parse("MEAS:VOLT?").unwrap(), None);
+        assert!(Delay::parse("Delay:soon").is_err());
+    }
+
+    #[test]
+    fn test_pause_before() {
+        let delay = Some(Duration::from_millis(50));
+        assert_eq!(pause_before(None, "OUTP ON", delay), None);
+        assert_eq!(pause_before(Some("VOLT 5"), "OUTP ON", delay), delay);
+        assert_eq!(pause_before(Some("VOLT 5"), "OUTP ON", None), None);
+        assert_eq!(pause_before(Some("Delay:500"), "OUTP ON", delay), None);
+        assert_eq!(pause_before(Some("VOLT 5"), "Delay:500", delay), None);
+    }
+}
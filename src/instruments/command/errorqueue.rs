@@ -0,0 +1,47 @@
+use crate::error::ApplicationError;
+
+/**
+ * Query reading the oldest entry of the SCPI error queue.
+ */
+pub const ERROR_QUERY: &str = "SYST:ERR?";
+
+/**
+ * Most error queue entries read in one check, in case an instrument never reports no error.
+ */
+pub const MAX_ERRORS: usize = 32;
+
+/**
+ * Parses an error queue entry such as `-113,"Undefined header"` or `+0,"No error"`.
+ *
+ * # Arguments
+ * `response` - The response to SYST:ERR?.
+ *
+ * # Returns
+ * A Result containing None for no error, Some(entry) for an error, or an ApplicationError
+ * if the response is not an error queue entry.
+ */
+pub fn parse_error(response: &[u8]) -> Result<Option<String>, ApplicationError> {
+    let text = String::from_utf8_lossy(response);
+    let entry = text.trim();
+    let code = entry.split(',').next().unwrap_or("").trim();
+    let code: i32 = code
+        .parse()
+        .map_err(|_| ApplicationError::Command(format!("Invalid error queue entry: {}", entry)))?;
+    Ok((code != 0).then(|| entry.to_string()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_error() {
+        assert_eq!(parse_error(b"0,\"No error\"\n").unwrap(), None);
+        assert_eq!(parse_error(b"+0,\"No error\"\n").unwrap(), None);
+        assert_eq!(
+            parse_error(b"-113,\"Undefined header\"\n").unwrap(),
+            Some("-113,\"Undefined header\"".to_string())
+        );
+        assert!(parse_error(b"1.234\n").is_err());
+    }
+}
@@ -0,0 +1,94 @@
+use crate::error::ApplicationError;
+
+/**
+ * Enum representing the commands of Fluke 287 / 289 meters.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub enum FlukeCommand {
+    /// Reads the primary value, `QM`.
+    Measure,
+    /// Reads the primary and secondary display with function, range and modes, `QDDA`.
+    Display,
+    /// Reads the model, firmware version and serial number, `ID`.
+    Identify,
+    /// Reads the number of stored recordings, min max, peak and measurement sessions, `QSLS`.
+    Sessions,
+    /// Downloads a stored recording session, `QRSI <index>`.
+    Session(u16),
+}
+
+#[cfg(feature = "serial")]
+impl FlukeCommand {
+    /**
+     * Serializes the command into the command sent to the meter.
+     *
+     * # Returns
+     * The wire command without the CR terminator.
+     */
+    pub fn to_wire(&self) -> String {
+        match self {
+            FlukeCommand::Measure => "QM".into(),
+            FlukeCommand::Display => "QDDA".into(),
+            FlukeCommand::Identify => "ID".into(),
+            FlukeCommand::Sessions => "QSLS".into(),
+            FlukeCommand::Session(index) => format!("QRSI {}", index),
+        }
+    }
+
+    /**
+     * Checks if the response is binary. Binary responses cannot be split at CR and are read
+     * until the meter stops sending.
+     *
+     * # Returns
+     * A boolean indicating whether the response is binary.
+     */
+    pub fn is_binary(&self) -> bool {
+        matches!(self, FlukeCommand::Session(_))
+    }
+}
+
+impl TryFrom<&str> for FlukeCommand {
+    type Error = ApplicationError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        let (name, argument) = match value.split_once(':') {
+            Some((name, argument)) => (name, Some(argument)),
+            None => (value, None),
+        };
+        match (name, argument) {
+            ("Measure", None) => Ok(FlukeCommand::Measure),
+            ("Display", None) => Ok(FlukeCommand::Display),
+            ("Identify", None) => Ok(FlukeCommand::Identify),
+            ("Sessions", None) => Ok(FlukeCommand::Sessions),
+            ("Session", Some(argument)) => argument
+                .trim()
+                .parse()
+                .map(FlukeCommand::Session)
+                .map_err(|_| ApplicationError::Command(format!("Invalid session index in {}", value))),
+            _ => Err(ApplicationError::Command(format!("Unknown command: {}", value))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_try_from_command() {
+        assert_eq!(FlukeCommand::try_from("Measure").unwrap(), FlukeCommand::Measure);
+        assert_eq!(FlukeCommand::try_from("Session:3").unwrap(), FlukeCommand::Session(3));
+        assert!(FlukeCommand::try_from("Session:x").is_err());
+        assert!(FlukeCommand::try_from("Display:1").is_err());
+        assert!(FlukeCommand::try_from("QM").is_err());
+    }
+
+    #[cfg(feature = "serial")]
+    #[test]
+    fn test_to_wire() {
+        assert_eq!(FlukeCommand::Display.to_wire(), "QDDA");
+        assert_eq!(FlukeCommand::Session(3).to_wire(), "QRSI 3");
+        assert!(FlukeCommand::Session(3).is_binary());
+        assert!(!FlukeCommand::Measure.is_binary());
+    }
+}
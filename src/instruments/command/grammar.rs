@@ -0,0 +1,360 @@
+use crate::error::ApplicationError;
+
+/**
+ * Unit suffixes accepted after numbers, with the factor to the base unit. Suffixes are
+ * case-insensitive as in SCPI, so `M` is milli except in `MHZ`.
+ */
+const UNIT_SUFFIXES: [(&str, f64); 11] = [
+    ("MHZ", 1e6),
+    ("KHZ", 1e3),
+    ("HZ", 1.0),
+    ("MVPP", 1e-3),
+    ("VPP", 1.0),
+    ("MV", 1e-3),
+    ("V", 1.0),
+    ("MS", 1e-3),
+    ("US", 1e-6),
+    ("S", 1.0),
+    ("PCT", 1.0),
+];
+
+/**
+ * Keywords accepted in place of a number.
+ */
+const NUMBER_KEYWORDS: [&str; 3] = ["MINimum", "MAXimum", "DEFault"];
+
+/**
+ * A parameter of a SCPI command.
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Parameter {
+    /// A number in the range, optionally with a unit suffix, or MIN, MAX or DEF.
+    Number(f64, f64),
+    /// One of the mnemonics, e.g. `SINusoid`.
+    Choice(&'static [&'static str]),
+    /// ON, OFF, 1 or 0.
+    Boolean,
+}
+
+impl Parameter {
+    /**
+     * Checks a parameter value.
+     *
+     * # Arguments
+     * `command` - The full command, used in error messages.
+     * `value` - The value.
+     *
+     * # Returns
+     * A Result indicating success or an ApplicationError if the value is invalid or out of range.
+     */
+    fn check(&self, command: &str, value: &str) -> Result<(), ApplicationError> {
+        let value = value.trim();
+        match self {
+            Parameter::Number(lowest, highest) => {
+                if NUMBER_KEYWORDS.iter().any(|keyword| mnemonic_matches(keyword, value)) {
+                    return Ok(());
+                }
+                let upper = value.to_uppercase();
+                let (number, factor) = UNIT_SUFFIXES
+                    .iter()
+                    .find_map(|(suffix, factor)| upper.strip_suffix(suffix).map(|number| (number, *factor)))
+                    .unwrap_or((&upper, 1.0));
+                let number = number
+                    .trim()
+                    .parse::<f64>()
+                    .map_err(|_| ApplicationError::Command(format!("Invalid number {} in {}", value, command)))?
+                    * factor;
+                match (*lowest..=*highest).contains(&number) {
+                    true => Ok(()),
+                    false => Err(ApplicationError::Command(format!(
+                        "{} is out of range in {}, expected {} to {}",
+                        value, command, lowest, highest
+                    ))),
+                }
+            }
+            Parameter::Choice(choices) => match choices.iter().any(|choice| mnemonic_matches(choice, value)) {
+                true => Ok(()),
+                false => Err(ApplicationError::Command(format!(
+                    "Expected one of {} in {}, got {}",
+                    choices.join(", "),
+                    command,
+                    value
+                ))),
+            },
+            Parameter::Boolean => match value.to_uppercase().as_str() {
+                "ON" | "OFF" | "1" | "0" => Ok(()),
+                _ => Err(ApplicationError::Command(format!("Expected ON or OFF in {}, got {}", command, value))),
+            },
+        }
+    }
+}
+
+/**
+ * A command of a SCPI grammar. The header is written in SCPI notation: the short form of every
+ * mnemonic in upper case, optional nodes in brackets and `#` where a numeric suffix such as a
+ * channel number may follow, e.g. `[SOURce#:]FREQuency`.
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ScpiNode {
+    pub header: &'static str,
+    pub parameters: &'static [Parameter],
+    /**
+     * Number of leading parameters that must be given.
+     */
+    pub required: usize,
+    /**
+     * Whether the command can be sent with parameters, or without if it has none.
+     */
+    pub set: bool,
+    /**
+     * Whether the command has a query form ending with `?`.
+     */
+    pub query: bool,
+}
+
+impl ScpiNode {
+    /**
+     * Creates a setting that can be set and queried.
+     *
+     * # Arguments
+     * `header` - The header.
+     * `parameters` - The parameters, all required.
+     *
+     * # Returns
+     * A new ScpiNode instance.
+     */
+    pub const fn setting(header: &'static str, parameters: &'static [Parameter]) -> Self {
+        Self { header, parameters, required: parameters.len(), set: true, query: true }
+    }
+
+    /**
+     * Creates a command that cannot be queried.
+     *
+     * # Arguments
+     * `header` - The header.
+     * `parameters` - The parameters.
+     * `required` - Number of leading parameters that must be given.
+     *
+     * # Returns
+     * A new ScpiNode instance.
+     */
+    pub const fn command(header: &'static str, parameters: &'static [Parameter], required: usize) -> Self {
+        Self { header, parameters, required, set: true, query: false }
+    }
+
+    /**
+     * Creates a query without a set form.
+     *
+     * # Arguments
+     * `header` - The header, without `?`.
+     *
+     * # Returns
+     * A new ScpiNode instance.
+     */
+    pub const fn query(header: &'static str) -> Self {
+        Self { header, parameters: &[], required: 0, set: false, query: true }
+    }
+
+    /**
+     * Checks if the header matches the mnemonics of a command.
+     *
+     * # Arguments
+     * `mnemonics` - The mnemonics of the command header, e.g. `SOUR2`, `FREQ`.
+     *
+     * # Returns
+     * A boolean indicating whether the header matches.
+     */
+    fn matches(&self, mnemonics: &[&str]) -> bool {
+        let nodes: Vec<(bool, &str)> = self
+            .header
+            .split_inclusive(']')
+            .flat_map(|part| {
+                let optional = part.starts_with('[');
+                part.trim_matches(|c| c == '[' || c == ']')
+                    .split(':')
+                    .filter(|node| !node.is_empty())
+                    .map(move |node| (optional, node))
+            })
+            .collect();
+        nodes_match(&nodes, mnemonics)
+    }
+}
+
+/**
+ * Matches header nodes against mnemonics, skipping optional nodes where needed.
+ *
+ * # Arguments
+ * `nodes` - The nodes of the header, with whether they are optional.
+ * `mnemonics` - The mnemonics of the command.
+ *
+ * # Returns
+ * A boolean indicating whether all mnemonics match.
+ */
+fn nodes_match(nodes: &[(bool, &str)], mnemonics: &[&str]) -> bool {
+    match (nodes.first(), mnemonics.first()) {
+        (None, None) => true,
+        (None, Some(_)) => false,
+        (Some((optional, node)), mnemonic) => {
+            let matched = mnemonic.is_some_and(|mnemonic| {
+                let (node, suffix) = match node.strip_suffix('#') {
+                    Some(node) => (node, true),
+                    None => (*node, false),
+                };
+                let name = match suffix {
+                    true => mnemonic.trim_end_matches(|c: char| c.is_ascii_digit()),
+                    false => mnemonic,
+                };
+                mnemonic_matches(node, name) && nodes_match(&nodes[1..], &mnemonics[1..])
+            });
+            matched || (*optional && nodes_match(&nodes[1..], mnemonics))
+        }
+    }
+}
+
+/**
+ * Checks if a word is the short or long form of a mnemonic, case-insensitively.
+ *
+ * # Arguments
+ * `mnemonic` - The mnemonic in SCPI notation, e.g. `FREQuency`.
+ * `word` - The word, e.g. `freq`.
+ *
+ * # Returns
+ * A boolean indicating whether the word is the mnemonic.
+ */
+fn mnemonic_matches(mnemonic: &str, word: &str) -> bool {
+    let short: String = mnemonic.chars().take_while(|c| !c.is_ascii_lowercase()).collect();
+    word.eq_ignore_ascii_case(&short) || word.eq_ignore_ascii_case(mnemonic)
+}
+
+/**
+ * The SCPI commands an instrument accepts, so typos such as `APLY:SIN` are rejected before
+ * anything is sent. IEEE 488.2 common commands such as `*RST` are always accepted.
+ */
+#[derive(Debug)]
+pub struct ScpiGrammar {
+    /**
+     * Name of the instrument, used in error messages.
+     */
+    pub instrument: &'static str,
+    pub nodes: &'static [ScpiNode],
+}
+
+impl ScpiGrammar {
+    /**
+     * Checks a command, which may consist of several commands separated by `;`. Headers after
+     * `;` without leading `:` are relative to the path of the previous header, as in SCPI.
+     *
+     * # Arguments
+     * `command` - The command.
+     *
+     * # Returns
+     * A Result indicating success or an ApplicationError describing the first problem.
+     */
+    pub fn check(&self, command: &str) -> Result<(), ApplicationError> {
+        let mut path: Vec<&str> = Vec::new();
+        for unit in command.split(';').map(str::trim).filter(|unit| !unit.is_empty()) {
+            if unit.starts_with('*') {
+                continue;
+            }
+            let (header, parameters) = unit.split_once(char::is_whitespace).unwrap_or((unit, ""));
+            let (header, query) = match header.strip_suffix('?') {
+                Some(header) => (header, true),
+                None => (header, false),
+            };
+            let mut mnemonics = match header.starts_with(':') {
+                true => Vec::new(),
+                false => path.clone(),
+            };
+            mnemonics.extend(header.trim_start_matches(':').split(':'));
+            let node = self
+                .nodes
+                .iter()
+                .find(|node| node.matches(&mnemonics))
+                .ok_or_else(|| ApplicationError::Command(format!("Unknown command {} for the {}", unit, self.instrument)))?;
+            let parameters: Vec<&str> = match parameters.trim() {
+                "" => Vec::new(),
+                parameters => parameters.split(',').collect(),
+            };
+            match query {
+                true if !node.query => return Err(ApplicationError::Command(format!("{} is not a query", unit))),
+                true if parameters.len() > 1 => {
+                    return Err(ApplicationError::Command(format!("Expected at most one parameter in {}", unit)))
+                }
+                true => {}
+                false if !node.set => return Err(ApplicationError::Command(format!("{} is a query, expected {}?", unit, header))),
+                false if parameters.len() < node.required || parameters.len() > node.parameters.len() => {
+                    return Err(ApplicationError::Command(format!(
+                        "Expected {} to {} parameters in {}, got {}",
+                        node.required,
+                        node.parameters.len(),
+                        unit,
+                        parameters.len()
+                    )))
+                }
+                false => {
+                    for (parameter, value) in node.parameters.iter().zip(&parameters) {
+                        parameter.check(unit, value)?;
+                    }
+                }
+            }
+            mnemonics.pop();
+            path = mnemonics;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const GRAMMAR: ScpiGrammar = ScpiGrammar {
+        instrument: "test generator",
+        nodes: &[
+            ScpiNode::command("[SOURce#:]APPLy:SINusoid", &[Parameter::Number(1.0, 1000.0), Parameter::Number(0.0, 10.0)], 0),
+            ScpiNode::setting("[SOURce#:]FREQuency", &[Parameter::Number(1.0, 1000.0)]),
+            ScpiNode::setting("[SOURce#:]FREQuency:STARt", &[Parameter::Number(1.0, 1000.0)]),
+            ScpiNode::setting("[SOURce#:]FREQuency:STOP", &[Parameter::Number(1.0, 1000.0)]),
+            ScpiNode::setting("OUTPut#", &[Parameter::Boolean]),
+            ScpiNode::setting("TRIGger#:SOURce", &[Parameter::Choice(&["IMMediate", "EXTernal"])]),
+            ScpiNode::query("SYSTem:ERRor"),
+        ],
+    };
+
+    #[test]
+    fn test_headers() {
+        assert!(GRAMMAR.check("APPL:SIN 100,1").is_ok());
+        assert!(GRAMMAR.check("source2:apply:sinusoid").is_ok());
+        assert!(GRAMMAR.check(":SOUR:FREQ 1e2").is_ok());
+        assert!(GRAMMAR.check("OUTP2 ON").is_ok());
+        assert!(GRAMMAR.check("OUTP?").is_ok());
+        assert!(GRAMMAR.check("*RST;*IDN?").is_ok());
+        assert!(GRAMMAR.check("APLY:SIN 100").is_err());
+        assert!(GRAMMAR.check("FREQU 100").is_err());
+        assert!(GRAMMAR.check("SOUR:SOUR:FREQ 100").is_err());
+    }
+
+    #[test]
+    fn test_parameters() {
+        assert!(GRAMMAR.check("FREQ 0.5kHz").is_ok());
+        assert!(GRAMMAR.check("FREQ MAX").is_ok());
+        assert!(GRAMMAR.check("FREQ? MIN").is_ok());
+        assert!(GRAMMAR.check("TRIG2:SOUR ext").is_ok());
+        assert!(GRAMMAR.check("FREQ 2kHz").is_err());
+        assert!(GRAMMAR.check("FREQ abc").is_err());
+        assert!(GRAMMAR.check("FREQ").is_err());
+        assert!(GRAMMAR.check("APPL:SIN 100,1,0").is_err());
+        assert!(GRAMMAR.check("OUTP MAYBE").is_err());
+        assert!(GRAMMAR.check("TRIG:SOUR BUS").is_err());
+    }
+
+    #[test]
+    fn test_forms_and_paths() {
+        assert!(GRAMMAR.check("SYST:ERR?").is_ok());
+        assert!(GRAMMAR.check("SYST:ERR").is_err());
+        assert!(GRAMMAR.check("APPL:SIN?").is_err());
+        assert!(GRAMMAR.check("FREQ:STAR 10;STOP 100").is_ok());
+        assert!(GRAMMAR.check("FREQ:STAR 10;:FREQ:STOP 100").is_ok());
+        assert!(GRAMMAR.check("FREQ:STAR 10;:STOP 100").is_err());
+    }
+}
@@ -0,0 +1,85 @@
+#[cfg(any(feature = "usb", feature = "hid"))]
+use std::time::Duration;
+
+use crate::error::ApplicationError;
+
+/**
+ * Command sounding the beeper of SCPI instruments.
+ */
+#[cfg(feature = "usb")]
+pub const SCPI_BEEP: &str = "SYST:BEEP";
+
+/**
+ * Time between signals of an indication.
+ */
+#[cfg(any(feature = "usb", feature = "hid"))]
+pub const INDICATE_INTERVAL: Duration = Duration::from_millis(300);
+
+/**
+ * Enum representing device independent status indications, e.g. `Indicate:Fail`. Devices
+ * signal them with their beeper (SCPI `SYST:BEEP`) or by blinking their backlight (Uni-T `Lamp`).
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Indicate {
+    Pass,
+    Fail,
+    Attention,
+}
+
+impl Indicate {
+    /**
+     * Parses an Indicate command.
+     *
+     * # Arguments
+     * `command` - The command string.
+     *
+     * # Returns
+     * A Result containing Some(Indicate) for Indicate commands, None for other commands,
+     * or an ApplicationError if the indication is unknown.
+     */
+    pub fn parse(command: &str) -> Result<Option<Self>, ApplicationError> {
+        let Some(indication) = command.strip_prefix("Indicate:") else {
+            return Ok(None);
+        };
+        match indication.trim() {
+            "Pass" => Ok(Some(Indicate::Pass)),
+            "Fail" => Ok(Some(Indicate::Fail)),
+            "Attention" => Ok(Some(Indicate::Attention)),
+            _ => Err(ApplicationError::Command(format!("Unknown indication: {}", indication))),
+        }
+    }
+
+    /**
+     * Returns the number of beeps or blinks signalling the indication.
+     *
+     * # Returns
+     * The number of signals.
+     */
+    #[cfg(any(feature = "usb", feature = "hid"))]
+    pub fn signals(&self) -> u32 {
+        match self {
+            Indicate::Pass => 1,
+            Indicate::Attention => 2,
+            Indicate::Fail => 3,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_indicate() {
+        assert_eq!(Indicate::parse("Indicate:Fail").unwrap(), Some(Indicate::Fail));
+        assert_eq!(Indicate::parse("Lamp").unwrap(), None);
+        assert!(Indicate::parse("Indicate:Maybe").is_err());
+    }
+
+    #[test]
+    #[cfg(any(feature = "usb", feature = "hid"))]
+    fn test_signals() {
+        assert_eq!(Indicate::Pass.signals(), 1);
+        assert_eq!(Indicate::Fail.signals(), 3);
+    }
+}
@@ -0,0 +1,292 @@
+use crate::error::ApplicationError;
+
+/**
+ * Enum representing the measurement functions of Keysight 34401A / 34461A bench multimeters.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DmmFunction {
+    VoltageDc,
+    VoltageAc,
+    CurrentDc,
+    CurrentAc,
+    Resistance,
+    Resistance4W,
+    Frequency,
+    Period,
+    Diode,
+    Continuity,
+    Capacitance,
+    Temperature,
+}
+
+// Command name, SCPI function, FUNC? response, unit and whether range and resolution can be given
+const FUNCTIONS: [(DmmFunction, &str, &str, &str, &str, bool); 12] = [
+    (DmmFunction::VoltageDc, "VoltageDc", "VOLT:DC", "VOLT", "V", true),
+    (DmmFunction::VoltageAc, "VoltageAc", "VOLT:AC", "VOLT:AC", "V", true),
+    (DmmFunction::CurrentDc, "CurrentDc", "CURR:DC", "CURR", "A", true),
+    (DmmFunction::CurrentAc, "CurrentAc", "CURR:AC", "CURR:AC", "A", true),
+    (DmmFunction::Resistance, "Resistance", "RES", "RES", "Ω", true),
+    (DmmFunction::Resistance4W, "Resistance4W", "FRES", "FRES", "Ω", true),
+    (DmmFunction::Frequency, "Frequency", "FREQ", "FREQ", "Hz", true),
+    (DmmFunction::Period, "Period", "PER", "PER", "s", true),
+    (DmmFunction::Diode, "Diode", "DIOD", "DIOD", "V", false),
+    (DmmFunction::Continuity, "Continuity", "CONT", "CONT", "Ω", false),
+    (DmmFunction::Capacitance, "Capacitance", "CAP", "CAP", "F", true),
+    (DmmFunction::Temperature, "Temperature", "TEMP", "TEMP", "°C", false),
+];
+
+#[cfg(any(feature = "serial", feature = "usb", test))]
+impl DmmFunction {
+    /**
+     * Gets the function entry of the table.
+     *
+     * # Returns
+     * The command name, SCPI function, FUNC? response, unit and whether it takes a range.
+     */
+    fn entry(&self) -> (&'static str, &'static str, &'static str, &'static str, bool) {
+        FUNCTIONS
+            .iter()
+            .find(|(function, ..)| function == self)
+            .map(|(_, name, scpi, query, unit, ranged)| (*name, *scpi, *query, *unit, *ranged))
+            .unwrap_or_default()
+    }
+
+    /**
+     * Returns the command name of the function, e.g. `VoltageDc`.
+     *
+     * # Returns
+     * The name.
+     */
+    pub fn name(&self) -> &'static str {
+        self.entry().0
+    }
+
+    /**
+     * Returns the SCPI function following `CONF:` and `MEAS:`, e.g. `VOLT:DC`.
+     *
+     * # Returns
+     * The SCPI function.
+     */
+    #[cfg(any(feature = "serial", feature = "usb"))]
+    pub fn scpi(&self) -> &'static str {
+        self.entry().1
+    }
+
+    /**
+     * Returns the unit of the values returned by `READ?` and `MEAS?`.
+     *
+     * # Returns
+     * The unit.
+     */
+    pub fn unit(&self) -> &'static str {
+        self.entry().3
+    }
+
+    /**
+     * Returns whether the function measures an AC quantity.
+     *
+     * # Returns
+     * True for AC voltage and current.
+     */
+    pub fn is_ac(&self) -> bool {
+        matches!(self, DmmFunction::VoltageAc | DmmFunction::CurrentAc)
+    }
+
+    /**
+     * Parses the response to `FUNC?`, e.g. `"VOLT:AC"`.
+     *
+     * # Arguments
+     * `response` - The raw response.
+     *
+     * # Returns
+     * A Result containing the function or an ApplicationError for unknown functions.
+     */
+    #[cfg(any(feature = "serial", feature = "usb"))]
+    pub fn from_response(response: &[u8]) -> Result<Self, ApplicationError> {
+        let text = String::from_utf8_lossy(response);
+        let text = text.trim().trim_matches('"');
+        FUNCTIONS
+            .iter()
+            .find(|(_, _, scpi, query, ..)| query.eq_ignore_ascii_case(text) || scpi.eq_ignore_ascii_case(text))
+            .map(|(function, ..)| *function)
+            .ok_or_else(|| ApplicationError::Command(format!("Unknown Keysight multimeter function: {}", text)))
+    }
+}
+
+/**
+ * Enum representing the commands of Keysight 34401A / 34461A bench multimeters.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub enum KeysightDmmCommand {
+    /// Selects the function with `CONF:`, with the range and resolution if given.
+    Configure(DmmFunction, Option<String>),
+    /// Selects the function and reads one value with `MEAS:...?`, with the range and resolution if given.
+    Measure(DmmFunction, Option<String>),
+    /// Reads the values of the selected function with `READ?`.
+    Read,
+    /// Reads the identification string.
+    Identify,
+}
+
+/**
+ * Parses a range or resolution, a number or one of `Auto`, `Min`, `Max` and `Def`.
+ *
+ * # Arguments
+ * `value` - The value of the typed command.
+ *
+ * # Returns
+ * The SCPI parameter, or None if the value is invalid.
+ */
+fn parameter(value: &str) -> Option<String> {
+    match value {
+        "Auto" | "Min" | "Max" | "Def" => Some(value.to_ascii_uppercase()),
+        _ => value.parse::<f64>().ok().filter(|value| *value > 0.0).map(|value| value.to_string()),
+    }
+}
+
+impl KeysightDmmCommand {
+    /**
+     * Parses the function and the optional range and resolution of `Configure` and `Measure`,
+     * e.g. `VoltageDc:10:0.0001`.
+     *
+     * # Arguments
+     * `command` - The whole command, for error messages.
+     * `argument` - The argument following the command name.
+     *
+     * # Returns
+     * A Result containing the function and the SCPI parameters or an ApplicationError.
+     */
+    fn function(command: &str, argument: &str) -> Result<(DmmFunction, Option<String>), ApplicationError> {
+        let mut fields = argument.split(':');
+        let name = fields.next().unwrap_or_default();
+        let (function, ranged) = FUNCTIONS
+            .iter()
+            .find(|(_, function_name, ..)| *function_name == name)
+            .map(|(function, .., ranged)| (*function, *ranged))
+            .ok_or_else(|| ApplicationError::Command(format!("Unknown function in {}", command)))?;
+        let parameters = fields
+            .map(|field| parameter(field).ok_or_else(|| ApplicationError::Command(format!("Invalid range or resolution in {}", command))))
+            .collect::<Result<Vec<String>, ApplicationError>>()?;
+        match (parameters.len(), ranged) {
+            (0, _) => Ok((function, None)),
+            (1 | 2, true) => Ok((function, Some(parameters.join(",")))),
+            (_, false) => Err(ApplicationError::Command(format!("{} takes no range in {}", name, command))),
+            _ => Err(ApplicationError::Command(format!("Too many parameters in {}", command))),
+        }
+    }
+
+    /**
+     * Serializes the command into the SCPI command sent to the meter. Commands containing `?`
+     * are queries with a response.
+     *
+     * # Returns
+     * The SCPI command.
+     */
+    #[cfg(any(feature = "serial", feature = "usb"))]
+    pub fn to_wire(&self) -> String {
+        match self {
+            KeysightDmmCommand::Configure(function, None) => format!("CONF:{}", function.scpi()),
+            KeysightDmmCommand::Configure(function, Some(parameters)) => format!("CONF:{} {}", function.scpi(), parameters),
+            KeysightDmmCommand::Measure(function, None) => format!("MEAS:{}?", function.scpi()),
+            KeysightDmmCommand::Measure(function, Some(parameters)) => format!("MEAS:{}? {}", function.scpi(), parameters),
+            KeysightDmmCommand::Read => "READ?".into(),
+            KeysightDmmCommand::Identify => "*IDN?".into(),
+        }
+    }
+
+    /**
+     * Parses a typed command. Other commands are SCPI sent as is.
+     *
+     * # Arguments
+     * `command` - The command string, e.g. `Measure:VoltageDc:10` or `Read`.
+     *
+     * # Returns
+     * A Result containing Some(KeysightDmmCommand), None if it is not a typed command, or an
+     * ApplicationError if a typed command is invalid.
+     */
+    pub fn parse(command: &str) -> Result<Option<Self>, ApplicationError> {
+        let (name, argument) = match command.split_once(':') {
+            Some((name, argument)) => (name, Some(argument.trim())),
+            None => (command.trim(), None),
+        };
+        match (name, argument) {
+            ("Configure", Some(argument)) => {
+                let (function, parameters) = Self::function(command, argument)?;
+                Ok(Some(KeysightDmmCommand::Configure(function, parameters)))
+            }
+            ("Measure", Some(argument)) => {
+                let (function, parameters) = Self::function(command, argument)?;
+                Ok(Some(KeysightDmmCommand::Measure(function, parameters)))
+            }
+            ("Read", None) => Ok(Some(KeysightDmmCommand::Read)),
+            ("Identify", None) => Ok(Some(KeysightDmmCommand::Identify)),
+            ("Configure" | "Measure" | "Read" | "Identify", _) => Err(ApplicationError::Command(format!("Invalid command: {}", command))),
+            _ => Ok(None),
+        }
+    }
+
+    /**
+     * Checks a command: typed commands are parsed, other commands are SCPI sent as is.
+     *
+     * # Arguments
+     * `command` - The command to check.
+     *
+     * # Returns
+     * A Result indicating success or an ApplicationError describing the invalid typed command.
+     */
+    pub fn check(command: &str) -> Result<(), ApplicationError> {
+        Self::parse(command).map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_command() {
+        assert_eq!(
+            KeysightDmmCommand::parse("Configure:VoltageDc").unwrap(),
+            Some(KeysightDmmCommand::Configure(DmmFunction::VoltageDc, None))
+        );
+        assert_eq!(
+            KeysightDmmCommand::parse("Measure:Resistance4W:1000:0.001").unwrap(),
+            Some(KeysightDmmCommand::Measure(DmmFunction::Resistance4W, Some("1000,0.001".into())))
+        );
+        assert_eq!(
+            KeysightDmmCommand::parse("Configure:CurrentAc:Auto").unwrap(),
+            Some(KeysightDmmCommand::Configure(DmmFunction::CurrentAc, Some("AUTO".into())))
+        );
+        assert_eq!(KeysightDmmCommand::parse("Read").unwrap(), Some(KeysightDmmCommand::Read));
+        assert_eq!(KeysightDmmCommand::parse("MEAS:VOLT:DC?").unwrap(), None);
+        assert_eq!(KeysightDmmCommand::parse("SAMP:COUN 5").unwrap(), None);
+        assert!(KeysightDmmCommand::parse("Configure:Volts").is_err());
+        assert!(KeysightDmmCommand::parse("Configure:Diode:10").is_err());
+        assert!(KeysightDmmCommand::parse("Measure:VoltageDc:-1").is_err());
+        assert!(KeysightDmmCommand::parse("Measure:VoltageDc:10:0.1:2").is_err());
+        assert!(KeysightDmmCommand::parse("Read:1").is_err());
+    }
+
+    #[cfg(any(feature = "serial", feature = "usb"))]
+    #[test]
+    fn test_to_wire() {
+        assert_eq!(KeysightDmmCommand::Configure(DmmFunction::CurrentDc, None).to_wire(), "CONF:CURR:DC");
+        assert_eq!(
+            KeysightDmmCommand::Configure(DmmFunction::VoltageDc, Some("10,0.0001".into())).to_wire(),
+            "CONF:VOLT:DC 10,0.0001"
+        );
+        assert_eq!(KeysightDmmCommand::Measure(DmmFunction::Frequency, None).to_wire(), "MEAS:FREQ?");
+        assert_eq!(KeysightDmmCommand::Measure(DmmFunction::Resistance, Some("MAX".into())).to_wire(), "MEAS:RES? MAX");
+        assert_eq!(KeysightDmmCommand::Read.to_wire(), "READ?");
+    }
+
+    #[cfg(any(feature = "serial", feature = "usb"))]
+    #[test]
+    fn test_function_from_response() {
+        assert_eq!(DmmFunction::from_response(b"\"VOLT:AC\"\r\n").unwrap(), DmmFunction::VoltageAc);
+        assert_eq!(DmmFunction::from_response(b"\"VOLT\"\n").unwrap(), DmmFunction::VoltageDc);
+        assert_eq!(DmmFunction::from_response(b"\"FRES\"\n").unwrap(), DmmFunction::Resistance4W);
+        assert_eq!(DmmFunction::Resistance4W.unit(), "Ω");
+        assert!(DmmFunction::from_response(b"\"VOLT:RAT\"").is_err());
+    }
+}
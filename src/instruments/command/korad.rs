@@ -0,0 +1,164 @@
+use crate::{
+    error::ApplicationError,
+    instruments::command::readback::{Expected, Readback},
+};
+
+/**
+ * Enum representing the commands of Korad KA3005P / Tenma 72-2540 style power supplies.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub enum KoradCommand {
+    /// Sets the output voltage in volts.
+    Voltage(f64),
+    /// Sets the current limit in amperes.
+    Current(f64),
+    /// Switches the output on or off.
+    Output(bool),
+    /// Enables or disables over current protection.
+    Ocp(bool),
+    /// Enables or disables over voltage protection.
+    Ovp(bool),
+    /// Reads the actual output voltage, current and status.
+    Measure,
+    /// Reads the voltage and current setpoints.
+    Setpoints,
+    /// Reads the status byte.
+    Status,
+    /// Reads the identification string.
+    Identify,
+}
+
+impl KoradCommand {
+    /**
+     * Serializes the command into the commands sent to the power supply. Commands ending
+     * with `?` are queries with a response.
+     *
+     * # Returns
+     * The wire commands in order.
+     */
+    #[cfg(feature = "serial")]
+    pub fn to_wire(&self) -> Vec<String> {
+        let switch = |on: &bool| if *on { 1 } else { 0 };
+        match self {
+            KoradCommand::Voltage(volts) => vec![format!("VSET1:{:.2}", volts)],
+            KoradCommand::Current(amperes) => vec![format!("ISET1:{:.3}", amperes)],
+            KoradCommand::Output(on) => vec![format!("OUT{}", switch(on))],
+            KoradCommand::Ocp(on) => vec![format!("OCP{}", switch(on))],
+            KoradCommand::Ovp(on) => vec![format!("OVP{}", switch(on))],
+            KoradCommand::Measure => vec!["VOUT1?".into(), "IOUT1?".into(), "STATUS?".into()],
+            KoradCommand::Setpoints => vec!["VSET1?".into(), "ISET1?".into()],
+            KoradCommand::Status => vec!["STATUS?".into()],
+            KoradCommand::Identify => vec!["*IDN?".into()],
+        }
+    }
+
+    /**
+     * Gets the query reading back a setpoint or the output state. Setpoints are compared at
+     * the resolution they are sent with.
+     *
+     * # Returns
+     * The readback, or None if the command is not a setting or cannot be read back.
+     */
+    pub fn readback(&self) -> Option<Readback> {
+        match self {
+            KoradCommand::Voltage(volts) => Some(Readback::field(
+                "Setpoints",
+                "voltage",
+                Expected::Number((volts * 100.0).round() / 100.0, 0.01),
+            )),
+            KoradCommand::Current(amperes) => Some(Readback::field(
+                "Setpoints",
+                "current",
+                Expected::Number((amperes * 1000.0).round() / 1000.0, 0.001),
+            )),
+            KoradCommand::Output(on) => Some(Readback::field("Status", "output", Expected::Switch(*on))),
+            _ => None,
+        }
+    }
+}
+
+/**
+ * Parses an On or Off argument.
+ *
+ * # Arguments
+ * `command` - The full command, used in error messages.
+ * `value` - The argument.
+ *
+ * # Returns
+ * A Result containing true for On, false for Off or an ApplicationError.
+ */
+fn parse_switch(command: &str, value: &str) -> Result<bool, ApplicationError> {
+    match value.trim() {
+        "On" => Ok(true),
+        "Off" => Ok(false),
+        _ => Err(ApplicationError::Command(format!("Expected On or Off, got {}", command))),
+    }
+}
+
+/**
+ * Parses a non negative number argument.
+ *
+ * # Arguments
+ * `command` - The full command, used in error messages.
+ * `value` - The argument.
+ *
+ * # Returns
+ * A Result containing the number or an ApplicationError.
+ */
+fn parse_number(command: &str, value: &str) -> Result<f64, ApplicationError> {
+    value
+        .trim()
+        .parse::<f64>()
+        .ok()
+        .filter(|number| *number >= 0.0)
+        .ok_or_else(|| ApplicationError::Command(format!("Invalid value in {}", command)))
+}
+
+impl TryFrom<&str> for KoradCommand {
+    type Error = ApplicationError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        let (name, argument) = match value.split_once(':') {
+            Some((name, argument)) => (name, Some(argument)),
+            None => (value, None),
+        };
+        match (name, argument) {
+            ("Voltage", Some(argument)) => Ok(KoradCommand::Voltage(parse_number(value, argument)?)),
+            ("Current", Some(argument)) => Ok(KoradCommand::Current(parse_number(value, argument)?)),
+            ("Output", Some(argument)) => Ok(KoradCommand::Output(parse_switch(value, argument)?)),
+            ("Ocp", Some(argument)) => Ok(KoradCommand::Ocp(parse_switch(value, argument)?)),
+            ("Ovp", Some(argument)) => Ok(KoradCommand::Ovp(parse_switch(value, argument)?)),
+            ("Measure", None) => Ok(KoradCommand::Measure),
+            ("Setpoints", None) => Ok(KoradCommand::Setpoints),
+            ("Status", None) => Ok(KoradCommand::Status),
+            ("Identify", None) => Ok(KoradCommand::Identify),
+            _ => Err(ApplicationError::Command(format!("Unknown command: {}", value))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_try_from_command() {
+        assert_eq!(KoradCommand::try_from("Voltage:5").unwrap(), KoradCommand::Voltage(5.0));
+        assert_eq!(KoradCommand::try_from("Output:Off").unwrap(), KoradCommand::Output(false));
+        assert_eq!(KoradCommand::try_from("Measure").unwrap(), KoradCommand::Measure);
+        assert!(KoradCommand::try_from("Voltage:-1").is_err());
+        assert!(KoradCommand::try_from("Output:1").is_err());
+        assert!(KoradCommand::try_from("Measure:1").is_err());
+        assert!(KoradCommand::try_from("VSET1:5").is_err());
+    }
+
+    #[cfg(feature = "serial")]
+    #[test]
+    fn test_to_wire() {
+        assert_eq!(KoradCommand::Voltage(5.0).to_wire(), vec!["VSET1:5.00"]);
+        assert_eq!(KoradCommand::Current(0.25).to_wire(), vec!["ISET1:0.250"]);
+        assert_eq!(KoradCommand::Output(true).to_wire(), vec!["OUT1"]);
+        assert_eq!(KoradCommand::Ovp(false).to_wire(), vec!["OVP0"]);
+        assert_eq!(KoradCommand::Measure.to_wire(), vec!["VOUT1?", "IOUT1?", "STATUS?"]);
+    }
+}
@@ -0,0 +1,251 @@
+use crate::error::ApplicationError;
+
+/**
+ * Enum representing the quantities a limit applies to.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Limited {
+    Voltage,
+    Current,
+}
+
+/**
+ * Hard limits on the voltage and current set on a device. Setpoints in typed commands
+ * (including generator amplitudes), Korad commands and SCPI `VOLT`, `CURR` and `APPL` commands are checked before they are sent.
+ */
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Limits {
+    pub max_voltage: Option<f64>,
+    pub max_current: Option<f64>,
+}
+
+impl Limits {
+    /**
+     * Checks if no limit is set.
+     *
+     * # Returns
+     * A boolean indicating whether no limit is set.
+     */
+    pub fn is_empty(&self) -> bool {
+        self.max_voltage.is_none() && self.max_current.is_none()
+    }
+
+    /**
+     * Checks the setpoints of a command against the limits.
+     *
+     * # Arguments
+     * `command` - The command string.
+     *
+     * # Returns
+     * A Result indicating success or an ApplicationError if a setpoint exceeds a limit or
+     * cannot be checked.
+     */
+    pub fn check(&self, command: &str) -> Result<(), ApplicationError> {
+        for (quantity, value) in setpoints(command) {
+            let (limit, unit) = match quantity {
+                Limited::Voltage => (self.max_voltage, "V"),
+                Limited::Current => (self.max_current, "A"),
+            };
+            let Some(limit) = limit else {
+                continue;
+            };
+            let value = match parse_value(value) {
+                Some(Some(value)) => value,
+                Some(None) => continue,
+                None => {
+                    return Err(ApplicationError::Command(format!(
+                        "Cannot check {} against the {:?} limit of {} {}",
+                        command, quantity, limit, unit
+                    )))
+                }
+            };
+            if value > limit {
+                return Err(ApplicationError::Command(format!(
+                    "{} sets {:?} to {} {}, above the limit of {} {}",
+                    command, quantity, value, unit, limit, unit
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+/**
+ * Finds the last voltage setpoint of a command.
+ *
+ * # Arguments
+ * `command` - The command string.
+ *
+ * # Returns
+ * The voltage in volts, or None if the command sets no numeric voltage.
+ */
+pub fn voltage_setpoint(command: &str) -> Option<f64> {
+    setpoints(command)
+        .into_iter()
+        .rev()
+        .filter(|(quantity, _)| *quantity == Limited::Voltage)
+        .find_map(|(_, value)| parse_value(value).flatten())
+}
+
+/**
+ * Parses a setpoint value with an optional unit, e.g. `5`, `500mV` or `1.5 A`.
+ *
+ * # Arguments
+ * `value` - The value.
+ *
+ * # Returns
+ * Some(Some(value)) for numbers, Some(None) for MIN and DEF, which never exceed a limit,
+ * or None if the value cannot be checked, e.g. MAX.
+ */
+fn parse_value(value: &str) -> Option<Option<f64>> {
+    let value = value.trim().to_uppercase();
+    if matches!(value.as_str(), "MIN" | "MINIMUM" | "DEF" | "DEFAULT") {
+        return Some(None);
+    }
+    let (number, factor) = if let Some(number) = value.strip_suffix("MV").or(value.strip_suffix("MA")) {
+        (number, 1e-3)
+    } else {
+        (value.trim_end_matches(['V', 'A']), 1.0)
+    };
+    number.trim().parse::<f64>().ok().map(|number| Some(number * factor))
+}
+
+/**
+ * Finds the voltage and current setpoints in a command. SCPI commands separated by `;` are
+ * checked separately.
+ *
+ * # Arguments
+ * `command` - The command string.
+ *
+ * # Returns
+ * The quantities and values set by the command.
+ */
+fn setpoints(command: &str) -> Vec<(Limited, &str)> {
+    if let Some(value) = command.strip_prefix("Voltage:") {
+        return vec![(Limited::Voltage, value)];
+    }
+    if let Some(value) = command.strip_prefix("Current:") {
+        return vec![(Limited::Current, value)];
+    }
+    // Generator amplitude of any channel, sent as SCPI VOLT
+    if let Some((_, value)) = command
+        .split_once(':')
+        .filter(|(name, _)| name.strip_prefix("Amplitude").is_some_and(|channel| channel.chars().all(|c| c.is_ascii_digit())))
+    {
+        return vec![(Limited::Voltage, value.trim().trim_end_matches("pp"))];
+    }
+    let command = command.strip_prefix("Raw:").unwrap_or(command);
+    command.split(';').flat_map(scpi_setpoints).collect()
+}
+
+/**
+ * Finds the voltage and current setpoints in a single SCPI or Korad command.
+ *
+ * # Arguments
+ * `command` - The command string.
+ *
+ * # Returns
+ * The quantities and values set by the command.
+ */
+fn scpi_setpoints(command: &str) -> Vec<(Limited, &str)> {
+    let command = command.trim();
+    let upper = command.to_ascii_uppercase();
+    // Korad: VSET1:5.00, ISET1:1.000
+    for (prefix, quantity) in [("VSET", Limited::Voltage), ("ISET", Limited::Current)] {
+        if let Some(rest) = upper.strip_prefix(prefix) {
+            return match rest.find(':') {
+                Some(index) if !rest.ends_with('?') => {
+                    vec![(quantity, &command[prefix.len() + index + 1..])]
+                }
+                _ => Vec::new(),
+            };
+        }
+    }
+    let (header, arguments) = match command.split_once(char::is_whitespace) {
+        Some((header, arguments)) => (header.to_uppercase(), arguments),
+        None => return Vec::new(),
+    };
+    let mut nodes: Vec<&str> = header.trim_start_matches(':').split(':').collect();
+    if nodes.first().is_some_and(|node| node.starts_with("SOUR")) {
+        nodes.remove(0);
+    }
+    let level_nodes = |nodes: &[&str]| {
+        nodes.iter().all(|node| {
+            matches!(*node, "LEV" | "LEVEL" | "IMM" | "IMMEDIATE" | "AMPL" | "AMPLITUDE")
+        })
+    };
+    match nodes.split_first() {
+        Some((&"VOLT" | &"VOLTAGE", rest)) if level_nodes(rest) => vec![(Limited::Voltage, arguments)],
+        Some((&"CURR" | &"CURRENT", rest)) if level_nodes(rest) => vec![(Limited::Current, arguments)],
+        // Power supply APPLy [<channel>,]<voltage>,<current>; APPL:<waveform> is a generator command
+        Some((&"APPL" | &"APPLY", [])) => {
+            let mut values: Vec<&str> = arguments.split(',').map(|value| value.trim()).collect();
+            if values.first().is_some_and(|value| value.parse::<f64>().is_err() && parse_value(value).is_none()) {
+                values.remove(0);
+            }
+            [Limited::Voltage, Limited::Current].into_iter().zip(values).collect()
+        }
+        _ => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn limits() -> Limits {
+        Limits {
+            max_voltage: Some(6.0),
+            max_current: Some(1.0),
+        }
+    }
+
+    #[test]
+    fn test_typed_and_korad_commands() {
+        assert!(limits().check("Voltage:5.0").is_ok());
+        assert!(limits().check("Voltage:50").is_err());
+        assert!(limits().check("Current:1.5").is_err());
+        assert!(limits().check("VSET1:50.00").is_err());
+        assert!(limits().check("ISET1:0.500").is_ok());
+        assert!(limits().check("VSET1?").is_ok());
+        assert!(limits().check("Measure").is_ok());
+        assert!(limits().check("Amplitude:5Vpp").is_ok());
+        assert!(limits().check("Amplitude:10").is_err());
+        assert!(limits().check("Amplitude2:10").is_err());
+        assert!(limits().check("Raw:VOLT 7").is_err());
+    }
+
+    #[test]
+    fn test_scpi_commands() {
+        assert!(limits().check("VOLT 5").is_ok());
+        assert!(limits().check(":SOUR1:VOLT:LEV:IMM 12").is_err());
+        assert!(limits().check("volt 5500mV").is_ok());
+        assert!(limits().check("CURR 2 A").is_err());
+        assert!(limits().check("VOLT MIN").is_ok());
+        assert!(limits().check("VOLT MAX").is_err());
+        assert!(limits().check("VOLT:PROT 30").is_ok());
+        assert!(limits().check("VOLT?").is_ok());
+        assert!(limits().check("OUTP ON;VOLT 7").is_err());
+    }
+
+    #[test]
+    fn test_apply_commands() {
+        assert!(limits().check("APPL CH1,5,0.5").is_ok());
+        assert!(limits().check("APPL CH1,12,0.5").is_err());
+        assert!(limits().check("APPL 5,1.5").is_err());
+        assert!(limits().check("APPL:SIN 1000,10,0").is_ok());
+    }
+
+    #[test]
+    fn test_voltage_setpoint() {
+        assert_eq!(voltage_setpoint("Voltage:12"), Some(12.0));
+        assert_eq!(voltage_setpoint("APPL CH1,24,0.5"), Some(24.0));
+        assert_eq!(voltage_setpoint("CURR 1"), None);
+    }
+
+    #[test]
+    fn test_no_limits() {
+        assert!(Limits::default().is_empty());
+        assert!(Limits::default().check("VOLT 500").is_ok());
+    }
+}
@@ -1,3 +1,61 @@
+pub mod atorch;
+#[cfg(feature = "usb")]
+mod common;
+pub mod danger;
+#[cfg(any(feature = "usb", feature = "gpib"))]
+pub mod delay;
+#[cfg(any(feature = "usb", feature = "gpib"))]
+pub mod errorqueue;
+pub mod fluke;
+pub mod grammar;
+pub mod indicate;
+pub mod keysight;
+pub mod korad;
+pub mod limits;
+pub mod modbus;
+#[cfg(feature = "usb")]
+pub mod opc;
+pub mod owonxdm;
+pub mod peaktech;
+pub mod raw;
+pub mod readback;
+pub mod readonly;
+pub mod riden;
+#[cfg(feature = "usb")]
+pub mod scope;
+pub mod switch;
 pub mod unit161d;
+#[cfg(feature = "usb")]
+pub mod usb488;
+pub mod ut325;
 
-pub use unit161d::Uni161dCommand;
\ No newline at end of file
+pub use atorch::AtorchCommand;
+#[cfg(feature = "usb")]
+pub use common::CommandSet;
+pub use danger::DangerRules;
+#[cfg(any(feature = "usb", feature = "gpib"))]
+pub use delay::Delay;
+pub use fluke::FlukeCommand;
+pub use indicate::Indicate;
+pub use keysight::KeysightDmmCommand;
+#[cfg(any(feature = "serial", feature = "usb", test))]
+pub use keysight::DmmFunction;
+pub use korad::KoradCommand;
+pub use limits::Limits;
+pub use modbus::ModbusCommand;
+pub use owonxdm::OwonXdmCommand;
+#[cfg(any(feature = "serial", test))]
+pub use owonxdm::XdmFunction;
+pub use peaktech::PeaktechChannelCommand;
+#[cfg(any(feature = "usb", feature = "hid", feature = "gpib"))]
+pub use raw::RawCommand;
+pub use riden::RidenCommand;
+#[cfg(feature = "usb")]
+pub use scope::{ScopeDialect, ScopeMeasurement, ScopeSegments, ScopeWaveform};
+pub use switch::SwitchCommand;
+pub use unit161d::Uni161dCommand;
+#[cfg(feature = "hid")]
+pub use unit161d::MinMaxSession;
+#[cfg(feature = "usb")]
+pub use usb488::WaitServiceRequest;
+pub use ut325::{Ut325Channel, Ut325Command};
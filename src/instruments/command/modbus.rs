@@ -0,0 +1,43 @@
+use crate::error::ApplicationError;
+
+/**
+ * Enum representing the commands of Modbus devices read through a register map.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub enum ModbusCommand {
+    /// Reads every register of the map.
+    Measure,
+    /// Reads the named registers of the map.
+    Read(Vec<String>),
+}
+
+impl TryFrom<&str> for ModbusCommand {
+    type Error = ApplicationError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value.split_once(':') {
+            None if value == "Measure" => Ok(ModbusCommand::Measure),
+            Some(("Read", names)) if names.split(',').all(|name| !name.trim().is_empty()) => Ok(ModbusCommand::Read(
+                names.split(',').map(|name| name.trim().to_string()).collect(),
+            )),
+            _ => Err(ApplicationError::Command(format!("Unsupported command for Modbus devices: {}", value))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_try_from_command() {
+        assert_eq!(ModbusCommand::try_from("Measure").unwrap(), ModbusCommand::Measure);
+        assert_eq!(
+            ModbusCommand::try_from("Read:voltage, current").unwrap(),
+            ModbusCommand::Read(vec!["voltage".into(), "current".into()])
+        );
+        assert!(ModbusCommand::try_from("Read:").is_err());
+        assert!(ModbusCommand::try_from("Read:voltage,").is_err());
+        assert!(ModbusCommand::try_from("Write:1,2").is_err());
+    }
+}
@@ -0,0 +1,99 @@
+use crate::error::ApplicationError;
+
+/**
+ * Pseudo-command waiting until the instrument has finished all pending operations.
+ */
+pub const OPC_COMMAND: &str = "Opc";
+
+/**
+ * Query answered with `1` once all pending operations are complete.
+ */
+pub const OPC_QUERY: &str = "*OPC?";
+
+/**
+ * Typed commands that are not sent as they are, so they are never followed by `*OPC?`.
+ */
+const PSEUDO_COMMANDS: [&str; 6] = ["Indicate:", "WaitSrq:", "Measure:", "Segments:", "Waveform:", "Delay:"];
+
+/**
+ * Checks if a command is the Opc pseudo-command.
+ *
+ * # Arguments
+ * `command` - The command string.
+ *
+ * # Returns
+ * A boolean indicating whether the command is Opc.
+ */
+pub fn is_opc(command: &str) -> bool {
+    command.trim() == OPC_COMMAND
+}
+
+/**
+ * Checks if a command configures the instrument and must be followed by `*OPC?` with
+ * `--opc-sync`. Queries, binary payloads, pseudo-commands and `*OPC`/`*WAI` themselves are not.
+ *
+ * # Arguments
+ * `command` - The command string.
+ *
+ * # Returns
+ * A boolean indicating whether the command is synchronized.
+ */
+pub fn needs_sync(command: &str) -> bool {
+    let command = command.trim();
+    let upper = command.to_ascii_uppercase();
+    !(command.is_empty()
+        || command.contains('?')
+        || command.starts_with("RawHex:")
+        || is_opc(command)
+        || upper == "*OPC"
+        || upper == "*WAI"
+        || PSEUDO_COMMANDS.iter().any(|prefix| command.starts_with(prefix)))
+}
+
+/**
+ * Checks the response to `*OPC?`.
+ *
+ * # Arguments
+ * `command` - The command waited for, used in the error message.
+ * `response` - The response to `*OPC?`.
+ *
+ * # Returns
+ * A Result indicating success or an ApplicationError if the response is not `1`.
+ */
+pub fn check_complete(command: &str, response: &[u8]) -> Result<(), ApplicationError> {
+    let text = String::from_utf8_lossy(response);
+    match text.trim().trim_start_matches('+') {
+        "1" => Ok(()),
+        other => Err(ApplicationError::Command(format!(
+            "Unexpected *OPC? response {:?} after {:?}",
+            other, command
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_needs_sync() {
+        assert!(needs_sync(":FREQ 1000"));
+        assert!(needs_sync("APPL:SIN 1000,10,0;OUTP ON"));
+        assert!(needs_sync("*RST"));
+        assert!(!needs_sync("MEAS:VOLT?"));
+        assert!(!needs_sync("*OPC"));
+        assert!(!needs_sync("*wai"));
+        assert!(!needs_sync("Opc"));
+        assert!(!needs_sync("RawHex:AA 01"));
+        assert!(!needs_sync("Indicate:Pass"));
+        assert!(!needs_sync("Delay:100"));
+        assert!(!needs_sync("Measure:Vpp, CHAN1"));
+    }
+
+    #[test]
+    fn test_check_complete() {
+        assert!(check_complete("*RST", b"1\n").is_ok());
+        assert!(check_complete("*RST", b"+1\r\n").is_ok());
+        assert!(check_complete("*RST", b"0\n").is_err());
+    }
+}
@@ -0,0 +1,217 @@
+use crate::error::ApplicationError;
+
+/**
+ * Enum representing the measurement functions of Owon XDM bench multimeters.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum XdmFunction {
+    VoltageDc,
+    VoltageAc,
+    CurrentDc,
+    CurrentAc,
+    Resistance,
+    Capacitance,
+    Frequency,
+    Period,
+    Diode,
+    Continuity,
+    Temperature,
+}
+
+// Command name, configuration command, FUNC? response and unit of each function
+const FUNCTIONS: [(XdmFunction, &str, &str, &str, &str); 11] = [
+    (XdmFunction::VoltageDc, "VoltageDc", "CONF:VOLT:DC", "VOLT", "V"),
+    (XdmFunction::VoltageAc, "VoltageAc", "CONF:VOLT:AC", "VOLT AC", "V"),
+    (XdmFunction::CurrentDc, "CurrentDc", "CONF:CURR:DC", "CURR", "A"),
+    (XdmFunction::CurrentAc, "CurrentAc", "CONF:CURR:AC", "CURR AC", "A"),
+    (XdmFunction::Resistance, "Resistance", "CONF:RES", "RES", "Ω"),
+    (XdmFunction::Capacitance, "Capacitance", "CONF:CAP", "CAP", "F"),
+    (XdmFunction::Frequency, "Frequency", "CONF:FREQ", "FREQ", "Hz"),
+    (XdmFunction::Period, "Period", "CONF:PER", "PER", "s"),
+    (XdmFunction::Diode, "Diode", "CONF:DIOD", "DIOD", "V"),
+    (XdmFunction::Continuity, "Continuity", "CONF:CONT", "CONT", "Ω"),
+    (XdmFunction::Temperature, "Temperature", "CONF:TEMP", "TEMP", "°C"),
+];
+
+#[cfg(any(feature = "serial", test))]
+impl XdmFunction {
+    /**
+     * Gets the function entry of the table.
+     *
+     * # Returns
+     * The command name, configuration command, FUNC? response and unit.
+     */
+    fn entry(&self) -> (&'static str, &'static str, &'static str, &'static str) {
+        FUNCTIONS
+            .iter()
+            .find(|(function, ..)| function == self)
+            .map(|(_, name, configure, query, unit)| (*name, *configure, *query, *unit))
+            .unwrap_or_default()
+    }
+
+    /**
+     * Returns the command name of the function, e.g. `VoltageDc`.
+     *
+     * # Returns
+     * The name.
+     */
+    pub fn name(&self) -> &'static str {
+        self.entry().0
+    }
+
+    /**
+     * Returns the SCPI command selecting the function.
+     *
+     * # Returns
+     * The command.
+     */
+    #[cfg(feature = "serial")]
+    pub fn configure(&self) -> &'static str {
+        self.entry().1
+    }
+
+    /**
+     * Returns the unit of the values returned by `MEAS?`.
+     *
+     * # Returns
+     * The unit.
+     */
+    pub fn unit(&self) -> &'static str {
+        self.entry().3
+    }
+
+    /**
+     * Returns whether the function measures an AC quantity.
+     *
+     * # Returns
+     * True for AC voltage and current.
+     */
+    pub fn is_ac(&self) -> bool {
+        matches!(self, XdmFunction::VoltageAc | XdmFunction::CurrentAc)
+    }
+
+    /**
+     * Parses the response to `FUNC?`, e.g. `"VOLT AC"`.
+     *
+     * # Arguments
+     * `response` - The raw response.
+     *
+     * # Returns
+     * A Result containing the function or an ApplicationError for unknown functions.
+     */
+    #[cfg(feature = "serial")]
+    pub fn from_response(response: &[u8]) -> Result<Self, ApplicationError> {
+        let text = String::from_utf8_lossy(response);
+        let text = text.trim().trim_matches('"');
+        FUNCTIONS
+            .iter()
+            .find(|(.., query, _)| query.eq_ignore_ascii_case(text))
+            .map(|(function, ..)| *function)
+            .ok_or_else(|| ApplicationError::Command(format!("Unknown Owon XDM function: {}", text)))
+    }
+}
+
+/**
+ * Enum representing the measurement rates of Owon XDM bench multimeters.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum XdmRate {
+    Slow,
+    Medium,
+    Fast,
+}
+
+/**
+ * Enum representing the commands of Owon XDM1041 / XDM2041 bench multimeters.
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OwonXdmCommand {
+    /// Selects the measurement function.
+    Function(XdmFunction),
+    /// Sets the measurement rate.
+    Rate(XdmRate),
+    /// Reads the primary value with `MEAS?`.
+    Measure,
+    /// Reads the identification string.
+    Identify,
+}
+
+#[cfg(feature = "serial")]
+impl OwonXdmCommand {
+    /**
+     * Serializes the command into the SCPI command sent to the meter. Commands ending with `?`
+     * are queries with a response.
+     *
+     * # Returns
+     * The SCPI command.
+     */
+    pub fn to_wire(self) -> String {
+        match self {
+            OwonXdmCommand::Function(function) => function.configure().to_string(),
+            OwonXdmCommand::Rate(XdmRate::Slow) => "RATE S".into(),
+            OwonXdmCommand::Rate(XdmRate::Medium) => "RATE M".into(),
+            OwonXdmCommand::Rate(XdmRate::Fast) => "RATE F".into(),
+            OwonXdmCommand::Measure => "MEAS?".into(),
+            OwonXdmCommand::Identify => "*IDN?".into(),
+        }
+    }
+}
+
+impl TryFrom<&str> for OwonXdmCommand {
+    type Error = ApplicationError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        let (name, argument) = match value.split_once(':') {
+            Some((name, argument)) => (name, Some(argument.trim())),
+            None => (value, None),
+        };
+        match (name, argument) {
+            ("Function", Some(argument)) => FUNCTIONS
+                .iter()
+                .find(|(_, name, ..)| *name == argument)
+                .map(|(function, ..)| OwonXdmCommand::Function(*function))
+                .ok_or_else(|| ApplicationError::Command(format!("Unknown function in {}", value))),
+            ("Rate", Some("Slow")) => Ok(OwonXdmCommand::Rate(XdmRate::Slow)),
+            ("Rate", Some("Medium")) => Ok(OwonXdmCommand::Rate(XdmRate::Medium)),
+            ("Rate", Some("Fast")) => Ok(OwonXdmCommand::Rate(XdmRate::Fast)),
+            ("Measure", None) => Ok(OwonXdmCommand::Measure),
+            ("Identify", None) => Ok(OwonXdmCommand::Identify),
+            _ => Err(ApplicationError::Command(format!("Unknown command: {}", value))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_try_from_command() {
+        assert_eq!(
+            OwonXdmCommand::try_from("Function:VoltageAc").unwrap(),
+            OwonXdmCommand::Function(XdmFunction::VoltageAc)
+        );
+        assert_eq!(OwonXdmCommand::try_from("Rate:Fast").unwrap(), OwonXdmCommand::Rate(XdmRate::Fast));
+        assert_eq!(OwonXdmCommand::try_from("Measure").unwrap(), OwonXdmCommand::Measure);
+        assert!(OwonXdmCommand::try_from("Function:Volts").is_err());
+        assert!(OwonXdmCommand::try_from("Rate:2").is_err());
+        assert!(OwonXdmCommand::try_from("MEAS?").is_err());
+    }
+
+    #[cfg(feature = "serial")]
+    #[test]
+    fn test_to_wire() {
+        assert_eq!(OwonXdmCommand::Function(XdmFunction::CurrentDc).to_wire(), "CONF:CURR:DC");
+        assert_eq!(OwonXdmCommand::Rate(XdmRate::Medium).to_wire(), "RATE M");
+        assert_eq!(OwonXdmCommand::Measure.to_wire(), "MEAS?");
+    }
+
+    #[cfg(feature = "serial")]
+    #[test]
+    fn test_function_from_response() {
+        assert_eq!(XdmFunction::from_response(b"\"VOLT AC\"\r\n").unwrap(), XdmFunction::VoltageAc);
+        assert_eq!(XdmFunction::from_response(b"RES\n").unwrap(), XdmFunction::Resistance);
+        assert_eq!(XdmFunction::Temperature.unit(), "°C");
+        assert!(XdmFunction::from_response(b"\"NCV\"").is_err());
+    }
+}
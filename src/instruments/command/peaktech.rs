@@ -0,0 +1,725 @@
+use crate::{
+    error::ApplicationError,
+    instruments::command::{
+        grammar::{Parameter, ScpiGrammar, ScpiNode},
+        readback::{Expected, Readback},
+    },
+};
+
+// Setting ranges of the Peaktech 4055MV
+const FREQUENCY_RANGE: (f64, f64) = (1e-6, 5e6);
+const AMPLITUDE_RANGE: (f64, f64) = (0.001, 20.0);
+const OFFSET_RANGE: (f64, f64) = (-10.0, 10.0);
+const DUTY_RANGE: (f64, f64) = (0.1, 99.9);
+const PHASE_RANGE: (f64, f64) = (-360.0, 360.0);
+const SWEEP_TIME_RANGE: (f64, f64) = (0.001, 500.0);
+
+// Parameters of the waveform commands: frequency, amplitude and offset
+const APPLY_PARAMETERS: &[Parameter] = &[
+    Parameter::Number(FREQUENCY_RANGE.0, FREQUENCY_RANGE.1),
+    Parameter::Number(AMPLITUDE_RANGE.0, AMPLITUDE_RANGE.1),
+    Parameter::Number(OFFSET_RANGE.0, OFFSET_RANGE.1),
+];
+const FREQUENCY: &[Parameter] = &[Parameter::Number(FREQUENCY_RANGE.0, FREQUENCY_RANGE.1)];
+
+/**
+ * SCPI commands of the Peaktech 4055MV, with the same ranges as the typed commands.
+ */
+pub const PEAKTECH_GRAMMAR: ScpiGrammar = ScpiGrammar {
+    instrument: "Peaktech 4055MV",
+    nodes: &[
+        ScpiNode::command("[SOURce#:]APPLy:SINusoid", APPLY_PARAMETERS, 0),
+        ScpiNode::command("[SOURce#:]APPLy:SQUare", APPLY_PARAMETERS, 0),
+        ScpiNode::command("[SOURce#:]APPLy:RAMP", APPLY_PARAMETERS, 0),
+        ScpiNode::command("[SOURce#:]APPLy:PULSe", APPLY_PARAMETERS, 0),
+        ScpiNode::command("[SOURce#:]APPLy:NOISe", APPLY_PARAMETERS, 0),
+        ScpiNode::command("[SOURce#:]APPLy:DC", APPLY_PARAMETERS, 0),
+        ScpiNode::query("[SOURce#:]APPLy"),
+        ScpiNode::setting(
+            "[SOURce#:]FUNCtion",
+            &[Parameter::Choice(&["SINusoid", "SQUare", "RAMP", "PULSe", "NOISe", "DC"])],
+        ),
+        ScpiNode::setting("[SOURce#:]FREQuency", FREQUENCY),
+        ScpiNode::setting("[SOURce#:]FREQuency:STARt", FREQUENCY),
+        ScpiNode::setting("[SOURce#:]FREQuency:STOP", FREQUENCY),
+        ScpiNode::setting("[SOURce#:]VOLTage", &[Parameter::Number(AMPLITUDE_RANGE.0, AMPLITUDE_RANGE.1)]),
+        ScpiNode::setting("[SOURce#:]VOLTage:OFFSet", &[Parameter::Number(OFFSET_RANGE.0, OFFSET_RANGE.1)]),
+        ScpiNode::setting("[SOURce#:]FUNCtion:SQUare:DCYCle", &[Parameter::Number(DUTY_RANGE.0, DUTY_RANGE.1)]),
+        ScpiNode::setting("[SOURce#:]PHASe", &[Parameter::Number(PHASE_RANGE.0, PHASE_RANGE.1)]),
+        ScpiNode::setting("[SOURce#:]SWEep:TIME", &[Parameter::Number(SWEEP_TIME_RANGE.0, SWEEP_TIME_RANGE.1)]),
+        ScpiNode::setting("[SOURce#:]SWEep:SPACing", &[Parameter::Choice(&["LINear", "LOGarithmic"])]),
+        ScpiNode::setting("[SOURce#:]SWEep:STATe", &[Parameter::Boolean]),
+        ScpiNode::setting("OUTPut#", &[Parameter::Boolean]),
+        ScpiNode::setting("TRIGger#:SOURce", &[Parameter::Choice(&["IMMediate", "EXTernal", "BUS"])]),
+        ScpiNode::query("SYSTem:ERRor"),
+        ScpiNode::command("SYSTem:REMote", &[], 0),
+        ScpiNode::command("SYSTem:LOCal", &[], 0),
+    ],
+};
+
+/**
+ * Number of output channels of the Peaktech 4055MV.
+ */
+const CHANNELS: u8 = 2;
+
+/**
+ * Checks that an output channel exists.
+ *
+ * # Arguments
+ * `channel` - The channel number.
+ *
+ * # Returns
+ * A Result containing the channel or an ApplicationError.
+ */
+pub fn check_channel(channel: u8) -> Result<u8, ApplicationError> {
+    match (1..=CHANNELS).contains(&channel) {
+        true => Ok(channel),
+        false => Err(ApplicationError::Command(format!(
+            "Channel {} does not exist, the generator has channels 1 to {}",
+            channel, CHANNELS
+        ))),
+    }
+}
+
+/**
+ * Enum representing the waveforms of the Peaktech 4055MV.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PeaktechWaveform {
+    Sine,
+    Square,
+    Ramp,
+    Pulse,
+    Noise,
+    Dc,
+}
+
+#[cfg(feature = "usb")]
+impl PeaktechWaveform {
+    /**
+     * Returns the SCPI mnemonic of the waveform.
+     *
+     * # Returns
+     * The mnemonic as a string slice.
+     */
+    fn mnemonic(&self) -> &'static str {
+        match self {
+            PeaktechWaveform::Sine => "SIN",
+            PeaktechWaveform::Square => "SQU",
+            PeaktechWaveform::Ramp => "RAMP",
+            PeaktechWaveform::Pulse => "PULS",
+            PeaktechWaveform::Noise => "NOIS",
+            PeaktechWaveform::Dc => "DC",
+        }
+    }
+}
+
+impl TryFrom<&str> for PeaktechWaveform {
+    type Error = ApplicationError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value.trim().to_lowercase().as_str() {
+            "sin" | "sine" => Ok(PeaktechWaveform::Sine),
+            "squ" | "square" => Ok(PeaktechWaveform::Square),
+            "ramp" => Ok(PeaktechWaveform::Ramp),
+            "puls" | "pulse" => Ok(PeaktechWaveform::Pulse),
+            "nois" | "noise" => Ok(PeaktechWaveform::Noise),
+            "dc" => Ok(PeaktechWaveform::Dc),
+            _ => Err(ApplicationError::Command(format!("Unknown waveform: {}", value.trim()))),
+        }
+    }
+}
+
+/**
+ * Enum representing the frequency spacing of a Peaktech 4055MV sweep.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PeaktechSweepSpacing {
+    Linear,
+    Logarithmic,
+}
+
+#[cfg(feature = "usb")]
+impl PeaktechSweepSpacing {
+    /**
+     * Returns the SCPI mnemonic of the spacing.
+     *
+     * # Returns
+     * The mnemonic as a string slice.
+     */
+    fn mnemonic(&self) -> &'static str {
+        match self {
+            PeaktechSweepSpacing::Linear => "LIN",
+            PeaktechSweepSpacing::Logarithmic => "LOG",
+        }
+    }
+}
+
+/**
+ * Enum representing the trigger sources of a Peaktech 4055MV sweep.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PeaktechTriggerSource {
+    /// Sweeps continuously.
+    Immediate,
+    /// Sweeps on the external trigger input.
+    External,
+    /// Sweeps on a bus trigger (`*TRG`).
+    Manual,
+}
+
+#[cfg(feature = "usb")]
+impl PeaktechTriggerSource {
+    /**
+     * Returns the SCPI mnemonic of the trigger source.
+     *
+     * # Returns
+     * The mnemonic as a string slice.
+     */
+    fn mnemonic(&self) -> &'static str {
+        match self {
+            PeaktechTriggerSource::Immediate => "IMM",
+            PeaktechTriggerSource::External => "EXT",
+            PeaktechTriggerSource::Manual => "BUS",
+        }
+    }
+}
+
+impl TryFrom<&str> for PeaktechTriggerSource {
+    type Error = ApplicationError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value.trim().to_lowercase().as_str() {
+            "imm" | "immediate" => Ok(PeaktechTriggerSource::Immediate),
+            "ext" | "external" => Ok(PeaktechTriggerSource::External),
+            "bus" | "manual" => Ok(PeaktechTriggerSource::Manual),
+            _ => Err(ApplicationError::Command(format!("Unknown trigger source: {}", value.trim()))),
+        }
+    }
+}
+
+/**
+ * Enum representing the settings of the Peaktech 4055MV that can be set and queried.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PeaktechSetting {
+    /// Frequency in Hz.
+    Frequency,
+    /// Amplitude in Vpp.
+    Amplitude,
+    /// DC offset in V.
+    Offset,
+    /// Square wave duty cycle in %.
+    Duty,
+    /// Phase in degrees.
+    Phase,
+    /// Sweep time in s.
+    SweepTime,
+}
+
+impl PeaktechSetting {
+    /**
+     * Returns the name of the setting in typed commands.
+     *
+     * # Returns
+     * The name as a string slice.
+     */
+    fn name(&self) -> &'static str {
+        match self {
+            PeaktechSetting::Frequency => "Frequency",
+            PeaktechSetting::Amplitude => "Amplitude",
+            PeaktechSetting::Offset => "Offset",
+            PeaktechSetting::Duty => "Duty",
+            PeaktechSetting::Phase => "Phase",
+            PeaktechSetting::SweepTime => "SweepTime",
+        }
+    }
+
+    /**
+     * Returns the SCPI header of the setting.
+     *
+     * # Returns
+     * The header as a string slice.
+     */
+    #[cfg(feature = "usb")]
+    fn header(&self) -> &'static str {
+        match self {
+            PeaktechSetting::Frequency => "FREQ",
+            PeaktechSetting::Amplitude => "VOLT",
+            PeaktechSetting::Offset => "VOLT:OFFS",
+            PeaktechSetting::Duty => "FUNC:SQU:DCYC",
+            PeaktechSetting::Phase => "PHAS",
+            PeaktechSetting::SweepTime => "SWE:TIME",
+        }
+    }
+
+    /**
+     * Returns the valid range and unit of the setting.
+     *
+     * # Returns
+     * The lowest and highest value and the unit.
+     */
+    fn range(&self) -> ((f64, f64), &'static str) {
+        match self {
+            PeaktechSetting::Frequency => (FREQUENCY_RANGE, "Hz"),
+            PeaktechSetting::Amplitude => (AMPLITUDE_RANGE, "Vpp"),
+            PeaktechSetting::Offset => (OFFSET_RANGE, "V"),
+            PeaktechSetting::Duty => (DUTY_RANGE, "%"),
+            PeaktechSetting::Phase => (PHASE_RANGE, "°"),
+            PeaktechSetting::SweepTime => (SWEEP_TIME_RANGE, "s"),
+        }
+    }
+
+    /**
+     * Parses a value of the setting and checks its range. Frequencies may have a `Hz`, `kHz`
+     * or `MHz` suffix, amplitudes and offsets a `V`, `Vpp` or `mV` suffix, duty cycles `%` and
+     * sweep times `s` or `ms`.
+     *
+     * # Arguments
+     * `command` - The full command, used in error messages.
+     * `value` - The value.
+     *
+     * # Returns
+     * A Result containing the value in the base unit or an ApplicationError.
+     */
+    fn parse_value(&self, command: &str, value: &str) -> Result<f64, ApplicationError> {
+        let value = value.trim();
+        let suffixes: &[(&str, f64)] = match self {
+            PeaktechSetting::Frequency => &[("MHz", 1e6), ("kHz", 1e3), ("Hz", 1.0)],
+            PeaktechSetting::Amplitude | PeaktechSetting::Offset => &[("mV", 1e-3), ("Vpp", 1.0), ("V", 1.0)],
+            PeaktechSetting::Duty => &[("%", 1.0)],
+            PeaktechSetting::Phase => &[],
+            PeaktechSetting::SweepTime => &[("ms", 1e-3), ("s", 1.0)],
+        };
+        let (number, factor) = suffixes
+            .iter()
+            .find_map(|(suffix, factor)| value.strip_suffix(suffix).map(|number| (number, *factor)))
+            .unwrap_or((value, 1.0));
+        let number = number
+            .trim()
+            .parse::<f64>()
+            .map_err(|_| ApplicationError::Command(format!("Invalid value in {}", command)))?
+            * factor;
+        let ((lowest, highest), unit) = self.range();
+        if !(lowest..=highest).contains(&number) {
+            return Err(ApplicationError::Command(format!(
+                "{} is out of range, expected {} to {} {}",
+                command, lowest, highest, unit
+            )));
+        }
+        Ok(number)
+    }
+}
+
+/**
+ * Enum representing the typed commands of the Peaktech 4055MV function generator.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub enum PeaktechCommand {
+    /// Selects a waveform with optional frequency, amplitude and offset.
+    Apply(PeaktechWaveform, Vec<f64>),
+    /// Resets the generator.
+    Reset,
+    /// Sends the text as is.
+    Raw(String),
+    /// Switches the output on or off.
+    Output(bool),
+    /// Queries the output state.
+    OutputQuery,
+    /// Sets a setting.
+    Set(PeaktechSetting, f64),
+    /// Queries a setting.
+    Query(PeaktechSetting),
+    /// Sweeps the frequency from the start to the stop frequency in the sweep time, in seconds.
+    Sweep(PeaktechSweepSpacing, f64, f64, f64),
+    /// Switches the sweep off.
+    SweepOff,
+    /// Selects the trigger source of the sweep.
+    SweepTrigger(PeaktechTriggerSource),
+}
+
+impl PeaktechCommand {
+    /**
+     * Parses a typed command. Other commands are SCPI sent as is.
+     *
+     * # Arguments
+     * `command` - The command string, e.g. `Apply:Sin, 10kHz, 3, 0.4` or `Frequency?`.
+     *
+     * # Returns
+     * A Result containing Some(PeaktechCommand), None if it is not a typed command, or an
+     * ApplicationError if a typed command is invalid or a value out of range.
+     */
+    pub fn parse(command: &str) -> Result<Option<Self>, ApplicationError> {
+        let (name, argument) = match command.split_once(':') {
+            Some((name, argument)) => (name, Some(argument)),
+            None => (command.trim(), None),
+        };
+        let setting = |name: &str| match name {
+            "Frequency" => Some(PeaktechSetting::Frequency),
+            "Amplitude" => Some(PeaktechSetting::Amplitude),
+            "Offset" => Some(PeaktechSetting::Offset),
+            "Duty" => Some(PeaktechSetting::Duty),
+            "Phase" => Some(PeaktechSetting::Phase),
+            "SweepTime" => Some(PeaktechSetting::SweepTime),
+            _ => None,
+        };
+        let typed = match (name, argument) {
+            ("Apply", Some(arguments)) => {
+                // The waveform is followed by a comma or a space
+                let arguments = arguments.trim();
+                let (waveform, values) = arguments
+                    .split_once(|c: char| c == ',' || c.is_whitespace())
+                    .unwrap_or((arguments, ""));
+                let waveform = PeaktechWaveform::try_from(waveform)?;
+                let values = values.trim().trim_start_matches(',');
+                let mut arguments = values.split(',').filter(|_| !values.trim().is_empty());
+                let values = [PeaktechSetting::Frequency, PeaktechSetting::Amplitude, PeaktechSetting::Offset]
+                    .iter()
+                    .zip(arguments.by_ref())
+                    .map(|(setting, value)| setting.parse_value(command, value))
+                    .collect::<Result<Vec<f64>, ApplicationError>>()?;
+                if arguments.next().is_some() {
+                    return Err(ApplicationError::Command(format!(
+                        "Expected Apply:<Waveform>[, <Frequency>, <Amplitude>, <Offset>], got {}",
+                        command
+                    )));
+                }
+                PeaktechCommand::Apply(waveform, values)
+            }
+            ("Sweep", Some(arguments)) => {
+                let arguments = arguments.trim();
+                let (spacing, values) = arguments
+                    .split_once(|c: char| c == ',' || c.is_whitespace())
+                    .unwrap_or((arguments, ""));
+                let spacing = match spacing {
+                    "Lin" => PeaktechSweepSpacing::Linear,
+                    "Log" => PeaktechSweepSpacing::Logarithmic,
+                    "Off" if values.trim().is_empty() => return Ok(Some(PeaktechCommand::SweepOff)),
+                    _ => {
+                        return Err(ApplicationError::Command(format!(
+                            "Expected Sweep:Lin|Log <Start>, <Stop>, <Time> or Sweep:Off, got {}",
+                            command
+                        )))
+                    }
+                };
+                let values: Vec<&str> = values.trim().trim_start_matches(',').split(',').collect();
+                let [start, stop, time] = values[..] else {
+                    return Err(ApplicationError::Command(format!(
+                        "Expected Sweep:Lin|Log <Start>, <Stop>, <Time>, got {}",
+                        command
+                    )));
+                };
+                let start = PeaktechSetting::Frequency.parse_value(command, start)?;
+                let stop = PeaktechSetting::Frequency.parse_value(command, stop)?;
+                if start == stop {
+                    return Err(ApplicationError::Command(format!(
+                        "Start and stop frequency are equal in {}",
+                        command
+                    )));
+                }
+                PeaktechCommand::Sweep(spacing, start, stop, PeaktechSetting::SweepTime.parse_value(command, time)?)
+            }
+            ("SweepTrigger", Some(source)) => PeaktechCommand::SweepTrigger(PeaktechTriggerSource::try_from(source)?),
+            ("Reset", None) => PeaktechCommand::Reset,
+            ("Raw", Some(text)) => PeaktechCommand::Raw(text.to_string()),
+            ("Output", Some(state)) => match state.trim() {
+                "On" => PeaktechCommand::Output(true),
+                "Off" => PeaktechCommand::Output(false),
+                _ => return Err(ApplicationError::Command(format!("Expected On or Off, got {}", command))),
+            },
+            ("Output?", None) => PeaktechCommand::OutputQuery,
+            (name, Some(value)) => match setting(name) {
+                Some(setting) => PeaktechCommand::Set(setting, setting.parse_value(command, value)?),
+                None => return Ok(None),
+            },
+            (name, None) => match name.strip_suffix('?').and_then(setting) {
+                Some(setting) => PeaktechCommand::Query(setting),
+                None => return Ok(None),
+            },
+        };
+        Ok(Some(typed))
+    }
+
+    /**
+     * Checks if the command is addressed to an output channel. Reset and Raw commands are not.
+     *
+     * # Returns
+     * A boolean indicating whether the command has a channel.
+     */
+    fn has_channel(&self) -> bool {
+        !matches!(self, PeaktechCommand::Reset | PeaktechCommand::Raw(_))
+    }
+
+    /**
+     * Serializes the command into SCPI. Commands ending with `?` are queries with a response.
+     * Channel 1 commands have no channel number, which the generator defaults to 1; other
+     * channels are addressed with `SOURn:`, `OUTPn` and `TRIGn:` headers.
+     *
+     * # Arguments
+     * `channel` - The output channel.
+     *
+     * # Returns
+     * The SCPI command.
+     */
+    #[cfg(feature = "usb")]
+    pub fn to_scpi(&self, channel: u8) -> String {
+        let (source, output, trigger) = match channel {
+            1 => (String::new(), "OUTP".to_string(), "TRIG".to_string()),
+            channel => (format!("SOUR{}:", channel), format!("OUTP{}", channel), format!("TRIG{}", channel)),
+        };
+        match self {
+            PeaktechCommand::Apply(waveform, values) if values.is_empty() => {
+                format!("{}APPL:{}", source, waveform.mnemonic())
+            }
+            PeaktechCommand::Apply(waveform, values) => format!(
+                "{}APPL:{} {}",
+                source,
+                waveform.mnemonic(),
+                values.iter().map(|value| value.to_string()).collect::<Vec<String>>().join(",")
+            ),
+            PeaktechCommand::Reset => "*RST".into(),
+            PeaktechCommand::Raw(text) => text.clone(),
+            PeaktechCommand::Output(true) => format!("{} ON", output),
+            PeaktechCommand::Output(false) => format!("{} OFF", output),
+            PeaktechCommand::OutputQuery => format!("{}?", output),
+            PeaktechCommand::Set(setting, value) => format!("{}{} {}", source, setting.header(), value),
+            PeaktechCommand::Query(setting) => format!("{}{}?", source, setting.header()),
+            PeaktechCommand::Sweep(spacing, start, stop, time) => format!(
+                "{s}SWE:SPAC {};:{s}FREQ:STAR {};:{s}FREQ:STOP {};:{s}SWE:TIME {};:{s}SWE:STAT ON",
+                spacing.mnemonic(),
+                start,
+                stop,
+                time,
+                s = source
+            ),
+            PeaktechCommand::SweepOff => format!("{}SWE:STAT OFF", source),
+            PeaktechCommand::SweepTrigger(source) => format!("{}:SOUR {}", trigger, source.mnemonic()),
+        }
+    }
+}
+
+/**
+ * A typed generator command with the output channel it is addressed to. The channel number
+ * follows the command name, e.g. `Apply2:Sin, 1kHz` or `Frequency2?`; commands without channel
+ * number go to the default channel.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct PeaktechChannelCommand {
+    pub channel: Option<u8>,
+    pub command: PeaktechCommand,
+}
+
+impl PeaktechChannelCommand {
+    /**
+     * Checks a command: typed commands are parsed and other commands are checked against the
+     * SCPI grammar of the generator. `Raw:` commands are not checked.
+     *
+     * # Arguments
+     * `command` - The command string.
+     *
+     * # Returns
+     * A Result indicating success or an ApplicationError if the command is invalid.
+     */
+    pub fn check(command: &str) -> Result<(), ApplicationError> {
+        match Self::parse(command)? {
+            Some(_) => Ok(()),
+            None => PEAKTECH_GRAMMAR.check(command),
+        }
+    }
+
+    /**
+     * Parses a typed command with an optional channel number. Other commands are SCPI sent as is.
+     *
+     * # Arguments
+     * `command` - The command string, e.g. `Apply2:Sin, 10kHz` or `Output2:On`.
+     *
+     * # Returns
+     * A Result containing Some(PeaktechChannelCommand), None if it is not a typed command, or an
+     * ApplicationError if a typed command is invalid or addressed to a channel that does not exist.
+     */
+    pub fn parse(command: &str) -> Result<Option<Self>, ApplicationError> {
+        let (name, rest) = match command.find(':') {
+            Some(index) => command.split_at(index),
+            None => (command.trim(), ""),
+        };
+        let (name, query) = match name.strip_suffix('?') {
+            Some(name) => (name, "?"),
+            None => (name, ""),
+        };
+        let numbered = name
+            .char_indices()
+            .last()
+            .filter(|(index, c)| *index > 0 && c.is_ascii_digit())
+            .map(|(index, c)| (&name[..index], c.to_digit(10).unwrap_or_default() as u8));
+        let Some((base, channel)) = numbered else {
+            return Ok(PeaktechCommand::parse(command)?.map(|command| Self { channel: None, command }));
+        };
+        let Some(typed) = PeaktechCommand::parse(&format!("{}{}{}", base, query, rest))? else {
+            return Ok(None);
+        };
+        if !typed.has_channel() {
+            return Err(ApplicationError::Command(format!("{} has no channel: {}", base, command)));
+        }
+        Ok(Some(Self {
+            channel: Some(check_channel(channel)?),
+            command: typed,
+        }))
+    }
+
+    /**
+     * Serializes the command into SCPI for its channel.
+     *
+     * # Arguments
+     * `default_channel` - The channel of commands without channel number.
+     *
+     * # Returns
+     * The SCPI command.
+     */
+    #[cfg(feature = "usb")]
+    pub fn to_scpi(&self, default_channel: u8) -> String {
+        self.command.to_scpi(self.channel.unwrap_or(default_channel))
+    }
+
+    /**
+     * Gets the query reading back a setting or output state on the same channel, e.g.
+     * `Frequency2?` after `Frequency2:1kHz`.
+     *
+     * # Returns
+     * The readback, or None if the command is not a setting.
+     */
+    pub fn readback(&self) -> Option<Readback> {
+        let channel = self.channel.map(|channel| channel.to_string()).unwrap_or_default();
+        match &self.command {
+            PeaktechCommand::Set(setting, value) => {
+                Some(Readback::new(&format!("{}{}?", setting.name(), channel), Expected::number(*value)))
+            }
+            PeaktechCommand::Output(on) => Some(Readback::new(&format!("Output{}?", channel), Expected::Switch(*on))),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(all(test, feature = "usb"))]
+mod test {
+    use super::*;
+
+    fn scpi(command: &str) -> String {
+        PeaktechCommand::parse(command).unwrap().unwrap().to_scpi(1)
+    }
+
+    fn channel_scpi(command: &str, default_channel: u8) -> String {
+        PeaktechChannelCommand::parse(command).unwrap().unwrap().to_scpi(default_channel)
+    }
+
+    #[test]
+    fn test_apply() {
+        assert_eq!(scpi("Apply:Sin, 10kHz, 3, 0.4"), "APPL:SIN 10000,3,0.4");
+        assert_eq!(scpi("Apply:Square, 1000"), "APPL:SQU 1000");
+        assert_eq!(scpi("Apply:Noise"), "APPL:NOIS");
+        assert_eq!(scpi("Apply:Ramp 1000, 5, 0"), "APPL:RAMP 1000,5,0");
+        assert!(PeaktechCommand::parse("Apply:Triangle").is_err());
+        assert!(PeaktechCommand::parse("Apply:Sin, 10MHz").is_err());
+        assert!(PeaktechCommand::parse("Apply:Sin, 1000, 1, 0, 5").is_err());
+    }
+
+    #[test]
+    fn test_sweep() {
+        assert_eq!(
+            scpi("Sweep:Lin 1kHz,10kHz,5s"),
+            "SWE:SPAC LIN;:FREQ:STAR 1000;:FREQ:STOP 10000;:SWE:TIME 5;:SWE:STAT ON"
+        );
+        assert_eq!(
+            scpi("Sweep:Log, 10, 1MHz, 500ms"),
+            "SWE:SPAC LOG;:FREQ:STAR 10;:FREQ:STOP 1000000;:SWE:TIME 0.5;:SWE:STAT ON"
+        );
+        assert_eq!(scpi("Sweep:Off"), "SWE:STAT OFF");
+        assert_eq!(scpi("SweepTrigger:External"), "TRIG:SOUR EXT");
+        assert_eq!(scpi("SweepTrigger:Manual"), "TRIG:SOUR BUS");
+        assert_eq!(scpi("SweepTime:2s"), "SWE:TIME 2");
+        assert_eq!(scpi("SweepTime?"), "SWE:TIME?");
+        assert!(PeaktechCommand::parse("Sweep:Exp 1kHz,10kHz,5s").is_err());
+        assert!(PeaktechCommand::parse("Sweep:Lin 1kHz,10kHz").is_err());
+        assert!(PeaktechCommand::parse("Sweep:Lin 1kHz,10MHz,5s").is_err());
+        assert!(PeaktechCommand::parse("Sweep:Lin 1kHz,1kHz,5s").is_err());
+        assert!(PeaktechCommand::parse("Sweep:Lin 1kHz,10kHz,1000s").is_err());
+        assert!(PeaktechCommand::parse("SweepTrigger:Timer").is_err());
+    }
+
+    #[test]
+    fn test_channels() {
+        assert_eq!(channel_scpi("Apply2:Sin, 10kHz, 3, 0.4", 1), "SOUR2:APPL:SIN 10000,3,0.4");
+        assert_eq!(channel_scpi("Apply1:Sin", 2), "APPL:SIN");
+        assert_eq!(channel_scpi("Apply:Sin", 2), "SOUR2:APPL:SIN");
+        assert_eq!(channel_scpi("Frequency2?", 1), "SOUR2:FREQ?");
+        assert_eq!(channel_scpi("Output2:On", 1), "OUTP2 ON");
+        assert_eq!(channel_scpi("Output2?", 1), "OUTP2?");
+        assert_eq!(channel_scpi("SweepTrigger2:External", 1), "TRIG2:SOUR EXT");
+        assert_eq!(
+            channel_scpi("Sweep2:Lin 1kHz,10kHz,5s", 1),
+            "SOUR2:SWE:SPAC LIN;:SOUR2:FREQ:STAR 1000;:SOUR2:FREQ:STOP 10000;:SOUR2:SWE:TIME 5;:SOUR2:SWE:STAT ON"
+        );
+        assert_eq!(channel_scpi("Reset", 2), "*RST");
+        assert_eq!(PeaktechChannelCommand::parse("SOUR2:FREQ 1000").unwrap(), None);
+        assert_eq!(PeaktechChannelCommand::parse("OUTP2?").unwrap(), None);
+        assert!(PeaktechChannelCommand::parse("Apply3:Sin").is_err());
+        assert!(PeaktechChannelCommand::parse("Reset2").is_err());
+        assert!(PeaktechChannelCommand::parse("Frequency2:10MHz").is_err());
+        assert!(check_channel(0).is_err());
+    }
+
+    #[test]
+    fn test_settings() {
+        assert_eq!(scpi("Frequency:2.5kHz"), "FREQ 2500");
+        assert_eq!(scpi("Amplitude:500mV"), "VOLT 0.5");
+        assert_eq!(scpi("Offset:-1.5"), "VOLT:OFFS -1.5");
+        assert_eq!(scpi("Duty:25%"), "FUNC:SQU:DCYC 25");
+        assert_eq!(scpi("Phase:90"), "PHAS 90");
+        assert_eq!(scpi("Output:On"), "OUTP ON");
+        assert_eq!(scpi("Reset"), "*RST");
+        assert_eq!(scpi("Raw:SYST:ERR?"), "SYST:ERR?");
+        assert!(PeaktechCommand::parse("Duty:100").is_err());
+        assert!(PeaktechCommand::parse("Amplitude:25").is_err());
+        assert!(PeaktechCommand::parse("Phase:abc").is_err());
+        assert!(PeaktechCommand::parse("Output:1").is_err());
+    }
+
+    #[test]
+    fn test_queries() {
+        assert_eq!(scpi("Frequency?"), "FREQ?");
+        assert_eq!(scpi("Offset?"), "VOLT:OFFS?");
+        assert_eq!(scpi("Output?"), "OUTP?");
+        assert_eq!(PeaktechCommand::parse("FREQ 1000").unwrap(), None);
+        assert_eq!(PeaktechCommand::parse("*IDN?").unwrap(), None);
+        assert_eq!(PeaktechCommand::parse("VOLT:OFFS?").unwrap(), None);
+    }
+
+    #[test]
+    fn test_grammar() {
+        assert!(PeaktechChannelCommand::check("APPL:SIN 1000,1").is_ok());
+        assert!(PeaktechChannelCommand::check("SOUR2:FUNC:SQU:DCYC 25").is_ok());
+        assert!(PeaktechChannelCommand::check("Raw:APLY:SIN").is_ok());
+        assert!(PeaktechChannelCommand::check("APLY:SIN").is_err());
+        assert!(PeaktechChannelCommand::check("FREQ 10MHz").is_err());
+        assert!(PeaktechChannelCommand::check("Apply:Triangle").is_err());
+        // The SCPI of every typed command is accepted by the grammar
+        for command in [
+            "Apply2:Sin, 10kHz, 3, 0.4",
+            "Apply:Noise",
+            "Sweep2:Log 1kHz,10kHz,5s",
+            "Sweep:Off",
+            "SweepTrigger2:Manual",
+            "Output2:Off",
+            "Output?",
+            "Frequency:2.5kHz",
+            "Amplitude2?",
+            "Offset:-1.5",
+            "Duty:25%",
+            "Phase:90",
+            "SweepTime:2s",
+            "Reset",
+        ] {
+            let scpi = channel_scpi(command, 1);
+            assert_eq!(PEAKTECH_GRAMMAR.check(&scpi).ok(), Some(()), "{}", scpi);
+        }
+    }
+}
@@ -0,0 +1,142 @@
+use crate::error::ApplicationError;
+
+/**
+ * Enum representing a command in the raw command paths. Text commands are sent with the
+ * configured terminator, hex payloads (`RawHex:AB CD 03`) are sent exactly as given.
+ */
+#[cfg(any(feature = "usb", feature = "hid", feature = "gpib"))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RawCommand {
+    /// A text command. Queries contain a `?`.
+    Text(String),
+    /// A binary payload. `RawHex?:` reads a response, `RawHex:` only writes.
+    Hex { bytes: Vec<u8>, query: bool },
+}
+
+#[cfg(any(feature = "usb", feature = "hid", feature = "gpib"))]
+impl RawCommand {
+    /**
+     * Parses a raw command.
+     *
+     * # Arguments
+     * `command` - The command string.
+     *
+     * # Returns
+     * A Result containing the RawCommand or an ApplicationError if the hex payload is invalid.
+     */
+    pub fn parse(command: &str) -> Result<Self, ApplicationError> {
+        let (payload, query) = if let Some(payload) = command.strip_prefix("RawHex?:") {
+            (payload, true)
+        } else if let Some(payload) = command.strip_prefix("RawHex:") {
+            (payload, false)
+        } else {
+            return Ok(RawCommand::Text(command.to_string()));
+        };
+        Ok(RawCommand::Hex {
+            bytes: parse_hex(payload)?,
+            query,
+        })
+    }
+
+    /**
+     * Encodes the command for the wire.
+     *
+     * # Arguments
+     * `terminator` - The terminator appended to text commands that do not already end with it.
+     *
+     * # Returns
+     * The bytes to send.
+     */
+    pub fn to_bytes(&self, terminator: &[u8]) -> Vec<u8> {
+        match self {
+            RawCommand::Text(command) => {
+                let mut bytes = command.as_bytes().to_vec();
+                if !bytes.ends_with(terminator) {
+                    bytes.extend_from_slice(terminator);
+                }
+                bytes
+            }
+            RawCommand::Hex { bytes, .. } => bytes.clone(),
+        }
+    }
+
+    /**
+     * Checks if a response is expected, overriding the detection of text queries for
+     * instruments whose commands do not follow the `?` convention. Hex payloads always say
+     * whether they are queries.
+     *
+     * # Arguments
+     * `expect_response` - Whether text commands are answered, or None to detect queries by `?`.
+     *
+     * # Returns
+     * A boolean indicating whether a response is read.
+     */
+    pub fn expects_response(&self, expect_response: Option<bool>) -> bool {
+        match self {
+            RawCommand::Text(command) => expect_response.unwrap_or_else(|| command.contains('?')),
+            RawCommand::Hex { query, .. } => *query,
+        }
+    }
+}
+
+/**
+ * Parses a hex payload such as `AB CD 03` or `ABCD03`.
+ *
+ * # Arguments
+ * `payload` - The hex digits, optionally separated by whitespace.
+ *
+ * # Returns
+ * A Result containing the bytes or an ApplicationError if the payload is invalid.
+ */
+pub fn parse_hex(payload: &str) -> Result<Vec<u8>, ApplicationError> {
+    let digits: String = payload.chars().filter(|c| !c.is_whitespace()).collect();
+    if digits.is_empty() || !digits.len().is_multiple_of(2) {
+        return Err(ApplicationError::Command(format!(
+            "Expected an even number of hex digits, got {}",
+            payload
+        )));
+    }
+    (0..digits.len())
+        .step_by(2)
+        .map(|index| {
+            digits
+                .get(index..index + 2)
+                .and_then(|digit| u8::from_str_radix(digit, 16).ok())
+                .ok_or_else(|| ApplicationError::Command(format!("Invalid hex payload {}", payload)))
+        })
+        .collect()
+}
+
+#[cfg(all(test, any(feature = "usb", feature = "hid", feature = "gpib")))]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_hex() {
+        assert_eq!(
+            RawCommand::parse("RawHex:AB CD 03").unwrap(),
+            RawCommand::Hex {
+                bytes: vec![0xAB, 0xCD, 0x03],
+                query: false
+            }
+        );
+        assert!(RawCommand::parse("RawHex?:abcd").unwrap().expects_response(None));
+        assert!(RawCommand::parse("RawHex?:abcd").unwrap().expects_response(Some(false)));
+        assert!(!RawCommand::parse("RawHex:abcd").unwrap().expects_response(Some(true)));
+        assert!(RawCommand::parse("RawHex:ABC").is_err());
+        assert!(RawCommand::parse("RawHex:ZZ").is_err());
+        assert!(RawCommand::parse("RawHex:").is_err());
+    }
+
+    #[test]
+    fn test_to_bytes() {
+        let command = RawCommand::parse("*IDN?").unwrap();
+        assert!(command.expects_response(None));
+        assert!(!command.expects_response(Some(false)));
+        assert!(RawCommand::parse("READ").unwrap().expects_response(Some(true)));
+        assert_eq!(command.to_bytes(b"\r\n"), b"*IDN?\r\n");
+        assert_eq!(command.to_bytes(b""), b"*IDN?");
+        assert_eq!(RawCommand::parse("OUTP ON\n").unwrap().to_bytes(b"\n"), b"OUTP ON\n");
+        assert_eq!(RawCommand::parse("RawHex:0102").unwrap().to_bytes(b"\n"), vec![1, 2]);
+    }
+}
@@ -0,0 +1,331 @@
+use crate::{error::ApplicationError, instruments::reading::Reading};
+
+/**
+ * Relative tolerance of numbers read back without a known resolution, 0.1 %.
+ */
+const RELATIVE_TOLERANCE: f64 = 1e-3;
+
+/**
+ * The value a query must return after a setting command.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expected {
+    /// A number, equal within the absolute tolerance.
+    Number(f64, f64),
+    /// On or off, read as `ON`/`OFF`, `1`/`0` or `true`/`false`.
+    Switch(bool),
+    /// A SCPI keyword, equal ignoring case when one is the short form of the other, e.g. `SIN` and `SINusoid`.
+    Text(String),
+    /// A flag of the measurement is set or cleared.
+    Flag(&'static str, bool),
+    /// A flag of the measurement changes state. The query is also sent before the command.
+    Toggled(&'static str),
+}
+
+impl Expected {
+    /**
+     * Creates an expected number with the default relative tolerance.
+     *
+     * # Arguments
+     * `value` - The number.
+     *
+     * # Returns
+     * The expected number.
+     */
+    pub fn number(value: f64) -> Self {
+        Expected::Number(value, (value.abs() * RELATIVE_TOLERANCE).max(1e-9))
+    }
+}
+
+/**
+ * The query verifying a setting command and the value it must return.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct Readback {
+    pub query: String,
+    /**
+     * CSV field of the reading holding the value, or None to use the response text.
+     */
+    pub field: Option<&'static str>,
+    pub expected: Expected,
+}
+
+impl Readback {
+    /**
+     * Creates a readback of the response text of a query.
+     *
+     * # Arguments
+     * `query` - The query.
+     * `expected` - The expected value.
+     *
+     * # Returns
+     * A new Readback instance.
+     */
+    pub fn new(query: &str, expected: Expected) -> Self {
+        Self {
+            query: query.to_string(),
+            field: None,
+            expected,
+        }
+    }
+
+    /**
+     * Creates a readback of a CSV field of the reading of a query.
+     *
+     * # Arguments
+     * `query` - The query.
+     * `field` - The CSV field.
+     * `expected` - The expected value.
+     *
+     * # Returns
+     * A new Readback instance.
+     */
+    pub fn field(query: &str, field: &'static str, expected: Expected) -> Self {
+        Self {
+            query: query.to_string(),
+            field: Some(field),
+            expected,
+        }
+    }
+
+    /**
+     * Gets the state of the flag of a toggle from the reading of the query before the command.
+     *
+     * # Arguments
+     * `readings` - The readings of the query.
+     *
+     * # Returns
+     * A Result containing the state of the flag, None if the command is not a toggle, or an
+     * ApplicationError if the reading has no measurement.
+     */
+    pub fn before(&self, readings: &[Box<dyn Reading>]) -> Result<Option<bool>, ApplicationError> {
+        match self.expected {
+            Expected::Toggled(flag) => Ok(Some(self.flag(readings, flag)?)),
+            _ => Ok(None),
+        }
+    }
+
+    /**
+     * Checks the readings of the query against the expected value.
+     *
+     * # Arguments
+     * `command` - The setting command, used in error messages.
+     * `readings` - The readings of the query.
+     * `before` - The state of the flag before a toggle.
+     *
+     * # Returns
+     * A Result indicating success or an ApplicationError if the value read back differs.
+     */
+    pub fn check(&self, command: &str, readings: &[Box<dyn Reading>], before: Option<bool>) -> Result<(), ApplicationError> {
+        let mismatch = |actual: &str, expected: String| {
+            ApplicationError::Command(format!(
+                "Readback of {} failed: {} returned {}, expected {}",
+                command, self.query, actual, expected
+            ))
+        };
+        let flag = |flag: &str, expected: bool| {
+            let actual = self.flag(readings, flag)?;
+            match actual == expected {
+                true => Ok(()),
+                false => Err(mismatch(&format!("{} {}", flag, on_off(actual)), format!("{} {}", flag, on_off(expected)))),
+            }
+        };
+        match &self.expected {
+            Expected::Flag(name, state) => flag(name, *state),
+            Expected::Toggled(name) => {
+                let before = before
+                    .ok_or_else(|| ApplicationError::Command(format!("No state of {} before {}", name, command)))?;
+                flag(name, !before)
+            }
+            Expected::Number(value, tolerance) => {
+                let text = self.text(readings)?;
+                match parse_number(&text) {
+                    Some(actual) if (actual - value).abs() <= *tolerance => Ok(()),
+                    _ => Err(mismatch(&text, value.to_string())),
+                }
+            }
+            Expected::Switch(on) => {
+                let text = self.text(readings)?;
+                match parse_switch(&text) {
+                    Some(actual) if actual == *on => Ok(()),
+                    _ => Err(mismatch(&text, on_off(*on).to_string())),
+                }
+            }
+            Expected::Text(keyword) => {
+                let text = self.text(readings)?;
+                let (actual, expected) = (text.to_ascii_uppercase(), keyword.to_ascii_uppercase());
+                match !actual.is_empty() && (actual.starts_with(&expected) || expected.starts_with(&actual)) {
+                    true => Ok(()),
+                    false => Err(mismatch(&text, keyword.clone())),
+                }
+            }
+        }
+    }
+
+    /**
+     * Gets the value read back as text: the CSV field or the response text of the first reading.
+     *
+     * # Arguments
+     * `readings` - The readings of the query.
+     *
+     * # Returns
+     * A Result containing the text or an ApplicationError if the query had no reading.
+     */
+    fn text(&self, readings: &[Box<dyn Reading>]) -> Result<String, ApplicationError> {
+        let reading = readings
+            .first()
+            .ok_or_else(|| ApplicationError::Command(format!("No response to readback query {}", self.query)))?;
+        let Some(field) = self.field else {
+            return Ok(reading.get_raw_string()?.trim().to_string());
+        };
+        let header = reading.get_csv_header()?;
+        let index = header
+            .iter()
+            .position(|name| name == field)
+            .ok_or_else(|| ApplicationError::Command(format!("No field {} in the response to {}", field, self.query)))?;
+        Ok(reading.get_csv()?.get(index).cloned().unwrap_or_default())
+    }
+
+    /**
+     * Checks if a flag of the measurement of the first reading is set.
+     *
+     * # Arguments
+     * `readings` - The readings of the query.
+     * `flag` - The flag.
+     *
+     * # Returns
+     * A Result containing the state of the flag or an ApplicationError if there is no measurement.
+     */
+    fn flag(&self, readings: &[Box<dyn Reading>], flag: &str) -> Result<bool, ApplicationError> {
+        readings
+            .iter()
+            .find_map(|reading| reading.get_measurement())
+            .map(|measurement| measurement.flags.iter().any(|name| name == flag))
+            .ok_or_else(|| ApplicationError::Command(format!("No measurement in the response to {}", self.query)))
+    }
+}
+
+/**
+ * Returns ON or OFF.
+ *
+ * # Arguments
+ * `on` - The state.
+ *
+ * # Returns
+ * The state as text.
+ */
+fn on_off(on: bool) -> &'static str {
+    match on {
+        true => "ON",
+        false => "OFF",
+    }
+}
+
+/**
+ * Parses the number at the start of a response, e.g. `1.000000E+03` or `5.00V`.
+ *
+ * # Arguments
+ * `text` - The response text.
+ *
+ * # Returns
+ * The number, or None if the response does not start with one.
+ */
+fn parse_number(text: &str) -> Option<f64> {
+    let text = text.trim();
+    let end = text
+        .find(|c: char| !(c.is_ascii_digit() || matches!(c, '.' | '+' | '-' | 'e' | 'E')))
+        .unwrap_or(text.len());
+    (1..=end).rev().find_map(|end| text[..end].parse::<f64>().ok())
+}
+
+/**
+ * Parses an on or off response.
+ *
+ * # Arguments
+ * `text` - The response text.
+ *
+ * # Returns
+ * The state, or None if the response is neither.
+ */
+fn parse_switch(text: &str) -> Option<bool> {
+    match text.trim().to_ascii_uppercase().as_str() {
+        "ON" | "1" | "TRUE" => Some(true),
+        "OFF" | "0" | "FALSE" => Some(false),
+        _ => None,
+    }
+}
+
+/**
+ * Gets the readback of a SCPI setting command, the query of its header, e.g. `FREQ?` after
+ * `FREQ 1000`. Commands with several values, compound commands and common commands have none.
+ *
+ * # Arguments
+ * `command` - The SCPI command.
+ *
+ * # Returns
+ * The readback, or None if the command is not a setting with one value.
+ */
+pub fn scpi_readback(command: &str) -> Option<Readback> {
+    let (header, value) = command.trim().split_once(char::is_whitespace)?;
+    let value = value.trim();
+    if header.starts_with('*') || header.ends_with('?') || header.contains(';') || value.is_empty() || value.contains([',', ';']) {
+        return None;
+    }
+    let query = format!("{}?", header);
+    let expected = match (parse_switch(value), value.parse::<f64>()) {
+        (_, Ok(number)) => Expected::number(number),
+        (Some(on), _) => Expected::Switch(on),
+        (None, Err(_)) => Expected::Text(value.to_string()),
+    };
+    Some(Readback::new(&query, expected))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::instruments::reading::ScpiRawReading;
+
+    /**
+     * Creates the readings of a raw SCPI response.
+     */
+    fn response(text: &str) -> Vec<Box<dyn Reading>> {
+        vec![Box::new(ScpiRawReading::new(text.as_bytes().to_vec()))]
+    }
+
+    #[test]
+    fn test_scpi_readback() {
+        assert_eq!(scpi_readback("FREQ 1000"), Some(Readback::new("FREQ?", Expected::number(1000.0))));
+        assert_eq!(scpi_readback(":OUTP ON"), Some(Readback::new(":OUTP?", Expected::Switch(true))));
+        assert_eq!(scpi_readback("FUNC SIN"), Some(Readback::new("FUNC?", Expected::Text("SIN".into()))));
+        assert_eq!(scpi_readback("FREQ?"), None);
+        assert_eq!(scpi_readback("*RST"), None);
+        assert_eq!(scpi_readback("APPL:SIN 1000,2"), None);
+        assert_eq!(scpi_readback("Voltage:5"), None);
+    }
+
+    #[test]
+    fn test_check() {
+        let readback = scpi_readback("FREQ 1000").unwrap();
+        assert!(readback.check("FREQ 1000", &response("1.000000E+03\n"), None).is_ok());
+        assert_eq!(
+            readback.check("FREQ 1000", &response("9.900000E+02\n"), None).unwrap_err().to_string(),
+            "Command Error: Readback of FREQ 1000 failed: FREQ? returned 9.900000E+02, expected 1000"
+        );
+        let readback = scpi_readback("OUTP OFF").unwrap();
+        assert!(readback.check("OUTP OFF", &response("0\n"), None).is_ok());
+        assert!(readback.check("OUTP OFF", &response("ON\n"), None).is_err());
+        let readback = scpi_readback("FUNC SINusoid").unwrap();
+        assert!(readback.check("FUNC SINusoid", &response("SIN\n"), None).is_ok());
+        assert!(readback.check("FUNC SINusoid", &response("SQU\n"), None).is_err());
+        assert!(readback.check("FUNC SINusoid", &[], None).is_err());
+    }
+
+    #[test]
+    fn test_parse_number() {
+        assert_eq!(parse_number("05.00"), Some(5.0));
+        assert_eq!(parse_number("5.00V"), Some(5.0));
+        assert_eq!(parse_number("-1.5E-3"), Some(-0.0015));
+        assert_eq!(parse_number("1e"), Some(1.0));
+        assert_eq!(parse_number("ON"), None);
+    }
+}
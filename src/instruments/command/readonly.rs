@@ -0,0 +1,89 @@
+use crate::error::ApplicationError;
+
+/**
+ * Typed commands that only read from the device.
+ */
+const READ_COMMANDS: [&str; 7] = ["Measure", "Read", "Setpoints", "Status", "Identify", "Opc", "WaitSrq"];
+
+/**
+ * Prefixes of typed commands that only read from the device or wait.
+ */
+const READ_PREFIXES: [&str; 7] = ["Measure:", "Read:", "Segments:", "Waveform:", "WaitSrq:", "Delay:", "RawHex?:"];
+
+/**
+ * SCPI roots that change the instrument state, named in the error instead of only rejecting the
+ * command for not being a query.
+ */
+const STATE_CHANGING_ROOTS: [&str; 14] = [
+    "*RST", "*CLS", "*RCL", "*SAV", "*TRG", "*ESE", "*SRE", "*PSC", "OUTP", "OUTPUT", "APPL", "APPLY", "SYST:PRES", "INIT",
+];
+
+/**
+ * SCPI queries that run an action before they answer.
+ */
+const ACTION_QUERIES: [&str; 4] = ["*TST", "*CAL", "CAL", "CAL:ALL"];
+
+/**
+ * Checks that a command only reads from the device, for `--read-only`. SCPI commands must be
+ * queries in every part separated by `;`; queries running a self test or calibration are
+ * rejected as well. Typed commands must be reads such as `Measure`.
+ *
+ * # Arguments
+ * `command` - The command string.
+ *
+ * # Returns
+ * A Result indicating success or an ApplicationError if the command may change the device.
+ */
+pub fn check_read_only(command: &str) -> Result<(), ApplicationError> {
+    let command = command.trim();
+    if READ_COMMANDS.contains(&command) || READ_PREFIXES.iter().any(|prefix| command.starts_with(prefix)) {
+        return Ok(());
+    }
+    let command = command.strip_prefix("Raw:").unwrap_or(command);
+    for part in command.split(';') {
+        let header = part.split_whitespace().next().unwrap_or_default();
+        let header = header.trim_start_matches(':').to_ascii_uppercase();
+        match header.strip_suffix('?') {
+            Some(query) if ACTION_QUERIES.contains(&query) => {
+                return Err(ApplicationError::Command(format!("{} runs an action, refused with --read-only", part.trim())));
+            }
+            Some(_) => {}
+            None if STATE_CHANGING_ROOTS.iter().any(|root| header == *root || header.starts_with(&format!("{}:", root))) => {
+                return Err(ApplicationError::Command(format!(
+                    "{} changes the instrument state, refused with --read-only",
+                    part.trim()
+                )));
+            }
+            None => {
+                return Err(ApplicationError::Command(format!("{} is not a query, refused with --read-only", part.trim())));
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_check_read_only() {
+        assert!(check_read_only("MEAS:VOLT:DC?").is_ok());
+        assert!(check_read_only("*IDN?;:SYST:ERR?").is_ok());
+        assert!(check_read_only("OUTP?").is_ok());
+        assert!(check_read_only("SENS:FREQ? MAX").is_ok());
+        assert!(check_read_only("Measure").is_ok());
+        assert!(check_read_only("Read:voltage").is_ok());
+        assert!(check_read_only("Measure:Vpp,CHAN1").is_ok());
+        assert!(check_read_only("RawHex?:AA 01").is_ok());
+        assert!(check_read_only("Raw:SYST:ERR?").is_ok());
+        assert!(check_read_only("Delay:100").is_ok());
+        for command in ["*RST", ":OUTP ON", "APPL:SIN 1000,1", "*IDN?;*RST", "*TST?", "Voltage:5", "Hold", "Indicate:Pass", "RawHex:AA 01"] {
+            assert!(check_read_only(command).is_err(), "{}", command);
+        }
+        assert_eq!(
+            check_read_only("OUTP:STAT ON").unwrap_err().to_string(),
+            "Command Error: OUTP:STAT ON changes the instrument state, refused with --read-only"
+        );
+    }
+}
@@ -0,0 +1,86 @@
+use crate::{
+    error::ApplicationError,
+    instruments::command::{
+        readback::{Expected, Readback},
+        KoradCommand,
+    },
+};
+
+/**
+ * Enum representing the commands of Riden RD60xx power supplies. The commands are named as
+ * for Korad power supplies, so scripts and safety limits work with both.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub enum RidenCommand {
+    /// Sets the output voltage in volts.
+    Voltage(f64),
+    /// Sets the current limit in amperes.
+    Current(f64),
+    /// Switches the output on or off.
+    Output(bool),
+    /// Reads the actual output voltage, current, power and status.
+    Measure,
+    /// Reads the voltage and current setpoints.
+    Setpoints,
+    /// Reads the model, serial number and firmware version.
+    Identify,
+}
+
+/**
+ * Coarsest resolution of the setpoints of the supported models, e.g. 0.01 A on the RD6012.
+ */
+const SETPOINT_RESOLUTION: f64 = 0.01;
+
+impl RidenCommand {
+    /**
+     * Gets the query reading back a setpoint or the output state.
+     *
+     * # Returns
+     * The readback, or None if the command is not a setting.
+     */
+    pub fn readback(&self) -> Option<Readback> {
+        match self {
+            RidenCommand::Voltage(volts) => {
+                Some(Readback::field("Setpoints", "voltage", Expected::Number(*volts, SETPOINT_RESOLUTION)))
+            }
+            RidenCommand::Current(amperes) => {
+                Some(Readback::field("Setpoints", "current", Expected::Number(*amperes, SETPOINT_RESOLUTION)))
+            }
+            RidenCommand::Output(on) => Some(Readback::field("Measure", "output", Expected::Switch(*on))),
+            _ => None,
+        }
+    }
+}
+
+impl TryFrom<&str> for RidenCommand {
+    type Error = ApplicationError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match KoradCommand::try_from(value)? {
+            KoradCommand::Voltage(volts) => Ok(RidenCommand::Voltage(volts)),
+            KoradCommand::Current(amperes) => Ok(RidenCommand::Current(amperes)),
+            KoradCommand::Output(on) => Ok(RidenCommand::Output(on)),
+            KoradCommand::Measure => Ok(RidenCommand::Measure),
+            KoradCommand::Setpoints => Ok(RidenCommand::Setpoints),
+            KoradCommand::Identify => Ok(RidenCommand::Identify),
+            _ => Err(ApplicationError::Command(format!(
+                "Unsupported command for Riden power supplies: {}",
+                value
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_try_from_command() {
+        assert_eq!(RidenCommand::try_from("Voltage:12.5").unwrap(), RidenCommand::Voltage(12.5));
+        assert_eq!(RidenCommand::try_from("Output:On").unwrap(), RidenCommand::Output(true));
+        assert_eq!(RidenCommand::try_from("Measure").unwrap(), RidenCommand::Measure);
+        assert!(RidenCommand::try_from("Ocp:On").is_err());
+        assert!(RidenCommand::try_from("Voltage:-1").is_err());
+    }
+}
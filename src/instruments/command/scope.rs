@@ -0,0 +1,411 @@
+use crate::error::ApplicationError;
+
+/**
+ * Enum representing the SCPI dialects of supported oscilloscopes.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScopeDialect {
+    Rigol,
+    Siglent,
+}
+
+/**
+ * Enum representing built-in oscilloscope measurements.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScopeItem {
+    Vpp,
+    Vmax,
+    Vmin,
+    Vavg,
+    Vrms,
+    Frequency,
+    Period,
+    RiseTime,
+    FallTime,
+    DutyCycle,
+}
+
+impl ScopeItem {
+    /**
+     * Returns the unit of the measurement.
+     *
+     * # Returns
+     * The unit as a string slice.
+     */
+    pub fn unit(&self) -> &'static str {
+        match self {
+            ScopeItem::Vpp | ScopeItem::Vmax | ScopeItem::Vmin | ScopeItem::Vavg | ScopeItem::Vrms => "V",
+            ScopeItem::Frequency => "Hz",
+            ScopeItem::Period | ScopeItem::RiseTime | ScopeItem::FallTime => "s",
+            ScopeItem::DutyCycle => "%",
+        }
+    }
+
+    /**
+     * Returns the measurement mnemonic used by the dialect.
+     *
+     * # Arguments
+     * `dialect` - The SCPI dialect.
+     *
+     * # Returns
+     * The mnemonic as a string slice.
+     */
+    fn mnemonic(&self, dialect: ScopeDialect) -> &'static str {
+        match dialect {
+            ScopeDialect::Rigol => match self {
+                ScopeItem::Vpp => "VPP",
+                ScopeItem::Vmax => "VMAX",
+                ScopeItem::Vmin => "VMIN",
+                ScopeItem::Vavg => "VAVG",
+                ScopeItem::Vrms => "VRMS",
+                ScopeItem::Frequency => "FREQ",
+                ScopeItem::Period => "PER",
+                ScopeItem::RiseTime => "RTIM",
+                ScopeItem::FallTime => "FTIM",
+                ScopeItem::DutyCycle => "PDUT",
+            },
+            ScopeDialect::Siglent => match self {
+                ScopeItem::Vpp => "PKPK",
+                ScopeItem::Vmax => "MAX",
+                ScopeItem::Vmin => "MIN",
+                ScopeItem::Vavg => "MEAN",
+                ScopeItem::Vrms => "RMS",
+                ScopeItem::Frequency => "FREQ",
+                ScopeItem::Period => "PER",
+                ScopeItem::RiseTime => "RISE",
+                ScopeItem::FallTime => "FALL",
+                ScopeItem::DutyCycle => "DUTY",
+            },
+        }
+    }
+}
+
+impl TryFrom<&str> for ScopeItem {
+    type Error = ApplicationError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "Vpp" => Ok(ScopeItem::Vpp),
+            "Vmax" => Ok(ScopeItem::Vmax),
+            "Vmin" => Ok(ScopeItem::Vmin),
+            "Vavg" => Ok(ScopeItem::Vavg),
+            "Vrms" => Ok(ScopeItem::Vrms),
+            "Frequency" => Ok(ScopeItem::Frequency),
+            "Period" => Ok(ScopeItem::Period),
+            "RiseTime" => Ok(ScopeItem::RiseTime),
+            "FallTime" => Ok(ScopeItem::FallTime),
+            "DutyCycle" => Ok(ScopeItem::DutyCycle),
+            _ => Err(ApplicationError::Command(format!(
+                "Unknown measurement: {}",
+                value
+            ))),
+        }
+    }
+}
+
+/**
+ * Represents a built-in measurement query on a scope channel, e.g. `Measure:Vpp, CHAN1`.
+ * The source is CHAN1 to CHAN4 or MATH.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScopeMeasurement {
+    pub item: ScopeItem,
+    pub source: String,
+}
+
+impl ScopeMeasurement {
+    /**
+     * Parses a Measure command.
+     *
+     * # Arguments
+     * `command` - The command string.
+     *
+     * # Returns
+     * A Result containing Some(ScopeMeasurement) for Measure commands, None for other commands,
+     * or an ApplicationError if the Measure command is invalid.
+     */
+    pub fn parse(command: &str) -> Result<Option<Self>, ApplicationError> {
+        let Some(arguments) = command.strip_prefix("Measure:") else {
+            return Ok(None);
+        };
+        let (item, source) = arguments.split_once(',').ok_or_else(|| {
+            ApplicationError::Command(format!("Expected Measure:<Item>, <Source>, got {}", command))
+        })?;
+        let source = source.trim().to_uppercase();
+        let valid_source = source == "MATH"
+            || matches!(source.strip_prefix("CHAN"), Some("1") | Some("2") | Some("3") | Some("4"));
+        if !valid_source {
+            return Err(ApplicationError::Command(format!("Unknown source: {}", source)));
+        }
+        Ok(Some(Self {
+            item: ScopeItem::try_from(item.trim())?,
+            source,
+        }))
+    }
+
+    /**
+     * Serializes the measurement into the SCPI query of the dialect.
+     *
+     * # Arguments
+     * `dialect` - The SCPI dialect.
+     *
+     * # Returns
+     * The SCPI query.
+     */
+    pub fn to_scpi(&self, dialect: ScopeDialect) -> String {
+        match dialect {
+            ScopeDialect::Rigol => format!(":MEAS:{}? {}", self.item.mnemonic(dialect), self.source),
+            ScopeDialect::Siglent => {
+                let source = match self.source.strip_prefix("CHAN") {
+                    Some(channel) => format!("C{}", channel),
+                    None => self.source.clone(),
+                };
+                format!("{}:PAVA? {}", source, self.item.mnemonic(dialect))
+            }
+        }
+    }
+}
+
+/**
+ * Represents a download of a range of segments from the segmented (history) memory,
+ * e.g. `Segments:CHAN1, 1, 10`.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScopeSegments {
+    pub source: String,
+    pub first: u32,
+    pub last: u32,
+}
+
+impl ScopeSegments {
+    /**
+     * Parses a Segments command.
+     *
+     * # Arguments
+     * `command` - The command string.
+     *
+     * # Returns
+     * A Result containing Some(ScopeSegments) for Segments commands, None for other commands,
+     * or an ApplicationError if the Segments command is invalid.
+     */
+    pub fn parse(command: &str) -> Result<Option<Self>, ApplicationError> {
+        let Some(arguments) = command.strip_prefix("Segments:") else {
+            return Ok(None);
+        };
+        let arguments: Vec<&str> = arguments.split(',').map(|argument| argument.trim()).collect();
+        let [source, first, last] = arguments[..] else {
+            return Err(ApplicationError::Command(format!(
+                "Expected Segments:<Source>, <First>, <Last>, got {}",
+                command
+            )));
+        };
+        let source = source.to_uppercase();
+        if !matches!(source.strip_prefix("CHAN"), Some("1") | Some("2") | Some("3") | Some("4")) {
+            return Err(ApplicationError::Command(format!("Unknown source: {}", source)));
+        }
+        let parse_segment = |value: &str| {
+            value
+                .parse::<u32>()
+                .map_err(|e| ApplicationError::ParseInt(format!("Invalid segment {}", value), e))
+        };
+        let (first, last) = (parse_segment(first)?, parse_segment(last)?);
+        if first == 0 || last < first {
+            return Err(ApplicationError::Command(format!(
+                "Invalid segment range {}..{}",
+                first, last
+            )));
+        }
+        Ok(Some(Self { source, first, last }))
+    }
+
+    /**
+     * Serializes the download into SCPI commands of the dialect. Each segment is selected
+     * and its waveform queried; the last command of every segment is the query.
+     *
+     * # Arguments
+     * `dialect` - The SCPI dialect.
+     *
+     * # Returns
+     * A vector of segment numbers with the commands for that segment.
+     */
+    pub fn to_scpi(&self, dialect: ScopeDialect) -> Vec<(u32, Vec<String>)> {
+        (self.first..=self.last)
+            .map(|segment| {
+                let commands = match dialect {
+                    ScopeDialect::Rigol => vec![
+                        format!(":FUNC:WREP:FCUR {}", segment),
+                        format!(":WAV:SOUR {}", self.source),
+                        ":WAV:DATA?".to_string(),
+                    ],
+                    ScopeDialect::Siglent => {
+                        let channel = self.source.trim_start_matches("CHAN");
+                        vec![format!("FRAM {}", segment), format!("C{}:WF? DAT2", channel)]
+                    }
+                };
+                (segment, commands)
+            })
+            .collect()
+    }
+}
+
+/**
+ * Number of points read per `:WAV:DATA?` query in RAW mode; Rigol scopes return at most
+ * 250000 bytes per query.
+ */
+const RIGOL_WAVEFORM_CHUNK: u64 = 250_000;
+
+/**
+ * Query returning the waveform preamble.
+ */
+pub const RIGOL_WAVEFORM_PREAMBLE: &str = ":WAV:PRE?";
+
+/**
+ * Represents a capture of the raw waveform memory of a channel, e.g. `Waveform:CHAN1`.
+ * Only the Rigol dialect is supported.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScopeWaveform {
+    pub source: String,
+}
+
+impl ScopeWaveform {
+    /**
+     * Parses a Waveform command.
+     *
+     * # Arguments
+     * `command` - The command string.
+     *
+     * # Returns
+     * A Result containing Some(ScopeWaveform) for Waveform commands, None for other commands,
+     * or an ApplicationError if the Waveform command is invalid.
+     */
+    pub fn parse(command: &str) -> Result<Option<Self>, ApplicationError> {
+        let Some(source) = command.strip_prefix("Waveform:") else {
+            return Ok(None);
+        };
+        let source = source.trim().to_uppercase();
+        let valid_source = source == "MATH"
+            || matches!(source.strip_prefix("CHAN"), Some("1") | Some("2") | Some("3") | Some("4"));
+        if !valid_source {
+            return Err(ApplicationError::Command(format!("Unknown source: {}", source)));
+        }
+        Ok(Some(Self { source }))
+    }
+
+    /**
+     * Returns the commands selecting the source and the raw byte format. The preamble is
+     * queried after these.
+     *
+     * # Returns
+     * The SCPI commands in order.
+     */
+    pub fn setup_commands(&self) -> Vec<String> {
+        vec![
+            format!(":WAV:SOUR {}", self.source),
+            ":WAV:MODE RAW".to_string(),
+            ":WAV:FORM BYTE".to_string(),
+        ]
+    }
+
+    /**
+     * Returns the commands reading the waveform memory in chunks. The last command of every
+     * chunk is the data query, answered with a binary block.
+     *
+     * # Arguments
+     * `points` - The number of points given by the preamble.
+     *
+     * # Returns
+     * The commands of every chunk in order.
+     */
+    pub fn chunk_commands(&self, points: u64) -> Vec<Vec<String>> {
+        (1..=points)
+            .step_by(RIGOL_WAVEFORM_CHUNK as usize)
+            .map(|start| {
+                let stop = (start + RIGOL_WAVEFORM_CHUNK - 1).min(points);
+                vec![
+                    format!(":WAV:STAR {}", start),
+                    format!(":WAV:STOP {}", stop),
+                    ":WAV:DATA?".to_string(),
+                ]
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_measure() {
+        assert_eq!(
+            ScopeMeasurement::parse("Measure:Vpp, chan1").unwrap(),
+            Some(ScopeMeasurement {
+                item: ScopeItem::Vpp,
+                source: "CHAN1".into()
+            })
+        );
+        assert_eq!(ScopeMeasurement::parse("*IDN?").unwrap(), None);
+        assert!(ScopeMeasurement::parse("Measure:Vpp").is_err());
+        assert!(ScopeMeasurement::parse("Measure:Vpp, CHAN9").is_err());
+        assert!(ScopeMeasurement::parse("Measure:Unknown, CHAN1").is_err());
+    }
+
+    #[test]
+    fn test_to_scpi() {
+        let measurement = ScopeMeasurement::parse("Measure:RiseTime, CHAN2").unwrap().unwrap();
+        assert_eq!(measurement.to_scpi(ScopeDialect::Rigol), ":MEAS:RTIM? CHAN2");
+        assert_eq!(measurement.to_scpi(ScopeDialect::Siglent), "C2:PAVA? RISE");
+        let measurement = ScopeMeasurement::parse("Measure:Frequency, MATH").unwrap().unwrap();
+        assert_eq!(measurement.to_scpi(ScopeDialect::Siglent), "MATH:PAVA? FREQ");
+    }
+
+    #[test]
+    fn test_parse_segments() {
+        assert_eq!(
+            ScopeSegments::parse("Segments:chan2, 3, 5").unwrap(),
+            Some(ScopeSegments {
+                source: "CHAN2".into(),
+                first: 3,
+                last: 5
+            })
+        );
+        assert_eq!(ScopeSegments::parse(":WAV:DATA?").unwrap(), None);
+        assert!(ScopeSegments::parse("Segments:CHAN1, 5, 3").is_err());
+        assert!(ScopeSegments::parse("Segments:CHAN1, 0, 3").is_err());
+        assert!(ScopeSegments::parse("Segments:MATH, 1, 3").is_err());
+        assert!(ScopeSegments::parse("Segments:CHAN1, 1").is_err());
+    }
+
+    #[test]
+    fn test_segments_to_scpi() {
+        let segments = ScopeSegments::parse("Segments:CHAN1, 1, 2").unwrap().unwrap();
+        let rigol = segments.to_scpi(ScopeDialect::Rigol);
+        assert_eq!(rigol.len(), 2);
+        assert_eq!(rigol[1].0, 2);
+        assert_eq!(rigol[1].1, vec![":FUNC:WREP:FCUR 2", ":WAV:SOUR CHAN1", ":WAV:DATA?"]);
+        let siglent = segments.to_scpi(ScopeDialect::Siglent);
+        assert_eq!(siglent[0].1, vec!["FRAM 1", "C1:WF? DAT2"]);
+    }
+
+    #[test]
+    fn test_parse_waveform() {
+        assert_eq!(
+            ScopeWaveform::parse("Waveform:chan3").unwrap(),
+            Some(ScopeWaveform { source: "CHAN3".into() })
+        );
+        assert_eq!(ScopeWaveform::parse(":WAV:DATA?").unwrap(), None);
+        assert!(ScopeWaveform::parse("Waveform:CHAN5").is_err());
+    }
+
+    #[test]
+    fn test_waveform_chunks() {
+        let waveform = ScopeWaveform::parse("Waveform:CHAN1").unwrap().unwrap();
+        let chunks = waveform.chunk_commands(600_000);
+        assert_eq!(chunks.len(), 3);
+        assert_eq!(chunks[0], vec![":WAV:STAR 1", ":WAV:STOP 250000", ":WAV:DATA?"]);
+        assert_eq!(chunks[2], vec![":WAV:STAR 500001", ":WAV:STOP 600000", ":WAV:DATA?"]);
+        assert_eq!(waveform.chunk_commands(1200).len(), 1);
+    }
+}
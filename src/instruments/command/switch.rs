@@ -0,0 +1,95 @@
+use crate::error::ApplicationError;
+
+/**
+ * Enum representing relay / switch matrix commands, e.g. `Close:1,4`, `Open:2` or `Open:all`.
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SwitchCommand {
+    Close(Vec<u16>),
+    Open(Vec<u16>),
+    OpenAll,
+}
+
+impl SwitchCommand {
+    /**
+     * Parses a comma separated channel list.
+     *
+     * # Arguments
+     * `channels` - The channel list.
+     *
+     * # Returns
+     * A Result containing the channels or an ApplicationError.
+     */
+    fn parse_channels(channels: &str) -> Result<Vec<u16>, ApplicationError> {
+        let channels = channels
+            .split(',')
+            .map(|channel| {
+                channel
+                    .trim()
+                    .parse::<u16>()
+                    .map_err(|e| ApplicationError::ParseInt(format!("Invalid channel {}", channel.trim()), e))
+            })
+            .collect::<Result<Vec<u16>, ApplicationError>>()?;
+        Ok(channels)
+    }
+
+    /**
+     * Serializes the command into the SCPI route command used by switch matrices and DAQ units.
+     *
+     * # Returns
+     * The SCPI command.
+     */
+    #[cfg(feature = "usb")]
+    pub fn to_scpi(&self) -> String {
+        let list = |channels: &[u16]| {
+            channels
+                .iter()
+                .map(|channel| channel.to_string())
+                .collect::<Vec<String>>()
+                .join(",")
+        };
+        match self {
+            SwitchCommand::Close(channels) => format!("ROUT:CLOS (@{})", list(channels)),
+            SwitchCommand::Open(channels) => format!("ROUT:OPEN (@{})", list(channels)),
+            SwitchCommand::OpenAll => "ROUT:OPEN:ALL".to_string(),
+        }
+    }
+}
+
+impl TryFrom<&str> for SwitchCommand {
+    type Error = ApplicationError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value.split_once(':') {
+            Some(("Close", channels)) => Ok(SwitchCommand::Close(Self::parse_channels(channels)?)),
+            Some(("Open", channels)) if channels.trim().eq_ignore_ascii_case("all") => Ok(SwitchCommand::OpenAll),
+            Some(("Open", channels)) => Ok(SwitchCommand::Open(Self::parse_channels(channels)?)),
+            _ => Err(ApplicationError::Command(format!(
+                "Unknown command: {}",
+                value
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_try_from_command() {
+        assert_eq!(SwitchCommand::try_from("Close:1,4").unwrap(), SwitchCommand::Close(vec![1, 4]));
+        assert_eq!(SwitchCommand::try_from("Open: 2").unwrap(), SwitchCommand::Open(vec![2]));
+        assert_eq!(SwitchCommand::try_from("Open:all").unwrap(), SwitchCommand::OpenAll);
+        assert!(SwitchCommand::try_from("Close:x").is_err());
+        assert!(SwitchCommand::try_from("Toggle:1").is_err());
+    }
+
+    #[cfg(feature = "usb")]
+    #[test]
+    fn test_to_scpi() {
+        assert_eq!(SwitchCommand::Close(vec![101, 104]).to_scpi(), "ROUT:CLOS (@101,104)");
+        assert_eq!(SwitchCommand::Open(vec![101]).to_scpi(), "ROUT:OPEN (@101)");
+        assert_eq!(SwitchCommand::OpenAll.to_scpi(), "ROUT:OPEN:ALL");
+    }
+}
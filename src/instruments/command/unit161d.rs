@@ -1,4 +1,12 @@
-use crate::error::ApplicationError;
+use std::time::Duration;
+
+use crate::{
+    error::ApplicationError,
+    instruments::command::{
+        readback::{Expected, Readback},
+        Indicate,
+    },
+};
 
 /**
  * Enum representing various commands for the Uni-T 161D instrument.
@@ -19,6 +27,75 @@ pub enum Uni161dCommand {
     NotPeak = 78,
 }
 
+impl Uni161dCommand {
+    /**
+     * Checks a command: a key press, a MinMaxSession or an Indicate command.
+     *
+     * # Arguments
+     * `command` - The command string.
+     *
+     * # Returns
+     * A Result indicating success or an ApplicationError if the command is invalid.
+     */
+    pub fn check(command: &str) -> Result<(), ApplicationError> {
+        match (Indicate::parse(command)?, MinMaxSession::parse(command)?) {
+            (None, None) => Uni161dCommand::try_from(command.to_string()).map(|_| ()),
+            _ => Ok(()),
+        }
+    }
+
+    /**
+     * Gets the measurement verifying a key press: Hold and Rel toggle their flag, Auto sets and
+     * Range clears the auto flag.
+     *
+     * # Returns
+     * The readback, or None if the key has no flag.
+     */
+    pub fn readback(&self) -> Option<Readback> {
+        let expected = match self {
+            Uni161dCommand::Hold => Expected::Toggled("hold"),
+            Uni161dCommand::Rel => Expected::Toggled("rel"),
+            Uni161dCommand::Auto => Expected::Flag("auto", true),
+            Uni161dCommand::Range => Expected::Flag("auto", false),
+            _ => return None,
+        };
+        Some(Readback::new("Measure", expected))
+    }
+}
+
+/**
+ * Min/max session of the Uni-T 161D, e.g. `MinMaxSession:60`: MinMax is enabled, the meter is
+ * measured for the given number of seconds and MinMax is disabled again, giving a summary of
+ * the values seen.
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MinMaxSession {
+    pub duration: Duration,
+}
+
+impl MinMaxSession {
+    /**
+     * Parses a MinMaxSession command, `MinMaxSession:<seconds>` or `MinMaxSession <seconds>`.
+     *
+     * # Arguments
+     * `command` - The command string.
+     *
+     * # Returns
+     * A Result containing Some(MinMaxSession) for MinMaxSession commands, None for other
+     * commands, or an ApplicationError if the duration is invalid.
+     */
+    pub fn parse(command: &str) -> Result<Option<Self>, ApplicationError> {
+        let Some(seconds) = command.strip_prefix("MinMaxSession") else {
+            return Ok(None);
+        };
+        let seconds = seconds.strip_prefix(':').unwrap_or(seconds).trim();
+        match seconds.parse::<f64>().ok().and_then(|seconds| Duration::try_from_secs_f64(seconds).ok()) {
+            Some(duration) if !duration.is_zero() => Ok(Some(Self { duration })),
+            _ => Err(ApplicationError::Command(format!("Invalid MinMaxSession duration: {}", seconds))),
+        }
+    }
+}
+
 impl TryFrom<String> for Uni161dCommand {
     type Error = ApplicationError;
 
@@ -46,7 +123,9 @@ impl TryFrom<String> for Uni161dCommand {
 
 #[cfg(test)]
 mod test {
-    use crate::instruments::command::unit161d::Uni161dCommand;
+    use std::time::Duration;
+
+    use crate::instruments::command::unit161d::{MinMaxSession, Uni161dCommand};
 
     #[test]
     fn test_try_from_command() {
@@ -60,4 +139,20 @@ mod test {
         );
         assert!(Uni161dCommand::try_from("Unknown".to_string()).is_err());
     }
+
+    #[test]
+    fn test_parse_min_max_session() {
+        assert_eq!(
+            MinMaxSession::parse("MinMaxSession:60").unwrap(),
+            Some(MinMaxSession { duration: Duration::from_secs(60) })
+        );
+        assert_eq!(
+            MinMaxSession::parse("MinMaxSession 0.5").unwrap(),
+            Some(MinMaxSession { duration: Duration::from_millis(500) })
+        );
+        assert_eq!(MinMaxSession::parse("MinMax").unwrap(), None);
+        assert!(MinMaxSession::parse("MinMaxSession:0").is_err());
+        assert!(MinMaxSession::parse("MinMaxSession:-1").is_err());
+        assert!(MinMaxSession::parse("MinMaxSession").is_err());
+    }
 }
\ No newline at end of file
@@ -0,0 +1,97 @@
+use std::time::Duration;
+
+use crate::error::ApplicationError;
+
+/**
+ * Default time to wait for a service request.
+ */
+const DEFAULT_SERVICE_REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/**
+ * Value of bNotify1 in a USB488 interrupt-IN packet signalling a service request.
+ */
+const NOTIFY_SERVICE_REQUEST: u8 = 0x81;
+
+/**
+ * Represents a wait for a USB488 service request (SRQ) on the interrupt-IN endpoint,
+ * e.g. `WaitSrq` or `WaitSrq:5000` with a timeout in milliseconds.
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WaitServiceRequest {
+    pub timeout: Duration,
+}
+
+impl WaitServiceRequest {
+    /**
+     * Parses a WaitSrq command.
+     *
+     * # Arguments
+     * `command` - The command string.
+     *
+     * # Returns
+     * A Result containing Some(WaitServiceRequest) for WaitSrq commands, None for other commands,
+     * or an ApplicationError if the timeout is invalid.
+     */
+    pub fn parse(command: &str) -> Result<Option<Self>, ApplicationError> {
+        if command == "WaitSrq" {
+            return Ok(Some(Self {
+                timeout: DEFAULT_SERVICE_REQUEST_TIMEOUT,
+            }));
+        }
+        let Some(timeout) = command.strip_prefix("WaitSrq:") else {
+            return Ok(None);
+        };
+        let timeout = timeout
+            .trim()
+            .parse::<u64>()
+            .map_err(|e| ApplicationError::ParseInt(format!("Invalid timeout {}", timeout), e))?;
+        Ok(Some(Self {
+            timeout: Duration::from_millis(timeout),
+        }))
+    }
+}
+
+/**
+ * Extracts the status byte from a USB488 interrupt-IN packet.
+ *
+ * # Arguments
+ * `packet` - The interrupt packet (bNotify1, bNotify2).
+ *
+ * # Returns
+ * Some(status byte) if the packet is a service request notification, otherwise None.
+ */
+pub fn service_request_status(packet: &[u8]) -> Option<u8> {
+    match packet {
+        [NOTIFY_SERVICE_REQUEST, status_byte, ..] => Some(*status_byte),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_wait_srq() {
+        assert_eq!(
+            WaitServiceRequest::parse("WaitSrq:500").unwrap(),
+            Some(WaitServiceRequest {
+                timeout: Duration::from_millis(500)
+            })
+        );
+        assert_eq!(
+            WaitServiceRequest::parse("WaitSrq").unwrap().unwrap().timeout,
+            DEFAULT_SERVICE_REQUEST_TIMEOUT
+        );
+        assert_eq!(WaitServiceRequest::parse("*STB?").unwrap(), None);
+        assert!(WaitServiceRequest::parse("WaitSrq:soon").is_err());
+    }
+
+    #[test]
+    fn test_service_request_status() {
+        assert_eq!(service_request_status(&[0x81, 0x60]), Some(0x60));
+        // Response to READ_STATUS_BYTE with bTag 2
+        assert_eq!(service_request_status(&[0x82, 0x40]), None);
+        assert_eq!(service_request_status(&[0x81]), None);
+    }
+}
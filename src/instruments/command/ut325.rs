@@ -0,0 +1,120 @@
+use crate::error::ApplicationError;
+
+// Header of the command and response frames
+#[cfg(feature = "serial")]
+pub const UT325_FRAME_START: [u8; 2] = [0xAB, 0xCD];
+
+// Command bytes, repeated as the first payload byte of the response
+pub const UT325_MEASURE: u8 = 0x5E;
+#[cfg(feature = "serial")]
+pub const UT325_DOWNLOAD: u8 = 0x60;
+pub const UT325_RECORD: u8 = 0x61;
+
+/**
+ * Enum representing the value of a UT325 reading used as its typed measurement.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Ut325Channel {
+    /// Thermocouple input T1.
+    #[default]
+    T1,
+    /// Thermocouple input T2.
+    T2,
+    /// Difference T1 - T2.
+    Difference,
+}
+
+impl TryFrom<&str> for Ut325Channel {
+    type Error = ApplicationError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value.trim() {
+            "T1" => Ok(Ut325Channel::T1),
+            "T2" => Ok(Ut325Channel::T2),
+            "T1-T2" => Ok(Ut325Channel::Difference),
+            _ => Err(ApplicationError::Command(format!("Unknown UT325 channel {}, expected T1, T2 or T1-T2", value))),
+        }
+    }
+}
+
+/**
+ * Enum representing the commands of UNI-T UT325 dual thermocouple loggers.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub enum Ut325Command {
+    /// Reads T1, T2 and T1 - T2; the channel is the typed measurement.
+    Measure(Ut325Channel),
+    /// Downloads the records logged in the meter's memory, one reading per record.
+    Download(Ut325Channel),
+}
+
+#[cfg(feature = "serial")]
+impl Ut325Command {
+    /**
+     * Serializes the command into the frame sent to the meter: the header, the length of the
+     * command byte and checksum, the command byte and the 16 bit big endian sum of the
+     * preceding bytes.
+     *
+     * # Returns
+     * The frame.
+     */
+    pub fn to_wire(&self) -> Vec<u8> {
+        let command = match self {
+            Ut325Command::Measure(_) => UT325_MEASURE,
+            Ut325Command::Download(_) => UT325_DOWNLOAD,
+        };
+        let mut frame = vec![UT325_FRAME_START[0], UT325_FRAME_START[1], 0x03, command];
+        let sum = frame.iter().map(|byte| *byte as u16).sum::<u16>();
+        frame.extend_from_slice(&sum.to_be_bytes());
+        frame
+    }
+
+    /**
+     * Returns the channel used as the typed measurement of the readings.
+     *
+     * # Returns
+     * The channel.
+     */
+    pub fn channel(&self) -> Ut325Channel {
+        match self {
+            Ut325Command::Measure(channel) | Ut325Command::Download(channel) => *channel,
+        }
+    }
+}
+
+impl TryFrom<&str> for Ut325Command {
+    type Error = ApplicationError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        let (name, channel) = match value.split_once(':') {
+            Some((name, channel)) => (name, Ut325Channel::try_from(channel)?),
+            None => (value, Ut325Channel::default()),
+        };
+        match name {
+            "Measure" => Ok(Ut325Command::Measure(channel)),
+            "Download" => Ok(Ut325Command::Download(channel)),
+            _ => Err(ApplicationError::Command(format!("Unknown command: {}", value))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_try_from_command() {
+        assert_eq!(Ut325Command::try_from("Measure").unwrap(), Ut325Command::Measure(Ut325Channel::T1));
+        assert_eq!(Ut325Command::try_from("Measure:T1-T2").unwrap(), Ut325Command::Measure(Ut325Channel::Difference));
+        assert_eq!(Ut325Command::try_from("Download:T2").unwrap(), Ut325Command::Download(Ut325Channel::T2));
+        assert!(Ut325Command::try_from("Measure:T3").is_err());
+        assert!(Ut325Command::try_from("Hold").is_err());
+    }
+
+    #[cfg(feature = "serial")]
+    #[test]
+    fn test_to_wire() {
+        assert_eq!(Ut325Command::Measure(Ut325Channel::T1).to_wire(), [0xAB, 0xCD, 0x03, 0x5E, 0x01, 0xD9]);
+        assert_eq!(Ut325Command::Download(Ut325Channel::T1).to_wire(), [0xAB, 0xCD, 0x03, 0x60, 0x01, 0xDB]);
+    }
+}
@@ -0,0 +1,140 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+
+use crate::{
+    arguments::Args,
+    error::ApplicationError,
+    instruments::{
+        command::AtorchCommand,
+        communication::{
+            common::{DeviceInfo, get_uart_config, Communication},
+            registry::Requirement,
+            serial::SerialLine,
+        },
+        reading::{AtorchReading, Reading, ATORCH_FRAME_LENGTH},
+    },
+};
+
+/**
+ * Timeout of a single read from the serial port.
+ */
+const ATORCH_READ_TIMEOUT: Duration = Duration::from_millis(50);
+
+/**
+ * Longest time the load takes to answer a frame.
+ */
+const ATORCH_RESPONSE_LIMIT: Duration = Duration::from_millis(500);
+
+/**
+ * Byte acknowledging a setting command.
+ */
+const ACKNOWLEDGE: u8 = 0x6F;
+
+/**
+ * Module for Atorch DL24 / PX100 style electronic loads on their USB serial port.
+ */
+pub struct AtorchLoad {
+    /**
+     * Serial port of the load.
+     */
+    line: SerialLine,
+}
+
+impl AtorchLoad {
+    /**
+     * Creates a new instance of AtorchLoad.
+     *
+     * # Arguments
+     * `line` - The opened serial port.
+     *
+     * # Returns
+     * A new AtorchLoad instance.
+     */
+    pub fn new(line: SerialLine) -> Self {
+        Self { line }
+    }
+
+    /**
+     * Sends the frames of a typed command. Settings are checked for the acknowledge byte.
+     *
+     * # Arguments
+     * `command` - The typed command.
+     *
+     * # Returns
+     * A Result containing the value frames of the queries in order or an ApplicationError.
+     */
+    fn send(&self, command: &AtorchCommand) -> Result<Vec<Vec<u8>>, ApplicationError> {
+        let mut responses = Vec::new();
+        for frame in command.to_wire() {
+            self.line.write(&frame)?;
+            let query = frame[2] >= 0x10;
+            let length = if query { ATORCH_FRAME_LENGTH } else { 1 };
+            let response = self.line.read_response(|data| data.len() >= length, ATORCH_RESPONSE_LIMIT)?;
+            match query {
+                true => responses.push(response),
+                false if response == [ACKNOWLEDGE] => {}
+                false => {
+                    return Err(ApplicationError::Command(format!(
+                        "The load did not acknowledge {:?}: {:02X?}",
+                        command, response
+                    )))
+                }
+            }
+        }
+        Ok(responses)
+    }
+}
+
+#[async_trait(?Send)]
+impl Communication for AtorchLoad {
+    /**
+     * Sends typed commands to the load.
+     *
+     * # Arguments
+     * `commands` - The commands to execute.
+     *
+     * # Returns
+     * A Result containing an optional vector of Reading trait objects or an ApplicationError.
+     */
+    async fn command(
+        &self,
+        commands: Vec<String>,
+    ) -> Result<Option<Vec<Box<dyn Reading>>>, ApplicationError> {
+        let mut readings: Vec<Box<dyn Reading>> = Vec::new();
+        for command in commands {
+            let command = AtorchCommand::try_from(command.as_str())?;
+            let responses = self.send(&command)?;
+            match command {
+                AtorchCommand::Measure => readings.push(Box::new(AtorchReading::measured(&responses)?)),
+                AtorchCommand::Setpoints => readings.push(Box::new(AtorchReading::setpoints(&responses)?)),
+                _ => {}
+            }
+        }
+        Ok(Some(readings))
+    }
+
+    /**
+     * Returns the USB descriptor strings of the serial adapter.
+     *
+     * # Returns
+     * The device info, or None if the port is not a USB adapter.
+     */
+    fn device_info(&self) -> Option<DeviceInfo> {
+        self.line.device_info()
+    }
+}
+
+/**
+ * Opens the load on the serial port of the arguments.
+ *
+ * # Arguments
+ * `args` - An Args struct containing the configuration for the desired device.
+ *
+ * # Returns
+ * A Result containing a boxed Communication trait object or an ApplicationError.
+ */
+pub(crate) async fn open(args: &Args) -> Result<Box<dyn Communication>, ApplicationError> {
+    let line = SerialLine::open(Requirement::Serial.argument(args)?, &get_uart_config(args), ATORCH_READ_TIMEOUT)?;
+    Ok(Box::new(AtorchLoad::new(line)))
+}
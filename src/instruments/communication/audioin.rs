@@ -0,0 +1,239 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use async_trait::async_trait;
+use cpal::{
+    traits::{DeviceTrait, HostTrait, StreamTrait},
+    SampleFormat, Stream, StreamConfig,
+};
+use tracing::{info, warn};
+
+use crate::{
+    arguments::{self, Args},
+    error::ApplicationError,
+    instruments::{
+        communication::common::Communication,
+        reading::{AnalysisReading, AudioReading, Reading},
+    },
+};
+
+/**
+ * Module for using a sound card input as a measurement source.
+ */
+pub struct AudioIn {
+    /**
+     * Sound card input device.
+     */
+    device: cpal::Device,
+    /**
+     * Reader type. AudioReading is produced if None.
+     */
+    reader: Option<arguments::Reader>,
+    /**
+     * Factor converting a full scale sample to volts.
+     */
+    calibration: f64,
+    /**
+     * Duration of each capture.
+     */
+    duration: Duration,
+}
+
+impl AudioIn {
+    /**
+     * Creates a new instance of AudioIn.
+     *
+     * # Arguments
+     * `device_name` - Name of the input device. The default input device is used if None.
+     * `reader` - Reader type. AudioReading is produced if None.
+     * `calibration` - Factor converting a full scale sample to volts.
+     * `duration` - Duration of each capture.
+     *
+     * # Returns
+     * A new AudioIn instance.
+     */
+    pub fn new(
+        device_name: Option<&str>,
+        reader: Option<arguments::Reader>,
+        calibration: f64,
+        duration: Duration,
+    ) -> Result<Self, ApplicationError> {
+        let host = cpal::default_host();
+        let device = match device_name {
+            Some(name) => host
+                .input_devices()
+                .map_err(|e| ApplicationError::Audio(format!("Could not list input devices: {}", e)))?
+                .find(|device| device.name().map(|device_name| device_name == name).unwrap_or(false))
+                .ok_or_else(|| ApplicationError::NotFound(format!("Input device {}", name)))?,
+            None => host
+                .default_input_device()
+                .ok_or_else(|| ApplicationError::Audio("No default input device".into()))?,
+        };
+        info!("Using input device {}", device.name().unwrap_or_default());
+        Ok(Self {
+            device,
+            reader,
+            calibration,
+            duration,
+        })
+    }
+
+    /**
+     * Builds an input stream that appends the first channel of every frame to the sample buffer.
+     *
+     * # Arguments
+     * `config` - The stream configuration.
+     * `sample_format` - The sample format of the device.
+     * `samples` - Shared sample buffer.
+     *
+     * # Returns
+     * The input stream.
+     */
+    fn build_stream(
+        &self,
+        config: &StreamConfig,
+        sample_format: SampleFormat,
+        samples: Arc<Mutex<Vec<f32>>>,
+    ) -> Result<Stream, ApplicationError> {
+        let channels = config.channels as usize;
+        let error_callback = |e| warn!("Audio stream error: {}", e);
+        let stream = match sample_format {
+            SampleFormat::F32 => self.device.build_input_stream(
+                config,
+                move |data: &[f32], _: &cpal::InputCallbackInfo| {
+                    if let Ok(mut samples) = samples.lock() {
+                        samples.extend(data.iter().step_by(channels));
+                    }
+                },
+                error_callback,
+                None,
+            ),
+            SampleFormat::I16 => self.device.build_input_stream(
+                config,
+                move |data: &[i16], _: &cpal::InputCallbackInfo| {
+                    if let Ok(mut samples) = samples.lock() {
+                        samples.extend(data.iter().step_by(channels).map(|sample| *sample as f32 / i16::MAX as f32));
+                    }
+                },
+                error_callback,
+                None,
+            ),
+            SampleFormat::U16 => self.device.build_input_stream(
+                config,
+                move |data: &[u16], _: &cpal::InputCallbackInfo| {
+                    if let Ok(mut samples) = samples.lock() {
+                        samples.extend(
+                            data.iter()
+                                .step_by(channels)
+                                .map(|sample| (*sample as f32 - 32768.0) / 32768.0),
+                        );
+                    }
+                },
+                error_callback,
+                None,
+            ),
+            format => {
+                return Err(ApplicationError::Audio(format!(
+                    "Unsupported sample format {:?}",
+                    format
+                )))
+            }
+        };
+        stream.map_err(|e| ApplicationError::Audio(format!("Could not build input stream: {}", e)))
+    }
+
+    /**
+     * Captures samples for the configured duration.
+     *
+     * # Returns
+     * A Result containing the samples and the sample rate or an ApplicationError.
+     */
+    async fn capture(&self) -> Result<(Vec<f32>, u32), ApplicationError> {
+        let supported_config = self
+            .device
+            .default_input_config()
+            .map_err(|e| ApplicationError::Audio(format!("Could not get input configuration: {}", e)))?;
+        let config = supported_config.config();
+        let samples = Arc::new(Mutex::new(Vec::new()));
+        let stream = self.build_stream(&config, supported_config.sample_format(), samples.clone())?;
+        stream
+            .play()
+            .map_err(|e| ApplicationError::Audio(format!("Could not start input stream: {}", e)))?;
+        tokio::time::sleep(self.duration).await;
+        drop(stream);
+        let samples = samples
+            .lock()
+            .map_err(|e| ApplicationError::Audio(format!("Could not read samples: {}", e)))?
+            .clone();
+        Ok((samples, config.sample_rate.0))
+    }
+}
+
+#[async_trait(?Send)]
+impl Communication for AudioIn {
+    /**
+     * Captures a block of samples for each Measure command.
+     *
+     * # Arguments
+     * `commands` - The commands to execute. Only Measure is supported.
+     *
+     * # Returns
+     * A Result containing an optional vector of Reading trait objects or an ApplicationError.
+     */
+    async fn command(
+        &self,
+        commands: Vec<String>,
+    ) -> Result<Option<Vec<Box<dyn Reading>>>, ApplicationError> {
+        let mut readings: Vec<Box<dyn Reading>> = Vec::new();
+        for command in commands {
+            match command.as_str() {
+                "Measure" => {
+                    let (samples, sample_rate) = self.capture().await?;
+                    let reading = AudioReading::new(samples, sample_rate, self.calibration);
+                    match self.reader {
+                        Some(arguments::Reader::Analysis) => readings.push(Box::new(AnalysisReading::new(
+                            &reading.samples,
+                            sample_rate as f64,
+                        )?)),
+                        Some(
+                            arguments::Reader::ScpiRawReader
+                            | arguments::Reader::ScreenshotReader
+                            | arguments::Reader::ScpiBlockReader
+                            | arguments::Reader::ScpiIdnReader
+                            | arguments::Reader::KeyValueReader,
+                        ) => {
+                            return Err(ApplicationError::Command(format!(
+                                "{:?} is not supported for AudioIn",
+                                self.reader
+                            )))
+                        }
+                        None => readings.push(Box::new(reading)),
+                    }
+                }
+                _ => return Err(ApplicationError::Command(format!("Unknown command: {}", command))),
+            }
+        }
+        Ok(Some(readings))
+    }
+}
+
+/**
+ * Opens the sound card input of the arguments.
+ *
+ * # Arguments
+ * `args` - An Args struct containing the configuration for the desired device.
+ *
+ * # Returns
+ * A Result containing a boxed Communication trait object or an ApplicationError.
+ */
+pub(crate) async fn open(args: &Args) -> Result<Box<dyn Communication>, ApplicationError> {
+    let audio_in = AudioIn::new(
+        args.connection.audio_device.as_deref(),
+        args.clone().readings.reader,
+        args.measurement.calibration,
+        Duration::from_millis(args.measurement.duration_ms),
+    )?;
+    Ok(Box::new(audio_in))
+}
@@ -0,0 +1,223 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use btleplug::{
+    api::{bleuuid::uuid_from_u16, Central, Characteristic, Manager as _, Peripheral as _, ScanFilter},
+    platform::{Manager, Peripheral},
+};
+use futures_util::StreamExt;
+use tracing::{debug, info};
+
+use crate::{
+    arguments::Args,
+    capture,
+    error::ApplicationError,
+    instruments::{
+        communication::{
+            common::Communication,
+            registry::Requirement,
+        },
+        reading::{OwonReading, Reading},
+    },
+    logging::hex_dump,
+};
+
+/**
+ * Service advertised by Owon BLE meters.
+ */
+const OWON_SERVICE: u16 = 0xfff0;
+
+/**
+ * Characteristic the Owon meters send their measurements on.
+ */
+const OWON_NOTIFY_CHARACTERISTIC: u16 = 0xfff4;
+
+/**
+ * Time to scan for the meter before giving up.
+ */
+const BLE_SCAN_TIMEOUT: Duration = Duration::from_secs(10);
+
+/**
+ * Time between checks of the discovered devices while scanning.
+ */
+const BLE_SCAN_INTERVAL: Duration = Duration::from_millis(250);
+
+/**
+ * Time to wait for a notification. The meters send two to three notifications per second.
+ */
+const BLE_NOTIFICATION_TIMEOUT: Duration = Duration::from_secs(3);
+
+/**
+ * Converts a btleplug error.
+ *
+ * # Arguments
+ * `error` - The btleplug error.
+ *
+ * # Returns
+ * The ApplicationError.
+ */
+fn ble_error(error: btleplug::Error) -> ApplicationError {
+    ApplicationError::Bluetooth(error.to_string())
+}
+
+/**
+ * Module for Owon B35T+ / B41T+ / OW18 series meters over Bluetooth LE. The meter streams
+ * measurement notifications; it cannot be controlled.
+ */
+pub struct OwonBle {
+    /**
+     * The connected meter.
+     */
+    peripheral: Peripheral,
+}
+
+impl OwonBle {
+    /**
+     * Scans for the meter, connects and subscribes to the measurement notifications.
+     *
+     * # Arguments
+     * `target` - The Bluetooth address or advertised name of the meter.
+     *
+     * # Returns
+     * A Result containing the OwonBle instance or an ApplicationError.
+     */
+    pub async fn connect(target: &str) -> Result<Self, ApplicationError> {
+        let peripheral = find_peripheral(target, OWON_SERVICE).await?;
+        info!("Connecting to Bluetooth device {}", target);
+        peripheral.connect().await.map_err(ble_error)?;
+        peripheral.discover_services().await.map_err(ble_error)?;
+        let characteristic = find_characteristic(&peripheral, OWON_NOTIFY_CHARACTERISTIC)?;
+        peripheral.subscribe(&characteristic).await.map_err(ble_error)?;
+        info!("Subscribed to the measurements of {}", target);
+        Ok(Self { peripheral })
+    }
+
+    /**
+     * Waits for the next measurement notification.
+     *
+     * # Returns
+     * A Result containing the notification bytes or an ApplicationError if none arrives in time.
+     */
+    async fn next_notification(&self) -> Result<Vec<u8>, ApplicationError> {
+        let mut notifications = self.peripheral.notifications().await.map_err(ble_error)?;
+        let uuid = uuid_from_u16(OWON_NOTIFY_CHARACTERISTIC);
+        tokio::time::timeout(BLE_NOTIFICATION_TIMEOUT, async {
+            while let Some(notification) = notifications.next().await {
+                if notification.uuid == uuid {
+                    debug!("Notification: {}", hex_dump(&notification.value));
+                    capture::read("ble", &notification.value);
+                    return Ok(notification.value);
+                }
+            }
+            Err(ApplicationError::Bluetooth("Meter disconnected".into()))
+        })
+        .await
+        .map_err(|_| ApplicationError::Bluetooth("No notification received from the meter".into()))?
+    }
+}
+
+#[async_trait(?Send)]
+impl Communication for OwonBle {
+    /**
+     * Reads a notification for each Measure command.
+     *
+     * # Arguments
+     * `commands` - The commands to execute, only Measure is supported.
+     *
+     * # Returns
+     * A Result containing an optional vector of Reading trait objects or an ApplicationError.
+     */
+    async fn command(
+        &self,
+        commands: Vec<String>,
+    ) -> Result<Option<Vec<Box<dyn Reading>>>, ApplicationError> {
+        let mut readings: Vec<Box<dyn Reading>> = Vec::new();
+        for command in commands {
+            if command != "Measure" {
+                return Err(ApplicationError::Command(format!(
+                    "Unsupported command for Owon BLE meters: {}",
+                    command
+                )));
+            }
+            let notification = self.next_notification().await?;
+            readings.push(Box::new(OwonReading::parse(&notification)?));
+        }
+        Ok(Some(readings))
+    }
+}
+
+/**
+ * Scans for a device advertising a service until one matches the address or name.
+ *
+ * # Arguments
+ * `target` - The Bluetooth address or advertised name.
+ * `service` - The 16 bit UUID of the advertised service.
+ *
+ * # Returns
+ * A Result containing the peripheral or an ApplicationError if no adapter or device is found.
+ */
+async fn find_peripheral(target: &str, service: u16) -> Result<Peripheral, ApplicationError> {
+    let manager = Manager::new().await.map_err(ble_error)?;
+    let central = manager
+        .adapters()
+        .await
+        .map_err(ble_error)?
+        .into_iter()
+        .next()
+        .ok_or_else(|| ApplicationError::Bluetooth("No Bluetooth adapter found".into()))?;
+    central
+        .start_scan(ScanFilter {
+            services: vec![uuid_from_u16(service)],
+        })
+        .await
+        .map_err(ble_error)?;
+    let deadline = tokio::time::Instant::now() + BLE_SCAN_TIMEOUT;
+    while tokio::time::Instant::now() < deadline {
+        for peripheral in central.peripherals().await.map_err(ble_error)? {
+            let Some(properties) = peripheral.properties().await.map_err(ble_error)? else {
+                continue;
+            };
+            if properties.address.to_string().eq_ignore_ascii_case(target)
+                || properties.local_name.as_deref() == Some(target)
+            {
+                central.stop_scan().await.map_err(ble_error)?;
+                return Ok(peripheral);
+            }
+        }
+        tokio::time::sleep(BLE_SCAN_INTERVAL).await;
+    }
+    central.stop_scan().await.map_err(ble_error)?;
+    Err(ApplicationError::NotFound(format!("Bluetooth device {}", target)))
+}
+
+/**
+ * Finds a characteristic of a connected peripheral.
+ *
+ * # Arguments
+ * `peripheral` - The peripheral with discovered services.
+ * `characteristic` - The 16 bit UUID of the characteristic.
+ *
+ * # Returns
+ * A Result containing the characteristic or an ApplicationError if the device does not have it.
+ */
+fn find_characteristic(peripheral: &Peripheral, characteristic: u16) -> Result<Characteristic, ApplicationError> {
+    let uuid = uuid_from_u16(characteristic);
+    peripheral
+        .characteristics()
+        .into_iter()
+        .find(|found| found.uuid == uuid)
+        .ok_or_else(|| ApplicationError::Bluetooth(format!("Characteristic {} not found", uuid)))
+}
+
+/**
+ * Connects to the meter with the Bluetooth address or name of the arguments.
+ *
+ * # Arguments
+ * `args` - An Args struct containing the configuration for the desired device.
+ *
+ * # Returns
+ * A Result containing a boxed Communication trait object or an ApplicationError.
+ */
+pub(crate) async fn open(args: &Args) -> Result<Box<dyn Communication>, ApplicationError> {
+    Ok(Box::new(OwonBle::connect(Requirement::Ble.argument(args)?).await?))
+}
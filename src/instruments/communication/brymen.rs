@@ -0,0 +1,150 @@
+use std::ffi::CString;
+
+use async_trait::async_trait;
+use tracing::info;
+
+use crate::{
+    arguments::Args,
+    capture,
+    error::ApplicationError,
+    instruments::{
+        communication::{
+            common::{hid_device_info, Communication, DeviceInfo},
+            registry::Requirement,
+        },
+        reading::{BrymenReading, Reading},
+    },
+};
+
+/**
+ * Feature report requesting a frame, report id 0 followed by the 4 byte request.
+ */
+const BRYMEN_REQUEST: [u8; 5] = [0x00, 0x00, 0x00, 0x86, 0x66];
+
+/**
+ * Length of the input reports a frame is split into.
+ */
+const BRYMEN_REPORT_LENGTH: usize = 8;
+
+/**
+ * Time to wait for each input report in milliseconds.
+ */
+const BRYMEN_READ_TIMEOUT_MS: i32 = 1000;
+
+/**
+ * Module for Brymen BM869s / BM867s meters with the BU-86X IR-to-USB cable (0820:0001).
+ * A frame is requested with a feature report and received as three input reports.
+ */
+pub struct BrymenHid {
+    // HID Device instance
+    hiddevice: hidapi::HidDevice,
+}
+
+impl BrymenHid {
+    /**
+     * Creates a new instance of BrymenHid with the given HID device path.
+     *
+     * # Arguments
+     * `hid_device_path` - A string slice representing the path to the HID device.
+     *
+     * # Returns
+     * A new BrymenHid instance.
+     */
+    pub fn new(hid_device_path: &str) -> Result<Self, ApplicationError> {
+        let api = hidapi::HidApi::new_without_enumerate().map_err(|e| {
+            ApplicationError::HidApi("Failed to create HID API instance".into(), e)
+        })?;
+        let c_path = CString::new(hid_device_path.to_string()).map_err(|e| {
+            ApplicationError::Hid(format!(
+                "Failed to create CString for HID device path: {}",
+                e
+            ))
+        })?;
+        let hiddevice = api.open_path(&c_path).map_err(|e| {
+            ApplicationError::HidApi(format!("Failed to open HID device at {}", hid_device_path), e)
+        })?;
+        info!("Opened Brymen HID adapter at {}", hid_device_path);
+        Ok(BrymenHid { hiddevice })
+    }
+
+    /**
+     * Requests a frame and reads its input reports.
+     *
+     * # Returns
+     * A Result containing the frame bytes or an ApplicationError if the meter does not answer.
+     */
+    fn read_frame(&self) -> Result<Vec<u8>, ApplicationError> {
+        capture::written("hid", &BRYMEN_REQUEST);
+        self.hiddevice
+            .send_feature_report(&BRYMEN_REQUEST)
+            .map_err(|e| ApplicationError::HidApi("Failed to send feature report".into(), e))?;
+        let mut frame = Vec::with_capacity(3 * BRYMEN_REPORT_LENGTH);
+        for _ in 0..3 {
+            let mut report = [0u8; BRYMEN_REPORT_LENGTH];
+            let length = self
+                .hiddevice
+                .read_timeout(&mut report, BRYMEN_READ_TIMEOUT_MS)
+                .map_err(|e| ApplicationError::HidApi("Failed to read from HID device".into(), e))?;
+            capture::read("hid", &report[..length]);
+            if length != BRYMEN_REPORT_LENGTH {
+                return Err(ApplicationError::Hid(
+                    "No frame received, check that the meter is on and the cable is attached".into(),
+                ));
+            }
+            frame.extend_from_slice(&report);
+        }
+        Ok(frame)
+    }
+}
+
+#[async_trait(?Send)]
+impl Communication for BrymenHid {
+    /**
+     * Reads a frame for each Measure command.
+     *
+     * # Arguments
+     * `commands` - The commands to execute, only Measure is supported.
+     *
+     * # Returns
+     * A Result containing an optional vector of Reading trait objects or an ApplicationError.
+     */
+    async fn command(
+        &self,
+        commands: Vec<String>,
+    ) -> Result<Option<Vec<Box<dyn Reading>>>, ApplicationError> {
+        let mut readings: Vec<Box<dyn Reading>> = Vec::new();
+        for command in commands {
+            if command != "Measure" {
+                return Err(ApplicationError::Command(format!(
+                    "Unsupported command for Brymen meters: {}",
+                    command
+                )));
+            }
+            readings.push(Box::new(BrymenReading::parse(&self.read_frame()?)?));
+        }
+        Ok(Some(readings))
+    }
+
+    /**
+     * Returns the USB descriptor strings of the HID device.
+     *
+     * # Returns
+     * The device info.
+     */
+    fn device_info(&self) -> Option<DeviceInfo> {
+        hid_device_info(&self.hiddevice)
+    }
+}
+
+/**
+ * Opens the meter on the HID device of the arguments.
+ *
+ * # Arguments
+ * `args` - An Args struct containing the configuration for the desired device.
+ *
+ * # Returns
+ * A Result containing a boxed Communication trait object or an ApplicationError.
+ */
+pub(crate) async fn open(args: &Args) -> Result<Box<dyn Communication>, ApplicationError> {
+    Ok(Box::new(BrymenHid::new(Requirement::Hid.argument(args)?)?))
+}
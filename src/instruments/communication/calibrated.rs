@@ -0,0 +1,91 @@
+use async_trait::async_trait;
+
+use crate::{
+    error::ApplicationError,
+    instruments::{
+        communication::common::{Communication, DeviceInfo, TransferTimings},
+        reading::{reading_mode, CalibratedReading, Calibration, Reading},
+    },
+};
+
+/**
+ * Wraps a device so that its readings are corrected by the calibration entries of the device.
+ * Every reading is corrected by the entry of its mode, or else the entry without a mode;
+ * readings without an entry are passed through unchanged.
+ */
+pub struct CalibratedCommunication {
+    /**
+     * The wrapped device.
+     */
+    device: Box<dyn Communication>,
+    /**
+     * The calibration entries.
+     */
+    calibrations: Vec<Calibration>,
+}
+
+impl CalibratedCommunication {
+    /**
+     * Creates a new instance of CalibratedCommunication.
+     *
+     * # Arguments
+     * `device` - The device to wrap.
+     * `calibrations` - The calibration entries of the device.
+     *
+     * # Returns
+     * A new CalibratedCommunication instance.
+     */
+    pub fn new(device: Box<dyn Communication>, calibrations: Vec<Calibration>) -> Self {
+        Self { device, calibrations }
+    }
+}
+
+#[async_trait(?Send)]
+impl Communication for CalibratedCommunication {
+    /**
+     * Sends the commands and corrects the readings.
+     *
+     * # Arguments
+     * `commands` - The commands to execute.
+     *
+     * # Returns
+     * A Result containing an optional vector of Reading trait objects or an ApplicationError.
+     */
+    async fn command(
+        &self,
+        commands: Vec<String>,
+    ) -> Result<Option<Vec<Box<dyn Reading>>>, ApplicationError> {
+        let Some(readings) = self.device.command(commands).await? else {
+            return Ok(None);
+        };
+        let mut calibrated: Vec<Box<dyn Reading>> = Vec::new();
+        for reading in readings {
+            let mode = reading_mode(reading.as_ref())?;
+            calibrated.push(match Calibration::select(&self.calibrations, mode.as_deref()) {
+                Some(calibration) => Box::new(CalibratedReading::new(calibration.clone(), reading)),
+                None => reading,
+            });
+        }
+        Ok(Some(calibrated))
+    }
+
+    /**
+     * Returns the transfer timings of the wrapped device.
+     *
+     * # Returns
+     * The timings, or None.
+     */
+    fn transfer_timings(&self) -> Option<TransferTimings> {
+        self.device.transfer_timings()
+    }
+
+    /**
+     * Returns the device info of the wrapped device.
+     *
+     * # Returns
+     * The device info, or None.
+     */
+    fn device_info(&self) -> Option<DeviceInfo> {
+        self.device.device_info()
+    }
+}
@@ -0,0 +1,110 @@
+use async_trait::async_trait;
+
+use crate::{
+    error::ApplicationError,
+    instruments::{
+        communication::{
+            common::{Communication, DeviceInfo},
+            registry::Driver,
+        },
+        reading::Reading,
+    },
+};
+
+/**
+ * Wraps a device so that every command is checked by the driver before anything is sent: typed
+ * commands are parsed and SCPI commands are checked against the grammar of the instrument, if
+ * it has one. A command batch with an invalid command is rejected as a whole.
+ */
+pub struct CheckingCommunication {
+    /**
+     * The wrapped device.
+     */
+    device: Box<dyn Communication>,
+    /**
+     * The driver of the device.
+     */
+    driver: &'static Driver,
+}
+
+impl CheckingCommunication {
+    /**
+     * Creates a new instance of CheckingCommunication.
+     *
+     * # Arguments
+     * `device` - The device to wrap.
+     * `driver` - The driver of the device.
+     *
+     * # Returns
+     * A new CheckingCommunication instance.
+     */
+    pub fn new(device: Box<dyn Communication>, driver: &'static Driver) -> Self {
+        Self { device, driver }
+    }
+}
+
+#[async_trait(?Send)]
+impl Communication for CheckingCommunication {
+    /**
+     * Checks the commands and sends them to the wrapped device.
+     *
+     * # Arguments
+     * `commands` - The commands to execute.
+     *
+     * # Returns
+     * A Result containing an optional vector of Reading trait objects or an ApplicationError.
+     */
+    async fn command(
+        &self,
+        commands: Vec<String>,
+    ) -> Result<Option<Vec<Box<dyn Reading>>>, ApplicationError> {
+        for command in &commands {
+            (self.driver.check_command)(command)?;
+        }
+        self.device.command(commands).await
+    }
+
+    /**
+     * Returns the device info of the wrapped device.
+     *
+     * # Returns
+     * The device info, or None.
+     */
+    fn device_info(&self) -> Option<DeviceInfo> {
+        self.device.device_info()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{cell::RefCell, rc::Rc};
+
+    use super::*;
+    use crate::{arguments::Device, instruments::communication::registry::driver};
+
+    /**
+     * Device recording the commands it is sent.
+     */
+    struct Recorder {
+        sent: Rc<RefCell<Vec<String>>>,
+    }
+
+    #[async_trait(?Send)]
+    impl Communication for Recorder {
+        async fn command(&self, commands: Vec<String>) -> Result<Option<Vec<Box<dyn Reading>>>, ApplicationError> {
+            self.sent.borrow_mut().extend(commands);
+            Ok(None)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_invalid_batch_is_not_sent() {
+        let sent = Rc::new(RefCell::new(Vec::new()));
+        let recorder = Recorder { sent: sent.clone() };
+        let device = CheckingCommunication::new(Box::new(recorder), driver(&Device::Peaktech4055mvUsb).unwrap());
+        assert!(device.command(vec!["Output:Off".into(), "APLY:SIN 1000".into()]).await.is_err());
+        assert!(sent.borrow().is_empty());
+        device.command(vec!["APPL:SIN 1000,1".into(), "Output:On".into()]).await.unwrap();
+        assert_eq!(*sent.borrow(), ["APPL:SIN 1000,1", "Output:On"]);
+    }
+}
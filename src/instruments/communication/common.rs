@@ -1,14 +1,57 @@
+use std::{
+    collections::VecDeque,
+    path::Path,
+    time::Duration,
+};
+
 use async_trait::async_trait;
+use futures_util::stream::{self, LocalBoxStream, StreamExt};
+use tokio::time::{Instant, Interval, MissedTickBehavior};
+use tracing::{debug, info};
+
+use crate::{arguments::{Args, Reader}, error::ApplicationError, instruments::{command::{DangerRules, Limits}, communication::{calibrated::CalibratedCommunication, checked::CheckingCommunication, confirm::ConfirmingCommunication, limited::LimitedCommunication, paired::PairingCommunication, readonly::ReadOnlyCommunication, registry::driver, timed::TimedCommunication, scpi::ScpiOptions, verified::VerifyingCommunication}, reading::{Reading}}};
+#[cfg(any(feature = "usb", feature = "hid", feature = "serial", feature = "gpib"))]
+use crate::arguments::Terminator;
+#[cfg(any(feature = "serial", feature = "hid"))]
+use crate::{arguments::Parity, instruments::communication::serial::UartConfig};
+#[cfg(feature = "hid")]
+use crate::instruments::command::raw::parse_hex;
+#[cfg(feature = "usb")]
+use crate::{arguments::RemoteMode, instruments::{command::CommandSet, communication::{registry::Requirement, scpiusb::UsbEndpoints, usbdevice::{find_device, DeviceCache, UsbTarget}}}};
+
+#[cfg(feature = "usb")]
+const DEFAULT_LOCAL_COMMAND: &str = "SYST:LOC";
 
-use crate::{arguments::{Args, Device}, error::ApplicationError, instruments::{communication::{scpiusb::ScpiUsb, unit161d::Unit161dHid}, reading::{Reading}}};
+#[cfg(any(feature = "usb", feature = "serial", feature = "gpib"))]
+const DEFAULT_READ_TIMEOUT_MS: u64 = 10000;
+#[cfg(any(feature = "usb", feature = "gpib"))]
+const DEFAULT_MAX_RESPONSE_SIZE: usize = 2000000;
+
+/**
+ * Time between attempts to open a device that is not plugged in, with --wait-for-device.
+ */
+const WAIT_FOR_DEVICE_POLL: Duration = Duration::from_millis(500);
 
-const DEFAULT_USB_INTERFACE_NUM: u8 = 0;
-const DEFAULT_USB_BULK_IN_ADDRESS: u8 = 0x81;
-const DEFAULT_USB_BULK_OUT_ADDRESS: u8 = 0x01;
+/**
+ * Transfer timings of the last command measured by the transport: the time from submitting the
+ * command until its transfer completed and until the first byte of the response arrived.
+ */
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct TransferTimings {
+    pub write: Option<Duration>,
+    pub first_byte: Option<Duration>,
+}
 
-const PEAKTECH_4055MV_USB_INTERFACE_NUM: u8 = 0;
-const PEAKTECH_4055MV_USB_BULK_IN_ADDRESS: u8 = 0x82;
-const PEAKTECH_4055MV_USB_BULK_OUT_ADDRESS: u8 = 0x02;
+/**
+ * Identification of the hardware a device was opened on, from the USB descriptors of the
+ * instrument, HID device or serial adapter.
+ */
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DeviceInfo {
+    pub manufacturer: Option<String>,
+    pub product: Option<String>,
+    pub serial_number: Option<String>,
+}
 
 #[async_trait(?Send)]
 pub trait Communication {
@@ -25,10 +68,133 @@ pub trait Communication {
         &self,
         commands: Vec<String>,
     ) -> Result<Option<Vec<Box<dyn Reading>>>, ApplicationError>;
+
+    /**
+     * Returns the transfer timings of the last command, if the transport measures them.
+     *
+     * # Returns
+     * The timings, or None.
+     */
+    fn transfer_timings(&self) -> Option<TransferTimings> {
+        None
+    }
+
+    /**
+     * Returns the manufacturer, product and serial number of the hardware, if the transport has them.
+     *
+     * # Returns
+     * The device info, or None.
+     */
+    fn device_info(&self) -> Option<DeviceInfo> {
+        None
+    }
+
+    /**
+     * Streams readings: the commands are sent every interval and their readings yielded one by
+     * one. The commands are only sent when the consumer asks for more readings than are left, so
+     * a slow consumer delays the next cycle instead of piling up readings. A failing cycle
+     * yields its error and the stream goes on with the next cycle; the stream ends when it is
+     * dropped.
+     *
+     * # Arguments
+     * `commands` - The commands of a cycle.
+     * `interval` - The interval of the cycles, zero to start every cycle right after the previous one.
+     *
+     * # Returns
+     * A stream of readings or the ApplicationErrors of failing cycles.
+     */
+    fn readings(&self, commands: Vec<String>, interval: Duration) -> LocalBoxStream<'_, Result<Box<dyn Reading>, ApplicationError>> {
+        let ticks = (!interval.is_zero()).then(|| {
+            let mut ticks = tokio::time::interval(interval);
+            ticks.set_missed_tick_behavior(MissedTickBehavior::Delay);
+            ticks
+        });
+        reading_stream(self, commands, ticks)
+    }
+}
+
+/**
+ * State of a stream of readings.
+ */
+struct ReadingStream<'a, D: ?Sized> {
+    device: &'a D,
+    commands: Vec<String>,
+    ticks: Option<Interval>,
+    /**
+     * Readings of the last cycle not yielded yet.
+     */
+    pending: VecDeque<Box<dyn Reading>>,
+}
+
+/**
+ * Creates a stream of readings of a device, see `Communication::readings`.
+ *
+ * # Arguments
+ * `device` - The device.
+ * `commands` - The commands of a cycle.
+ * `ticks` - The interval of the cycles, None to start every cycle right after the previous one.
+ *
+ * # Returns
+ * A stream of readings or the ApplicationErrors of failing cycles.
+ */
+fn reading_stream<D: Communication + ?Sized>(
+    device: &D,
+    commands: Vec<String>,
+    ticks: Option<Interval>,
+) -> LocalBoxStream<'_, Result<Box<dyn Reading>, ApplicationError>> {
+    let state = ReadingStream { device, commands, ticks, pending: VecDeque::new() };
+    stream::unfold(state, |mut state| async move {
+        loop {
+            if let Some(reading) = state.pending.pop_front() {
+                return Some((Ok(reading), state));
+            }
+            match &mut state.ticks {
+                Some(ticks) => {
+                    ticks.tick().await;
+                }
+                // Let other tasks run between cycles that may not wait for anything
+                None => tokio::task::yield_now().await,
+            }
+            match state.device.command(state.commands.clone()).await {
+                Ok(readings) => state.pending.extend(readings.unwrap_or_default()),
+                Err(e) => return Some((Err(e), state)),
+            }
+        }
+    })
+    .boxed_local()
+}
+
+/**
+ * Gets the USB descriptor strings of a HID device.
+ *
+ * # Arguments
+ * `device` - The HID device.
+ *
+ * # Returns
+ * The device info.
+ */
+#[cfg(feature = "hid")]
+pub(crate) fn hid_device_info(device: &hidapi::HidDevice) -> Option<DeviceInfo> {
+    Some(DeviceInfo {
+        manufacturer: device.get_manufacturer_string().ok().flatten(),
+        product: device.get_product_string().ok().flatten(),
+        serial_number: device.get_serial_number_string().ok().flatten(),
+    })
 }
 
 /**
  * Factory function to create a Communication device based on the provided arguments.
+ * With --verify settings are read back after they are sent, and the readings are corrected by
+ * the calibration entries of the profile, if any. With --latency the readings are tagged with
+ * the timings of their command, and with --pair they are paired with the setpoints before them.
+ * Commands are checked against the voltage and current limits of the device, if any, and
+ * dangerous commands must be confirmed if --confirm-dangerous is given. With --check-commands
+ * every batch is checked by the driver before it is sent, and with --read-only batches with
+ * commands that may change the device are refused.
+ *
+ * The wrappers are applied from the device outwards in this order: verify, calibration,
+ * latency, pair, limits, confirm, check and read-only. The initialization commands of the
+ * device are then run through all of them before it is returned.
  *
  * # Arguments
  * `args` - An Args struct containing the configuration for the desired device.
@@ -37,21 +203,390 @@ pub trait Communication {
  * A Result containing a boxed Communication trait object or an ApplicationError.
  */
 pub async fn get_communication_device(args: &Args) -> Result<Box<dyn Communication>, ApplicationError> {
-    match args.device {
-        Device::Unit161d => {
-            let hid = args.hid.as_ref().ok_or_else(|| ApplicationError::Hid("HID device not provided".into()))?;
-            let hid_device = Unit161dHid::new(hid)?;
-            Ok(Box::new(hid_device))            
+    let mut communication_device = wait_for_device(args).await?;
+    if let (true, Some(device)) = (args.control.verify, &args.connection.device) {
+        communication_device = Box::new(VerifyingCommunication::new(communication_device, driver(device)?));
+    }
+    if !args.calibrations.is_empty() {
+        communication_device = Box::new(CalibratedCommunication::new(communication_device, args.calibrations.clone()));
+    }
+    if args.measurement.latency {
+        communication_device = Box::new(TimedCommunication::new(communication_device));
+    }
+    if args.measurement.pair {
+        communication_device = Box::new(PairingCommunication::new(communication_device));
+    }
+    let limits = Limits {
+        max_voltage: args.control.max_voltage,
+        max_current: args.control.max_current,
+    };
+    if !limits.is_empty() {
+        communication_device = Box::new(LimitedCommunication::new(communication_device, limits));
+    }
+    let rules = DangerRules {
+        patterns: args.control.dangerous_commands.clone(),
+        output_voltage: args.control.dangerous_output_voltage,
+    };
+    if args.control.confirm_dangerous && !rules.is_empty() {
+        communication_device = Box::new(ConfirmingCommunication::new(communication_device, rules));
+    }
+    if let (true, Some(device)) = (args.control.check_commands, &args.connection.device) {
+        communication_device = Box::new(CheckingCommunication::new(communication_device, driver(device)?));
+    }
+    if args.control.read_only {
+        communication_device = Box::new(ReadOnlyCommunication::new(communication_device));
+    }
+    if !args.connection.init_commands.is_empty() {
+        communication_device.command(args.connection.init_commands.clone()).await?;
+    }
+    Ok(communication_device)
+}
+
+/**
+ * Checks if opening a device failed because it is not plugged in: it was not found on the bus,
+ * or its device node, hidraw path or serial port does not exist.
+ *
+ * # Arguments
+ * `args` - The arguments of the device.
+ * `error` - The error opening the device.
+ *
+ * # Returns
+ * A boolean indicating whether the device is absent.
+ */
+fn device_absent(args: &Args, error: &ApplicationError) -> bool {
+    error.is_not_found()
+        || [&args.connection.usb, &args.connection.hid, &args.connection.serial]
+            .into_iter()
+            .flatten()
+            .any(|path| path.starts_with('/') && !Path::new(path).exists())
+}
+
+/**
+ * Opens the device given by the arguments. With --wait-for-device, a device that is not plugged
+ * in is opened again until it appears or the time is up.
+ *
+ * # Arguments
+ * `args` - An Args struct containing the configuration for the desired device.
+ *
+ * # Returns
+ * A Result containing a boxed Communication trait object or an ApplicationError.
+ */
+async fn wait_for_device(args: &Args) -> Result<Box<dyn Communication>, ApplicationError> {
+    let Some(seconds) = args.connection.wait_for_device else {
+        return open_communication_device(args).await;
+    };
+    let deadline = Instant::now() + Duration::from_secs(seconds);
+    let mut waited = false;
+    loop {
+        match open_communication_device(args).await {
+            Err(e) if device_absent(args, &e) && Instant::now() < deadline => {
+                if !waited {
+                    info!("Waiting up to {} s for the device: {}", seconds, e);
+                    waited = true;
+                }
+                debug!("Device not present: {}", e);
+                tokio::time::sleep(WAIT_FOR_DEVICE_POLL).await;
+            }
+            Ok(device) if waited => {
+                info!("Device present");
+                return Ok(device);
+            }
+            result => return result,
         }
-        Device::GenericScpiUsb => {
-            let usb = args.usb.as_ref().ok_or_else(|| ApplicationError::Usb("USB device not provided".into()))?;
-            let scpi_usb_device = ScpiUsb::new(usb, args.clone().reader, args.interface_number.unwrap_or(DEFAULT_USB_INTERFACE_NUM), args.bulk_in_address.unwrap_or(DEFAULT_USB_BULK_IN_ADDRESS), args.bulk_out_address.unwrap_or(DEFAULT_USB_BULK_OUT_ADDRESS)).await?;
-            Ok(Box::new(scpi_usb_device))
+    }
+}
+
+/**
+ * Opens the Communication device given by the arguments.
+ *
+ * # Arguments
+ * `args` - An Args struct containing the configuration for the desired device.
+ *
+ * # Returns
+ * A Result containing a boxed Communication trait object or an ApplicationError.
+ */
+async fn open_communication_device(args: &Args) -> Result<Box<dyn Communication>, ApplicationError> {
+    let device = args.connection.device.as_ref().ok_or_else(|| ApplicationError::Config("No device given".into()))?;
+    driver(device)?.open(args).await
+}
+
+/**
+ * Finds the USB device given by the arguments, using the device cache if one is given.
+ *
+ * # Arguments
+ * `args` - An Args struct containing the configuration for the desired device.
+ *
+ * # Returns
+ * A Result containing the USB device or an ApplicationError.
+ */
+#[cfg(feature = "usb")]
+pub(crate) async fn get_usb_device(args: &Args) -> Result<UsbTarget, ApplicationError> {
+    let usb = Requirement::Usb.argument(args)?;
+    let cache = args.connection.device_cache.as_deref().map(DeviceCache::new);
+    find_device(usb, cache.as_ref()).await
+}
+
+/**
+ * Gets the USB interface and endpoints, using the arguments where given and the device defaults otherwise.
+ *
+ * # Arguments
+ * `args` - An Args struct containing the configuration for the desired device.
+ * `interface_number` - The default interface number.
+ * `bulk_in_address` - The default Bulk IN endpoint address.
+ * `bulk_out_address` - The default Bulk OUT endpoint address.
+ *
+ * # Returns
+ * The USB interface and endpoints.
+ */
+#[cfg(feature = "usb")]
+pub(crate) fn get_usb_endpoints(args: &Args, interface_number: u8, bulk_in_address: u8, bulk_out_address: u8) -> UsbEndpoints {
+    UsbEndpoints {
+        interface_number: args.connection.interface_number.unwrap_or(interface_number),
+        bulk_in_address: args.connection.bulk_in_address.unwrap_or(bulk_in_address),
+        bulk_out_address: args.connection.bulk_out_address.unwrap_or(bulk_out_address),
+        interrupt_in_address: args.connection.interrupt_in_address,
+    }
+}
+
+/**
+ * Gets the HID feature reports sent to initialize a device.
+ *
+ * # Arguments
+ * `args` - An Args struct containing the configuration for the desired device.
+ *
+ * # Returns
+ * A Result containing the feature reports or an ApplicationError if a report is not valid hex.
+ */
+#[cfg(feature = "hid")]
+pub(crate) fn get_hid_feature_reports(args: &Args) -> Result<Vec<Vec<u8>>, ApplicationError> {
+    args.connection.hid_feature_reports.iter().map(|report| parse_hex(report)).collect()
+}
+
+/**
+ * Gets the SCPI command and response options from the arguments.
+ *
+ * # Arguments
+ * `args` - An Args struct containing the configuration for the desired device.
+ *
+ * # Returns
+ * The SCPI options, with commands sent as is.
+ */
+pub(crate) fn get_scpi_options(args: &Args) -> ScpiOptions {
+    ScpiOptions {
+        reader: args.clone().readings.reader.unwrap_or(Reader::ScpiRawReader),
+        sample_rate: args.readings.sample_rate,
+        output: args.printing.output.clone(),
+        text_fields: args.readings.text_fields.clone(),
+        #[cfg(feature = "usb")]
+        command_set: CommandSet::Raw,
+        #[cfg(feature = "usb")]
+        quirks: !args.connection.no_quirks,
+        #[cfg(any(feature = "usb", feature = "serial", feature = "gpib"))]
+        terminator: get_terminator(args.connection.terminator.as_ref()),
+        #[cfg(any(feature = "usb", feature = "gpib"))]
+        response_terminator: get_terminator(args.connection.response_terminator.as_ref()),
+        #[cfg(any(feature = "usb", feature = "serial", feature = "gpib"))]
+        read_timeout: Duration::from_millis(args.connection.read_timeout_ms.unwrap_or(DEFAULT_READ_TIMEOUT_MS)),
+        #[cfg(any(feature = "usb", feature = "gpib"))]
+        max_response_size: args.connection.max_response_size.unwrap_or(DEFAULT_MAX_RESPONSE_SIZE),
+        #[cfg(feature = "usb")]
+        remote: args.connection.remote.clone().unwrap_or(RemoteMode::Off),
+        #[cfg(feature = "usb")]
+        local_command: args.connection.local_command.clone().unwrap_or_else(|| DEFAULT_LOCAL_COMMAND.to_string()),
+        #[cfg(any(feature = "usb", feature = "gpib"))]
+        check_errors: args.control.check_errors,
+        #[cfg(feature = "usb")]
+        opc_sync: args.control.opc_sync,
+        #[cfg(any(feature = "usb", feature = "gpib"))]
+        delay: args.control.delay_ms.map(Duration::from_millis),
+        #[cfg(any(feature = "usb", feature = "serial", feature = "gpib"))]
+        expect_response: get_expect_response(args),
+    }
+}
+
+/**
+ * Gets the override of query detection from --expect-response and --no-response.
+ *
+ * # Arguments
+ * `args` - An Args struct containing the configuration for the desired device.
+ *
+ * # Returns
+ * Whether text commands are answered, or None to treat commands containing `?` as queries.
+ */
+#[cfg(any(feature = "usb", feature = "serial", feature = "gpib"))]
+pub(crate) fn get_expect_response(args: &Args) -> Option<bool> {
+    match (args.connection.expect_response, args.connection.no_response) {
+        (true, _) => Some(true),
+        (_, true) => Some(false),
+        _ => None,
+    }
+}
+
+/**
+ * Gets the bytes of a command or response terminator.
+ *
+ * # Arguments
+ * `terminator` - The configured terminator, if any.
+ *
+ * # Returns
+ * The terminator bytes, a newline by default.
+ */
+#[cfg(any(feature = "usb", feature = "hid", feature = "serial", feature = "gpib"))]
+pub(crate) fn get_terminator(terminator: Option<&Terminator>) -> Vec<u8> {
+    match terminator {
+        Some(Terminator::Lf) | None => b"\n".to_vec(),
+        Some(Terminator::Cr) => b"\r".to_vec(),
+        Some(Terminator::Crlf) => b"\r\n".to_vec(),
+        Some(Terminator::None) => Vec::new(),
+    }
+}
+
+/**
+ * Gets the serial line settings of a serial port or HID-UART bridge.
+ *
+ * # Arguments
+ * `args` - An Args struct containing the configuration for the desired device.
+ *
+ * # Returns
+ * The serial line settings.
+ */
+#[cfg(any(feature = "serial", feature = "hid"))]
+pub(crate) fn get_uart_config(args: &Args) -> UartConfig {
+    UartConfig {
+        baud_rate: args.connection.baud_rate,
+        data_bits: args.connection.data_bits,
+        parity: args.connection.parity.clone().unwrap_or(Parity::None),
+        stop_bits: args.connection.stop_bits,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::cell::Cell;
+
+    use clap::Parser;
+
+    use super::*;
+    use crate::arguments::Device;
+    #[cfg(all(feature = "network", feature = "modbus"))]
+    use crate::instruments::communication::LoopbackModbus;
+    use crate::instruments::reading::ScpiRawReading;
+
+    /**
+     * Device answering every command with a numbered reading, failing the second cycle.
+     */
+    #[derive(Default)]
+    struct Counter(Cell<u32>);
+
+    #[async_trait(?Send)]
+    impl Communication for Counter {
+        async fn command(&self, commands: Vec<String>) -> Result<Option<Vec<Box<dyn Reading>>>, ApplicationError> {
+            let cycle = self.0.get();
+            self.0.set(cycle + 1);
+            if cycle == 1 {
+                return Err(ApplicationError::Command("failed".into()));
+            }
+            Ok(Some(
+                commands
+                    .iter()
+                    .map(|command| Box::new(ScpiRawReading::new(format!("{}{}", command, cycle).into_bytes())) as Box<dyn Reading>)
+                    .collect(),
+            ))
+        }
+    }
+
+    /**
+     * Device with the descriptor strings of a USB serial adapter.
+     */
+    struct Described;
+
+    #[async_trait(?Send)]
+    impl Communication for Described {
+        async fn command(&self, _commands: Vec<String>) -> Result<Option<Vec<Box<dyn Reading>>>, ApplicationError> {
+            Ok(None)
         }
-        Device::Peaktech4055mvUsb => {
-            let usb = args.usb.as_ref().ok_or_else(|| ApplicationError::Usb("USB device not provided".into()))?;
-            let scpi_usb_device = ScpiUsb::new(usb, args.clone().reader, args.interface_number.unwrap_or(PEAKTECH_4055MV_USB_INTERFACE_NUM), args.bulk_in_address.unwrap_or(PEAKTECH_4055MV_USB_BULK_IN_ADDRESS), PEAKTECH_4055MV_USB_BULK_OUT_ADDRESS).await?;
-            Ok(Box::new(scpi_usb_device))
+
+        fn device_info(&self) -> Option<DeviceInfo> {
+            Some(DeviceInfo {
+                manufacturer: Some("Silicon Labs".into()),
+                product: Some("CP2102".into()),
+                serial_number: Some("0001".into()),
+            })
         }
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_wrappers_forward_device_info() {
+        let driver = driver(&Device::KoradPsu).unwrap();
+        let mut device: Box<dyn Communication> = Box::new(Described);
+        device = Box::new(VerifyingCommunication::new(device, driver));
+        device = Box::new(CalibratedCommunication::new(device, Vec::new()));
+        device = Box::new(TimedCommunication::new(device));
+        device = Box::new(PairingCommunication::new(device));
+        device = Box::new(LimitedCommunication::new(device, Limits { max_voltage: Some(6.0), max_current: None }));
+        device = Box::new(ConfirmingCommunication::new(
+            device,
+            DangerRules {
+                patterns: vec!["OVP0".into()],
+                output_voltage: None,
+            },
+        ));
+        device = Box::new(CheckingCommunication::new(device, driver));
+        device = Box::new(ReadOnlyCommunication::new(device));
+        assert_eq!(device.device_info(), Described.device_info());
+    }
+
+    #[tokio::test]
+    async fn test_readings_stream() {
+        let device = Counter::default();
+        let results: Vec<Result<String, ApplicationError>> = device
+            .readings(vec!["a".into(), "b".into()], Duration::ZERO)
+            .take(5)
+            .map(|reading| reading.and_then(|reading| reading.get_raw_string()))
+            .collect()
+            .await;
+        assert_eq!(results[..2].iter().map(|result| result.as_deref().unwrap()).collect::<Vec<_>>(), ["a0", "b0"]);
+        assert!(results[2].is_err());
+        assert_eq!(results[3].as_deref().unwrap(), "a2");
+        assert_eq!(results[4].as_deref().unwrap(), "b2");
+        // Only the cycles needed for the requested readings were run
+        assert_eq!(device.0.get(), 3);
+    }
+
+    #[test]
+    fn test_device_absent() {
+        let args = Args::parse_from(["hardware-measurement", "--device=korad-psu", "--serial=/dev/hardware-measurement-missing"]);
+        assert!(device_absent(&args, &ApplicationError::Serial("Failed to open".into())));
+        let args = Args::parse_from(["hardware-measurement", "--device=korad-psu", "--serial=/dev/null"]);
+        assert!(!device_absent(&args, &ApplicationError::Serial("Failed to open".into())));
+        #[cfg(has_discovery)]
+        assert!(device_absent(&args, &ApplicationError::NotFound("USB device 1ab1:04ce".into())));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_wait_for_device_gives_up() {
+        let args = Args::parse_from([
+            "hardware-measurement",
+            "--device=korad-psu",
+            "--serial=/dev/hardware-measurement-missing",
+            "--wait-for-device=1",
+        ]);
+        let start = Instant::now();
+        assert!(wait_for_device(&args).await.is_err());
+        assert!(start.elapsed() >= Duration::from_secs(1));
+    }
+
+    #[cfg(all(feature = "network", feature = "modbus"))]
+    #[tokio::test]
+    async fn test_init_commands() {
+        let server = LoopbackModbus::start();
+        let mut args = server.args(&["--init", "Measure", "Read:voltage"]);
+        let device = get_communication_device(&args).await.unwrap();
+        assert_eq!(server.requests(), 2);
+        device.command(vec!["Measure".into()]).await.unwrap();
+        assert_eq!(server.requests(), 3);
+        // A failing initialization command fails the open
+        args.connection.init_commands = vec!["Read:current".into()];
+        assert!(matches!(get_communication_device(&args).await, Err(ApplicationError::Command(_))));
+    }
+}
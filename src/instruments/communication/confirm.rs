@@ -0,0 +1,115 @@
+use std::{
+    cell::Cell,
+    io::{stdin, stdout, IsTerminal, Write},
+};
+
+use async_trait::async_trait;
+
+use crate::{
+    error::ApplicationError,
+    instruments::{command::DangerRules, communication::common::{Communication, DeviceInfo}, reading::Reading},
+};
+
+/**
+ * Wraps a device so that commands flagged dangerous must be confirmed interactively before
+ * the batch is sent. Without a terminal on stdin dangerous commands are refused.
+ */
+pub struct ConfirmingCommunication {
+    /**
+     * The wrapped device.
+     */
+    device: Box<dyn Communication>,
+    /**
+     * The rules flagging dangerous commands.
+     */
+    rules: DangerRules,
+    /**
+     * The last voltage set on the device.
+     */
+    voltage: Cell<Option<f64>>,
+}
+
+impl ConfirmingCommunication {
+    /**
+     * Creates a new instance of ConfirmingCommunication.
+     *
+     * # Arguments
+     * `device` - The device to wrap.
+     * `rules` - The rules flagging dangerous commands.
+     *
+     * # Returns
+     * A new ConfirmingCommunication instance.
+     */
+    pub fn new(device: Box<dyn Communication>, rules: DangerRules) -> Self {
+        Self {
+            device,
+            rules,
+            voltage: Cell::new(None),
+        }
+    }
+}
+
+/**
+ * Asks the operator to confirm a dangerous command.
+ *
+ * # Arguments
+ * `command` - The dangerous command.
+ * `reason` - Why the command is dangerous.
+ *
+ * # Returns
+ * A Result indicating confirmation or an ApplicationError if the command is refused.
+ */
+fn confirm(command: &str, reason: &str) -> Result<(), ApplicationError> {
+    let refused = || ApplicationError::Command(format!("Dangerous command {} refused: {}", command, reason));
+    if !stdin().is_terminal() {
+        return Err(refused());
+    }
+    print!("Send dangerous command {} ({})? [y/N] ", command, reason);
+    stdout()
+        .flush()
+        .map_err(|e| ApplicationError::General(format!("Failed to write prompt: {}", e)))?;
+    let mut answer = String::new();
+    stdin()
+        .read_line(&mut answer)
+        .map_err(|e| ApplicationError::General(format!("Failed to read answer: {}", e)))?;
+    match answer.trim() {
+        "y" | "Y" | "yes" => Ok(()),
+        _ => Err(refused()),
+    }
+}
+
+#[async_trait(?Send)]
+impl Communication for ConfirmingCommunication {
+    /**
+     * Confirms the dangerous commands and sends the batch to the wrapped device.
+     *
+     * # Arguments
+     * `commands` - The commands to execute.
+     *
+     * # Returns
+     * A Result containing an optional vector of Reading trait objects or an ApplicationError.
+     */
+    async fn command(
+        &self,
+        commands: Vec<String>,
+    ) -> Result<Option<Vec<Box<dyn Reading>>>, ApplicationError> {
+        let mut voltage = self.voltage.get();
+        for command in &commands {
+            if let Some(reason) = self.rules.check(command, &mut voltage) {
+                confirm(command, &reason)?;
+            }
+        }
+        self.voltage.set(voltage);
+        self.device.command(commands).await
+    }
+
+    /**
+     * Returns the device info of the wrapped device.
+     *
+     * # Returns
+     * The device info, or None.
+     */
+    fn device_info(&self) -> Option<DeviceInfo> {
+        self.device.device_info()
+    }
+}
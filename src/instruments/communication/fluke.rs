@@ -0,0 +1,207 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+
+use crate::{
+    arguments::{Args, Parity},
+    error::ApplicationError,
+    instruments::{
+        command::FlukeCommand,
+        communication::{
+            common::{Communication, DeviceInfo},
+            registry::Requirement,
+            serial::{SerialLine, UartConfig},
+        },
+        reading::{FlukeReading, Reading, ScpiRawReading},
+    },
+};
+
+/**
+ * Time to wait for the acknowledge and data line of a response.
+ */
+const FLUKE_RESPONSE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/**
+ * Read timeout of the serial port. A binary session download is complete when no data
+ * arrives within it.
+ */
+pub const FLUKE_READ_TIMEOUT: Duration = Duration::from_millis(500);
+
+/**
+ * Returns the fixed serial settings of the Fluke 287 / 289 IR cable, 115200 baud 8N1.
+ *
+ * # Returns
+ * The serial line settings.
+ */
+pub fn fluke_uart_config() -> UartConfig {
+    UartConfig {
+        baud_rate: 115200,
+        data_bits: 8,
+        parity: Parity::None,
+        stop_bits: 1,
+    }
+}
+
+/**
+ * Checks if a text response is complete: the acknowledge line and, if the command was
+ * accepted, the data line.
+ *
+ * # Arguments
+ * `data` - The received bytes.
+ *
+ * # Returns
+ * A boolean indicating whether the response is complete.
+ */
+fn is_complete(data: &[u8]) -> bool {
+    let lines = data.iter().filter(|byte| **byte == b'\r').count();
+    match data.first() {
+        Some(b'0') => lines >= 2,
+        Some(_) => lines >= 1,
+        None => false,
+    }
+}
+
+/**
+ * Splits the acknowledge code from a response.
+ *
+ * # Arguments
+ * `data` - The response.
+ * `command` - The command, used in error messages.
+ *
+ * # Returns
+ * A Result containing the data after the acknowledge line or an ApplicationError if the
+ * meter rejected the command.
+ */
+fn acknowledge<'a>(data: &'a [u8], command: &FlukeCommand) -> Result<&'a [u8], ApplicationError> {
+    let reason = match data.first() {
+        Some(b'0') => return Ok(data.get(2..).unwrap_or_default()),
+        Some(b'1') => "syntax error",
+        Some(b'2') => "execution error",
+        Some(b'5') => "no data available",
+        _ => "invalid acknowledge",
+    };
+    Err(ApplicationError::Command(format!(
+        "Fluke meter rejected {}: {}",
+        command.to_wire(),
+        reason
+    )))
+}
+
+/**
+ * Module for Fluke 287 / 289 meters on the IR serial cable. The meter answers each command
+ * with an acknowledge line followed by the data, both terminated by CR.
+ */
+pub struct FlukeSerial {
+    /**
+     * Serial port of the IR cable.
+     */
+    line: SerialLine,
+}
+
+impl FlukeSerial {
+    /**
+     * Creates a new instance of FlukeSerial.
+     *
+     * # Arguments
+     * `line` - The opened serial port.
+     *
+     * # Returns
+     * A new FlukeSerial instance.
+     */
+    pub fn new(line: SerialLine) -> Self {
+        Self { line }
+    }
+
+    /**
+     * Sends a command and reads the response without the acknowledge line.
+     *
+     * # Arguments
+     * `command` - The typed command.
+     *
+     * # Returns
+     * A Result containing the response data or an ApplicationError.
+     */
+    fn send(&self, command: &FlukeCommand) -> Result<Vec<u8>, ApplicationError> {
+        self.line.write(format!("{}\r", command.to_wire()).as_bytes())?;
+        let data = match command.is_binary() {
+            true => self.line.read()?,
+            false => self.line.read_response(is_complete, FLUKE_RESPONSE_TIMEOUT)?,
+        };
+        acknowledge(&data, command).map(|data| data.to_vec())
+    }
+}
+
+#[async_trait(?Send)]
+impl Communication for FlukeSerial {
+    /**
+     * Executes typed Fluke commands. Measure and Display are decoded, the other responses are
+     * returned as they are received.
+     *
+     * # Arguments
+     * `commands` - The commands to execute.
+     *
+     * # Returns
+     * A Result containing an optional vector of Reading trait objects or an ApplicationError.
+     */
+    async fn command(
+        &self,
+        commands: Vec<String>,
+    ) -> Result<Option<Vec<Box<dyn Reading>>>, ApplicationError> {
+        let mut readings: Vec<Box<dyn Reading>> = Vec::new();
+        for command in commands {
+            let command = FlukeCommand::try_from(command.as_str())?;
+            let data = self.send(&command)?;
+            let reading: Box<dyn Reading> = match command {
+                FlukeCommand::Measure => Box::new(FlukeReading::parse_qm(&data)?),
+                FlukeCommand::Display => Box::new(FlukeReading::parse_qdda(&data)?),
+                _ => Box::new(ScpiRawReading::new(data)),
+            };
+            readings.push(reading);
+        }
+        Ok(Some(readings))
+    }
+
+    /**
+     * Returns the USB descriptor strings of the serial adapter.
+     *
+     * # Returns
+     * The device info, or None if the port is not a USB adapter.
+     */
+    fn device_info(&self) -> Option<DeviceInfo> {
+        self.line.device_info()
+    }
+}
+
+/**
+ * Opens the meter on the serial port of the arguments, with the fixed line settings of the meter.
+ *
+ * # Arguments
+ * `args` - An Args struct containing the configuration for the desired device.
+ *
+ * # Returns
+ * A Result containing a boxed Communication trait object or an ApplicationError.
+ */
+pub(crate) async fn open(args: &Args) -> Result<Box<dyn Communication>, ApplicationError> {
+    let line = SerialLine::open(Requirement::Serial.argument(args)?, &fluke_uart_config(), FLUKE_READ_TIMEOUT)?;
+    Ok(Box::new(FlukeSerial::new(line)))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_is_complete() {
+        assert!(!is_complete(b""));
+        assert!(!is_complete(b"0\r1.0,VDC"));
+        assert!(is_complete(b"0\r1.0,VDC,NORMAL,NONE\r"));
+        assert!(is_complete(b"1\r"));
+    }
+
+    #[test]
+    fn test_acknowledge() {
+        assert_eq!(acknowledge(b"0\rFLUKE 289\r", &FlukeCommand::Identify).unwrap(), b"FLUKE 289\r");
+        assert!(acknowledge(b"5\r", &FlukeCommand::Sessions).is_err());
+        assert!(acknowledge(b"", &FlukeCommand::Measure).is_err());
+    }
+}
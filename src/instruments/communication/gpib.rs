@@ -0,0 +1,496 @@
+use std::{
+    ffi::{c_char, c_int, c_long, c_void},
+    time::Duration,
+};
+
+use async_trait::async_trait;
+use tracing::{debug, info};
+
+use crate::{
+    arguments::{Args, CheckErrors},
+    capture,
+    error::ApplicationError,
+    instruments::{
+        command::{
+            errorqueue::{parse_error, ERROR_QUERY, MAX_ERRORS},
+            delay,
+            Delay, RawCommand,
+        },
+        communication::{
+            common::{get_scpi_options, Communication},
+            registry::Requirement,
+            scpi::ScpiOptions,
+        },
+        reading::{Reading, ScpiRawReading},
+    },
+    logging::hex_dump,
+};
+
+#[link(name = "gpib")]
+extern "C" {
+    fn ibdev(board_index: c_int, pad: c_int, sad: c_int, tmo: c_int, send_eoi: c_int, eos: c_int) -> c_int;
+    fn ibwrt(ud: c_int, buffer: *const c_void, count: c_long) -> c_int;
+    fn ibrd(ud: c_int, buffer: *mut c_void, count: c_long) -> c_int;
+    fn ibrsp(ud: c_int, poll_byte: *mut c_char) -> c_int;
+    fn ibonl(ud: c_int, online: c_int) -> c_int;
+    #[allow(non_snake_case)]
+    fn ThreadIberr() -> c_int;
+    #[allow(non_snake_case)]
+    fn ThreadIbcntl() -> c_long;
+}
+
+/**
+ * ibsta bit set when a call failed.
+ */
+const ERR: c_int = 0x8000;
+/**
+ * ibsta bit set when a call timed out.
+ */
+const TIMO: c_int = 0x4000;
+/**
+ * ibsta bit set when a read ended with EOI or the end-of-string character.
+ */
+const END: c_int = 0x2000;
+/**
+ * eos mode bit ending reads at the end-of-string character.
+ */
+const REOS: c_int = 0x400;
+/**
+ * Offset of secondary addresses in ibdev.
+ */
+const SECONDARY_ADDRESS_OFFSET: c_int = 0x60;
+
+/**
+ * Status byte bit set while the error queue is not empty (SCPI-1999).
+ */
+const STB_ERROR_QUEUE: u8 = 0x04;
+/**
+ * Status byte bit summarizing the event status register.
+ */
+const STB_EVENT_STATUS: u8 = 0x20;
+/**
+ * Event status register bits of query, device, execution and command errors.
+ */
+const ESR_ERRORS: u8 = 0x3C;
+
+/**
+ * Largest read in one ibrd call.
+ */
+const READ_CHUNK_SIZE: usize = 65536;
+
+/**
+ * Timeouts of the ibtmo codes T10us (1) to T1000s (17), in microseconds.
+ */
+const TIMEOUTS_US: [u64; 17] = [
+    10, 30, 100, 300, 1_000, 3_000, 10_000, 30_000, 100_000, 300_000, 1_000_000, 3_000_000, 10_000_000, 30_000_000,
+    100_000_000, 300_000_000, 1_000_000_000,
+];
+
+/**
+ * Names of the iberr codes, indexed by code.
+ */
+const ERROR_NAMES: [&str; 21] = [
+    "EDVR system error",
+    "ECIC not controller in charge",
+    "ENOL no listeners",
+    "EADR not addressed",
+    "EARG invalid argument",
+    "ESAC not system controller",
+    "EABO aborted, e.g. timeout",
+    "ENEB no such board",
+    "EDMA DMA error",
+    "",
+    "EOIP asynchronous operation in progress",
+    "ECAP not supported",
+    "EFSO file system error",
+    "",
+    "EBUS bus error",
+    "ESTB serial poll queue overflow",
+    "ESRQ SRQ stuck on",
+    "",
+    "",
+    "",
+    "ETAB table problem",
+];
+
+/**
+ * Address of an instrument on a GPIB board.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GpibAddress {
+    /**
+     * Board index, the minor number of /dev/gpibN.
+     */
+    pub board: u8,
+    /**
+     * Primary address, 0 to 30.
+     */
+    pub primary: u8,
+    /**
+     * Secondary address, 0 to 30, if the instrument uses one.
+     */
+    pub secondary: Option<u8>,
+}
+
+impl GpibAddress {
+    /**
+     * Parses an address such as `0:22` (board 0, primary address 22) or `0:5:2` with a secondary
+     * address.
+     *
+     * # Arguments
+     * `address` - The address.
+     *
+     * # Returns
+     * A Result containing the GpibAddress or an ApplicationError if it is invalid.
+     */
+    pub fn parse(address: &str) -> Result<Self, ApplicationError> {
+        let invalid = || ApplicationError::Config(format!("Invalid GPIB address {}, expected board:primary[:secondary]", address));
+        let numbers = address
+            .split(':')
+            .map(|number| number.trim().parse::<u8>().map_err(|_| invalid()))
+            .collect::<Result<Vec<u8>, ApplicationError>>()?;
+        match numbers[..] {
+            [board, primary] if primary <= 30 => Ok(Self { board, primary, secondary: None }),
+            [board, primary, secondary] if primary <= 30 && secondary <= 30 => Ok(Self {
+                board,
+                primary,
+                secondary: Some(secondary),
+            }),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+/**
+ * Gets the ibtmo code of a timeout: the shortest timeout at least as long.
+ *
+ * # Arguments
+ * `timeout` - The timeout.
+ *
+ * # Returns
+ * The code, T1000s for longer timeouts.
+ */
+fn timeout_code(timeout: Duration) -> c_int {
+    let micros = timeout.as_micros();
+    TIMEOUTS_US.iter().position(|limit| *limit as u128 >= micros).unwrap_or(TIMEOUTS_US.len() - 1) as c_int + 1
+}
+
+/**
+ * Describes an iberr code.
+ *
+ * # Arguments
+ * `code` - The code.
+ *
+ * # Returns
+ * The name and meaning of the code.
+ */
+fn error_name(code: c_int) -> String {
+    match usize::try_from(code).ok().and_then(|index| ERROR_NAMES.get(index)) {
+        Some(name) if !name.is_empty() => name.to_string(),
+        _ => format!("error {}", code),
+    }
+}
+
+/**
+ * Checks the status of a call, converting failures and timeouts.
+ *
+ * # Arguments
+ * `status` - The ibsta returned by the call.
+ * `action` - What was done, for the error message.
+ *
+ * # Returns
+ * A Result containing the status or an ApplicationError.
+ */
+fn check(status: c_int, action: &str) -> Result<c_int, ApplicationError> {
+    if status & TIMO != 0 {
+        return Err(ApplicationError::Timeout(format!("GPIB timeout: {}", action)));
+    }
+    if status & ERR != 0 {
+        // SAFETY: reads the error of the last call of this thread
+        let code = unsafe { ThreadIberr() };
+        return Err(ApplicationError::Gpib(format!("{}: {}", action, error_name(code))));
+    }
+    Ok(status)
+}
+
+/**
+ * Module for SCPI instruments on a GPIB interface card, using the linux-gpib library. Commands
+ * are written with EOI asserted on the last byte and responses are read until EOI, or the
+ * response terminator if one is given. With --check-errors the instrument is serial polled and
+ * its error queue is only read when the status byte reports errors.
+ */
+pub struct GpibScpi {
+    /**
+     * Device descriptor returned by ibdev.
+     */
+    ud: c_int,
+    /**
+     * Command and response options.
+     */
+    options: ScpiOptions,
+}
+
+impl GpibScpi {
+    /**
+     * Opens the instrument at an address.
+     *
+     * # Arguments
+     * `address` - The address of the instrument.
+     * `options` - Command and response options.
+     * `end_of_string` - Character also ending responses besides EOI, if any.
+     *
+     * # Returns
+     * A Result containing the GpibScpi instance or an ApplicationError.
+     */
+    pub fn open(address: GpibAddress, options: ScpiOptions, end_of_string: Option<u8>) -> Result<Self, ApplicationError> {
+        let secondary = address.secondary.map_or(0, |secondary| secondary as c_int + SECONDARY_ADDRESS_OFFSET);
+        let eos = end_of_string.map_or(0, |character| REOS | character as c_int);
+        // SAFETY: ibdev only takes plain integers
+        let ud = unsafe {
+            ibdev(
+                address.board as c_int,
+                address.primary as c_int,
+                secondary,
+                timeout_code(options.read_timeout),
+                1,
+                eos,
+            )
+        };
+        if ud < 0 {
+            // SAFETY: reads the error of the last call of this thread
+            let code = unsafe { ThreadIberr() };
+            return Err(ApplicationError::NotFound(format!(
+                "GPIB device at {:?}: {}",
+                address,
+                error_name(code)
+            )));
+        }
+        info!("Opened GPIB device {} at board {} address {}", ud, address.board, address.primary);
+        Ok(Self { ud, options })
+    }
+
+    /**
+     * Writes a command, appending the terminator if missing. EOI is asserted on the last byte.
+     *
+     * # Arguments
+     * `command` - The command.
+     * `raw_command` - The parsed command.
+     */
+    fn write(&self, command: &str, raw_command: &RawCommand) -> Result<(), ApplicationError> {
+        let bytes = raw_command.to_bytes(&self.options.terminator);
+        debug!("Writing {:?}: {}", command, hex_dump(&bytes));
+        capture::written("gpib", &bytes);
+        // SAFETY: the buffer is valid for the given length
+        let status = unsafe { ibwrt(self.ud, bytes.as_ptr() as *const c_void, bytes.len() as c_long) };
+        check(status, &format!("Writing {:?}", command))?;
+        Ok(())
+    }
+
+    /**
+     * Reads a response until EOI, the end-of-string character or the maximum response size.
+     *
+     * # Arguments
+     * `command` - The query the response belongs to.
+     *
+     * # Returns
+     * A Result containing the response or an ApplicationError.
+     */
+    fn read(&self, command: &str) -> Result<Vec<u8>, ApplicationError> {
+        let mut data = Vec::new();
+        let mut chunk = vec![0u8; READ_CHUNK_SIZE.min(self.options.max_response_size.max(1))];
+        while data.len() < self.options.max_response_size {
+            // SAFETY: the buffer is valid for its length
+            let status = unsafe { ibrd(self.ud, chunk.as_mut_ptr() as *mut c_void, chunk.len() as c_long) };
+            let status = check(status, &format!("Reading the response to {:?}", command))?;
+            // SAFETY: reads the count of the last call of this thread
+            let count = (unsafe { ThreadIbcntl() } as usize).min(chunk.len());
+            data.extend_from_slice(&chunk[..count]);
+            if status & END != 0 {
+                break;
+            }
+        }
+        debug!("Read for {:?}: {}", command, hex_dump(&data));
+        capture::read("gpib", &data);
+        Ok(data)
+    }
+
+    /**
+     * Sends a command and reads the response if the command is a query.
+     *
+     * # Arguments
+     * `command` - The command.
+     *
+     * # Returns
+     * A Result containing the response for queries, None for other commands, or an ApplicationError.
+     */
+    fn send(&self, command: &str) -> Result<Option<Vec<u8>>, ApplicationError> {
+        self.send_expecting(command, None)
+    }
+
+    /**
+     * Sends a command and reads the response if one is expected.
+     *
+     * # Arguments
+     * `command` - The command.
+     * `expect_response` - Whether a text command is answered, or None to detect queries by `?`.
+     *
+     * # Returns
+     * A Result containing the response if expected, None otherwise, or an ApplicationError.
+     */
+    fn send_expecting(&self, command: &str, expect_response: Option<bool>) -> Result<Option<Vec<u8>>, ApplicationError> {
+        let raw_command = RawCommand::parse(command)?;
+        self.write(command, &raw_command)?;
+        match raw_command.expects_response(expect_response) {
+            true => Ok(Some(self.read(command)?)),
+            false => Ok(None),
+        }
+    }
+
+    /**
+     * Serial polls the instrument.
+     *
+     * # Returns
+     * A Result containing the status byte or an ApplicationError.
+     */
+    fn serial_poll(&self) -> Result<u8, ApplicationError> {
+        let mut status_byte: c_char = 0;
+        // SAFETY: the poll byte is a valid location
+        let status = unsafe { ibrsp(self.ud, &mut status_byte) };
+        check(status, "Serial poll")?;
+        debug!("Serial poll: {:#04x}", status_byte as u8);
+        Ok(status_byte as u8)
+    }
+
+    /**
+     * Checks for errors with a serial poll. Only when the status byte reports errors in the
+     * error queue or the event status register are they read, which also clears them.
+     *
+     * # Arguments
+     * `command` - The command checked, used in the error message.
+     *
+     * # Returns
+     * A Result indicating success or an ApplicationError listing the reported errors.
+     */
+    fn check_errors(&self, command: &str) -> Result<(), ApplicationError> {
+        let status_byte = self.serial_poll()?;
+        let mut errors = Vec::new();
+        if status_byte & STB_ERROR_QUEUE != 0 {
+            while errors.len() < MAX_ERRORS {
+                let response = self
+                    .send(ERROR_QUERY)?
+                    .ok_or_else(|| ApplicationError::Command("No response to SYST:ERR?".into()))?;
+                match parse_error(&response)? {
+                    Some(error) => errors.push(error),
+                    None => break,
+                }
+            }
+        }
+        if status_byte & STB_EVENT_STATUS != 0 {
+            let response = self.send("*ESR?")?.unwrap_or_default();
+            let register = String::from_utf8_lossy(&response).trim().parse::<u8>().unwrap_or_default();
+            if register & ESR_ERRORS != 0 && errors.is_empty() {
+                errors.push(format!("event status register {:#04x}", register));
+            }
+        }
+        match errors.is_empty() {
+            true => Ok(()),
+            false => Err(ApplicationError::Command(format!(
+                "Instrument reported errors after {:?}: {}",
+                command,
+                errors.join("; ")
+            ))),
+        }
+    }
+}
+
+impl Drop for GpibScpi {
+    fn drop(&mut self) {
+        // SAFETY: takes the descriptor offline, it is not used afterwards
+        unsafe { ibonl(self.ud, 0) };
+    }
+}
+
+#[async_trait(?Send)]
+impl Communication for GpibScpi {
+    /**
+     * Sends the commands and reads the responses to the queries.
+     *
+     * # Arguments
+     * `commands` - The commands to send.
+     *
+     * # Returns
+     * A Result containing an optional vector of Reading trait objects or an ApplicationError.
+     */
+    async fn command(&self, commands: Vec<String>) -> Result<Option<Vec<Box<dyn Reading>>>, ApplicationError> {
+        let mut readings: Vec<Box<dyn Reading>> = Vec::new();
+        for (index, command) in commands.iter().enumerate() {
+            let previous = index.checked_sub(1).map(|previous| commands[previous].as_str());
+            if let Some(pause) = delay::pause_before(previous, command, self.options.delay) {
+                tokio::time::sleep(pause).await;
+            }
+            if let Some(delay) = Delay::parse(command)? {
+                tokio::time::sleep(delay.duration).await;
+                continue;
+            }
+            if let Some(data) = self.send_expecting(command, self.options.expect_response)? {
+                readings.push(Box::new(ScpiRawReading::new(data)));
+            }
+            if self.options.check_errors == Some(CheckErrors::Command) {
+                self.check_errors(command)?;
+            }
+        }
+        if let (Some(CheckErrors::Batch), Some(last)) = (self.options.check_errors, commands.last()) {
+            self.check_errors(last)?;
+        }
+        Ok((!readings.is_empty()).then_some(readings))
+    }
+}
+
+/**
+ * Opens the instrument at the GPIB address of the arguments. Responses end with EOI; a single
+ * character --response-terminator also ends them, for instruments not asserting EOI.
+ *
+ * # Arguments
+ * `args` - An Args struct containing the configuration for the desired device.
+ *
+ * # Returns
+ * A Result containing a boxed Communication trait object or an ApplicationError.
+ */
+pub(crate) async fn open(args: &Args) -> Result<Box<dyn Communication>, ApplicationError> {
+    let address = GpibAddress::parse(Requirement::Gpib.argument(args)?)?;
+    let options = get_scpi_options(args);
+    let end_of_string = match (&args.connection.response_terminator, &options.response_terminator[..]) {
+        (Some(_), [character]) => Some(*character),
+        _ => None,
+    };
+    Ok(Box::new(GpibScpi::open(address, options, end_of_string)?))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_address() {
+        assert_eq!(GpibAddress::parse("0:22").unwrap(), GpibAddress { board: 0, primary: 22, secondary: None });
+        assert_eq!(GpibAddress::parse("1:5:2").unwrap().secondary, Some(2));
+        assert!(GpibAddress::parse("0:31").is_err());
+        assert!(GpibAddress::parse("22").is_err());
+        assert!(GpibAddress::parse("0:x").is_err());
+    }
+
+    #[test]
+    fn test_timeout_code() {
+        assert_eq!(timeout_code(Duration::from_millis(1)), 5);
+        assert_eq!(timeout_code(Duration::from_millis(2)), 6);
+        assert_eq!(timeout_code(Duration::from_secs(10)), 13);
+        assert_eq!(timeout_code(Duration::from_secs(5000)), 17);
+    }
+
+    #[test]
+    fn test_error_name() {
+        assert_eq!(error_name(2), "ENOL no listeners");
+        assert_eq!(error_name(9), "error 9");
+        assert_eq!(error_name(-1), "error -1");
+    }
+}
@@ -0,0 +1,287 @@
+use std::ffi::CString;
+
+use tracing::{debug, info};
+
+use crate::{
+    arguments::{Bridge, Parity},
+    capture,
+    error::ApplicationError,
+    instruments::communication::{
+        common::{hid_device_info, DeviceInfo},
+        serial::UartConfig,
+    },
+    logging::hex_dump,
+};
+
+/**
+ * CP2110 feature report enabling the UART.
+ */
+const CP2110_UART_ENABLE: [u8; 2] = [0x41, 0x01];
+/**
+ * CP2110 feature report purging the transmit and receive FIFOs.
+ */
+const CP2110_PURGE_FIFOS: [u8; 2] = [0x43, 0x03];
+/**
+ * CP2110 feature report id of the UART configuration.
+ */
+const CP2110_UART_CONFIG: u8 = 0x50;
+/**
+ * Largest payload of a CP2110 data report. The report id is the payload length.
+ */
+const CP2110_MAX_PAYLOAD: usize = 63;
+/**
+ * Largest payload of a CH9325 data report.
+ */
+const CH9325_MAX_PAYLOAD: usize = 7;
+
+/**
+ * A HID-to-UART bridge, as used in many meter cables. The bridge is configured with feature
+ * reports, after which the serial data is streamed in input and output reports.
+ */
+pub struct HidBridge {
+    // HID Device instance
+    hiddevice: hidapi::HidDevice,
+    /**
+     * Bridge chip.
+     */
+    kind: Bridge,
+}
+
+impl HidBridge {
+    /**
+     * Opens a HID-UART bridge.
+     *
+     * # Arguments
+     * `hid_device_path` - A string slice representing the path to the HID device.
+     * `kind` - The bridge chip.
+     *
+     * # Returns
+     * A new HidBridge instance.
+     */
+    pub fn open(hid_device_path: &str, kind: Bridge) -> Result<Self, ApplicationError> {
+        let api = hidapi::HidApi::new_without_enumerate().map_err(|e| {
+            ApplicationError::HidApi("Failed to create HID API instance".into(), e)
+        })?;
+        let c_path = CString::new(hid_device_path.to_string()).map_err(|e| {
+            ApplicationError::Hid(format!(
+                "Failed to create CString for HID device path: {}",
+                e
+            ))
+        })?;
+        let hiddevice = api.open_path(&c_path).map_err(|e| {
+            ApplicationError::HidApi(format!("Failed to open HID device at {}", hid_device_path), e)
+        })?;
+        info!("Opened {:?} bridge at {}", kind, hid_device_path);
+        Ok(Self { hiddevice, kind })
+    }
+
+    /**
+     * Configures the serial line of the bridge.
+     *
+     * # Arguments
+     * `config` - The serial line settings.
+     */
+    pub fn configure(&self, config: &UartConfig) -> Result<(), ApplicationError> {
+        for report in config_reports(&self.kind, config)? {
+            capture::written("hid", &report);
+            self.hiddevice.send_feature_report(&report).map_err(|e| {
+                ApplicationError::HidApi(format!("Failed to configure {:?} bridge", self.kind), e)
+            })?;
+        }
+        Ok(())
+    }
+
+    /**
+     * Writes serial data through the bridge.
+     *
+     * # Arguments
+     * `data` - The bytes to send.
+     */
+    pub fn write(&self, data: &[u8]) -> Result<(), ApplicationError> {
+        debug!("Writing to {:?} bridge: {}", self.kind, hex_dump(data));
+        for report in output_reports(&self.kind, data) {
+            capture::written("hid", &report);
+            self.hiddevice
+                .write(&report)
+                .map_err(|e| ApplicationError::HidApi("Failed to write to HID device".into(), e))?;
+        }
+        Ok(())
+    }
+
+    /**
+     * Reads the serial data of one input report.
+     *
+     * # Arguments
+     * `timeout_ms` - The time to wait for a report in milliseconds.
+     *
+     * # Returns
+     * A Result containing the received bytes (empty on timeout) or an ApplicationError.
+     */
+    pub fn read(&self, timeout_ms: i32) -> Result<Vec<u8>, ApplicationError> {
+        let mut report = [0u8; 64];
+        let length = self
+            .hiddevice
+            .read_timeout(&mut report, timeout_ms)
+            .map_err(|e| ApplicationError::HidApi("Failed to read from HID device".into(), e))?;
+        capture::read("hid", &report[..length]);
+        let data = decode_input(&self.kind, &report[..length]);
+        if !data.is_empty() {
+            debug!("Read from {:?} bridge: {}", self.kind, hex_dump(data));
+        }
+        Ok(data.to_vec())
+    }
+
+    /**
+     * Gets the USB descriptor strings of the bridge.
+     *
+     * # Returns
+     * The device info.
+     */
+    pub fn device_info(&self) -> Option<DeviceInfo> {
+        hid_device_info(&self.hiddevice)
+    }
+}
+
+/**
+ * Builds the feature reports configuring the bridge. The CH9325 only supports setting the
+ * baud rate; other settings are handled by the meter driver, e.g. by masking the parity bit.
+ *
+ * # Arguments
+ * `kind` - The bridge chip.
+ * `config` - The serial line settings.
+ *
+ * # Returns
+ * A Result containing the feature reports in order or an ApplicationError for unsupported settings.
+ */
+fn config_reports(kind: &Bridge, config: &UartConfig) -> Result<Vec<Vec<u8>>, ApplicationError> {
+    match kind {
+        Bridge::Cp2110 => {
+            if !(5..=8).contains(&config.data_bits) || !(1..=2).contains(&config.stop_bits) {
+                return Err(ApplicationError::Hid(format!(
+                    "Unsupported serial settings: {} data bits, {} stop bits",
+                    config.data_bits, config.stop_bits
+                )));
+            }
+            let parity = match config.parity {
+                Parity::None => 0,
+                Parity::Odd => 1,
+                Parity::Even => 2,
+            };
+            let mut uart_config = vec![CP2110_UART_CONFIG];
+            uart_config.extend_from_slice(&config.baud_rate.to_be_bytes());
+            // Parity, no flow control, data bits and stop bits
+            uart_config.extend_from_slice(&[parity, 0, config.data_bits - 5, config.stop_bits - 1]);
+            Ok(vec![CP2110_UART_ENABLE.to_vec(), uart_config, CP2110_PURGE_FIFOS.to_vec()])
+        }
+        Bridge::Ch9325 => {
+            let baud_rate = u16::try_from(config.baud_rate).map_err(|_| {
+                ApplicationError::Hid(format!("Unsupported baud rate {}", config.baud_rate))
+            })?;
+            let [low, high] = baud_rate.to_le_bytes();
+            Ok(vec![vec![0x00, low, high, low, high, 0x03]])
+        }
+    }
+}
+
+/**
+ * Splits serial data into output reports.
+ *
+ * # Arguments
+ * `kind` - The bridge chip.
+ * `data` - The bytes to send.
+ *
+ * # Returns
+ * The output reports in order.
+ */
+fn output_reports(kind: &Bridge, data: &[u8]) -> Vec<Vec<u8>> {
+    match kind {
+        Bridge::Cp2110 => data
+            .chunks(CP2110_MAX_PAYLOAD)
+            .map(|chunk| {
+                let mut report = vec![chunk.len() as u8];
+                report.extend_from_slice(chunk);
+                report
+            })
+            .collect(),
+        Bridge::Ch9325 => data
+            .chunks(CH9325_MAX_PAYLOAD)
+            .map(|chunk| {
+                let mut report = vec![0x00, chunk.len() as u8];
+                report.extend_from_slice(chunk);
+                report.resize(2 + CH9325_MAX_PAYLOAD, 0);
+                report
+            })
+            .collect(),
+    }
+}
+
+/**
+ * Extracts the serial data from an input report.
+ *
+ * # Arguments
+ * `kind` - The bridge chip.
+ * `report` - The input report as returned by hidapi.
+ *
+ * # Returns
+ * The received bytes.
+ */
+fn decode_input<'a>(kind: &Bridge, report: &'a [u8]) -> &'a [u8] {
+    let (length, data) = match (kind, report) {
+        (Bridge::Cp2110, [length, data @ ..]) if (*length as usize) <= CP2110_MAX_PAYLOAD => {
+            (*length as usize, data)
+        }
+        (Bridge::Ch9325, [length, data @ ..]) if length & 0xF0 == 0xF0 => ((length & 0x07) as usize, data),
+        _ => (0, report),
+    };
+    &data[..length.min(data.len())]
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn config(baud_rate: u32) -> UartConfig {
+        UartConfig {
+            baud_rate,
+            data_bits: 7,
+            parity: Parity::Odd,
+            stop_bits: 1,
+        }
+    }
+
+    #[test]
+    fn test_cp2110_config() {
+        let reports = config_reports(&Bridge::Cp2110, &config(19200)).unwrap();
+        assert_eq!(reports[0], vec![0x41, 0x01]);
+        assert_eq!(reports[1], vec![0x50, 0x00, 0x00, 0x4B, 0x00, 1, 0, 2, 0]);
+        assert_eq!(reports[2], vec![0x43, 0x03]);
+        let mut invalid = config(9600);
+        invalid.data_bits = 9;
+        assert!(config_reports(&Bridge::Cp2110, &invalid).is_err());
+    }
+
+    #[test]
+    fn test_ch9325_config() {
+        let reports = config_reports(&Bridge::Ch9325, &config(2400)).unwrap();
+        assert_eq!(reports, vec![vec![0x00, 0x60, 0x09, 0x60, 0x09, 0x03]]);
+        assert!(config_reports(&Bridge::Ch9325, &config(115200)).is_err());
+    }
+
+    #[test]
+    fn test_output_reports() {
+        let data: Vec<u8> = (0..70).collect();
+        let reports = output_reports(&Bridge::Cp2110, &data);
+        assert_eq!(reports.len(), 2);
+        assert_eq!(reports[0][0], 63);
+        assert_eq!(reports[1][..], [7, 63, 64, 65, 66, 67, 68, 69]);
+        let reports = output_reports(&Bridge::Ch9325, b"AB");
+        assert_eq!(reports, vec![vec![0x00, 2, b'A', b'B', 0, 0, 0, 0, 0]]);
+    }
+
+    #[test]
+    fn test_decode_input() {
+        assert_eq!(decode_input(&Bridge::Cp2110, &[3, b'a', b'b', b'c', 0, 0]), b"abc");
+        assert_eq!(decode_input(&Bridge::Ch9325, &[0xF2, b'1', b'2', 0, 0, 0, 0, 0]), b"12");
+        assert_eq!(decode_input(&Bridge::Ch9325, &[0x00, 0, 0, 0, 0, 0, 0, 0]), b"");
+    }
+}
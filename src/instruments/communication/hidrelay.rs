@@ -0,0 +1,155 @@
+use std::ffi::CString;
+
+use async_trait::async_trait;
+use tracing::info;
+
+use crate::{
+    arguments::Args,
+    capture,
+    error::ApplicationError,
+    instruments::{
+        command::SwitchCommand,
+        communication::{
+            common::{hid_device_info, Communication, DeviceInfo},
+            registry::Requirement,
+        },
+        reading::Reading,
+    },
+};
+
+/**
+ * Feature report command turning a relay on.
+ */
+const RELAY_ON: u8 = 0xFF;
+/**
+ * Feature report command turning a relay off.
+ */
+const RELAY_OFF: u8 = 0xFD;
+/**
+ * Feature report command turning all relays off.
+ */
+const ALL_OFF: u8 = 0xFC;
+
+/**
+ * Module for USB HID relay boards (the common 16c0:05df "USBRelay" boards) using HID feature reports.
+ */
+pub struct HidRelay {
+    // HID Device instance
+    hiddevice: hidapi::HidDevice,
+}
+
+impl HidRelay {
+    /**
+     * Creates a new instance of HidRelay with the given HID device path.
+     *
+     * # Arguments
+     * `hid_device_path` - A string slice representing the path to the HID device.
+     *
+     * # Returns
+     * A new HidRelay instance.
+     */
+    pub fn new(hid_device_path: &str) -> Result<Self, ApplicationError> {
+        let api = hidapi::HidApi::new_without_enumerate().map_err(|e| {
+            ApplicationError::HidApi("Failed to create HID API instance".into(), e)
+        })?;
+        let c_path = CString::new(hid_device_path.to_string()).map_err(|e| {
+            ApplicationError::Hid(format!(
+                "Failed to create CString for HID device path: {}",
+                e
+            ))
+        })?;
+        let hiddevice = api.open_path(&c_path).map_err(|e| {
+            ApplicationError::HidApi(format!("Failed to open HID device at {}", hid_device_path), e)
+        })?;
+        info!("Opened HID relay at {}", hid_device_path);
+        Ok(HidRelay { hiddevice })
+    }
+
+    /**
+     * Sends a relay feature report.
+     *
+     * # Arguments
+     * `command` - The relay command byte.
+     * `relay` - The relay number, 0 for commands addressing all relays.
+     */
+    fn send_report(&self, command: u8, relay: u8) -> Result<(), ApplicationError> {
+        let report = [0x00, command, relay, 0, 0, 0, 0, 0, 0];
+        capture::written("hid", &report);
+        self.hiddevice
+            .send_feature_report(&report)
+            .map_err(|e| ApplicationError::HidApi("Failed to send feature report".into(), e))
+    }
+
+    /**
+     * Converts a channel number to a relay number.
+     *
+     * # Arguments
+     * `channel` - The channel number, starting at 1.
+     *
+     * # Returns
+     * A Result containing the relay number or an ApplicationError.
+     */
+    fn relay(channel: u16) -> Result<u8, ApplicationError> {
+        match u8::try_from(channel) {
+            Ok(relay) if relay >= 1 => Ok(relay),
+            _ => Err(ApplicationError::Command(format!("Invalid relay {}", channel))),
+        }
+    }
+}
+
+#[async_trait(?Send)]
+impl Communication for HidRelay {
+    /**
+     * Closes or opens relays.
+     *
+     * # Arguments
+     * `commands` - The switch commands to execute.
+     *
+     * # Returns
+     * Always Ok(None) as relays produce no readings, or an ApplicationError.
+     */
+    async fn command(
+        &self,
+        commands: Vec<String>,
+    ) -> Result<Option<Vec<Box<dyn Reading>>>, ApplicationError> {
+        for command in commands {
+            match SwitchCommand::try_from(command.as_str())? {
+                SwitchCommand::Close(channels) => {
+                    for channel in channels {
+                        self.send_report(RELAY_ON, Self::relay(channel)?)?;
+                    }
+                }
+                SwitchCommand::Open(channels) => {
+                    for channel in channels {
+                        self.send_report(RELAY_OFF, Self::relay(channel)?)?;
+                    }
+                }
+                SwitchCommand::OpenAll => self.send_report(ALL_OFF, 0)?,
+            }
+        }
+        Ok(None)
+    }
+
+    /**
+     * Returns the USB descriptor strings of the HID device.
+     *
+     * # Returns
+     * The device info.
+     */
+    fn device_info(&self) -> Option<DeviceInfo> {
+        hid_device_info(&self.hiddevice)
+    }
+}
+
+/**
+ * Opens the relay board on the HID device of the arguments.
+ *
+ * # Arguments
+ * `args` - An Args struct containing the configuration for the desired device.
+ *
+ * # Returns
+ * A Result containing a boxed Communication trait object or an ApplicationError.
+ */
+pub(crate) async fn open(args: &Args) -> Result<Box<dyn Communication>, ApplicationError> {
+    Ok(Box::new(HidRelay::new(Requirement::Hid.argument(args)?)?))
+}
@@ -0,0 +1,138 @@
+use async_trait::async_trait;
+
+use crate::{
+    arguments::Args,
+    error::ApplicationError,
+    instruments::{
+        command::RawCommand,
+        communication::{
+            common::{DeviceInfo, get_terminator, get_uart_config, Communication},
+            hidbridge::HidBridge,
+            registry::Requirement,
+        },
+        reading::{Reading, ScpiRawReading},
+    },
+};
+
+/**
+ * Module for serial instruments behind a HID-UART bridge cable, using the bridge as a plain
+ * serial line. Text commands and RawHex payloads are written, queries and `Read` read a line.
+ */
+pub struct HidUart {
+    /**
+     * Configured bridge.
+     */
+    bridge: HidBridge,
+    /**
+     * Terminator appended to text commands.
+     */
+    terminator: Vec<u8>,
+    /**
+     * Time without data after which a read ends, in milliseconds.
+     */
+    timeout_ms: i32,
+}
+
+impl HidUart {
+    /**
+     * Creates a new instance of HidUart.
+     *
+     * # Arguments
+     * `bridge` - The configured bridge.
+     * `terminator` - Terminator appended to text commands.
+     * `timeout_ms` - Time without data after which a read ends, in milliseconds.
+     *
+     * # Returns
+     * A new HidUart instance.
+     */
+    pub fn new(bridge: HidBridge, terminator: Vec<u8>, timeout_ms: i32) -> Self {
+        Self {
+            bridge,
+            terminator,
+            timeout_ms,
+        }
+    }
+
+    /**
+     * Reads until a newline is received or no data arrives within the timeout.
+     *
+     * # Returns
+     * A Result containing the received line or an ApplicationError if nothing was received.
+     */
+    fn read_line(&self) -> Result<Vec<u8>, ApplicationError> {
+        let mut line = Vec::new();
+        while !line.ends_with(b"\n") {
+            let data = self.bridge.read(self.timeout_ms)?;
+            if data.is_empty() {
+                break;
+            }
+            line.extend_from_slice(&data);
+        }
+        if line.is_empty() {
+            return Err(ApplicationError::Timeout(format!(
+                "No data received within {} ms",
+                self.timeout_ms
+            )));
+        }
+        Ok(line)
+    }
+}
+
+#[async_trait(?Send)]
+impl Communication for HidUart {
+    /**
+     * Sends commands through the bridge.
+     *
+     * # Arguments
+     * `commands` - The commands to execute. `Read` only reads a line.
+     *
+     * # Returns
+     * A Result containing an optional vector of Reading trait objects or an ApplicationError.
+     */
+    async fn command(
+        &self,
+        commands: Vec<String>,
+    ) -> Result<Option<Vec<Box<dyn Reading>>>, ApplicationError> {
+        let mut readings: Vec<Box<dyn Reading>> = Vec::new();
+        for command in commands {
+            if command == "Read" {
+                readings.push(Box::new(ScpiRawReading::new(self.read_line()?)));
+                continue;
+            }
+            let raw_command = RawCommand::parse(&command)?;
+            self.bridge.write(&raw_command.to_bytes(&self.terminator))?;
+            if raw_command.expects_response(None) {
+                readings.push(Box::new(ScpiRawReading::new(self.read_line()?)));
+            }
+        }
+        Ok(Some(readings))
+    }
+
+    /**
+     * Returns the USB descriptor strings of the HID-UART bridge.
+     *
+     * # Returns
+     * The device info, or device info.
+     */
+    fn device_info(&self) -> Option<DeviceInfo> {
+        self.bridge.device_info()
+    }
+}
+
+/**
+ * Opens the bridge on the HID device of the arguments and sets its serial line.
+ *
+ * # Arguments
+ * `args` - An Args struct containing the configuration for the desired device.
+ *
+ * # Returns
+ * A Result containing a boxed Communication trait object or an ApplicationError.
+ */
+pub(crate) async fn open(args: &Args) -> Result<Box<dyn Communication>, ApplicationError> {
+    let bridge =
+        args.connection.bridge.clone().ok_or_else(|| ApplicationError::Hid("HID-UART bridge not provided".into()))?;
+    let bridge = HidBridge::open(Requirement::Hid.argument(args)?, bridge)?;
+    bridge.configure(&get_uart_config(args))?;
+    let terminator = get_terminator(args.connection.terminator.as_ref());
+    Ok(Box::new(HidUart::new(bridge, terminator, args.measurement.duration_ms as i32)))
+}
@@ -0,0 +1,202 @@
+use std::{cell::Cell, time::Duration};
+
+use async_trait::async_trait;
+
+use crate::{
+    arguments::{Args, Parity},
+    error::ApplicationError,
+    instruments::{
+        command::{DmmFunction, KeysightDmmCommand},
+        communication::{
+            common::{Communication, DeviceInfo},
+            registry::Requirement,
+            serial::{SerialLine, UartConfig},
+        },
+        reading::{KeysightDmmReading, Reading, ScpiRawReading},
+    },
+};
+
+/**
+ * Command putting the meter in remote mode. Over RS-232 the 34401A rejects commands until it
+ * is sent.
+ */
+const REMOTE_COMMAND: &str = "SYST:REM";
+
+/**
+ * Time to wait for a response line. A measurement at 100 NPLC with autorange takes several
+ * seconds.
+ */
+const KEYSIGHT_DMM_RESPONSE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/**
+ * Read timeout of the serial port while waiting for response bytes.
+ */
+pub const KEYSIGHT_DMM_READ_TIMEOUT: Duration = Duration::from_millis(100);
+
+/**
+ * Returns the serial settings of the 34401A, 9600 baud with 7 data bits, even parity and 2 stop
+ * bits as shipped.
+ *
+ * # Returns
+ * The serial line settings.
+ */
+pub fn keysight_dmm_uart_config() -> UartConfig {
+    UartConfig {
+        baud_rate: 9600,
+        data_bits: 7,
+        parity: Parity::Even,
+        stop_bits: 2,
+    }
+}
+
+/**
+ * Module for Keysight (Agilent, HP) 34401A bench multimeters on their RS-232 port. Commands are
+ * SCPI terminated with a newline; responses end with a carriage return and a newline. Commands
+ * that are not typed are sent as is.
+ */
+pub struct KeysightDmmSerial {
+    /**
+     * Serial port of the meter.
+     */
+    line: SerialLine,
+    /**
+     * Configured function, queried with `FUNC?` before the first `READ?` unless selected.
+     */
+    function: Cell<Option<DmmFunction>>,
+}
+
+impl KeysightDmmSerial {
+    /**
+     * Creates a new instance of KeysightDmmSerial.
+     *
+     * # Arguments
+     * `line` - The opened serial port.
+     *
+     * # Returns
+     * A new KeysightDmmSerial instance.
+     */
+    pub fn new(line: SerialLine) -> Self {
+        Self {
+            line,
+            function: Cell::new(None),
+        }
+    }
+
+    /**
+     * Sends a SCPI command and reads the response line of queries.
+     *
+     * # Arguments
+     * `command` - The SCPI command without terminator.
+     *
+     * # Returns
+     * A Result containing the response, empty for commands that are not queries, or an ApplicationError.
+     */
+    fn send(&self, command: &str) -> Result<Vec<u8>, ApplicationError> {
+        self.line.write(format!("{}\n", command).as_bytes())?;
+        if !command.contains('?') {
+            return Ok(Vec::new());
+        }
+        let response = self
+            .line
+            .read_response(|data| data.ends_with(b"\n"), KEYSIGHT_DMM_RESPONSE_TIMEOUT)?;
+        if response.is_empty() {
+            return Err(ApplicationError::Timeout(format!("No response to {}", command)));
+        }
+        Ok(response)
+    }
+
+    /**
+     * Returns the configured function, querying the meter the first time.
+     *
+     * # Returns
+     * A Result containing the function or an ApplicationError.
+     */
+    fn function(&self) -> Result<DmmFunction, ApplicationError> {
+        if let Some(function) = self.function.get() {
+            return Ok(function);
+        }
+        let function = DmmFunction::from_response(&self.send("FUNC?")?)?;
+        self.function.set(Some(function));
+        Ok(function)
+    }
+}
+
+#[async_trait(?Send)]
+impl Communication for KeysightDmmSerial {
+    /**
+     * Sends typed commands and SCPI commands to the meter.
+     *
+     * # Arguments
+     * `commands` - The commands to execute.
+     *
+     * # Returns
+     * A Result containing an optional vector of Reading trait objects or an ApplicationError.
+     */
+    async fn command(
+        &self,
+        commands: Vec<String>,
+    ) -> Result<Option<Vec<Box<dyn Reading>>>, ApplicationError> {
+        let mut readings: Vec<Box<dyn Reading>> = Vec::new();
+        for command in commands {
+            match KeysightDmmCommand::parse(&command)? {
+                Some(typed @ KeysightDmmCommand::Configure(function, _)) => {
+                    self.send(&typed.to_wire())?;
+                    self.function.set(Some(function));
+                }
+                Some(typed @ KeysightDmmCommand::Measure(function, _)) => {
+                    let response = self.send(&typed.to_wire())?;
+                    self.function.set(Some(function));
+                    for reading in KeysightDmmReading::parse(function, &response)? {
+                        readings.push(Box::new(reading));
+                    }
+                }
+                Some(typed @ KeysightDmmCommand::Read) => {
+                    let function = self.function()?;
+                    for reading in KeysightDmmReading::parse(function, &self.send(&typed.to_wire())?)? {
+                        readings.push(Box::new(reading));
+                    }
+                }
+                Some(typed @ KeysightDmmCommand::Identify) => {
+                    readings.push(Box::new(ScpiRawReading::new(self.send(&typed.to_wire())?)));
+                }
+                None => {
+                    // The function may have changed, query it again before the next READ?
+                    self.function.set(None);
+                    let response = self.send(&command)?;
+                    if !response.is_empty() {
+                        readings.push(Box::new(ScpiRawReading::new(response)));
+                    }
+                }
+            }
+        }
+        Ok(Some(readings))
+    }
+
+    /**
+     * Returns the USB descriptor strings of the serial adapter.
+     *
+     * # Returns
+     * The device info, or None if the port is not a USB adapter.
+     */
+    fn device_info(&self) -> Option<DeviceInfo> {
+        self.line.device_info()
+    }
+}
+
+/**
+ * Opens the meter on the serial port of the arguments with the fixed line settings of the meter,
+ * asserts DTR as the meter only sends while it is set, and puts the meter in remote mode.
+ *
+ * # Arguments
+ * `args` - An Args struct containing the configuration for the desired device.
+ *
+ * # Returns
+ * A Result containing a boxed Communication trait object or an ApplicationError.
+ */
+pub(crate) async fn open(args: &Args) -> Result<Box<dyn Communication>, ApplicationError> {
+    let line = SerialLine::open(Requirement::Serial.argument(args)?, &keysight_dmm_uart_config(), KEYSIGHT_DMM_READ_TIMEOUT)?;
+    line.set_control_lines(true, true)?;
+    let meter = KeysightDmmSerial::new(line);
+    meter.send(REMOTE_COMMAND)?;
+    Ok(Box::new(meter))
+}
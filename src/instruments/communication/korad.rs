@@ -0,0 +1,135 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+
+use crate::{
+    arguments::Args,
+    error::ApplicationError,
+    instruments::{
+        command::KoradCommand,
+        communication::{
+            common::{DeviceInfo, get_uart_config, Communication},
+            registry::Requirement,
+            serial::SerialLine,
+        },
+        reading::{KoradReading, Reading, ScpiRawReading},
+    },
+};
+
+/**
+ * Time without data after which a response is complete. The responses are not terminated.
+ */
+pub const KORAD_RESPONSE_TIMEOUT: Duration = Duration::from_millis(150);
+
+/**
+ * Time to wait between commands; commands sent back to back are dropped by the power supply.
+ */
+const KORAD_COMMAND_DELAY: Duration = Duration::from_millis(50);
+
+/**
+ * Module for Korad KA3005P / Tenma 72-2540 style power supplies on a USB serial port.
+ */
+pub struct KoradPsu {
+    /**
+     * Serial port of the power supply.
+     */
+    line: SerialLine,
+}
+
+impl KoradPsu {
+    /**
+     * Creates a new instance of KoradPsu.
+     *
+     * # Arguments
+     * `line` - The opened serial port.
+     *
+     * # Returns
+     * A new KoradPsu instance.
+     */
+    pub fn new(line: SerialLine) -> Self {
+        Self { line }
+    }
+
+    /**
+     * Sends the wire commands of a typed command.
+     *
+     * # Arguments
+     * `command` - The typed command.
+     *
+     * # Returns
+     * A Result containing the responses of the queries in order or an ApplicationError.
+     */
+    async fn send(&self, command: &KoradCommand) -> Result<Vec<Vec<u8>>, ApplicationError> {
+        let mut responses = Vec::new();
+        for wire_command in command.to_wire() {
+            self.line.write(wire_command.as_bytes())?;
+            if wire_command.ends_with('?') {
+                responses.push(self.line.read()?);
+            } else {
+                tokio::time::sleep(KORAD_COMMAND_DELAY).await;
+            }
+        }
+        Ok(responses)
+    }
+}
+
+#[async_trait(?Send)]
+impl Communication for KoradPsu {
+    /**
+     * Sends typed commands to the power supply.
+     *
+     * # Arguments
+     * `commands` - The commands to execute.
+     *
+     * # Returns
+     * A Result containing an optional vector of Reading trait objects or an ApplicationError.
+     */
+    async fn command(
+        &self,
+        commands: Vec<String>,
+    ) -> Result<Option<Vec<Box<dyn Reading>>>, ApplicationError> {
+        let mut readings: Vec<Box<dyn Reading>> = Vec::new();
+        for command in commands {
+            let command = KoradCommand::try_from(command.as_str())?;
+            let responses = self.send(&command).await?;
+            match (command, &responses[..]) {
+                (KoradCommand::Measure, [voltage, current, status]) => {
+                    readings.push(Box::new(KoradReading::measured(voltage, current, status)))
+                }
+                (KoradCommand::Setpoints, [voltage, current]) => {
+                    readings.push(Box::new(KoradReading::setpoints(voltage, current)))
+                }
+                (KoradCommand::Status, [status]) => readings.push(Box::new(KoradReading::status(status))),
+                (KoradCommand::Identify, [identity]) => {
+                    readings.push(Box::new(ScpiRawReading::new(identity.clone())))
+                }
+                _ => {}
+            }
+        }
+        Ok(Some(readings))
+    }
+
+    /**
+     * Returns the USB descriptor strings of the serial adapter.
+     *
+     * # Returns
+     * The device info, or None if the port is not a USB adapter.
+     */
+    fn device_info(&self) -> Option<DeviceInfo> {
+        self.line.device_info()
+    }
+}
+
+/**
+ * Opens the power supply on the serial port of the arguments.
+ *
+ * # Arguments
+ * `args` - An Args struct containing the configuration for the desired device.
+ *
+ * # Returns
+ * A Result containing a boxed Communication trait object or an ApplicationError.
+ */
+pub(crate) async fn open(args: &Args) -> Result<Box<dyn Communication>, ApplicationError> {
+    let line = SerialLine::open(Requirement::Serial.argument(args)?, &get_uart_config(args), KORAD_RESPONSE_TIMEOUT)?;
+    Ok(Box::new(KoradPsu::new(line)))
+}
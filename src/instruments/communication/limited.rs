@@ -0,0 +1,69 @@
+use async_trait::async_trait;
+
+use crate::{
+    error::ApplicationError,
+    instruments::{command::Limits, communication::common::{Communication, DeviceInfo}, reading::Reading},
+};
+
+/**
+ * Wraps a device so that every command is checked against the hard limits of the device
+ * before anything is sent. A command batch with a setpoint above a limit is rejected as a whole.
+ */
+pub struct LimitedCommunication {
+    /**
+     * The wrapped device.
+     */
+    device: Box<dyn Communication>,
+    /**
+     * The limits of the device.
+     */
+    limits: Limits,
+}
+
+impl LimitedCommunication {
+    /**
+     * Creates a new instance of LimitedCommunication.
+     *
+     * # Arguments
+     * `device` - The device to wrap.
+     * `limits` - The limits of the device.
+     *
+     * # Returns
+     * A new LimitedCommunication instance.
+     */
+    pub fn new(device: Box<dyn Communication>, limits: Limits) -> Self {
+        Self { device, limits }
+    }
+}
+
+#[async_trait(?Send)]
+impl Communication for LimitedCommunication {
+    /**
+     * Checks the commands against the limits and sends them to the wrapped device.
+     *
+     * # Arguments
+     * `commands` - The commands to execute.
+     *
+     * # Returns
+     * A Result containing an optional vector of Reading trait objects or an ApplicationError.
+     */
+    async fn command(
+        &self,
+        commands: Vec<String>,
+    ) -> Result<Option<Vec<Box<dyn Reading>>>, ApplicationError> {
+        for command in &commands {
+            self.limits.check(command)?;
+        }
+        self.device.command(commands).await
+    }
+
+    /**
+     * Returns the device info of the wrapped device.
+     *
+     * # Returns
+     * The device info, or None.
+     */
+    fn device_info(&self) -> Option<DeviceInfo> {
+        self.device.device_info()
+    }
+}
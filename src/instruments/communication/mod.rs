@@ -1,6 +1,70 @@
+#[cfg(feature = "serial")]
+mod atorch;
+#[cfg(feature = "audio")]
+mod audioin;
+#[cfg(feature = "ble")]
+mod ble;
+#[cfg(feature = "hid")]
+mod brymen;
+mod calibrated;
+mod checked;
 pub mod common;
+mod confirm;
+#[cfg(feature = "serial")]
+mod fluke;
+#[cfg(feature = "gpib")]
+mod gpib;
+#[cfg(feature = "hid")]
+mod hidbridge;
+#[cfg(feature = "hid")]
+mod hidrelay;
+#[cfg(feature = "hid")]
+mod hiduart;
+#[cfg(feature = "serial")]
+mod keysightdmm;
+#[cfg(feature = "serial")]
+mod korad;
+mod limited;
+#[cfg(all(feature = "modbus", any(feature = "serial", feature = "network")))]
+mod modbus;
+#[cfg(all(feature = "network", feature = "modbus"))]
+mod modbustcp;
+#[cfg(feature = "serial")]
+mod owonxdm;
+mod paired;
+#[cfg(feature = "usb")]
+mod quirks;
+mod readonly;
+mod replay;
+pub mod registry;
+#[cfg(all(feature = "serial", feature = "modbus"))]
+mod riden;
+mod scpi;
+#[cfg(feature = "usb")]
 mod scpiusb;
+#[cfg(any(feature = "serial", feature = "hid"))]
+mod serial;
+#[cfg(feature = "serial")]
+mod serialtext;
+#[cfg(feature = "serial")]
+mod tc66;
+mod timed;
+#[cfg(any(feature = "usb", feature = "hid", all(test, feature = "network", feature = "modbus")))]
+mod transport;
+#[cfg(feature = "hid")]
 mod unit161d;
+#[cfg(feature = "usb")]
+mod usbdevice;
+#[cfg(any(feature = "usb", feature = "hid"))]
+mod usbselector;
+#[cfg(feature = "serial")]
+mod ut325;
+#[cfg(feature = "serial")]
+mod ut71;
+mod verified;
 
 pub use common::Communication;
-pub use common::get_communication_device;
\ No newline at end of file
+pub use common::get_communication_device;
+pub use replay::decode_frame;
+#[cfg(all(test, feature = "network", feature = "modbus"))]
+pub(crate) use transport::scripted::LoopbackModbus;
\ No newline at end of file
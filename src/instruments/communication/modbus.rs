@@ -0,0 +1,592 @@
+use std::time::Duration;
+#[cfg(feature = "network")]
+use std::{
+    cell::Cell,
+    io::{ErrorKind, Read, Write},
+    net::{IpAddr, SocketAddr, TcpStream, ToSocketAddrs},
+};
+
+use tracing::debug;
+#[cfg(feature = "network")]
+use tracing::info;
+
+use crate::{error::ApplicationError, logging::hex_dump};
+#[cfg(feature = "network")]
+use crate::capture;
+#[cfg(feature = "serial")]
+use crate::instruments::{communication::{common::DeviceInfo, serial::SerialLine}, crc::crc16_modbus};
+
+/**
+ * Time to wait for a complete response frame.
+ */
+pub const MODBUS_RESPONSE_TIMEOUT: Duration = Duration::from_secs(1);
+
+/**
+ * Time to wait for a Modbus TCP server to accept the connection.
+ */
+#[cfg(feature = "network")]
+const MODBUS_CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/**
+ * Port of Modbus TCP servers.
+ */
+#[cfg(feature = "network")]
+pub const MODBUS_TCP_PORT: u16 = 502;
+
+/**
+ * Length of the MBAP header of Modbus TCP frames, up to and including the unit identifier.
+ */
+#[cfg(feature = "network")]
+const MBAP_HEADER_LENGTH: usize = 7;
+
+// Function codes
+const READ_HOLDING_REGISTERS: u8 = 0x03;
+const READ_INPUT_REGISTERS: u8 = 0x04;
+#[cfg(feature = "serial")]
+const WRITE_SINGLE_REGISTER: u8 = 0x06;
+
+/**
+ * Bit set in the function code of exception responses.
+ */
+const EXCEPTION: u8 = 0x80;
+
+/**
+ * Largest number of registers one read request may return.
+ */
+const MAX_READ_REGISTERS: u16 = 125;
+
+/**
+ * Appends the CRC to a frame.
+ *
+ * # Arguments
+ * `frame` - The frame without CRC.
+ *
+ * # Returns
+ * The frame with the CRC, low byte first.
+ */
+#[cfg(feature = "serial")]
+fn with_crc(mut frame: Vec<u8>) -> Vec<u8> {
+    let crc = crc16_modbus(&frame);
+    frame.extend_from_slice(&crc.to_le_bytes());
+    frame
+}
+
+/**
+ * Returns the length of a response frame once its header has been received.
+ *
+ * # Arguments
+ * `data` - The received bytes.
+ *
+ * # Returns
+ * The frame length including the CRC, or None while the header is incomplete.
+ */
+#[cfg(feature = "serial")]
+fn response_length(data: &[u8]) -> Option<usize> {
+    match *data.get(1)? {
+        function if function & EXCEPTION != 0 => Some(5),
+        READ_HOLDING_REGISTERS | READ_INPUT_REGISTERS => data.get(2).map(|count| 3 + *count as usize + 2),
+        _ => Some(8),
+    }
+}
+
+/**
+ * Gets the description of a Modbus exception code.
+ *
+ * # Arguments
+ * `code` - The exception code.
+ *
+ * # Returns
+ * The description.
+ */
+fn exception(code: u8) -> &'static str {
+    match code {
+        0x01 => "illegal function",
+        0x02 => "illegal data address",
+        0x03 => "illegal data value",
+        0x04 => "server device failure",
+        0x06 => "server device busy",
+        _ => "unknown exception",
+    }
+}
+
+/**
+ * Checks a response frame and returns its payload.
+ *
+ * # Arguments
+ * `address` - The address of the unit the request was sent to.
+ * `function` - The function code of the request.
+ * `frame` - The response frame.
+ *
+ * # Returns
+ * A Result containing the bytes after the function code without CRC, or an ApplicationError
+ * for an invalid frame or an exception response.
+ */
+#[cfg(feature = "serial")]
+fn check_response(address: u8, function: u8, frame: &[u8]) -> Result<&[u8], ApplicationError> {
+    let invalid = |reason: &str| ApplicationError::Command(format!("Invalid Modbus response {:02x?}: {}", frame, reason));
+    if frame.len() < 5 || response_length(frame) != Some(frame.len()) {
+        return Err(invalid("incomplete frame"));
+    }
+    let (body, crc) = frame.split_at(frame.len() - 2);
+    if crc16_modbus(body).to_le_bytes() != crc {
+        debug!("Modbus CRC mismatch: computed {}, received {}", hex_dump(&crc16_modbus(body).to_le_bytes()), hex_dump(crc));
+        return Err(invalid("CRC mismatch"));
+    }
+    debug!("Modbus CRC {} ok", hex_dump(crc));
+    if body[0] != address {
+        return Err(invalid("wrong unit address"));
+    }
+    check_pdu(address, function, &body[1..])
+}
+
+/**
+ * Checks the function code of a response PDU and returns its data.
+ *
+ * # Arguments
+ * `address` - The address of the unit the request was sent to.
+ * `function` - The function code of the request.
+ * `pdu` - The response PDU, starting with the function code.
+ *
+ * # Returns
+ * A Result containing the bytes after the function code, or an ApplicationError for an
+ * exception response or a wrong function code.
+ */
+fn check_pdu(address: u8, function: u8, pdu: &[u8]) -> Result<&[u8], ApplicationError> {
+    match pdu {
+        [code, exception_code, ..] if *code == function | EXCEPTION => Err(ApplicationError::Command(format!(
+            "Modbus unit {} rejected function {:#04x}: {}",
+            address,
+            function,
+            exception(*exception_code)
+        ))),
+        [code, data @ ..] if *code == function => Ok(data),
+        _ => Err(ApplicationError::Command(format!(
+            "Invalid Modbus response {:02x?}: wrong function code",
+            pdu
+        ))),
+    }
+}
+
+/**
+ * A Modbus client. The register functions are built on a request/response transaction, which
+ * the serial and TCP clients frame differently.
+ */
+pub trait Modbus {
+    /**
+     * Sends a request and reads the response.
+     *
+     * # Arguments
+     * `request` - The request PDU, starting with the function code.
+     *
+     * # Returns
+     * A Result containing the response bytes after the function code or an ApplicationError.
+     */
+    fn transact(&self, request: &[u8]) -> Result<Vec<u8>, ApplicationError>;
+
+    /**
+     * Reads consecutive holding registers (function 0x03).
+     *
+     * # Arguments
+     * `start` - The first register.
+     * `count` - The number of registers, 1 to 125.
+     *
+     * # Returns
+     * A Result containing the register values or an ApplicationError.
+     */
+    fn read_holding_registers(&self, start: u16, count: u16) -> Result<Vec<u16>, ApplicationError> {
+        read_registers(self, READ_HOLDING_REGISTERS, start, count)
+    }
+
+    /**
+     * Reads consecutive input registers (function 0x04).
+     *
+     * # Arguments
+     * `start` - The first register.
+     * `count` - The number of registers, 1 to 125.
+     *
+     * # Returns
+     * A Result containing the register values or an ApplicationError.
+     */
+    #[cfg(feature = "network")]
+    fn read_input_registers(&self, start: u16, count: u16) -> Result<Vec<u16>, ApplicationError> {
+        read_registers(self, READ_INPUT_REGISTERS, start, count)
+    }
+
+    /**
+     * Writes a holding register (function 0x06). The unit echoes the request.
+     *
+     * # Arguments
+     * `register` - The register.
+     * `value` - The value.
+     *
+     * # Returns
+     * A Result indicating success or an ApplicationError.
+     */
+    #[cfg(feature = "serial")]
+    fn write_register(&self, register: u16, value: u16) -> Result<(), ApplicationError> {
+        let mut request = vec![WRITE_SINGLE_REGISTER];
+        request.extend_from_slice(&register.to_be_bytes());
+        request.extend_from_slice(&value.to_be_bytes());
+        let payload = self.transact(&request)?;
+        if payload != request[1..] {
+            return Err(ApplicationError::Command(format!(
+                "Modbus write of register {} was not confirmed",
+                register
+            )));
+        }
+        Ok(())
+    }
+}
+
+/**
+ * Reads consecutive holding or input registers.
+ *
+ * # Arguments
+ * `modbus` - The client.
+ * `function` - The read function code.
+ * `start` - The first register.
+ * `count` - The number of registers, 1 to 125.
+ *
+ * # Returns
+ * A Result containing the register values or an ApplicationError.
+ */
+fn read_registers<M: Modbus + ?Sized>(modbus: &M, function: u8, start: u16, count: u16) -> Result<Vec<u16>, ApplicationError> {
+    if count == 0 || count > MAX_READ_REGISTERS {
+        return Err(ApplicationError::Command(format!("Cannot read {} Modbus registers", count)));
+    }
+    let mut request = vec![function];
+    request.extend_from_slice(&start.to_be_bytes());
+    request.extend_from_slice(&count.to_be_bytes());
+    let payload = modbus.transact(&request)?;
+    if payload.len() != 1 + 2 * count as usize {
+        return Err(ApplicationError::Command(format!(
+            "Expected {} Modbus registers, got {} bytes",
+            count,
+            payload.len()
+        )));
+    }
+    Ok(payload[1..]
+        .chunks_exact(2)
+        .map(|word| u16::from_be_bytes([word[0], word[1]]))
+        .collect())
+}
+
+/**
+ * Minimal Modbus-RTU client on a serial port, supporting the holding register functions used
+ * by bench power supplies.
+ */
+#[cfg(feature = "serial")]
+pub struct ModbusRtu {
+    line: SerialLine,
+    /**
+     * Address of the unit, 1 to 247.
+     */
+    address: u8,
+}
+
+#[cfg(feature = "serial")]
+impl ModbusRtu {
+    /**
+     * Creates a new instance of ModbusRtu.
+     *
+     * # Arguments
+     * `line` - The opened serial port.
+     * `address` - The address of the unit.
+     *
+     * # Returns
+     * A new ModbusRtu instance.
+     */
+    pub fn new(line: SerialLine, address: u8) -> Self {
+        Self { line, address }
+    }
+
+    /**
+     * Gets the USB descriptor strings of the serial adapter.
+     *
+     * # Returns
+     * The device info, or None if the port is not a USB adapter.
+     */
+    pub fn device_info(&self) -> Option<DeviceInfo> {
+        self.line.device_info()
+    }
+}
+
+#[cfg(feature = "serial")]
+impl Modbus for ModbusRtu {
+    /**
+     * Sends a request framed with the unit address and CRC and reads the response.
+     *
+     * # Arguments
+     * `request` - The request without address and CRC.
+     *
+     * # Returns
+     * A Result containing the response payload or an ApplicationError.
+     */
+    fn transact(&self, request: &[u8]) -> Result<Vec<u8>, ApplicationError> {
+        let mut frame = vec![self.address];
+        frame.extend_from_slice(request);
+        self.line.write(&with_crc(frame))?;
+        let response = self.line.read_response(
+            |data| response_length(data).is_some_and(|length| data.len() >= length),
+            MODBUS_RESPONSE_TIMEOUT,
+        )?;
+        let length = response_length(&response).unwrap_or(response.len()).min(response.len());
+        check_response(self.address, request[0], &response[..length]).map(|payload| payload.to_vec())
+    }
+}
+
+/**
+ * Adds the Modbus TCP port to an address without port.
+ *
+ * # Arguments
+ * `address` - A host name or IP address, optionally with a port, e.g. `meter.lan:1502` or `[::1]:502`.
+ *
+ * # Returns
+ * The address with port.
+ */
+#[cfg(feature = "network")]
+fn with_default_port(address: &str) -> String {
+    if address.parse::<SocketAddr>().is_ok() {
+        return address.to_string();
+    }
+    if let Ok(ip) = address.trim_matches(['[', ']']).parse::<IpAddr>() {
+        return SocketAddr::new(ip, MODBUS_TCP_PORT).to_string();
+    }
+    match address.rsplit_once(':') {
+        Some((_, port)) if port.parse::<u16>().is_ok() => address.to_string(),
+        _ => format!("{}:{}", address, MODBUS_TCP_PORT),
+    }
+}
+
+/**
+ * Builds a Modbus TCP request frame: the MBAP header followed by the request PDU.
+ *
+ * # Arguments
+ * `transaction` - The transaction identifier.
+ * `unit` - The unit identifier.
+ * `request` - The request PDU.
+ *
+ * # Returns
+ * The frame.
+ */
+#[cfg(feature = "network")]
+fn tcp_frame(transaction: u16, unit: u8, request: &[u8]) -> Vec<u8> {
+    let mut frame = Vec::with_capacity(MBAP_HEADER_LENGTH + request.len());
+    frame.extend_from_slice(&transaction.to_be_bytes());
+    frame.extend_from_slice(&0u16.to_be_bytes());
+    frame.extend_from_slice(&(request.len() as u16 + 1).to_be_bytes());
+    frame.push(unit);
+    frame.extend_from_slice(request);
+    frame
+}
+
+/**
+ * Checks the MBAP header of a response.
+ *
+ * # Arguments
+ * `transaction` - The transaction identifier of the request.
+ * `unit` - The unit identifier of the request.
+ * `header` - The header of the response.
+ *
+ * # Returns
+ * A Result containing the length of the PDU following the header, or an ApplicationError.
+ */
+#[cfg(feature = "network")]
+fn check_mbap(transaction: u16, unit: u8, header: &[u8; MBAP_HEADER_LENGTH]) -> Result<usize, ApplicationError> {
+    let invalid = |reason: &str| ApplicationError::Command(format!("Invalid Modbus TCP header {:02x?}: {}", header, reason));
+    let length = u16::from_be_bytes([header[4], header[5]]) as usize;
+    if u16::from_be_bytes([header[0], header[1]]) != transaction {
+        return Err(invalid("wrong transaction"));
+    }
+    if header[2..4] != [0, 0] {
+        return Err(invalid("not the Modbus protocol"));
+    }
+    if header[6] != unit {
+        return Err(invalid("wrong unit identifier"));
+    }
+    if !(2..=254).contains(&length) {
+        return Err(invalid("invalid length"));
+    }
+    Ok(length - 1)
+}
+
+/**
+ * Modbus TCP client, for panel meters, gateways and other devices on the network.
+ */
+#[cfg(feature = "network")]
+pub struct ModbusTcp {
+    stream: TcpStream,
+    /**
+     * Unit identifier, used by gateways to address serial devices behind them.
+     */
+    unit: u8,
+    /**
+     * Identifier of the last transaction.
+     */
+    transaction: Cell<u16>,
+}
+
+#[cfg(feature = "network")]
+impl ModbusTcp {
+    /**
+     * Connects to a Modbus TCP server.
+     *
+     * # Arguments
+     * `address` - The host name or IP address, with the port if not 502.
+     * `unit` - The unit identifier.
+     * `timeout` - Time to wait for a response.
+     *
+     * # Returns
+     * A Result containing the ModbusTcp instance or an ApplicationError.
+     */
+    pub fn connect(address: &str, unit: u8, timeout: Duration) -> Result<Self, ApplicationError> {
+        let address = with_default_port(address);
+        let addresses = address
+            .to_socket_addrs()
+            .map_err(|e| ApplicationError::NotFound(format!("Could not resolve {}: {}", address, e)))?;
+        let mut error = None;
+        for socket_address in addresses {
+            match TcpStream::connect_timeout(&socket_address, MODBUS_CONNECT_TIMEOUT) {
+                Ok(stream) => {
+                    let configure = |e: std::io::Error| ApplicationError::Network(format!("Could not configure {}: {}", address, e));
+                    stream.set_read_timeout(Some(timeout)).map_err(configure)?;
+                    stream.set_write_timeout(Some(timeout)).map_err(configure)?;
+                    stream.set_nodelay(true).map_err(configure)?;
+                    info!("Connected to Modbus TCP server {}", socket_address);
+                    return Ok(Self { stream, unit, transaction: Cell::new(0) });
+                }
+                Err(e) => error = Some(e),
+            }
+        }
+        Err(ApplicationError::NotFound(format!(
+            "Could not connect to {}: {}",
+            address,
+            error.map_or("no address".to_string(), |e| e.to_string())
+        )))
+    }
+
+    /**
+     * Converts an error of the connection.
+     *
+     * # Arguments
+     * `e` - The error.
+     *
+     * # Returns
+     * A Timeout error if no response arrived in time, else a Network error.
+     */
+    fn error(e: std::io::Error) -> ApplicationError {
+        match e.kind() {
+            ErrorKind::WouldBlock | ErrorKind::TimedOut => ApplicationError::Timeout("No Modbus TCP response".into()),
+            _ => ApplicationError::Network(format!("Modbus TCP connection failed: {}", e)),
+        }
+    }
+}
+
+#[cfg(feature = "network")]
+impl Modbus for ModbusTcp {
+    /**
+     * Sends a request framed with the MBAP header and reads the response.
+     *
+     * # Arguments
+     * `request` - The request PDU.
+     *
+     * # Returns
+     * A Result containing the response payload or an ApplicationError.
+     */
+    fn transact(&self, request: &[u8]) -> Result<Vec<u8>, ApplicationError> {
+        let transaction = self.transaction.get().wrapping_add(1);
+        self.transaction.set(transaction);
+        let frame = tcp_frame(transaction, self.unit, request);
+        debug!("Modbus TCP request: {}", hex_dump(&frame));
+        capture::written("tcp", &frame);
+        (&self.stream).write_all(&frame).map_err(Self::error)?;
+        let mut header = [0u8; MBAP_HEADER_LENGTH];
+        (&self.stream).read_exact(&mut header).map_err(Self::error)?;
+        let length = check_mbap(transaction, self.unit, &header)?;
+        let mut pdu = vec![0u8; length];
+        (&self.stream).read_exact(&mut pdu).map_err(Self::error)?;
+        debug!("Modbus TCP response: {} {}", hex_dump(&header), hex_dump(&pdu));
+        capture::read("tcp", &[header.as_slice(), &pdu].concat());
+        check_pdu(self.unit, request[0], &pdu).map(|payload| payload.to_vec())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    #[cfg(feature = "serial")]
+    fn test_with_crc() {
+        assert_eq!(
+            with_crc(vec![0x01, 0x03, 0x00, 0x00, 0x00, 0x01]),
+            [0x01, 0x03, 0x00, 0x00, 0x00, 0x01, 0x84, 0x0a]
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "serial")]
+    fn test_response_length() {
+        assert_eq!(response_length(&[0x01]), None);
+        assert_eq!(response_length(&[0x01, 0x03]), None);
+        assert_eq!(response_length(&[0x01, 0x03, 0x04]), Some(9));
+        assert_eq!(response_length(&[0x01, 0x06]), Some(8));
+        assert_eq!(response_length(&[0x01, 0x83]), Some(5));
+    }
+
+    #[test]
+    #[cfg(feature = "serial")]
+    fn test_check_response() {
+        let frame = with_crc(vec![0x01, 0x03, 0x02, 0xea, 0x9e]);
+        assert_eq!(check_response(1, READ_HOLDING_REGISTERS, &frame).unwrap(), [0x02, 0xea, 0x9e]);
+        assert!(check_response(2, READ_HOLDING_REGISTERS, &frame).is_err());
+        let mut corrupted = frame.clone();
+        corrupted[3] ^= 1;
+        assert!(check_response(1, READ_HOLDING_REGISTERS, &corrupted).is_err());
+        assert!(check_response(1, WRITE_SINGLE_REGISTER, &frame).is_err());
+        let exception = with_crc(vec![0x01, 0x86, 0x02]);
+        let error = check_response(1, WRITE_SINGLE_REGISTER, &exception).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Command Error: Modbus unit 1 rejected function 0x06: illegal data address"
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "network")]
+    fn test_with_default_port() {
+        assert_eq!(with_default_port("192.168.1.50"), "192.168.1.50:502");
+        assert_eq!(with_default_port("192.168.1.50:1502"), "192.168.1.50:1502");
+        assert_eq!(with_default_port("meter.lan"), "meter.lan:502");
+        assert_eq!(with_default_port("meter.lan:1502"), "meter.lan:1502");
+        assert_eq!(with_default_port("::1"), "[::1]:502");
+        assert_eq!(with_default_port("[::1]:1502"), "[::1]:1502");
+    }
+
+    #[test]
+    #[cfg(feature = "network")]
+    fn test_tcp_frame() {
+        assert_eq!(
+            tcp_frame(0x0102, 1, &[READ_INPUT_REGISTERS, 0x00, 0x00, 0x00, 0x02]),
+            [0x01, 0x02, 0x00, 0x00, 0x00, 0x06, 0x01, 0x04, 0x00, 0x00, 0x00, 0x02]
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "network")]
+    fn test_check_mbap() {
+        assert_eq!(check_mbap(7, 1, &[0x00, 0x07, 0x00, 0x00, 0x00, 0x07, 0x01]).unwrap(), 6);
+        assert!(check_mbap(8, 1, &[0x00, 0x07, 0x00, 0x00, 0x00, 0x07, 0x01]).is_err());
+        assert!(check_mbap(7, 2, &[0x00, 0x07, 0x00, 0x00, 0x00, 0x07, 0x01]).is_err());
+        assert!(check_mbap(7, 1, &[0x00, 0x07, 0x00, 0x01, 0x00, 0x07, 0x01]).is_err());
+        assert!(check_mbap(7, 1, &[0x00, 0x07, 0x00, 0x00, 0x00, 0x01, 0x01]).is_err());
+    }
+
+    #[test]
+    fn test_check_pdu() {
+        assert_eq!(check_pdu(1, READ_INPUT_REGISTERS, &[0x04, 0x02, 0x00, 0x2a]).unwrap(), [0x02, 0x00, 0x2a]);
+        let error = check_pdu(1, READ_INPUT_REGISTERS, &[0x84, 0x02]).unwrap_err();
+        assert_eq!(error.to_string(), "Command Error: Modbus unit 1 rejected function 0x04: illegal data address");
+        assert!(check_pdu(1, READ_INPUT_REGISTERS, &[0x03, 0x02, 0x00, 0x2a]).is_err());
+    }
+}
@@ -0,0 +1,241 @@
+use std::{collections::HashMap, time::Duration};
+
+use async_trait::async_trait;
+
+use crate::{
+    arguments::Args,
+    error::ApplicationError,
+    instruments::{
+        command::ModbusCommand,
+        communication::{
+            common::Communication,
+            modbus::{Modbus, ModbusTcp, MODBUS_RESPONSE_TIMEOUT},
+            registry::Requirement,
+        },
+        reading::{ModbusReading, ModbusRegister, Reading, RegisterTable},
+    },
+};
+
+/**
+ * Unit identifier used when none is given. Most devices answer on any identifier.
+ */
+const MODBUS_TCP_DEFAULT_UNIT: u8 = 1;
+
+/**
+ * Largest number of registers read in one request.
+ */
+const MAX_SPAN: u16 = 125;
+
+/**
+ * Consecutive registers of one table read in a single request.
+ */
+#[derive(Debug, PartialEq)]
+struct Span {
+    table: RegisterTable,
+    start: u16,
+    count: u16,
+}
+
+/**
+ * Groups registers into as few read requests as possible. Registers are only merged when they
+ * are adjacent or overlap, as reading unmapped registers in a gap fails on many devices.
+ *
+ * # Arguments
+ * `registers` - The registers to read.
+ *
+ * # Returns
+ * The spans to read.
+ */
+fn spans(registers: &[&ModbusRegister]) -> Vec<Span> {
+    let mut sorted: Vec<&&ModbusRegister> = registers.iter().collect();
+    sorted.sort_by_key(|register| (register.table, register.address));
+    let mut spans: Vec<Span> = Vec::new();
+    for register in sorted {
+        let end = register.address as u32 + register.words() as u32;
+        match spans.last_mut() {
+            Some(span)
+                if span.table == register.table
+                    && register.address as u32 <= span.start as u32 + span.count as u32
+                    && end - span.start as u32 <= MAX_SPAN as u32 =>
+            {
+                span.count = span.count.max((end - span.start as u32) as u16);
+            }
+            _ => spans.push(Span {
+                table: register.table,
+                start: register.address,
+                count: register.words(),
+            }),
+        }
+    }
+    spans
+}
+
+/**
+ * Module for Modbus TCP devices such as panel power meters, industrial sensors and Modbus
+ * gateways. The registers read and how their values are decoded are given by the register map
+ * of the device profile.
+ */
+pub struct ModbusTcpDevice {
+    modbus: ModbusTcp,
+    registers: Vec<ModbusRegister>,
+}
+
+impl ModbusTcpDevice {
+    /**
+     * Creates a new instance of ModbusTcpDevice.
+     *
+     * # Arguments
+     * `modbus` - The connected Modbus TCP client.
+     * `registers` - The register map.
+     *
+     * # Returns
+     * A new ModbusTcpDevice instance.
+     */
+    pub fn new(modbus: ModbusTcp, registers: Vec<ModbusRegister>) -> Self {
+        Self { modbus, registers }
+    }
+
+    /**
+     * Reads registers of the map, merging adjacent registers into one request.
+     *
+     * # Arguments
+     * `registers` - The registers to read.
+     *
+     * # Returns
+     * A Result containing the reading with the values in the order given, or an ApplicationError.
+     */
+    fn read(&self, registers: &[&ModbusRegister]) -> Result<ModbusReading, ApplicationError> {
+        let mut words = HashMap::new();
+        for span in spans(registers) {
+            let values = match span.table {
+                RegisterTable::Holding => self.modbus.read_holding_registers(span.start, span.count)?,
+                RegisterTable::Input => self.modbus.read_input_registers(span.start, span.count)?,
+            };
+            for (address, value) in (span.start..).zip(values) {
+                words.insert((span.table, address), value);
+            }
+        }
+        let decoded = registers
+            .iter()
+            .map(|register| {
+                let values = (register.address..register.address.saturating_add(register.words()))
+                    .filter_map(|address| words.get(&(register.table, address)).copied())
+                    .collect();
+                (*register, values)
+            })
+            .collect::<Vec<(&ModbusRegister, Vec<u16>)>>();
+        ModbusReading::decode(&decoded)
+    }
+
+    /**
+     * Finds registers of the map by name.
+     *
+     * # Arguments
+     * `names` - The register names.
+     *
+     * # Returns
+     * A Result containing the registers or an ApplicationError for unknown names.
+     */
+    fn find(&self, names: &[String]) -> Result<Vec<&ModbusRegister>, ApplicationError> {
+        names
+            .iter()
+            .map(|name| {
+                self.registers
+                    .iter()
+                    .find(|register| &register.name == name)
+                    .ok_or_else(|| ApplicationError::Command(format!("No register {} in the register map", name)))
+            })
+            .collect()
+    }
+}
+
+#[async_trait(?Send)]
+impl Communication for ModbusTcpDevice {
+    /**
+     * Reads the register map of the device.
+     *
+     * # Arguments
+     * `commands` - The commands to execute.
+     *
+     * # Returns
+     * A Result containing an optional vector of Reading trait objects or an ApplicationError.
+     */
+    async fn command(&self, commands: Vec<String>) -> Result<Option<Vec<Box<dyn Reading>>>, ApplicationError> {
+        let mut readings: Vec<Box<dyn Reading>> = Vec::new();
+        for command in commands {
+            let registers = match ModbusCommand::try_from(command.as_str())? {
+                ModbusCommand::Measure => self.registers.iter().collect(),
+                ModbusCommand::Read(names) => self.find(&names)?,
+            };
+            readings.push(Box::new(self.read(&registers)?));
+        }
+        Ok(Some(readings))
+    }
+}
+
+/**
+ * Connects to the device at the TCP address of the arguments, with the unit identifier of the
+ * --modbus-address argument and the register map of the profile.
+ *
+ * # Arguments
+ * `args` - An Args struct containing the configuration for the desired device.
+ *
+ * # Returns
+ * A Result containing a boxed Communication trait object or an ApplicationError.
+ */
+pub(crate) async fn open(args: &Args) -> Result<Box<dyn Communication>, ApplicationError> {
+    if args.registers.is_empty() {
+        return Err(ApplicationError::Config("Modbus TCP devices need the registers of a profile".into()));
+    }
+    let timeout = args.connection.read_timeout_ms.map_or(MODBUS_RESPONSE_TIMEOUT, Duration::from_millis);
+    let modbus = ModbusTcp::connect(
+        Requirement::Tcp.argument(args)?,
+        args.connection.modbus_address.unwrap_or(MODBUS_TCP_DEFAULT_UNIT),
+        timeout,
+    )?;
+    Ok(Box::new(ModbusTcpDevice::new(modbus, args.registers.clone())))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn register(address: u16, table: &str, data_type: &str) -> ModbusRegister {
+        toml::from_str(&format!(
+            "name = \"r{}\"\naddress = {}\ntable = \"{}\"\ntype = \"{}\"",
+            address, address, table, data_type
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_spans() {
+        let registers = [
+            register(6, "input", "f32"),
+            register(0, "input", "f32"),
+            register(2, "input", "u16"),
+            register(3, "input", "u32"),
+            register(0, "holding", "u16"),
+            register(200, "input", "u16"),
+        ];
+        let registers: Vec<&ModbusRegister> = registers.iter().collect();
+        assert_eq!(
+            spans(&registers),
+            [
+                Span { table: RegisterTable::Holding, start: 0, count: 1 },
+                Span { table: RegisterTable::Input, start: 0, count: 5 },
+                Span { table: RegisterTable::Input, start: 6, count: 2 },
+                Span { table: RegisterTable::Input, start: 200, count: 1 },
+            ]
+        );
+        let long: Vec<ModbusRegister> = (0..63).map(|index| register(2 * index, "input", "f32")).collect();
+        let long: Vec<&ModbusRegister> = long.iter().collect();
+        assert_eq!(
+            spans(&long),
+            [
+                Span { table: RegisterTable::Input, start: 0, count: 124 },
+                Span { table: RegisterTable::Input, start: 124, count: 2 },
+            ]
+        );
+    }
+}
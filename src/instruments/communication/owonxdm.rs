@@ -0,0 +1,183 @@
+use std::{cell::Cell, time::Duration};
+
+use async_trait::async_trait;
+
+use crate::{
+    arguments::{Args, Parity},
+    error::ApplicationError,
+    instruments::{
+        command::{OwonXdmCommand, XdmFunction},
+        communication::{
+            common::{Communication, DeviceInfo},
+            registry::Requirement,
+            serial::{SerialLine, UartConfig},
+        },
+        reading::{OwonXdmReading, Reading, ScpiRawReading},
+    },
+};
+
+/**
+ * Time to wait for a response line. `MEAS?` at the slow rate takes up to a second.
+ */
+const OWON_XDM_RESPONSE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/**
+ * Time to wait after selecting a function; the meter ignores commands while it switches relays.
+ */
+const OWON_XDM_SWITCH_DELAY: Duration = Duration::from_millis(500);
+
+/**
+ * Read timeout of the serial port while waiting for response bytes.
+ */
+pub const OWON_XDM_READ_TIMEOUT: Duration = Duration::from_millis(100);
+
+/**
+ * Returns the serial settings of the XDM meters, 115200 baud 8N1 as shipped.
+ *
+ * # Returns
+ * The serial line settings.
+ */
+pub fn owon_xdm_uart_config() -> UartConfig {
+    UartConfig {
+        baud_rate: 115200,
+        data_bits: 8,
+        parity: Parity::None,
+        stop_bits: 1,
+    }
+}
+
+/**
+ * Module for Owon XDM1041 / XDM2041 bench multimeters on their USB serial port. Commands are
+ * SCPI terminated with a newline; responses end with a newline.
+ */
+pub struct OwonXdm {
+    /**
+     * Serial port of the meter.
+     */
+    line: SerialLine,
+    /**
+     * Selected function, queried with `FUNC?` before the first measurement.
+     */
+    function: Cell<Option<XdmFunction>>,
+}
+
+impl OwonXdm {
+    /**
+     * Creates a new instance of OwonXdm.
+     *
+     * # Arguments
+     * `line` - The opened serial port.
+     *
+     * # Returns
+     * A new OwonXdm instance.
+     */
+    pub fn new(line: SerialLine) -> Self {
+        Self {
+            line,
+            function: Cell::new(None),
+        }
+    }
+
+    /**
+     * Sends a SCPI command and reads the response line of queries.
+     *
+     * # Arguments
+     * `command` - The SCPI command without terminator.
+     *
+     * # Returns
+     * A Result containing the response, empty for commands that are not queries, or an ApplicationError.
+     */
+    fn send(&self, command: &str) -> Result<Vec<u8>, ApplicationError> {
+        self.line.write(format!("{}\n", command).as_bytes())?;
+        if !command.ends_with('?') {
+            return Ok(Vec::new());
+        }
+        let response = self
+            .line
+            .read_response(|data| data.ends_with(b"\n"), OWON_XDM_RESPONSE_TIMEOUT)?;
+        if response.is_empty() {
+            return Err(ApplicationError::Timeout(format!("No response to {}", command)));
+        }
+        Ok(response)
+    }
+
+    /**
+     * Returns the selected function, querying the meter the first time.
+     *
+     * # Returns
+     * A Result containing the function or an ApplicationError.
+     */
+    fn function(&self) -> Result<XdmFunction, ApplicationError> {
+        if let Some(function) = self.function.get() {
+            return Ok(function);
+        }
+        let function = XdmFunction::from_response(&self.send("FUNC?")?)?;
+        self.function.set(Some(function));
+        Ok(function)
+    }
+}
+
+#[async_trait(?Send)]
+impl Communication for OwonXdm {
+    /**
+     * Sends typed commands to the meter.
+     *
+     * # Arguments
+     * `commands` - The commands to execute.
+     *
+     * # Returns
+     * A Result containing an optional vector of Reading trait objects or an ApplicationError.
+     */
+    async fn command(
+        &self,
+        commands: Vec<String>,
+    ) -> Result<Option<Vec<Box<dyn Reading>>>, ApplicationError> {
+        let mut readings: Vec<Box<dyn Reading>> = Vec::new();
+        for command in commands {
+            let command = OwonXdmCommand::try_from(command.as_str())?;
+            match command {
+                OwonXdmCommand::Function(function) => {
+                    self.send(&command.to_wire())?;
+                    self.function.set(Some(function));
+                    tokio::time::sleep(OWON_XDM_SWITCH_DELAY).await;
+                }
+                OwonXdmCommand::Rate(_) => {
+                    self.send(&command.to_wire())?;
+                }
+                OwonXdmCommand::Measure => {
+                    let function = self.function()?;
+                    let response = self.send(&command.to_wire())?;
+                    readings.push(Box::new(OwonXdmReading::parse(function, &response)?));
+                }
+                OwonXdmCommand::Identify => {
+                    readings.push(Box::new(ScpiRawReading::new(self.send(&command.to_wire())?)));
+                }
+            }
+        }
+        Ok(Some(readings))
+    }
+
+    /**
+     * Returns the USB descriptor strings of the serial adapter.
+     *
+     * # Returns
+     * The device info, or None if the port is not a USB adapter.
+     */
+    fn device_info(&self) -> Option<DeviceInfo> {
+        self.line.device_info()
+    }
+}
+
+/**
+ * Opens the meter on the serial port of the arguments, with the fixed line settings of the meter.
+ *
+ * # Arguments
+ * `args` - An Args struct containing the configuration for the desired device.
+ *
+ * # Returns
+ * A Result containing a boxed Communication trait object or an ApplicationError.
+ */
+pub(crate) async fn open(args: &Args) -> Result<Box<dyn Communication>, ApplicationError> {
+    let line = SerialLine::open(Requirement::Serial.argument(args)?, &owon_xdm_uart_config(), OWON_XDM_READ_TIMEOUT)?;
+    Ok(Box::new(OwonXdm::new(line)))
+}
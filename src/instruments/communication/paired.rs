@@ -0,0 +1,159 @@
+use async_trait::async_trait;
+
+use crate::{
+    error::ApplicationError,
+    instruments::{
+        communication::common::{Communication, DeviceInfo},
+        reading::{PairedReading, Reading},
+    },
+};
+
+/**
+ * Gets the setpoint name and value of a command, e.g. (`Voltage`, `5`) for `Voltage:5` and
+ * (`FREQ`, `1000`) for `FREQ 1000`. Commands without value, e.g. `*RST`, have an empty value.
+ *
+ * # Arguments
+ * `command` - The command.
+ *
+ * # Returns
+ * The name and value.
+ */
+fn setpoint(command: &str) -> (String, String) {
+    let command = command.trim();
+    let typed = command
+        .split_once(':')
+        .filter(|(name, _)| name.chars().next().is_some_and(|c| c.is_ascii_uppercase()))
+        .filter(|(name, _)| name.chars().all(|c| c.is_ascii_alphanumeric()))
+        .filter(|(name, _)| name.chars().any(|c| c.is_ascii_lowercase()));
+    let (name, value) = typed
+        .or_else(|| command.split_once(char::is_whitespace))
+        .unwrap_or((command, ""));
+    (name.to_string(), value.trim().to_string())
+}
+
+/**
+ * Wraps a device so that setpoints and the readings of the queries that follow them are paired
+ * into one record per stimulus. The commands are sent one at a time; commands without readings
+ * are setpoints, and the latest value of every setpoint of the batch is included in the records.
+ */
+pub struct PairingCommunication {
+    /**
+     * The wrapped device.
+     */
+    device: Box<dyn Communication>,
+}
+
+impl PairingCommunication {
+    /**
+     * Creates a new instance of PairingCommunication.
+     *
+     * # Arguments
+     * `device` - The device to wrap.
+     *
+     * # Returns
+     * A new PairingCommunication instance.
+     */
+    pub fn new(device: Box<dyn Communication>) -> Self {
+        Self { device }
+    }
+}
+
+#[async_trait(?Send)]
+impl Communication for PairingCommunication {
+    /**
+     * Sends the commands one at a time and pairs the readings with the setpoints before them.
+     *
+     * # Arguments
+     * `commands` - The commands to execute.
+     *
+     * # Returns
+     * A Result containing an optional vector of Reading trait objects or an ApplicationError.
+     */
+    async fn command(
+        &self,
+        commands: Vec<String>,
+    ) -> Result<Option<Vec<Box<dyn Reading>>>, ApplicationError> {
+        let mut records: Vec<Box<dyn Reading>> = Vec::new();
+        let mut setpoints: Vec<(String, String)> = Vec::new();
+        let mut responses: Vec<Box<dyn Reading>> = Vec::new();
+        for command in commands {
+            let readings = self.device.command(vec![command.clone()]).await?.unwrap_or_default();
+            if !readings.is_empty() {
+                responses.extend(readings);
+                continue;
+            }
+            if !responses.is_empty() {
+                records.push(Box::new(PairedReading::new(setpoints.clone(), std::mem::take(&mut responses))));
+            }
+            let (name, value) = setpoint(&command);
+            match setpoints.iter_mut().find(|(existing, _)| *existing == name) {
+                Some(setpoint) => setpoint.1 = value,
+                None => setpoints.push((name, value)),
+            }
+        }
+        if !responses.is_empty() {
+            records.push(Box::new(PairedReading::new(setpoints, responses)));
+        }
+        Ok(Some(records))
+    }
+
+    /**
+     * Returns the device info of the wrapped device.
+     *
+     * # Returns
+     * The device info, or None.
+     */
+    fn device_info(&self) -> Option<DeviceInfo> {
+        self.device.device_info()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::instruments::reading::ScpiRawReading;
+
+    /**
+     * Device answering queries ending with `?` with the query itself.
+     */
+    struct Echo;
+
+    #[async_trait(?Send)]
+    impl Communication for Echo {
+        async fn command(
+            &self,
+            commands: Vec<String>,
+        ) -> Result<Option<Vec<Box<dyn Reading>>>, ApplicationError> {
+            Ok(Some(
+                commands
+                    .into_iter()
+                    .filter(|command| command.ends_with('?'))
+                    .map(|command| Box::new(ScpiRawReading::new(command.into_bytes())) as Box<dyn Reading>)
+                    .collect(),
+            ))
+        }
+    }
+
+    #[test]
+    fn test_setpoint() {
+        assert_eq!(setpoint("Voltage:5"), ("Voltage".into(), "5".into()));
+        assert_eq!(setpoint("FREQ 1000"), ("FREQ".into(), "1000".into()));
+        assert_eq!(setpoint(":SOUR1:VOLT 2.5"), (":SOUR1:VOLT".into(), "2.5".into()));
+        assert_eq!(setpoint("*RST"), ("*RST".into(), "".into()));
+    }
+
+    #[tokio::test]
+    async fn test_pairing() {
+        let device = PairingCommunication::new(Box::new(Echo));
+        let commands = ["OUTP ON", "VOLT 1", "MEAS:VOLT?", "MEAS:CURR?", "VOLT 2", "MEAS:VOLT?"];
+        let records = device
+            .command(commands.iter().map(|command| command.to_string()).collect())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].get_csv().unwrap(), ["ON", "1", "MEAS:VOLT?", "MEAS:CURR?"]);
+        assert_eq!(records[1].get_csv_header().unwrap(), ["set_OUTP", "set_VOLT", "response"]);
+        assert_eq!(records[1].get_csv().unwrap(), ["ON", "2", "MEAS:VOLT?"]);
+    }
+}
@@ -0,0 +1,261 @@
+use std::{cmp::Ordering, time::Duration};
+
+use crate::instruments::reading::Identity;
+
+/**
+ * Enum representing a firmware workaround applied by the SCPI layer.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuirkKind {
+    /// Wait after sending a command starting with the prefix (case insensitive).
+    DelayAfter { prefix: &'static str, delay: Duration },
+    /// Responses are not terminated by a newline; one is appended.
+    NoResponseTerminator,
+}
+
+/**
+ * A known firmware issue. Applies when the manufacturer matches, the model starts with the model
+ * prefix and the firmware version is within the inclusive range (an unset bound is open).
+ */
+#[derive(Debug)]
+pub struct Quirk {
+    pub manufacturer: &'static str,
+    pub model_prefix: &'static str,
+    pub firmware_min: Option<&'static str>,
+    pub firmware_max: Option<&'static str>,
+    pub kind: QuirkKind,
+}
+
+/**
+ * Known firmware issues.
+ */
+const QUIRKS: &[Quirk] = &[
+    Quirk {
+        manufacturer: "PeakTech",
+        model_prefix: "4055",
+        firmware_min: None,
+        firmware_max: None,
+        kind: QuirkKind::DelayAfter {
+            prefix: "APPL",
+            delay: Duration::from_millis(50),
+        },
+    },
+    Quirk {
+        manufacturer: "PeakTech",
+        model_prefix: "4055",
+        firmware_min: None,
+        firmware_max: None,
+        kind: QuirkKind::DelayAfter {
+            prefix: "*RST",
+            delay: Duration::from_millis(500),
+        },
+    },
+];
+
+/**
+ * Compares two dotted version strings numerically, e.g. `1.10` is newer than `1.9`.
+ * Non numeric characters in a component are ignored.
+ *
+ * # Arguments
+ * `a` - The first version.
+ * `b` - The second version.
+ *
+ * # Returns
+ * The ordering of a relative to b.
+ */
+fn compare_versions(a: &str, b: &str) -> Ordering {
+    let components = |version: &str| -> Vec<u64> {
+        version
+            .split(['.', '-'])
+            .map(|component| {
+                component
+                    .chars()
+                    .filter(|c| c.is_ascii_digit())
+                    .collect::<String>()
+                    .parse()
+                    .unwrap_or(0)
+            })
+            .collect()
+    };
+    let (a, b) = (components(a), components(b));
+    for index in 0..a.len().max(b.len()) {
+        let ordering = a.get(index).unwrap_or(&0).cmp(b.get(index).unwrap_or(&0));
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+    Ordering::Equal
+}
+
+impl Quirk {
+    /**
+     * Checks if the quirk applies to an instrument.
+     *
+     * # Arguments
+     * `identity` - The identity of the instrument.
+     *
+     * # Returns
+     * A boolean indicating whether the quirk applies.
+     */
+    fn applies_to(&self, identity: &Identity) -> bool {
+        identity.manufacturer.eq_ignore_ascii_case(self.manufacturer)
+            && identity.model.starts_with(self.model_prefix)
+            && self
+                .firmware_min
+                .is_none_or(|min| compare_versions(&identity.firmware, min) != Ordering::Less)
+            && self
+                .firmware_max
+                .is_none_or(|max| compare_versions(&identity.firmware, max) != Ordering::Greater)
+    }
+}
+
+/**
+ * Workarounds active for a connected instrument.
+ */
+#[derive(Debug, Default, PartialEq)]
+pub struct Quirks {
+    kinds: Vec<QuirkKind>,
+}
+
+impl Quirks {
+    /**
+     * Looks up the known quirks of an instrument.
+     *
+     * # Arguments
+     * `identity` - The identity of the instrument.
+     *
+     * # Returns
+     * The quirks that apply to the instrument.
+     */
+    pub fn lookup(identity: &Identity) -> Self {
+        Self::lookup_in(QUIRKS, identity)
+    }
+
+    /**
+     * Looks up the quirks of an instrument in a quirk table.
+     *
+     * # Arguments
+     * `table` - The quirk table.
+     * `identity` - The identity of the instrument.
+     *
+     * # Returns
+     * The quirks that apply to the instrument.
+     */
+    fn lookup_in(table: &[Quirk], identity: &Identity) -> Self {
+        Self {
+            kinds: table
+                .iter()
+                .filter(|quirk| quirk.applies_to(identity))
+                .map(|quirk| quirk.kind)
+                .collect(),
+        }
+    }
+
+    /**
+     * Returns the delay to wait after sending a command.
+     *
+     * # Arguments
+     * `command` - The command that was sent.
+     *
+     * # Returns
+     * The longest matching delay, or None if no delay is needed.
+     */
+    pub fn delay_after(&self, command: &str) -> Option<Duration> {
+        let command = command.trim_start().to_uppercase();
+        self.kinds
+            .iter()
+            .filter_map(|kind| match kind {
+                QuirkKind::DelayAfter { prefix, delay } if command.starts_with(prefix) => Some(*delay),
+                _ => None,
+            })
+            .max()
+    }
+
+    /**
+     * Checks if the instrument leaves its responses unterminated, so a response ends with the
+     * first transfer.
+     *
+     * # Returns
+     * A boolean indicating whether responses are unterminated.
+     */
+    pub fn no_response_terminator(&self) -> bool {
+        self.kinds.contains(&QuirkKind::NoResponseTerminator)
+    }
+
+    /**
+     * Applies response workarounds to a response.
+     *
+     * # Arguments
+     * `response` - The response bytes.
+     */
+    pub fn fix_response(&self, response: &mut Vec<u8>) {
+        if self.kinds.contains(&QuirkKind::NoResponseTerminator) && response.last() != Some(&b'\n') {
+            response.push(b'\n');
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const TABLE: &[Quirk] = &[
+        Quirk {
+            manufacturer: "ACME",
+            model_prefix: "DMM",
+            firmware_min: Some("1.2"),
+            firmware_max: Some("1.10"),
+            kind: QuirkKind::DelayAfter {
+                prefix: "CONF",
+                delay: Duration::from_millis(50),
+            },
+        },
+        Quirk {
+            manufacturer: "ACME",
+            model_prefix: "DMM",
+            firmware_min: None,
+            firmware_max: None,
+            kind: QuirkKind::NoResponseTerminator,
+        },
+    ];
+
+    fn identity(firmware: &str) -> Identity {
+        Identity::parse(format!("acme,DMM100,SN1,{}\n", firmware).as_bytes()).unwrap()
+    }
+
+    #[test]
+    fn test_parse_identity() {
+        let identity = Identity::parse(b"RIGOL TECHNOLOGIES,DS1054Z,DS1ZA000000001,00.04.04.SP4\n").unwrap();
+        assert_eq!(identity.manufacturer, "RIGOL TECHNOLOGIES");
+        assert_eq!(identity.model, "DS1054Z");
+        assert_eq!(identity.serial_number, "DS1ZA000000001");
+        assert_eq!(identity.firmware, "00.04.04.SP4");
+        assert_eq!(Identity::parse(b"garbage"), None);
+    }
+
+    #[test]
+    fn test_compare_versions() {
+        assert_eq!(compare_versions("1.10", "1.9"), Ordering::Greater);
+        assert_eq!(compare_versions("1.2", "1.2.0"), Ordering::Equal);
+        assert_eq!(compare_versions("V1.01", "1.2"), Ordering::Less);
+    }
+
+    #[test]
+    fn test_delay_quirk_firmware_range() {
+        let quirks = Quirks::lookup_in(TABLE, &identity("1.5"));
+        assert_eq!(quirks.delay_after("conf:volt:dc"), Some(Duration::from_millis(50)));
+        assert_eq!(quirks.delay_after("MEAS?"), None);
+        let quirks = Quirks::lookup_in(TABLE, &identity("1.11"));
+        assert_eq!(quirks.delay_after("CONF:VOLT:DC"), None);
+    }
+
+    #[test]
+    fn test_fix_response() {
+        let quirks = Quirks::lookup_in(TABLE, &identity("2.0"));
+        let mut response = b"1.234".to_vec();
+        quirks.fix_response(&mut response);
+        assert_eq!(response, b"1.234\n");
+        quirks.fix_response(&mut response);
+        assert_eq!(response, b"1.234\n");
+    }
+}
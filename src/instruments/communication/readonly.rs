@@ -0,0 +1,64 @@
+use async_trait::async_trait;
+
+use crate::{
+    error::ApplicationError,
+    instruments::{command::readonly::check_read_only, communication::common::{Communication, DeviceInfo}, reading::Reading},
+};
+
+/**
+ * Wraps a device so that only commands reading from it are sent. A batch with a command that
+ * may change the device is rejected as a whole, before anything is written.
+ */
+pub struct ReadOnlyCommunication {
+    /**
+     * The wrapped device.
+     */
+    device: Box<dyn Communication>,
+}
+
+impl ReadOnlyCommunication {
+    /**
+     * Creates a new instance of ReadOnlyCommunication.
+     *
+     * # Arguments
+     * `device` - The device to wrap.
+     *
+     * # Returns
+     * A new ReadOnlyCommunication instance.
+     */
+    pub fn new(device: Box<dyn Communication>) -> Self {
+        Self { device }
+    }
+}
+
+#[async_trait(?Send)]
+impl Communication for ReadOnlyCommunication {
+    /**
+     * Checks that the commands only read and sends them to the wrapped device.
+     *
+     * # Arguments
+     * `commands` - The commands to execute.
+     *
+     * # Returns
+     * A Result containing an optional vector of Reading trait objects or an ApplicationError.
+     */
+    async fn command(
+        &self,
+        commands: Vec<String>,
+    ) -> Result<Option<Vec<Box<dyn Reading>>>, ApplicationError> {
+        for command in &commands {
+            check_read_only(command)?;
+        }
+        self.device.command(commands).await
+    }
+
+    /**
+     * Returns the device info of the wrapped device.
+     *
+     * # Returns
+     * The device info, or None.
+     */
+    fn device_info(&self) -> Option<DeviceInfo> {
+        self.device.device_info()
+    }
+}
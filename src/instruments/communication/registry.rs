@@ -0,0 +1,845 @@
+use clap::ValueEnum;
+use futures_util::future::LocalBoxFuture;
+
+use crate::{
+    arguments::{Args, Device},
+    error::ApplicationError,
+    instruments::{
+        command::{
+            readback::{scpi_readback, Readback},
+            AtorchCommand, FlukeCommand, KeysightDmmCommand, KoradCommand, ModbusCommand, OwonXdmCommand,
+            PeaktechChannelCommand, RidenCommand, SwitchCommand, Uni161dCommand, Ut325Command,
+        },
+        communication::Communication,
+    },
+};
+#[cfg(feature = "audio")]
+use crate::instruments::communication::audioin;
+#[cfg(feature = "ble")]
+use crate::instruments::communication::ble;
+#[cfg(feature = "gpib")]
+use crate::instruments::communication::gpib;
+#[cfg(all(feature = "network", feature = "modbus"))]
+use crate::instruments::communication::modbustcp;
+#[cfg(all(feature = "serial", feature = "modbus"))]
+use crate::instruments::communication::riden;
+#[cfg(feature = "usb")]
+use crate::instruments::{
+    command::{CommandSet, ScopeDialect},
+    communication::scpiusb,
+};
+#[cfg(feature = "hid")]
+use crate::instruments::communication::{brymen, hidrelay, hiduart, unit161d};
+#[cfg(feature = "serial")]
+use crate::instruments::communication::{atorch, fluke, keysightdmm, korad, owonxdm, serialtext, tc66, ut325, ut71};
+
+/**
+ * Opens a device from the arguments.
+ */
+pub type OpenFn = for<'a> fn(&'a Args) -> LocalBoxFuture<'a, Result<Box<dyn Communication>, ApplicationError>>;
+
+/**
+ * An argument a device cannot be opened without.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Requirement {
+    Usb,
+    Hid,
+    Serial,
+    Ble,
+    Gpib,
+    Tcp,
+}
+
+impl Requirement {
+    /**
+     * Gets the option giving the argument.
+     *
+     * # Returns
+     * The option, e.g. `--serial`.
+     */
+    pub fn option(&self) -> &'static str {
+        match self {
+            Requirement::Usb => "--usb",
+            Requirement::Hid => "--hid",
+            Requirement::Serial => "--serial",
+            Requirement::Ble => "--ble",
+            Requirement::Gpib => "--gpib",
+            Requirement::Tcp => "--tcp",
+        }
+    }
+
+    /**
+     * Gets the argument from the arguments.
+     *
+     * # Arguments
+     * `args` - The arguments of the device.
+     *
+     * # Returns
+     * A Result containing the argument or an ApplicationError if it is not given.
+     */
+    pub fn argument<'a>(&self, args: &'a Args) -> Result<&'a str, ApplicationError> {
+        match self {
+            Requirement::Usb => {
+                args.connection.usb.as_deref().ok_or_else(|| ApplicationError::Usb("USB device not provided".into()))
+            }
+            Requirement::Hid => {
+                args.connection.hid.as_deref().ok_or_else(|| ApplicationError::Hid("HID device not provided".into()))
+            }
+            Requirement::Serial => {
+                args.connection.serial.as_deref().ok_or_else(|| ApplicationError::Serial("Serial port not provided".into()))
+            }
+            Requirement::Ble => {
+                args.connection.ble.as_deref().ok_or_else(|| ApplicationError::Bluetooth("Bluetooth device not provided".into()))
+            }
+            Requirement::Gpib => {
+                args.connection.gpib.as_deref().ok_or_else(|| ApplicationError::Gpib("GPIB address not provided".into()))
+            }
+            Requirement::Tcp => {
+                args.connection.tcp.as_deref().ok_or_else(|| ApplicationError::Network("Network address not provided".into()))
+            }
+        }
+    }
+}
+
+// Descriptions of the capabilities in listings and error messages
+const MEASURER: &str = "measure";
+const SOURCE_CONTROL: &str = "drive an output";
+const SWITCH: &str = "switch channels";
+
+/**
+ * What a device can do, declared by its driver, e.g. `Capabilities::NONE.measurer()`, so options
+ * the device cannot act on are rejected before it is opened. A measurer returns measurements, e.g.
+ * a meter, a scope or the measured output of a power supply. A device with source control drives
+ * an output, e.g. a power supply, an electronic load or a signal generator. A switch opens and
+ * closes channels with `Open:all` and `Close:<channel>`, as the switch of a scan.
+ */
+#[derive(Clone, Copy)]
+pub struct Capabilities {
+    measurer: bool,
+    source_control: bool,
+    switch: bool,
+}
+
+impl Capabilities {
+    /**
+     * A device without capabilities, to add them to.
+     */
+    pub const NONE: Self = Self {
+        measurer: false,
+        source_control: false,
+        switch: false,
+    };
+
+    /**
+     * Adds the measurer capability.
+     *
+     * # Returns
+     * The capabilities.
+     */
+    pub const fn measurer(self) -> Self {
+        Self { measurer: true, ..self }
+    }
+
+    /**
+     * Adds the source control capability.
+     *
+     * # Returns
+     * The capabilities.
+     */
+    pub const fn source_control(self) -> Self {
+        Self {
+            source_control: true,
+            ..self
+        }
+    }
+
+    /**
+     * Adds the switch capability.
+     *
+     * # Returns
+     * The capabilities.
+     */
+    pub const fn switch(self) -> Self {
+        Self { switch: true, ..self }
+    }
+
+    /**
+     * Describes the capabilities for listings.
+     *
+     * # Returns
+     * What the device can do, e.g. `measure` and `drive an output`.
+     */
+    pub fn descriptions(&self) -> Vec<&'static str> {
+        [
+            (self.measurer, MEASURER),
+            (self.source_control, SOURCE_CONTROL),
+            (self.switch, SWITCH),
+        ]
+        .iter()
+        .filter(|(capable, _)| *capable)
+        .map(|(_, description)| *description)
+        .collect()
+    }
+}
+
+/**
+ * Everything the tool knows about a device: the arguments it needs, what it can do, how it is opened, which
+ * commands it accepts and how its settings are read back with --verify. Adding a device means
+ * adding a `Device` variant and its driver in `DRIVERS`. Drivers are compiled whatever the
+ * features, only opening a device whose transport is compiled out fails.
+ */
+pub struct Driver {
+    pub device: Device,
+    pub requires: &'static [Requirement],
+    pub capabilities: Capabilities,
+    /**
+     * Command returning the identity of the device, e.g. `*IDN?`, if it has one.
+     */
+    pub identify: Option<&'static str>,
+    pub open: OpenFn,
+    /**
+     * Checks a command without opening the device, for --validate.
+     */
+    pub check_command: fn(&str) -> Result<(), ApplicationError>,
+    /**
+     * Gets the readback of a command, None if the command has none.
+     */
+    pub readback: fn(&str) -> Result<Option<Readback>, ApplicationError>,
+}
+
+impl Driver {
+    /**
+     * Gets the name of the device on the command line and in profiles.
+     *
+     * # Returns
+     * The name, e.g. `korad-psu`.
+     */
+    pub fn name(&self) -> String {
+        self.device.to_possible_value().map(|value| value.get_name().to_string()).unwrap_or_default()
+    }
+
+    /**
+     * Opens the device, after checking that the arguments it needs are given.
+     *
+     * # Arguments
+     * `args` - The arguments of the device.
+     *
+     * # Returns
+     * A Result containing a boxed Communication trait object or an ApplicationError.
+     */
+    pub async fn open(&self, args: &Args) -> Result<Box<dyn Communication>, ApplicationError> {
+        for requirement in self.requires {
+            requirement.argument(args)?;
+        }
+        (self.open)(args).await
+    }
+
+    /**
+     * Creates the error of an option the device has no capability for.
+     *
+     * # Arguments
+     * `option` - The option.
+     * `action` - What the option needs the device to do.
+     *
+     * # Returns
+     * The ApplicationError.
+     */
+    fn lacks(&self, option: &str, action: &str) -> ApplicationError {
+        ApplicationError::Config(format!("{} needs a device that can {}, {} cannot", option, action, self.name()))
+    }
+
+    /**
+     * Checks that the device measures, as an option needs.
+     *
+     * # Arguments
+     * `option` - The option, for the error message.
+     *
+     * # Returns
+     * A Result indicating success or an ApplicationError if the device does not measure.
+     */
+    pub fn require_measurer(&self, option: &str) -> Result<(), ApplicationError> {
+        match self.capabilities.measurer {
+            true => Ok(()),
+            false => Err(self.lacks(option, MEASURER)),
+        }
+    }
+
+    /**
+     * Checks that the device drives an output, as an option needs.
+     *
+     * # Arguments
+     * `option` - The option, for the error message.
+     *
+     * # Returns
+     * A Result indicating success or an ApplicationError if the device drives no output.
+     */
+    pub fn require_source_control(&self, option: &str) -> Result<(), ApplicationError> {
+        match self.capabilities.source_control {
+            true => Ok(()),
+            false => Err(self.lacks(option, SOURCE_CONTROL)),
+        }
+    }
+
+    /**
+     * Checks that the device switches channels, as an option needs.
+     *
+     * # Arguments
+     * `option` - The option, for the error message.
+     *
+     * # Returns
+     * A Result indicating success or an ApplicationError if the device cannot switch.
+     */
+    pub fn require_switch(&self, option: &str) -> Result<(), ApplicationError> {
+        match self.capabilities.switch {
+            true => Ok(()),
+            false => Err(self.lacks(option, SWITCH)),
+        }
+    }
+}
+
+/**
+ * The drivers of all devices.
+ */
+static DRIVERS: &[Driver] = &[
+    // UNI-T UT161D meters
+    Driver {
+        device: Device::Unit161d,
+        requires: &[Requirement::Hid],
+        capabilities: Capabilities::NONE.measurer(),
+        identify: None,
+        #[cfg(feature = "hid")]
+        open: |args| Box::pin(unit161d::open(args)),
+        #[cfg(not(feature = "hid"))]
+        open: |_| Box::pin(async { Err(ApplicationError::Hid("Compiled without the hid feature".into())) }),
+        check_command: Uni161dCommand::check,
+        readback: |command| Ok(Uni161dCommand::try_from(command.to_string()).ok().and_then(|command| command.readback())),
+    },
+    // SCPI instruments whose commands are sent as is
+    Driver {
+        device: Device::GenericScpiUsb,
+        requires: &[Requirement::Usb],
+        capabilities: Capabilities::NONE.measurer().source_control().switch(),
+        identify: Some("*IDN?"),
+        #[cfg(feature = "usb")]
+        open: |args| Box::pin(scpiusb::open(args, CommandSet::Raw)),
+        #[cfg(not(feature = "usb"))]
+        open: |_| Box::pin(async { Err(ApplicationError::Usb("Compiled without the usb feature".into())) }),
+        check_command: any_command,
+        readback: |command| Ok(scpi_readback(command)),
+    },
+    // The PeakTech 4055MV generator
+    Driver {
+        device: Device::Peaktech4055mvUsb,
+        requires: &[Requirement::Usb],
+        capabilities: Capabilities::NONE.source_control(),
+        identify: Some("*IDN?"),
+        #[cfg(feature = "usb")]
+        open: |args| Box::pin(scpiusb::open_peaktech_4055mv(args)),
+        #[cfg(not(feature = "usb"))]
+        open: |_| Box::pin(async { Err(ApplicationError::Usb("Compiled without the usb feature".into())) }),
+        check_command: PeaktechChannelCommand::check,
+        readback: |command| match PeaktechChannelCommand::parse(command)? {
+            Some(typed) => Ok(typed.readback()),
+            None => Ok(scpi_readback(command)),
+        },
+    },
+    // Rigol oscilloscopes
+    Driver {
+        device: Device::RigolScopeUsb,
+        requires: &[Requirement::Usb],
+        capabilities: Capabilities::NONE.measurer(),
+        identify: Some("*IDN?"),
+        #[cfg(feature = "usb")]
+        open: |args| Box::pin(scpiusb::open(args, CommandSet::Scope(ScopeDialect::Rigol))),
+        #[cfg(not(feature = "usb"))]
+        open: |_| Box::pin(async { Err(ApplicationError::Usb("Compiled without the usb feature".into())) }),
+        check_command: any_command,
+        readback: |command| Ok(scpi_readback(command)),
+    },
+    // Siglent oscilloscopes
+    Driver {
+        device: Device::SiglentScopeUsb,
+        requires: &[Requirement::Usb],
+        capabilities: Capabilities::NONE.measurer(),
+        identify: Some("*IDN?"),
+        #[cfg(feature = "usb")]
+        open: |args| Box::pin(scpiusb::open(args, CommandSet::Scope(ScopeDialect::Siglent))),
+        #[cfg(not(feature = "usb"))]
+        open: |_| Box::pin(async { Err(ApplicationError::Usb("Compiled without the usb feature".into())) }),
+        check_command: any_command,
+        readback: |command| Ok(scpi_readback(command)),
+    },
+    // SCPI switch and relay units
+    Driver {
+        device: Device::ScpiSwitchUsb,
+        requires: &[Requirement::Usb],
+        capabilities: Capabilities::NONE.switch(),
+        identify: Some("*IDN?"),
+        #[cfg(feature = "usb")]
+        open: |args| Box::pin(scpiusb::open(args, CommandSet::Switch)),
+        #[cfg(not(feature = "usb"))]
+        open: |_| Box::pin(async { Err(ApplicationError::Usb("Compiled without the usb feature".into())) }),
+        check_command: any_command,
+        readback: |command| Ok(scpi_readback(command)),
+    },
+    // Keysight 34461A / 34465A and other Truevolt multimeters
+    Driver {
+        device: Device::KeysightDmmUsb,
+        requires: &[Requirement::Usb],
+        capabilities: Capabilities::NONE.measurer(),
+        identify: Some("*IDN?"),
+        #[cfg(feature = "usb")]
+        open: |args| Box::pin(scpiusb::open(args, CommandSet::Dmm)),
+        #[cfg(not(feature = "usb"))]
+        open: |_| Box::pin(async { Err(ApplicationError::Usb("Compiled without the usb feature".into())) }),
+        check_command: KeysightDmmCommand::check,
+        readback: |command| Ok(scpi_readback(command)),
+    },
+    // USB HID relay boards
+    Driver {
+        device: Device::HidRelay,
+        requires: &[Requirement::Hid],
+        capabilities: Capabilities::NONE.switch(),
+        identify: None,
+        #[cfg(feature = "hid")]
+        open: |args| Box::pin(hidrelay::open(args)),
+        #[cfg(not(feature = "hid"))]
+        open: |_| Box::pin(async { Err(ApplicationError::Hid("Compiled without the hid feature".into())) }),
+        check_command: |command| SwitchCommand::try_from(command).map(|_| ()),
+        readback: no_readback,
+    },
+    // Instruments behind a HID-UART bridge
+    Driver {
+        device: Device::HidUart,
+        requires: &[Requirement::Hid],
+        capabilities: Capabilities::NONE.measurer().source_control().switch(),
+        identify: None,
+        #[cfg(feature = "hid")]
+        open: |args| Box::pin(hiduart::open(args)),
+        #[cfg(not(feature = "hid"))]
+        open: |_| Box::pin(async { Err(ApplicationError::Hid("Compiled without the hid feature".into())) }),
+        check_command: any_command,
+        readback: no_readback,
+    },
+    // Korad and Tenma power supplies
+    Driver {
+        device: Device::KoradPsu,
+        requires: &[Requirement::Serial],
+        capabilities: Capabilities::NONE.measurer().source_control(),
+        identify: Some("Identify"),
+        #[cfg(feature = "serial")]
+        open: |args| Box::pin(korad::open(args)),
+        #[cfg(not(feature = "serial"))]
+        open: |_| Box::pin(async { Err(ApplicationError::Serial("Compiled without the serial feature".into())) }),
+        check_command: |command| KoradCommand::try_from(command).map(|_| ()),
+        readback: |command| Ok(KoradCommand::try_from(command)?.readback()),
+    },
+    // Riden power supplies
+    Driver {
+        device: Device::RidenPsu,
+        requires: &[Requirement::Serial],
+        capabilities: Capabilities::NONE.measurer().source_control(),
+        identify: Some("Identify"),
+        #[cfg(all(feature = "serial", feature = "modbus"))]
+        open: |args| Box::pin(riden::open(args)),
+        #[cfg(not(all(feature = "serial", feature = "modbus")))]
+        open: |_| {
+            Box::pin(async { Err(ApplicationError::Serial("Compiled without the serial or the modbus feature".into())) })
+        },
+        check_command: |command| RidenCommand::try_from(command).map(|_| ()),
+        readback: |command| Ok(RidenCommand::try_from(command)?.readback()),
+    },
+    // UNI-T UT71 meters
+    Driver {
+        device: Device::Ut71Serial,
+        requires: &[Requirement::Serial],
+        capabilities: Capabilities::NONE.measurer(),
+        identify: None,
+        #[cfg(feature = "serial")]
+        open: |args| Box::pin(ut71::open(args)),
+        #[cfg(not(feature = "serial"))]
+        open: |_| Box::pin(async { Err(ApplicationError::Serial("Compiled without the serial feature".into())) }),
+        check_command: |command| measure_only("UT71 meters", command),
+        readback: no_readback,
+    },
+    // Owon Bluetooth LE meters
+    Driver {
+        device: Device::OwonBle,
+        requires: &[Requirement::Ble],
+        capabilities: Capabilities::NONE.measurer(),
+        identify: None,
+        #[cfg(feature = "ble")]
+        open: |args| Box::pin(ble::open(args)),
+        #[cfg(not(feature = "ble"))]
+        open: |_| Box::pin(async { Err(ApplicationError::Bluetooth("Compiled without the ble feature".into())) }),
+        check_command: |command| measure_only("Owon BLE meters", command),
+        readback: no_readback,
+    },
+    // Brymen meters
+    Driver {
+        device: Device::BrymenHid,
+        requires: &[Requirement::Hid],
+        capabilities: Capabilities::NONE.measurer(),
+        identify: None,
+        #[cfg(feature = "hid")]
+        open: |args| Box::pin(brymen::open(args)),
+        #[cfg(not(feature = "hid"))]
+        open: |_| Box::pin(async { Err(ApplicationError::Hid("Compiled without the hid feature".into())) }),
+        check_command: |command| measure_only("Brymen meters", command),
+        readback: no_readback,
+    },
+    // Fluke meters
+    Driver {
+        device: Device::FlukeSerial,
+        requires: &[Requirement::Serial],
+        capabilities: Capabilities::NONE.measurer(),
+        identify: Some("Identify"),
+        #[cfg(feature = "serial")]
+        open: |args| Box::pin(fluke::open(args)),
+        #[cfg(not(feature = "serial"))]
+        open: |_| Box::pin(async { Err(ApplicationError::Serial("Compiled without the serial feature".into())) }),
+        check_command: |command| FlukeCommand::try_from(command).map(|_| ()),
+        readback: no_readback,
+    },
+    // RDTech TC66 USB testers
+    Driver {
+        device: Device::Tc66Serial,
+        requires: &[Requirement::Serial],
+        capabilities: Capabilities::NONE.measurer(),
+        identify: None,
+        #[cfg(feature = "serial")]
+        open: |args| Box::pin(tc66::open(args)),
+        #[cfg(not(feature = "serial"))]
+        open: |_| Box::pin(async { Err(ApplicationError::Serial("Compiled without the serial feature".into())) }),
+        check_command: |command| measure_only("TC66 testers", command),
+        readback: no_readback,
+    },
+    // Owon XDM bench meters
+    Driver {
+        device: Device::OwonXdmSerial,
+        requires: &[Requirement::Serial],
+        capabilities: Capabilities::NONE.measurer(),
+        identify: Some("Identify"),
+        #[cfg(feature = "serial")]
+        open: |args| Box::pin(owonxdm::open(args)),
+        #[cfg(not(feature = "serial"))]
+        open: |_| Box::pin(async { Err(ApplicationError::Serial("Compiled without the serial feature".into())) }),
+        check_command: |command| OwonXdmCommand::try_from(command).map(|_| ()),
+        readback: no_readback,
+    },
+    // Keysight 34401A bench meters on RS-232
+    Driver {
+        device: Device::KeysightDmmSerial,
+        requires: &[Requirement::Serial],
+        capabilities: Capabilities::NONE.measurer(),
+        identify: Some("*IDN?"),
+        #[cfg(feature = "serial")]
+        open: |args| Box::pin(keysightdmm::open(args)),
+        #[cfg(not(feature = "serial"))]
+        open: |_| Box::pin(async { Err(ApplicationError::Serial("Compiled without the serial feature".into())) }),
+        check_command: KeysightDmmCommand::check,
+        readback: no_readback,
+    },
+    // Atorch electronic loads
+    Driver {
+        device: Device::AtorchDl24,
+        requires: &[Requirement::Serial],
+        capabilities: Capabilities::NONE.measurer().source_control(),
+        identify: None,
+        #[cfg(feature = "serial")]
+        open: |args| Box::pin(atorch::open(args)),
+        #[cfg(not(feature = "serial"))]
+        open: |_| Box::pin(async { Err(ApplicationError::Serial("Compiled without the serial feature".into())) }),
+        check_command: |command| AtorchCommand::try_from(command).map(|_| ()),
+        readback: |command| Ok(AtorchCommand::try_from(command)?.readback()),
+    },
+    // Microcontrollers printing text
+    Driver {
+        device: Device::GenericSerialText,
+        requires: &[Requirement::Serial],
+        capabilities: Capabilities::NONE.measurer(),
+        identify: None,
+        #[cfg(feature = "serial")]
+        open: |args| Box::pin(serialtext::open(args)),
+        #[cfg(not(feature = "serial"))]
+        open: |_| Box::pin(async { Err(ApplicationError::Serial("Compiled without the serial feature".into())) }),
+        check_command: any_command,
+        readback: no_readback,
+    },
+    // UNI-T UT325 thermocouple loggers
+    Driver {
+        device: Device::Ut325Serial,
+        requires: &[Requirement::Serial],
+        capabilities: Capabilities::NONE.measurer(),
+        identify: None,
+        #[cfg(feature = "serial")]
+        open: |args| Box::pin(ut325::open(args)),
+        #[cfg(not(feature = "serial"))]
+        open: |_| Box::pin(async { Err(ApplicationError::Serial("Compiled without the serial feature".into())) }),
+        check_command: |command| Ut325Command::try_from(command).map(|_| ()),
+        readback: no_readback,
+    },
+    // Modbus TCP devices
+    Driver {
+        device: Device::ModbusTcp,
+        requires: &[Requirement::Tcp],
+        capabilities: Capabilities::NONE.measurer(),
+        identify: None,
+        #[cfg(all(feature = "network", feature = "modbus"))]
+        open: |args| Box::pin(modbustcp::open(args)),
+        #[cfg(not(all(feature = "network", feature = "modbus")))]
+        open: |_| {
+            Box::pin(async { Err(ApplicationError::Network("Compiled without the network or the modbus feature".into())) })
+        },
+        check_command: |command| ModbusCommand::try_from(command).map(|_| ()),
+        readback: no_readback,
+    },
+    // SCPI instruments on a GPIB interface card
+    Driver {
+        device: Device::GenericScpiGpib,
+        requires: &[Requirement::Gpib],
+        capabilities: Capabilities::NONE.measurer().source_control().switch(),
+        identify: Some("*IDN?"),
+        #[cfg(feature = "gpib")]
+        open: |args| Box::pin(gpib::open(args)),
+        #[cfg(not(feature = "gpib"))]
+        open: |_| Box::pin(async { Err(ApplicationError::Gpib("Compiled without the gpib feature".into())) }),
+        check_command: any_command,
+        readback: |command| Ok(scpi_readback(command)),
+    },
+    // The sound card input
+    Driver {
+        device: Device::AudioIn,
+        requires: &[],
+        capabilities: Capabilities::NONE.measurer(),
+        identify: None,
+        #[cfg(feature = "audio")]
+        open: |args| Box::pin(audioin::open(args)),
+        #[cfg(not(feature = "audio"))]
+        open: |_| Box::pin(async { Err(ApplicationError::Audio("Compiled without the audio feature".into())) }),
+        check_command: |command| measure_only("sound card input", command),
+        readback: no_readback,
+    },
+];
+
+/**
+ * Gets the drivers of all devices.
+ *
+ * # Returns
+ * The drivers, in the order of the devices on the command line.
+ */
+pub fn drivers() -> &'static [Driver] {
+    DRIVERS
+}
+
+/**
+ * Gets the driver of a device.
+ *
+ * # Arguments
+ * `device` - The device.
+ *
+ * # Returns
+ * A Result containing the driver or an ApplicationError if the device has none.
+ */
+pub fn driver(device: &Device) -> Result<&'static Driver, ApplicationError> {
+    DRIVERS
+        .iter()
+        .find(|driver| driver.device == *device)
+        .ok_or_else(|| ApplicationError::Config(format!("No driver for {:?}", device)))
+}
+
+/**
+ * Checks that the devices of the arguments can do what the options ask of them: voltage and
+ * current limits need a device driving an output, and scanning a measuring device and a switch.
+ *
+ * # Arguments
+ * `args` - The arguments of the device.
+ *
+ * # Returns
+ * A Result indicating success or an ApplicationError naming the option the device cannot act on.
+ */
+pub fn check_capabilities(args: &Args) -> Result<(), ApplicationError> {
+    if let Some(device) = &args.connection.device {
+        let driver = driver(device)?;
+        let limits = [
+            (args.control.max_voltage.is_some(), "--max-voltage"),
+            (args.control.max_current.is_some(), "--max-current"),
+            (args.control.dangerous_output_voltage.is_some(), "--dangerous-output-voltage"),
+        ];
+        for (_, option) in limits.iter().filter(|(given, _)| *given) {
+            driver.require_source_control(option)?;
+        }
+        if !args.sequence.scan_channels.is_empty() {
+            driver.require_measurer("--scan-channels")?;
+        }
+    }
+    if let (false, Some(switch_device)) = (args.sequence.scan_channels.is_empty(), &args.sequence.switch_device) {
+        driver(switch_device)?.require_switch("--switch-device")?;
+    }
+    Ok(())
+}
+
+/**
+ * Checks the capabilities of every configured device. Limits given on the command line for
+ * several devices, e.g. a group, apply to the devices that drive an output and are dropped for
+ * the others; limits a profile gives a device that drives no output are still rejected.
+ *
+ * # Arguments
+ * `devices` - The device labels (None for a single device) with their arguments.
+ * `args` - The command line arguments.
+ *
+ * # Returns
+ * A Result indicating success or an ApplicationError naming the device and the option it cannot act on.
+ */
+pub fn check_devices(devices: &mut [(Option<String>, Args)], args: &Args) -> Result<(), ApplicationError> {
+    for (label, device_args) in devices.iter_mut() {
+        let Some(label) = label else {
+            check_capabilities(device_args)?;
+            continue;
+        };
+        if let Some(device) = &device_args.connection.device {
+            if !driver(device)?.capabilities.source_control {
+                for (limit, given) in [
+                    (&mut device_args.control.max_voltage, args.control.max_voltage),
+                    (&mut device_args.control.max_current, args.control.max_current),
+                    (&mut device_args.control.dangerous_output_voltage, args.control.dangerous_output_voltage),
+                ] {
+                    if *limit == given {
+                        *limit = None;
+                    }
+                }
+            }
+        }
+        check_capabilities(device_args).map_err(|e| match e {
+            ApplicationError::Config(message) => ApplicationError::Config(format!("Device {}: {}", label, message)),
+            e => e,
+        })?;
+    }
+    Ok(())
+}
+
+/**
+ * Command check of devices that only measure.
+ *
+ * # Arguments
+ * `name` - The name of the devices in the error message.
+ * `command` - The command.
+ *
+ * # Returns
+ * A Result indicating success or an ApplicationError if the command is not `Measure`.
+ */
+pub fn measure_only(name: &str, command: &str) -> Result<(), ApplicationError> {
+    match command {
+        "Measure" => Ok(()),
+        _ => Err(ApplicationError::Command(format!("Unsupported command for {}: {}", name, command))),
+    }
+}
+
+/**
+ * Command check of devices that send commands as is.
+ *
+ * # Arguments
+ * `command` - The command.
+ *
+ * # Returns
+ * Always Ok.
+ */
+pub fn any_command(_command: &str) -> Result<(), ApplicationError> {
+    Ok(())
+}
+
+/**
+ * Readback of devices whose settings are not read back.
+ *
+ * # Arguments
+ * `command` - The command.
+ *
+ * # Returns
+ * Always None.
+ */
+pub fn no_readback(_command: &str) -> Result<Option<Readback>, ApplicationError> {
+    Ok(None)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{config::Config, instruments::command::readback::Expected};
+
+    #[test]
+    fn test_every_device_has_one_driver() {
+        for device in Device::value_variants() {
+            assert_eq!(DRIVERS.iter().filter(|driver| driver.device == *device).count(), 1, "{:?}", device);
+        }
+    }
+
+    #[test]
+    fn test_readback() {
+        let readback = |device: Device, command: &str| (driver(&device).unwrap().readback)(command);
+        assert_eq!(
+            readback(Device::KoradPsu, "Voltage:5").unwrap(),
+            Some(Readback::field("Setpoints", "voltage", Expected::Number(5.0, 0.01)))
+        );
+        assert_eq!(readback(Device::KoradPsu, "Measure").unwrap(), None);
+        assert!(readback(Device::KoradPsu, "VSET1:5").is_err());
+        assert_eq!(
+            readback(Device::Peaktech4055mvUsb, "Frequency2:1kHz").unwrap(),
+            Some(Readback::new("Frequency2?", Expected::number(1000.0)))
+        );
+        assert_eq!(
+            readback(Device::Unit161d, "Hold").unwrap(),
+            Some(Readback::new("Measure", Expected::Toggled("hold")))
+        );
+        assert_eq!(readback(Device::FlukeSerial, "Measure").unwrap(), None);
+    }
+
+    #[test]
+    fn test_check_capabilities() {
+        let parse = |args: &[&str]| <Args as clap::Parser>::parse_from([&["hardware-measurement"], args].concat());
+        assert!(check_capabilities(&parse(&["--device=korad-psu", "--max-voltage=5"])).is_ok());
+        assert_eq!(
+            check_capabilities(&parse(&["--device=unit161d", "--max-voltage=5"])).err().map(|e| e.to_string()),
+            Some("Config Error: --max-voltage needs a device that can drive an output, unit161d cannot".into())
+        );
+        assert!(check_capabilities(&parse(&["--device=fluke-serial", "--scan-channels=1,2", "--switch-device=hid-relay"])).is_ok());
+        assert!(check_capabilities(&parse(&["--device=fluke-serial", "--scan-channels=1,2", "--switch-device=korad-psu"])).is_err());
+        assert!(check_capabilities(&parse(&["--device=hid-relay", "--scan-channels=1,2", "--switch-device=hid-relay"])).is_err());
+    }
+
+    #[test]
+    fn test_check_devices() {
+        let config = Config::parse(
+            "[profiles.psu]\ndevice = \"korad-psu\"\n\
+             [profiles.dmm]\ndevice = \"fluke-serial\"\n\
+             [profiles.limited-dmm]\ndevice = \"fluke-serial\"\nmax_voltage = 5.0\n\
+             [groups]\nbench = [\"psu\", \"dmm\"]\n",
+        )
+        .unwrap();
+        let parse = |args: &[&str]| <Args as clap::Parser>::parse_from([&["hardware-measurement"], args].concat());
+        // A limit given for a group applies to the supply only
+        let args = parse(&["--group=bench", "--max-voltage=12"]);
+        let mut devices = config.devices(&args).unwrap();
+        check_devices(&mut devices, &args).unwrap();
+        assert_eq!(devices[0].1.control.max_voltage, Some(12.0));
+        assert_eq!(devices[1].1.control.max_voltage, None);
+        // A limit a profile gives a meter is rejected, naming the device
+        let args = parse(&["--profile=psu", "--profile=limited-dmm"]);
+        let mut devices = config.devices(&args).unwrap();
+        assert_eq!(
+            check_devices(&mut devices, &args).err().map(|e| e.to_string()),
+            Some("Config Error: Device limited-dmm: --max-voltage needs a device that can drive an output, fluke-serial cannot".into())
+        );
+        // A single device is checked as before
+        let args = parse(&["--device=fluke-serial", "--max-current=1"]);
+        let mut devices = config.devices(&args).unwrap();
+        assert!(check_devices(&mut devices, &args).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_open_checks_requirements() {
+        let args = <Args as clap::Parser>::parse_from(["hardware-measurement", "--device=korad-psu"]);
+        let result = driver(&Device::KoradPsu).unwrap().open(&args).await;
+        assert_eq!(result.err().map(|e| e.to_string()), Some("Serial Error: Serial port not provided".into()));
+    }
+}
@@ -0,0 +1,86 @@
+use crate::{
+    arguments::{Args, Device, Reader},
+    error::ApplicationError,
+    instruments::{
+        communication::{
+            common::get_scpi_options,
+            scpi::{scpi_reading, ScpiOptions},
+        },
+        reading::{BrymenReading, FlukeReading, Reading, Tc66Reading, Unit161dReading, Ut325Reading, Ut71Reading},
+    },
+};
+
+/**
+ * Decodes a frame or response captured earlier from a device, as the device would have when
+ * reading it live. Meter frames are decoded by the reading of the device, responses of SCPI
+ * instruments by the --reader.
+ *
+ * # Arguments
+ * `args` - The arguments with the device and, for SCPI instruments, the reader.
+ * `frame` - The captured bytes, as written by `--format=raw`.
+ *
+ * # Returns
+ * A Result containing the reading, or an ApplicationError if the frame is invalid or captures of
+ * the device cannot be decoded.
+ */
+pub fn decode_frame(args: &Args, frame: Vec<u8>) -> Result<Box<dyn Reading>, ApplicationError> {
+    let device = args.connection.device.as_ref().ok_or_else(|| ApplicationError::Config("No device given".into()))?;
+    match device {
+        Device::Unit161d => Unit161dReading::parse(frame)
+            .map(|reading| Box::new(reading) as Box<dyn Reading>)
+            .ok_or_else(|| ApplicationError::Command("Frame too short for a UT161D measurement".into())),
+        Device::BrymenHid => Ok(Box::new(BrymenReading::parse(&frame)?)),
+        Device::Ut71Serial => Ok(Box::new(Ut71Reading::parse(&frame)?)),
+        Device::Ut325Serial => Ok(Box::new(Ut325Reading::parse(&frame, Default::default(), None)?)),
+        Device::Tc66Serial => Ok(Box::new(Tc66Reading::parse(&frame)?)),
+        Device::FlukeSerial => Ok(Box::new(FlukeReading::parse_qm(&frame).or_else(|_| FlukeReading::parse_qdda(&frame))?)),
+        Device::GenericSerialText => {
+            let options = ScpiOptions {
+                reader: args.readings.reader.clone().unwrap_or(Reader::KeyValueReader),
+                ..get_scpi_options(args)
+            };
+            scpi_reading(&options, frame)
+        }
+        #[cfg(feature = "ble")]
+        Device::OwonBle => Ok(Box::new(crate::instruments::reading::OwonReading::parse(&frame)?)),
+        Device::GenericScpiUsb
+        | Device::GenericScpiGpib
+        | Device::Peaktech4055mvUsb
+        | Device::RigolScopeUsb
+        | Device::SiglentScopeUsb
+        | Device::ScpiSwitchUsb
+        | Device::KeysightDmmUsb => scpi_reading(&get_scpi_options(args), frame),
+        _ => Err(ApplicationError::Config(format!("Captures of {:?} cannot be decoded", device))),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use clap::Parser;
+
+    use super::*;
+
+    #[test]
+    fn test_decode_frame() {
+        let args = Args::parse_from(["test_program", "--device=unit161d"]);
+        let frame = vec![2, b'0', b' ', b' ', b'1', b'.', b'2', b'3', b'4', 0, 0, 0, 0, 0];
+        let reading = decode_frame(&args, frame).unwrap();
+        assert_eq!(reading.get_csv().unwrap()[..3], ["DCV", "0", "1.234"]);
+        assert!(decode_frame(&args, vec![2, b'0']).is_err());
+
+        let args = Args::parse_from(["test_program", "--device=generic-scpi-usb", "--reader=scpi-idn-reader"]);
+        let reading = decode_frame(&args, b"RIGOL TECHNOLOGIES,DS1054Z,DS1ZA0000,00.04.04\n".to_vec()).unwrap();
+        assert!(reading.get_raw_string().unwrap().contains("DS1054Z"));
+
+        let args = Args::parse_from(["test_program", "--device=generic-serial-text", "--text-fields=temperature,humidity"]);
+        let reading = decode_frame(&args, b"23.5,41\r\n".to_vec()).unwrap();
+        assert_eq!(reading.get_csv_header().unwrap(), ["temperature", "humidity"]);
+
+        let args = Args::parse_from(["test_program", "--device=ut325-serial"]);
+        let reading = decode_frame(&args, vec![0x5E, 0x00, 0x00, 0xEB, 0x00, 0xEB, 0x00]).unwrap();
+        assert_eq!(reading.get_raw_string().unwrap(), "T1 23.5 T2 23.5 T1-T2 0 °C type K");
+
+        let args = Args::parse_from(["test_program", "--device=korad-psu"]);
+        assert!(matches!(decode_frame(&args, vec![0]), Err(ApplicationError::Config(_))));
+    }
+}
@@ -0,0 +1,169 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+
+use crate::{
+    arguments::Args,
+    error::ApplicationError,
+    instruments::{
+        command::RidenCommand,
+        communication::{
+            common::{DeviceInfo, get_uart_config, Communication},
+            modbus::{Modbus, ModbusRtu},
+            registry::Requirement,
+            serial::SerialLine,
+        },
+        reading::{
+            riden::{
+                REG_CURRENT_SET, REG_FIRMWARE, REG_MODEL, REG_OUTPUT, REG_SERIAL_HIGH, REG_SERIAL_LOW,
+                REG_VOLTAGE_SET, STATUS_REGISTERS,
+            },
+            Reading, RidenModel, RidenReading, ScpiRawReading,
+        },
+    },
+};
+
+/**
+ * Read timeout of the serial port while waiting for response bytes.
+ */
+pub const RIDEN_READ_TIMEOUT: Duration = Duration::from_millis(100);
+
+/**
+ * Modbus unit address the power supplies are shipped with.
+ */
+pub const RIDEN_DEFAULT_ADDRESS: u8 = 1;
+
+/**
+ * Module for Riden RD6006, RD6006P, RD6012, RD6018 and RD6024 power supplies on their USB serial
+ * port or the WiFi module serial header, over Modbus-RTU.
+ */
+pub struct RidenPsu {
+    modbus: ModbusRtu,
+    /**
+     * Model read when the power supply was opened, giving the register scales.
+     */
+    model: RidenModel,
+}
+
+impl RidenPsu {
+    /**
+     * Creates a new instance of RidenPsu and reads the model of the power supply.
+     *
+     * # Arguments
+     * `modbus` - The Modbus-RTU client of the power supply.
+     *
+     * # Returns
+     * A Result containing the new RidenPsu instance or an ApplicationError.
+     */
+    pub fn new(modbus: ModbusRtu) -> Result<Self, ApplicationError> {
+        let id = modbus.read_holding_registers(REG_MODEL, 1)?[0];
+        let model = RidenModel::from_id(id)?;
+        Ok(Self { modbus, model })
+    }
+
+    /**
+     * Writes a setpoint in the register scale of the model.
+     *
+     * # Arguments
+     * `register` - The setpoint register.
+     * `value` - The setpoint in volts or amperes.
+     * `scale` - The register units per volt or ampere.
+     *
+     * # Returns
+     * A Result indicating success or an ApplicationError if the setpoint does not fit the register.
+     */
+    fn write_setpoint(&self, register: u16, value: f64, scale: f64) -> Result<(), ApplicationError> {
+        let raw = (value * scale).round();
+        if raw > u16::MAX as f64 {
+            return Err(ApplicationError::Command(format!(
+                "Setpoint {} is out of range for the {}",
+                value, self.model.name
+            )));
+        }
+        self.modbus.write_register(register, raw as u16)
+    }
+
+    /**
+     * Reads the model, serial number and firmware version.
+     *
+     * # Returns
+     * A Result containing the identification or an ApplicationError.
+     */
+    fn identify(&self) -> Result<String, ApplicationError> {
+        let registers = self.modbus.read_holding_registers(REG_MODEL, REG_FIRMWARE - REG_MODEL + 1)?;
+        let serial = ((registers[REG_SERIAL_HIGH as usize] as u32) << 16) | registers[REG_SERIAL_LOW as usize] as u32;
+        Ok(format!(
+            "{} serial {} firmware {:.2}",
+            self.model.name,
+            serial,
+            registers[REG_FIRMWARE as usize] as f64 / 100.0
+        ))
+    }
+}
+
+#[async_trait(?Send)]
+impl Communication for RidenPsu {
+    /**
+     * Sends typed commands to the power supply.
+     *
+     * # Arguments
+     * `commands` - The commands to execute.
+     *
+     * # Returns
+     * A Result containing an optional vector of Reading trait objects or an ApplicationError.
+     */
+    async fn command(
+        &self,
+        commands: Vec<String>,
+    ) -> Result<Option<Vec<Box<dyn Reading>>>, ApplicationError> {
+        let mut readings: Vec<Box<dyn Reading>> = Vec::new();
+        for command in commands {
+            match RidenCommand::try_from(command.as_str())? {
+                RidenCommand::Voltage(volts) => {
+                    self.write_setpoint(REG_VOLTAGE_SET, volts, self.model.voltage_scale)?
+                }
+                RidenCommand::Current(amperes) => {
+                    self.write_setpoint(REG_CURRENT_SET, amperes, self.model.current_scale)?
+                }
+                RidenCommand::Output(on) => self.modbus.write_register(REG_OUTPUT, on as u16)?,
+                RidenCommand::Measure => {
+                    let registers = self.modbus.read_holding_registers(REG_VOLTAGE_SET, STATUS_REGISTERS)?;
+                    readings.push(Box::new(RidenReading::measured(&self.model, &registers)?))
+                }
+                RidenCommand::Setpoints => {
+                    let registers = self.modbus.read_holding_registers(REG_VOLTAGE_SET, STATUS_REGISTERS)?;
+                    readings.push(Box::new(RidenReading::setpoints(&self.model, &registers)?))
+                }
+                RidenCommand::Identify => {
+                    readings.push(Box::new(ScpiRawReading::new(self.identify()?.into_bytes())))
+                }
+            }
+        }
+        Ok(Some(readings))
+    }
+
+    /**
+     * Returns the USB descriptor strings of the serial adapter.
+     *
+     * # Returns
+     * The device info, or None if the port is not a USB adapter.
+     */
+    fn device_info(&self) -> Option<DeviceInfo> {
+        self.modbus.device_info()
+    }
+}
+
+/**
+ * Opens the power supply on the serial port of the arguments, at the Modbus address of the arguments.
+ *
+ * # Arguments
+ * `args` - An Args struct containing the configuration for the desired device.
+ *
+ * # Returns
+ * A Result containing a boxed Communication trait object or an ApplicationError.
+ */
+pub(crate) async fn open(args: &Args) -> Result<Box<dyn Communication>, ApplicationError> {
+    let line = SerialLine::open(Requirement::Serial.argument(args)?, &get_uart_config(args), RIDEN_READ_TIMEOUT)?;
+    let modbus = ModbusRtu::new(line, args.connection.modbus_address.unwrap_or(RIDEN_DEFAULT_ADDRESS));
+    Ok(Box::new(RidenPsu::new(modbus)?))
+}
@@ -0,0 +1,129 @@
+#[cfg(any(feature = "usb", feature = "serial", feature = "gpib"))]
+use std::time::Duration;
+
+#[cfg(any(feature = "usb", feature = "gpib"))]
+use crate::arguments::CheckErrors;
+#[cfg(feature = "usb")]
+use crate::{arguments::RemoteMode, instruments::command::CommandSet};
+use crate::{
+    arguments,
+    error::ApplicationError,
+    instruments::{
+        reading::{AnalysisReading, IdnReading, KeyValueReading, Reading, ScreenshotReading, ScpiBlockReading, ScpiRawReading},
+    },
+};
+
+/**
+ * Options controlling how commands are sent and responses are interpreted.
+ */
+#[derive(Debug, Clone)]
+pub struct ScpiOptions {
+    /**
+     * Reader type for interpreting instrument responses.
+     */
+    pub reader: arguments::Reader,
+    /**
+     * Sample rate of waveform data, used by the Analysis reader.
+     */
+    pub sample_rate: Option<f64>,
+    /**
+     * File the ScreenshotReader writes the image to.
+     */
+    pub output: Option<String>,
+    /**
+     * Names of the columns of CSV lines, used by the KeyValueReader.
+     */
+    pub text_fields: Vec<String>,
+    /**
+     * How typed commands are translated to SCPI.
+     */
+    #[cfg(feature = "usb")]
+    pub command_set: CommandSet,
+    /**
+     * Whether the instrument is identified with `*IDN?` to apply known firmware workarounds.
+     */
+    #[cfg(feature = "usb")]
+    pub quirks: bool,
+    /**
+     * Terminator appended to text commands.
+     */
+    #[cfg(any(feature = "usb", feature = "serial", feature = "gpib"))]
+    pub terminator: Vec<u8>,
+    /**
+     * Terminator ending responses, or empty if responses end at the read timeout.
+     */
+    #[cfg(any(feature = "usb", feature = "gpib"))]
+    pub response_terminator: Vec<u8>,
+    /**
+     * Time to wait for more response data.
+     */
+    #[cfg(any(feature = "usb", feature = "serial", feature = "gpib"))]
+    pub read_timeout: Duration,
+    /**
+     * Size at which a response is complete even without terminator.
+     */
+    #[cfg(any(feature = "usb", feature = "gpib"))]
+    pub max_response_size: usize,
+    /**
+     * Whether the instrument is put in remote mode at the start of a session and back in local mode at the end.
+     */
+    #[cfg(feature = "usb")]
+    pub remote: RemoteMode,
+    /**
+     * Command returning the instrument to local mode.
+     */
+    #[cfg(feature = "usb")]
+    pub local_command: String,
+    /**
+     * When the error queue is checked, if at all.
+     */
+    #[cfg(any(feature = "usb", feature = "gpib"))]
+    pub check_errors: Option<CheckErrors>,
+    /**
+     * Whether configuration commands are followed by `*OPC?` before the next command is sent.
+     */
+    #[cfg(feature = "usb")]
+    pub opc_sync: bool,
+    /**
+     * Time to wait between consecutive commands, for instruments dropping commands sent back to back.
+     */
+    #[cfg(any(feature = "usb", feature = "gpib"))]
+    pub delay: Option<Duration>,
+    /**
+     * Whether text commands are answered, or None to treat commands containing `?` as queries.
+     */
+    #[cfg(any(feature = "usb", feature = "serial", feature = "gpib"))]
+    pub expect_response: Option<bool>,
+}
+
+/**
+ * Interprets an instrument response with the configured reader type.
+ *
+ * # Arguments
+ * `options` - The options with the reader, and the sample rate and output file it may need.
+ * `data` - The response data.
+ *
+ * # Returns
+ * A Result containing a boxed Reading instance or an ApplicationError.
+ */
+pub(crate) fn scpi_reading(options: &ScpiOptions, data: Vec<u8>) -> Result<Box<dyn Reading>, ApplicationError> {
+    match options.reader {
+        arguments::Reader::ScpiRawReader => Ok(Box::new(ScpiRawReading::new(data))),
+        arguments::Reader::Analysis => {
+            let sample_rate = options.sample_rate.ok_or_else(|| {
+                ApplicationError::Command("The Analysis reader requires a sample rate".into())
+            })?;
+            Ok(Box::new(AnalysisReading::parse_ascii(&data, sample_rate)?))
+        }
+        arguments::Reader::ScpiBlockReader => Ok(Box::new(ScpiBlockReading::new(data))),
+        arguments::Reader::ScpiIdnReader => Ok(Box::new(IdnReading::parse(data)?)),
+        arguments::Reader::KeyValueReader => Ok(Box::new(KeyValueReading::parse(&data, &options.text_fields)?)),
+        arguments::Reader::ScreenshotReader => {
+            let mut reading = ScreenshotReading::new(data);
+            if let Some(path) = &options.output {
+                reading.save(path)?;
+            }
+            Ok(Box::new(reading))
+        }
+    }
+}
@@ -1,73 +1,190 @@
+use std::{
+    cell::Cell,
+    time::{Duration, Instant},
+};
+
 use async_trait::async_trait;
 
 use crate::{
-    arguments,
+    arguments::{self, Args, CheckErrors, RemoteMode},
+    capture,
     error::ApplicationError,
+    logging::hex_dump,
     instruments::{
-        communication::common::Communication, reading::{Reading, ScpiRawReading}
+        command::{
+            errorqueue::{parse_error, ERROR_QUERY, MAX_ERRORS}, peaktech::check_channel,
+            delay, indicate::{INDICATE_INTERVAL, SCPI_BEEP}, opc::{self, OPC_QUERY}, scope::RIGOL_WAVEFORM_PREAMBLE, usb488, CommandSet, Indicate, PeaktechChannelCommand, RawCommand, ScopeDialect, ScopeMeasurement,
+            ScopeSegments, ScopeWaveform, SwitchCommand, WaitServiceRequest, Delay, DmmFunction, KeysightDmmCommand,
+        },
+        communication::{
+            common::{get_scpi_options, get_usb_device, get_usb_endpoints, Communication, DeviceInfo, TransferTimings},
+            quirks::Quirks,
+            scpi::{scpi_reading, ScpiOptions},
+            transport::{BulkPipes, NusbPipes},
+            usbdevice::UsbTarget,
+        },
+        reading::{
+            block::block_payload, Identity, KeysightDmmReading, Reading, ScopeMeasurementReading, ScpiRawReading,
+            SegmentReading, StatusByteReading, WaveformPreamble, WaveformReading,
+        },
     },
 };
-use nusb::{
-    list_devices,
-    transfer::{Buffer, Bulk, Out},
-    DeviceInfo,
-};
+use tokio::sync::Mutex;
+use nusb::transfer::{Bulk, In, Interrupt, Out};
+use tracing::{debug, info, warn};
+
+/**
+ * Command putting the instrument in remote mode.
+ */
+const REMOTE_COMMAND: &str = "SYST:REM";
+
+/**
+ * Longest wait for the answer to `*IDN?` when identifying the instrument, so instruments that
+ * do not answer it open without waiting for the full read timeout.
+ */
+const IDENTIFY_TIMEOUT: Duration = Duration::from_millis(500);
+
+/**
+ * Largest Bulk IN transfer. Responses are assembled from as many transfers as needed.
+ */
+const READ_TRANSFER_SIZE: usize = 65536;
+
+/**
+ * USB interface and endpoints used to communicate with a SCPI instrument.
+ */
+#[derive(Debug, Clone, Copy)]
+pub struct UsbEndpoints {
+    /**
+     * USB interface number.
+     */
+    pub interface_number: u8,
+    /**
+     * USB Bulk IN endpoint address.
+     */
+    pub bulk_in_address: u8,
+    /**
+     * USB Bulk OUT endpoint address.
+     */
+    pub bulk_out_address: u8,
+    /**
+     * USB488 Interrupt IN endpoint address, used to wait for service requests.
+     */
+    pub interrupt_in_address: Option<u8>,
+}
 
 /**
  * Module for the ScpiUsb instrument using USB.
  */
 pub struct ScpiUsb {
     /** 
-    * USB Device Info 
+    * USB device, found by enumeration or given by its device node
     */
-    device: DeviceInfo,
+    device: UsbTarget,
     /**
-    * Reader type for interpreting instrument responses.
-    */
-    reader: arguments::Reader,
+     * USB interface and endpoints.
+     */
+    endpoints: UsbEndpoints,
     /**
-     * USB interface number.
+     * Command and response options.
      */
-    interface_number: u8,
+    options: ScpiOptions,
     /**
-     * USB Bulk IN endpoint address.
+     * Transfer timings of the last command.
      */
-    bulk_in_address: u8,
+    timings: Cell<TransferTimings>,
     /**
-     * USB Bulk OUT endpoint address.
+     * Configured function of multimeters, queried with `FUNC?` before the first `READ?` unless
+     * selected.
      */
-    bulk_out_address: u8,
+    dmm_function: Cell<Option<DmmFunction>>,
+    /**
+     * Session opened by the first command and kept for the next ones, so the device is claimed
+     * once. Held while a batch is sent.
+     */
+    session: Mutex<Option<Session>>,
 }
 
 impl ScpiUsb {
     /**
-     * Creates a new instance of ScpiUsb with the given USB device path.
+     * Creates a new instance of ScpiUsb with the given USB device.
      *
      * # Arguments
-     * `device` - A string slice representing the path to the USB device.
+     * `device` - The USB device.
+     * `endpoints` - The USB interface and endpoints.
+     * `options` - Command and response options.
      *
      * # Returns
      * A new ScpiUsb instance.
      */
-    pub async fn new(
-        device: &str,
-        reader: Option<arguments::Reader>,
-        interface_number: u8,
-        bulk_in_address: u8,
-        bulk_out_address: u8,
-    ) -> Result<Self, ApplicationError> {
-        let device = list_devices()
-            .await
-            .map_err(|e| ApplicationError::Usb(format!("Could not list usb devices: {}", e)))?
-            .find(|dev| format!("{:x}:{:x}", dev.vendor_id(), dev.product_id()) == device)
-            .ok_or_else(|| ApplicationError::Usb("ScpiUsb device not found".into()))?;
-        Ok(Self {
+    pub fn new(device: UsbTarget, endpoints: UsbEndpoints, options: ScpiOptions) -> Self {
+        Self {
             device,
-            reader: reader.unwrap_or(arguments::Reader::ScpiRawReader),
-            interface_number,
-            bulk_in_address,
-            bulk_out_address,
-        })
+            endpoints,
+            options,
+            timings: Cell::default(),
+            dmm_function: Cell::new(None),
+            session: Mutex::new(None),
+        }
+    }
+
+    /**
+     * Opens the device, claims the interface, resolves the endpoints and tries to identify the
+     * instrument if quirks are enabled.
+     *
+     * # Returns
+     * A Result containing the open session or an ApplicationError.
+     */
+    async fn open_session(&self) -> Result<Session, ApplicationError> {
+        let open_device = self.device.open().await?;
+        // Claim the interface
+        let interface = open_device
+            .claim_interface(self.endpoints.interface_number)
+            .await
+            .map_err(|e| ApplicationError::Nusb(format!("Could not open interface {}", self.endpoints.interface_number), e))?;
+        info!("Claimed interface {}", self.endpoints.interface_number);
+        let endpoint_out = interface
+            .endpoint::<Bulk, Out>(self.endpoints.bulk_out_address)
+            .map_err(|e| ApplicationError::Nusb(format!("Failed to get endpoint {}", self.endpoints.bulk_out_address), e))?;
+
+        let endpoint_in = interface
+            .endpoint::<Bulk, In>(self.endpoints.bulk_in_address)
+            .map_err(|e| ApplicationError::Nusb(format!("Failed to get endpoint {}", self.endpoints.bulk_in_address), e))?;
+
+        let endpoint_interrupt = match self.endpoints.interrupt_in_address {
+            Some(address) => Some(
+                interface
+                    .endpoint::<Interrupt, In>(address)
+                    .map_err(|e| ApplicationError::Nusb(format!("Failed to get endpoint {}", address), e))?,
+            ),
+            None => None,
+        };
+
+        let pipes = NusbPipes {
+            endpoint_out,
+            endpoint_in,
+            endpoint_interrupt,
+            in_buffer: None,
+        };
+        Session::open(Box::new(pipes), &self.options).await
+    }
+
+    /**
+     * Sends a batch of commands in an open session.
+     *
+     * # Arguments
+     * `session` - The open session.
+     * `commands` - The commands to send.
+     *
+     * # Returns
+     * A Result containing the readings or an ApplicationError.
+     */
+    async fn send_batch(&self, session: &mut Session, commands: Vec<String>) -> Result<Vec<Box<dyn Reading>>, ApplicationError> {
+        // Time the first command of this batch
+        session.submitted = None;
+        session.timings = TransferTimings::default();
+        let result = self.send_commands(session, commands).await;
+        self.timings.set(session.timings);
+        result
     }
 
     /**
@@ -77,102 +194,960 @@ impl ScpiUsb {
      * `data` - A vector of strings representing the instrument response data.
      *
      * # Returns
-     * A boxed Reading instance.
+     * A Result containing a boxed Reading instance or an ApplicationError.
      */
-    fn get_reading(&self, data: Vec<u8>) -> Box<dyn Reading> {
-        match self.reader {
-            arguments::Reader::ScpiRawReader => Box::new(ScpiRawReading::new(data)),
+    fn get_reading(&self, data: Vec<u8>) -> Result<Box<dyn Reading>, ApplicationError> {
+        scpi_reading(&self.options, data)
+    }
+
+    /**
+     * Sends a command whose response is interpreted by the reader. The ScpiBlockReader and
+     * ScreenshotReader read query responses as binary blocks, in as many transfers as needed.
+     *
+     * # Arguments
+     * `session` - The open session.
+     * `command` - The command to send.
+     *
+     * # Returns
+     * A Result containing the response for queries, None for other commands, or an ApplicationError.
+     */
+    async fn query(&self, session: &mut Session, command: &str) -> Result<Option<Vec<u8>>, ApplicationError> {
+        let block_reader = matches!(
+            self.options.reader,
+            arguments::Reader::ScpiBlockReader | arguments::Reader::ScreenshotReader
+        );
+        if block_reader && RawCommand::parse(command)?.expects_response(self.options.expect_response) {
+            Ok(Some(session.query_block(command).await?))
+        } else {
+            session.send_expecting(command, self.options.expect_response).await
         }
     }
-}
 
-#[async_trait(?Send)]
-impl Communication for ScpiUsb {
     /**
-     * Sends a command to the instrument.
+     * Captures the raw waveform memory of a channel: selects the source, reads the preamble
+     * and reads the points in chunks.
      *
      * # Arguments
-     * `command` - A Command enum variant representing the command to be sent.
+     * `session` - The open session.
+     * `waveform` - The waveform command.
+     * `dialect` - The SCPI dialect of the scope.
+     *
+     * # Returns
+     * A Result containing the WaveformReading or an ApplicationError.
      */
-    async fn command(
+    async fn capture_waveform(
         &self,
-        commands: Vec<String>,
-    ) -> Result<Option<Vec<Box<dyn Reading>>>, ApplicationError> {
-        let open_device = self
-            .device
-            .open()
-            .await
-            .map_err(|e| ApplicationError::Usb(format!("Could not open usb device: {}", e)))?;
-        // Claim the interface
-        let interface = open_device
-            .claim_interface(self.interface_number)
-            .await
-            .map_err(|e| ApplicationError::Usb(format!("Could not open interface {}: {}", self.interface_number, e)))?;
-        // Get the endpoint and submit transfer
-        let mut endpoint_out = interface
-            .endpoint::<Bulk, Out>(self.bulk_out_address)
-            .map_err(|e| ApplicationError::Usb(format!("Failed to get endpoint {}: {}", self.bulk_out_address, e)))?;
-
-        let mut endpoint_in = interface
-            .endpoint::<Bulk, nusb::transfer::In>(self.bulk_in_address)
-            .map_err(|e| ApplicationError::Usb(format!("Failed to get endpoint {}: {}", self.bulk_in_address, e)))?;
+        session: &mut Session,
+        waveform: &ScopeWaveform,
+        dialect: ScopeDialect,
+    ) -> Result<WaveformReading, ApplicationError> {
+        if dialect != ScopeDialect::Rigol {
+            return Err(ApplicationError::Command(format!(
+                "Waveform is not supported for {:?} scopes",
+                dialect
+            )));
+        }
+        for command in waveform.setup_commands() {
+            session.send(&command).await?;
+        }
+        let preamble = session
+            .send(RIGOL_WAVEFORM_PREAMBLE)
+            .await?
+            .ok_or_else(|| ApplicationError::Command("No waveform preamble received".into()))?;
+        let preamble = WaveformPreamble::parse(&preamble)?;
+        let mut data = Vec::new();
+        for chunk_commands in waveform.chunk_commands(preamble.points) {
+            if let Some((query, setup)) = chunk_commands.split_last() {
+                for command in setup {
+                    session.send(command).await?;
+                }
+                data.extend(session.query_block(query).await?);
+            }
+        }
+        Ok(WaveformReading::new(&waveform.source, preamble, data))
+    }
 
+    /**
+     * Sends the commands in a session, translating them with the command set.
+     *
+     * # Arguments
+     * `session` - The open session.
+     * `commands` - The commands to send.
+     *
+     * # Returns
+     * A Result containing the readings or an ApplicationError.
+     */
+    async fn send_commands(&self, session: &mut Session, commands: Vec<String>) -> Result<Vec<Box<dyn Reading>>, ApplicationError> {
         let mut response: Vec<Box<dyn Reading>> = Vec::new();
+        for (index, command) in commands.iter().enumerate() {
+            let previous = index.checked_sub(1).map(|previous| commands[previous].as_str());
+            if let Some(pause) = delay::pause_before(previous, command, self.options.delay) {
+                tokio::time::sleep(pause).await;
+            }
+            self.send_command(session, command, &mut response).await?;
+            if self.options.opc_sync && opc::needs_sync(command) {
+                session.wait_complete(command).await?;
+            }
+            if self.options.check_errors == Some(CheckErrors::Command) {
+                session.check_errors(command).await?;
+            }
+        }
+        if let (Some(CheckErrors::Batch), Some(last)) = (self.options.check_errors, commands.last()) {
+            session.check_errors(last).await?;
+        }
+        Ok(response)
+    }
+
+    /**
+     * Sends one command, translating it with the command set.
+     *
+     * # Arguments
+     * `session` - The open session.
+     * `command` - The command to send.
+     * `response` - The readings, extended with the readings of the command.
+     *
+     * # Returns
+     * A Result indicating success or an ApplicationError.
+     */
+    async fn send_command(&self, session: &mut Session, command: &str, response: &mut Vec<Box<dyn Reading>>) -> Result<(), ApplicationError> {
+        if let Some(wait) = WaitServiceRequest::parse(command)? {
+            response.push(Box::new(StatusByteReading::new(session.wait_service_request(&wait).await?)));
+            return Ok(());
+        }
+        if opc::is_opc(command) {
+            return session.wait_complete(command).await;
+        }
+        if let Some(delay) = Delay::parse(command)? {
+            tokio::time::sleep(delay.duration).await;
+            return Ok(());
+        }
+        if let Some(indicate) = Indicate::parse(command)? {
+            for _ in 0..indicate.signals() {
+                session.send(SCPI_BEEP).await?;
+                tokio::time::sleep(INDICATE_INTERVAL).await;
+            }
+            return Ok(());
+        }
+        match self.options.command_set {
+            CommandSet::Scope(dialect) => {
+                if let Some(segments) = ScopeSegments::parse(command)? {
+                    for (segment, segment_commands) in segments.to_scpi(dialect) {
+                        for segment_command in segment_commands {
+                            if let Some(data) = self.query(session, &segment_command).await? {
+                                response.push(Box::new(SegmentReading::new(
+                                    segment,
+                                    &segments.source,
+                                    self.get_reading(data)?,
+                                )));
+                            }
+                        }
+                    }
+                } else if let Some(waveform) = ScopeWaveform::parse(command)? {
+                    response.push(Box::new(self.capture_waveform(session, &waveform, dialect).await?));
+                } else if let Some(measurement) = ScopeMeasurement::parse(command)? {
+                    if let Some(data) = session.send(&measurement.to_scpi(dialect)).await? {
+                        response.push(Box::new(ScopeMeasurementReading::parse(measurement, data)));
+                    }
+                } else if let Some(data) = self.query(session, command).await? {
+                    response.push(self.get_reading(data)?);
+                }
+            }
+            CommandSet::Switch => {
+                let wire_command = match SwitchCommand::try_from(command) {
+                    Ok(switch_command) => switch_command.to_scpi(),
+                    Err(_) => command.to_string(),
+                };
+                if let Some(data) = self.query(session, &wire_command).await? {
+                    response.push(self.get_reading(data)?);
+                }
+            }
+            CommandSet::Generator(channel) => {
+                let wire_command = match PeaktechChannelCommand::parse(command)? {
+                    Some(generator_command) => generator_command.to_scpi(channel),
+                    None => command.to_string(),
+                };
+                if let Some(data) = self.query(session, &wire_command).await? {
+                    response.push(self.get_reading(data)?);
+                }
+            }
+            CommandSet::Dmm => match KeysightDmmCommand::parse(command)? {
+                Some(dmm_command) => self.send_dmm_command(session, &dmm_command, response).await?,
+                None => {
+                    // The function may have changed, query it again before the next READ?
+                    self.dmm_function.set(None);
+                    if let Some(data) = self.query(session, command).await? {
+                        response.push(self.get_reading(data)?);
+                    }
+                }
+            },
+            CommandSet::Raw => {
+                if let Some(data) = self.query(session, command).await? {
+                    response.push(self.get_reading(data)?);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /**
+     * Sends a typed multimeter command. Values of `MEAS:` and `READ?` are read with the unit of
+     * the configured function, which is queried with `FUNC?` if no function was selected.
+     *
+     * # Arguments
+     * `session` - The open session.
+     * `command` - The typed command.
+     * `response` - The readings, extended with a reading for each value.
+     *
+     * # Returns
+     * A Result indicating success or an ApplicationError.
+     */
+    async fn send_dmm_command(&self, session: &mut Session, command: &KeysightDmmCommand, response: &mut Vec<Box<dyn Reading>>) -> Result<(), ApplicationError> {
+        let function = match command {
+            KeysightDmmCommand::Configure(function, _) | KeysightDmmCommand::Measure(function, _) => *function,
+            KeysightDmmCommand::Read => match self.dmm_function.get() {
+                Some(function) => function,
+                None => DmmFunction::from_response(&session.send("FUNC?").await?.unwrap_or_default())?,
+            },
+            KeysightDmmCommand::Identify => {
+                if let Some(data) = session.send(&command.to_wire()).await? {
+                    response.push(Box::new(ScpiRawReading::new(data)));
+                }
+                return Ok(());
+            }
+        };
+        self.dmm_function.set(Some(function));
+        if let Some(data) = session.send(&command.to_wire()).await? {
+            for reading in KeysightDmmReading::parse(function, &data)? {
+                response.push(Box::new(reading));
+            }
+        }
+        Ok(())
+    }
+}
 
-        for command in commands {
+/**
+ * Open endpoints and active firmware workarounds, kept from the first command until the device
+ * is dropped.
+ */
+struct Session {
+    /**
+     * The Bulk and Interrupt pipes of the claimed interface.
+     */
+    pipes: Box<dyn BulkPipes>,
+    /**
+     * Firmware workarounds for the connected instrument.
+     */
+    quirks: Quirks,
+    /**
+     * Terminator appended to text commands.
+     */
+    terminator: Vec<u8>,
+    /**
+     * Terminator ending responses, or empty if responses end at the read timeout.
+     */
+    response_terminator: Vec<u8>,
+    /**
+     * Time to wait for more response data.
+     */
+    read_timeout: Duration,
+    /**
+     * Size at which a response is complete even without terminator.
+     */
+    max_response_size: usize,
+    /**
+     * Time the first command of the batch was submitted.
+     */
+    submitted: Option<Instant>,
+    /**
+     * Transfer timings of the first command of the batch.
+     */
+    timings: TransferTimings,
+    /**
+     * Size of the Bulk IN transfers, the maximum response size rounded up to whole packets.
+     */
+    transfer_size: usize,
+    /**
+     * Command putting the instrument back in local mode when the session is dropped, once it
+     * has been put in remote mode with --remote=remote-local.
+     */
+    local_command: Option<String>,
+}
 
-            let command_bytes = if command.clone().ends_with('\n') {
-                command.clone().into_bytes()
-            } else {
-                let mut cmd_bytes = command.clone().into_bytes();
-                cmd_bytes.push(b'\n');
-                cmd_bytes
-            };
+impl Session {
+    /**
+     * Creates a session on open pipes, without quirks until the instrument is identified.
+     *
+     * # Arguments
+     * `pipes` - The pipes of the claimed interface.
+     * `options` - Command and response options.
+     *
+     * # Returns
+     * A new Session instance.
+     */
+    fn new(pipes: Box<dyn BulkPipes>, options: &ScpiOptions) -> Self {
+        Self {
+            transfer_size: transfer_size(options.max_response_size, pipes.max_packet_size()),
+            pipes,
+            quirks: Quirks::default(),
+            terminator: options.terminator.clone(),
+            response_terminator: options.response_terminator.clone(),
+            read_timeout: options.read_timeout,
+            max_response_size: options.max_response_size,
+            submitted: None,
+            timings: TransferTimings::default(),
+            local_command: None,
+        }
+    }
 
-            let buffer = Buffer::from(command_bytes);
+    /**
+     * Creates a session on open pipes, puts the instrument in remote mode if asked and tries to
+     * identify it if quirks are enabled.
+     *
+     * # Arguments
+     * `pipes` - The pipes of the claimed interface.
+     * `options` - Command and response options.
+     *
+     * # Returns
+     * A Result containing the open session or an ApplicationError.
+     */
+    async fn open(pipes: Box<dyn BulkPipes>, options: &ScpiOptions) -> Result<Self, ApplicationError> {
+        let mut session = Self::new(pipes, options);
+        if options.remote != RemoteMode::Off {
+            session.send(REMOTE_COMMAND).await?;
+            if options.remote == RemoteMode::RemoteLocal {
+                session.local_command = Some(options.local_command.clone());
+            }
+        }
+        if options.quirks {
+            session.identify().await;
+        }
+        Ok(session)
+    }
 
-            endpoint_out.submit(buffer);
-            let completion = endpoint_out.next_complete().await;
+    /**
+     * Writes a command to the instrument, appending the terminator if missing.
+     *
+     * # Arguments
+     * `command` - The command to write.
+     * `raw_command` - The parsed command.
+     */
+    async fn write(&mut self, command: &str, raw_command: &RawCommand) -> Result<(), ApplicationError> {
+        let start = Instant::now();
+        let bytes = raw_command.to_bytes(&self.terminator);
+        debug!("Writing {:?}: {}", command, hex_dump(&bytes));
+        capture::written("usb", &bytes);
+        self.pipes
+            .write(bytes)
+            .await
+            .map_err(|e| ApplicationError::Transfer(format!("Failed to send command {:?}", command), e))?;
+        debug!("Wrote {:?} in {:?}", command, start.elapsed());
+        if self.submitted.is_none() {
+            self.submitted = Some(start);
+            self.timings.write = Some(start.elapsed());
+        }
+        if let Some(delay) = self.quirks.delay_after(command) {
+            tokio::time::sleep(delay).await;
+        }
+        Ok(())
+    }
 
-            match completion.status {
-                Ok(()) => {}
-                Err(e) => {
-                    return Err(ApplicationError::Command(format!(
-                        "Failed to send command {:?}: {:?}",
-                        command, e
-                    )))
+    /**
+     * Reads the response to a query from the instrument. Transfers are read until the response
+     * ends with the response terminator, reaches the maximum size or no more data arrives.
+     * Binary responses end with the first transfer.
+     *
+     * # Arguments
+     * `command` - The query the response belongs to.
+     * `raw_command` - The parsed query.
+     *
+     * # Returns
+     * A Result containing the response bytes or an ApplicationError.
+     */
+    async fn read(&mut self, command: &str, raw_command: &RawCommand) -> Result<Vec<u8>, ApplicationError> {
+        let mut data = Vec::with_capacity(self.transfer_size);
+        loop {
+            if self.read_transfer(command, &mut data).await? == 0 {
+                if data.is_empty() {
+                    return Err(ApplicationError::Timeout(format!(
+                        "No response to command {:?} within {} ms",
+                        command,
+                        self.read_timeout.as_millis()
+                    )));
                 }
+                break;
+            }
+            if response_complete(&data, &self.response_terminator, self.max_response_size)
+                || self.quirks.no_response_terminator()
+                || matches!(raw_command, RawCommand::Hex { .. })
+            {
+                break;
             }
+        }
+        self.quirks.fix_response(&mut data);
+        Ok(data)
+    }
 
-            let data_as_vec: Option<Vec<u8>> = if command.contains('?') {
-                let read_buffer = Buffer::new(2000000);
-                endpoint_in.submit(read_buffer);
-                let completion = endpoint_in.next_complete().await;
+    /**
+     * Reads one Bulk IN transfer of the transfer size.
+     *
+     * # Arguments
+     * `command` - The query the response belongs to.
+     * `data` - The response so far, extended with the received bytes.
+     *
+     * # Returns
+     * A Result containing the number of received bytes, 0 if nothing arrived within the read
+     * timeout, or an ApplicationError.
+     */
+    async fn read_transfer(&mut self, command: &str, data: &mut Vec<u8>) -> Result<usize, ApplicationError> {
+        let received = self.pipes.read(data, self.transfer_size, self.read_timeout).await.map_err(|e| {
+            ApplicationError::Transfer(format!("Failed to read response for command {:?}", command), e)
+        })?;
+        debug!("Read for {:?}: {}", command, hex_dump(&data[data.len() - received..]));
+        capture::read("usb", &data[data.len() - received..]);
+        if self.timings.first_byte.is_none() && received > 0 {
+            self.timings.first_byte = self.submitted.map(|submitted| submitted.elapsed());
+        }
+        Ok(received)
+    }
 
-                match completion.status {
-                    Ok(()) => {
-                        let data = completion.buffer.to_vec();
-                        Some(data)
-                    }
-                    Err(e) => {
-                        return Err(ApplicationError::Command(format!(
-                            "Failed to read response for command {:?}: {:?}",
-                            command, e
-                        )))
-                    }
+    /**
+     * Sends a query answered with an IEEE 488.2 binary block, reading transfers until the
+     * declared length has been received.
+     *
+     * # Arguments
+     * `command` - The query to send.
+     *
+     * # Returns
+     * A Result containing the block payload or an ApplicationError.
+     */
+    async fn query_block(&mut self, command: &str) -> Result<Vec<u8>, ApplicationError> {
+        let raw_command = RawCommand::parse(command)?;
+        self.write(command, &raw_command).await?;
+        let mut data = Vec::with_capacity(self.transfer_size);
+        self.read_transfer(command, &mut data).await?;
+        loop {
+            if let Some(length) = block_payload(&data)?.map(<[u8]>::len) {
+                // Strip the header in place, the payload starts after `#`, the digit count and the length
+                let start = 2 + (data[1] - b'0') as usize;
+                data.truncate(start + length);
+                data.drain(..start);
+                return Ok(data);
+            }
+            if self.read_transfer(command, &mut data).await? == 0 {
+                return Err(ApplicationError::Command(format!(
+                    "Incomplete binary block for command {:?}",
+                    command
+                )));
+            }
+        }
+    }
+
+    /**
+     * Writes a command and reads the response if the command is a query.
+     *
+     * # Arguments
+     * `command` - The command to send.
+     *
+     * # Returns
+     * A Result containing the response for queries, None for other commands, or an ApplicationError.
+     */
+    async fn send(&mut self, command: &str) -> Result<Option<Vec<u8>>, ApplicationError> {
+        self.send_expecting(command, None).await
+    }
+
+    /**
+     * Writes a command and reads the response if one is expected.
+     *
+     * # Arguments
+     * `command` - The command to send.
+     * `expect_response` - Whether a text command is answered, or None to detect queries by `?`.
+     *
+     * # Returns
+     * A Result containing the response if expected, None otherwise, or an ApplicationError.
+     */
+    async fn send_expecting(&mut self, command: &str, expect_response: Option<bool>) -> Result<Option<Vec<u8>>, ApplicationError> {
+        let raw_command = RawCommand::parse(command)?;
+        self.write(command, &raw_command).await?;
+        if raw_command.expects_response(expect_response) {
+            Ok(Some(self.read(command, &raw_command).await?))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /**
+     * Waits for a service request on the USB488 Interrupt IN endpoint. Other notifications are skipped.
+     *
+     * # Arguments
+     * `wait` - The wait command with its timeout.
+     *
+     * # Returns
+     * A Result containing the status byte of the service request or an ApplicationError.
+     */
+    async fn wait_service_request(&mut self, wait: &WaitServiceRequest) -> Result<u8, ApplicationError> {
+        if !self.pipes.has_interrupt() {
+            return Err(ApplicationError::Usb("WaitSrq requires an interrupt endpoint (--interrupt-in-address)".into()));
+        }
+        let pipes = &mut self.pipes;
+        let result = tokio::time::timeout(wait.timeout, async {
+            loop {
+                let packet = pipes
+                    .read_interrupt()
+                    .await
+                    .map_err(|e| ApplicationError::Transfer("Failed to read interrupt endpoint".into(), e))?;
+                if let Some(status_byte) = usb488::service_request_status(&packet) {
+                    return Ok(status_byte);
                 }
-            } else {
-                None
-            };
+            }
+        })
+        .await;
+        match result {
+            Ok(status_byte) => status_byte,
+            Err(_) => {
+                self.pipes.cancel_interrupt();
+                Err(ApplicationError::Timeout(format!(
+                    "No service request within {} ms",
+                    wait.timeout.as_millis()
+                )))
+            }
+        }
+    }
 
-            if let Some(data) = data_as_vec {
-                response.push(self.get_reading(data));
+    /**
+     * Waits with `*OPC?` until the instrument has finished all pending operations.
+     *
+     * # Arguments
+     * `command` - The command waited for, used in the error message.
+     *
+     * # Returns
+     * A Result indicating success or an ApplicationError.
+     */
+    async fn wait_complete(&mut self, command: &str) -> Result<(), ApplicationError> {
+        let response = self
+            .send(OPC_QUERY)
+            .await?
+            .ok_or_else(|| ApplicationError::Command("No response to *OPC?".into()))?;
+        opc::check_complete(command, &response)
+    }
+
+    /**
+     * Reads the error queue until it reports no error.
+     *
+     * # Arguments
+     * `command` - The command checked, used in the error message.
+     *
+     * # Returns
+     * A Result indicating success or an ApplicationError listing the reported errors.
+     */
+    async fn check_errors(&mut self, command: &str) -> Result<(), ApplicationError> {
+        let mut errors = Vec::new();
+        while errors.len() < MAX_ERRORS {
+            let response = self
+                .send(ERROR_QUERY)
+                .await?
+                .ok_or_else(|| ApplicationError::Command("No response to SYST:ERR?".into()))?;
+            match parse_error(&response)? {
+                Some(error) => errors.push(error),
+                None => break,
+            }
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(ApplicationError::Command(format!(
+                "Instrument reported errors after {:?}: {}",
+                command,
+                errors.join("; ")
+            )))
+        }
+    }
+
+    /**
+     * Identifies the instrument with `*IDN?` and activates its known firmware workarounds. An
+     * instrument that does not answer in time is used without workarounds.
+     */
+    async fn identify(&mut self) {
+        let read_timeout = self.read_timeout;
+        self.read_timeout = read_timeout.min(IDENTIFY_TIMEOUT);
+        let response = self.send("*IDN?").await;
+        self.read_timeout = read_timeout;
+        match response {
+            Ok(data) => {
+                if let Some(identity) = data.and_then(|data| Identity::parse(&data)) {
+                    self.quirks = Quirks::lookup(&identity);
+                    info!("Identified {} {}, quirks {:?}", identity.manufacturer, identity.model, self.quirks);
+                }
             }
+            Err(e) => warn!("Could not identify the instrument, continuing without quirks: {}", e),
         }
+    }
+}
+
+impl Drop for Session {
+    /**
+     * Puts the instrument back in local mode if the session put it in remote mode. The command
+     * is written without waiting for the runtime, as the session may be dropped while it runs.
+     */
+    fn drop(&mut self) {
+        let Some(command) = self.local_command.take() else {
+            return;
+        };
+        let bytes = match RawCommand::parse(&command) {
+            Ok(raw_command) => raw_command.to_bytes(&self.terminator),
+            Err(e) => return warn!("Could not send the local command {:?}: {}", command, e),
+        };
+        debug!("Writing {:?}: {}", command, hex_dump(&bytes));
+        capture::written("usb", &bytes);
+        if let Err(e) = futures_executor::block_on(self.pipes.write(bytes)) {
+            warn!("Could not send the local command {:?}: {}", command, e);
+        }
+    }
+}
+
+/**
+ * Checks if a response is complete.
+ *
+ * # Arguments
+ * `data` - The response received so far.
+ * `terminator` - The response terminator, or empty if responses end at the read timeout.
+ * `max_size` - The maximum response size.
+ *
+ * # Returns
+ * A boolean indicating whether the response is complete.
+ */
+fn response_complete(data: &[u8], terminator: &[u8], max_size: usize) -> bool {
+    data.len() >= max_size || (!terminator.is_empty() && data.ends_with(terminator))
+}
+
+/**
+ * Computes the size of the Bulk IN transfers: large enough for the largest response, up to
+ * READ_TRANSFER_SIZE, in whole packets as IN transfers must be.
+ *
+ * # Arguments
+ * `max_response_size` - The maximum response size.
+ * `max_packet_size` - The maximum packet size of the endpoint.
+ *
+ * # Returns
+ * The transfer size in bytes.
+ */
+fn transfer_size(max_response_size: usize, max_packet_size: usize) -> usize {
+    let packet = max_packet_size.max(1);
+    max_response_size.clamp(1, READ_TRANSFER_SIZE).div_ceil(packet) * packet
+}
+
+#[async_trait(?Send)]
+impl Communication for ScpiUsb {
+    /**
+     * Sends commands to the instrument. The first command opens and claims the device, the next
+     * ones reuse the endpoints; after a failure the device is opened again.
+     *
+     * # Arguments
+     * `command` - A Command enum variant representing the command to be sent.
+     */
+    async fn command(
+        &self,
+        commands: Vec<String>,
+    ) -> Result<Option<Vec<Box<dyn Reading>>>, ApplicationError> {
+        let mut open = self.session.lock().await;
+        let session = match open.as_mut() {
+            Some(session) => session,
+            None => open.insert(self.open_session().await?),
+        };
+        let result = self.send_batch(session, commands).await;
+        if result.is_err() {
+            // Reopen for the next command, the device may be gone or have a response pending
+            *open = None;
+        }
+        let response = result?;
 
         Ok(match response.is_empty() {
             false => Some(response),
             true => None,
         })
     }
+
+    /**
+     * Returns the USB descriptor strings of the instrument. Devices given by their device node
+     * are opened without enumerating the bus and have none.
+     *
+     * # Returns
+     * The device info, or None.
+     */
+    fn device_info(&self) -> Option<DeviceInfo> {
+        match &self.device {
+            UsbTarget::Info(info) => Some(DeviceInfo {
+                manufacturer: info.manufacturer_string().map(String::from),
+                product: info.product_string().map(String::from),
+                serial_number: info.serial_number().map(String::from),
+            }),
+            UsbTarget::Node(_) => None,
+        }
+    }
+
+    /**
+     * Returns the transfer timings of the first command of the last batch.
+     *
+     * # Returns
+     * The timings.
+     */
+    fn transfer_timings(&self) -> Option<TransferTimings> {
+        Some(self.timings.get())
+    }
+}
+
+const DEFAULT_USB_INTERFACE_NUM: u8 = 0;
+const DEFAULT_USB_BULK_IN_ADDRESS: u8 = 0x81;
+const DEFAULT_USB_BULK_OUT_ADDRESS: u8 = 0x01;
+
+const PEAKTECH_4055MV_USB_INTERFACE_NUM: u8 = 0;
+const PEAKTECH_4055MV_USB_BULK_IN_ADDRESS: u8 = 0x82;
+const PEAKTECH_4055MV_USB_BULK_OUT_ADDRESS: u8 = 0x02;
+
+/**
+ * Opens the USB instrument of the arguments with the default interface and endpoints.
+ *
+ * # Arguments
+ * `args` - An Args struct containing the configuration for the desired device.
+ * `command_set` - How typed commands are translated for the device.
+ *
+ * # Returns
+ * A Result containing a boxed Communication trait object or an ApplicationError.
+ */
+pub(crate) async fn open(args: &Args, command_set: CommandSet) -> Result<Box<dyn Communication>, ApplicationError> {
+    let endpoints = get_usb_endpoints(args, DEFAULT_USB_INTERFACE_NUM, DEFAULT_USB_BULK_IN_ADDRESS, DEFAULT_USB_BULK_OUT_ADDRESS);
+    Ok(Box::new(ScpiUsb::new(get_usb_device(args).await?, endpoints, ScpiOptions { command_set, ..get_scpi_options(args) })))
+}
+
+/**
+ * Opens the PeakTech 4055MV generator of the arguments, on the channel of the arguments.
+ *
+ * # Arguments
+ * `args` - An Args struct containing the configuration for the desired device.
+ *
+ * # Returns
+ * A Result containing a boxed Communication trait object or an ApplicationError.
+ */
+pub(crate) async fn open_peaktech_4055mv(args: &Args) -> Result<Box<dyn Communication>, ApplicationError> {
+    let endpoints = get_usb_endpoints(args, PEAKTECH_4055MV_USB_INTERFACE_NUM, PEAKTECH_4055MV_USB_BULK_IN_ADDRESS, PEAKTECH_4055MV_USB_BULK_OUT_ADDRESS);
+    let command_set = CommandSet::Generator(check_channel(args.connection.channel.unwrap_or(1))?);
+    Ok(Box::new(ScpiUsb::new(get_usb_device(args).await?, endpoints, ScpiOptions { command_set, ..get_scpi_options(args) })))
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::PathBuf;
+
+    use clap::Parser;
+    use nusb::transfer::TransferError;
+
+    use super::*;
+    use crate::{
+        config::Config,
+        instruments::communication::transport::scripted::{ScriptedPipes, Written},
+        workflow::script,
+    };
+
+    /**
+     * Returns the arguments of a generic SCPI instrument.
+     */
+    fn scpi_args(args: &[&str]) -> Args {
+        Args::parse_from([&["hardware-measurement", "--device=generic-scpi-usb", "--usb=1ab1:0e11"], args].concat())
+    }
+
+    /**
+     * Creates a generic SCPI instrument with a session on scripted pipes.
+     */
+    fn scripted(args: &[&str], pipes: ScriptedPipes) -> (ScpiUsb, Session, Written) {
+        let args = scpi_args(args);
+        let endpoints = get_usb_endpoints(&args, DEFAULT_USB_INTERFACE_NUM, DEFAULT_USB_BULK_IN_ADDRESS, DEFAULT_USB_BULK_OUT_ADDRESS);
+        let options = get_scpi_options(&args);
+        let written = pipes.written.clone();
+        let session = Session::new(Box::new(pipes), &options);
+        (ScpiUsb::new(UsbTarget::Node(PathBuf::from("/dev/null")), endpoints, options), session, written)
+    }
+
+    #[tokio::test]
+    async fn test_query_split_across_transfers() {
+        let pipes = ScriptedPipes::new(vec![Ok(b"RIGOL TECHNOLOGIES,".to_vec()), Ok(b"DS1054Z,DS1ZA1,00.04\n".to_vec())]);
+        let (scpi, mut session, written) = scripted(&[], pipes);
+        let readings = scpi.send_batch(&mut session, vec!["OUTP ON".into(), "*IDN?".into()]).await.unwrap();
+        assert_eq!(readings.len(), 1);
+        assert_eq!(readings[0].get_raw_string().unwrap(), "RIGOL TECHNOLOGIES,DS1054Z,DS1ZA1,00.04\n");
+        assert_eq!(*written.borrow(), [b"OUTP ON\n".to_vec(), b"*IDN?\n".to_vec()]);
+    }
+
+    #[tokio::test]
+    async fn test_query_failures() {
+        let (scpi, mut session, _) = scripted(&[], ScriptedPipes::new(Vec::new()));
+        let result = scpi.send_batch(&mut session, vec!["MEAS:VOLT?".into()]).await;
+        assert!(matches!(result, Err(ApplicationError::Timeout(_))));
+        let (scpi, mut session, _) = scripted(&[], ScriptedPipes::new(vec![Err(TransferError::Stall)]));
+        let Err(error) = scpi.send_batch(&mut session, vec!["MEAS:VOLT?".into()]).await else {
+            panic!("Expected the stall to fail the query");
+        };
+        assert!(error.to_string().starts_with("Command Error: Failed to read response for command \"MEAS:VOLT?\": "));
+        let source = std::error::Error::source(&error).and_then(|source| source.downcast_ref::<TransferError>());
+        assert_eq!(source, Some(&TransferError::Stall));
+    }
+
+    #[tokio::test]
+    async fn test_identify_timeout() {
+        let options = get_scpi_options(&scpi_args(&[]));
+        assert!(options.quirks);
+        let pipes = ScriptedPipes::new(Vec::new());
+        let (written, read_timeouts) = (pipes.written.clone(), pipes.read_timeouts.clone());
+        let session = Session::open(Box::new(pipes), &options).await.unwrap();
+        assert_eq!(*written.borrow(), [b"*IDN?\n".to_vec()]);
+        assert_eq!(*read_timeouts.borrow(), [IDENTIFY_TIMEOUT]);
+        assert_eq!(session.quirks, Quirks::default());
+        assert_eq!(session.read_timeout, options.read_timeout);
+    }
+
+    /**
+     * Opens a session on scripted pipes, sends two batches and drops it.
+     *
+     * # Returns
+     * The written commands.
+     */
+    async fn remote_session(args: &[&str], pipes: ScriptedPipes) -> Vec<String> {
+        let (scpi, _, _) = scripted(args, ScriptedPipes::new(Vec::new()));
+        let written = pipes.written.clone();
+        let mut session = Session::open(Box::new(pipes), &scpi.options).await.unwrap();
+        for command in ["OUTP ON", "MEAS:VOLT?"] {
+            let _ = scpi.send_batch(&mut session, vec![command.into()]).await;
+        }
+        drop(session);
+        written.take().iter().map(|bytes| String::from_utf8_lossy(bytes).trim_end().to_string()).collect()
+    }
+
+    #[tokio::test]
+    async fn test_remote_mode() {
+        let written = remote_session(&["--no-quirks"], ScriptedPipes::new(vec![Ok(b"1.5\n".to_vec())])).await;
+        assert_eq!(written, ["OUTP ON", "MEAS:VOLT?"]);
+        let args = ["--no-quirks", "--remote=remote"];
+        let written = remote_session(&args, ScriptedPipes::new(vec![Ok(b"1.5\n".to_vec())])).await;
+        assert_eq!(written, ["SYST:REM", "OUTP ON", "MEAS:VOLT?"]);
+        // Remote once when the session is opened and local once when it is dropped, also after a failing batch
+        let args = ["--no-quirks", "--remote=remote-local", "--local-command=GTL"];
+        let written = remote_session(&args, ScriptedPipes::new(vec![Err(TransferError::Stall)])).await;
+        assert_eq!(written, ["SYST:REM", "OUTP ON", "MEAS:VOLT?", "GTL"]);
+    }
+
+    #[tokio::test]
+    async fn test_binary_block_split_across_transfers() {
+        let pipes = ScriptedPipes::new(vec![Ok(b"#211hello".to_vec()), Ok(b" world\n".to_vec())]);
+        let (_, mut session, _) = scripted(&[], pipes);
+        assert_eq!(session.query_block(":DISP:DATA?").await.unwrap(), b"hello world");
+        let (_, mut session, _) = scripted(&[], ScriptedPipes::new(vec![Ok(b"#15hel".to_vec())]));
+        assert!(session.query_block(":DISP:DATA?").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_error_queue_after_command() {
+        let pipes = ScriptedPipes::new(vec![Ok(b"-222,\"Data out of range\"\n".to_vec()), Ok(b"0,\"No error\"\n".to_vec())]);
+        let (scpi, mut session, written) = scripted(&["--check-errors=command"], pipes);
+        let Err(error) = scpi.send_batch(&mut session, vec!["VOLT 99".into()]).await else {
+            panic!("Expected the reported error to fail the command");
+        };
+        assert!(error.to_string().contains("Data out of range"));
+        assert_eq!(*written.borrow(), [b"VOLT 99\n".to_vec(), b"SYST:ERR?\n".to_vec(), b"SYST:ERR?\n".to_vec()]);
+    }
+
+    #[tokio::test]
+    async fn test_wait_service_request() {
+        let pipes = ScriptedPipes::new(Vec::new()).with_interrupts(vec![vec![0x01, 0x00], vec![0x81, 0x40]]);
+        let (_, mut session, _) = scripted(&[], pipes);
+        let wait = WaitServiceRequest::parse("WaitSrq:100").unwrap().unwrap();
+        assert_eq!(session.wait_service_request(&wait).await.unwrap(), 0x40);
+        assert!(matches!(session.wait_service_request(&wait).await, Err(ApplicationError::Timeout(_))));
+        let (_, mut session, _) = scripted(&[], ScriptedPipes::new(Vec::new()));
+        assert!(matches!(session.wait_service_request(&wait).await, Err(ApplicationError::Usb(_))));
+    }
+
+    #[tokio::test]
+    async fn test_dmm_commands() {
+        let pipes = ScriptedPipes::new(vec![
+            Ok(b"+1.23456789E+00\n".to_vec()),
+            Ok(b"\"FRES\"\n".to_vec()),
+            Ok(b"+9.90000000E+37,+1.00012000E+02\n".to_vec()),
+        ]);
+        let (mut scpi, mut session, written) = scripted(&[], pipes);
+        scpi.options.command_set = CommandSet::Dmm;
+        let readings = scpi.send_batch(&mut session, vec!["Measure:VoltageDc:10".into()]).await.unwrap();
+        assert_eq!(readings[0].get_csv().unwrap(), ["VoltageDc", "1.23456789", "V", "false"]);
+        let readings = scpi.send_batch(&mut session, vec!["SAMP:COUN 2".into(), "Read".into()]).await.unwrap();
+        assert_eq!(readings.len(), 2);
+        assert_eq!(readings[0].get_raw_string().unwrap(), "OL Ω");
+        assert_eq!(readings[1].get_csv().unwrap(), ["Resistance4W", "100.012", "Ω", "false"]);
+        assert_eq!(
+            *written.borrow(),
+            [b"MEAS:VOLT:DC? 10\n".to_vec(), b"SAMP:COUN 2\n".to_vec(), b"FUNC?\n".to_vec(), b"READ?\n".to_vec()]
+        );
+        assert!(scpi.send_batch(&mut session, vec!["Measure:Volts".into()]).await.is_err());
+    }
+
+    #[test]
+    fn test_response_complete() {
+        assert!(response_complete(b"1.234\n", b"\n", 100));
+        assert!(!response_complete(b"1.23", b"\n", 100));
+        assert!(!response_complete(b"1.234\n", b"\r\n", 100));
+        assert!(response_complete(b"1.23", b"\n", 4));
+        assert!(!response_complete(b"1.234\n", b"", 100));
+    }
+
+    #[test]
+    fn test_transfer_size() {
+        assert_eq!(transfer_size(2_000_000, 512), READ_TRANSFER_SIZE);
+        assert_eq!(transfer_size(100, 64), 128);
+        assert_eq!(transfer_size(512, 512), 512);
+        assert_eq!(transfer_size(0, 64), 64);
+    }
+
+    /**
+     * Runs the script of the sweep example on a generator with the settings of its profile, on
+     * scripted pipes answering the frequency queries.
+     */
+    #[tokio::test(start_paused = true)]
+    async fn test_sweep_example() {
+        let config = Config::parse(&std::fs::read_to_string("examples/sweep/config.toml").unwrap()).unwrap();
+        let args = ["hardware-measurement", "--profile=generator", "--script=examples/sweep/steps.txt", "--no-quirks"];
+        let (_, args) = config.devices(&Args::parse_from(args)).unwrap().remove(0);
+        let pipes = ScriptedPipes::new(vec![Ok(b"100\n".to_vec()), Ok(b"1000\n".to_vec()), Ok(b"10000\n".to_vec())]);
+        let written = pipes.written.clone();
+        let options = ScpiOptions { command_set: CommandSet::Generator(1), ..get_scpi_options(&args) };
+        let session = Session::new(Box::new(pipes), &options);
+        let endpoints = get_usb_endpoints(
+            &args,
+            PEAKTECH_4055MV_USB_INTERFACE_NUM,
+            PEAKTECH_4055MV_USB_BULK_IN_ADDRESS,
+            PEAKTECH_4055MV_USB_BULK_OUT_ADDRESS,
+        );
+        let generator = ScpiUsb::new(UsbTarget::Node(PathBuf::from("/dev/null")), endpoints, options);
+        *generator.session.lock().await = Some(session);
+
+        let readings = script::run(&args, &mut Some(Box::new(generator))).await.unwrap().unwrap();
+        let written: Vec<String> =
+            written.take().iter().map(|bytes| String::from_utf8_lossy(bytes).trim_end().to_string()).collect();
+        assert_eq!(
+            written,
+            [
+                "APPL:SIN 100,1,0",
+                "OUTP ON",
+                "FREQ 100",
+                "FREQ?",
+                "FREQ 1000",
+                "FREQ?",
+                "FREQ 10000",
+                "FREQ?",
+                "SOUR2:SWE:SPAC LOG;:SOUR2:FREQ:STAR 10;:SOUR2:FREQ:STOP 100000;:SOUR2:SWE:TIME 10;:SOUR2:SWE:STAT ON",
+                "TRIG2:SOUR IMM",
+                "OUTP2 ON",
+                // The all-off macro of the configuration file
+                "OUTP OFF",
+                "OUTP2 OFF",
+            ]
+        );
+        let readings: Vec<String> = readings.iter().map(|reading| reading.get_raw_string().unwrap()).collect();
+        assert_eq!(readings, ["100\n", "1000\n", "10000\n"]);
+    }
 }
@@ -0,0 +1,224 @@
+#[cfg(feature = "serial")]
+use std::{
+    cell::RefCell,
+    io::{ErrorKind, Read, Write},
+    time::{Duration, Instant},
+};
+
+#[cfg(feature = "serial")]
+use tracing::{debug, info};
+
+use crate::arguments::Parity;
+#[cfg(feature = "serial")]
+use crate::{capture, error::ApplicationError, instruments::communication::common::DeviceInfo, logging::hex_dump};
+
+/**
+ * Serial line settings of a serial port or HID-UART bridge.
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UartConfig {
+    pub baud_rate: u32,
+    pub data_bits: u8,
+    pub parity: Parity,
+    pub stop_bits: u8,
+}
+
+/**
+ * A serial port, e.g. the USB CDC port of a power supply.
+ */
+#[cfg(feature = "serial")]
+pub struct SerialLine {
+    // Serial port instance
+    port: RefCell<Box<dyn serialport::SerialPort>>,
+    /**
+     * Path of the port, used in error messages.
+     */
+    path: String,
+}
+
+#[cfg(feature = "serial")]
+impl SerialLine {
+    /**
+     * Opens and configures a serial port.
+     *
+     * # Arguments
+     * `path` - The path of the serial port, e.g. /dev/ttyACM0.
+     * `config` - The serial line settings.
+     * `timeout` - Time without data after which a read ends.
+     *
+     * # Returns
+     * A new SerialLine instance.
+     */
+    pub fn open(path: &str, config: &UartConfig, timeout: Duration) -> Result<Self, ApplicationError> {
+        let data_bits = match config.data_bits {
+            5 => serialport::DataBits::Five,
+            6 => serialport::DataBits::Six,
+            7 => serialport::DataBits::Seven,
+            8 => serialport::DataBits::Eight,
+            bits => return Err(ApplicationError::Serial(format!("Unsupported data bits {}", bits))),
+        };
+        let stop_bits = match config.stop_bits {
+            1 => serialport::StopBits::One,
+            2 => serialport::StopBits::Two,
+            bits => return Err(ApplicationError::Serial(format!("Unsupported stop bits {}", bits))),
+        };
+        let parity = match config.parity {
+            Parity::None => serialport::Parity::None,
+            Parity::Odd => serialport::Parity::Odd,
+            Parity::Even => serialport::Parity::Even,
+        };
+        let port = serialport::new(path, config.baud_rate)
+            .data_bits(data_bits)
+            .stop_bits(stop_bits)
+            .parity(parity)
+            .timeout(timeout)
+            .open()
+            .map_err(|e| ApplicationError::SerialPort(format!("Failed to open serial port {}", path), e))?;
+        info!("Opened serial port {} at {} baud", path, config.baud_rate);
+        Ok(Self {
+            port: RefCell::new(port),
+            path: path.to_string(),
+        })
+    }
+
+    /**
+     * Discards unread input and writes data to the port.
+     *
+     * # Arguments
+     * `data` - The bytes to send.
+     */
+    pub fn write(&self, data: &[u8]) -> Result<(), ApplicationError> {
+        let mut port = self.port.borrow_mut();
+        port.clear(serialport::ClearBuffer::Input)
+            .map_err(|e| ApplicationError::SerialPort(format!("Failed to clear {}", self.path), e))?;
+        debug!("Writing to {}: {}", self.path, hex_dump(data));
+        capture::written(&self.path, data);
+        port.write_all(data)
+            .map_err(|e| ApplicationError::SerialPort(format!("Failed to write to {}", self.path), e.into()))
+    }
+
+    /**
+     * Sets the modem control lines, e.g. to power the optocoupler of an interface cable.
+     *
+     * # Arguments
+     * `dtr` - The state of Data Terminal Ready.
+     * `rts` - The state of Request To Send.
+     *
+     * # Returns
+     * A Result indicating success or an ApplicationError.
+     */
+    pub fn set_control_lines(&self, dtr: bool, rts: bool) -> Result<(), ApplicationError> {
+        let mut port = self.port.borrow_mut();
+        port.write_data_terminal_ready(dtr)
+            .and_then(|_| port.write_request_to_send(rts))
+            .map_err(|e| ApplicationError::SerialPort(format!("Failed to set control lines of {}", self.path), e))
+    }
+
+    /**
+     * Discards unread input and reads a stream until the received data is complete.
+     *
+     * # Arguments
+     * `complete` - Checks if the received data is complete.
+     * `limit` - The longest time to read.
+     *
+     * # Returns
+     * A Result containing the received bytes or an ApplicationError if the data is not
+     * complete within the limit.
+     */
+    pub fn read_until(&self, complete: impl Fn(&[u8]) -> bool, limit: Duration) -> Result<Vec<u8>, ApplicationError> {
+        self.port
+            .borrow_mut()
+            .clear(serialport::ClearBuffer::Input)
+            .map_err(|e| ApplicationError::SerialPort(format!("Failed to clear {}", self.path), e))?;
+        self.read_response(complete, limit)
+    }
+
+    /**
+     * Reads the response to a command until it is complete. Input received since the command
+     * was written is kept.
+     *
+     * # Arguments
+     * `complete` - Checks if the received data is complete.
+     * `limit` - The longest time to read.
+     *
+     * # Returns
+     * A Result containing the received bytes or an ApplicationError if the data is not
+     * complete within the limit.
+     */
+    pub fn read_response(&self, complete: impl Fn(&[u8]) -> bool, limit: Duration) -> Result<Vec<u8>, ApplicationError> {
+        let mut port = self.port.borrow_mut();
+        let start = Instant::now();
+        let mut data = Vec::new();
+        let mut buffer = [0u8; 64];
+        while !complete(&data) {
+            if start.elapsed() > limit {
+                return Err(ApplicationError::Timeout(format!(
+                    "No complete data from {} within {} ms",
+                    self.path,
+                    limit.as_millis()
+                )));
+            }
+            match port.read(&mut buffer) {
+                Ok(length) => data.extend_from_slice(&buffer[..length]),
+                Err(e) if e.kind() == ErrorKind::TimedOut => {}
+                Err(e) => {
+                    return Err(ApplicationError::SerialPort(format!("Failed to read from {}", self.path), e.into()))
+                }
+            }
+        }
+        debug!("Read from {} in {:?}: {}", self.path, start.elapsed(), hex_dump(&data));
+        capture::read(&self.path, &data);
+        Ok(data)
+    }
+
+    /**
+     * Reads until no data arrives within the timeout.
+     *
+     * # Returns
+     * A Result containing the received bytes or an ApplicationError if nothing was received.
+     */
+    pub fn read(&self) -> Result<Vec<u8>, ApplicationError> {
+        let mut port = self.port.borrow_mut();
+        let mut data = Vec::new();
+        let mut buffer = [0u8; 64];
+        loop {
+            match port.read(&mut buffer) {
+                Ok(0) => break,
+                Ok(length) => data.extend_from_slice(&buffer[..length]),
+                Err(e) if e.kind() == ErrorKind::TimedOut => break,
+                Err(e) => {
+                    return Err(ApplicationError::SerialPort(format!("Failed to read from {}", self.path), e.into()))
+                }
+            }
+        }
+        if data.is_empty() {
+            return Err(ApplicationError::Timeout(format!("No response from {}", self.path)));
+        }
+        debug!("Read from {}: {}", self.path, hex_dump(&data));
+        capture::read(&self.path, &data);
+        Ok(data)
+    }
+
+    /**
+     * Gets the USB descriptor strings of the adapter of the port. Links such as
+     * /dev/serial/by-id/... are resolved to the port.
+     *
+     * # Returns
+     * The device info, or None if the port is not a USB adapter.
+     */
+    pub fn device_info(&self) -> Option<DeviceInfo> {
+        let path = std::fs::canonicalize(&self.path).unwrap_or_else(|_| self.path.clone().into());
+        let port = serialport::available_ports()
+            .ok()?
+            .into_iter()
+            .find(|port| std::fs::canonicalize(&port.port_name).is_ok_and(|port_path| port_path == path))?;
+        match port.port_type {
+            serialport::SerialPortType::UsbPort(usb) => Some(DeviceInfo {
+                manufacturer: usb.manufacturer,
+                product: usb.product,
+                serial_number: usb.serial_number,
+            }),
+            _ => None,
+        }
+    }
+}
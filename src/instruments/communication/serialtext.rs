@@ -0,0 +1,164 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+
+use crate::{
+    arguments::{Args, Reader},
+    error::ApplicationError,
+    instruments::{
+        communication::{
+            common::{get_scpi_options, get_uart_config, Communication, DeviceInfo},
+            registry::Requirement,
+            scpi::{scpi_reading, ScpiOptions},
+            serial::SerialLine,
+        },
+        reading::Reading,
+    },
+};
+
+/**
+ * Read timeout of the serial port while waiting for the bytes of a line.
+ */
+const SERIAL_TEXT_READ_TIMEOUT: Duration = Duration::from_millis(50);
+
+/**
+ * Gets the first complete line of the data.
+ *
+ * # Arguments
+ * `data` - The received data.
+ * `skip_partial` - Whether the data starts in the middle of a line, which is skipped.
+ *
+ * # Returns
+ * The line without its line feed, or None if no line is complete.
+ */
+fn complete_line(data: &[u8], skip_partial: bool) -> Option<&[u8]> {
+    let start = match skip_partial {
+        true => data.iter().position(|byte| *byte == b'\n')? + 1,
+        false => 0,
+    };
+    let mut lines = data[start..].split_inclusive(|byte| *byte == b'\n');
+    lines.find(|line| line.ends_with(b"\n") && !line.trim_ascii().is_empty()).map(|line| &line[..line.len() - 1])
+}
+
+/**
+ * Module for microcontrollers, e.g. Arduino or ESP32 boards, printing lines of text on a serial
+ * port. Lines are interpreted with --reader, the KeyValueReader by default.
+ */
+pub struct SerialText {
+    /**
+     * Serial port of the board.
+     */
+    line: SerialLine,
+    /**
+     * The reader, terminator and read timeout of the text commands.
+     */
+    options: ScpiOptions,
+}
+
+impl SerialText {
+    /**
+     * Creates a new instance of SerialText.
+     *
+     * # Arguments
+     * `line` - The opened serial port.
+     * `options` - The options of the text commands.
+     *
+     * # Returns
+     * A new SerialText instance.
+     */
+    pub fn new(line: SerialLine, options: ScpiOptions) -> Self {
+        Self { line, options }
+    }
+
+    /**
+     * Reads a line from the board.
+     *
+     * # Arguments
+     * `next` - Whether input received before is discarded and the next complete line is read,
+     * rather than the response to a command just sent.
+     *
+     * # Returns
+     * A Result containing the line or an ApplicationError if no line is complete within the
+     * read timeout.
+     */
+    fn read_line(&self, next: bool) -> Result<Vec<u8>, ApplicationError> {
+        let complete = |data: &[u8]| complete_line(data, next).is_some();
+        let data = match next {
+            true => self.line.read_until(complete, self.options.read_timeout)?,
+            false => self.line.read_response(complete, self.options.read_timeout)?,
+        };
+        Ok(complete_line(&data, next).map(<[u8]>::to_vec).unwrap_or_default())
+    }
+}
+
+#[async_trait(?Send)]
+impl Communication for SerialText {
+    /**
+     * Reads the next printed line for each Measure command. Other commands are sent as text with
+     * the terminator and, if they are queries, the response line is read.
+     *
+     * # Arguments
+     * `commands` - The commands to execute.
+     *
+     * # Returns
+     * A Result containing an optional vector of Reading trait objects or an ApplicationError.
+     */
+    async fn command(
+        &self,
+        commands: Vec<String>,
+    ) -> Result<Option<Vec<Box<dyn Reading>>>, ApplicationError> {
+        let mut readings: Vec<Box<dyn Reading>> = Vec::new();
+        for command in commands {
+            if command == "Measure" {
+                readings.push(scpi_reading(&self.options, self.read_line(true)?)?);
+                continue;
+            }
+            self.line.write(&[command.as_bytes(), &self.options.terminator].concat())?;
+            if self.options.expect_response.unwrap_or(command.contains('?')) {
+                readings.push(scpi_reading(&self.options, self.read_line(false)?)?);
+            }
+        }
+        Ok(Some(readings))
+    }
+
+    /**
+     * Returns the USB descriptor strings of the serial adapter.
+     *
+     * # Returns
+     * The device info, or None if the port is not a USB adapter.
+     */
+    fn device_info(&self) -> Option<DeviceInfo> {
+        self.line.device_info()
+    }
+}
+
+/**
+ * Opens the board on the serial port of the arguments.
+ *
+ * # Arguments
+ * `args` - An Args struct containing the configuration for the desired device.
+ *
+ * # Returns
+ * A Result containing a boxed Communication trait object or an ApplicationError.
+ */
+pub(crate) async fn open(args: &Args) -> Result<Box<dyn Communication>, ApplicationError> {
+    let line = SerialLine::open(Requirement::Serial.argument(args)?, &get_uart_config(args), SERIAL_TEXT_READ_TIMEOUT)?;
+    let options = ScpiOptions {
+        reader: args.readings.reader.clone().unwrap_or(Reader::KeyValueReader),
+        ..get_scpi_options(args)
+    };
+    Ok(Box::new(SerialText::new(line, options)))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_complete_line() {
+        assert_eq!(complete_line(b"mp=23.5\ntemp=23.6\r\ntemp=", true), Some(&b"temp=23.6\r"[..]));
+        assert_eq!(complete_line(b"mp=23.5\ntemp=23.6", true), None);
+        assert_eq!(complete_line(b"\r\nOK\n", false), Some(&b"OK"[..]));
+        assert_eq!(complete_line(b"OK", false), None);
+    }
+}
@@ -0,0 +1,124 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+
+use crate::{
+    arguments::{Args, Parity},
+    error::ApplicationError,
+    instruments::{
+        communication::{
+            common::{Communication, DeviceInfo},
+            registry::Requirement,
+            serial::{SerialLine, UartConfig},
+        },
+        reading::{Reading, Tc66Reading, TC66_FRAME_LENGTH},
+    },
+};
+
+/**
+ * Time to wait for a complete poll frame.
+ */
+const TC66_FRAME_TIMEOUT: Duration = Duration::from_secs(2);
+
+/**
+ * Read timeout of the serial port while waiting for frame bytes.
+ */
+pub const TC66_READ_TIMEOUT: Duration = Duration::from_millis(100);
+
+/**
+ * Returns the fixed serial settings of the TC66C, 115200 baud 8N1.
+ *
+ * # Returns
+ * The serial line settings.
+ */
+pub fn tc66_uart_config() -> UartConfig {
+    UartConfig {
+        baud_rate: 115200,
+        data_bits: 8,
+        parity: Parity::None,
+        stop_bits: 1,
+    }
+}
+
+/**
+ * Module for RDTech TC66C USB testers on their USB serial port. Each Measure polls a frame
+ * with `getva`.
+ */
+pub struct Tc66Serial {
+    /**
+     * Serial port of the tester.
+     */
+    line: SerialLine,
+}
+
+impl Tc66Serial {
+    /**
+     * Creates a new instance of Tc66Serial.
+     *
+     * # Arguments
+     * `line` - The opened serial port.
+     *
+     * # Returns
+     * A new Tc66Serial instance.
+     */
+    pub fn new(line: SerialLine) -> Self {
+        Self { line }
+    }
+}
+
+#[async_trait(?Send)]
+impl Communication for Tc66Serial {
+    /**
+     * Polls a frame for each Measure command.
+     *
+     * # Arguments
+     * `commands` - The commands to execute, only Measure is supported.
+     *
+     * # Returns
+     * A Result containing an optional vector of Reading trait objects or an ApplicationError.
+     */
+    async fn command(
+        &self,
+        commands: Vec<String>,
+    ) -> Result<Option<Vec<Box<dyn Reading>>>, ApplicationError> {
+        let mut readings: Vec<Box<dyn Reading>> = Vec::new();
+        for command in commands {
+            if command != "Measure" {
+                return Err(ApplicationError::Command(format!(
+                    "Unsupported command for TC66 testers: {}",
+                    command
+                )));
+            }
+            self.line.write(b"getva")?;
+            let frame = self
+                .line
+                .read_response(|data| data.len() >= TC66_FRAME_LENGTH, TC66_FRAME_TIMEOUT)?;
+            readings.push(Box::new(Tc66Reading::parse(&frame[..TC66_FRAME_LENGTH])?));
+        }
+        Ok(Some(readings))
+    }
+
+    /**
+     * Returns the USB descriptor strings of the serial adapter.
+     *
+     * # Returns
+     * The device info, or None if the port is not a USB adapter.
+     */
+    fn device_info(&self) -> Option<DeviceInfo> {
+        self.line.device_info()
+    }
+}
+
+/**
+ * Opens the meter on the serial port of the arguments, with the fixed line settings of the meter.
+ *
+ * # Arguments
+ * `args` - An Args struct containing the configuration for the desired device.
+ *
+ * # Returns
+ * A Result containing a boxed Communication trait object or an ApplicationError.
+ */
+pub(crate) async fn open(args: &Args) -> Result<Box<dyn Communication>, ApplicationError> {
+    let line = SerialLine::open(Requirement::Serial.argument(args)?, &tc66_uart_config(), TC66_READ_TIMEOUT)?;
+    Ok(Box::new(Tc66Serial::new(line)))
+}
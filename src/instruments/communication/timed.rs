@@ -0,0 +1,139 @@
+use std::time::Instant;
+
+use async_trait::async_trait;
+
+use crate::{
+    error::ApplicationError,
+    instruments::{
+        communication::common::{Communication, DeviceInfo},
+        reading::{Reading, TimedReading},
+    },
+};
+
+/**
+ * Wraps a device so that the readings are tagged with the command they answer and the time the
+ * command took. The commands are sent one at a time, so every round trip covers one command,
+ * including the time to open the device; the transfer timings of the device separate the time
+ * spent by the instrument from the overhead of the tool.
+ */
+pub struct TimedCommunication {
+    /**
+     * The wrapped device.
+     */
+    device: Box<dyn Communication>,
+}
+
+impl TimedCommunication {
+    /**
+     * Creates a new instance of TimedCommunication.
+     *
+     * # Arguments
+     * `device` - The device to wrap.
+     *
+     * # Returns
+     * A new TimedCommunication instance.
+     */
+    pub fn new(device: Box<dyn Communication>) -> Self {
+        Self { device }
+    }
+}
+
+#[async_trait(?Send)]
+impl Communication for TimedCommunication {
+    /**
+     * Sends the commands one at a time and tags their readings with the timings.
+     *
+     * # Arguments
+     * `commands` - The commands to execute.
+     *
+     * # Returns
+     * A Result containing an optional vector of Reading trait objects or an ApplicationError.
+     */
+    async fn command(
+        &self,
+        commands: Vec<String>,
+    ) -> Result<Option<Vec<Box<dyn Reading>>>, ApplicationError> {
+        let mut records: Vec<Box<dyn Reading>> = Vec::new();
+        for command in commands {
+            let start = Instant::now();
+            let readings = self.device.command(vec![command.clone()]).await?.unwrap_or_default();
+            let latency = start.elapsed();
+            let timings = self.device.transfer_timings().unwrap_or_default();
+            records.extend(readings.into_iter().map(|reading| {
+                Box::new(TimedReading::new(command.clone(), latency, timings.write, timings.first_byte, reading))
+                    as Box<dyn Reading>
+            }));
+        }
+        Ok(Some(records))
+    }
+
+    /**
+     * Returns the device info of the wrapped device.
+     *
+     * # Returns
+     * The device info, or None.
+     */
+    fn device_info(&self) -> Option<DeviceInfo> {
+        self.device.device_info()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::instruments::{communication::common::TransferTimings, reading::ScpiRawReading};
+
+    /**
+     * Device answering queries ending with `?` with the query itself after a delay.
+     */
+    struct SlowEcho;
+
+    #[async_trait(?Send)]
+    impl Communication for SlowEcho {
+        async fn command(
+            &self,
+            commands: Vec<String>,
+        ) -> Result<Option<Vec<Box<dyn Reading>>>, ApplicationError> {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+            Ok(Some(
+                commands
+                    .into_iter()
+                    .filter(|command| command.ends_with('?'))
+                    .map(|command| Box::new(ScpiRawReading::new(command.into_bytes())) as Box<dyn Reading>)
+                    .collect(),
+            ))
+        }
+
+        fn transfer_timings(&self) -> Option<TransferTimings> {
+            Some(TransferTimings {
+                write: Some(Duration::from_millis(1)),
+                first_byte: None,
+            })
+        }
+
+        fn device_info(&self) -> Option<DeviceInfo> {
+            Some(DeviceInfo {
+                serial_number: Some("1234".into()),
+                ..Default::default()
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_timed() {
+        let device = TimedCommunication::new(Box::new(SlowEcho));
+        let readings = device
+            .command(vec!["VOLT 1".into(), "MEAS:VOLT?".into()])
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(readings.len(), 1);
+        let fields = readings[0].get_csv().unwrap();
+        assert_eq!(fields[0], "MEAS:VOLT?");
+        assert!(fields[1].parse::<f64>().unwrap() >= 5.0);
+        assert_eq!(fields[2..], ["1.000", "", "MEAS:VOLT?"]);
+        assert_eq!(device.device_info().unwrap().serial_number.as_deref(), Some("1234"));
+    }
+}
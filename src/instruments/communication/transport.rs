@@ -0,0 +1,463 @@
+#[cfg(feature = "usb")]
+use std::time::Duration;
+
+#[cfg(feature = "usb")]
+use async_trait::async_trait;
+#[cfg(feature = "hid")]
+use hidapi::HidResult;
+#[cfg(feature = "usb")]
+use nusb::{
+    transfer::{Buffer, Bulk, In, Interrupt, Out, TransferError},
+    Endpoint,
+};
+
+#[cfg(feature = "hid")]
+use crate::instruments::communication::common::{hid_device_info, DeviceInfo};
+
+/**
+ * The reports of an open HID device. Implemented by hidapi devices, and by scripted devices in
+ * the tests so the protocols run without hardware.
+ */
+#[cfg(feature = "hid")]
+pub(crate) trait HidPort {
+    /**
+     * Writes an output report.
+     *
+     * # Arguments
+     * `data` - The report, starting with the report id.
+     *
+     * # Returns
+     * A HidResult containing the number of bytes written.
+     */
+    fn write(&self, data: &[u8]) -> HidResult<usize>;
+
+    /**
+     * Reads an input report.
+     *
+     * # Arguments
+     * `buf` - The buffer the report is read into.
+     * `timeout_ms` - The time to wait in milliseconds, -1 to wait until a report arrives.
+     *
+     * # Returns
+     * A HidResult containing the length of the report, 0 if none arrived in time.
+     */
+    fn read_timeout(&self, buf: &mut [u8], timeout_ms: i32) -> HidResult<usize>;
+
+    /**
+     * Sends a feature report.
+     *
+     * # Arguments
+     * `data` - The report, starting with the report id.
+     *
+     * # Returns
+     * A HidResult indicating success.
+     */
+    fn send_feature_report(&self, data: &[u8]) -> HidResult<()>;
+
+    /**
+     * Gets the USB descriptor strings of the device.
+     *
+     * # Returns
+     * The device info, or None.
+     */
+    fn device_info(&self) -> Option<DeviceInfo>;
+}
+
+#[cfg(feature = "hid")]
+impl HidPort for hidapi::HidDevice {
+    fn write(&self, data: &[u8]) -> HidResult<usize> {
+        hidapi::HidDevice::write(self, data)
+    }
+
+    fn read_timeout(&self, buf: &mut [u8], timeout_ms: i32) -> HidResult<usize> {
+        hidapi::HidDevice::read_timeout(self, buf, timeout_ms)
+    }
+
+    fn send_feature_report(&self, data: &[u8]) -> HidResult<()> {
+        hidapi::HidDevice::send_feature_report(self, data)
+    }
+
+    fn device_info(&self) -> Option<DeviceInfo> {
+        hid_device_info(self)
+    }
+}
+
+/**
+ * The pipes of a USB instrument: Bulk OUT for commands, Bulk IN for responses and optionally
+ * the USB488 Interrupt IN endpoint for service requests. Implemented by the nusb endpoints,
+ * and by scripted pipes in the tests so the SCPI flow runs without hardware.
+ */
+#[cfg(feature = "usb")]
+#[async_trait(?Send)]
+pub(crate) trait BulkPipes {
+    /**
+     * Gets the maximum packet size of the Bulk IN endpoint.
+     *
+     * # Returns
+     * The packet size in bytes.
+     */
+    fn max_packet_size(&self) -> usize;
+
+    /**
+     * Writes one Bulk OUT transfer and waits for it to complete.
+     *
+     * # Arguments
+     * `data` - The bytes to send.
+     *
+     * # Returns
+     * A Result indicating success or the TransferError.
+     */
+    async fn write(&mut self, data: Vec<u8>) -> Result<(), TransferError>;
+
+    /**
+     * Reads one Bulk IN transfer. A transfer that times out is cancelled and what arrived
+     * before is kept.
+     *
+     * # Arguments
+     * `data` - The response so far, extended with the received bytes.
+     * `length` - The size of the transfer.
+     * `timeout` - The time to wait for the transfer.
+     *
+     * # Returns
+     * A Result containing the number of received bytes, 0 if nothing arrived in time, or the
+     * TransferError.
+     */
+    async fn read(&mut self, data: &mut Vec<u8>, length: usize, timeout: Duration) -> Result<usize, TransferError>;
+
+    /**
+     * Checks if the Interrupt IN endpoint is open.
+     *
+     * # Returns
+     * A boolean indicating whether service requests can be read.
+     */
+    fn has_interrupt(&self) -> bool;
+
+    /**
+     * Reads one packet from the Interrupt IN endpoint, waiting until one arrives.
+     *
+     * # Returns
+     * A Result containing the packet or the TransferError.
+     */
+    async fn read_interrupt(&mut self) -> Result<Vec<u8>, TransferError>;
+
+    /**
+     * Cancels a pending read of the Interrupt IN endpoint.
+     */
+    fn cancel_interrupt(&mut self);
+}
+
+/**
+ * The nusb endpoints of a claimed USB interface.
+ */
+#[cfg(feature = "usb")]
+pub(crate) struct NusbPipes {
+    /**
+     * USB Bulk OUT endpoint.
+     */
+    pub endpoint_out: Endpoint<Bulk, Out>,
+    /**
+     * USB Bulk IN endpoint.
+     */
+    pub endpoint_in: Endpoint<Bulk, In>,
+    /**
+     * USB488 Interrupt IN endpoint, if configured.
+     */
+    pub endpoint_interrupt: Option<Endpoint<Interrupt, In>>,
+    /**
+     * Buffer of the last completed Bulk IN transfer, submitted again by the next one.
+     */
+    pub in_buffer: Option<Buffer>,
+}
+
+#[cfg(feature = "usb")]
+#[async_trait(?Send)]
+impl BulkPipes for NusbPipes {
+    fn max_packet_size(&self) -> usize {
+        self.endpoint_in.max_packet_size()
+    }
+
+    async fn write(&mut self, data: Vec<u8>) -> Result<(), TransferError> {
+        self.endpoint_out.submit(Buffer::from(data));
+        self.endpoint_out.next_complete().await.status
+    }
+
+    async fn read(&mut self, data: &mut Vec<u8>, length: usize, timeout: Duration) -> Result<usize, TransferError> {
+        let mut buffer = self.in_buffer.take().unwrap_or_else(|| Buffer::new(length));
+        buffer.clear();
+        buffer.set_requested_len(length);
+        self.endpoint_in.submit(buffer);
+        let (completion, timed_out) = match tokio::time::timeout(timeout, self.endpoint_in.next_complete()).await {
+            Ok(completion) => (completion, false),
+            Err(_) => {
+                // Keep whatever arrived before the transfer was cancelled
+                self.endpoint_in.cancel_all();
+                (self.endpoint_in.next_complete().await, true)
+            }
+        };
+        let received = completion.buffer.len();
+        data.extend_from_slice(&completion.buffer);
+        self.in_buffer = Some(completion.buffer);
+        match timed_out {
+            true => Ok(received),
+            false => completion.status.map(|_| received),
+        }
+    }
+
+    fn has_interrupt(&self) -> bool {
+        self.endpoint_interrupt.is_some()
+    }
+
+    async fn read_interrupt(&mut self) -> Result<Vec<u8>, TransferError> {
+        let Some(endpoint) = self.endpoint_interrupt.as_mut() else {
+            return Err(TransferError::InvalidArgument);
+        };
+        endpoint.submit(Buffer::new(endpoint.max_packet_size()));
+        let completion = endpoint.next_complete().await;
+        completion.status.map(|_| completion.buffer.to_vec())
+    }
+
+    fn cancel_interrupt(&mut self) {
+        if let Some(endpoint) = self.endpoint_interrupt.as_mut() {
+            endpoint.cancel_all();
+        }
+    }
+}
+
+/**
+ * Scripted devices replaying injected byte streams, and a loopback server, for testing the
+ * protocols and the device handling without hardware.
+ */
+#[cfg(test)]
+pub(crate) mod scripted {
+    #[cfg(any(feature = "usb", feature = "hid"))]
+    use std::{cell::RefCell, collections::VecDeque, rc::Rc};
+    #[cfg(all(feature = "network", feature = "modbus"))]
+    use std::{
+        io::{Read, Write},
+        net::TcpListener,
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+        thread,
+    };
+    #[cfg(feature = "usb")]
+    use std::time::Duration;
+
+    #[cfg(feature = "usb")]
+    use async_trait::async_trait;
+    #[cfg(feature = "hid")]
+    use hidapi::{HidError, HidResult};
+    #[cfg(feature = "usb")]
+    use nusb::transfer::TransferError;
+
+    #[cfg(feature = "usb")]
+    use super::BulkPipes;
+    #[cfg(feature = "hid")]
+    use super::HidPort;
+    #[cfg(all(feature = "network", feature = "modbus"))]
+    use clap::Parser;
+
+    #[cfg(all(feature = "network", feature = "modbus"))]
+    use crate::arguments::Args;
+    #[cfg(feature = "hid")]
+    use crate::instruments::communication::common::DeviceInfo;
+
+    /**
+     * The bytes written to a scripted device, shared with the test after the device is handed
+     * over.
+     */
+    #[cfg(any(feature = "usb", feature = "hid"))]
+    pub type Written = Rc<RefCell<Vec<Vec<u8>>>>;
+
+    /**
+     * A HID device answering with the given input reports in order. Reads time out once the
+     * reports are used up, and blocking reads fail.
+     */
+    #[cfg(feature = "hid")]
+    pub struct ScriptedHid {
+        pub written: Written,
+        reports: RefCell<VecDeque<Vec<u8>>>,
+    }
+
+    #[cfg(feature = "hid")]
+    impl ScriptedHid {
+        pub fn new(reports: Vec<Vec<u8>>) -> Self {
+            Self {
+                written: Written::default(),
+                reports: RefCell::new(reports.into()),
+            }
+        }
+    }
+
+    #[cfg(feature = "hid")]
+    impl HidPort for ScriptedHid {
+        fn write(&self, data: &[u8]) -> HidResult<usize> {
+            self.written.borrow_mut().push(data.to_vec());
+            Ok(data.len())
+        }
+
+        fn read_timeout(&self, buf: &mut [u8], timeout_ms: i32) -> HidResult<usize> {
+            match (self.reports.borrow_mut().pop_front(), timeout_ms) {
+                (Some(report), _) => {
+                    let length = report.len().min(buf.len());
+                    buf[..length].copy_from_slice(&report[..length]);
+                    Ok(length)
+                }
+                (None, -1) => Err(HidError::HidApiError {
+                    message: "No more scripted reports".into(),
+                }),
+                (None, _) => Ok(0),
+            }
+        }
+
+        fn send_feature_report(&self, data: &[u8]) -> HidResult<()> {
+            self.written.borrow_mut().push(data.to_vec());
+            Ok(())
+        }
+
+        fn device_info(&self) -> Option<DeviceInfo> {
+            None
+        }
+    }
+
+    /**
+     * USB pipes answering with the given Bulk IN transfers and interrupt packets in order. Reads
+     * time out once the transfers are used up, and interrupt reads wait forever.
+     */
+    #[cfg(feature = "usb")]
+    pub struct ScriptedPipes {
+        pub written: Written,
+        /**
+         * Timeouts of the Bulk IN reads, in order.
+         */
+        pub read_timeouts: Rc<RefCell<Vec<Duration>>>,
+        transfers: VecDeque<Result<Vec<u8>, TransferError>>,
+        interrupts: Option<VecDeque<Vec<u8>>>,
+    }
+
+    #[cfg(feature = "usb")]
+    impl ScriptedPipes {
+        pub fn new(transfers: Vec<Result<Vec<u8>, TransferError>>) -> Self {
+            Self {
+                written: Written::default(),
+                read_timeouts: Rc::default(),
+                transfers: transfers.into(),
+                interrupts: None,
+            }
+        }
+
+        pub fn with_interrupts(mut self, packets: Vec<Vec<u8>>) -> Self {
+            self.interrupts = Some(packets.into());
+            self
+        }
+    }
+
+    #[cfg(feature = "usb")]
+    #[async_trait(?Send)]
+    impl BulkPipes for ScriptedPipes {
+        fn max_packet_size(&self) -> usize {
+            64
+        }
+
+        async fn write(&mut self, data: Vec<u8>) -> Result<(), TransferError> {
+            self.written.borrow_mut().push(data);
+            Ok(())
+        }
+
+        async fn read(&mut self, data: &mut Vec<u8>, length: usize, timeout: Duration) -> Result<usize, TransferError> {
+            self.read_timeouts.borrow_mut().push(timeout);
+            let transfer = self.transfers.pop_front().unwrap_or_else(|| Ok(Vec::new()))?;
+            let received = transfer.len().min(length);
+            data.extend_from_slice(&transfer[..received]);
+            Ok(received)
+        }
+
+        fn has_interrupt(&self) -> bool {
+            self.interrupts.is_some()
+        }
+
+        async fn read_interrupt(&mut self) -> Result<Vec<u8>, TransferError> {
+            match self.interrupts.as_mut().and_then(VecDeque::pop_front) {
+                Some(packet) => Ok(packet),
+                None => std::future::pending().await,
+            }
+        }
+
+        fn cancel_interrupt(&mut self) {}
+    }
+    /**
+     * A Modbus TCP server on the loopback interface answering every register read with zeros. It
+     * counts the connections and requests, so the tests can tell how often a device was opened
+     * and how many commands reached it.
+     */
+    #[cfg(all(feature = "network", feature = "modbus"))]
+    pub struct LoopbackModbus {
+        pub address: String,
+        connections: Arc<AtomicUsize>,
+        requests: Arc<AtomicUsize>,
+    }
+
+    #[cfg(all(feature = "network", feature = "modbus"))]
+    impl LoopbackModbus {
+        pub fn start() -> Self {
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            let address = listener.local_addr().unwrap().to_string();
+            let connections = Arc::new(AtomicUsize::new(0));
+            let requests = Arc::new(AtomicUsize::new(0));
+            let (accepted, served) = (connections.clone(), requests.clone());
+            thread::spawn(move || {
+                for mut stream in listener.incoming().map_while(Result::ok) {
+                    accepted.fetch_add(1, Ordering::SeqCst);
+                    let served = served.clone();
+                    thread::spawn(move || {
+                        // MBAP header, function, first register and register count
+                        let mut request = [0u8; 12];
+                        while stream.read_exact(&mut request).is_ok() {
+                            served.fetch_add(1, Ordering::SeqCst);
+                            let bytes = 2 * u16::from_be_bytes([request[10], request[11]]);
+                            let mut response = vec![request[0], request[1], 0, 0];
+                            response.extend_from_slice(&(bytes + 3).to_be_bytes());
+                            response.extend_from_slice(&[request[6], request[7], bytes as u8]);
+                            response.resize(response.len() + bytes as usize, 0);
+                            if stream.write_all(&response).is_err() {
+                                break;
+                            }
+                        }
+                    });
+                }
+            });
+            Self {
+                address,
+                connections,
+                requests,
+            }
+        }
+
+        pub fn connections(&self) -> usize {
+            self.connections.load(Ordering::SeqCst)
+        }
+
+        pub fn requests(&self) -> usize {
+            self.requests.load(Ordering::SeqCst)
+        }
+
+        /**
+         * Returns the arguments of a Modbus TCP device connected to the server, with a `voltage`
+         * register at address 0.
+         *
+         * # Arguments
+         * `options` - The options following the device and address.
+         *
+         * # Returns
+         * The parsed arguments.
+         */
+        pub fn args(&self, options: &[&str]) -> Args {
+            let tcp = format!("--tcp={}", self.address);
+            let mut args =
+                Args::parse_from([&["hardware-measurement", "--device=modbus-tcp", tcp.as_str()], options].concat());
+            args.registers = vec![toml::from_str("name = \"voltage\"\naddress = 0").unwrap()];
+            args
+        }
+    }
+}
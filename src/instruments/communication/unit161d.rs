@@ -1,11 +1,18 @@
-use std::ffi::CString;
+use std::{
+    ffi::CString,
+    time::{Duration, Instant},
+};
 
 use async_trait::async_trait;
+use tracing::{debug, info, trace};
 
 use crate::{
+    arguments::Args,
+    capture,
     error::ApplicationError,
+    logging::hex_dump,
     instruments::{
-        command::Uni161dCommand, communication::common::Communication, reading::{Reading, Unit161dReading}
+        command::{indicate::INDICATE_INTERVAL, Indicate, MinMaxSession, Uni161dCommand}, communication::{common::{DeviceInfo, get_hid_feature_reports, Communication}, registry::Requirement, transport::HidPort, usbselector::UsbSelector}, reading::{MinMaxReading, Reading, Unit161dReading}
     },
 };
 
@@ -14,28 +21,258 @@ use crate::{
  */
 const SEQUENCE_SEND_CMD: [u8; 3] = [0xAB, 0xCD, 0x03];
 
+/**
+ * Size of the HID reports of the meter cable.
+ */
+const HID_REPORT_SIZE: usize = 64;
+
+/**
+ * Time between the measurements of a min/max session.
+ */
+const MIN_MAX_SAMPLE_INTERVAL: Duration = Duration::from_millis(200);
+
+/**
+ * Read timeout in record mode, after which the next cycle is started.
+ */
+const RECORD_READ_TIMEOUT_MS: i32 = 1000;
+
+/**
+ * State machine decoding AB CD framed responses: header, length, payload and a 16 bit sum of
+ * the frame.
+ */
+struct FrameDecoder {
+    state: u8,
+    buf: Vec<u8>,
+    index: usize,
+    sum: u32,
+    /**
+     * Whether a bad checksum or header waits for the next header instead of failing.
+     */
+    resync: bool,
+}
+
+impl FrameDecoder {
+    /**
+     * Creates a new FrameDecoder waiting for a header.
+     *
+     * # Arguments
+     * `resync` - Whether a bad frame waits for the next header instead of failing.
+     *
+     * # Returns
+     * A new FrameDecoder instance.
+     */
+    fn new(resync: bool) -> Self {
+        Self {
+            state: 0,
+            buf: Vec::new(),
+            index: 0,
+            sum: 0,
+            resync,
+        }
+    }
+
+    /**
+     * Feeds a received byte to the decoder. When resynchronizing, a byte that does not fit the
+     * frame waits for the next header instead of failing.
+     *
+     * # Arguments
+     * `b` - The byte.
+     *
+     * # Returns
+     * A Result containing the payload without the checksum once a frame is complete, None
+     * before that, or an ApplicationError for a bad frame unless resynchronizing.
+     */
+    fn feed(&mut self, b: u8) -> Result<Option<Vec<u8>>, ApplicationError> {
+        match self.step(b) {
+            Err(_) if self.resync => {
+                debug!("Unexpected byte 0x{:02X} in state {}, waiting for the next header", b, self.state);
+                self.state = if b == 0xAB { 1 } else { 0 };
+                self.sum = b as u32;
+                Ok(None)
+            }
+            result => result,
+        }
+    }
+
+    /**
+     * Advances the state machine by a received byte.
+     *
+     * # Arguments
+     * `b` - The byte.
+     *
+     * # Returns
+     * A Result containing the payload without the checksum once a frame is complete, None
+     * before that, or an ApplicationError for a byte that does not fit the frame.
+     */
+    fn step(&mut self, b: u8) -> Result<Option<Vec<u8>>, ApplicationError> {
+        if self.state < 3 || self.index + 2 < self.buf.len() {
+            self.sum += b as u32;
+        }
+        match self.state {
+            0 => {
+                if b == 0xAB {
+                    self.sum = b as u32;
+                    self.state = 1;
+                }
+            }
+            1 => {
+                if b == 0xCD {
+                    self.state = 2;
+                } else {
+                    return Err(ApplicationError::Hid(format!(
+                        "Unexpected byte 0x{:02X} in state {}",
+                        b, self.state
+                    )));
+                }
+            }
+            // The length includes the two checksum bytes
+            2 if b >= 2 => {
+                self.buf.clear();
+                self.buf.resize(b as usize, 0);
+                self.index = 0;
+                self.state = 3;
+            }
+            3 => {
+                self.buf[self.index] = b;
+                self.index += 1;
+                if self.index == self.buf.len() {
+                    let length = self.buf.len() - 2;
+                    let received_sum = ((self.buf[length] as u16) << 8) + (self.buf[length + 1] as u16);
+                    if self.sum != received_sum as u32 {
+                        debug!("Checksum mismatch: computed {:#06x}, received {:#06x}", self.sum, received_sum);
+                        if self.resync {
+                            self.state = 0;
+                            return Ok(None);
+                        }
+                        return Err(ApplicationError::Hid("Checksum mismatch".into()));
+                    }
+                    debug!("Checksum {:#06x} ok, response {}", self.sum, hex_dump(&self.buf[..length]));
+                    self.state = 0;
+                    // Drop last 2 bytes (checksum), the frame is handed over without copying
+                    let mut frame = std::mem::take(&mut self.buf);
+                    frame.truncate(length);
+                    return Ok(Some(frame));
+                }
+            }
+            _ => {
+                return Err(ApplicationError::Hid(format!(
+                    "Unexpected byte 0x{:02X} in state {}",
+                    b, self.state
+                )));
+            }
+        }
+        Ok(None)
+    }
+}
+
+/**
+ * A HID device found by enumeration.
+ */
+#[derive(Debug, Clone, PartialEq)]
+struct HidCandidate {
+    path: String,
+    vendor_id: u16,
+    product_id: u16,
+    serial: Option<String>,
+}
+
+/**
+ * Selects the device path of the HID device with the vendor and product ID and serial number.
+ * Devices with several HID interfaces are listed once per interface, so each path counts once.
+ *
+ * # Arguments
+ * `hid` - The --hid argument, used in error messages.
+ * `selector` - The vendor and product ID and optional serial number.
+ * `candidates` - The enumerated HID devices.
+ *
+ * # Returns
+ * A Result containing the path, or an ApplicationError if no device or more than one matches.
+ */
+fn select_hid_device(hid: &str, selector: &UsbSelector, candidates: Vec<HidCandidate>) -> Result<String, ApplicationError> {
+    let mut matching: Vec<HidCandidate> = candidates
+        .into_iter()
+        .filter(|candidate| selector.matches(candidate.vendor_id, candidate.product_id, candidate.serial.as_deref(), None))
+        .collect();
+    matching.sort_by(|a, b| a.path.cmp(&b.path));
+    matching.dedup_by(|a, b| a.path == b.path);
+    match matching.len() {
+        0 => Err(ApplicationError::NotFound(format!("HID device {}", hid))),
+        1 => Ok(matching.remove(0).path),
+        count => {
+            let listed: Vec<String> = matching
+                .iter()
+                .map(|candidate| format!("{} (serial {})", candidate.path, candidate.serial.as_deref().unwrap_or("none")))
+                .collect();
+            Err(ApplicationError::Config(format!(
+                "{} HID devices match {}, add the serial number or give the path: {}",
+                count,
+                hid,
+                listed.join(", ")
+            )))
+        }
+    }
+}
+
+/**
+ * Gets the data bytes of an input report of the meter cable: the first byte is their number.
+ *
+ * # Arguments
+ * `report` - The input report.
+ *
+ * # Returns
+ * The data bytes, cut to the length of the report.
+ */
+fn report_data(report: &[u8]) -> &[u8] {
+    match report.split_first() {
+        Some((&count, data)) => &data[..data.len().min(count as usize)],
+        None => &[],
+    }
+}
+
 /**
  * Module for the Unit161d instrument using HID API.
  */
 pub struct Unit161dHid {
     // HID Device instance
-    hiddevice: hidapi::HidDevice,
+    hiddevice: Box<dyn HidPort>,
+    // Record mode: reads time out and bad frames are skipped
+    record: bool,
 }
 
 impl Unit161dHid {
     /**
-     * Creates a new instance of Unit161dHid with the given HID API.
+     * Creates a new instance of Unit161dHid with the given HID API. Devices given by vendor and
+     * product ID are found by enumerating the HID devices, device paths are opened directly.
      *
      * # Arguments
-     * `hid_device_path` - A string slice representing the path to the HID device.
+     * `hid_device` - The path to the HID device, or vendor_id:product_id[:serial] in hex.
+     * `feature_reports` - Feature reports sent to initialize the device, starting with the report id.
+     * `record` - Whether reads time out and frames with a bad checksum are skipped.
      *
      * # Returns
      * A new Unit161dHid instance.
      */
-    pub fn new(hid_device_path: &str) -> Result<Self, ApplicationError> {
-        let api = hidapi::HidApi::new().map_err(|e| {
-            ApplicationError::Hid(format!("Failed to create HID API instance: {}", e))
-        })?;
+    pub fn new(hid_device: &str, feature_reports: &[Vec<u8>], record: bool) -> Result<Self, ApplicationError> {
+        let hid_error = |e: hidapi::HidError| ApplicationError::HidApi("Failed to create HID API instance".into(), e);
+        let (api, hid_device_path) = match UsbSelector::parse(hid_device) {
+            Ok(selector @ UsbSelector::Id { .. }) => {
+                let api = hidapi::HidApi::new().map_err(hid_error)?;
+                let candidates = api
+                    .device_list()
+                    .map(|info| HidCandidate {
+                        path: info.path().to_string_lossy().into_owned(),
+                        vendor_id: info.vendor_id(),
+                        product_id: info.product_id(),
+                        serial: info.serial_number().map(|serial| serial.to_string()),
+                    })
+                    .collect();
+                let path = select_hid_device(hid_device, &selector, candidates)?;
+                info!("HID device {} found at {}", hid_device, path);
+                (api, path)
+            }
+            _ => (hidapi::HidApi::new_without_enumerate().map_err(hid_error)?, hid_device.to_string()),
+        };
+        let hid_device_path = hid_device_path.as_str();
         let c_path = CString::new(hid_device_path.to_string()).map_err(|e| {
             ApplicationError::Hid(format!(
                 "Failed to create CString for HID device path: {}",
@@ -45,13 +282,33 @@ impl Unit161dHid {
         let hiddevice = match api.open_path(&c_path) {
             Ok(dev) => dev,
             Err(e) => {
-                return Err(ApplicationError::Hid(format!(
-                    "Failed to open HID device at {}: {}",
-                    hid_device_path, e
-                )));
+                return Err(ApplicationError::HidApi(format!("Failed to open HID device at {}", hid_device_path), e));
             }
         };
-        Ok(Unit161dHid { hiddevice })
+        info!("Opened UNI-T HID device at {}", hid_device_path);
+        Self::with_port(Box::new(hiddevice), feature_reports, record)
+    }
+
+    /**
+     * Creates a new instance of Unit161dHid on an open HID device and sends the feature reports.
+     *
+     * # Arguments
+     * `hiddevice` - The open HID device.
+     * `feature_reports` - Feature reports sent to initialize the device, starting with the report id.
+     * `record` - Whether reads time out and frames with a bad checksum are skipped.
+     *
+     * # Returns
+     * A new Unit161dHid instance.
+     */
+    fn with_port(hiddevice: Box<dyn HidPort>, feature_reports: &[Vec<u8>], record: bool) -> Result<Self, ApplicationError> {
+        for report in feature_reports {
+            debug!("Sending feature report {}", hex_dump(report));
+            capture::written("hid", report);
+            hiddevice.send_feature_report(report).map_err(|e| {
+                ApplicationError::HidApi(format!("Failed to send feature report {:02X?}", report), e)
+            })?;
+        }
+        Ok(Unit161dHid { hiddevice, record })
     }
 
     /**
@@ -62,86 +319,102 @@ impl Unit161dHid {
      */
     fn write_with_length(&self, data: &[u8]) -> Result<(), ApplicationError> {
         let len = data.len();
-        let mut buf = vec![0u8; 1 + len];
+        let mut report = [0u8; HID_REPORT_SIZE + 1];
+        let buf = report.get_mut(..1 + len).ok_or_else(|| ApplicationError::Hid("Command too long for a HID report".into()))?;
         buf[0] = len as u8;
         buf[1..].copy_from_slice(data);
+        debug!("Writing to HID device: {}", hex_dump(buf));
+        capture::written("hid", buf);
         self.hiddevice
-            .write(&buf)
-            .map_err(|e| ApplicationError::Hid(format!("Failed to write to HID device: {}", e)))?;
+            .write(buf)
+            .map_err(|e| ApplicationError::HidApi("Failed to write to HID device".into(), e))?;
         Ok(())
     }
 
     /**
-     * Reads a response from the HID device using a state machine.
+     * Reads a response from the HID device. The first byte of every input report is the number
+     * of data bytes after it, so frames split across reports are reassembled. In record mode
+     * reads time out and a frame with a bad checksum is dropped, waiting for the next AB CD
+     * header instead of failing.
      * # Returns
-     * An Option containing the response bytes if successful, or None if failed.
+     * An Option containing the response bytes if successful, or None if the read timed out.
      */
     fn read_response(&self) -> Result<Option<Vec<u8>>, ApplicationError> {
-        let mut state = 0;
-        let mut buf: Vec<u8> = Vec::new();
-        let mut index: usize = 0;
-        let mut sum: u32 = 0;
+        let mut decoder = FrameDecoder::new(self.record);
         loop {
-            let mut x = [0u8; 64];
-            match self.hiddevice.read(&mut x) {
-                Ok(_) => {}
-                Err(e) => {
-                    return Err(ApplicationError::Hid(format!(
-                        "Failed to read from HID device: {}",
-                        e
-                    )));
+            let mut x = [0u8; HID_REPORT_SIZE];
+            let timeout_ms = if self.record { RECORD_READ_TIMEOUT_MS } else { -1 };
+            let length = match self.hiddevice.read_timeout(&mut x, timeout_ms) {
+                Ok(0) if self.record => {
+                    debug!("No response within {} ms", RECORD_READ_TIMEOUT_MS);
+                    return Ok(None);
                 }
-            }
-            for &b in &x[1..] {
-                if state < 3 || index + 2 < buf.len() {
-                    sum += b as u32;
+                Ok(length) => {
+                    trace!("Read from HID device: {}", hex_dump(&x[..length]));
+                    capture::read("hid", &x[..length]);
+                    length
                 }
-
-                match state {
-                    0 => {
-                        if b == 0xAB {
-                            state = 1;
-                        }
-                    }
-                    1 => {
-                        if b == 0xCD {
-                            state = 2;
-                        } else {
-                            return Err(ApplicationError::Hid(format!(
-                                "Unexpected byte 0x{:02X} in state {}",
-                                b, state
-                            )));
-                        }
-                    }
-                    2 => {
-                        buf = vec![0u8; b as usize];
-                        index = 0;
-                        state = 3;
-                    }
-                    3 => {
-                        buf[index] = b;
-                        index += 1;
-                        if index == buf.len() {
-                            let received_sum =
-                                ((buf[buf.len() - 2] as u16) << 8) + (buf[buf.len() - 1] as u16);
-                            if sum != received_sum as u32 {
-                                return Err(ApplicationError::Hid("Checksum mismatch".into()));
-                            }
-                            // Drop last 2 bytes (checksum)
-                            buf.truncate(buf.len() - 2);
-                            return Ok(Some(buf));
-                        }
-                    }
-                    _ => {
-                        return Err(ApplicationError::Hid(format!(
-                            "Unexpected byte 0x{:02X} in state {}",
-                            b, state
-                        )));
-                    }
+                Err(e) => {
+                    return Err(ApplicationError::HidApi("Failed to read from HID device".into(), e));
+                }
+            };
+            for &b in report_data(&x[..length]) {
+                if let Some(frame) = decoder.feed(b)? {
+                    return Ok(Some(frame));
                 }
             }
         }
     }
+
+    /**
+     * Sends a command and reads the response.
+     *
+     * # Arguments
+     * `command` - The command to send.
+     *
+     * # Returns
+     * A Result containing the measurement if the response is one, or an ApplicationError.
+     */
+    fn send_command(&self, command: Uni161dCommand) -> Result<Option<Unit161dReading>, ApplicationError> {
+        let mut cmd = command as u16;
+        let mut cmd_bytes = [0u8; 3];
+        cmd_bytes[0] = (cmd & 0xff) as u8;
+        cmd += 379;
+        cmd_bytes[1] = (cmd >> 8) as u8;
+        cmd_bytes[2] = (cmd & 0xff) as u8;
+        let mut seq = [0u8; SEQUENCE_SEND_CMD.len() + 3];
+        seq[..SEQUENCE_SEND_CMD.len()].copy_from_slice(&SEQUENCE_SEND_CMD);
+        seq[SEQUENCE_SEND_CMD.len()..].copy_from_slice(&cmd_bytes);
+        self.write_with_length(&seq)?;
+        Ok(self.read_response()?.and_then(Unit161dReading::parse))
+    }
+
+    /**
+     * Runs a min/max session: enables MinMax, measures for the duration of the session and
+     * disables MinMax again, also when measuring fails.
+     *
+     * # Arguments
+     * `session` - The session.
+     *
+     * # Returns
+     * A Result containing the summary of the measurements or an ApplicationError.
+     */
+    async fn min_max_session(&self, session: MinMaxSession) -> Result<MinMaxReading, ApplicationError> {
+        self.send_command(Uni161dCommand::MinMax)?;
+        let mut summary = MinMaxReading::default();
+        let start = Instant::now();
+        let mut result = Ok(());
+        while result.is_ok() && start.elapsed() < session.duration {
+            result = match self.send_command(Uni161dCommand::Measure) {
+                Ok(Some(reading)) => summary.add(&reading),
+                Ok(None) => Ok(()),
+                Err(e) => Err(e),
+            };
+            tokio::time::sleep(MIN_MAX_SAMPLE_INTERVAL).await;
+        }
+        self.send_command(Uni161dCommand::NotMinMax)?;
+        result.map(|_| summary)
+    }
 }
 
 #[async_trait(?Send)]
@@ -153,28 +426,182 @@ impl Communication for Unit161dHid {
     ) -> Result<Option<Vec<Box<dyn Reading>>>, ApplicationError> {
         let mut measurements: Vec<Box<dyn Reading>> = Vec::new();
         for command in commands {
-            let mut cmd = Uni161dCommand::try_from(command)? as u16;
-            let mut cmd_bytes = [0u8; 3];
-            cmd_bytes[0] = (cmd & 0xff) as u8;
-            cmd += 379;
-            cmd_bytes[1] = (cmd >> 8) as u8;
-            cmd_bytes[2] = (cmd & 0xff) as u8;
-            let mut seq = Vec::new();
-            seq.extend_from_slice(&SEQUENCE_SEND_CMD);
-            seq.extend_from_slice(&cmd_bytes);
-            let _ = self.write_with_length(&seq)?;
-            if let Some(parsed_measurement) = self.read_response()?.and_then(Unit161dReading::parse)
-            {
+            // Indications blink the backlight; every blink toggles it twice to restore its state
+            if let Some(indicate) = Indicate::parse(&command)? {
+                for _ in 0..indicate.signals() * 2 {
+                    self.send_command(Uni161dCommand::Lamp)?;
+                    tokio::time::sleep(INDICATE_INTERVAL / 2).await;
+                }
+                continue;
+            }
+            if let Some(session) = MinMaxSession::parse(&command)? {
+                measurements.push(Box::new(self.min_max_session(session).await?));
+                continue;
+            }
+            if let Some(parsed_measurement) = self.send_command(Uni161dCommand::try_from(command)?)? {
                 measurements.push(Box::new(parsed_measurement));
             }
         }
         Ok(Some(measurements))
 
     }
+
+    /**
+     * Returns the USB descriptor strings of the HID device.
+     *
+     * # Returns
+     * The device info.
+     */
+    fn device_info(&self) -> Option<DeviceInfo> {
+        self.hiddevice.device_info()
+    }
+}
+
+/**
+ * Opens the meter on the HID device of the arguments and sends the feature reports of the arguments.
+ *
+ * # Arguments
+ * `args` - An Args struct containing the configuration for the desired device.
+ *
+ * # Returns
+ * A Result containing a boxed Communication trait object or an ApplicationError.
+ */
+pub(crate) async fn open(args: &Args) -> Result<Box<dyn Communication>, ApplicationError> {
+    Ok(Box::new(Unit161dHid::new(
+        Requirement::Hid.argument(args)?,
+        &get_hid_feature_reports(args)?,
+        args.monitoring.record,
+    )?))
 }
 
+#[cfg(test)]
 mod test {
-    use crate::instruments::command::Uni161dCommand;
+    use super::*;
+    use crate::instruments::{command::Uni161dCommand, communication::transport::scripted::ScriptedHid};
+
+    /**
+     * Creates an enumerated HID device with the UT161D IDs.
+     */
+    fn candidate(path: &str, serial: Option<&str>) -> HidCandidate {
+        HidCandidate {
+            path: path.into(),
+            vendor_id: 0x1a86,
+            product_id: 0xe429,
+            serial: serial.map(|serial| serial.to_string()),
+        }
+    }
+
+    #[test]
+    fn test_select_hid_device() {
+        let candidates = vec![
+            candidate("/dev/hidraw3", Some("A1")),
+            candidate("/dev/hidraw3", Some("A1")),
+            candidate("/dev/hidraw6", Some("B2")),
+            HidCandidate {
+                vendor_id: 0x046d,
+                ..candidate("/dev/hidraw0", None)
+            },
+        ];
+        let selector = UsbSelector::parse("1a86:e429:B2").unwrap();
+        assert_eq!(select_hid_device("1a86:e429:B2", &selector, candidates.clone()).unwrap(), "/dev/hidraw6");
+        let selector = UsbSelector::parse("1a86:e429").unwrap();
+        assert_eq!(
+            select_hid_device("1a86:e429", &selector, candidates.clone()).unwrap_err().to_string(),
+            "Config Error: 2 HID devices match 1a86:e429, add the serial number or give the path: /dev/hidraw3 (serial A1), /dev/hidraw6 (serial B2)"
+        );
+        assert_eq!(select_hid_device("1a86:e429", &selector, candidates[..2].to_vec()).unwrap(), "/dev/hidraw3");
+        let selector = UsbSelector::parse("1a86:e429:C3").unwrap();
+        assert_eq!(select_hid_device("1a86:e429:C3", &selector, candidates).unwrap_err().exit_code(), 2);
+    }
+
+    /**
+     * Frames a payload with the header, length and sum.
+     */
+    fn frame(payload: &[u8]) -> Vec<u8> {
+        let mut frame = vec![0xAB, 0xCD, payload.len() as u8 + 2];
+        frame.extend(payload);
+        let sum: u32 = frame.iter().map(|b| *b as u32).sum();
+        frame.extend([(sum >> 8) as u8, sum as u8]);
+        frame
+    }
+
+    /**
+     * Feeds bytes to a decoder and collects the frames.
+     */
+    fn decode(decoder: &mut FrameDecoder, bytes: &[u8]) -> Result<Vec<Vec<u8>>, ApplicationError> {
+        let mut frames = Vec::new();
+        for &b in bytes {
+            frames.extend(decoder.feed(b)?);
+        }
+        Ok(frames)
+    }
+
+    #[test]
+    fn test_decode_frame() {
+        let frame = frame(&[2, b'1', b'1', b'2']);
+        assert_eq!(decode(&mut FrameDecoder::new(false), &frame).unwrap(), vec![vec![2, b'1', b'1', b'2']]);
+        let mut corrupt = frame.clone();
+        corrupt[4] ^= 1;
+        assert!(decode(&mut FrameDecoder::new(false), &corrupt).is_err());
+    }
+
+    #[test]
+    fn test_resync_after_bad_frame() {
+        let good = frame(&[3, b'0', b'5']);
+        let mut bytes = good.clone();
+        bytes[4] ^= 1;
+        bytes.extend([0xAB, 0x00, 0x17]);
+        bytes.extend(&good);
+        assert_eq!(decode(&mut FrameDecoder::new(true), &bytes).unwrap(), vec![vec![3, b'0', b'5']]);
+    }
+
+    /**
+     * Creates an input report of the meter cable with the data bytes, padded to the report size.
+     */
+    fn report(data: &[u8]) -> Vec<u8> {
+        let mut report = vec![data.len() as u8];
+        report.extend(data);
+        report.resize(HID_REPORT_SIZE, 0);
+        report
+    }
+
+    /**
+     * A measurement of 123.456 in DCV mode.
+     */
+    const MEASUREMENT: [u8; 14] = [2, 0, b'1', b'2', b'3', b'.', b'4', b'5', b'6', 5, 0, 0, 0, 0];
+
+    #[test]
+    fn test_report_data() {
+        assert_eq!(report_data(&report(&[0xAB, 0xCD])), [0xAB, 0xCD]);
+        assert_eq!(report_data(&[5, 0xAB]), [0xAB]);
+        assert!(report_data(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_measure_split_across_reports() {
+        let frame = frame(&MEASUREMENT);
+        let hid = ScriptedHid::new(vec![report(&frame[..7]), report(&frame[7..])]);
+        let written = hid.written.clone();
+        let meter = Unit161dHid::with_port(Box::new(hid), &[vec![0x41, 0x01]], false).unwrap();
+        let reading = meter.send_command(Uni161dCommand::Measure).unwrap().unwrap();
+        assert_eq!((reading.mode.as_str(), reading.decimal_value), ("DCV", Some(123.456)));
+        assert_eq!(*written.borrow(), [vec![0x41, 0x01], vec![6, 0xAB, 0xCD, 0x03, 0x5E, 0x01, 0xD9]]);
+    }
+
+    #[test]
+    fn test_measure_malformed_frames() {
+        let good = frame(&MEASUREMENT);
+        let mut corrupt = good.clone();
+        corrupt[5] ^= 1;
+        let meter = Unit161dHid::with_port(Box::new(ScriptedHid::new(vec![report(&corrupt), report(&good)])), &[], false).unwrap();
+        assert!(meter.send_command(Uni161dCommand::Measure).is_err());
+        // Record mode skips the bad frame, and a silent meter ends the cycle without a reading
+        let meter = Unit161dHid::with_port(Box::new(ScriptedHid::new(vec![report(&corrupt), report(&good)])), &[], true).unwrap();
+        assert!(meter.send_command(Uni161dCommand::Measure).unwrap().is_some());
+        assert!(meter.send_command(Uni161dCommand::Measure).unwrap().is_none());
+        let meter = Unit161dHid::with_port(Box::new(ScriptedHid::new(Vec::new())), &[], false).unwrap();
+        assert!(matches!(meter.send_command(Uni161dCommand::Measure), Err(ApplicationError::HidApi(..))));
+    }
 
     #[test]
     fn test_try_from_command() {
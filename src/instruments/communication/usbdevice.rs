@@ -0,0 +1,391 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use nusb::{list_devices, Device, DeviceInfo, MaybeFuture};
+use tracing::{debug, info};
+
+use crate::{error::ApplicationError, instruments::communication::usbselector::UsbSelector};
+
+impl UsbSelector {
+    /**
+     * Returns the key of the device in the device cache, e.g. `1ab1:04ce:DS1ZA000000001`.
+     *
+     * # Returns
+     * The key.
+     */
+    fn key(&self) -> String {
+        match self {
+            UsbSelector::Node(path) => path.display().to_string(),
+            UsbSelector::Id {
+                vendor_id,
+                product_id,
+                serial,
+                location,
+            } => {
+                let mut key = format!("{:04x}:{:04x}:{}", vendor_id, product_id, serial.as_deref().unwrap_or_default());
+                if let Some((bus, address)) = location {
+                    key.push_str(&format!("@{}/{}", bus, address));
+                }
+                key
+            }
+        }
+    }
+}
+
+/**
+ * A USB device found by enumeration.
+ */
+#[derive(Debug, Clone, PartialEq)]
+struct UsbCandidate {
+    vendor_id: u16,
+    product_id: u16,
+    serial: Option<String>,
+    /**
+     * Bus number and device address, if the bus number is numeric.
+     */
+    location: Option<(u8, u8)>,
+}
+
+impl UsbCandidate {
+    /**
+     * Gets the IDs, serial number and bus address of an enumerated device.
+     *
+     * # Arguments
+     * `device` - The device.
+     *
+     * # Returns
+     * A new UsbCandidate instance.
+     */
+    fn new(device: &DeviceInfo) -> Self {
+        Self {
+            vendor_id: device.vendor_id(),
+            product_id: device.product_id(),
+            serial: device.serial_number().map(|serial| serial.to_string()),
+            location: device.bus_id().parse::<u8>().ok().map(|bus| (bus, device.device_address())),
+        }
+    }
+
+    /**
+     * Describes the device as a selector choosing it.
+     *
+     * # Returns
+     * The selector, e.g. `1ab1:04ce:DS1ZA000000001@1/5`.
+     */
+    fn selector(&self) -> String {
+        let mut selector = format!("{:04x}:{:04x}", self.vendor_id, self.product_id);
+        if let Some(serial) = &self.serial {
+            selector.push_str(&format!(":{}", serial));
+        }
+        if let Some((bus, address)) = self.location {
+            selector.push_str(&format!("@{}/{}", bus, address));
+        }
+        selector
+    }
+}
+
+/**
+ * Selects the enumerated USB device matching the selector.
+ *
+ * # Arguments
+ * `usb` - The --usb argument, used in error messages.
+ * `selector` - The vendor and product ID with the optional serial number and bus address.
+ * `candidates` - The enumerated USB devices.
+ *
+ * # Returns
+ * A Result containing the index of the device, or an ApplicationError if no device or more than
+ * one matches.
+ */
+fn select_usb_device(usb: &str, selector: &UsbSelector, candidates: &[UsbCandidate]) -> Result<usize, ApplicationError> {
+    let matching: Vec<usize> = (0..candidates.len())
+        .filter(|index| {
+            let candidate = &candidates[*index];
+            selector.matches(candidate.vendor_id, candidate.product_id, candidate.serial.as_deref(), candidate.location)
+        })
+        .collect();
+    match matching[..] {
+        [] => Err(ApplicationError::NotFound(format!("USB device {}", usb))),
+        [index] => Ok(index),
+        _ => {
+            let listed: Vec<String> = matching.iter().map(|index| candidates[*index].selector()).collect();
+            Err(ApplicationError::Config(format!(
+                "{} USB devices match {}, add the serial number or bus address: {}",
+                matching.len(),
+                usb,
+                listed.join(", ")
+            )))
+        }
+    }
+}
+
+/**
+ * A USB device found by enumeration, or a device node opened without enumerating.
+ */
+pub enum UsbTarget {
+    Info(DeviceInfo),
+    Node(PathBuf),
+}
+
+impl UsbTarget {
+    /**
+     * Opens the device.
+     *
+     * # Returns
+     * A Result containing the open device or an ApplicationError.
+     */
+    pub async fn open(&self) -> Result<Device, ApplicationError> {
+        match self {
+            UsbTarget::Info(info) => {
+                info!("Opening USB device {:04x}:{:04x}", info.vendor_id(), info.product_id());
+                info.open()
+                    .await
+                    .map_err(|e| ApplicationError::Nusb("Could not open usb device".into(), e))
+            }
+            UsbTarget::Node(path) => {
+                info!("Opening USB device {}", path.display());
+                open_node(path).await
+            }
+        }
+    }
+}
+
+/**
+ * Opens a device node, e.g. `/dev/bus/usb/001/005`.
+ *
+ * # Arguments
+ * `path` - The device node.
+ *
+ * # Returns
+ * A Result containing the open device or an ApplicationError.
+ */
+#[cfg(target_os = "linux")]
+async fn open_node(path: &Path) -> Result<Device, ApplicationError> {
+    let file = fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open(path)
+        .map_err(|e| ApplicationError::Usb(format!("Could not open usb device {}: {}", path.display(), e)))?;
+    Device::from_fd(file.into())
+        .await
+        .map_err(|e| ApplicationError::Nusb(format!("Could not open usb device {}", path.display()), e))
+}
+
+/**
+ * Opens a device node. Device nodes are only supported on Linux.
+ *
+ * # Arguments
+ * `path` - The device node.
+ *
+ * # Returns
+ * An ApplicationError.
+ */
+#[cfg(not(target_os = "linux"))]
+async fn open_node(path: &Path) -> Result<Device, ApplicationError> {
+    Err(ApplicationError::Usb(format!(
+        "Could not open usb device {}: device nodes are only supported on Linux",
+        path.display()
+    )))
+}
+
+/**
+ * Gets the device node of a device from its sysfs directory, if the device there is still the
+ * selected one. Devices get a new address when they are plugged in again.
+ *
+ * # Arguments
+ * `sysfs` - The sysfs directory of the device.
+ * `selector` - The selected device.
+ *
+ * # Returns
+ * The device node, or None if the device has changed or is gone.
+ */
+fn sysfs_node(sysfs: &Path, selector: &UsbSelector) -> Option<PathBuf> {
+    let attribute = |name: &str| fs::read_to_string(sysfs.join(name)).ok().map(|value| value.trim().to_string());
+    let vendor_id = u16::from_str_radix(&attribute("idVendor")?, 16).ok()?;
+    let product_id = u16::from_str_radix(&attribute("idProduct")?, 16).ok()?;
+    let bus: u8 = attribute("busnum")?.parse().ok()?;
+    let address: u8 = attribute("devnum")?.parse().ok()?;
+    if !selector.matches(vendor_id, product_id, attribute("serial").as_deref(), Some((bus, address))) {
+        return None;
+    }
+    Some(PathBuf::from(format!("/dev/bus/usb/{:03}/{:03}", bus, address)))
+}
+
+/**
+ * Persistent cache of the sysfs directories of selected USB devices, so later invocations open
+ * them without enumerating the bus. One line per device: the key and the sysfs directory,
+ * separated by a tab.
+ */
+pub struct DeviceCache {
+    /**
+     * The cache file.
+     */
+    path: PathBuf,
+}
+
+impl DeviceCache {
+    /**
+     * Creates a new instance of DeviceCache.
+     *
+     * # Arguments
+     * `path` - The cache file, created when the first device is stored.
+     *
+     * # Returns
+     * A new DeviceCache instance.
+     */
+    pub fn new(path: &str) -> Self {
+        Self { path: PathBuf::from(path) }
+    }
+
+    /**
+     * Reads the cached devices. A missing or unreadable cache is empty.
+     *
+     * # Returns
+     * The keys and sysfs directories.
+     */
+    fn entries(&self) -> Vec<(String, PathBuf)> {
+        fs::read_to_string(&self.path)
+            .unwrap_or_default()
+            .lines()
+            .filter_map(|line| line.split_once('\t'))
+            .map(|(key, sysfs)| (key.to_string(), PathBuf::from(sysfs)))
+            .collect()
+    }
+
+    /**
+     * Looks up the sysfs directory of a device.
+     *
+     * # Arguments
+     * `key` - The key of the device.
+     *
+     * # Returns
+     * The sysfs directory, if cached.
+     */
+    fn lookup(&self, key: &str) -> Option<PathBuf> {
+        self.entries().into_iter().find(|(cached, _)| cached == key).map(|(_, sysfs)| sysfs)
+    }
+
+    /**
+     * Stores the sysfs directory of a device, replacing an earlier entry.
+     *
+     * # Arguments
+     * `key` - The key of the device.
+     * `sysfs` - The sysfs directory.
+     *
+     * # Returns
+     * A Result indicating success or an ApplicationError.
+     */
+    fn store(&self, key: &str, sysfs: &Path) -> Result<(), ApplicationError> {
+        let mut entries = self.entries();
+        entries.retain(|(cached, _)| cached != key);
+        entries.push((key.to_string(), sysfs.to_path_buf()));
+        if let Some(directory) = self.path.parent().filter(|directory| !directory.as_os_str().is_empty()) {
+            fs::create_dir_all(directory).map_err(|e| {
+                ApplicationError::Io(format!("Could not create {}", directory.display()), e)
+            })?;
+        }
+        let content: String = entries
+            .iter()
+            .map(|(key, sysfs)| format!("{}\t{}\n", key, sysfs.display()))
+            .collect();
+        fs::write(&self.path, content).map_err(|e| {
+            ApplicationError::Io(format!("Could not write device cache {}", self.path.display()), e)
+        })
+    }
+}
+
+/**
+ * Finds the selected USB device. Device nodes are opened as given, and devices in the cache are
+ * opened through their device node while it still belongs to them; otherwise the bus is
+ * enumerated on a blocking thread, so other devices can be opened meanwhile, and the device is
+ * stored in the cache. Several matching devices are an error listing them.
+ *
+ * # Arguments
+ * `usb` - The --usb argument.
+ * `cache` - The device cache, if used.
+ *
+ * # Returns
+ * A Result containing the device or an ApplicationError.
+ */
+pub async fn find_device(usb: &str, cache: Option<&DeviceCache>) -> Result<UsbTarget, ApplicationError> {
+    let selector = UsbSelector::parse(usb)?;
+    if let UsbSelector::Node(path) = selector {
+        return Ok(UsbTarget::Node(path));
+    }
+    let key = selector.key();
+    if cfg!(target_os = "linux") {
+        let cached = cache.and_then(|cache| cache.lookup(&key));
+        if let Some(node) = cached.and_then(|sysfs| sysfs_node(&sysfs, &selector)) {
+            info!("USB device {} found in the device cache at {}", usb, node.display());
+            return Ok(UsbTarget::Node(node));
+        }
+    }
+    debug!("Enumerating USB devices to find {}", usb);
+    let devices = tokio::task::spawn_blocking(|| list_devices().wait().map(|devices| devices.collect::<Vec<DeviceInfo>>()))
+        .await
+        .map_err(|e| ApplicationError::Usb(format!("Could not list usb devices: {}", e)))?
+        .map_err(|e| ApplicationError::Nusb("Could not list usb devices".into(), e))?;
+    let candidates: Vec<UsbCandidate> = devices.iter().map(UsbCandidate::new).collect();
+    let index = select_usb_device(usb, &selector, &candidates)?;
+    let device = devices.into_iter().nth(index).ok_or_else(|| ApplicationError::NotFound(format!("USB device {}", usb)))?;
+    info!("USB device {} found", usb);
+    #[cfg(target_os = "linux")]
+    if let Some(cache) = cache {
+        cache.store(&key, device.sysfs_path())?;
+    }
+    Ok(UsbTarget::Info(device))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_selector_key() {
+        assert_eq!(UsbSelector::parse("1ab1:04ce:DS1ZA000000001").unwrap().key(), "1ab1:04ce:DS1ZA000000001");
+        assert_eq!(UsbSelector::parse("1ab1:04ce@1/5").unwrap().key(), "1ab1:04ce:@1/5");
+        assert_eq!(UsbSelector::parse("/dev/bus/usb/001/005").unwrap().key(), "/dev/bus/usb/001/005");
+    }
+
+    #[test]
+    fn test_select_usb_device() {
+        let candidate = |serial: Option<&str>, address: u8| UsbCandidate {
+            vendor_id: 0x1ab1,
+            product_id: 0x04ce,
+            serial: serial.map(|serial| serial.to_string()),
+            location: Some((1, address)),
+        };
+        let candidates = [candidate(Some("DS1ZA1"), 5), candidate(Some("DS1ZA2"), 7), candidate(None, 9)];
+        let select = |usb: &str| select_usb_device(usb, &UsbSelector::parse(usb).unwrap(), &candidates);
+        assert_eq!(select("1ab1:04ce:DS1ZA2").unwrap(), 1);
+        assert_eq!(select("1ab1:04ce@1/9").unwrap(), 2);
+        assert!(matches!(select("1ab1:04ce:DS1ZA3"), Err(ApplicationError::NotFound(_))));
+        assert_eq!(
+            select("1ab1:04ce").unwrap_err().to_string(),
+            "Config Error: 3 USB devices match 1ab1:04ce, add the serial number or bus address: \
+             1ab1:04ce:DS1ZA1@1/5, 1ab1:04ce:DS1ZA2@1/7, 1ab1:04ce@1/9"
+        );
+    }
+
+    #[test]
+    fn test_device_cache() {
+        let directory = std::env::temp_dir().join(format!("devicecache-{}", std::process::id()));
+        let cache = DeviceCache::new(directory.join("usb").to_str().unwrap());
+        assert_eq!(cache.lookup("1ab1:04ce:"), None);
+        cache.store("1ab1:04ce:", Path::new("/sys/bus/usb/devices/1-2")).unwrap();
+        cache.store("f4ec:ee38:", Path::new("/sys/bus/usb/devices/1-3")).unwrap();
+        cache.store("1ab1:04ce:", Path::new("/sys/bus/usb/devices/1-4")).unwrap();
+        assert_eq!(cache.lookup("1ab1:04ce:"), Some(PathBuf::from("/sys/bus/usb/devices/1-4")));
+        assert_eq!(cache.entries().len(), 2);
+
+        let sysfs = directory.join("1-4");
+        fs::create_dir_all(&sysfs).unwrap();
+        for (name, value) in [("idVendor", "1ab1\n"), ("idProduct", "04ce\n"), ("busnum", "1\n"), ("devnum", "17\n")] {
+            fs::write(sysfs.join(name), value).unwrap();
+        }
+        let selector = UsbSelector::parse("1ab1:4ce").unwrap();
+        assert_eq!(sysfs_node(&sysfs, &selector), Some(PathBuf::from("/dev/bus/usb/001/017")));
+        assert_eq!(sysfs_node(&sysfs, &UsbSelector::parse("1ab1:4ce:DS1Z").unwrap()), None);
+        fs::remove_dir_all(directory).unwrap();
+    }
+}
@@ -0,0 +1,123 @@
+use std::path::PathBuf;
+
+use crate::error::ApplicationError;
+
+/**
+ * Selects a USB device: its device node, e.g. `/dev/bus/usb/001/005`, or its vendor and product
+ * ID with an optional serial number, e.g. `1ab1:04ce:DS1ZA000000001`, and bus number and device
+ * address, e.g. `1ab1:04ce@1/5`.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub enum UsbSelector {
+    Node(PathBuf),
+    Id {
+        vendor_id: u16,
+        product_id: u16,
+        serial: Option<String>,
+        /**
+         * Bus number and device address, as listed by lsusb.
+         */
+        location: Option<(u8, u8)>,
+    },
+}
+
+impl UsbSelector {
+    /**
+     * Parses the --usb argument.
+     *
+     * # Arguments
+     * `usb` - The device node or vendor_id:product_id[:serial][@bus/address], the IDs in hex and
+     * the bus number and address in decimal.
+     *
+     * # Returns
+     * A Result containing the UsbSelector or an ApplicationError.
+     */
+    pub fn parse(usb: &str) -> Result<Self, ApplicationError> {
+        if usb.starts_with('/') {
+            return Ok(UsbSelector::Node(PathBuf::from(usb)));
+        }
+        let invalid = || ApplicationError::Usb(format!("Expected vendor_id:product_id[:serial][@bus/address], got {}", usb));
+        let (ids, location) = match usb.split_once('@') {
+            Some((ids, location)) => {
+                let (bus, address) = location.split_once('/').ok_or_else(invalid)?;
+                let number = |value: &str| value.parse::<u8>().map_err(|_| invalid());
+                (ids, Some((number(bus)?, number(address)?)))
+            }
+            None => (usb, None),
+        };
+        let mut parts = ids.splitn(3, ':');
+        let mut id = || parts.next().and_then(|part| u16::from_str_radix(part, 16).ok()).ok_or_else(invalid);
+        let (vendor_id, product_id) = (id()?, id()?);
+        Ok(UsbSelector::Id {
+            vendor_id,
+            product_id,
+            serial: parts.next().filter(|serial| !serial.is_empty()).map(|serial| serial.to_string()),
+            location,
+        })
+    }
+
+    /**
+     * Checks if a device is selected: it has the IDs and, if given, the serial number and the
+     * bus number and address.
+     *
+     * # Arguments
+     * `vendor` - The vendor ID of the device.
+     * `product` - The product ID of the device.
+     * `serial_number` - The serial number of the device, if it has one.
+     * `bus_address` - The bus number and address of the device, if known.
+     *
+     * # Returns
+     * A boolean indicating whether the device is selected.
+     */
+    pub(crate) fn matches(&self, vendor: u16, product: u16, serial_number: Option<&str>, bus_address: Option<(u8, u8)>) -> bool {
+        match self {
+            UsbSelector::Node(_) => false,
+            UsbSelector::Id {
+                vendor_id,
+                product_id,
+                serial,
+                location,
+            } => {
+                *vendor_id == vendor
+                    && *product_id == product
+                    && serial.as_deref().is_none_or(|serial| Some(serial) == serial_number)
+                    && location.is_none_or(|location| Some(location) == bus_address)
+            }
+        }
+    }
+
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_selector() {
+        assert_eq!(
+            UsbSelector::parse("1ab1:4ce").unwrap(),
+            UsbSelector::Id {
+                vendor_id: 0x1ab1,
+                product_id: 0x04ce,
+                serial: None,
+                location: None
+            }
+        );
+        let selector = UsbSelector::parse("1ab1:04ce:DS1ZA000000001").unwrap();
+        assert!(selector.matches(0x1ab1, 0x04ce, Some("DS1ZA000000001"), None));
+        assert!(!selector.matches(0x1ab1, 0x04ce, Some("DS1ZA000000002"), None));
+        assert!(UsbSelector::parse("1ab1:4ce").unwrap().matches(0x1ab1, 0x04ce, None, Some((1, 5))));
+        let selector = UsbSelector::parse("1ab1:04ce@1/5").unwrap();
+        assert!(selector.matches(0x1ab1, 0x04ce, Some("DS1ZA000000001"), Some((1, 5))));
+        assert!(!selector.matches(0x1ab1, 0x04ce, None, Some((1, 6))));
+        assert!(!selector.matches(0x1ab1, 0x04ce, None, None));
+        assert!(UsbSelector::parse("1ab1:04ce:DS1Z@1/5").unwrap().matches(0x1ab1, 0x04ce, Some("DS1Z"), Some((1, 5))));
+        assert!(UsbSelector::parse("1ab1:04ce@1").is_err());
+        assert!(UsbSelector::parse("1ab1:04ce@1/300").is_err());
+        assert_eq!(
+            UsbSelector::parse("/dev/bus/usb/001/005").unwrap(),
+            UsbSelector::Node(PathBuf::from("/dev/bus/usb/001/005"))
+        );
+        assert!(UsbSelector::parse("rigol").is_err());
+    }
+}
@@ -0,0 +1,213 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+
+use crate::{
+    arguments::{Args, Parity},
+    error::ApplicationError,
+    instruments::{
+        command::Ut325Command,
+        communication::{
+            common::{Communication, DeviceInfo},
+            registry::Requirement,
+            serial::{SerialLine, UartConfig},
+        },
+        reading::{ut325_download_header, ut325_frames, Reading, Ut325Reading},
+    },
+};
+
+/**
+ * Read timeout of the serial port while waiting for frame bytes.
+ */
+const UT325_READ_TIMEOUT: Duration = Duration::from_millis(100);
+
+/**
+ * Time to wait for the response to a measurement.
+ */
+const UT325_RESPONSE_LIMIT: Duration = Duration::from_secs(2);
+
+/**
+ * Longest time a download of the logged records takes, enough for a full memory at 9600 baud.
+ */
+const UT325_DOWNLOAD_LIMIT: Duration = Duration::from_secs(300);
+
+/**
+ * Returns the fixed serial settings of the UT325, 9600 baud 8N1.
+ *
+ * # Returns
+ * The serial line settings.
+ */
+pub fn ut325_uart_config() -> UartConfig {
+    UartConfig {
+        baud_rate: 9600,
+        data_bits: 8,
+        parity: Parity::None,
+        stop_bits: 1,
+    }
+}
+
+/**
+ * Checks if a download is complete: the header frame and as many record frames as it announces.
+ * Invalid data is complete so the error is reported instead of waiting for the limit.
+ *
+ * # Arguments
+ * `data` - The received data.
+ *
+ * # Returns
+ * A boolean indicating whether the download is complete.
+ */
+fn is_download_complete(data: &[u8]) -> bool {
+    let Ok(frames) = ut325_frames(data) else {
+        return true;
+    };
+    match frames.first().map(|header| ut325_download_header(header)) {
+        Some(Ok((count, _))) => frames.len() > count as usize,
+        Some(Err(_)) => true,
+        None => false,
+    }
+}
+
+/**
+ * Module for UNI-T UT325 dual thermocouple loggers on their USB serial port. The meter answers
+ * each command frame with one frame, or with a header and a frame per record for a download.
+ */
+pub struct Ut325Serial {
+    /**
+     * Serial port of the meter.
+     */
+    line: SerialLine,
+}
+
+impl Ut325Serial {
+    /**
+     * Creates a new instance of Ut325Serial.
+     *
+     * # Arguments
+     * `line` - The opened serial port.
+     *
+     * # Returns
+     * A new Ut325Serial instance.
+     */
+    pub fn new(line: SerialLine) -> Self {
+        Self { line }
+    }
+
+    /**
+     * Reads T1 and T2.
+     *
+     * # Arguments
+     * `command` - The Measure command.
+     *
+     * # Returns
+     * A Result containing the reading or an ApplicationError.
+     */
+    fn measure(&self, command: &Ut325Command) -> Result<Ut325Reading, ApplicationError> {
+        self.line.write(&command.to_wire())?;
+        let data = self
+            .line
+            .read_response(|data| ut325_frames(data).map_or(true, |frames| !frames.is_empty()), UT325_RESPONSE_LIMIT)?;
+        let frames = ut325_frames(&data)?;
+        Ut325Reading::parse(&frames[0], command.channel(), None)
+    }
+
+    /**
+     * Downloads the logged records, oldest first.
+     *
+     * # Arguments
+     * `command` - The Download command.
+     *
+     * # Returns
+     * A Result containing a reading per record or an ApplicationError.
+     */
+    fn download(&self, command: &Ut325Command) -> Result<Vec<Ut325Reading>, ApplicationError> {
+        self.line.write(&command.to_wire())?;
+        let data = self.line.read_response(is_download_complete, UT325_DOWNLOAD_LIMIT)?;
+        let frames = ut325_frames(&data)?;
+        let (count, interval) = ut325_download_header(&frames[0])?;
+        frames[1..=count as usize]
+            .iter()
+            .map(|frame| Ut325Reading::parse(frame, command.channel(), Some(interval)))
+            .collect()
+    }
+}
+
+#[async_trait(?Send)]
+impl Communication for Ut325Serial {
+    /**
+     * Executes typed UT325 commands. A download returns a reading per logged record.
+     *
+     * # Arguments
+     * `commands` - The commands to execute.
+     *
+     * # Returns
+     * A Result containing an optional vector of Reading trait objects or an ApplicationError.
+     */
+    async fn command(
+        &self,
+        commands: Vec<String>,
+    ) -> Result<Option<Vec<Box<dyn Reading>>>, ApplicationError> {
+        let mut readings: Vec<Box<dyn Reading>> = Vec::new();
+        for command in commands {
+            let command = Ut325Command::try_from(command.as_str())?;
+            match command {
+                Ut325Command::Measure(_) => readings.push(Box::new(self.measure(&command)?)),
+                Ut325Command::Download(_) => {
+                    for reading in self.download(&command)? {
+                        readings.push(Box::new(reading));
+                    }
+                }
+            }
+        }
+        Ok(Some(readings))
+    }
+
+    /**
+     * Returns the USB descriptor strings of the serial adapter.
+     *
+     * # Returns
+     * The device info, or None if the port is not a USB adapter.
+     */
+    fn device_info(&self) -> Option<DeviceInfo> {
+        self.line.device_info()
+    }
+}
+
+/**
+ * Opens the meter on the serial port of the arguments, with the fixed line settings of the meter.
+ *
+ * # Arguments
+ * `args` - An Args struct containing the configuration for the desired device.
+ *
+ * # Returns
+ * A Result containing a boxed Communication trait object or an ApplicationError.
+ */
+pub(crate) async fn open(args: &Args) -> Result<Box<dyn Communication>, ApplicationError> {
+    let line = SerialLine::open(Requirement::Serial.argument(args)?, &ut325_uart_config(), UT325_READ_TIMEOUT)?;
+    Ok(Box::new(Ut325Serial::new(line)))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn frame(payload: &[u8]) -> Vec<u8> {
+        let mut frame = vec![0xAB, 0xCD, payload.len() as u8 + 2];
+        frame.extend_from_slice(payload);
+        let sum = frame.iter().map(|byte| *byte as u16).sum::<u16>();
+        frame.extend_from_slice(&sum.to_be_bytes());
+        frame
+    }
+
+    #[test]
+    fn test_is_download_complete() {
+        let mut data = frame(&[0x60, 0x02, 0x00, 0x0A, 0x00]);
+        assert!(!is_download_complete(&data[..4]));
+        assert!(!is_download_complete(&data));
+        data.extend(frame(&[0x61, 0x00, 0x00, 0x00, 0x00, 0xEB, 0x00, 0xEB, 0x00]));
+        assert!(!is_download_complete(&data));
+        data.extend(frame(&[0x61, 0x01, 0x00, 0x00, 0x00, 0xEC, 0x00, 0xEB, 0x00]));
+        assert!(is_download_complete(&data));
+        assert!(is_download_complete(&frame(&[0x5E, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00])));
+        assert!(is_download_complete(&[0xAB, 0xCD, 0x02, 0x00, 0x00]));
+    }
+}
@@ -0,0 +1,127 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+
+use crate::{
+    arguments::{Args, Parity},
+    error::ApplicationError,
+    instruments::{
+        communication::{
+            common::{Communication, DeviceInfo},
+            registry::Requirement,
+            serial::{SerialLine, UartConfig},
+        },
+        reading::{Reading, Ut71Reading},
+    },
+};
+
+/**
+ * Time to wait for a complete frame. The meter sends about two frames per second.
+ */
+const UT71_FRAME_TIMEOUT: Duration = Duration::from_secs(3);
+
+/**
+ * Read timeout of the serial port while waiting for frame bytes.
+ */
+pub const UT71_READ_TIMEOUT: Duration = Duration::from_millis(100);
+
+/**
+ * Returns the fixed serial settings of the UT71 series, 2400 baud 7O1.
+ *
+ * # Returns
+ * The serial line settings.
+ */
+pub fn ut71_uart_config() -> UartConfig {
+    UartConfig {
+        baud_rate: 2400,
+        data_bits: 7,
+        parity: Parity::Odd,
+        stop_bits: 1,
+    }
+}
+
+/**
+ * Module for UNI-T UT71 series meters on an RS-232 or IR-serial cable (UT-D02). The meter
+ * streams measurement frames; it cannot be controlled.
+ */
+pub struct Ut71Serial {
+    /**
+     * Serial port of the cable.
+     */
+    line: SerialLine,
+}
+
+impl Ut71Serial {
+    /**
+     * Creates a new instance of Ut71Serial. DTR is raised and RTS lowered to power the
+     * optocoupler of the cable.
+     *
+     * # Arguments
+     * `line` - The opened serial port.
+     *
+     * # Returns
+     * A Result containing the Ut71Serial instance or an ApplicationError.
+     */
+    pub fn new(line: SerialLine) -> Result<Self, ApplicationError> {
+        line.set_control_lines(true, false)?;
+        Ok(Self { line })
+    }
+}
+
+#[async_trait(?Send)]
+impl Communication for Ut71Serial {
+    /**
+     * Reads a frame for each Measure command.
+     *
+     * # Arguments
+     * `commands` - The commands to execute, only Measure is supported.
+     *
+     * # Returns
+     * A Result containing an optional vector of Reading trait objects or an ApplicationError.
+     */
+    async fn command(
+        &self,
+        commands: Vec<String>,
+    ) -> Result<Option<Vec<Box<dyn Reading>>>, ApplicationError> {
+        let mut readings: Vec<Box<dyn Reading>> = Vec::new();
+        for command in commands {
+            if command != "Measure" {
+                return Err(ApplicationError::Command(format!(
+                    "Unsupported command for UT71 meters: {}",
+                    command
+                )));
+            }
+            let data = self
+                .line
+                .read_until(|data| Ut71Reading::find_frame(data).is_some(), UT71_FRAME_TIMEOUT)?;
+            let frame = Ut71Reading::find_frame(&data)
+                .ok_or_else(|| ApplicationError::Serial("No complete UT71 frame received".into()))?;
+            readings.push(Box::new(Ut71Reading::parse(frame)?));
+        }
+        Ok(Some(readings))
+    }
+
+    /**
+     * Returns the USB descriptor strings of the serial adapter.
+     *
+     * # Returns
+     * The device info, or None if the port is not a USB adapter.
+     */
+    fn device_info(&self) -> Option<DeviceInfo> {
+        self.line.device_info()
+    }
+}
+
+/**
+ * Opens the meter on the serial port of the arguments, with the fixed line settings of the meter.
+ *
+ * # Arguments
+ * `args` - An Args struct containing the configuration for the desired device.
+ *
+ * # Returns
+ * A Result containing a boxed Communication trait object or an ApplicationError.
+ */
+pub(crate) async fn open(args: &Args) -> Result<Box<dyn Communication>, ApplicationError> {
+    let line = SerialLine::open(Requirement::Serial.argument(args)?, &ut71_uart_config(), UT71_READ_TIMEOUT)?;
+    Ok(Box::new(Ut71Serial::new(line)?))
+}
@@ -0,0 +1,161 @@
+use async_trait::async_trait;
+use tracing::debug;
+
+use crate::{
+    error::ApplicationError,
+    instruments::{
+        command::readback::Expected,
+        communication::{common::{Communication, DeviceInfo}, registry::Driver},
+        reading::Reading,
+    },
+};
+
+/**
+ * Wraps a device so that every setting command is read back: after the command the query of
+ * the setting is sent and its response compared with the requested value, e.g. `FREQ?` after
+ * `FREQ 1000`. The commands are sent one at a time and a mismatch fails the batch. The readings
+ * of the readback queries are not returned.
+ */
+pub struct VerifyingCommunication {
+    /**
+     * The wrapped device.
+     */
+    device: Box<dyn Communication>,
+    /**
+     * The driver of the device, which decides the readback of each command.
+     */
+    driver: &'static Driver,
+}
+
+impl VerifyingCommunication {
+    /**
+     * Creates a new instance of VerifyingCommunication.
+     *
+     * # Arguments
+     * `device` - The device to wrap.
+     * `driver` - The driver of the device.
+     *
+     * # Returns
+     * A new VerifyingCommunication instance.
+     */
+    pub fn new(device: Box<dyn Communication>, driver: &'static Driver) -> Self {
+        Self { device, driver }
+    }
+
+    /**
+     * Sends one command to the wrapped device.
+     *
+     * # Arguments
+     * `command` - The command.
+     *
+     * # Returns
+     * A Result containing the readings of the command or an ApplicationError.
+     */
+    async fn send(&self, command: &str) -> Result<Vec<Box<dyn Reading>>, ApplicationError> {
+        Ok(self.device.command(vec![command.to_string()]).await?.unwrap_or_default())
+    }
+}
+
+#[async_trait(?Send)]
+impl Communication for VerifyingCommunication {
+    /**
+     * Sends the commands one at a time and reads back the settings.
+     *
+     * # Arguments
+     * `commands` - The commands to execute.
+     *
+     * # Returns
+     * A Result containing an optional vector of Reading trait objects or an ApplicationError
+     * if a setting reads back a different value.
+     */
+    async fn command(
+        &self,
+        commands: Vec<String>,
+    ) -> Result<Option<Vec<Box<dyn Reading>>>, ApplicationError> {
+        let mut readings: Vec<Box<dyn Reading>> = Vec::new();
+        for command in commands {
+            let Some(readback) = (self.driver.readback)(&command)? else {
+                readings.extend(self.send(&command).await?);
+                continue;
+            };
+            let before = match readback.expected {
+                Expected::Toggled(_) => readback.before(&self.send(&readback.query).await?)?,
+                _ => None,
+            };
+            readings.extend(self.send(&command).await?);
+            readback.check(&command, &self.send(&readback.query).await?, before)?;
+            debug!("Readback of {} with {} ok", command, readback.query);
+        }
+        Ok(Some(readings))
+    }
+
+    /**
+     * Returns the device info of the wrapped device.
+     *
+     * # Returns
+     * The device info, or None.
+     */
+    fn device_info(&self) -> Option<DeviceInfo> {
+        self.device.device_info()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::cell::RefCell;
+
+    use super::*;
+    use crate::{arguments::Device, instruments::{communication::registry::driver, reading::ScpiRawReading}};
+
+    /**
+     * SCPI device storing settings and answering their queries. `FREQ` is stored rounded to
+     * whole kHz, as by an instrument with a coarse resolution.
+     */
+    #[derive(Default)]
+    struct Settings {
+        values: RefCell<Vec<(String, String)>>,
+        sent: RefCell<Vec<String>>,
+    }
+
+    #[async_trait(?Send)]
+    impl Communication for Settings {
+        async fn command(
+            &self,
+            commands: Vec<String>,
+        ) -> Result<Option<Vec<Box<dyn Reading>>>, ApplicationError> {
+            let mut readings: Vec<Box<dyn Reading>> = Vec::new();
+            for command in commands {
+                self.sent.borrow_mut().push(command.clone());
+                if let Some(header) = command.strip_suffix('?') {
+                    let values = self.values.borrow();
+                    let value = values.iter().find(|(name, _)| name == header).map(|(_, value)| value.clone());
+                    readings.push(Box::new(ScpiRawReading::new(value.unwrap_or_default().into_bytes())));
+                } else if let Some((header, value)) = command.split_once(' ') {
+                    let value = match header {
+                        "FREQ" => ((value.parse::<f64>().unwrap() / 1000.0).round() * 1000.0).to_string(),
+                        _ => value.to_string(),
+                    };
+                    self.values.borrow_mut().push((header.to_string(), value));
+                }
+            }
+            Ok(Some(readings))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_readback() {
+        let device = VerifyingCommunication::new(Box::<Settings>::default(), driver(&Device::GenericScpiUsb).unwrap());
+        let readings = device
+            .command(vec!["FREQ 2000".into(), "OUTP ON".into(), "*RST".into(), "MEAS?".into()])
+            .await
+            .unwrap()
+            .unwrap();
+        // Only the reading of MEAS? is returned, not those of the readback queries
+        assert_eq!(readings.len(), 1);
+        let error = device.command(vec!["FREQ 2400".into()]).await.err().unwrap();
+        assert_eq!(
+            error.to_string(),
+            "Command Error: Readback of FREQ 2400 failed: FREQ? returned 2000, expected 2400"
+        );
+    }
+}
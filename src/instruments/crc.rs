@@ -0,0 +1,29 @@
+/**
+ * Computes the CRC-16/MODBUS of data, used by Modbus-RTU frames and the TC66C packets.
+ *
+ * # Arguments
+ * `data` - The bytes.
+ *
+ * # Returns
+ * The CRC, sent low byte first on the wire.
+ */
+pub fn crc16_modbus(data: &[u8]) -> u16 {
+    data.iter().fold(0xffff, |crc, byte| {
+        (0..8).fold(crc ^ *byte as u16, |crc, _| match crc & 1 {
+            0 => crc >> 1,
+            _ => (crc >> 1) ^ 0xa001,
+        })
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_crc16_modbus() {
+        assert_eq!(crc16_modbus(b"123456789"), 0x4b37);
+        // Read holding registers 0 to 1 of unit 1
+        assert_eq!(crc16_modbus(&[0x01, 0x03, 0x00, 0x00, 0x00, 0x01]).to_le_bytes(), [0x84, 0x0a]);
+    }
+}
@@ -1,4 +1,5 @@
 pub mod communication;
-mod reading;
+pub mod reading;
 pub mod command;
+pub mod crc;
 
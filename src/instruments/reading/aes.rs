@@ -0,0 +1,263 @@
+/**
+ * Block size of AES in bytes.
+ */
+pub const BLOCK_SIZE: usize = 16;
+
+/**
+ * Number of rounds of AES-256.
+ */
+const ROUNDS: usize = 14;
+
+/**
+ * Key length of AES-256 in 32 bit words.
+ */
+const KEY_WORDS: usize = 8;
+
+/**
+ * The substitution box, generated at compile time.
+ */
+const SBOX: [u8; 256] = sbox();
+
+/**
+ * The inverse substitution box.
+ */
+const INV_SBOX: [u8; 256] = inverse(&SBOX);
+
+/**
+ * Generates the S-box: the multiplicative inverse in GF(2^8) followed by the affine
+ * transformation (FIPS 197 section 5.1.1). p walks all non-zero elements as powers of 3
+ * while q walks the matching inverses.
+ *
+ * # Returns
+ * The S-box.
+ */
+const fn sbox() -> [u8; 256] {
+    let mut sbox = [0u8; 256];
+    let mut p: u8 = 1;
+    let mut q: u8 = 1;
+    loop {
+        p = p ^ (p << 1) ^ if p & 0x80 != 0 { 0x1b } else { 0 };
+        q ^= q << 1;
+        q ^= q << 2;
+        q ^= q << 4;
+        if q & 0x80 != 0 {
+            q ^= 0x09;
+        }
+        let affine = q ^ q.rotate_left(1) ^ q.rotate_left(2) ^ q.rotate_left(3) ^ q.rotate_left(4);
+        sbox[p as usize] = affine ^ 0x63;
+        if p == 1 {
+            break;
+        }
+    }
+    sbox[0] = 0x63;
+    sbox
+}
+
+/**
+ * Inverts a substitution box.
+ *
+ * # Arguments
+ * `sbox` - The substitution box.
+ *
+ * # Returns
+ * The inverse substitution box.
+ */
+const fn inverse(sbox: &[u8; 256]) -> [u8; 256] {
+    let mut inverse = [0u8; 256];
+    let mut index = 0;
+    while index < 256 {
+        inverse[sbox[index] as usize] = index as u8;
+        index += 1;
+    }
+    inverse
+}
+
+/**
+ * Multiplies two elements of GF(2^8).
+ *
+ * # Arguments
+ * `a` - The first factor.
+ * `b` - The second factor.
+ *
+ * # Returns
+ * The product.
+ */
+fn multiply(mut a: u8, mut b: u8) -> u8 {
+    let mut product = 0;
+    while b != 0 {
+        if b & 1 != 0 {
+            product ^= a;
+        }
+        a = (a << 1) ^ if a & 0x80 != 0 { 0x1b } else { 0 };
+        b >>= 1;
+    }
+    product
+}
+
+/**
+ * Expands a 256 bit key into the round keys (FIPS 197 section 5.2).
+ *
+ * # Arguments
+ * `key` - The key.
+ *
+ * # Returns
+ * The round keys, one block per round.
+ */
+fn expand_key(key: &[u8; 32]) -> [[u8; BLOCK_SIZE]; ROUNDS + 1] {
+    let mut words = [[0u8; 4]; 4 * (ROUNDS + 1)];
+    for (index, word) in key.chunks_exact(4).enumerate() {
+        words[index].copy_from_slice(word);
+    }
+    let mut round_constant = 1u8;
+    for index in KEY_WORDS..words.len() {
+        let mut temp = words[index - 1];
+        if index % KEY_WORDS == 0 {
+            temp.rotate_left(1);
+            temp = temp.map(|byte| SBOX[byte as usize]);
+            temp[0] ^= round_constant;
+            round_constant = multiply(round_constant, 2);
+        } else if index % KEY_WORDS == 4 {
+            temp = temp.map(|byte| SBOX[byte as usize]);
+        }
+        for byte in 0..4 {
+            words[index][byte] = words[index - KEY_WORDS][byte] ^ temp[byte];
+        }
+    }
+    let mut round_keys = [[0u8; BLOCK_SIZE]; ROUNDS + 1];
+    for (round, round_key) in round_keys.iter_mut().enumerate() {
+        for column in 0..4 {
+            round_key[4 * column..4 * column + 4].copy_from_slice(&words[4 * round + column]);
+        }
+    }
+    round_keys
+}
+
+/**
+ * Adds a round key to the state.
+ *
+ * # Arguments
+ * `state` - The state, in column order.
+ * `round_key` - The round key.
+ */
+fn add_round_key(state: &mut [u8; BLOCK_SIZE], round_key: &[u8; BLOCK_SIZE]) {
+    state.iter_mut().zip(round_key).for_each(|(byte, key)| *byte ^= key);
+}
+
+/**
+ * Decrypts a block with the inverse cipher (FIPS 197 section 5.3).
+ *
+ * # Arguments
+ * `round_keys` - The expanded key.
+ * `block` - The block, decrypted in place.
+ */
+fn decrypt_block(round_keys: &[[u8; BLOCK_SIZE]; ROUNDS + 1], block: &mut [u8]) {
+    let mut state = [0u8; BLOCK_SIZE];
+    state.copy_from_slice(block);
+    add_round_key(&mut state, &round_keys[ROUNDS]);
+    for round in (0..ROUNDS).rev() {
+        // Inverse shift rows and inverse substitution
+        let shifted = state;
+        for row in 0..4 {
+            for column in 0..4 {
+                state[row + 4 * ((column + row) % 4)] = INV_SBOX[shifted[row + 4 * column] as usize];
+            }
+        }
+        add_round_key(&mut state, &round_keys[round]);
+        if round > 0 {
+            for column in state.chunks_exact_mut(4) {
+                let [a, b, c, d] = [column[0], column[1], column[2], column[3]];
+                column[0] = multiply(a, 14) ^ multiply(b, 11) ^ multiply(c, 13) ^ multiply(d, 9);
+                column[1] = multiply(a, 9) ^ multiply(b, 14) ^ multiply(c, 11) ^ multiply(d, 13);
+                column[2] = multiply(a, 13) ^ multiply(b, 9) ^ multiply(c, 14) ^ multiply(d, 11);
+                column[3] = multiply(a, 11) ^ multiply(b, 13) ^ multiply(c, 9) ^ multiply(d, 14);
+            }
+        }
+    }
+    block.copy_from_slice(&state);
+}
+
+/**
+ * Decrypts data with AES-256 in ECB mode.
+ *
+ * # Arguments
+ * `key` - The 256 bit key.
+ * `data` - The data, a multiple of the block size; a trailing partial block is left as is.
+ *
+ * # Returns
+ * The decrypted data.
+ */
+pub fn decrypt_ecb(key: &[u8; 32], data: &[u8]) -> Vec<u8> {
+    let round_keys = expand_key(key);
+    let mut plain = data.to_vec();
+    for block in plain.chunks_exact_mut(BLOCK_SIZE) {
+        decrypt_block(&round_keys, block);
+    }
+    plain
+}
+
+/**
+ * Encrypts data with AES-256 in ECB mode, used to build encrypted test frames.
+ *
+ * # Arguments
+ * `key` - The 256 bit key.
+ * `data` - The data, a multiple of the block size.
+ *
+ * # Returns
+ * The encrypted data.
+ */
+#[cfg(test)]
+pub fn encrypt_ecb(key: &[u8; 32], data: &[u8]) -> Vec<u8> {
+    let round_keys = expand_key(key);
+    let mut encrypted = data.to_vec();
+    for block in encrypted.chunks_exact_mut(BLOCK_SIZE) {
+        let mut state = [0u8; BLOCK_SIZE];
+        state.copy_from_slice(block);
+        add_round_key(&mut state, &round_keys[0]);
+        for (round, round_key) in round_keys.iter().enumerate().skip(1) {
+            let substituted = state;
+            for row in 0..4 {
+                for column in 0..4 {
+                    state[row + 4 * column] = SBOX[substituted[row + 4 * ((column + row) % 4)] as usize];
+                }
+            }
+            if round < ROUNDS {
+                for column in state.chunks_exact_mut(4) {
+                    let [a, b, c, d] = [column[0], column[1], column[2], column[3]];
+                    column[0] = multiply(a, 2) ^ multiply(b, 3) ^ c ^ d;
+                    column[1] = a ^ multiply(b, 2) ^ multiply(c, 3) ^ d;
+                    column[2] = a ^ b ^ multiply(c, 2) ^ multiply(d, 3);
+                    column[3] = multiply(a, 3) ^ b ^ c ^ multiply(d, 2);
+                }
+            }
+            add_round_key(&mut state, round_key);
+        }
+        block.copy_from_slice(&state);
+    }
+    encrypted
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_sbox() {
+        assert_eq!(SBOX[0x00], 0x63);
+        assert_eq!(SBOX[0x01], 0x7c);
+        assert_eq!(SBOX[0x53], 0xed);
+        assert_eq!(SBOX[0xff], 0x16);
+        assert_eq!(INV_SBOX[0x63], 0x00);
+    }
+
+    #[test]
+    fn test_aes256() {
+        // FIPS 197 appendix C.3
+        let key: [u8; 32] = std::array::from_fn(|index| index as u8);
+        let plain: Vec<u8> = (0..16).map(|index| index * 0x11).collect();
+        let encrypted = [
+            0x8e, 0xa2, 0xb7, 0xca, 0x51, 0x67, 0x45, 0xbf, 0xea, 0xfc, 0x49, 0x90, 0x4b, 0x49, 0x60, 0x89,
+        ];
+        assert_eq!(encrypt_ecb(&key, &plain), encrypted);
+        assert_eq!(decrypt_ecb(&key, &encrypted), plain);
+    }
+}
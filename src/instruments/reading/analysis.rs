@@ -0,0 +1,305 @@
+use rustfft::{num_complex::Complex, FftPlanner};
+
+use crate::{error::ApplicationError, instruments::reading::Reading};
+
+/**
+ * Highest harmonic included in the THD calculation.
+ */
+const MAX_HARMONIC: usize = 10;
+
+/**
+ * Number of bins on each side of a peak summed to collect the energy spread by the window.
+ */
+const PEAK_HALF_WIDTH: usize = 2;
+
+/**
+ * Represents a spectral analysis of a captured waveform.
+ */
+#[derive(Debug)]
+pub struct AnalysisReading {
+    pub sample_rate: f64,
+    pub samples: usize,
+    pub fundamental: f64,
+    pub rms: f64,
+    pub thd: f64,
+    /**
+     * Level of harmonics 2 to MAX_HARMONIC relative to the fundamental in dBc.
+     * Harmonics above the Nyquist frequency are None.
+     */
+    pub harmonics: Vec<Option<f64>>,
+}
+
+impl AnalysisReading {
+    /**
+     * Analyzes a block of samples.
+     *
+     * # Arguments
+     * `samples` - The waveform samples.
+     * `sample_rate` - The sample rate in Hz.
+     *
+     * # Returns
+     * A Result containing the AnalysisReading or an ApplicationError if the waveform is too short.
+     */
+    pub fn new(samples: &[f64], sample_rate: f64) -> Result<Self, ApplicationError> {
+        if samples.len() < 16 {
+            return Err(ApplicationError::General(format!(
+                "At least 16 samples are needed for analysis, got {}",
+                samples.len()
+            )));
+        }
+        let mean = samples.iter().sum::<f64>() / samples.len() as f64;
+        let ac: Vec<f64> = samples.iter().map(|sample| sample - mean).collect();
+        let rms = (ac.iter().map(|sample| sample * sample).sum::<f64>() / ac.len() as f64).sqrt();
+        let spectrum = Self::spectrum(&ac);
+        let bin_width = sample_rate / ac.len() as f64;
+
+        let peak_bin = (1..spectrum.len())
+            .max_by(|a, b| spectrum[*a].total_cmp(&spectrum[*b]))
+            .unwrap_or(1);
+        let fundamental_bin = peak_bin as f64 + Self::interpolate(&spectrum, peak_bin);
+        let fundamental_level = Self::level(&spectrum, peak_bin);
+
+        let harmonics: Vec<Option<f64>> = (2..=MAX_HARMONIC)
+            .map(|harmonic| {
+                let bin = (fundamental_bin * harmonic as f64).round() as usize;
+                if bin + PEAK_HALF_WIDTH >= spectrum.len() {
+                    None
+                } else {
+                    Some(Self::level(&spectrum, bin))
+                }
+            })
+            .collect();
+        let harmonic_sum = harmonics.iter().flatten().map(|level| level * level).sum::<f64>();
+        let thd = if fundamental_level > 0.0 {
+            harmonic_sum.sqrt() / fundamental_level * 100.0
+        } else {
+            0.0
+        };
+
+        Ok(Self {
+            sample_rate,
+            samples: samples.len(),
+            fundamental: fundamental_bin * bin_width,
+            rms,
+            thd,
+            harmonics: harmonics
+                .iter()
+                .map(|level| level.map(|level| 20.0 * (level / fundamental_level).log10()))
+                .collect(),
+        })
+    }
+
+    /**
+     * Parses an ASCII waveform (comma or whitespace separated values, optionally prefixed by an
+     * IEEE 488.2 block header) and analyzes it.
+     *
+     * # Arguments
+     * `data` - The raw instrument response.
+     * `sample_rate` - The sample rate in Hz.
+     *
+     * # Returns
+     * A Result containing the AnalysisReading or an ApplicationError.
+     */
+    pub fn parse_ascii(data: &[u8], sample_rate: f64) -> Result<Self, ApplicationError> {
+        let text = String::from_utf8_lossy(data);
+        let mut text = text.trim();
+        if let Some(rest) = text.strip_prefix('#') {
+            let digits = rest
+                .chars()
+                .next()
+                .and_then(|digit| digit.to_digit(10))
+                .ok_or_else(|| ApplicationError::General("Invalid block header".into()))?;
+            text = rest.get(1 + digits as usize..).unwrap_or("");
+        }
+        let samples = text
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|value| !value.is_empty())
+            .map(|value| {
+                value
+                    .parse::<f64>()
+                    .map_err(|e| ApplicationError::ParseFloat(format!("Invalid sample {}", value), e))
+            })
+            .collect::<Result<Vec<f64>, ApplicationError>>()?;
+        Self::new(&samples, sample_rate)
+    }
+
+    /**
+     * Calculates the single sided magnitude spectrum using a Hann window.
+     *
+     * # Arguments
+     * `samples` - The samples with the DC offset removed.
+     *
+     * # Returns
+     * The magnitudes of bins 0 to N/2.
+     */
+    fn spectrum(samples: &[f64]) -> Vec<f64> {
+        let n = samples.len();
+        let mut buffer: Vec<Complex<f64>> = samples
+            .iter()
+            .enumerate()
+            .map(|(i, sample)| {
+                let window = 0.5 - 0.5 * (2.0 * std::f64::consts::PI * i as f64 / n as f64).cos();
+                Complex::new(sample * window, 0.0)
+            })
+            .collect();
+        FftPlanner::new().plan_fft_forward(n).process(&mut buffer);
+        buffer.iter().take(n / 2 + 1).map(|value| value.norm()).collect()
+    }
+
+    /**
+     * Interpolates the peak position between bins with a parabolic fit.
+     *
+     * # Arguments
+     * `spectrum` - The magnitude spectrum.
+     * `bin` - The bin with the local maximum.
+     *
+     * # Returns
+     * The offset from the bin, between -0.5 and 0.5.
+     */
+    fn interpolate(spectrum: &[f64], bin: usize) -> f64 {
+        if bin == 0 || bin + 1 >= spectrum.len() {
+            return 0.0;
+        }
+        let (a, b, c) = (spectrum[bin - 1], spectrum[bin], spectrum[bin + 1]);
+        let denominator = a - 2.0 * b + c;
+        if denominator == 0.0 {
+            0.0
+        } else {
+            (0.5 * (a - c) / denominator).clamp(-0.5, 0.5)
+        }
+    }
+
+    /**
+     * Collects the energy around a bin.
+     *
+     * # Arguments
+     * `spectrum` - The magnitude spectrum.
+     * `bin` - The center bin.
+     *
+     * # Returns
+     * The magnitude of the peak.
+     */
+    fn level(spectrum: &[f64], bin: usize) -> f64 {
+        let start = bin.saturating_sub(PEAK_HALF_WIDTH).max(1);
+        let end = (bin + PEAK_HALF_WIDTH).min(spectrum.len() - 1);
+        spectrum[start..=end].iter().map(|value| value * value).sum::<f64>().sqrt()
+    }
+}
+
+impl Reading for AnalysisReading {
+    /**
+     * Returns the names of the CSV fields.
+     *
+     * # Returns
+     * A Result containing the CSV header fields or an ApplicationError.
+     */
+    fn get_csv_header(&self) -> Result<Vec<String>, ApplicationError> {
+        let mut header: Vec<String> = ["fundamental", "rms", "thd_percent", "sample_rate", "samples"]
+            .iter()
+            .map(|field| field.to_string())
+            .collect();
+        header.extend((2..=MAX_HARMONIC).map(|harmonic| format!("h{}_dbc", harmonic)));
+        Ok(header)
+    }
+
+    /**
+     * Returns the analysis as CSV fields.
+     *
+     * # Returns
+     * A Result containing the CSV field values or an ApplicationError.
+     */
+    fn get_csv(&self) -> Result<Vec<String>, ApplicationError> {
+        let mut fields = vec![
+            self.fundamental.to_string(),
+            self.rms.to_string(),
+            self.thd.to_string(),
+            self.sample_rate.to_string(),
+            self.samples.to_string(),
+        ];
+        fields.extend(
+            self.harmonics
+                .iter()
+                .map(|level| level.map(|level| level.to_string()).unwrap_or_default()),
+        );
+        Ok(fields)
+    }
+
+    /**
+     * Not supported for AnalysisReading.
+     *
+     * # Returns
+     * Always Err.
+     */
+    fn get_raw(&self) -> Result<Vec<u8>, ApplicationError> {
+        Err(ApplicationError::General(
+            "AnalysisReading does not support raw format".into(),
+        ))
+    }
+
+    /**
+     * Returns a human readable summary of the analysis.
+     *
+     * # Returns
+     * A Result containing the summary or an ApplicationError.
+     */
+    fn get_raw_string(&self) -> Result<String, ApplicationError> {
+        Ok(format!(
+            "fundamental={} Hz rms={} thd={} %",
+            self.fundamental, self.rms, self.thd
+        ))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn waveform(sample_rate: f64, count: usize, components: &[(f64, f64)]) -> Vec<f64> {
+        (0..count)
+            .map(|n| {
+                components
+                    .iter()
+                    .map(|(frequency, amplitude)| {
+                        amplitude * (2.0 * std::f64::consts::PI * frequency * n as f64 / sample_rate).sin()
+                    })
+                    .sum()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_pure_sine() {
+        let samples = waveform(48000.0, 4800, &[(1000.0, 1.0)]);
+        let reading = AnalysisReading::new(&samples, 48000.0).unwrap();
+        assert!((reading.fundamental - 1000.0).abs() < 1.0);
+        assert!((reading.rms - 1.0 / 2f64.sqrt()).abs() < 1e-3);
+        assert!(reading.thd < 0.1);
+    }
+
+    #[test]
+    fn test_known_distortion() {
+        let samples = waveform(48000.0, 4800, &[(1000.0, 1.0), (2000.0, 0.1), (3000.0, 0.05)]);
+        let reading = AnalysisReading::new(&samples, 48000.0).unwrap();
+        let expected = (0.1f64.powi(2) + 0.05f64.powi(2)).sqrt() * 100.0;
+        assert!((reading.thd - expected).abs() < 0.2);
+        assert!((reading.harmonics[0].unwrap() + 20.0).abs() < 0.2);
+    }
+
+    #[test]
+    fn test_parse_ascii_with_block_header() {
+        let values = waveform(1000.0, 100, &[(50.0, 1.0)])
+            .iter()
+            .map(|value| value.to_string())
+            .collect::<Vec<String>>()
+            .join(",");
+        let data = format!("#9{:09}{}\n", values.len(), values);
+        let reading = AnalysisReading::parse_ascii(data.as_bytes(), 1000.0).unwrap();
+        assert_eq!(reading.samples, 100);
+        assert!((reading.fundamental - 50.0).abs() < 1.0);
+    }
+
+    #[test]
+    fn test_too_few_samples() {
+        assert!(AnalysisReading::new(&[0.0; 4], 1000.0).is_err());
+    }
+}
@@ -0,0 +1,259 @@
+use crate::{
+    error::ApplicationError,
+    instruments::reading::{Measurement, Reading},
+};
+
+// First two and last two bytes of a value frame
+const FRAME_START: [u8; 2] = [0xCA, 0xCB];
+const FRAME_END: [u8; 2] = [0xCE, 0xCF];
+/**
+ * Length of a value frame: start, three data bytes and end.
+ */
+pub const ATORCH_FRAME_LENGTH: usize = 7;
+
+// CSV field names, in the order returned by get_csv
+const CSV_HEADER: [&str; 9] = ["values", "voltage", "current", "power", "capacity", "energy", "temperature", "time", "output"];
+
+/**
+ * Gets the three data bytes of a value frame, `CA CB d1 d2 d3 CE CF`.
+ *
+ * # Arguments
+ * `response` - The response to a query.
+ *
+ * # Returns
+ * A Result containing the data bytes or an ApplicationError if the frame is invalid.
+ */
+fn frame_data(response: &[u8]) -> Result<[u8; 3], ApplicationError> {
+    match response {
+        [0xCA, 0xCB, d1, d2, d3, 0xCE, 0xCF] => Ok([*d1, *d2, *d3]),
+        _ => Err(ApplicationError::Command(format!(
+            "Invalid Atorch value frame {:02X?}, expected {:02X?} .. {:02X?}",
+            response, FRAME_START, FRAME_END
+        ))),
+    }
+}
+
+/**
+ * Decodes the 24 bit big endian value of a value frame.
+ *
+ * # Arguments
+ * `response` - The response to a query.
+ *
+ * # Returns
+ * A Result containing the value or an ApplicationError if the frame is invalid.
+ */
+fn frame_value(response: &[u8]) -> Result<u32, ApplicationError> {
+    let [d1, d2, d3] = frame_data(response)?;
+    Ok(u32::from_be_bytes([0, d1, d2, d3]))
+}
+
+/**
+ * Represents the values read from an Atorch DL24 / PX100 style electronic load.
+ */
+#[derive(Debug)]
+pub struct AtorchReading {
+    /**
+     * True for the setpoints, false for the measured values.
+     */
+    pub setpoint: bool,
+    /**
+     * Voltage in V; the cutoff voltage for setpoints.
+     */
+    pub voltage: f64,
+    /**
+     * Current in A; the load current for setpoints.
+     */
+    pub current: f64,
+    /**
+     * Capacity drawn since the counters were reset in Ah, None for setpoints.
+     */
+    pub capacity: Option<f64>,
+    /**
+     * Energy drawn since the counters were reset in Wh, None for setpoints.
+     */
+    pub energy: Option<f64>,
+    /**
+     * Temperature of the heat sink in °C, None for setpoints.
+     */
+    pub temperature: Option<f64>,
+    /**
+     * Time the load was on since the counters were reset in seconds, None for setpoints.
+     */
+    pub time: Option<u32>,
+    pub output: Option<bool>,
+    /**
+     * The value frames as read.
+     */
+    pub frames: Vec<u8>,
+}
+
+impl AtorchReading {
+    /**
+     * Decodes the responses to the queries of `Measure`.
+     *
+     * # Arguments
+     * `responses` - The value frames of the load state, voltage, current, time, capacity, energy
+     * and temperature queries.
+     *
+     * # Returns
+     * A Result containing the AtorchReading or an ApplicationError if a frame is invalid.
+     */
+    pub fn measured(responses: &[Vec<u8>]) -> Result<Self, ApplicationError> {
+        let [output, voltage, current, time, capacity, energy, temperature] = responses else {
+            return Err(ApplicationError::Command(format!("Expected 7 Atorch value frames, got {}", responses.len())));
+        };
+        let [hours, minutes, seconds] = frame_data(time)?;
+        Ok(Self {
+            setpoint: false,
+            voltage: frame_value(voltage)? as f64 / 1000.0,
+            current: frame_value(current)? as f64 / 1000.0,
+            capacity: Some(frame_value(capacity)? as f64 / 1000.0),
+            energy: Some(frame_value(energy)? as f64 / 1000.0),
+            temperature: Some(frame_value(temperature)? as f64),
+            time: Some(hours as u32 * 3600 + minutes as u32 * 60 + seconds as u32),
+            output: Some(frame_value(output)? != 0),
+            frames: responses.concat(),
+        })
+    }
+
+    /**
+     * Decodes the responses to the queries of `Setpoints`.
+     *
+     * # Arguments
+     * `responses` - The value frames of the load current and cutoff voltage queries.
+     *
+     * # Returns
+     * A Result containing the AtorchReading or an ApplicationError if a frame is invalid.
+     */
+    pub fn setpoints(responses: &[Vec<u8>]) -> Result<Self, ApplicationError> {
+        let [current, cutoff] = responses else {
+            return Err(ApplicationError::Command(format!("Expected 2 Atorch value frames, got {}", responses.len())));
+        };
+        Ok(Self {
+            setpoint: true,
+            voltage: frame_value(cutoff)? as f64 / 100.0,
+            current: frame_value(current)? as f64 / 100.0,
+            capacity: None,
+            energy: None,
+            temperature: None,
+            time: None,
+            output: None,
+            frames: responses.concat(),
+        })
+    }
+}
+
+impl Reading for AtorchReading {
+    /**
+     * Returns the names of the CSV fields.
+     *
+     * # Returns
+     * A Result containing the CSV header fields or an ApplicationError.
+     */
+    fn get_csv_header(&self) -> Result<Vec<String>, ApplicationError> {
+        Ok(CSV_HEADER.iter().map(|field| field.to_string()).collect())
+    }
+
+    /**
+     * Returns the values as CSV fields. Values not read for setpoints are empty.
+     *
+     * # Returns
+     * A Result containing the CSV field values or an ApplicationError.
+     */
+    fn get_csv(&self) -> Result<Vec<String>, ApplicationError> {
+        let optional = |value: Option<String>| value.unwrap_or_default();
+        let power = (!self.setpoint).then(|| ((self.voltage * self.current * 1000.0).round() / 1000.0).to_string());
+        Ok(vec![
+            if self.setpoint { "setpoint" } else { "output" }.to_string(),
+            self.voltage.to_string(),
+            self.current.to_string(),
+            optional(power),
+            optional(self.capacity.map(|capacity| capacity.to_string())),
+            optional(self.energy.map(|energy| energy.to_string())),
+            optional(self.temperature.map(|temperature| temperature.to_string())),
+            optional(self.time.map(|time| time.to_string())),
+            optional(self.output.map(|output| output.to_string())),
+        ])
+    }
+
+    /**
+     * Returns the value frames as read.
+     *
+     * # Returns
+     * A Result containing a byte vector with the raw data or an ApplicationError.
+     */
+    fn get_raw(&self) -> Result<Vec<u8>, ApplicationError> {
+        Ok(self.frames.clone())
+    }
+
+    /**
+     * Returns a human readable summary of the reading.
+     *
+     * # Returns
+     * A Result containing the summary or an ApplicationError.
+     */
+    fn get_raw_string(&self) -> Result<String, ApplicationError> {
+        let mut fields = vec![format!("{} V", self.voltage), format!("{} A", self.current)];
+        if let (Some(capacity), Some(energy)) = (self.capacity, self.energy) {
+            fields.push(format!("{} Ah {} Wh", capacity, energy));
+        }
+        if let Some(temperature) = self.temperature {
+            fields.push(format!("{} °C", temperature));
+        }
+        if let Some(output) = self.output {
+            fields.push(format!("load {}", if output { "on" } else { "off" }));
+        }
+        Ok(fields.join(" "))
+    }
+
+    /**
+     * Returns the voltage at the load terminals as the measurement, so a discharge test can
+     * stop at the cutoff without a separate meter.
+     *
+     * # Returns
+     * The measurement, or None for setpoints.
+     */
+    fn get_measurement(&self) -> Option<Measurement> {
+        match self.setpoint {
+            true => None,
+            false => Measurement::from_display(self.voltage, "V", Vec::new()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn value(data: [u8; 3]) -> Vec<u8> {
+        vec![0xCA, 0xCB, data[0], data[1], data[2], 0xCE, 0xCF]
+    }
+
+    #[test]
+    fn test_measured() {
+        // Load on, 3.702 V, 1.000 A, 1:02:03, 1.234 Ah, 4.567 Wh, 31 °C
+        let responses = [[0, 0, 1], [0, 0x0E, 0x76], [0, 0x03, 0xE8], [1, 2, 3], [0, 0x04, 0xD2], [0, 0x11, 0xD7], [0, 0, 31]];
+        let reading = AtorchReading::measured(&responses.map(value)).unwrap();
+        assert_eq!(
+            reading.get_csv().unwrap(),
+            vec!["output", "3.702", "1", "3.702", "1.234", "4.567", "31", "3723", "true"]
+        );
+        assert_eq!(reading.get_raw_string().unwrap(), "3.702 V 1 A 1.234 Ah 4.567 Wh 31 °C load on");
+        assert_eq!(reading.get_measurement().unwrap().value, 3.702);
+        assert_eq!(reading.get_raw().unwrap().len(), 7 * ATORCH_FRAME_LENGTH);
+    }
+
+    #[test]
+    fn test_setpoints() {
+        let reading = AtorchReading::setpoints(&[value([0, 0, 150]), value([0, 0x01, 0x2C])]).unwrap();
+        assert_eq!(reading.get_csv().unwrap(), vec!["setpoint", "3", "1.5", "", "", "", "", "", ""]);
+        assert!(reading.get_measurement().is_none());
+    }
+
+    #[test]
+    fn test_invalid_frames() {
+        assert!(AtorchReading::setpoints(&[value([0, 0, 1])]).is_err());
+        assert!(AtorchReading::setpoints(&[value([0, 0, 1]), vec![0x6F]]).is_err());
+        assert!(frame_value(&[0xCA, 0xCB, 0, 0, 1, 0xCE, 0xCE]).is_err());
+    }
+}
@@ -0,0 +1,178 @@
+use crate::{error::ApplicationError, instruments::reading::Reading};
+
+// CSV field names, in the order returned by get_csv
+const CSV_HEADER: [&str; 5] = ["rms", "peak", "frequency", "sample_rate", "samples"];
+
+/**
+ * Calculates the RMS value of the samples.
+ *
+ * # Arguments
+ * `samples` - The calibrated samples.
+ *
+ * # Returns
+ * The RMS value, 0 if there are no samples.
+ */
+pub fn rms(samples: &[f64]) -> f64 {
+    if samples.is_empty() {
+        return 0.0;
+    }
+    (samples.iter().map(|sample| sample * sample).sum::<f64>() / samples.len() as f64).sqrt()
+}
+
+/**
+ * Calculates the absolute peak value of the samples.
+ *
+ * # Arguments
+ * `samples` - The calibrated samples.
+ *
+ * # Returns
+ * The largest absolute sample value, 0 if there are no samples.
+ */
+pub fn peak(samples: &[f64]) -> f64 {
+    samples.iter().fold(0.0, |max, sample| f64::max(max, sample.abs()))
+}
+
+/**
+ * Estimates the signal frequency by counting rising zero crossings after removing the DC offset.
+ * Crossing times are linearly interpolated between samples.
+ *
+ * # Arguments
+ * `samples` - The calibrated samples.
+ * `sample_rate` - The sample rate in Hz.
+ *
+ * # Returns
+ * The estimated frequency in Hz, or None if fewer than two crossings were found.
+ */
+pub fn frequency(samples: &[f64], sample_rate: u32) -> Option<f64> {
+    if samples.len() < 2 || sample_rate == 0 {
+        return None;
+    }
+    let mean = samples.iter().sum::<f64>() / samples.len() as f64;
+    let mut crossings: Vec<f64> = Vec::new();
+    for (index, window) in samples.windows(2).enumerate() {
+        let (previous, current) = (window[0] - mean, window[1] - mean);
+        if previous < 0.0 && current >= 0.0 {
+            crossings.push(index as f64 + (-previous / (current - previous)));
+        }
+    }
+    if crossings.len() < 2 {
+        return None;
+    }
+    let periods = (crossings.len() - 1) as f64;
+    let span = crossings[crossings.len() - 1] - crossings[0];
+    Some(periods * sample_rate as f64 / span)
+}
+
+/**
+ * Represents a block of samples captured from a sound card input.
+ */
+#[derive(Debug)]
+pub struct AudioReading {
+    pub samples: Vec<f64>,
+    pub sample_rate: u32,
+    pub rms: f64,
+    pub peak: f64,
+    pub frequency: Option<f64>,
+}
+
+impl AudioReading {
+    /**
+     * Creates a new AudioReading from raw samples.
+     *
+     * # Arguments
+     * `samples` - The raw samples in the range -1.0 to 1.0.
+     * `sample_rate` - The sample rate in Hz.
+     * `calibration` - Factor converting a full scale sample to volts.
+     *
+     * # Returns
+     * A new AudioReading instance.
+     */
+    pub fn new(samples: Vec<f32>, sample_rate: u32, calibration: f64) -> Self {
+        let samples: Vec<f64> = samples.iter().map(|sample| *sample as f64 * calibration).collect();
+        Self {
+            rms: rms(&samples),
+            peak: peak(&samples),
+            frequency: frequency(&samples, sample_rate),
+            samples,
+            sample_rate,
+        }
+    }
+}
+
+impl Reading for AudioReading {
+    /**
+     * Returns the names of the CSV fields.
+     *
+     * # Returns
+     * A Result containing the CSV header fields or an ApplicationError.
+     */
+    fn get_csv_header(&self) -> Result<Vec<String>, ApplicationError> {
+        Ok(CSV_HEADER.iter().map(|field| field.to_string()).collect())
+    }
+
+    /**
+     * Returns the measurement data as CSV fields.
+     *
+     * # Returns
+     * A Result containing the CSV field values or an ApplicationError.
+     */
+    fn get_csv(&self) -> Result<Vec<String>, ApplicationError> {
+        Ok(vec![
+            self.rms.to_string(),
+            self.peak.to_string(),
+            self.frequency.map(|value| value.to_string()).unwrap_or_default(),
+            self.sample_rate.to_string(),
+            self.samples.len().to_string(),
+        ])
+    }
+
+    /**
+     * Returns the calibrated samples as little endian f64 bytes.
+     *
+     * # Returns
+     * A Result containing a byte vector with the samples or an ApplicationError.
+     */
+    fn get_raw(&self) -> Result<Vec<u8>, ApplicationError> {
+        Ok(self.samples.iter().flat_map(|sample| sample.to_le_bytes()).collect())
+    }
+
+    /**
+     * Returns the calibrated samples, one per line.
+     *
+     * # Returns
+     * A Result containing a String with the samples or an ApplicationError.
+     */
+    fn get_raw_string(&self) -> Result<String, ApplicationError> {
+        Ok(self.samples.iter().map(|sample| sample.to_string()).collect::<Vec<String>>().join("\n"))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn sine(frequency: f64, amplitude: f64, sample_rate: u32, count: usize) -> Vec<f32> {
+        (0..count)
+            .map(|n| (amplitude * (2.0 * std::f64::consts::PI * frequency * n as f64 / sample_rate as f64).sin()) as f32)
+            .collect()
+    }
+
+    #[test]
+    fn test_rms_and_peak_of_sine() {
+        let reading = AudioReading::new(sine(1000.0, 0.5, 48000, 48000), 48000, 2.0);
+        assert!((reading.peak - 1.0).abs() < 1e-3);
+        assert!((reading.rms - 1.0 / 2f64.sqrt()).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_frequency_of_sine() {
+        let reading = AudioReading::new(sine(1234.0, 0.5, 48000, 48000), 48000, 1.0);
+        assert!((reading.frequency.unwrap() - 1234.0).abs() < 0.5);
+    }
+
+    #[test]
+    fn test_frequency_of_dc() {
+        assert_eq!(frequency(&[0.5; 100], 48000), None);
+        assert_eq!(rms(&[]), 0.0);
+    }
+}
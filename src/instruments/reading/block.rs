@@ -0,0 +1,74 @@
+use crate::error::ApplicationError;
+
+/**
+ * Extracts the payload of an IEEE 488.2 definite length block, `#<n><length><payload>`.
+ * Only a newline terminator may follow the payload.
+ *
+ * # Arguments
+ * `data` - The received bytes, starting with the block header.
+ *
+ * # Returns
+ * A Result containing Some(payload) once the block is complete, None while more bytes are
+ * needed, or an ApplicationError if the header is invalid or more bytes were received than declared.
+ */
+pub fn block_payload(data: &[u8]) -> Result<Option<&[u8]>, ApplicationError> {
+    let Some(&first) = data.first() else {
+        return Ok(None);
+    };
+    if first != b'#' {
+        return Err(ApplicationError::Command("Expected a binary block starting with #".into()));
+    }
+    let Some(&digits) = data.get(1) else {
+        return Ok(None);
+    };
+    let digits = match digits {
+        b'1'..=b'9' => (digits - b'0') as usize,
+        _ => {
+            return Err(ApplicationError::Command(
+                "Indefinite length or invalid binary block header".into(),
+            ))
+        }
+    };
+    let Some(length) = data.get(2..2 + digits) else {
+        return Ok(None);
+    };
+    let length: usize = std::str::from_utf8(length)
+        .ok()
+        .and_then(|length| length.parse().ok())
+        .ok_or_else(|| ApplicationError::Command("Invalid binary block length".into()))?;
+    let end = 2 + digits + length;
+    if data.len() > end && !matches!(&data[end..], b"\n" | b"\r\n" | b"\r") {
+        return Err(ApplicationError::Command(format!(
+            "Binary block declares {} bytes but {} were received",
+            length,
+            data.len() - 2 - digits
+        )));
+    }
+    Ok(data.get(2 + digits..end))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_complete_block() {
+        assert_eq!(block_payload(b"#15hello\n").unwrap(), Some(&b"hello"[..]));
+        assert_eq!(block_payload(b"#2100123456789").unwrap(), Some(&b"0123456789"[..]));
+    }
+
+    #[test]
+    fn test_incomplete_block() {
+        assert_eq!(block_payload(b"").unwrap(), None);
+        assert_eq!(block_payload(b"#9000").unwrap(), None);
+        assert_eq!(block_payload(b"#15hel").unwrap(), None);
+    }
+
+    #[test]
+    fn test_invalid_block() {
+        assert!(block_payload(b"1.234\n").is_err());
+        assert!(block_payload(b"#0hello").is_err());
+        assert!(block_payload(b"#2x5hello").is_err());
+        assert!(block_payload(b"#13hello\n").is_err());
+    }
+}
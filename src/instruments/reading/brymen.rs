@@ -0,0 +1,416 @@
+use crate::{
+    error::ApplicationError,
+    instruments::reading::{Measurement, Reading},
+};
+
+/**
+ * Length of a BM869s frame, three 8 byte HID reports.
+ */
+pub const BRYMEN_FRAME_LENGTH: usize = 24;
+
+// Main display: sign bit and the bytes of its 6 digits
+const MAIN_SIGN: (usize, u8) = (2, 0x80);
+const MAIN_DIGITS: std::ops::Range<usize> = 3..9;
+
+// Secondary display: sign bit and the bytes of its 4 digits
+const SECONDARY_SIGN: (usize, u8) = (9, 0x10);
+const SECONDARY_DIGITS: std::ops::Range<usize> = 10..14;
+
+// Flag annunciators
+const FLAGS: [(usize, u8, &str); 7] = [
+    (1, 0x01, "auto"),
+    (1, 0x02, "hold"),
+    (1, 0x04, "rel"),
+    (1, 0x08, "max"),
+    (1, 0x10, "min"),
+    (1, 0x20, "avg"),
+    (1, 0x40, "low_battery"),
+];
+
+// Unit annunciators of the main display, prefixes first
+const MAIN_UNITS: [(usize, u8, &str); 17] = [
+    (14, 0x01, "n"),
+    (14, 0x02, "µ"),
+    (14, 0x04, "m"),
+    (14, 0x08, "k"),
+    (14, 0x10, "M"),
+    (15, 0x01, "V"),
+    (15, 0x02, "A"),
+    (15, 0x04, "Ω"),
+    (15, 0x08, "F"),
+    (15, 0x10, "Hz"),
+    (15, 0x20, "%"),
+    (15, 0x40, "°C"),
+    (15, 0x80, "°F"),
+    (16, 0x01, "dBm"),
+    (16, 0x02, "S"),
+    (16, 0x04, "s"),
+    (16, 0x08, "D"),
+];
+const MAIN_COUPLING: [(usize, u8, &str); 2] = [(0, 0x01, "DC"), (0, 0x02, "AC")];
+
+// Unit annunciators of the secondary display, prefixes first
+const SECONDARY_UNITS: [(usize, u8, &str); 11] = [
+    (17, 0x01, "µ"),
+    (17, 0x02, "m"),
+    (17, 0x04, "k"),
+    (17, 0x08, "M"),
+    (18, 0x01, "V"),
+    (18, 0x02, "A"),
+    (18, 0x04, "Hz"),
+    (18, 0x08, "%"),
+    (18, 0x10, "°C"),
+    (18, 0x20, "°F"),
+    (18, 0x40, "dBm"),
+];
+const SECONDARY_COUPLING: [(usize, u8, &str); 2] = [(19, 0x01, "DC"), (19, 0x02, "AC")];
+
+// CSV field names, in the order returned by get_csv
+const CSV_HEADER: [&str; 13] = [
+    "display_value", "overload", "decimal_value", "display_unit", "coupling", "secondary_display_value",
+    "secondary_overload", "secondary_decimal_value", "secondary_display_unit", "secondary_coupling", "flags",
+    "scaled_value", "secondary_scaled_value",
+];
+
+/**
+ * Decodes the seven segment pattern of a digit. Segments a-g are bits 2, 6, 4, 3, 0, 1 and 5
+ * of the pattern; bit 0 of the digit byte is the decimal point and not part of the pattern.
+ *
+ * # Arguments
+ * `pattern` - The digit byte shifted right by one.
+ *
+ * # Returns
+ * Some(character) for known patterns, with a space for a blank digit, otherwise None.
+ */
+fn segment(pattern: u8) -> Option<char> {
+    match pattern {
+        0x00 => Some(' '),
+        0x5f => Some('0'),
+        0x50 => Some('1'),
+        0x6d => Some('2'),
+        0x7c => Some('3'),
+        0x72 => Some('4'),
+        0x3e => Some('5'),
+        0x3f => Some('6'),
+        0x54 => Some('7'),
+        0x7f => Some('8'),
+        0x7e => Some('9'),
+        0x20 => Some('-'),
+        0x0f => Some('C'),
+        0x79 => Some('d'),
+        0x2f => Some('E'),
+        0x27 => Some('F'),
+        0x10 => Some('i'),
+        0x0b => Some('L'),
+        0x31 => Some('n'),
+        0x39 => Some('o'),
+        0x21 => Some('r'),
+        _ => None,
+    }
+}
+
+/**
+ * One of the two displays of the meter.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct BrymenDisplay {
+    /**
+     * The displayed text, e.g. `-1.23456`, `0.L` or `diod`.
+     */
+    pub display_value: String,
+    pub overload: bool,
+    pub decimal_value: Option<f64>,
+    pub display_unit: String,
+    /**
+     * `AC`, `DC`, `AC+DC` or empty.
+     */
+    pub coupling: String,
+}
+
+impl BrymenDisplay {
+    /**
+     * Decodes a display.
+     *
+     * # Arguments
+     * `frame` - The frame.
+     * `sign` - The byte and mask of the minus sign.
+     * `digits` - The bytes of the digits.
+     * `units` - The unit annunciators.
+     * `coupling` - The AC and DC annunciators.
+     *
+     * # Returns
+     * A Result containing Some(BrymenDisplay), None if the display is blank, or an ApplicationError
+     * if a digit has an unknown segment pattern.
+     */
+    fn parse(
+        frame: &[u8],
+        sign: (usize, u8),
+        digits: std::ops::Range<usize>,
+        units: &[(usize, u8, &str)],
+        coupling: &[(usize, u8, &str)],
+    ) -> Result<Option<Self>, ApplicationError> {
+        let mut text = String::new();
+        for (position, index) in digits.enumerate() {
+            let byte = frame[index];
+            if position > 0 && byte & 0x01 != 0 {
+                text.push('.');
+            }
+            text.push(segment(byte >> 1).ok_or_else(|| {
+                ApplicationError::Hid(format!("Unknown BM869s segment pattern {:#04x} in byte {}", byte >> 1, index))
+            })?);
+        }
+        let text = text.trim();
+        if text.is_empty() {
+            return Ok(None);
+        }
+        let negative = frame[sign.0] & sign.1 != 0;
+        let display_value = if negative { format!("-{}", text) } else { text.to_string() };
+        let overload = text.contains("0.L");
+        let active = |(index, mask, _): &&(usize, u8, &str)| frame[*index] & mask != 0;
+        Ok(Some(Self {
+            decimal_value: display_value.parse().ok(),
+            display_value,
+            overload,
+            display_unit: units.iter().filter(active).map(|(_, _, unit)| *unit).collect(),
+            coupling: coupling
+                .iter()
+                .filter(active)
+                .map(|(_, _, coupling)| *coupling)
+                .collect::<Vec<&str>>()
+                .join("+"),
+        }))
+    }
+
+    /**
+     * Returns the typed measurement of the display.
+     *
+     * # Arguments
+     * `flags` - Active instrument flags.
+     *
+     * # Returns
+     * The measurement, or None for overload, text and unknown units.
+     */
+    fn measurement(&self, flags: Vec<String>) -> Option<Measurement> {
+        Measurement::from_display(self.decimal_value?, &self.display_unit, flags)
+    }
+}
+
+/**
+ * Represents a frame of a Brymen BM869s / BM867s with the BU-86X IR-to-USB cable. The frame
+ * mirrors the LCD: each digit byte holds a seven segment pattern and a decimal point, and the
+ * other bytes hold annunciator bits. Both the main and the secondary display are decoded.
+ */
+#[derive(Debug)]
+pub struct BrymenReading {
+    pub main: BrymenDisplay,
+    /**
+     * The secondary display, None when it is blank.
+     */
+    pub secondary: Option<BrymenDisplay>,
+    pub flags: Vec<String>,
+    pub original_bytes: Vec<u8>,
+}
+
+impl BrymenReading {
+    /**
+     * Parses a frame.
+     *
+     * # Arguments
+     * `frame` - The 24 frame bytes.
+     *
+     * # Returns
+     * A Result containing the BrymenReading or an ApplicationError if the frame is invalid.
+     */
+    pub fn parse(frame: &[u8]) -> Result<Self, ApplicationError> {
+        if frame.len() != BRYMEN_FRAME_LENGTH {
+            return Err(ApplicationError::Hid(format!("Invalid BM869s frame {:02x?}", frame)));
+        }
+        let main = BrymenDisplay::parse(frame, MAIN_SIGN, MAIN_DIGITS, &MAIN_UNITS, &MAIN_COUPLING)?
+            .ok_or_else(|| ApplicationError::Hid("BM869s main display is blank".into()))?;
+        let secondary =
+            BrymenDisplay::parse(frame, SECONDARY_SIGN, SECONDARY_DIGITS, &SECONDARY_UNITS, &SECONDARY_COUPLING)?;
+        let flags = FLAGS
+            .iter()
+            .filter(|(index, mask, _)| frame[*index] & mask != 0)
+            .map(|(_, _, flag)| flag.to_string())
+            .collect();
+        Ok(Self {
+            main,
+            secondary,
+            flags,
+            original_bytes: frame.to_vec(),
+        })
+    }
+
+    /**
+     * Returns the typed measurement of the secondary display.
+     *
+     * # Returns
+     * The measurement, or None if the secondary display is blank or shows no value.
+     */
+    pub fn get_secondary_measurement(&self) -> Option<Measurement> {
+        self.secondary.as_ref()?.measurement(self.flags.clone())
+    }
+}
+
+impl Reading for BrymenReading {
+    /**
+     * Returns the names of the CSV fields.
+     *
+     * # Returns
+     * A Result containing the CSV header fields or an ApplicationError.
+     */
+    fn get_csv_header(&self) -> Result<Vec<String>, ApplicationError> {
+        Ok(CSV_HEADER.iter().map(|field| field.to_string()).collect())
+    }
+
+    /**
+     * Returns the values of both displays as CSV fields. The secondary fields are empty when
+     * the secondary display is blank.
+     *
+     * # Returns
+     * A Result containing the CSV field values or an ApplicationError.
+     */
+    fn get_csv(&self) -> Result<Vec<String>, ApplicationError> {
+        let secondary = |field: fn(&BrymenDisplay) -> String| self.secondary.as_ref().map(field).unwrap_or_default();
+        Ok(vec![
+            self.main.display_value.clone(),
+            self.main.overload.to_string(),
+            self.main.decimal_value.map(|value| value.to_string()).unwrap_or_default(),
+            self.main.display_unit.clone(),
+            self.main.coupling.clone(),
+            secondary(|display| display.display_value.clone()),
+            secondary(|display| display.overload.to_string()),
+            secondary(|display| display.decimal_value.map(|value| value.to_string()).unwrap_or_default()),
+            secondary(|display| display.display_unit.clone()),
+            secondary(|display| display.coupling.clone()),
+            self.flags.join(" "),
+            self.get_measurement()
+                .map(|measurement| measurement.value.to_string())
+                .unwrap_or_default(),
+            self.get_secondary_measurement()
+                .map(|measurement| measurement.value.to_string())
+                .unwrap_or_default(),
+        ])
+    }
+
+    /**
+     * Returns the raw frame as a byte vector.
+     *
+     * # Returns
+     * A Result containing a byte vector with the raw data or an ApplicationError.
+     */
+    fn get_raw(&self) -> Result<Vec<u8>, ApplicationError> {
+        Ok(self.original_bytes.clone())
+    }
+
+    /**
+     * Returns both displays as a String.
+     *
+     * # Returns
+     * A Result containing a String with the values or an ApplicationError.
+     */
+    fn get_raw_string(&self) -> Result<String, ApplicationError> {
+        let display = |display: &BrymenDisplay| {
+            format!("{} {} {}", display.display_value, display.display_unit, display.coupling)
+                .trim()
+                .to_string()
+        };
+        Ok(match &self.secondary {
+            Some(secondary) => format!("{} / {}", display(&self.main), display(secondary)),
+            None => display(&self.main),
+        })
+    }
+
+    /**
+     * Returns the typed measurement of the main display.
+     *
+     * # Returns
+     * The measurement, or None for overload and text.
+     */
+    fn get_measurement(&self) -> Option<Measurement> {
+        self.main.measurement(self.flags.clone())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /**
+     * Encodes a digit as the meter does, with the decimal point in bit 0.
+     */
+    fn digit(character: char, point: bool) -> u8 {
+        let pattern = (0..=0x7f).find(|pattern| segment(*pattern) == Some(character)).unwrap();
+        (pattern << 1) | point as u8
+    }
+
+    fn frame(main: &str, secondary: &str) -> [u8; BRYMEN_FRAME_LENGTH] {
+        let mut frame = [0; BRYMEN_FRAME_LENGTH];
+        for (start, text) in [(MAIN_DIGITS.start, main), (SECONDARY_DIGITS.start, secondary)] {
+            let mut index = start;
+            let mut point = false;
+            for character in text.chars() {
+                if character == '.' {
+                    point = true;
+                    continue;
+                }
+                frame[index] = digit(character, point);
+                point = false;
+                index += 1;
+            }
+        }
+        frame
+    }
+
+    #[test]
+    fn test_parse_dual_display() {
+        // -1.23456 V DC, 50.00 Hz AC on the secondary display, auto
+        let mut frame = frame("1.23456", "50.00");
+        frame[2] |= 0x80;
+        frame[0] |= 0x01;
+        frame[15] |= 0x01;
+        frame[18] |= 0x04;
+        frame[19] |= 0x02;
+        frame[1] |= 0x01;
+        let reading = BrymenReading::parse(&frame).unwrap();
+        assert_eq!(reading.main.display_value, "-1.23456");
+        assert_eq!(reading.main.display_unit, "V");
+        assert_eq!(reading.main.coupling, "DC");
+        assert_eq!(reading.flags, vec!["auto"]);
+        let secondary = reading.secondary.as_ref().unwrap();
+        assert_eq!(secondary.display_value, "50.00");
+        assert_eq!(secondary.display_unit, "Hz");
+        assert_eq!(reading.get_measurement().unwrap().value, -1.23456);
+        assert_eq!(reading.get_secondary_measurement().unwrap().value, 50.0);
+        assert_eq!(reading.get_raw_string().unwrap(), "-1.23456 V DC / 50.00 Hz AC");
+    }
+
+    #[test]
+    fn test_parse_prefix_and_blank_secondary() {
+        // 4.7000 kΩ
+        let mut frame = frame(" 4.7000", "");
+        frame[14] |= 0x08;
+        frame[15] |= 0x04;
+        let reading = BrymenReading::parse(&frame).unwrap();
+        assert_eq!(reading.main.display_value, "4.7000");
+        assert_eq!(reading.main.display_unit, "kΩ");
+        assert!(reading.secondary.is_none());
+        assert!((reading.get_measurement().unwrap().value - 4700.0).abs() < 1e-9);
+        assert_eq!(reading.get_csv().unwrap()[5], "");
+    }
+
+    #[test]
+    fn test_overload_and_invalid() {
+        let mut frame = frame("  0.L  ", "");
+        frame[15] |= 0x04;
+        let reading = BrymenReading::parse(&frame).unwrap();
+        assert!(reading.main.overload);
+        assert!(reading.get_measurement().is_none());
+        let mut invalid = frame;
+        invalid[4] = 0x55 << 1;
+        assert!(BrymenReading::parse(&invalid).is_err());
+        assert!(BrymenReading::parse(&frame[..8]).is_err());
+        assert!(BrymenReading::parse(&[0; BRYMEN_FRAME_LENGTH]).is_err());
+    }
+}
@@ -0,0 +1,273 @@
+use serde::Deserialize;
+
+use crate::{
+    error::ApplicationError,
+    instruments::reading::{measurement::parse_unit, Measurement, Reading, Samples},
+};
+
+/**
+ * Fields holding the decoded decimal value in the displayed unit.
+ */
+const DECIMAL_FIELDS: [&str; 2] = ["decimal_value", "value"];
+/**
+ * Field holding the decimal value in the base unit.
+ */
+const SCALED_FIELD: &str = "scaled_value";
+/**
+ * Field holding the decimal value before the correction.
+ */
+const UNCALIBRATED_FIELD: &str = "uncalibrated_value";
+/**
+ * Fields holding the measurement mode a calibration entry may be limited to.
+ */
+const MODE_FIELDS: [&str; 2] = ["mode", "function"];
+
+fn unity() -> f64 {
+    1.0
+}
+
+/**
+ * A calibration entry of a device profile: the decoded decimal value is corrected to
+ * `value * gain + offset`, with the offset in the displayed unit, e.g. `mV` for DCmV.
+ */
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Calibration {
+    /**
+     * Mode the entry applies to, e.g. `DCV`. Entries without a mode apply to every mode
+     * without an entry of its own.
+     */
+    pub mode: Option<String>,
+    #[serde(default = "unity")]
+    pub gain: f64,
+    #[serde(default)]
+    pub offset: f64,
+}
+
+impl Calibration {
+    /**
+     * Finds the calibration entry of a mode.
+     *
+     * # Arguments
+     * `calibrations` - The calibration entries of the device.
+     * `mode` - The mode of the reading, if it has one.
+     *
+     * # Returns
+     * The entry of the mode, else the entry without a mode, or None.
+     */
+    pub fn select<'a>(calibrations: &'a [Calibration], mode: Option<&str>) -> Option<&'a Calibration> {
+        calibrations
+            .iter()
+            .find(|calibration| mode.is_some() && calibration.mode.as_deref() == mode)
+            .or_else(|| calibrations.iter().find(|calibration| calibration.mode.is_none()))
+    }
+
+    /**
+     * Corrects a value in the displayed unit.
+     *
+     * # Arguments
+     * `value` - The value.
+     *
+     * # Returns
+     * The corrected value.
+     */
+    pub fn correct(&self, value: f64) -> f64 {
+        value * self.gain + self.offset
+    }
+}
+
+/**
+ * Gets the mode of a reading from its `mode` or `function` field.
+ *
+ * # Arguments
+ * `reading` - The reading.
+ *
+ * # Returns
+ * A Result containing the mode, if the reading has one, or an ApplicationError.
+ */
+pub fn reading_mode(reading: &dyn Reading) -> Result<Option<String>, ApplicationError> {
+    let fields = reading.get_csv_header()?.into_iter().zip(reading.get_csv()?);
+    Ok(fields.into_iter().find(|(name, _)| MODE_FIELDS.contains(&name.as_str())).map(|(_, value)| value))
+}
+
+/**
+ * A reading corrected by a calibration entry. The `decimal_value` or `value` field, the
+ * `scaled_value` field and the typed measurement are corrected, and the value before the
+ * correction is appended as `uncalibrated_value`. Raw data is passed through unchanged.
+ */
+pub struct CalibratedReading {
+    pub calibration: Calibration,
+    pub reading: Box<dyn Reading>,
+}
+
+impl CalibratedReading {
+    /**
+     * Creates a new CalibratedReading.
+     *
+     * # Arguments
+     * `calibration` - The calibration entry.
+     * `reading` - The reading to correct.
+     *
+     * # Returns
+     * A new CalibratedReading instance.
+     */
+    pub fn new(calibration: Calibration, reading: Box<dyn Reading>) -> Self {
+        Self { calibration, reading }
+    }
+
+    /**
+     * Returns the factor converting the displayed unit of the reading to its base unit.
+     *
+     * # Returns
+     * The factor, 1 if the unit is unknown or a temperature.
+     */
+    fn factor(&self) -> f64 {
+        self.reading
+            .get_measurement()
+            .and_then(|measurement| parse_unit(&measurement.display_unit))
+            .map(|(_, factor)| factor)
+            .unwrap_or(1.0)
+    }
+}
+
+impl Reading for CalibratedReading {
+    /**
+     * Returns the fields of the reading followed by `uncalibrated_value`.
+     *
+     * # Returns
+     * A Result containing the CSV header fields or an ApplicationError.
+     */
+    fn get_csv_header(&self) -> Result<Vec<String>, ApplicationError> {
+        let mut header = self.reading.get_csv_header()?;
+        header.push(UNCALIBRATED_FIELD.into());
+        Ok(header)
+    }
+
+    /**
+     * Returns the corrected fields of the reading followed by the value before the correction.
+     * Empty values, e.g. for overload, stay empty.
+     *
+     * # Returns
+     * A Result containing the CSV field values or an ApplicationError.
+     */
+    fn get_csv(&self) -> Result<Vec<String>, ApplicationError> {
+        let header = self.reading.get_csv_header()?;
+        let mut uncalibrated = String::new();
+        let mut fields = Vec::new();
+        for (name, value) in header.iter().zip(self.reading.get_csv()?) {
+            let number = value.parse::<f64>().ok();
+            fields.push(match (name.as_str(), number) {
+                (name, Some(number)) if DECIMAL_FIELDS.contains(&name) => {
+                    uncalibrated = value;
+                    self.calibration.correct(number).to_string()
+                }
+                (SCALED_FIELD, Some(number)) => {
+                    (number * self.calibration.gain + self.calibration.offset * self.factor()).to_string()
+                }
+                _ => value,
+            });
+        }
+        fields.push(uncalibrated);
+        Ok(fields)
+    }
+
+    /**
+     * Returns the raw data of the reading as a byte vector.
+     *
+     * # Returns
+     * A Result containing a byte vector with the raw data or an ApplicationError.
+     */
+    fn get_raw(&self) -> Result<Vec<u8>, ApplicationError> {
+        self.reading.get_raw()
+    }
+
+    /**
+     * Returns the raw data of the reading as a String.
+     *
+     * # Returns
+     * A Result containing a String with the raw data or an ApplicationError.
+     */
+    fn get_raw_string(&self) -> Result<String, ApplicationError> {
+        self.reading.get_raw_string()
+    }
+
+    /**
+     * Returns the corrected typed measurement of the reading.
+     *
+     * # Returns
+     * The measurement, if the reading has one.
+     */
+    fn get_measurement(&self) -> Option<Measurement> {
+        let measurement = self.reading.get_measurement()?;
+        let value = self.calibration.correct(measurement.display_value());
+        Measurement::from_display(value, &measurement.display_unit, measurement.flags)
+    }
+
+    /**
+     * Returns the corrected samples of the reading.
+     *
+     * # Returns
+     * The samples, if the reading is a waveform.
+     */
+    fn get_samples(&self) -> Option<Samples> {
+        let mut samples = self.reading.get_samples()?;
+        for (_, value) in samples.points.iter_mut() {
+            *value = self.calibration.correct(*value);
+        }
+        Some(samples)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::instruments::reading::Unit161dReading;
+
+    fn reading(mode: u8, value: &[u8; 7]) -> Box<dyn Reading> {
+        let mut raw_data = vec![mode, b'0'];
+        raw_data.extend(value);
+        raw_data.extend([0, 0, 0, 0, 0]);
+        Box::new(Unit161dReading::parse(raw_data).unwrap())
+    }
+
+    #[test]
+    fn test_select() {
+        let calibrations = [
+            Calibration { mode: None, gain: 1.0, offset: 0.5 },
+            Calibration { mode: Some("DCV".into()), gain: 0.5, offset: 0.0 },
+        ];
+        assert_eq!(Calibration::select(&calibrations, Some("DCV")), Some(&calibrations[1]));
+        assert_eq!(Calibration::select(&calibrations, Some("ACV")), Some(&calibrations[0]));
+        assert_eq!(Calibration::select(&calibrations, None), Some(&calibrations[0]));
+        assert_eq!(Calibration::select(&calibrations[1..], Some("ACV")), None);
+    }
+
+    #[test]
+    fn test_calibrated_csv() {
+        let calibration = Calibration { mode: Some("DCmV".into()), gain: 0.5, offset: 1.0 };
+        let reading = reading(3, b"  100.0");
+        assert_eq!(reading_mode(reading.as_ref()).unwrap().as_deref(), Some("DCmV"));
+        let calibrated = CalibratedReading::new(calibration, reading);
+        let header = calibrated.get_csv_header().unwrap();
+        let fields = calibrated.get_csv().unwrap();
+        let field = |name: &str| fields[header.iter().position(|field| field == name).unwrap()].clone();
+        assert_eq!(field("display_value"), "100.0");
+        assert_eq!(field("decimal_value"), "51");
+        assert!((field("scaled_value").parse::<f64>().unwrap() - 0.051).abs() < 1e-12);
+        assert_eq!(field("uncalibrated_value"), "100");
+        let measurement = calibrated.get_measurement().unwrap();
+        assert!((measurement.value - 0.051).abs() < 1e-12);
+        assert_eq!(measurement.display_unit, "mV");
+        assert_eq!(calibrated.get_raw().unwrap()[2..9], *b"  100.0");
+    }
+
+    #[test]
+    fn test_overload_stays_empty() {
+        let calibration = Calibration { mode: None, gain: 2.0, offset: 0.0 };
+        let calibrated = CalibratedReading::new(calibration, reading(2, b"    .OL"));
+        let fields = calibrated.get_csv().unwrap();
+        assert_eq!(fields[5], "");
+        assert_eq!(fields.last().unwrap(), "");
+        assert!(calibrated.get_measurement().is_none());
+    }
+}
@@ -1,6 +1,21 @@
 use async_trait::async_trait;
 
-use crate::error::ApplicationError;
+use crate::{error::ApplicationError, instruments::reading::Measurement};
+
+/**
+ * Evenly spaced samples of a waveform.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct Samples {
+    /**
+     * Samples per second.
+     */
+    pub sample_rate: f64,
+    /**
+     * The samples as (time in s, value).
+     */
+    pub points: Vec<(f64, f64)>,
+}
 
 /**
  * Defines the Reading trait for measurement data returned by instruments.
@@ -8,12 +23,20 @@ use crate::error::ApplicationError;
 #[async_trait(?Send)]
 pub trait Reading {
     /**
-     * Returns the measurement data in CSV format.
+     * Returns the names of the CSV fields.
+     *
+     * # Returns
+     * A Result containing the CSV header fields or an ApplicationError.
+     */
+    fn get_csv_header(&self) -> Result<Vec<String>, ApplicationError>;
+
+    /**
+     * Returns the measurement data as CSV fields.
      *
      * # Returns
-     * A Result containing a String in CSV format or an ApplicationError.
+     * A Result containing the CSV field values or an ApplicationError.
      */
-    fn get_csv(&self) -> Result<String, ApplicationError>;
+    fn get_csv(&self) -> Result<Vec<String>, ApplicationError>;
 
     /**
      * Returns the raw measurement data as a byte vector.
@@ -30,4 +53,24 @@ pub trait Reading {
      * The raw measurement as a String.
      */
     fn get_raw_string(&self) -> Result<String, ApplicationError>;
+
+    /**
+     * Returns the reading as a typed measurement normalized to SI base units.
+     *
+     * # Returns
+     * Some(Measurement) for readings with a single value and known unit, otherwise None.
+     */
+    fn get_measurement(&self) -> Option<Measurement> {
+        None
+    }
+
+    /**
+     * Returns the samples of a waveform reading, for the waveform CSV and WAV exports.
+     *
+     * # Returns
+     * Some(Samples) for waveform readings, otherwise None.
+     */
+    fn get_samples(&self) -> Option<Samples> {
+        None
+    }
 }
\ No newline at end of file
@@ -0,0 +1,158 @@
+use std::time::Duration;
+
+use crate::{error::ApplicationError, instruments::reading::Reading};
+
+/**
+ * Summary of a battery discharge test at a constant current: the capacity drawn in mAh and the
+ * energy in mWh, integrated over the voltages logged during the test.
+ */
+#[derive(Debug, Default)]
+pub struct DischargeReading {
+    /**
+     * The discharge current in A.
+     */
+    pub current: f64,
+    pub samples: u32,
+    /**
+     * Time of the last voltage after the start of the discharge.
+     */
+    pub elapsed: Duration,
+    pub end_voltage: Option<f64>,
+    pub cutoff_reached: bool,
+    /**
+     * Energy in Wh up to the last voltage.
+     */
+    energy: f64,
+}
+
+impl DischargeReading {
+    /**
+     * Creates a new DischargeReading.
+     *
+     * # Arguments
+     * `current` - The discharge current in A.
+     *
+     * # Returns
+     * A new DischargeReading instance.
+     */
+    pub fn new(current: f64) -> Self {
+        Self { current, ..Default::default() }
+    }
+
+    /**
+     * Adds a logged voltage. The energy between two voltages is integrated with the average of
+     * the voltages.
+     *
+     * # Arguments
+     * `elapsed` - The time of the voltage after the start of the discharge.
+     * `voltage` - The battery voltage in V.
+     */
+    pub fn add(&mut self, elapsed: Duration, voltage: f64) {
+        if let Some(previous) = self.end_voltage {
+            let hours = elapsed.saturating_sub(self.elapsed).as_secs_f64() / 3600.0;
+            self.energy += (previous + voltage) / 2.0 * self.current * hours;
+        }
+        self.samples += 1;
+        self.elapsed = elapsed;
+        self.end_voltage = Some(voltage);
+    }
+
+    /**
+     * Returns the capacity drawn up to the last voltage.
+     *
+     * # Returns
+     * The capacity in mAh.
+     */
+    pub fn capacity_mah(&self) -> f64 {
+        self.current * self.elapsed.as_secs_f64() / 3600.0 * 1000.0
+    }
+
+    /**
+     * Returns the energy drawn up to the last voltage.
+     *
+     * # Returns
+     * The energy in mWh.
+     */
+    pub fn energy_mwh(&self) -> f64 {
+        self.energy * 1000.0
+    }
+}
+
+impl Reading for DischargeReading {
+    /**
+     * Returns the CSV header for the summary.
+     *
+     * # Returns
+     * A Result containing the CSV header fields or an ApplicationError.
+     */
+    fn get_csv_header(&self) -> Result<Vec<String>, ApplicationError> {
+        Ok(["current", "samples", "duration", "end_voltage", "capacity_mah", "energy_mwh", "cutoff_reached"]
+            .iter()
+            .map(|field| field.to_string())
+            .collect())
+    }
+
+    /**
+     * Returns the summary as CSV fields. The end voltage is empty without samples.
+     *
+     * # Returns
+     * A Result containing the CSV field values or an ApplicationError.
+     */
+    fn get_csv(&self) -> Result<Vec<String>, ApplicationError> {
+        Ok(vec![
+            self.current.to_string(),
+            self.samples.to_string(),
+            format!("{:.3}", self.elapsed.as_secs_f64()),
+            self.end_voltage.map(|voltage| voltage.to_string()).unwrap_or_default(),
+            format!("{:.3}", self.capacity_mah()),
+            format!("{:.3}", self.energy_mwh()),
+            self.cutoff_reached.to_string(),
+        ])
+    }
+
+    /**
+     * Returns the summary as CSV line, as the summary has no raw data.
+     *
+     * # Returns
+     * A Result containing a byte vector with the summary or an ApplicationError.
+     */
+    fn get_raw(&self) -> Result<Vec<u8>, ApplicationError> {
+        Ok(self.get_raw_string()?.into_bytes())
+    }
+
+    /**
+     * Returns the summary as CSV line, as the summary has no raw data.
+     *
+     * # Returns
+     * A Result containing a String with the summary or an ApplicationError.
+     */
+    fn get_raw_string(&self) -> Result<String, ApplicationError> {
+        Ok(self.get_csv()?.join(","))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_summary() {
+        let mut summary = DischargeReading::new(0.5);
+        summary.add(Duration::ZERO, 4.2);
+        summary.add(Duration::from_secs(1800), 3.8);
+        summary.add(Duration::from_secs(3600), 3.0);
+        summary.cutoff_reached = true;
+        assert_eq!(
+            summary.get_csv().unwrap(),
+            vec!["0.5", "3", "3600.000", "3", "500.000", "1850.000", "true"]
+        );
+    }
+
+    #[test]
+    fn test_empty_summary() {
+        assert_eq!(
+            DischargeReading::new(1.0).get_csv().unwrap(),
+            vec!["1", "0", "0.000", "", "0.000", "0.000", "false"]
+        );
+    }
+}
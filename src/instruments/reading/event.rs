@@ -0,0 +1,135 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::{error::ApplicationError, instruments::reading::Reading};
+
+// CSV field names, in the order returned by get_csv
+const CSV_HEADER: [&str; 4] = ["type", "timestamp", "kind", "message"];
+
+/**
+ * Enum representing tool-side events reported in the output stream.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventKind {
+    /// A command batch failed.
+    Error,
+    /// A failed command batch is retried.
+    Retry,
+    /// The device was reopened.
+    Reconnect,
+    /// A log statement in a script.
+    Log,
+}
+
+/**
+ * Represents a tool-side event, such as a transport error, in the output stream.
+ * Event records have the type `event` so they can be told apart from measurements.
+ */
+#[derive(Debug)]
+pub struct EventReading {
+    pub timestamp: SystemTime,
+    pub kind: EventKind,
+    pub message: String,
+}
+
+impl EventReading {
+    /**
+     * Creates a new EventReading timestamped now.
+     *
+     * # Arguments
+     * `kind` - The kind of event.
+     * `message` - A description of the event.
+     *
+     * # Returns
+     * A new EventReading instance.
+     */
+    pub fn new(kind: EventKind, message: String) -> Self {
+        Self {
+            timestamp: SystemTime::now(),
+            kind,
+            message,
+        }
+    }
+
+    /**
+     * Returns the timestamp as seconds since the Unix epoch.
+     *
+     * # Returns
+     * The timestamp with millisecond resolution.
+     */
+    fn unix_timestamp(&self) -> String {
+        let since_epoch = self.timestamp.duration_since(UNIX_EPOCH).unwrap_or_default();
+        format!("{}.{:03}", since_epoch.as_secs(), since_epoch.subsec_millis())
+    }
+}
+
+impl Reading for EventReading {
+    /**
+     * Returns the names of the CSV fields.
+     *
+     * # Returns
+     * A Result containing the CSV header fields or an ApplicationError.
+     */
+    fn get_csv_header(&self) -> Result<Vec<String>, ApplicationError> {
+        Ok(CSV_HEADER.iter().map(|field| field.to_string()).collect())
+    }
+
+    /**
+     * Returns the event as CSV fields.
+     *
+     * # Returns
+     * A Result containing the CSV field values or an ApplicationError.
+     */
+    fn get_csv(&self) -> Result<Vec<String>, ApplicationError> {
+        Ok(vec![
+            "event".to_string(),
+            self.unix_timestamp(),
+            format!("{:?}", self.kind).to_lowercase(),
+            self.message.clone(),
+        ])
+    }
+
+    /**
+     * Returns the event message as a byte vector.
+     *
+     * # Returns
+     * A Result containing a byte vector with the message or an ApplicationError.
+     */
+    fn get_raw(&self) -> Result<Vec<u8>, ApplicationError> {
+        Ok(self.get_raw_string()?.into_bytes())
+    }
+
+    /**
+     * Returns the event as a String.
+     *
+     * # Returns
+     * A Result containing a String with the event or an ApplicationError.
+     */
+    fn get_raw_string(&self) -> Result<String, ApplicationError> {
+        Ok(format!(
+            "type=event timestamp={} kind={} message={}",
+            self.unix_timestamp(),
+            format!("{:?}", self.kind).to_lowercase(),
+            self.message
+        ))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_event_csv() {
+        let event = EventReading {
+            timestamp: UNIX_EPOCH + Duration::from_millis(1700000000123),
+            kind: EventKind::Reconnect,
+            message: "USB Error: device gone".into(),
+        };
+        assert_eq!(
+            event.get_csv().unwrap(),
+            vec!["event", "1700000000.123", "reconnect", "USB Error: device gone"]
+        );
+        assert_eq!(event.get_csv_header().unwrap()[0], "type");
+    }
+}
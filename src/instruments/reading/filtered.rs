@@ -0,0 +1,144 @@
+use crate::{
+    error::ApplicationError,
+    instruments::reading::{Measurement, Reading},
+};
+
+/**
+ * Field holding the averaged value in the displayed unit of the last reading.
+ */
+const AVERAGE_FIELD: &str = "average";
+/**
+ * Field holding the number of readings in the average.
+ */
+const AVERAGE_SAMPLES_FIELD: &str = "average_samples";
+
+/**
+ * A moving average with --average. The fields of the last reading are kept and the average is
+ * appended; the typed measurement is the average, so assertions, metrics and VCD output see the
+ * filtered value. The fields are copied, so the last reading can still be written as taken.
+ */
+pub struct FilteredReading {
+    /**
+     * The averaged measurement, in the displayed unit of the last reading.
+     */
+    pub average: Measurement,
+    /**
+     * Number of readings averaged.
+     */
+    pub samples: usize,
+    pub header: Vec<String>,
+    pub fields: Vec<String>,
+    pub original_bytes: Vec<u8>,
+}
+
+impl FilteredReading {
+    /**
+     * Creates a new FilteredReading.
+     *
+     * # Arguments
+     * `average` - The averaged measurement.
+     * `samples` - The number of readings averaged.
+     * `reading` - The last reading of the average.
+     *
+     * # Returns
+     * A Result containing a new FilteredReading instance or an ApplicationError if the fields
+     * of the reading cannot be read.
+     */
+    pub fn new(average: Measurement, samples: usize, reading: &dyn Reading) -> Result<Self, ApplicationError> {
+        Ok(Self {
+            average,
+            samples,
+            header: reading.get_csv_header()?,
+            fields: reading.get_csv()?,
+            original_bytes: reading.get_raw()?,
+        })
+    }
+
+    /**
+     * Returns the appended fields with empty values, for the raw readings written next to the
+     * filtered ones with --keep-raw so every record has the same columns.
+     *
+     * # Returns
+     * The field names and empty values.
+     */
+    pub fn empty_fields() -> Vec<(String, String)> {
+        [AVERAGE_FIELD, AVERAGE_SAMPLES_FIELD]
+            .iter()
+            .map(|name| (name.to_string(), String::new()))
+            .collect()
+    }
+}
+
+impl Reading for FilteredReading {
+    /**
+     * Returns the fields of the reading followed by `average` and `average_samples`.
+     *
+     * # Returns
+     * A Result containing the CSV header fields or an ApplicationError.
+     */
+    fn get_csv_header(&self) -> Result<Vec<String>, ApplicationError> {
+        let mut header = self.header.clone();
+        header.extend([AVERAGE_FIELD.to_string(), AVERAGE_SAMPLES_FIELD.to_string()]);
+        Ok(header)
+    }
+
+    /**
+     * Returns the fields of the reading followed by the average and the number of readings in it.
+     *
+     * # Returns
+     * A Result containing the CSV field values or an ApplicationError.
+     */
+    fn get_csv(&self) -> Result<Vec<String>, ApplicationError> {
+        let mut fields = self.fields.clone();
+        fields.extend([self.average.display_value().to_string(), self.samples.to_string()]);
+        Ok(fields)
+    }
+
+    /**
+     * Returns the raw data of the last reading as a byte vector.
+     *
+     * # Returns
+     * A Result containing a byte vector with the raw data or an ApplicationError.
+     */
+    fn get_raw(&self) -> Result<Vec<u8>, ApplicationError> {
+        Ok(self.original_bytes.clone())
+    }
+
+    /**
+     * Returns the average and its unit as a String.
+     *
+     * # Returns
+     * A Result containing a String with the average or an ApplicationError.
+     */
+    fn get_raw_string(&self) -> Result<String, ApplicationError> {
+        Ok(format!("{} {}", self.average.display_value(), self.average.display_unit))
+    }
+
+    /**
+     * Returns the averaged measurement.
+     *
+     * # Returns
+     * The average.
+     */
+    fn get_measurement(&self) -> Option<Measurement> {
+        Some(self.average.clone())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::instruments::{command::DmmFunction, reading::KeysightDmmReading};
+
+    #[test]
+    fn test_filtered_csv() {
+        let reading = KeysightDmmReading::parse(DmmFunction::VoltageDc, b"+1.20000000E+00\n").unwrap().remove(0);
+        let average = Measurement::from_display(1250.0, "mV", vec![]).unwrap();
+        let filtered = FilteredReading::new(average, 4, &reading).unwrap();
+        assert_eq!(filtered.get_csv_header().unwrap(), vec!["function", "value", "unit", "overload", "average", "average_samples"]);
+        assert_eq!(filtered.get_csv().unwrap(), vec!["VoltageDc", "1.2", "V", "false", "1250", "4"]);
+        assert_eq!(filtered.get_raw().unwrap(), b"+1.20000000E+00");
+        assert_eq!(filtered.get_raw_string().unwrap(), "1250 mV");
+        assert!((filtered.get_measurement().unwrap().value - 1.25).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,383 @@
+use crate::{
+    error::ApplicationError,
+    instruments::reading::{Measurement, Reading},
+};
+
+/**
+ * Number of fields of each reading in a QDDA response.
+ */
+const QDDA_READING_FIELDS: usize = 9;
+
+// CSV field names, in the order returned by get_csv
+const CSV_HEADER: [&str; 13] = [
+    "function", "secondary_function", "range", "modes", "value", "unit", "display_value", "display_unit",
+    "state", "secondary_value", "secondary_unit", "secondary_state", "scaled_value",
+];
+
+/**
+ * Converts a Fluke unit to a displayed unit.
+ *
+ * # Arguments
+ * `unit` - The Fluke unit, e.g. `VDC` or `CEL`.
+ *
+ * # Returns
+ * The unit, e.g. `V` or `°C`; unknown units are returned unchanged.
+ */
+fn display_unit(unit: &str) -> &str {
+    match unit {
+        "VDC" | "VAC" | "V_AC_PLUS_DC" => "V",
+        "ADC" | "AAC" | "A_AC_PLUS_DC" => "A",
+        "OHM" => "Ω",
+        "SIE" => "S",
+        "F" => "F",
+        "HZ" => "Hz",
+        "S" => "s",
+        "CEL" => "°C",
+        "FAR" => "°F",
+        "PCT" => "%",
+        "DBV" => "dBV",
+        "DBM" => "dBm",
+        unit => unit,
+    }
+}
+
+/**
+ * Gets the SI prefix of a unit multiplier.
+ *
+ * # Arguments
+ * `multiplier` - The power of ten of the displayed unit.
+ *
+ * # Returns
+ * The prefix, empty for unknown multipliers.
+ */
+fn prefix(multiplier: i32) -> &'static str {
+    match multiplier {
+        -12 => "p",
+        -9 => "n",
+        -6 => "µ",
+        -3 => "m",
+        3 => "k",
+        6 => "M",
+        9 => "G",
+        _ => "",
+    }
+}
+
+/**
+ * A value reported by a Fluke 287 / 289, always in the base unit of the Fluke unit.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct FlukeValue {
+    /**
+     * `PRIMARY`, `SECONDARY`, `LIVE`, `MINIMUM`, ... for QDDA readings, empty for QM.
+     */
+    pub reading_id: String,
+    /**
+     * The value, None unless the state is NORMAL, e.g. for overload.
+     */
+    pub value: Option<f64>,
+    /**
+     * The Fluke unit, e.g. `VDC`.
+     */
+    pub unit: String,
+    /**
+     * The value as displayed, scaled to the display unit.
+     */
+    pub display_value: String,
+    pub display_unit: String,
+    /**
+     * `NORMAL`, `OL`, `OL_MINUS`, `INVALID`, ...
+     */
+    pub state: String,
+    pub attribute: String,
+}
+
+impl FlukeValue {
+    /**
+     * Creates a value.
+     *
+     * # Arguments
+     * `reading_id` - The reading id.
+     * `value` - The value in the base unit as sent by the meter.
+     * `unit` - The Fluke unit.
+     * `multiplier` - The power of ten of the displayed unit and the number of decimals, if known.
+     * `state` - The state.
+     * `attribute` - The attribute.
+     *
+     * # Returns
+     * A Result containing the FlukeValue or an ApplicationError if the value is not a number.
+     */
+    fn new(
+        reading_id: &str,
+        value: &str,
+        unit: &str,
+        multiplier: Option<(i32, usize)>,
+        state: &str,
+        attribute: &str,
+    ) -> Result<Self, ApplicationError> {
+        let number: f64 = value
+            .trim()
+            .parse()
+            .map_err(|_| ApplicationError::Command(format!("Invalid Fluke value {}", value)))?;
+        let normal = state == "NORMAL";
+        let base_unit = display_unit(unit);
+        let (display_value, display_unit) = match (normal, multiplier) {
+            (false, _) => (state.replace('_', " "), base_unit.to_string()),
+            (true, Some((multiplier, decimals))) => (
+                format!("{:.*}", decimals, number / 10f64.powi(multiplier)),
+                format!("{}{}", prefix(multiplier), base_unit),
+            ),
+            (true, None) => (value.trim().to_string(), base_unit.to_string()),
+        };
+        Ok(Self {
+            reading_id: reading_id.to_string(),
+            value: normal.then_some(number),
+            unit: unit.to_string(),
+            display_value,
+            display_unit,
+            state: state.to_string(),
+            attribute: attribute.to_string(),
+        })
+    }
+
+    /**
+     * Returns the typed measurement of the value.
+     *
+     * # Returns
+     * The measurement, or None if the value is invalid or has no SI unit.
+     */
+    fn measurement(&self) -> Option<Measurement> {
+        Measurement::from_display(self.value?, display_unit(&self.unit), Vec::new())
+    }
+}
+
+/**
+ * Represents the response to a Fluke 287 / 289 `QM` or `QDDA` query. QM only returns the
+ * primary value; QDDA returns the function, range, active modes and every value on the
+ * display, of which the primary and secondary value are kept.
+ */
+#[derive(Debug)]
+pub struct FlukeReading {
+    /**
+     * The primary function, e.g. `V_DC`, empty for QM.
+     */
+    pub function: String,
+    pub secondary_function: String,
+    /**
+     * `AUTO` or `MANUAL` and the range number, empty for QM.
+     */
+    pub range: String,
+    pub modes: Vec<String>,
+    pub primary: FlukeValue,
+    pub secondary: Option<FlukeValue>,
+    pub original_bytes: Vec<u8>,
+}
+
+impl FlukeReading {
+    /**
+     * Parses a QM response, `value,unit,state,attribute`.
+     *
+     * # Arguments
+     * `response` - The data line of the response without the acknowledge line.
+     *
+     * # Returns
+     * A Result containing the FlukeReading or an ApplicationError if the response is invalid.
+     */
+    pub fn parse_qm(response: &[u8]) -> Result<Self, ApplicationError> {
+        let text = String::from_utf8_lossy(response);
+        let fields: Vec<&str> = text.trim().split(',').collect();
+        let [value, unit, state, attribute] = fields[..] else {
+            return Err(ApplicationError::Command(format!("Invalid QM response {}", text.trim())));
+        };
+        Ok(Self {
+            function: String::new(),
+            secondary_function: String::new(),
+            range: String::new(),
+            modes: Vec::new(),
+            primary: FlukeValue::new("", value, unit, None, state, attribute)?,
+            secondary: None,
+            original_bytes: response.to_vec(),
+        })
+    }
+
+    /**
+     * Parses a QDDA response: primary function, secondary function, auto range state, base
+     * unit, range number, range multiplier, lightning bolt, min max start time, the number of
+     * modes and the modes, then the number of readings and 9 fields per reading: id, value,
+     * unit, unit multiplier, decimals, display digits, state, attribute and timestamp.
+     *
+     * # Arguments
+     * `response` - The data line of the response without the acknowledge line.
+     *
+     * # Returns
+     * A Result containing the FlukeReading or an ApplicationError if the response is invalid.
+     */
+    pub fn parse_qdda(response: &[u8]) -> Result<Self, ApplicationError> {
+        let text = String::from_utf8_lossy(response);
+        let fields: Vec<&str> = text.trim().split(',').map(|field| field.trim()).collect();
+        let invalid = || ApplicationError::Command(format!("Invalid QDDA response {}", text.trim()));
+        let count = |index: usize| -> Result<usize, ApplicationError> {
+            fields.get(index).and_then(|field| field.parse().ok()).ok_or_else(invalid)
+        };
+        let mode_count = count(8)?;
+        let modes: Vec<String> = fields
+            .get(9..9 + mode_count)
+            .ok_or_else(invalid)?
+            .iter()
+            .map(|mode| mode.to_string())
+            .collect();
+        let readings_index = 9 + mode_count;
+        let reading_count = count(readings_index)?;
+        let reading_fields = fields
+            .get(readings_index + 1..readings_index + 1 + reading_count * QDDA_READING_FIELDS)
+            .ok_or_else(invalid)?;
+        let values = reading_fields
+            .chunks_exact(QDDA_READING_FIELDS)
+            .map(|reading| {
+                let multiplier = reading[3].parse().map_err(|_| invalid())?;
+                let decimals = reading[4].parse().map_err(|_| invalid())?;
+                FlukeValue::new(reading[0], reading[1], reading[2], Some((multiplier, decimals)), reading[6], reading[7])
+            })
+            .collect::<Result<Vec<FlukeValue>, ApplicationError>>()?;
+        let find = |id: &str| values.iter().find(|value| value.reading_id == id).cloned();
+        let primary = find("PRIMARY")
+            .or_else(|| find("LIVE"))
+            .ok_or_else(|| ApplicationError::Command(format!("No primary reading in QDDA response {}", text.trim())))?;
+        Ok(Self {
+            function: fields[0].to_string(),
+            secondary_function: fields[1].to_string(),
+            range: format!("{} {}", fields[2], fields[4]),
+            modes,
+            primary,
+            secondary: find("SECONDARY"),
+            original_bytes: response.to_vec(),
+        })
+    }
+}
+
+impl Reading for FlukeReading {
+    /**
+     * Returns the names of the CSV fields.
+     *
+     * # Returns
+     * A Result containing the CSV header fields or an ApplicationError.
+     */
+    fn get_csv_header(&self) -> Result<Vec<String>, ApplicationError> {
+        Ok(CSV_HEADER.iter().map(|field| field.to_string()).collect())
+    }
+
+    /**
+     * Returns the function and values as CSV fields. The secondary fields are empty when the
+     * meter shows no secondary value.
+     *
+     * # Returns
+     * A Result containing the CSV field values or an ApplicationError.
+     */
+    fn get_csv(&self) -> Result<Vec<String>, ApplicationError> {
+        let value = |value: Option<f64>| value.map(|value| value.to_string()).unwrap_or_default();
+        let secondary = self.secondary.as_ref();
+        Ok(vec![
+            self.function.clone(),
+            self.secondary_function.clone(),
+            self.range.clone(),
+            self.modes.join(" "),
+            value(self.primary.value),
+            self.primary.unit.clone(),
+            self.primary.display_value.clone(),
+            self.primary.display_unit.clone(),
+            self.primary.state.clone(),
+            value(secondary.and_then(|secondary| secondary.value)),
+            secondary.map(|secondary| secondary.unit.clone()).unwrap_or_default(),
+            secondary.map(|secondary| secondary.state.clone()).unwrap_or_default(),
+            self.get_measurement()
+                .map(|measurement| measurement.value.to_string())
+                .unwrap_or_default(),
+        ])
+    }
+
+    /**
+     * Returns the raw response as a byte vector.
+     *
+     * # Returns
+     * A Result containing a byte vector with the raw data or an ApplicationError.
+     */
+    fn get_raw(&self) -> Result<Vec<u8>, ApplicationError> {
+        Ok(self.original_bytes.clone())
+    }
+
+    /**
+     * Returns the displayed values and units as a String.
+     *
+     * # Returns
+     * A Result containing a String with the values or an ApplicationError.
+     */
+    fn get_raw_string(&self) -> Result<String, ApplicationError> {
+        let primary = format!("{} {}", self.primary.display_value, self.primary.display_unit);
+        Ok(match &self.secondary {
+            Some(secondary) => format!("{} / {} {}", primary, secondary.display_value, secondary.display_unit),
+            None => primary,
+        })
+    }
+
+    /**
+     * Returns the typed measurement of the primary value.
+     *
+     * # Returns
+     * The measurement, or None if the value is invalid.
+     */
+    fn get_measurement(&self) -> Option<Measurement> {
+        self.primary.measurement().map(|mut measurement| {
+            measurement.flags = self.modes.iter().map(|mode| mode.to_lowercase()).collect();
+            measurement
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_qm() {
+        let reading = FlukeReading::parse_qm(b"-1.2345E-3,VDC,NORMAL,NONE\r").unwrap();
+        assert_eq!(reading.primary.value, Some(-0.0012345));
+        assert_eq!(reading.primary.display_unit, "V");
+        assert_eq!(reading.get_measurement().unwrap().value, -0.0012345);
+        let reading = FlukeReading::parse_qm(b"9.99999999E+37,OHM,OL,NONE\r").unwrap();
+        assert_eq!(reading.primary.value, None);
+        assert_eq!(reading.primary.display_value, "OL");
+        assert!(FlukeReading::parse_qm(b"1.0,VDC\r").is_err());
+    }
+
+    #[test]
+    fn test_parse_qdda() {
+        let response = b"V_AC,HZ,AUTO,VAC,2,0,OFF,0.000,1,HOLD,2,\
+            PRIMARY,2.3012E2,VAC,0,2,5,NORMAL,NONE,1700000000.123,\
+            SECONDARY,5.0012E1,HZ,0,3,5,NORMAL,NONE,1700000000.123\r";
+        let reading = FlukeReading::parse_qdda(response).unwrap();
+        assert_eq!(reading.function, "V_AC");
+        assert_eq!(reading.range, "AUTO 2");
+        assert_eq!(reading.modes, vec!["HOLD"]);
+        assert_eq!(reading.primary.display_value, "230.12");
+        assert_eq!(reading.primary.display_unit, "V");
+        let secondary = reading.secondary.as_ref().unwrap();
+        assert_eq!(secondary.display_value, "50.012");
+        assert_eq!(secondary.display_unit, "Hz");
+        let measurement = reading.get_measurement().unwrap();
+        assert_eq!(measurement.value, 230.12);
+        assert_eq!(measurement.flags, vec!["hold"]);
+        assert_eq!(reading.get_raw_string().unwrap(), "230.12 V / 50.012 Hz");
+    }
+
+    #[test]
+    fn test_parse_qdda_multiplier() {
+        let response = b"OHMS,NONE,MANUAL,OHM,4,3,OFF,0.000,0,1,\
+            LIVE,4.7012E3,OHM,3,4,5,NORMAL,NONE,1700000000.123\r";
+        let reading = FlukeReading::parse_qdda(response).unwrap();
+        assert_eq!(reading.primary.display_value, "4.7012");
+        assert_eq!(reading.primary.display_unit, "kΩ");
+        assert!(reading.secondary.is_none());
+        assert!(FlukeReading::parse_qdda(b"V_DC,NONE,AUTO,VDC,1,0,OFF,0.000,0,1,PRIMARY,1.0\r").is_err());
+        assert!(FlukeReading::parse_qdda(b"V_DC,NONE,AUTO,VDC,1,0,OFF,0.000,0,0\r").is_err());
+    }
+}
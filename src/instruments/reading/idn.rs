@@ -0,0 +1,141 @@
+use crate::{error::ApplicationError, instruments::reading::Reading};
+
+/**
+ * Identity of a SCPI instrument as reported by `*IDN?`.
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Identity {
+    pub manufacturer: String,
+    pub model: String,
+    pub serial_number: String,
+    pub firmware: String,
+}
+
+impl Identity {
+    /**
+     * Parses an `*IDN?` response of the form `Manufacturer,Model,Serial,Firmware`.
+     *
+     * # Arguments
+     * `response` - The raw `*IDN?` response.
+     *
+     * # Returns
+     * Some(Identity) if the response has four fields, otherwise None.
+     */
+    pub fn parse(response: &[u8]) -> Option<Self> {
+        let text = String::from_utf8_lossy(response);
+        let fields: Vec<&str> = text.trim().splitn(4, ',').map(|field| field.trim()).collect();
+        match fields[..] {
+            [manufacturer, model, serial_number, firmware] => Some(Self {
+                manufacturer: manufacturer.to_string(),
+                model: model.to_string(),
+                serial_number: serial_number.to_string(),
+                firmware: firmware.to_string(),
+            }),
+            _ => None,
+        }
+    }
+}
+
+/**
+ * A `*IDN?` response split into manufacturer, model, serial number and firmware.
+ */
+#[derive(Debug)]
+pub struct IdnReading {
+    identity: Identity,
+    data: Vec<u8>,
+}
+
+impl IdnReading {
+    /**
+     * Parses a `*IDN?` response.
+     *
+     * # Arguments
+     * `data` - The raw response.
+     *
+     * # Returns
+     * A Result containing the IdnReading or an ApplicationError if the response does not have
+     * four fields.
+     */
+    pub fn parse(data: Vec<u8>) -> Result<Self, ApplicationError> {
+        let identity = Identity::parse(&data).ok_or_else(|| {
+            ApplicationError::Command(format!(
+                "Not an *IDN? response: {}",
+                String::from_utf8_lossy(&data).trim()
+            ))
+        })?;
+        Ok(Self { identity, data })
+    }
+}
+
+impl Reading for IdnReading {
+    /**
+     * Returns the CSV header for the identity fields.
+     *
+     * # Returns
+     * A Result containing the CSV header fields or an ApplicationError.
+     */
+    fn get_csv_header(&self) -> Result<Vec<String>, ApplicationError> {
+        Ok(vec![
+            "manufacturer".into(),
+            "model".into(),
+            "serial_number".into(),
+            "firmware".into(),
+        ])
+    }
+
+    /**
+     * Returns the identity fields.
+     *
+     * # Returns
+     * A Result containing the CSV field values or an ApplicationError.
+     */
+    fn get_csv(&self) -> Result<Vec<String>, ApplicationError> {
+        Ok(vec![
+            self.identity.manufacturer.clone(),
+            self.identity.model.clone(),
+            self.identity.serial_number.clone(),
+            self.identity.firmware.clone(),
+        ])
+    }
+
+    /**
+     * Returns the raw response.
+     *
+     * # Returns
+     * A Result containing a byte vector with the raw data or an ApplicationError.
+     */
+    fn get_raw(&self) -> Result<Vec<u8>, ApplicationError> {
+        Ok(self.data.clone())
+    }
+
+    /**
+     * Returns the raw response as a String.
+     *
+     * # Returns
+     * A Result containing a String with the raw data or an ApplicationError.
+     */
+    fn get_raw_string(&self) -> Result<String, ApplicationError> {
+        Ok(String::from_utf8_lossy(&self.data).into_owned())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_idn() {
+        let reading = IdnReading::parse(b"RIGOL TECHNOLOGIES,DS1054Z,DS1ZA000000001,00.04.04.SP4\n".to_vec()).unwrap();
+        assert_eq!(
+            reading.get_csv().unwrap(),
+            vec!["RIGOL TECHNOLOGIES", "DS1054Z", "DS1ZA000000001", "00.04.04.SP4"]
+        );
+        assert_eq!(reading.get_csv_header().unwrap()[2], "serial_number");
+        assert!(reading.get_raw_string().unwrap().starts_with("RIGOL"));
+    }
+
+    #[test]
+    fn test_parse_not_idn() {
+        assert!(IdnReading::parse(b"+1.234E+00\n".to_vec()).is_err());
+    }
+}
@@ -0,0 +1,154 @@
+use crate::{
+    error::ApplicationError,
+    instruments::{
+        command::DmmFunction,
+        reading::{Measurement, Reading},
+    },
+};
+
+// CSV field names, in the order returned by get_csv
+const CSV_HEADER: [&str; 4] = ["function", "value", "unit", "overload"];
+
+/**
+ * Magnitude from which a value is the SCPI overflow value 9.9E37, returned for OVLD.
+ */
+const OVERFLOW: f64 = 9.9e37;
+
+/**
+ * Represents one value of a Keysight 34401A / 34461A bench multimeter, an NR3 number such as
+ * `-1.23456789E-03` in the unit of the configured function.
+ */
+#[derive(Debug)]
+pub struct KeysightDmmReading {
+    pub function: DmmFunction,
+    /**
+     * The value, None on overload.
+     */
+    pub value: Option<f64>,
+    pub original_bytes: Vec<u8>,
+}
+
+impl KeysightDmmReading {
+    /**
+     * Parses a response to `READ?` or `MEAS:...?`. With a sample or trigger count above one the
+     * meter returns the values separated by commas, giving a reading each.
+     *
+     * # Arguments
+     * `function` - The configured function, giving the unit.
+     * `response` - The raw response.
+     *
+     * # Returns
+     * A Result containing the readings or an ApplicationError if a value is not a number.
+     */
+    pub fn parse(function: DmmFunction, response: &[u8]) -> Result<Vec<Self>, ApplicationError> {
+        String::from_utf8_lossy(response)
+            .trim()
+            .split(',')
+            .map(|field| {
+                let value = field
+                    .trim()
+                    .parse::<f64>()
+                    .map_err(|_| ApplicationError::Command(format!("Invalid Keysight multimeter value: {}", field.trim())))?;
+                Ok(Self {
+                    function,
+                    value: Some(value).filter(|value| value.abs() < OVERFLOW),
+                    original_bytes: field.trim().as_bytes().to_vec(),
+                })
+            })
+            .collect()
+    }
+}
+
+impl Reading for KeysightDmmReading {
+    /**
+     * Returns the names of the CSV fields.
+     *
+     * # Returns
+     * A Result containing the CSV header fields or an ApplicationError.
+     */
+    fn get_csv_header(&self) -> Result<Vec<String>, ApplicationError> {
+        Ok(CSV_HEADER.iter().map(|field| field.to_string()).collect())
+    }
+
+    /**
+     * Returns the measurement as CSV fields. The value is empty on overload.
+     *
+     * # Returns
+     * A Result containing the CSV field values or an ApplicationError.
+     */
+    fn get_csv(&self) -> Result<Vec<String>, ApplicationError> {
+        Ok(vec![
+            self.function.name().to_string(),
+            self.value.map(|value| value.to_string()).unwrap_or_default(),
+            self.function.unit().to_string(),
+            self.value.is_none().to_string(),
+        ])
+    }
+
+    /**
+     * Returns the value as sent by the meter.
+     *
+     * # Returns
+     * A Result containing a byte vector with the raw data or an ApplicationError.
+     */
+    fn get_raw(&self) -> Result<Vec<u8>, ApplicationError> {
+        Ok(self.original_bytes.clone())
+    }
+
+    /**
+     * Returns the value and unit as a String, `OL` on overload.
+     *
+     * # Returns
+     * A Result containing a String with the value or an ApplicationError.
+     */
+    fn get_raw_string(&self) -> Result<String, ApplicationError> {
+        Ok(match self.value {
+            Some(value) => format!("{} {}", value, self.function.unit()),
+            None => format!("OL {}", self.function.unit()),
+        })
+    }
+
+    /**
+     * Returns the typed measurement. AC functions are flagged `ac`.
+     *
+     * # Returns
+     * The measurement, or None on overload.
+     */
+    fn get_measurement(&self) -> Option<Measurement> {
+        let flags = match self.function.is_ac() {
+            true => vec!["ac".to_string()],
+            false => Vec::new(),
+        };
+        Measurement::from_display(self.value?, self.function.unit(), flags)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::instruments::reading::measurement::Quantity;
+
+    #[test]
+    fn test_parse() {
+        let readings = KeysightDmmReading::parse(DmmFunction::VoltageAc, b"+2.30512340E+02\n").unwrap();
+        assert_eq!(readings.len(), 1);
+        assert_eq!(readings[0].value, Some(230.51234));
+        assert_eq!(readings[0].get_csv().unwrap(), vec!["VoltageAc", "230.51234", "V", "false"]);
+        let measurement = readings[0].get_measurement().unwrap();
+        assert_eq!(measurement.quantity, Quantity::Voltage);
+        assert_eq!(measurement.flags, vec!["ac"]);
+        let readings = KeysightDmmReading::parse(DmmFunction::CurrentDc, b"-1.25000000E-03,+1.26000000E-03\r\n").unwrap();
+        assert_eq!(readings.iter().map(|reading| reading.value).collect::<Vec<_>>(), [Some(-0.00125), Some(0.00126)]);
+        assert_eq!(readings[1].get_raw().unwrap(), b"+1.26000000E-03");
+    }
+
+    #[test]
+    fn test_overload() {
+        let readings = KeysightDmmReading::parse(DmmFunction::Resistance4W, b"+9.90000000E+37\n").unwrap();
+        assert_eq!(readings[0].value, None);
+        assert_eq!(readings[0].get_raw_string().unwrap(), "OL Ω");
+        assert!(readings[0].get_measurement().is_none());
+        assert!(KeysightDmmReading::parse(DmmFunction::Resistance, b"\n").is_err());
+        assert!(KeysightDmmReading::parse(DmmFunction::Resistance, b"+1.0E+03,OVLD\n").is_err());
+    }
+}
@@ -0,0 +1,180 @@
+use crate::{
+    error::ApplicationError,
+    instruments::reading::{Measurement, Reading},
+};
+
+/**
+ * Characters separating the pairs of a `key=value` line.
+ */
+const PAIR_SEPARATORS: [char; 4] = [',', ';', '\t', ' '];
+
+/**
+ * A line of text printed by a microcontroller, e.g. `temp=23.5°C,hum=41%` or `23.5,41`. The
+ * fields of `key=value` lines are named by their keys; the columns of CSV lines are named with
+ * --text-fields, or `field1`, `field2`, ... without it.
+ */
+#[derive(Debug)]
+pub struct KeyValueReading {
+    /**
+     * The names and values of the fields in the order of the line.
+     */
+    pub fields: Vec<(String, String)>,
+    /**
+     * The line as read, without the line terminator.
+     */
+    pub line: Vec<u8>,
+}
+
+/**
+ * Splits a value into its number and the unit following it, e.g. `3.30V` or `12 mA`.
+ *
+ * # Arguments
+ * `value` - The value.
+ *
+ * # Returns
+ * The number and the unit, or None if the value does not start with a number.
+ */
+fn split_unit(value: &str) -> Option<(f64, &str)> {
+    let end = value
+        .find(|c: char| !(c.is_ascii_digit() || matches!(c, '.' | '-' | '+' | 'e' | 'E')))
+        .unwrap_or(value.len());
+    // The exponent characters may start the unit instead, e.g. `5e` is not a number
+    (1..=end)
+        .rev()
+        .find_map(|end| value[..end].parse::<f64>().ok().map(|number| (number, value[end..].trim())))
+}
+
+impl KeyValueReading {
+    /**
+     * Parses a line of `key=value` pairs or of comma separated values.
+     *
+     * # Arguments
+     * `data` - The line, with or without the line terminator.
+     * `names` - The names of the columns of CSV lines, from --text-fields.
+     *
+     * # Returns
+     * A Result containing the KeyValueReading or an ApplicationError if the line is empty or a
+     * pair has no `=`.
+     */
+    pub fn parse(data: &[u8], names: &[String]) -> Result<Self, ApplicationError> {
+        let text = String::from_utf8_lossy(data);
+        let text = text.trim();
+        if text.is_empty() {
+            return Err(ApplicationError::Command("Empty line".into()));
+        }
+        let fields = match text.contains('=') {
+            true => text
+                .split(PAIR_SEPARATORS)
+                .filter(|pair| !pair.is_empty())
+                .map(|pair| {
+                    pair.split_once('=')
+                        .map(|(key, value)| (key.trim().to_string(), value.trim().to_string()))
+                        .ok_or_else(|| ApplicationError::Command(format!("Expected key=value, got {} in {}", pair, text)))
+                })
+                .collect::<Result<Vec<_>, _>>()?,
+            false => text
+                .split(',')
+                .enumerate()
+                .map(|(index, value)| {
+                    let name = names.get(index).cloned().unwrap_or_else(|| format!("field{}", index + 1));
+                    (name, value.trim().to_string())
+                })
+                .collect(),
+        };
+        Ok(Self {
+            fields,
+            line: text.as_bytes().to_vec(),
+        })
+    }
+}
+
+impl Reading for KeyValueReading {
+    /**
+     * Returns the names of the fields of the line.
+     *
+     * # Returns
+     * A Result containing the CSV header fields or an ApplicationError.
+     */
+    fn get_csv_header(&self) -> Result<Vec<String>, ApplicationError> {
+        Ok(self.fields.iter().map(|(name, _)| name.clone()).collect())
+    }
+
+    /**
+     * Returns the values of the fields as printed, with their units.
+     *
+     * # Returns
+     * A Result containing the CSV field values or an ApplicationError.
+     */
+    fn get_csv(&self) -> Result<Vec<String>, ApplicationError> {
+        Ok(self.fields.iter().map(|(_, value)| value.clone()).collect())
+    }
+
+    /**
+     * Returns the line as read.
+     *
+     * # Returns
+     * A Result containing a byte vector with the raw data or an ApplicationError.
+     */
+    fn get_raw(&self) -> Result<Vec<u8>, ApplicationError> {
+        Ok(self.line.clone())
+    }
+
+    /**
+     * Returns the line as read.
+     *
+     * # Returns
+     * A Result containing the line or an ApplicationError.
+     */
+    fn get_raw_string(&self) -> Result<String, ApplicationError> {
+        Ok(String::from_utf8_lossy(&self.line).to_string())
+    }
+
+    /**
+     * Returns the first value with a known unit, e.g. `3.30V` or `23.5°C`, as the measurement.
+     *
+     * # Returns
+     * The measurement, or None if no value has a known unit.
+     */
+    fn get_measurement(&self) -> Option<Measurement> {
+        self.fields.iter().find_map(|(_, value)| {
+            let (number, unit) = split_unit(value)?;
+            Measurement::from_display(number, unit, Vec::new())
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::instruments::reading::Quantity;
+
+    #[test]
+    fn test_key_value_line() {
+        let reading = KeyValueReading::parse(b"temp=23.5\xc2\xb0C, hum=41% vbat=3.7V\r\n", &[]).unwrap();
+        assert_eq!(reading.get_csv_header().unwrap(), vec!["temp", "hum", "vbat"]);
+        assert_eq!(reading.get_csv().unwrap(), vec!["23.5°C", "41%", "3.7V"]);
+        assert_eq!(reading.get_raw_string().unwrap(), "temp=23.5°C, hum=41% vbat=3.7V");
+        let measurement = reading.get_measurement().unwrap();
+        assert_eq!(measurement.quantity, Quantity::Temperature);
+        assert!((measurement.value - 296.65).abs() < 1e-9);
+        assert!(KeyValueReading::parse(b"temp=1 oops", &[]).is_err());
+    }
+
+    #[test]
+    fn test_csv_line() {
+        let names = vec!["voltage".to_string()];
+        let reading = KeyValueReading::parse(b"12 mV,1023\n", &names).unwrap();
+        assert_eq!(reading.get_csv_header().unwrap(), vec!["voltage", "field2"]);
+        assert_eq!(reading.get_csv().unwrap(), vec!["12 mV", "1023"]);
+        assert!((reading.get_measurement().unwrap().value - 0.012).abs() < 1e-12);
+        assert!(KeyValueReading::parse(b"1023,512", &[]).unwrap().get_measurement().is_none());
+        assert!(KeyValueReading::parse(b"\r\n", &[]).is_err());
+    }
+
+    #[test]
+    fn test_split_unit() {
+        assert_eq!(split_unit("-1.5e-3A"), Some((-1.5e-3, "A")));
+        assert_eq!(split_unit("5e"), Some((5.0, "e")));
+        assert_eq!(split_unit("ok"), None);
+    }
+}
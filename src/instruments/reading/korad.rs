@@ -0,0 +1,217 @@
+use crate::{error::ApplicationError, instruments::reading::Reading};
+
+// CSV field names, in the order returned by get_csv
+const CSV_HEADER: [&str; 6] = ["values", "voltage", "current", "status", "mode", "output"];
+
+// Status bit set in constant voltage mode, clear in constant current mode
+const STATUS_CV: u8 = 0x01;
+// Status bit set when the output is on
+const STATUS_OUTPUT: u8 = 0x40;
+
+/**
+ * Represents voltage, current and status read from a Korad style power supply.
+ */
+#[derive(Debug)]
+pub struct KoradReading {
+    /**
+     * True for the setpoints, false for the actual output values.
+     */
+    pub setpoint: bool,
+    pub voltage: Option<f64>,
+    pub current: Option<f64>,
+    pub status: Option<u8>,
+}
+
+/**
+ * Parses a numeric response such as `05.00`. Some firmware versions append a stray
+ * character, so only the leading number is used.
+ *
+ * # Arguments
+ * `response` - The raw response.
+ *
+ * # Returns
+ * The value, or None if the response does not start with a number.
+ */
+fn parse_value(response: &[u8]) -> Option<f64> {
+    let text = String::from_utf8_lossy(response);
+    let text = text.trim();
+    let end = text
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(text.len());
+    text[..end].parse().ok()
+}
+
+impl KoradReading {
+    /**
+     * Creates a reading from the responses to `VOUT1?`, `IOUT1?` and `STATUS?`.
+     *
+     * # Arguments
+     * `voltage` - The voltage response.
+     * `current` - The current response.
+     * `status` - The status response, a single byte.
+     *
+     * # Returns
+     * A new KoradReading instance.
+     */
+    pub fn measured(voltage: &[u8], current: &[u8], status: &[u8]) -> Self {
+        Self {
+            setpoint: false,
+            voltage: parse_value(voltage),
+            current: parse_value(current),
+            status: status.first().copied(),
+        }
+    }
+
+    /**
+     * Creates a reading from the responses to `VSET1?` and `ISET1?`.
+     *
+     * # Arguments
+     * `voltage` - The voltage setpoint response.
+     * `current` - The current setpoint response.
+     *
+     * # Returns
+     * A new KoradReading instance.
+     */
+    pub fn setpoints(voltage: &[u8], current: &[u8]) -> Self {
+        Self {
+            setpoint: true,
+            voltage: parse_value(voltage),
+            current: parse_value(current),
+            status: None,
+        }
+    }
+
+    /**
+     * Creates a reading from the response to `STATUS?`.
+     *
+     * # Arguments
+     * `status` - The status response, a single byte.
+     *
+     * # Returns
+     * A new KoradReading instance.
+     */
+    pub fn status(status: &[u8]) -> Self {
+        Self {
+            setpoint: false,
+            voltage: None,
+            current: None,
+            status: status.first().copied(),
+        }
+    }
+
+    /**
+     * Returns the regulation mode decoded from the status byte.
+     *
+     * # Returns
+     * Some("CV") or Some("CC"), or None without a status byte.
+     */
+    pub fn mode(&self) -> Option<&'static str> {
+        self.status
+            .map(|status| if status & STATUS_CV != 0 { "CV" } else { "CC" })
+    }
+
+    /**
+     * Returns whether the output is on, decoded from the status byte.
+     *
+     * # Returns
+     * Some(bool), or None without a status byte.
+     */
+    pub fn output(&self) -> Option<bool> {
+        self.status.map(|status| status & STATUS_OUTPUT != 0)
+    }
+}
+
+impl Reading for KoradReading {
+    /**
+     * Returns the names of the CSV fields.
+     *
+     * # Returns
+     * A Result containing the CSV header fields or an ApplicationError.
+     */
+    fn get_csv_header(&self) -> Result<Vec<String>, ApplicationError> {
+        Ok(CSV_HEADER.iter().map(|field| field.to_string()).collect())
+    }
+
+    /**
+     * Returns the values as CSV fields. Values that were not read are empty.
+     *
+     * # Returns
+     * A Result containing the CSV field values or an ApplicationError.
+     */
+    fn get_csv(&self) -> Result<Vec<String>, ApplicationError> {
+        let optional = |value: Option<String>| value.unwrap_or_default();
+        Ok(vec![
+            if self.setpoint { "setpoint" } else { "output" }.to_string(),
+            optional(self.voltage.map(|voltage| voltage.to_string())),
+            optional(self.current.map(|current| current.to_string())),
+            optional(self.status.map(|status| status.to_string())),
+            optional(self.mode().map(|mode| mode.to_string())),
+            optional(self.output().map(|output| output.to_string())),
+        ])
+    }
+
+    /**
+     * Not supported for KoradReading, as the responses are not terminated.
+     *
+     * # Returns
+     * Always Err.
+     */
+    fn get_raw(&self) -> Result<Vec<u8>, ApplicationError> {
+        Err(ApplicationError::General(
+            "KoradReading does not support raw format".into(),
+        ))
+    }
+
+    /**
+     * Returns a human readable summary of the reading.
+     *
+     * # Returns
+     * A Result containing the summary or an ApplicationError.
+     */
+    fn get_raw_string(&self) -> Result<String, ApplicationError> {
+        let mut fields = Vec::new();
+        if let Some(voltage) = self.voltage {
+            fields.push(format!("{} V", voltage));
+        }
+        if let Some(current) = self.current {
+            fields.push(format!("{} A", current));
+        }
+        if let (Some(mode), Some(output)) = (self.mode(), self.output()) {
+            fields.push(format!("{} output {}", mode, if output { "on" } else { "off" }));
+        }
+        Ok(fields.join(" "))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_measured() {
+        let reading = KoradReading::measured(b"05.00", b"0.123", &[0x41]);
+        assert_eq!(reading.voltage, Some(5.0));
+        assert_eq!(reading.current, Some(0.123));
+        assert_eq!(
+            reading.get_csv().unwrap(),
+            vec!["output", "5", "0.123", "65", "CV", "true"]
+        );
+        assert_eq!(reading.get_raw_string().unwrap(), "5 V 0.123 A CV output on");
+    }
+
+    #[test]
+    fn test_setpoints_with_stray_character() {
+        let reading = KoradReading::setpoints(b"12.00", b"1.0005");
+        assert_eq!(reading.current, Some(1.0005));
+        let reading = KoradReading::setpoints(b"12.00", b"1.000K");
+        assert_eq!(reading.current, Some(1.0));
+        assert_eq!(reading.get_csv().unwrap(), vec!["setpoint", "12", "1", "", "", ""]);
+    }
+
+    #[test]
+    fn test_status() {
+        let reading = KoradReading::status(&[0x00]);
+        assert_eq!(reading.mode(), Some("CC"));
+        assert_eq!(reading.output(), Some(false));
+    }
+}
@@ -0,0 +1,193 @@
+use std::fmt;
+
+/**
+ * Enum representing the physical quantity of a measurement.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Quantity {
+    Voltage,
+    Current,
+    Resistance,
+    Capacitance,
+    Frequency,
+    Time,
+    Temperature,
+    Ratio,
+}
+
+impl Quantity {
+    /**
+     * Returns the SI base unit the values of the quantity are normalized to.
+     *
+     * # Returns
+     * The unit as a string slice.
+     */
+    pub fn base_unit(&self) -> &'static str {
+        match self {
+            Quantity::Voltage => "V",
+            Quantity::Current => "A",
+            Quantity::Resistance => "Ω",
+            Quantity::Capacitance => "F",
+            Quantity::Frequency => "Hz",
+            Quantity::Time => "s",
+            Quantity::Temperature => "K",
+            Quantity::Ratio => "%",
+        }
+    }
+}
+
+impl fmt::Display for Quantity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", format!("{:?}", self).to_lowercase())
+    }
+}
+
+/**
+ * Represents a measured value normalized to the SI base unit of its quantity, so that e.g.
+ * 2.5 kΩ and 2500 Ω compare equal. The unit shown by the instrument is kept for display.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct Measurement {
+    pub quantity: Quantity,
+    /**
+     * Value in the base unit of the quantity.
+     */
+    pub value: f64,
+    /**
+     * Unit as displayed by the instrument, e.g. `kΩ`.
+     */
+    pub display_unit: String,
+    /**
+     * Instrument flags active for the measurement, e.g. `hold` or `rel`.
+     */
+    pub flags: Vec<String>,
+}
+
+/**
+ * Splits a unit into its quantity and the factor converting it to the base unit.
+ * Temperatures are converted separately as they have an offset.
+ *
+ * # Arguments
+ * `unit` - The unit, optionally with an SI prefix, e.g. `mV`, `kΩ` or `nF`.
+ *
+ * # Returns
+ * Some((quantity, factor)) for known units, otherwise None.
+ */
+pub fn parse_unit(unit: &str) -> Option<(Quantity, f64)> {
+    let base = |unit: &str| match unit {
+        "V" => Some(Quantity::Voltage),
+        "A" => Some(Quantity::Current),
+        "Ω" | "ohm" | "OHM" => Some(Quantity::Resistance),
+        "F" => Some(Quantity::Capacitance),
+        "Hz" => Some(Quantity::Frequency),
+        "s" => Some(Quantity::Time),
+        _ => None,
+    };
+    match unit {
+        "%" => return Some((Quantity::Ratio, 1.0)),
+        "°C" | "°F" | "K" => return Some((Quantity::Temperature, 1.0)),
+        _ => {}
+    }
+    if let Some(quantity) = base(unit) {
+        return Some((quantity, 1.0));
+    }
+    let mut chars = unit.chars();
+    let factor = match chars.next()? {
+        'p' => 1e-12,
+        'n' => 1e-9,
+        'u' | 'µ' | 'μ' => 1e-6,
+        'm' => 1e-3,
+        'k' => 1e3,
+        'M' => 1e6,
+        'G' => 1e9,
+        _ => return None,
+    };
+    base(chars.as_str()).map(|quantity| (quantity, factor))
+}
+
+impl Measurement {
+    /**
+     * Creates a measurement from a displayed value and unit.
+     *
+     * # Arguments
+     * `value` - The value as displayed.
+     * `display_unit` - The unit as displayed.
+     * `flags` - Active instrument flags.
+     *
+     * # Returns
+     * Some(Measurement) if the unit is known, otherwise None.
+     */
+    pub fn from_display(value: f64, display_unit: &str, flags: Vec<String>) -> Option<Self> {
+        let (quantity, factor) = parse_unit(display_unit)?;
+        let value = match display_unit {
+            "°C" => value + 273.15,
+            "°F" => (value - 32.0) * 5.0 / 9.0 + 273.15,
+            _ => value * factor,
+        };
+        Some(Self {
+            quantity,
+            value,
+            display_unit: display_unit.to_string(),
+            flags,
+        })
+    }
+
+    /**
+     * Returns the base unit of the value.
+     *
+     * # Returns
+     * The unit as a string slice.
+     */
+    pub fn unit(&self) -> &'static str {
+        self.quantity.base_unit()
+    }
+
+    /**
+     * Returns the value in the displayed unit, the inverse of from_display.
+     *
+     * # Returns
+     * The value as displayed.
+     */
+    pub fn display_value(&self) -> f64 {
+        match self.display_unit.as_str() {
+            "°C" => self.value - 273.15,
+            "°F" => (self.value - 273.15) * 9.0 / 5.0 + 32.0,
+            unit => self.value / parse_unit(unit).map(|(_, factor)| factor).unwrap_or(1.0),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_unit() {
+        assert_eq!(parse_unit("mV"), Some((Quantity::Voltage, 1e-3)));
+        assert_eq!(parse_unit("kΩ"), Some((Quantity::Resistance, 1e3)));
+        assert_eq!(parse_unit("nF"), Some((Quantity::Capacitance, 1e-9)));
+        assert_eq!(parse_unit("MHz"), Some((Quantity::Frequency, 1e6)));
+        assert_eq!(parse_unit("m"), None);
+        assert_eq!(parse_unit("NCV"), None);
+    }
+
+    #[test]
+    fn test_normalized_values_compare() {
+        let kilo = Measurement::from_display(2.5, "kΩ", vec![]).unwrap();
+        let base = Measurement::from_display(2500.0, "Ω", vec![]).unwrap();
+        assert_eq!(kilo.quantity, base.quantity);
+        assert!((kilo.value - base.value).abs() < 1e-9);
+        assert_eq!(kilo.unit(), "Ω");
+        assert_eq!(kilo.display_unit, "kΩ");
+    }
+
+    #[test]
+    fn test_temperature() {
+        let celsius = Measurement::from_display(25.0, "°C", vec![]).unwrap();
+        assert!((celsius.value - 298.15).abs() < 1e-9);
+        let fahrenheit = Measurement::from_display(212.0, "°F", vec![]).unwrap();
+        assert!((fahrenheit.value - 373.15).abs() < 1e-9);
+        assert!((fahrenheit.display_value() - 212.0).abs() < 1e-9);
+        assert!((celsius.display_value() - 25.0).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,154 @@
+use crate::{
+    error::ApplicationError,
+    instruments::reading::{measurement::Quantity, Reading},
+};
+
+/**
+ * Summary of the readings of a min/max session: the minimum, maximum and average of the values
+ * seen by the host, in the base unit of the quantity. Overloads are counted but not summarized.
+ */
+#[derive(Debug, Default)]
+pub struct MinMaxReading {
+    pub mode: String,
+    pub quantity: Option<Quantity>,
+    pub samples: u32,
+    pub overloads: u32,
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+    sum: f64,
+}
+
+impl MinMaxReading {
+    /**
+     * Adds a reading to the summary.
+     *
+     * # Arguments
+     * `reading` - The reading.
+     *
+     * # Returns
+     * A Result indicating success, or an ApplicationError if the reading has another mode or
+     * quantity than the earlier ones.
+     */
+    pub fn add(&mut self, reading: &dyn Reading) -> Result<(), ApplicationError> {
+        let header = reading.get_csv_header()?;
+        let mode = header
+            .iter()
+            .zip(reading.get_csv()?)
+            .find(|(name, _)| name.as_str() == "mode")
+            .map(|(_, mode)| mode)
+            .unwrap_or_default();
+        if self.samples + self.overloads == 0 {
+            self.mode = mode;
+        } else if self.mode != mode {
+            return Err(ApplicationError::Command(format!(
+                "The mode changed from {} to {} during the min/max session",
+                self.mode, mode
+            )));
+        }
+        let Some(measurement) = reading.get_measurement() else {
+            self.overloads += 1;
+            return Ok(());
+        };
+        if self.quantity.is_some_and(|quantity| quantity != measurement.quantity) {
+            return Err(ApplicationError::Command("The quantity changed during the min/max session".into()));
+        }
+        self.quantity = Some(measurement.quantity);
+        self.samples += 1;
+        self.sum += measurement.value;
+        self.min = Some(self.min.map_or(measurement.value, |min| min.min(measurement.value)));
+        self.max = Some(self.max.map_or(measurement.value, |max| max.max(measurement.value)));
+        Ok(())
+    }
+
+    /**
+     * Returns the average of the values.
+     *
+     * # Returns
+     * The average, or None without values.
+     */
+    pub fn average(&self) -> Option<f64> {
+        (self.samples > 0).then(|| self.sum / self.samples as f64)
+    }
+}
+
+impl Reading for MinMaxReading {
+    /**
+     * Returns the CSV header for the summary.
+     *
+     * # Returns
+     * A Result containing the CSV header fields or an ApplicationError.
+     */
+    fn get_csv_header(&self) -> Result<Vec<String>, ApplicationError> {
+        Ok(["mode", "unit", "samples", "overloads", "min", "max", "average"]
+            .iter()
+            .map(|field| field.to_string())
+            .collect())
+    }
+
+    /**
+     * Returns the summary as CSV fields. The values are empty without samples.
+     *
+     * # Returns
+     * A Result containing the CSV field values or an ApplicationError.
+     */
+    fn get_csv(&self) -> Result<Vec<String>, ApplicationError> {
+        let value = |value: Option<f64>| value.map(|value| value.to_string()).unwrap_or_default();
+        Ok(vec![
+            self.mode.clone(),
+            self.quantity.map(|quantity| quantity.base_unit().to_string()).unwrap_or_default(),
+            self.samples.to_string(),
+            self.overloads.to_string(),
+            value(self.min),
+            value(self.max),
+            value(self.average()),
+        ])
+    }
+
+    /**
+     * Returns the summary as CSV line, as the summary has no raw data.
+     *
+     * # Returns
+     * A Result containing a byte vector with the summary or an ApplicationError.
+     */
+    fn get_raw(&self) -> Result<Vec<u8>, ApplicationError> {
+        Ok(self.get_raw_string()?.into_bytes())
+    }
+
+    /**
+     * Returns the summary as CSV line, as the summary has no raw data.
+     *
+     * # Returns
+     * A Result containing a String with the summary or an ApplicationError.
+     */
+    fn get_raw_string(&self) -> Result<String, ApplicationError> {
+        Ok(self.get_csv()?.join(","))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::instruments::reading::Unit161dReading;
+
+    fn reading(mode: u8, range: u8, value: &[u8; 7]) -> Unit161dReading {
+        let mut raw_data = vec![mode, range];
+        raw_data.extend(value);
+        raw_data.extend([0, 0, 0, 0, 0]);
+        Unit161dReading::parse(raw_data).unwrap()
+    }
+
+    #[test]
+    fn test_summary() {
+        let mut summary = MinMaxReading::default();
+        summary.add(&reading(3, b'0', b"  100.0")).unwrap();
+        summary.add(&reading(3, b'0', b"  300.0")).unwrap();
+        summary.add(&reading(3, b'0', b"    .OL")).unwrap();
+        assert_eq!(summary.get_csv().unwrap(), vec!["DCmV", "V", "2", "1", "0.1", "0.3", "0.2"]);
+        assert!(summary.add(&reading(2, b'1', b"  1.000")).is_err());
+    }
+
+    #[test]
+    fn test_empty_summary() {
+        assert_eq!(MinMaxReading::default().get_csv().unwrap(), vec!["", "", "0", "0", "", "", ""]);
+    }
+}
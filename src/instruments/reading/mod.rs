@@ -1,7 +1,104 @@
+mod aes;
+mod analysis;
+#[cfg(feature = "serial")]
+mod atorch;
+#[cfg(feature = "usb")]
+pub mod block;
+#[cfg(feature = "audio")]
+mod audio;
+mod brymen;
+mod calibrated;
+mod keyvalue;
+#[cfg(any(feature = "serial", test))]
+mod korad;
+#[cfg(all(feature = "serial", feature = "modbus"))]
+pub mod riden;
+#[cfg(feature = "usb")]
+mod scopemeasurement;
+mod screenshot;
+mod scpiblock;
 mod scpiraw;
+#[cfg(feature = "usb")]
+mod segment;
+#[cfg(feature = "usb")]
+mod statusbyte;
+mod paired;
+mod tagged;
+mod timed;
+mod tc66;
+#[cfg(any(feature = "usb", test))]
+mod waveform;
 mod common;
+mod discharge;
+mod event;
+mod filtered;
+mod fluke;
+mod idn;
+#[cfg(any(feature = "serial", feature = "usb", test))]
+mod keysight;
+mod measurement;
+#[cfg(feature = "hid")]
+mod minmax;
+mod modbus;
+#[cfg(feature = "ble")]
+mod owon;
+#[cfg(any(feature = "serial", test))]
+mod owonxdm;
 mod unit161d;
+mod ut71;
+mod ut325;
 
+pub use analysis::AnalysisReading;
+#[cfg(feature = "serial")]
+pub use atorch::{AtorchReading, ATORCH_FRAME_LENGTH};
+#[cfg(feature = "audio")]
+pub use audio::AudioReading;
+pub use brymen::BrymenReading;
+pub use calibrated::{reading_mode, CalibratedReading, Calibration};
+pub use keyvalue::KeyValueReading;
+#[cfg(any(feature = "serial", test))]
+pub use korad::KoradReading;
+#[cfg(all(feature = "serial", feature = "modbus"))]
+pub use riden::{RidenModel, RidenReading};
+#[cfg(feature = "usb")]
+pub use scopemeasurement::ScopeMeasurementReading;
+pub use screenshot::ScreenshotReading;
+pub use scpiblock::ScpiBlockReading;
 pub use scpiraw::ScpiRawReading;
-pub use common::Reading;
+#[cfg(feature = "usb")]
+pub use segment::SegmentReading;
+#[cfg(feature = "usb")]
+pub use statusbyte::StatusByteReading;
+pub use paired::PairedReading;
+pub use tagged::TaggedReading;
+pub use timed::{TimedReading, LATENCY_FIELD};
+pub use tc66::Tc66Reading;
+#[cfg(feature = "serial")]
+pub use tc66::TC66_FRAME_LENGTH;
+#[cfg(any(feature = "usb", test))]
+pub use waveform::{WaveformPreamble, WaveformReading};
+pub use common::{Reading, Samples};
+pub use discharge::DischargeReading;
+pub use event::{EventKind, EventReading};
+pub use filtered::FilteredReading;
+pub use fluke::FlukeReading;
+pub use idn::IdnReading;
+#[cfg(any(feature = "usb", feature = "network"))]
+pub use idn::Identity;
+#[cfg(any(feature = "serial", feature = "usb", test))]
+pub use keysight::KeysightDmmReading;
+pub use measurement::{Measurement, Quantity};
+#[cfg(feature = "hid")]
+pub use minmax::MinMaxReading;
+pub use modbus::ModbusRegister;
+#[cfg(all(feature = "network", feature = "modbus"))]
+pub use modbus::{ModbusReading, RegisterTable};
+#[cfg(feature = "ble")]
+pub use owon::OwonReading;
+#[cfg(any(feature = "serial", test))]
+pub use owonxdm::OwonXdmReading;
 pub use unit161d::Unit161dReading;
+pub use ut71::Ut71Reading;
+pub use ut325::Ut325Reading;
+#[cfg(feature = "serial")]
+pub use ut325::{ut325_download_header, ut325_frames};
@@ -0,0 +1,300 @@
+use serde::Deserialize;
+
+#[cfg(all(feature = "network", feature = "modbus"))]
+use crate::{
+    error::ApplicationError,
+    instruments::reading::{Measurement, Reading},
+};
+
+fn unity() -> f64 {
+    1.0
+}
+
+/**
+ * Register table of a Modbus register.
+ */
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, PartialOrd, Ord, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum RegisterTable {
+    /// Read with function 0x03.
+    #[default]
+    Holding,
+    /// Read with function 0x04, used for the measurements of most power meters.
+    Input,
+}
+
+/**
+ * Data type of a Modbus register value. 32-bit values span two registers.
+ */
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum RegisterType {
+    #[default]
+    U16,
+    I16,
+    U32,
+    I32,
+    F32,
+}
+
+/**
+ * Order of the registers of 32-bit values.
+ */
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum WordOrder {
+    /// High word first, as in the Modbus specification.
+    #[default]
+    Big,
+    /// Low word first, used by some PLCs and meters.
+    Little,
+}
+
+/**
+ * A register of a Modbus register map. The value read is converted to `value * scale + offset`.
+ */
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ModbusRegister {
+    /**
+     * Field name of the value, e.g. `voltage`.
+     */
+    pub name: String,
+    /**
+     * Register address as sent on the wire, starting at 0.
+     */
+    pub address: u16,
+    #[serde(default)]
+    pub table: RegisterTable,
+    #[serde(default, rename = "type")]
+    pub data_type: RegisterType,
+    #[serde(default)]
+    pub word_order: WordOrder,
+    #[serde(default = "unity")]
+    pub scale: f64,
+    #[serde(default)]
+    pub offset: f64,
+    /**
+     * Unit of the scaled value, e.g. `V`.
+     */
+    pub unit: Option<String>,
+}
+
+#[cfg(all(feature = "network", feature = "modbus"))]
+impl ModbusRegister {
+    /**
+     * Returns the number of registers the value spans.
+     *
+     * # Returns
+     * 1 for 16-bit values, 2 for 32-bit values.
+     */
+    pub fn words(&self) -> u16 {
+        match self.data_type {
+            RegisterType::U16 | RegisterType::I16 => 1,
+            RegisterType::U32 | RegisterType::I32 | RegisterType::F32 => 2,
+        }
+    }
+
+    /**
+     * Decodes and scales the value.
+     *
+     * # Arguments
+     * `words` - The registers of the value, as many as `words()`.
+     *
+     * # Returns
+     * A Result containing the scaled value or an ApplicationError if registers are missing.
+     */
+    pub fn decode(&self, words: &[u16]) -> Result<f64, ApplicationError> {
+        if words.len() != self.words() as usize {
+            return Err(ApplicationError::Command(format!(
+                "Expected {} registers for {}, got {}",
+                self.words(),
+                self.name,
+                words.len()
+            )));
+        }
+        let long = || match self.word_order {
+            WordOrder::Big => ((words[0] as u32) << 16) | words[1] as u32,
+            WordOrder::Little => ((words[1] as u32) << 16) | words[0] as u32,
+        };
+        let value = match self.data_type {
+            RegisterType::U16 => words[0] as f64,
+            RegisterType::I16 => words[0] as i16 as f64,
+            RegisterType::U32 => long() as f64,
+            RegisterType::I32 => long() as i32 as f64,
+            RegisterType::F32 => f32::from_bits(long()) as f64,
+        };
+        Ok(value * self.scale + self.offset)
+    }
+}
+
+/**
+ * A value read from a Modbus register map.
+ */
+#[cfg(all(feature = "network", feature = "modbus"))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct RegisterValue {
+    pub name: String,
+    pub value: f64,
+    pub unit: Option<String>,
+    /**
+     * The registers as read.
+     */
+    pub words: Vec<u16>,
+}
+
+/**
+ * Represents values read from the register map of a Modbus device, one CSV field per register.
+ */
+#[cfg(all(feature = "network", feature = "modbus"))]
+#[derive(Debug)]
+pub struct ModbusReading {
+    pub values: Vec<RegisterValue>,
+}
+
+#[cfg(all(feature = "network", feature = "modbus"))]
+impl ModbusReading {
+    /**
+     * Decodes the registers of a register map.
+     *
+     * # Arguments
+     * `registers` - The registers with the words read for each.
+     *
+     * # Returns
+     * A Result containing the ModbusReading or an ApplicationError.
+     */
+    pub fn decode(registers: &[(&ModbusRegister, Vec<u16>)]) -> Result<Self, ApplicationError> {
+        let values = registers
+            .iter()
+            .map(|(register, words)| {
+                Ok(RegisterValue {
+                    name: register.name.clone(),
+                    value: register.decode(words)?,
+                    unit: register.unit.clone(),
+                    words: words.clone(),
+                })
+            })
+            .collect::<Result<Vec<RegisterValue>, ApplicationError>>()?;
+        Ok(Self { values })
+    }
+}
+
+#[cfg(all(feature = "network", feature = "modbus"))]
+impl Reading for ModbusReading {
+    /**
+     * Returns the register names as CSV fields.
+     *
+     * # Returns
+     * A Result containing the CSV header fields or an ApplicationError.
+     */
+    fn get_csv_header(&self) -> Result<Vec<String>, ApplicationError> {
+        Ok(self.values.iter().map(|value| value.name.clone()).collect())
+    }
+
+    /**
+     * Returns the scaled values as CSV fields.
+     *
+     * # Returns
+     * A Result containing the CSV field values or an ApplicationError.
+     */
+    fn get_csv(&self) -> Result<Vec<String>, ApplicationError> {
+        Ok(self.values.iter().map(|value| value.value.to_string()).collect())
+    }
+
+    /**
+     * Returns the registers as big endian bytes, as sent by the device.
+     *
+     * # Returns
+     * A Result containing a byte vector with the raw data or an ApplicationError.
+     */
+    fn get_raw(&self) -> Result<Vec<u8>, ApplicationError> {
+        Ok(self.values.iter().flat_map(|value| &value.words).flat_map(|word| word.to_be_bytes()).collect())
+    }
+
+    /**
+     * Returns the values with their names and units.
+     *
+     * # Returns
+     * A Result containing the summary or an ApplicationError.
+     */
+    fn get_raw_string(&self) -> Result<String, ApplicationError> {
+        Ok(self
+            .values
+            .iter()
+            .map(|value| match &value.unit {
+                Some(unit) => format!("{} {} {}", value.name, value.value, unit),
+                None => format!("{} {}", value.name, value.value),
+            })
+            .collect::<Vec<String>>()
+            .join(" "))
+    }
+
+    /**
+     * Returns the typed measurement of the first value with a known unit.
+     *
+     * # Returns
+     * The measurement, if a value has a known unit.
+     */
+    fn get_measurement(&self) -> Option<Measurement> {
+        self.values
+            .iter()
+            .find_map(|value| Measurement::from_display(value.value, value.unit.as_deref()?, Vec::new()))
+    }
+}
+
+#[cfg(all(test, feature = "network", feature = "modbus"))]
+mod test {
+    use super::*;
+
+    fn register(name: &str, data_type: RegisterType, word_order: WordOrder, scale: f64, unit: Option<&str>) -> ModbusRegister {
+        ModbusRegister {
+            name: name.into(),
+            address: 0,
+            table: RegisterTable::Input,
+            data_type,
+            word_order,
+            scale,
+            offset: 0.0,
+            unit: unit.map(|unit| unit.into()),
+        }
+    }
+
+    #[test]
+    fn test_decode() {
+        let f32_big = register("voltage", RegisterType::F32, WordOrder::Big, 1.0, Some("V"));
+        assert_eq!(f32_big.decode(&[0x4366, 0x2666]).unwrap() as f32, 230.15);
+        let f32_little = register("voltage", RegisterType::F32, WordOrder::Little, 1.0, Some("V"));
+        assert_eq!(f32_little.decode(&[0x2666, 0x4366]).unwrap() as f32, 230.15);
+        let i16 = register("power", RegisterType::I16, WordOrder::Big, 0.1, Some("W"));
+        assert_eq!(i16.decode(&[0xfffb]).unwrap(), -0.5);
+        let u32 = register("energy", RegisterType::U32, WordOrder::Big, 0.01, None);
+        assert_eq!(u32.decode(&[0x0001, 0x0000]).unwrap(), 655.36);
+        let i32 = register("energy", RegisterType::I32, WordOrder::Little, 1.0, None);
+        assert_eq!(i32.decode(&[0xfffe, 0xffff]).unwrap(), -2.0);
+        assert!(u32.decode(&[0x0001]).is_err());
+    }
+
+    #[test]
+    fn test_reading() {
+        let voltage = register("voltage", RegisterType::U16, WordOrder::Big, 0.1, Some("V"));
+        let energy = register("energy", RegisterType::U32, WordOrder::Big, 1.0, Some("kWh"));
+        let reading = ModbusReading::decode(&[(&energy, vec![0, 42]), (&voltage, vec![2301])]).unwrap();
+        assert_eq!(reading.get_csv_header().unwrap(), ["energy", "voltage"]);
+        assert_eq!(reading.get_csv().unwrap(), ["42", "230.10000000000002"]);
+        assert_eq!(reading.get_raw().unwrap(), [0, 0, 0, 42, 0x08, 0xfd]);
+        assert_eq!(reading.get_raw_string().unwrap(), "energy 42 kWh voltage 230.10000000000002 V");
+        let measurement = reading.get_measurement().unwrap();
+        assert_eq!(measurement.display_unit, "V");
+    }
+
+    #[test]
+    fn test_deserialize() {
+        let register: ModbusRegister =
+            toml::from_str("name = \"current\"\naddress = 6\ntable = \"input\"\ntype = \"f32\"\nunit = \"A\"").unwrap();
+        assert_eq!(register.table, RegisterTable::Input);
+        assert_eq!(register.data_type, RegisterType::F32);
+        assert_eq!(register.words(), 2);
+        assert_eq!(register.scale, 1.0);
+        assert!(toml::from_str::<ModbusRegister>("name = \"x\"\naddress = 0\ntype = \"f64\"").is_err());
+    }
+}
@@ -0,0 +1,493 @@
+use crate::{
+    error::ApplicationError,
+    instruments::reading::{Measurement, Reading},
+};
+
+/**
+ * Length of a B35T+ / B41T+ / OW18 notification, three little endian 16 bit words.
+ */
+pub const OWON_FRAME_LENGTH: usize = 6;
+
+/**
+ * Length of a notification of the original B35T, an FS9922 display frame ending with CR LF.
+ */
+pub const OWON_LEGACY_FRAME_LENGTH: usize = 14;
+
+// Decimal point position reported for overload
+const DECIMALS_OVERLOAD: u16 = 7;
+
+// Digits shown by the original B35T for overload
+const LEGACY_OVERLOAD: &[u8; 4] = b"?0:?";
+
+// Status bytes of the original B35T, SB1 to SB4
+const SB1_AUTO: u8 = 0x20;
+const SB1_AC: u8 = 0x08;
+const SB1_REL: u8 = 0x04;
+const SB1_HOLD: u8 = 0x02;
+const SB2_MAX: u8 = 0x20;
+const SB2_MIN: u8 = 0x10;
+const SB2_LOW_BATTERY: u8 = 0x04;
+const SB2_NANO: u8 = 0x02;
+const SB3_MICRO: u8 = 0x80;
+const SB3_MILLI: u8 = 0x40;
+const SB3_KILO: u8 = 0x20;
+const SB3_MEGA: u8 = 0x10;
+const SB3_CONTINUITY: u8 = 0x08;
+const SB3_DIODE: u8 = 0x04;
+const SB3_PERCENT: u8 = 0x02;
+const SB4_VOLT: u8 = 0x80;
+const SB4_AMPERE: u8 = 0x40;
+const SB4_OHM: u8 = 0x20;
+const SB4_HFE: u8 = 0x10;
+const SB4_HERTZ: u8 = 0x08;
+const SB4_FARAD: u8 = 0x04;
+const SB4_CELSIUS: u8 = 0x02;
+const SB4_FAHRENHEIT: u8 = 0x01;
+
+// Flags in the second word
+const FLAG_HOLD: u16 = 0x01;
+const FLAG_REL: u16 = 0x02;
+const FLAG_AUTO: u16 = 0x04;
+const FLAG_LOW_BATTERY: u16 = 0x08;
+const FLAG_MIN: u16 = 0x10;
+const FLAG_MAX: u16 = 0x20;
+
+// CSV field names, in the order returned by get_csv
+const CSV_HEADER: [&str; 12] = [
+    "mode", "display_value", "overload", "decimal_value", "display_unit", "hold", "rel", "auto", "low_battery",
+    "min", "max", "scaled_value",
+];
+
+/**
+ * Gets the mode name and base unit of a function code.
+ *
+ * # Arguments
+ * `function` - The function code, bits 6-9 of the first word.
+ *
+ * # Returns
+ * Some((mode, unit)) for known function codes, otherwise None.
+ */
+fn function(function: u16) -> Option<(&'static str, &'static str)> {
+    match function {
+        0x0 => Some(("DCV", "V")),
+        0x1 => Some(("ACV", "V")),
+        0x2 => Some(("DCA", "A")),
+        0x3 => Some(("ACA", "A")),
+        0x4 => Some(("OHM", "Ω")),
+        0x5 => Some(("CAP", "F")),
+        0x6 => Some(("Hz", "Hz")),
+        0x7 => Some(("%", "%")),
+        0x8 => Some(("°C", "°C")),
+        0x9 => Some(("°F", "°F")),
+        0xa => Some(("DIODE", "V")),
+        0xb => Some(("CONT", "Ω")),
+        0xc => Some(("hFE", "")),
+        0xf => Some(("NCV", "")),
+        _ => None,
+    }
+}
+
+/**
+ * Gets the SI prefix of a scale code.
+ *
+ * # Arguments
+ * `scale` - The scale code, bits 3-5 of the first word.
+ *
+ * # Returns
+ * The prefix, empty for no prefix.
+ */
+fn prefix(scale: u16) -> &'static str {
+    match scale {
+        1 => "n",
+        2 => "µ",
+        3 => "m",
+        5 => "k",
+        6 => "M",
+        _ => "",
+    }
+}
+
+/**
+ * Gets the mode name and base unit of the status bytes of an original B35T frame.
+ *
+ * # Arguments
+ * `sb1` - The first status byte, with the AC flag.
+ * `sb3` - The third status byte, with the diode, continuity and percent flags.
+ * `sb4` - The fourth status byte, with the unit flags.
+ *
+ * # Returns
+ * Some((mode, unit)) if a function is shown, otherwise None.
+ */
+fn legacy_function(sb1: u8, sb3: u8, sb4: u8) -> Option<(&'static str, &'static str)> {
+    let ac = sb1 & SB1_AC != 0;
+    if sb3 & SB3_DIODE != 0 {
+        Some(("DIODE", "V"))
+    } else if sb3 & SB3_CONTINUITY != 0 {
+        Some(("CONT", "Ω"))
+    } else if sb3 & SB3_PERCENT != 0 {
+        Some(("%", "%"))
+    } else if sb4 & SB4_VOLT != 0 {
+        Some(if ac { ("ACV", "V") } else { ("DCV", "V") })
+    } else if sb4 & SB4_AMPERE != 0 {
+        Some(if ac { ("ACA", "A") } else { ("DCA", "A") })
+    } else if sb4 & SB4_OHM != 0 {
+        Some(("OHM", "Ω"))
+    } else if sb4 & SB4_FARAD != 0 {
+        Some(("CAP", "F"))
+    } else if sb4 & SB4_HERTZ != 0 {
+        Some(("Hz", "Hz"))
+    } else if sb4 & SB4_CELSIUS != 0 {
+        Some(("°C", "°C"))
+    } else if sb4 & SB4_FAHRENHEIT != 0 {
+        Some(("°F", "°F"))
+    } else if sb4 & SB4_HFE != 0 {
+        Some(("hFE", ""))
+    } else {
+        None
+    }
+}
+
+/**
+ * Gets the SI prefix of the status bytes of an original B35T frame.
+ *
+ * # Arguments
+ * `sb2` - The second status byte, with the nano flag.
+ * `sb3` - The third status byte, with the other prefix flags.
+ *
+ * # Returns
+ * The prefix, empty for no prefix.
+ */
+fn legacy_prefix(sb2: u8, sb3: u8) -> &'static str {
+    if sb2 & SB2_NANO != 0 {
+        "n"
+    } else if sb3 & SB3_MICRO != 0 {
+        "µ"
+    } else if sb3 & SB3_MILLI != 0 {
+        "m"
+    } else if sb3 & SB3_KILO != 0 {
+        "k"
+    } else if sb3 & SB3_MEGA != 0 {
+        "M"
+    } else {
+        ""
+    }
+}
+
+/**
+ * Formats the displayed digits with the decimal point and sign.
+ *
+ * # Arguments
+ * `digits` - The displayed digits as an integer.
+ * `decimals` - Number of digits after the decimal point.
+ * `negative` - Whether the minus sign is shown.
+ *
+ * # Returns
+ * The displayed value, e.g. `-12.5`.
+ */
+fn display_digits(digits: u16, decimals: u16, negative: bool) -> String {
+    let value = digits as f64 / 10f64.powi(decimals as i32);
+    format!("{}{:.*}", if negative { "-" } else { "" }, decimals as usize, value)
+}
+
+/**
+ * Represents a measurement notification of an Owon BLE meter, in the format of the
+ * B35T+ / B41T+ / OW18 series or of the original B35T.
+ */
+#[derive(Debug)]
+pub struct OwonReading {
+    pub mode: String,
+    pub display_value: String,
+    pub overload: bool,
+    pub decimal_value: Option<f64>,
+    pub display_unit: String,
+    pub hold: bool,
+    pub rel: bool,
+    pub auto: bool,
+    pub low_battery: bool,
+    pub min: bool,
+    pub max: bool,
+    pub original_bytes: Vec<u8>,
+}
+
+impl OwonReading {
+    /**
+     * Parses a notification, telling the formats apart by their length.
+     *
+     * # Arguments
+     * `frame` - The 6 notification bytes of a B35T+ / B41T+ / OW18, or the 14 of an original B35T.
+     *
+     * # Returns
+     * A Result containing the OwonReading or an ApplicationError if the notification is invalid.
+     */
+    pub fn parse(frame: &[u8]) -> Result<Self, ApplicationError> {
+        match frame.len() {
+            OWON_FRAME_LENGTH => Self::parse_words(frame),
+            OWON_LEGACY_FRAME_LENGTH => Self::parse_legacy(frame),
+            _ => Err(ApplicationError::Command(format!("Invalid Owon frame {:02x?}", frame))),
+        }
+    }
+
+    /**
+     * Parses a B35T+ / B41T+ / OW18 notification. It holds three little endian words: function,
+     * scale and decimal point position, the status flags, and the displayed digits with the
+     * sign in the top bit.
+     *
+     * # Arguments
+     * `frame` - The 6 notification bytes.
+     *
+     * # Returns
+     * A Result containing the OwonReading or an ApplicationError if the notification is invalid.
+     */
+    fn parse_words(frame: &[u8]) -> Result<Self, ApplicationError> {
+        let words: Vec<u16> = frame.chunks_exact(2).map(|word| u16::from_le_bytes([word[0], word[1]])).collect();
+        let (mode, unit) = function((words[0] >> 6) & 0x0f)
+            .ok_or_else(|| ApplicationError::Command(format!("Unknown Owon function {:#x}", (words[0] >> 6) & 0x0f)))?;
+        let decimals = words[0] & 0x07;
+        let overload = decimals == DECIMALS_OVERLOAD;
+        let display_value = if overload {
+            "OL".to_string()
+        } else {
+            display_digits(words[2] & 0x7fff, decimals, words[2] & 0x8000 != 0)
+        };
+        let display_unit = match unit {
+            "" => String::new(),
+            _ => format!("{}{}", prefix((words[0] >> 3) & 0x07), unit),
+        };
+        let flags = words[1];
+        Ok(Self {
+            mode: mode.to_string(),
+            decimal_value: if overload { None } else { display_value.parse().ok() },
+            display_value,
+            overload,
+            display_unit,
+            hold: flags & FLAG_HOLD != 0,
+            rel: flags & FLAG_REL != 0,
+            auto: flags & FLAG_AUTO != 0,
+            low_battery: flags & FLAG_LOW_BATTERY != 0,
+            min: flags & FLAG_MIN != 0,
+            max: flags & FLAG_MAX != 0,
+            original_bytes: frame.to_vec(),
+        })
+    }
+
+    /**
+     * Parses a notification of the original B35T, an FS9922 display frame: the sign, four
+     * ASCII digits, a space, the decimal point position, four status bytes with the flags,
+     * prefix and unit, the bar graph and CR LF.
+     *
+     * # Arguments
+     * `frame` - The 14 notification bytes.
+     *
+     * # Returns
+     * A Result containing the OwonReading or an ApplicationError if the notification is invalid.
+     */
+    fn parse_legacy(frame: &[u8]) -> Result<Self, ApplicationError> {
+        let invalid = || ApplicationError::Command(format!("Invalid Owon B35T frame {:02x?}", frame));
+        if !frame.ends_with(b"\r\n") || !matches!(frame[0], b'+' | b'-') {
+            return Err(invalid());
+        }
+        let (sb1, sb2, sb3, sb4) = (frame[7], frame[8], frame[9], frame[10]);
+        let (mode, unit) = legacy_function(sb1, sb3, sb4)
+            .ok_or_else(|| ApplicationError::Command(format!("Unknown Owon B35T function {:02x?}", &frame[7..11])))?;
+        let decimals = match frame[6] {
+            b'0' => 0,
+            b'1' => 3,
+            b'2' => 2,
+            b'4' => 1,
+            _ => return Err(invalid()),
+        };
+        let overload = &frame[1..5] == LEGACY_OVERLOAD;
+        let display_value = if overload {
+            "OL".to_string()
+        } else {
+            let digits = std::str::from_utf8(&frame[1..5])
+                .ok()
+                .filter(|digits| digits.bytes().all(|digit| digit.is_ascii_digit()))
+                .and_then(|digits| digits.parse().ok())
+                .ok_or_else(invalid)?;
+            display_digits(digits, decimals, frame[0] == b'-')
+        };
+        let display_unit = match unit {
+            "" => String::new(),
+            _ => format!("{}{}", legacy_prefix(sb2, sb3), unit),
+        };
+        Ok(Self {
+            mode: mode.to_string(),
+            decimal_value: if overload { None } else { display_value.parse().ok() },
+            display_value,
+            overload,
+            display_unit,
+            hold: sb1 & SB1_HOLD != 0,
+            rel: sb1 & SB1_REL != 0,
+            auto: sb1 & SB1_AUTO != 0,
+            low_battery: sb2 & SB2_LOW_BATTERY != 0,
+            min: sb2 & SB2_MIN != 0,
+            max: sb2 & SB2_MAX != 0,
+            original_bytes: frame.to_vec(),
+        })
+    }
+}
+
+impl Reading for OwonReading {
+    /**
+     * Returns the names of the CSV fields.
+     *
+     * # Returns
+     * A Result containing the CSV header fields or an ApplicationError.
+     */
+    fn get_csv_header(&self) -> Result<Vec<String>, ApplicationError> {
+        Ok(CSV_HEADER.iter().map(|field| field.to_string()).collect())
+    }
+
+    /**
+     * Returns the measurement data as CSV fields.
+     *
+     * # Returns
+     * A Result containing the CSV field values or an ApplicationError.
+     */
+    fn get_csv(&self) -> Result<Vec<String>, ApplicationError> {
+        Ok(vec![
+            self.mode.clone(),
+            self.display_value.clone(),
+            self.overload.to_string(),
+            self.decimal_value.map(|value| value.to_string()).unwrap_or_default(),
+            self.display_unit.clone(),
+            self.hold.to_string(),
+            self.rel.to_string(),
+            self.auto.to_string(),
+            self.low_battery.to_string(),
+            self.min.to_string(),
+            self.max.to_string(),
+            self.get_measurement()
+                .map(|measurement| measurement.value.to_string())
+                .unwrap_or_default(),
+        ])
+    }
+
+    /**
+     * Returns the raw notification as a byte vector.
+     *
+     * # Returns
+     * A Result containing a byte vector with the raw data or an ApplicationError.
+     */
+    fn get_raw(&self) -> Result<Vec<u8>, ApplicationError> {
+        Ok(self.original_bytes.clone())
+    }
+
+    /**
+     * Returns the displayed value and unit as a String.
+     *
+     * # Returns
+     * A Result containing a String with the value or an ApplicationError.
+     */
+    fn get_raw_string(&self) -> Result<String, ApplicationError> {
+        Ok(format!("{} {} {}", self.mode, self.display_value, self.display_unit))
+    }
+
+    /**
+     * Returns the typed measurement of the notification.
+     *
+     * # Returns
+     * The measurement, or None for overload and functions without a unit.
+     */
+    fn get_measurement(&self) -> Option<Measurement> {
+        let flags = [
+            ("max", self.max),
+            ("min", self.min),
+            ("hold", self.hold),
+            ("rel", self.rel),
+            ("auto", self.auto),
+        ]
+        .iter()
+        .filter(|(_, active)| *active)
+        .map(|(flag, _)| flag.to_string())
+        .collect();
+        Measurement::from_display(self.decimal_value?, &self.display_unit, flags)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_dc_voltage() {
+        // 1.234 V, DCV no prefix, 3 decimals, auto
+        let reading = OwonReading::parse(&[0x23, 0x00, 0x04, 0x00, 0xd2, 0x04]).unwrap();
+        assert_eq!(reading.mode, "DCV");
+        assert_eq!(reading.display_value, "1.234");
+        assert_eq!(reading.display_unit, "V");
+        assert!(reading.auto && !reading.hold);
+        assert_eq!(reading.get_measurement().unwrap().value, 1.234);
+    }
+
+    #[test]
+    fn test_parse_prefix_sign_and_flags() {
+        // -12.5 mA, DCA milli, 1 decimal, hold and low battery
+        let reading = OwonReading::parse(&[0x99, 0x00, 0x09, 0x00, 0x7d, 0x80]).unwrap();
+        assert_eq!(reading.mode, "DCA");
+        assert_eq!(reading.display_value, "-12.5");
+        assert_eq!(reading.display_unit, "mA");
+        assert!(reading.hold && reading.low_battery && !reading.auto);
+        assert!((reading.get_measurement().unwrap().value + 0.0125).abs() < 1e-12);
+        // 4.70 kΩ
+        let reading = OwonReading::parse(&[0x2a, 0x01, 0x00, 0x00, 0xd6, 0x01]).unwrap();
+        assert_eq!(reading.display_value, "4.70");
+        assert_eq!(reading.display_unit, "kΩ");
+    }
+
+    #[test]
+    fn test_overload_and_invalid() {
+        let reading = OwonReading::parse(&[0x27, 0x01, 0x00, 0x00, 0x00, 0x00]).unwrap();
+        assert!(reading.overload);
+        assert!(reading.get_measurement().is_none());
+        assert!(OwonReading::parse(&[0x40, 0x03, 0x00, 0x00, 0x00, 0x00]).is_err());
+        assert!(OwonReading::parse(&[0x23, 0x00, 0x04, 0x00]).is_err());
+    }
+
+    #[test]
+    fn test_parse_legacy_dc_voltage() {
+        // +1.234 V, auto DC
+        let reading = OwonReading::parse(b"+1234 1\x30\x00\x00\x80\x00\r\n").unwrap();
+        assert_eq!(reading.mode, "DCV");
+        assert_eq!(reading.display_value, "1.234");
+        assert_eq!(reading.display_unit, "V");
+        assert!(reading.auto && !reading.hold);
+        assert_eq!(reading.get_measurement().unwrap().value, 1.234);
+    }
+
+    #[test]
+    fn test_parse_legacy_prefix_sign_and_flags() {
+        // -12.5 mA, DC with hold and low battery
+        let reading = OwonReading::parse(b"-0125 4\x12\x04\x40\x40\x00\r\n").unwrap();
+        assert_eq!(reading.mode, "DCA");
+        assert_eq!(reading.display_value, "-12.5");
+        assert_eq!(reading.display_unit, "mA");
+        assert!(reading.hold && reading.low_battery && !reading.auto);
+        assert!((reading.get_measurement().unwrap().value + 0.0125).abs() < 1e-12);
+        // 230.1 V AC, max
+        let reading = OwonReading::parse(b"+2301 4\x28\x20\x00\x80\x00\r\n").unwrap();
+        assert_eq!(reading.mode, "ACV");
+        assert!(reading.max && !reading.min);
+        // 4.70 kΩ, and diode taking precedence over its volt unit
+        let reading = OwonReading::parse(b"+0470 2\x20\x00\x20\x20\x00\r\n").unwrap();
+        assert_eq!(reading.display_value, "4.70");
+        assert_eq!(reading.display_unit, "kΩ");
+        let reading = OwonReading::parse(b"+0512 1\x00\x00\x04\x80\x00\r\n").unwrap();
+        assert_eq!(reading.mode, "DIODE");
+        assert_eq!(reading.display_value, "0.512");
+    }
+
+    #[test]
+    fn test_legacy_overload_and_invalid() {
+        let reading = OwonReading::parse(b"+?0:? 4\x20\x00\x20\x20\x00\r\n").unwrap();
+        assert!(reading.overload);
+        assert_eq!(reading.display_value, "OL");
+        assert!(reading.get_measurement().is_none());
+        // No unit, bad decimal point, bad digits, missing CR LF
+        assert!(OwonReading::parse(b"+1234 1\x00\x00\x00\x00\x00\r\n").is_err());
+        assert!(OwonReading::parse(b"+1234 3\x00\x00\x00\x80\x00\r\n").is_err());
+        assert!(OwonReading::parse(b"+12a4 1\x00\x00\x00\x80\x00\r\n").is_err());
+        assert!(OwonReading::parse(b"+1234 1\x00\x00\x00\x80\x00\x00\x00").is_err());
+    }
+}
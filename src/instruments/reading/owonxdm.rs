@@ -0,0 +1,145 @@
+use crate::{
+    error::ApplicationError,
+    instruments::{
+        command::XdmFunction,
+        reading::{Measurement, Reading},
+    },
+};
+
+// CSV field names, in the order returned by get_csv
+const CSV_HEADER: [&str; 4] = ["function", "value", "unit", "overload"];
+
+/**
+ * Magnitude from which a value is the SCPI overflow value 9.9E37, returned for OL.
+ */
+const OVERFLOW: f64 = 9.9e37;
+
+/**
+ * Represents the response of an Owon XDM bench multimeter to `MEAS?`, an NR3 number such as
+ * `1.23456E+00` in the unit of the selected function.
+ */
+#[derive(Debug)]
+pub struct OwonXdmReading {
+    pub function: XdmFunction,
+    /**
+     * The value, None on overload.
+     */
+    pub value: Option<f64>,
+    pub original_bytes: Vec<u8>,
+}
+
+impl OwonXdmReading {
+    /**
+     * Parses a `MEAS?` response.
+     *
+     * # Arguments
+     * `function` - The selected function, giving the unit.
+     * `response` - The raw response.
+     *
+     * # Returns
+     * A Result containing the OwonXdmReading or an ApplicationError if the response is not a number.
+     */
+    pub fn parse(function: XdmFunction, response: &[u8]) -> Result<Self, ApplicationError> {
+        let text = String::from_utf8_lossy(response);
+        let value = text
+            .trim()
+            .parse::<f64>()
+            .map_err(|_| ApplicationError::Command(format!("Invalid Owon XDM measurement: {}", text.trim())))?;
+        Ok(Self {
+            function,
+            value: Some(value).filter(|value| value.abs() < OVERFLOW),
+            original_bytes: response.to_vec(),
+        })
+    }
+}
+
+impl Reading for OwonXdmReading {
+    /**
+     * Returns the names of the CSV fields.
+     *
+     * # Returns
+     * A Result containing the CSV header fields or an ApplicationError.
+     */
+    fn get_csv_header(&self) -> Result<Vec<String>, ApplicationError> {
+        Ok(CSV_HEADER.iter().map(|field| field.to_string()).collect())
+    }
+
+    /**
+     * Returns the measurement as CSV fields. The value is empty on overload.
+     *
+     * # Returns
+     * A Result containing the CSV field values or an ApplicationError.
+     */
+    fn get_csv(&self) -> Result<Vec<String>, ApplicationError> {
+        Ok(vec![
+            self.function.name().to_string(),
+            self.value.map(|value| value.to_string()).unwrap_or_default(),
+            self.function.unit().to_string(),
+            self.value.is_none().to_string(),
+        ])
+    }
+
+    /**
+     * Returns the response as a byte vector.
+     *
+     * # Returns
+     * A Result containing a byte vector with the raw data or an ApplicationError.
+     */
+    fn get_raw(&self) -> Result<Vec<u8>, ApplicationError> {
+        Ok(self.original_bytes.clone())
+    }
+
+    /**
+     * Returns the value and unit as a String, `OL` on overload.
+     *
+     * # Returns
+     * A Result containing a String with the value or an ApplicationError.
+     */
+    fn get_raw_string(&self) -> Result<String, ApplicationError> {
+        Ok(match self.value {
+            Some(value) => format!("{} {}", value, self.function.unit()),
+            None => format!("OL {}", self.function.unit()),
+        })
+    }
+
+    /**
+     * Returns the typed measurement. AC functions are flagged `ac`.
+     *
+     * # Returns
+     * The measurement, or None on overload.
+     */
+    fn get_measurement(&self) -> Option<Measurement> {
+        let flags = match self.function.is_ac() {
+            true => vec!["ac".to_string()],
+            false => Vec::new(),
+        };
+        Measurement::from_display(self.value?, self.function.unit(), flags)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::instruments::reading::measurement::Quantity;
+
+    #[test]
+    fn test_parse() {
+        let reading = OwonXdmReading::parse(XdmFunction::VoltageAc, b"2.30512E+02\r\n").unwrap();
+        assert_eq!(reading.value, Some(230.512));
+        assert_eq!(reading.get_csv().unwrap(), vec!["VoltageAc", "230.512", "V", "false"]);
+        let measurement = reading.get_measurement().unwrap();
+        assert_eq!(measurement.quantity, Quantity::Voltage);
+        assert_eq!(measurement.flags, vec!["ac"]);
+        let reading = OwonXdmReading::parse(XdmFunction::Resistance, b"1.2E+03\n").unwrap();
+        assert_eq!(reading.get_raw_string().unwrap(), "1200 Ω");
+    }
+
+    #[test]
+    fn test_overload() {
+        let reading = OwonXdmReading::parse(XdmFunction::Resistance, b"9.9E+37\n").unwrap();
+        assert_eq!(reading.value, None);
+        assert_eq!(reading.get_raw_string().unwrap(), "OL Ω");
+        assert!(reading.get_measurement().is_none());
+        assert!(OwonXdmReading::parse(XdmFunction::Resistance, b"ERR\n").is_err());
+    }
+}
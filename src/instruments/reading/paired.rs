@@ -0,0 +1,171 @@
+use crate::{
+    error::ApplicationError,
+    instruments::reading::{Measurement, Reading},
+};
+
+/**
+ * Prefix of the setpoint columns.
+ */
+const SETPOINT_PREFIX: &str = "set_";
+
+/**
+ * A stimulus/response record: the setpoints in effect and the readings of the queries that
+ * followed them, e.g. `Voltage:5` followed by `Measure`. The setpoint columns come first, then
+ * the fields of every response. Responses without CSV fields, e.g. raw SCPI responses, give a
+ * `response` field with their text. Response fields whose name occurs in more than one response
+ * are numbered, e.g. `response_1` and `response_2`.
+ */
+pub struct PairedReading {
+    /**
+     * Setpoint names and values, e.g. (`Voltage`, `5`).
+     */
+    pub setpoints: Vec<(String, String)>,
+    pub responses: Vec<Box<dyn Reading>>,
+}
+
+impl PairedReading {
+    /**
+     * Creates a new PairedReading.
+     *
+     * # Arguments
+     * `setpoints` - The setpoint names and values.
+     * `responses` - The readings of the queries.
+     *
+     * # Returns
+     * A new PairedReading instance.
+     */
+    pub fn new(setpoints: Vec<(String, String)>, responses: Vec<Box<dyn Reading>>) -> Self {
+        Self { setpoints, responses }
+    }
+}
+
+/**
+ * Returns the CSV fields of a response, or its text for responses without CSV fields.
+ *
+ * # Arguments
+ * `response` - The response reading.
+ *
+ * # Returns
+ * A Result containing the field names and values or an ApplicationError.
+ */
+pub(crate) fn response_fields(response: &dyn Reading) -> Result<(Vec<String>, Vec<String>), ApplicationError> {
+    match (response.get_csv_header(), response.get_csv()) {
+        (Ok(header), Ok(values)) => Ok((header, values)),
+        _ => Ok((vec!["response".into()], vec![response.get_raw_string()?.trim().to_string()])),
+    }
+}
+
+impl Reading for PairedReading {
+    /**
+     * Returns the setpoint columns followed by the fields of the responses.
+     *
+     * # Returns
+     * A Result containing the CSV header fields or an ApplicationError.
+     */
+    fn get_csv_header(&self) -> Result<Vec<String>, ApplicationError> {
+        let mut header: Vec<String> = self
+            .setpoints
+            .iter()
+            .map(|(name, _)| format!("{}{}", SETPOINT_PREFIX, name))
+            .collect();
+        let headers = self
+            .responses
+            .iter()
+            .map(|response| response_fields(response.as_ref()).map(|(header, _)| header))
+            .collect::<Result<Vec<Vec<String>>, ApplicationError>>()?;
+        for (index, fields) in headers.iter().enumerate() {
+            for field in fields {
+                let shared = headers
+                    .iter()
+                    .enumerate()
+                    .any(|(other, fields)| other != index && fields.contains(field));
+                header.push(match shared {
+                    true => format!("{}_{}", field, index + 1),
+                    false => field.clone(),
+                });
+            }
+        }
+        Ok(header)
+    }
+
+    /**
+     * Returns the setpoint values followed by the fields of the responses.
+     *
+     * # Returns
+     * A Result containing the CSV field values or an ApplicationError.
+     */
+    fn get_csv(&self) -> Result<Vec<String>, ApplicationError> {
+        let mut fields: Vec<String> = self.setpoints.iter().map(|(_, value)| value.clone()).collect();
+        for response in &self.responses {
+            fields.extend(response_fields(response.as_ref())?.1);
+        }
+        Ok(fields)
+    }
+
+    /**
+     * Returns the raw data of the responses, concatenated.
+     *
+     * # Returns
+     * A Result containing a byte vector with the raw data or an ApplicationError.
+     */
+    fn get_raw(&self) -> Result<Vec<u8>, ApplicationError> {
+        let mut raw = Vec::new();
+        for response in &self.responses {
+            raw.extend(response.get_raw()?);
+        }
+        Ok(raw)
+    }
+
+    /**
+     * Returns the setpoints and the responses as a String, e.g. `Voltage=5: 4.99`.
+     *
+     * # Returns
+     * A Result containing a String with the data or an ApplicationError.
+     */
+    fn get_raw_string(&self) -> Result<String, ApplicationError> {
+        let setpoints: Vec<String> = self
+            .setpoints
+            .iter()
+            .map(|(name, value)| format!("{}={}", name, value))
+            .collect();
+        let responses = self
+            .responses
+            .iter()
+            .map(|response| response.get_raw_string().map(|text| text.trim().to_string()))
+            .collect::<Result<Vec<String>, ApplicationError>>()?;
+        Ok(format!("{}: {}", setpoints.join(" "), responses.join("; ")))
+    }
+
+    /**
+     * Returns the typed measurement of the first response that has one.
+     *
+     * # Returns
+     * The measurement, if a response has one.
+     */
+    fn get_measurement(&self) -> Option<Measurement> {
+        self.responses.iter().find_map(|response| response.get_measurement())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::instruments::reading::ScpiRawReading;
+
+    #[test]
+    fn test_paired_csv() {
+        let reading = PairedReading::new(
+            vec![("Voltage".into(), "5".into()), ("FREQ".into(), "1000".into())],
+            vec![
+                Box::new(ScpiRawReading::new(b"4.99\n".to_vec())),
+                Box::new(ScpiRawReading::new(b"0.25\n".to_vec())),
+            ],
+        );
+        let header = reading.get_csv_header().unwrap();
+        assert_eq!(header[..2], ["set_Voltage", "set_FREQ"]);
+        assert_eq!(header.len(), reading.get_csv().unwrap().len());
+        assert_eq!(header[2..], ["response_1", "response_2"]);
+        assert_eq!(reading.get_csv().unwrap(), ["5", "1000", "4.99", "0.25"]);
+        assert_eq!(reading.get_raw_string().unwrap(), "Voltage=5 FREQ=1000: 4.99; 0.25");
+    }
+}
@@ -0,0 +1,285 @@
+use crate::{
+    error::ApplicationError,
+    instruments::reading::{Measurement, Reading},
+};
+
+// Holding registers of the RD60xx series
+pub const REG_MODEL: u16 = 0;
+pub const REG_SERIAL_HIGH: u16 = 1;
+pub const REG_SERIAL_LOW: u16 = 2;
+pub const REG_FIRMWARE: u16 = 3;
+pub const REG_VOLTAGE_SET: u16 = 8;
+pub const REG_CURRENT_SET: u16 = 9;
+pub const REG_VOLTAGE_OUT: u16 = 10;
+pub const REG_CURRENT_OUT: u16 = 11;
+pub const REG_POWER_HIGH: u16 = 12;
+pub const REG_POWER_LOW: u16 = 13;
+pub const REG_VOLTAGE_IN: u16 = 14;
+pub const REG_PROTECTION: u16 = 16;
+pub const REG_REGULATION: u16 = 17;
+pub const REG_OUTPUT: u16 = 18;
+
+/**
+ * Number of registers of a status block, from the voltage setpoint to the output state.
+ */
+pub const STATUS_REGISTERS: u16 = REG_OUTPUT - REG_VOLTAGE_SET + 1;
+
+// CSV field names, in the order returned by get_csv
+const CSV_HEADER: [&str; 8] = ["values", "voltage", "current", "power", "input_voltage", "mode", "output", "protection"];
+
+/**
+ * A Riden RD60xx model with the scale of its voltage, current and power registers.
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RidenModel {
+    pub name: &'static str,
+    pub voltage_scale: f64,
+    pub current_scale: f64,
+    pub power_scale: f64,
+}
+
+impl RidenModel {
+    /**
+     * Gets the model from the model register.
+     *
+     * # Arguments
+     * `id` - The value of the model register, e.g. 60062 for the RD6006.
+     *
+     * # Returns
+     * A Result containing the RidenModel or an ApplicationError for unknown models.
+     */
+    pub fn from_id(id: u16) -> Result<Self, ApplicationError> {
+        let model = |name, voltage_scale, current_scale, power_scale| {
+            Ok(Self { name, voltage_scale, current_scale, power_scale })
+        };
+        match id {
+            60065 => model("RD6006P", 1000.0, 10000.0, 1000.0),
+            60060..=60069 => model("RD6006", 100.0, 1000.0, 100.0),
+            60120..=60129 => model("RD6012", 100.0, 100.0, 100.0),
+            60180..=60189 => model("RD6018", 100.0, 100.0, 100.0),
+            60240..=60249 => model("RD6024", 100.0, 100.0, 100.0),
+            _ => Err(ApplicationError::Command(format!("Unsupported Riden model id {}", id))),
+        }
+    }
+}
+
+/**
+ * Represents the voltage, current and status registers of a Riden RD60xx power supply.
+ */
+#[derive(Debug)]
+pub struct RidenReading {
+    /**
+     * True for the setpoints, false for the actual output values.
+     */
+    pub setpoint: bool,
+    pub voltage: f64,
+    pub current: f64,
+    /**
+     * Output power in W, None for setpoints.
+     */
+    pub power: Option<f64>,
+    pub input_voltage: Option<f64>,
+    /**
+     * `CV` or `CC`, None for setpoints.
+     */
+    pub mode: Option<&'static str>,
+    pub output: Option<bool>,
+    /**
+     * `OVP` or `OCP` after a protection tripped, empty otherwise. None for setpoints.
+     */
+    pub protection: Option<&'static str>,
+    /**
+     * The status block registers as read.
+     */
+    pub registers: Vec<u16>,
+}
+
+impl RidenReading {
+    /**
+     * Decodes the actual output values from a status block.
+     *
+     * # Arguments
+     * `model` - The model, giving the register scales.
+     * `registers` - The status block, STATUS_REGISTERS registers from REG_VOLTAGE_SET.
+     *
+     * # Returns
+     * A Result containing the RidenReading or an ApplicationError if the block is incomplete.
+     */
+    pub fn measured(model: &RidenModel, registers: &[u16]) -> Result<Self, ApplicationError> {
+        let register = status_block(registers)?;
+        let power = ((register(REG_POWER_HIGH) as u32) << 16) | register(REG_POWER_LOW) as u32;
+        Ok(Self {
+            setpoint: false,
+            voltage: register(REG_VOLTAGE_OUT) as f64 / model.voltage_scale,
+            current: register(REG_CURRENT_OUT) as f64 / model.current_scale,
+            power: Some(power as f64 / model.power_scale),
+            input_voltage: Some(register(REG_VOLTAGE_IN) as f64 / 100.0),
+            mode: Some(if register(REG_REGULATION) == 0 { "CV" } else { "CC" }),
+            output: Some(register(REG_OUTPUT) != 0),
+            protection: Some(match register(REG_PROTECTION) {
+                1 => "OVP",
+                2 => "OCP",
+                _ => "",
+            }),
+            registers: registers.to_vec(),
+        })
+    }
+
+    /**
+     * Decodes the voltage and current setpoints from a status block.
+     *
+     * # Arguments
+     * `model` - The model, giving the register scales.
+     * `registers` - The status block, STATUS_REGISTERS registers from REG_VOLTAGE_SET.
+     *
+     * # Returns
+     * A Result containing the RidenReading or an ApplicationError if the block is incomplete.
+     */
+    pub fn setpoints(model: &RidenModel, registers: &[u16]) -> Result<Self, ApplicationError> {
+        let register = status_block(registers)?;
+        Ok(Self {
+            setpoint: true,
+            voltage: register(REG_VOLTAGE_SET) as f64 / model.voltage_scale,
+            current: register(REG_CURRENT_SET) as f64 / model.current_scale,
+            power: None,
+            input_voltage: None,
+            mode: None,
+            output: None,
+            protection: None,
+            registers: registers.to_vec(),
+        })
+    }
+}
+
+/**
+ * Checks the length of a status block and returns a register accessor.
+ *
+ * # Arguments
+ * `registers` - The status block.
+ *
+ * # Returns
+ * A Result containing a function returning a register by address, or an ApplicationError.
+ */
+fn status_block(registers: &[u16]) -> Result<impl Fn(u16) -> u16 + '_, ApplicationError> {
+    if registers.len() != STATUS_REGISTERS as usize {
+        return Err(ApplicationError::Command(format!(
+            "Expected {} Riden status registers, got {}",
+            STATUS_REGISTERS,
+            registers.len()
+        )));
+    }
+    Ok(move |register: u16| registers[(register - REG_VOLTAGE_SET) as usize])
+}
+
+impl Reading for RidenReading {
+    /**
+     * Returns the names of the CSV fields.
+     *
+     * # Returns
+     * A Result containing the CSV header fields or an ApplicationError.
+     */
+    fn get_csv_header(&self) -> Result<Vec<String>, ApplicationError> {
+        Ok(CSV_HEADER.iter().map(|field| field.to_string()).collect())
+    }
+
+    /**
+     * Returns the values as CSV fields. Values not part of the setpoints are empty.
+     *
+     * # Returns
+     * A Result containing the CSV field values or an ApplicationError.
+     */
+    fn get_csv(&self) -> Result<Vec<String>, ApplicationError> {
+        let optional = |value: Option<String>| value.unwrap_or_default();
+        Ok(vec![
+            if self.setpoint { "setpoint" } else { "output" }.to_string(),
+            self.voltage.to_string(),
+            self.current.to_string(),
+            optional(self.power.map(|power| power.to_string())),
+            optional(self.input_voltage.map(|voltage| voltage.to_string())),
+            optional(self.mode.map(|mode| mode.to_string())),
+            optional(self.output.map(|output| output.to_string())),
+            optional(self.protection.map(|protection| protection.to_string())),
+        ])
+    }
+
+    /**
+     * Returns the status block registers as big endian bytes, as sent by the power supply.
+     *
+     * # Returns
+     * A Result containing a byte vector with the raw data or an ApplicationError.
+     */
+    fn get_raw(&self) -> Result<Vec<u8>, ApplicationError> {
+        Ok(self.registers.iter().flat_map(|register| register.to_be_bytes()).collect())
+    }
+
+    /**
+     * Returns a human readable summary of the reading.
+     *
+     * # Returns
+     * A Result containing the summary or an ApplicationError.
+     */
+    fn get_raw_string(&self) -> Result<String, ApplicationError> {
+        let mut fields = vec![format!("{} V", self.voltage), format!("{} A", self.current)];
+        if let Some(power) = self.power {
+            fields.push(format!("{} W", power));
+        }
+        if let (Some(mode), Some(output)) = (self.mode, self.output) {
+            fields.push(format!("{} output {}", mode, if output { "on" } else { "off" }));
+        }
+        if let Some(protection) = self.protection.filter(|protection| !protection.is_empty()) {
+            fields.push(protection.to_string());
+        }
+        Ok(fields.join(" "))
+    }
+
+    /**
+     * Returns the typed measurement of the output voltage.
+     *
+     * # Returns
+     * The measurement, or None for setpoints.
+     */
+    fn get_measurement(&self) -> Option<Measurement> {
+        match self.setpoint {
+            true => None,
+            false => Measurement::from_display(self.voltage, "V", Vec::new()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // 12.00 V / 1.500 A set, 11.98 V / 1.234 A / 14.78 W out, 24.1 V in, CC, output on
+    const REGISTERS: [u16; STATUS_REGISTERS as usize] = [1200, 1500, 1198, 1234, 0, 1478, 2410, 0, 0, 1, 1];
+
+    #[test]
+    fn test_model() {
+        assert_eq!(RidenModel::from_id(60062).unwrap().name, "RD6006");
+        assert_eq!(RidenModel::from_id(60065).unwrap().current_scale, 10000.0);
+        assert_eq!(RidenModel::from_id(60121).unwrap().current_scale, 100.0);
+        assert!(RidenModel::from_id(1).is_err());
+    }
+
+    #[test]
+    fn test_measured() {
+        let model = RidenModel::from_id(60062).unwrap();
+        let reading = RidenReading::measured(&model, &REGISTERS).unwrap();
+        assert_eq!(
+            reading.get_csv().unwrap(),
+            vec!["output", "11.98", "1.234", "14.78", "24.1", "CC", "true", ""]
+        );
+        assert_eq!(reading.get_raw_string().unwrap(), "11.98 V 1.234 A 14.78 W CC output on");
+        assert_eq!(reading.get_measurement().unwrap().value, 11.98);
+        assert_eq!(reading.get_raw().unwrap()[..4], [0x04, 0xb0, 0x05, 0xdc]);
+    }
+
+    #[test]
+    fn test_setpoints() {
+        let model = RidenModel::from_id(60121).unwrap();
+        let reading = RidenReading::setpoints(&model, &REGISTERS).unwrap();
+        assert_eq!(reading.get_csv().unwrap(), vec!["setpoint", "12", "15", "", "", "", "", ""]);
+        assert!(reading.get_measurement().is_none());
+        assert!(RidenReading::setpoints(&model, &REGISTERS[..4]).is_err());
+    }
+}
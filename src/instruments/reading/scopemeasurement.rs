@@ -0,0 +1,142 @@
+use crate::{
+    error::ApplicationError,
+    instruments::{
+        command::ScopeMeasurement,
+        reading::{Measurement, Reading},
+    },
+};
+
+// Values at or above this are used by scopes to signal an invalid measurement
+const INVALID_THRESHOLD: f64 = 9.9e37;
+
+/**
+ * Represents the result of a built-in oscilloscope measurement.
+ */
+#[derive(Debug)]
+pub struct ScopeMeasurementReading {
+    pub original_bytes: Vec<u8>,
+    pub measurement: ScopeMeasurement,
+    pub value: Option<f64>,
+}
+
+impl ScopeMeasurementReading {
+    /**
+     * Parses a measurement response. Both plain NR3 values (`1.234e+00`) and Siglent style
+     * responses (`C1:PAVA PKPK,1.234E+00V`) are accepted.
+     *
+     * # Arguments
+     * `measurement` - The measurement that was queried.
+     * `bytes` - The raw response.
+     *
+     * # Returns
+     * A new ScopeMeasurementReading. The value is None if the scope reported an invalid measurement.
+     */
+    pub fn parse(measurement: ScopeMeasurement, bytes: Vec<u8>) -> Self {
+        let text = String::from_utf8_lossy(&bytes);
+        let field = text.trim().rsplit(',').next().unwrap_or("");
+        let number = field.trim_end_matches(|c: char| c.is_ascii_alphabetic() || c == '%');
+        let value = number
+            .parse::<f64>()
+            .ok()
+            .filter(|value| value.abs() < INVALID_THRESHOLD);
+        Self {
+            original_bytes: bytes,
+            measurement,
+            value,
+        }
+    }
+}
+
+impl Reading for ScopeMeasurementReading {
+    /**
+     * Returns the names of the CSV fields.
+     *
+     * # Returns
+     * A Result containing the CSV header fields or an ApplicationError.
+     */
+    fn get_csv_header(&self) -> Result<Vec<String>, ApplicationError> {
+        Ok(vec!["item".into(), "source".into(), "value".into(), "unit".into()])
+    }
+
+    /**
+     * Returns the measurement as CSV fields.
+     *
+     * # Returns
+     * A Result containing the CSV field values or an ApplicationError.
+     */
+    fn get_csv(&self) -> Result<Vec<String>, ApplicationError> {
+        Ok(vec![
+            format!("{:?}", self.measurement.item),
+            self.measurement.source.clone(),
+            self.value.map(|value| value.to_string()).unwrap_or_default(),
+            self.measurement.item.unit().to_string(),
+        ])
+    }
+
+    /**
+     * Returns the raw response as a byte vector.
+     *
+     * # Returns
+     * A Result containing a byte vector with the raw data or an ApplicationError.
+     */
+    fn get_raw(&self) -> Result<Vec<u8>, ApplicationError> {
+        Ok(self.original_bytes.clone())
+    }
+
+    /**
+     * Returns the raw response as a String.
+     *
+     * # Returns
+     * A Result containing a String with the raw data or an ApplicationError.
+     */
+    fn get_raw_string(&self) -> Result<String, ApplicationError> {
+        String::from_utf8(self.original_bytes.clone()).map_err(|e| {
+            ApplicationError::General(format!("Failed to convert raw bytes to string: {}", e))
+        })
+    }
+
+    /**
+     * Returns the measurement value as a typed measurement.
+     *
+     * # Returns
+     * The measurement, or None if the scope reported an invalid measurement.
+     */
+    fn get_measurement(&self) -> Option<Measurement> {
+        Measurement::from_display(self.value?, self.measurement.item.unit(), Vec::new())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::instruments::command::scope::ScopeItem;
+
+    fn measurement() -> ScopeMeasurement {
+        ScopeMeasurement {
+            item: ScopeItem::Vpp,
+            source: "CHAN1".into(),
+        }
+    }
+
+    #[test]
+    fn test_parse_rigol_response() {
+        let reading = ScopeMeasurementReading::parse(measurement(), b"1.234000e+00\n".to_vec());
+        assert_eq!(reading.value, Some(1.234));
+        assert_eq!(reading.get_csv().unwrap(), vec!["Vpp", "CHAN1", "1.234", "V"]);
+        assert_eq!(reading.get_measurement().unwrap().value, 1.234);
+    }
+
+    #[test]
+    fn test_parse_siglent_response() {
+        let reading = ScopeMeasurementReading::parse(measurement(), b"C1:PAVA PKPK,2.50E-01V\n".to_vec());
+        assert_eq!(reading.value, Some(0.25));
+    }
+
+    #[test]
+    fn test_parse_invalid_measurement() {
+        let reading = ScopeMeasurementReading::parse(measurement(), b"9.9E37\n".to_vec());
+        assert_eq!(reading.value, None);
+        let reading = ScopeMeasurementReading::parse(measurement(), b"C1:PAVA PKPK,****\n".to_vec());
+        assert_eq!(reading.value, None);
+    }
+}
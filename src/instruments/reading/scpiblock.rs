@@ -0,0 +1,82 @@
+use crate::{error::ApplicationError, instruments::reading::Reading};
+
+/**
+ * Represents the payload of an IEEE 488.2 definite length binary block, e.g. waveform data,
+ * a screenshot or arbitrary waveform readback. The block header has been removed.
+ */
+#[derive(Debug)]
+pub struct ScpiBlockReading {
+    payload: Vec<u8>,
+}
+
+impl ScpiBlockReading {
+    /**
+     * Creates a new instance of ScpiBlockReading.
+     *
+     * # Arguments
+     * `payload` - The block payload without the header.
+     *
+     * # Returns
+     * A new ScpiBlockReading instance.
+     */
+    pub fn new(payload: Vec<u8>) -> Self {
+        Self { payload }
+    }
+}
+
+impl Reading for ScpiBlockReading {
+    /**
+     * Returns the names of the CSV fields.
+     *
+     * # Returns
+     * A Result containing the CSV header fields or an ApplicationError.
+     */
+    fn get_csv_header(&self) -> Result<Vec<String>, ApplicationError> {
+        Ok(vec!["bytes".into()])
+    }
+
+    /**
+     * Returns the payload length as a CSV field.
+     *
+     * # Returns
+     * A Result containing the CSV field values or an ApplicationError.
+     */
+    fn get_csv(&self) -> Result<Vec<String>, ApplicationError> {
+        Ok(vec![self.payload.len().to_string()])
+    }
+
+    /**
+     * Returns the payload as a byte vector.
+     *
+     * # Returns
+     * A Result containing a byte vector with the payload or an ApplicationError.
+     */
+    fn get_raw(&self) -> Result<Vec<u8>, ApplicationError> {
+        Ok(self.payload.clone())
+    }
+
+    /**
+     * Returns the payload as a String.
+     *
+     * # Returns
+     * A Result containing a String with the payload or an ApplicationError if it is not UTF-8.
+     */
+    fn get_raw_string(&self) -> Result<String, ApplicationError> {
+        String::from_utf8(self.payload.clone()).map_err(|e| {
+            ApplicationError::General(format!("Failed to convert raw bytes to string: {}", e))
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_block_reading() {
+        let reading = ScpiBlockReading::new(vec![0x00, 0xff, 0x10]);
+        assert_eq!(reading.get_raw().unwrap(), vec![0x00, 0xff, 0x10]);
+        assert_eq!(reading.get_csv().unwrap(), vec!["3"]);
+        assert!(reading.get_raw_string().is_err());
+    }
+}
@@ -27,7 +27,19 @@ impl Reading for ScpiRawReading {
      * # Returns
      * Always Err.
      */
-    fn get_csv(&self) -> Result<String, ApplicationError> {
+    fn get_csv_header(&self) -> Result<Vec<String>, ApplicationError> {
+        Err(ApplicationError::General(
+            "ScpiRawReading does not support CSV format".into(),
+        ))
+    }
+
+    /**
+     * Not supported for ScpiRawReading.
+     *
+     * # Returns
+     * Always Err.
+     */
+    fn get_csv(&self) -> Result<Vec<String>, ApplicationError> {
         Err(ApplicationError::General(
             "ScpiRawReading does not support CSV format".into(),
         ))
@@ -0,0 +1,138 @@
+use crate::{error::ApplicationError, instruments::reading::Reading};
+
+// CSV field names, in the order returned by get_csv
+const CSV_HEADER: [&str; 3] = ["image_format", "bytes", "path"];
+
+/**
+ * Represents a screenshot of an instrument display, the payload of the binary block
+ * returned by `:DISP:DATA?` or `HCOPy:SDUMp:DATA?`.
+ */
+#[derive(Debug)]
+pub struct ScreenshotReading {
+    pub image: Vec<u8>,
+    /**
+     * File the image was written to, if any.
+     */
+    pub path: Option<String>,
+}
+
+impl ScreenshotReading {
+    /**
+     * Creates a new ScreenshotReading.
+     *
+     * # Arguments
+     * `image` - The image data without the block header.
+     *
+     * # Returns
+     * A new ScreenshotReading instance.
+     */
+    pub fn new(image: Vec<u8>) -> Self {
+        Self { image, path: None }
+    }
+
+    /**
+     * Detects the image format from the file signature.
+     *
+     * # Returns
+     * The format name, or "unknown".
+     */
+    pub fn image_format(&self) -> &'static str {
+        match self.image.as_slice() {
+            [0x89, b'P', b'N', b'G', ..] => "png",
+            [b'B', b'M', ..] => "bmp",
+            [0xff, 0xd8, ..] => "jpeg",
+            [b'G', b'I', b'F', ..] => "gif",
+            _ => "unknown",
+        }
+    }
+
+    /**
+     * Writes the image to a file.
+     *
+     * # Arguments
+     * `path` - The file path.
+     *
+     * # Returns
+     * A Result indicating success or an ApplicationError if the file cannot be written.
+     */
+    pub fn save(&mut self, path: &str) -> Result<(), ApplicationError> {
+        std::fs::write(path, &self.image)
+            .map_err(|e| ApplicationError::General(format!("Failed to write screenshot to {}: {}", path, e)))?;
+        self.path = Some(path.to_string());
+        Ok(())
+    }
+}
+
+impl Reading for ScreenshotReading {
+    /**
+     * Returns the names of the CSV fields.
+     *
+     * # Returns
+     * A Result containing the CSV header fields or an ApplicationError.
+     */
+    fn get_csv_header(&self) -> Result<Vec<String>, ApplicationError> {
+        Ok(CSV_HEADER.iter().map(|field| field.to_string()).collect())
+    }
+
+    /**
+     * Returns the image format, size and file as CSV fields.
+     *
+     * # Returns
+     * A Result containing the CSV field values or an ApplicationError.
+     */
+    fn get_csv(&self) -> Result<Vec<String>, ApplicationError> {
+        Ok(vec![
+            self.image_format().to_string(),
+            self.image.len().to_string(),
+            self.path.clone().unwrap_or_default(),
+        ])
+    }
+
+    /**
+     * Returns the image as a byte vector.
+     *
+     * # Returns
+     * A Result containing a byte vector with the image or an ApplicationError.
+     */
+    fn get_raw(&self) -> Result<Vec<u8>, ApplicationError> {
+        Ok(self.image.clone())
+    }
+
+    /**
+     * Returns a human readable summary of the screenshot.
+     *
+     * # Returns
+     * A Result containing the summary or an ApplicationError.
+     */
+    fn get_raw_string(&self) -> Result<String, ApplicationError> {
+        Ok(format!(
+            "{} image, {} bytes{}",
+            self.image_format(),
+            self.image.len(),
+            self.path.as_ref().map(|path| format!(", written to {}", path)).unwrap_or_default()
+        ))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_image_format() {
+        assert_eq!(ScreenshotReading::new(b"\x89PNG\r\n\x1a\n".to_vec()).image_format(), "png");
+        assert_eq!(ScreenshotReading::new(b"BM\x36\x10".to_vec()).image_format(), "bmp");
+        assert_eq!(ScreenshotReading::new(b"1.234\n".to_vec()).image_format(), "unknown");
+    }
+
+    #[test]
+    fn test_save() {
+        let path = std::env::temp_dir().join(format!("screenshot-{}.bmp", std::process::id()));
+        let path = path.to_str().unwrap();
+        let mut reading = ScreenshotReading::new(b"BM\x00\x01".to_vec());
+        reading.save(path).unwrap();
+        assert_eq!(std::fs::read(path).unwrap(), b"BM\x00\x01");
+        assert_eq!(reading.get_csv().unwrap(), vec!["bmp", "4", path]);
+        std::fs::remove_file(path).unwrap();
+    }
+}
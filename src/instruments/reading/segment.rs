@@ -0,0 +1,115 @@
+use crate::{
+    error::ApplicationError,
+    instruments::reading::{Measurement, Reading, Samples},
+};
+
+/**
+ * Represents a reading of one segment from a scope's segmented (history) memory.
+ * The segment number and source are prepended to the CSV fields of the wrapped reading.
+ */
+pub struct SegmentReading {
+    pub segment: u32,
+    pub source: String,
+    pub reading: Box<dyn Reading>,
+}
+
+impl SegmentReading {
+    /**
+     * Creates a new SegmentReading.
+     *
+     * # Arguments
+     * `segment` - The segment number.
+     * `source` - The source channel.
+     * `reading` - The reading of the segment data.
+     *
+     * # Returns
+     * A new SegmentReading instance.
+     */
+    pub fn new(segment: u32, source: &str, reading: Box<dyn Reading>) -> Self {
+        Self {
+            segment,
+            source: source.to_string(),
+            reading,
+        }
+    }
+}
+
+impl Reading for SegmentReading {
+    /**
+     * Returns the names of the CSV fields.
+     *
+     * # Returns
+     * A Result containing the CSV header fields or an ApplicationError.
+     */
+    fn get_csv_header(&self) -> Result<Vec<String>, ApplicationError> {
+        let mut header = vec!["segment".to_string(), "source".to_string()];
+        header.extend(self.reading.get_csv_header()?);
+        Ok(header)
+    }
+
+    /**
+     * Returns the segment data as CSV fields.
+     *
+     * # Returns
+     * A Result containing the CSV field values or an ApplicationError.
+     */
+    fn get_csv(&self) -> Result<Vec<String>, ApplicationError> {
+        let mut fields = vec![self.segment.to_string(), self.source.clone()];
+        fields.extend(self.reading.get_csv()?);
+        Ok(fields)
+    }
+
+    /**
+     * Returns the raw segment data as a byte vector.
+     *
+     * # Returns
+     * A Result containing a byte vector with the raw data or an ApplicationError.
+     */
+    fn get_raw(&self) -> Result<Vec<u8>, ApplicationError> {
+        self.reading.get_raw()
+    }
+
+    /**
+     * Returns the raw segment data as a String.
+     *
+     * # Returns
+     * A Result containing a String with the raw data or an ApplicationError.
+     */
+    fn get_raw_string(&self) -> Result<String, ApplicationError> {
+        self.reading.get_raw_string()
+    }
+
+    /**
+     * Returns the typed measurement of the wrapped reading.
+     *
+     * # Returns
+     * The measurement, if the wrapped reading has one.
+     */
+    fn get_measurement(&self) -> Option<Measurement> {
+        self.reading.get_measurement()
+    }
+
+    /**
+     * Returns the samples of the wrapped reading.
+     *
+     * # Returns
+     * The samples, if the wrapped reading is a waveform.
+     */
+    fn get_samples(&self) -> Option<Samples> {
+        self.reading.get_samples()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::instruments::reading::AnalysisReading;
+
+    #[test]
+    fn test_segment_csv() {
+        let samples: Vec<f64> = (0..100).map(|n| (n as f64 * 0.5).sin()).collect();
+        let reading = SegmentReading::new(3, "CHAN1", Box::new(AnalysisReading::new(&samples, 1000.0).unwrap()));
+        assert_eq!(reading.get_csv_header().unwrap()[..3], ["segment", "source", "fundamental"]);
+        assert_eq!(reading.get_csv().unwrap()[..2], ["3", "CHAN1"]);
+    }
+}
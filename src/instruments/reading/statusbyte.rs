@@ -0,0 +1,98 @@
+use crate::{error::ApplicationError, instruments::reading::Reading};
+
+// CSV field names, in the order returned by get_csv
+const CSV_HEADER: [&str; 4] = ["status_byte", "rqs", "esb", "mav"];
+
+/**
+ * Represents an IEEE 488.2 status byte, e.g. from a USB488 service request.
+ */
+#[derive(Debug)]
+pub struct StatusByteReading {
+    pub status_byte: u8,
+}
+
+impl StatusByteReading {
+    /**
+     * Creates a new StatusByteReading.
+     *
+     * # Arguments
+     * `status_byte` - The status byte.
+     *
+     * # Returns
+     * A new StatusByteReading instance.
+     */
+    pub fn new(status_byte: u8) -> Self {
+        Self { status_byte }
+    }
+
+    /**
+     * Checks if a bit of the status byte is set.
+     *
+     * # Arguments
+     * `bit` - The bit number.
+     *
+     * # Returns
+     * A boolean indicating whether the bit is set.
+     */
+    fn bit(&self, bit: u8) -> bool {
+        self.status_byte & (1 << bit) != 0
+    }
+}
+
+impl Reading for StatusByteReading {
+    /**
+     * Returns the names of the CSV fields.
+     *
+     * # Returns
+     * A Result containing the CSV header fields or an ApplicationError.
+     */
+    fn get_csv_header(&self) -> Result<Vec<String>, ApplicationError> {
+        Ok(CSV_HEADER.iter().map(|field| field.to_string()).collect())
+    }
+
+    /**
+     * Returns the status byte and its request service, event status and message available bits.
+     *
+     * # Returns
+     * A Result containing the CSV field values or an ApplicationError.
+     */
+    fn get_csv(&self) -> Result<Vec<String>, ApplicationError> {
+        Ok(vec![
+            self.status_byte.to_string(),
+            self.bit(6).to_string(),
+            self.bit(5).to_string(),
+            self.bit(4).to_string(),
+        ])
+    }
+
+    /**
+     * Returns the status byte as a byte vector.
+     *
+     * # Returns
+     * A Result containing a byte vector with the status byte or an ApplicationError.
+     */
+    fn get_raw(&self) -> Result<Vec<u8>, ApplicationError> {
+        Ok(vec![self.status_byte])
+    }
+
+    /**
+     * Returns the status byte as a String.
+     *
+     * # Returns
+     * A Result containing a String with the status byte or an ApplicationError.
+     */
+    fn get_raw_string(&self) -> Result<String, ApplicationError> {
+        Ok(format!("{}", self.status_byte))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_status_byte_csv() {
+        let reading = StatusByteReading::new(0x60);
+        assert_eq!(reading.get_csv().unwrap(), vec!["96", "true", "true", "false"]);
+    }
+}
@@ -0,0 +1,141 @@
+use crate::{
+    error::ApplicationError,
+    instruments::reading::{Measurement, Reading, Samples},
+};
+
+/**
+ * Wraps a reading with additional named fields, e.g. the scan channel it was taken on.
+ * The tags are prepended to the CSV fields of the wrapped reading.
+ */
+pub struct TaggedReading {
+    pub tags: Vec<(String, String)>,
+    pub reading: Box<dyn Reading>,
+    /**
+     * Fields appended after the wrapped reading, e.g. empty columns lining the record up with
+     * other records of the same output.
+     */
+    pub appended: Vec<(String, String)>,
+}
+
+impl TaggedReading {
+    /**
+     * Creates a new TaggedReading.
+     *
+     * # Arguments
+     * `tags` - The field names and values to prepend.
+     * `reading` - The wrapped reading.
+     *
+     * # Returns
+     * A new TaggedReading instance.
+     */
+    pub fn new(tags: Vec<(String, String)>, reading: Box<dyn Reading>) -> Self {
+        Self {
+            tags,
+            reading,
+            appended: Vec::new(),
+        }
+    }
+
+    /**
+     * Appends fields after the fields of the wrapped reading.
+     *
+     * # Arguments
+     * `appended` - The field names and values to append.
+     *
+     * # Returns
+     * The TaggedReading with the appended fields.
+     */
+    pub fn with_appended(mut self, appended: Vec<(String, String)>) -> Self {
+        self.appended = appended;
+        self
+    }
+}
+
+impl Reading for TaggedReading {
+    /**
+     * Returns the names of the CSV fields.
+     *
+     * # Returns
+     * A Result containing the CSV header fields or an ApplicationError.
+     */
+    fn get_csv_header(&self) -> Result<Vec<String>, ApplicationError> {
+        let mut header: Vec<String> = self.tags.iter().map(|(name, _)| name.clone()).collect();
+        header.extend(self.reading.get_csv_header()?);
+        header.extend(self.appended.iter().map(|(name, _)| name.clone()));
+        Ok(header)
+    }
+
+    /**
+     * Returns the tags, the wrapped reading and the appended fields as CSV fields.
+     *
+     * # Returns
+     * A Result containing the CSV field values or an ApplicationError.
+     */
+    fn get_csv(&self) -> Result<Vec<String>, ApplicationError> {
+        let mut fields: Vec<String> = self.tags.iter().map(|(_, value)| value.clone()).collect();
+        fields.extend(self.reading.get_csv()?);
+        fields.extend(self.appended.iter().map(|(_, value)| value.clone()));
+        Ok(fields)
+    }
+
+    /**
+     * Returns the raw data of the wrapped reading as a byte vector.
+     *
+     * # Returns
+     * A Result containing a byte vector with the raw data or an ApplicationError.
+     */
+    fn get_raw(&self) -> Result<Vec<u8>, ApplicationError> {
+        self.reading.get_raw()
+    }
+
+    /**
+     * Returns the raw data of the wrapped reading as a String.
+     *
+     * # Returns
+     * A Result containing a String with the raw data or an ApplicationError.
+     */
+    fn get_raw_string(&self) -> Result<String, ApplicationError> {
+        self.reading.get_raw_string()
+    }
+
+    /**
+     * Returns the typed measurement of the wrapped reading.
+     *
+     * # Returns
+     * The measurement, if the wrapped reading has one.
+     */
+    fn get_measurement(&self) -> Option<Measurement> {
+        self.reading.get_measurement()
+    }
+
+    /**
+     * Returns the samples of the wrapped reading.
+     *
+     * # Returns
+     * The samples, if the wrapped reading is a waveform.
+     */
+    fn get_samples(&self) -> Option<Samples> {
+        self.reading.get_samples()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::instruments::reading::Unit161dReading;
+
+    #[test]
+    fn test_tagged_csv() {
+        let raw_data = vec![2, b'0', b' ', b' ', b'1', b'.', b'2', b'3', b'4', 0, 0, 0, 0, 0];
+        let reading = TaggedReading::new(
+            vec![("channel".into(), "3".into())],
+            Box::new(Unit161dReading::parse(raw_data).unwrap()),
+        );
+        assert_eq!(reading.get_csv_header().unwrap()[..2], ["channel", "mode"]);
+        assert_eq!(reading.get_csv().unwrap()[..3], ["3", "DCV", "0"]);
+        let reading = reading.with_appended(vec![("note".into(), String::new())]);
+        assert_eq!(reading.get_csv_header().unwrap().last().unwrap(), "note");
+        assert_eq!(reading.get_csv().unwrap().last().unwrap(), "");
+        assert_eq!(reading.get_csv_header().unwrap().len(), reading.get_csv().unwrap().len());
+    }
+}
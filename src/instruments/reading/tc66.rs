@@ -0,0 +1,282 @@
+use tracing::debug;
+
+use crate::{
+    error::ApplicationError,
+    instruments::{
+        crc::crc16_modbus,
+        reading::{aes, Measurement, Reading},
+    },
+};
+
+/**
+ * Length of an encrypted TC66C poll frame, three 64 byte packets.
+ */
+pub const TC66_FRAME_LENGTH: usize = 192;
+
+/**
+ * Length of each packet of a poll frame.
+ */
+const PACKET_LENGTH: usize = 64;
+
+/**
+ * Offset of the CRC in each packet; the CRC covers the bytes before it.
+ */
+const PACKET_CRC_OFFSET: usize = 60;
+
+/**
+ * AES-256 key the TC66C firmware encrypts the poll frames with.
+ */
+const TC66_KEY: [u8; 32] = [
+    0x58, 0x21, 0xfa, 0x56, 0x01, 0xb2, 0xf0, 0x26, 0x87, 0xff, 0x12, 0x04, 0x62, 0x2a, 0x4f, 0xb0,
+    0x86, 0xf4, 0x02, 0x60, 0x81, 0x6f, 0x9a, 0x0b, 0xa7, 0xf1, 0x06, 0x61, 0x9a, 0xb8, 0x72, 0x88,
+];
+
+// CSV field names, in the order returned by get_csv
+const CSV_HEADER: [&str; 16] = [
+    "product", "version", "serial", "runs", "voltage", "current", "power", "resistance", "group0_mah",
+    "group0_mwh", "group1_mah", "group1_mwh", "temperature", "dplus_voltage", "dminus_voltage", "scaled_value",
+];
+
+/**
+ * Reads a little endian 32 bit word.
+ *
+ * # Arguments
+ * `data` - The bytes.
+ * `offset` - The offset of the word.
+ *
+ * # Returns
+ * The word.
+ */
+fn word(data: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes([data[offset], data[offset + 1], data[offset + 2], data[offset + 3]])
+}
+
+/**
+ * Represents a poll frame of an RDTech TC66C USB tester, the response to `getva`. The frame is
+ * AES-256-ECB encrypted; decrypted it holds the packets `pac1` (device, voltage, current and
+ * power), `pac2` (resistance, accumulated charge and energy of both groups, temperature and
+ * data line voltages) and `pac3`, each closed by a CRC.
+ */
+#[derive(Debug)]
+pub struct Tc66Reading {
+    pub product: String,
+    pub version: String,
+    pub serial: u32,
+    /**
+     * Number of times the tester was powered on.
+     */
+    pub runs: u32,
+    /**
+     * Bus voltage in V.
+     */
+    pub voltage: f64,
+    /**
+     * Current in A.
+     */
+    pub current: f64,
+    /**
+     * Power in W.
+     */
+    pub power: f64,
+    /**
+     * Load resistance in Ω.
+     */
+    pub resistance: f64,
+    /**
+     * Accumulated charge in mAh and energy in mWh of recording groups 0 and 1.
+     */
+    pub group_mah: [u32; 2],
+    pub group_mwh: [u32; 2],
+    /**
+     * Temperature in °C.
+     */
+    pub temperature: i32,
+    /**
+     * Voltages of the D+ and D- data lines in V.
+     */
+    pub dplus_voltage: f64,
+    pub dminus_voltage: f64,
+    pub original_bytes: Vec<u8>,
+}
+
+impl Tc66Reading {
+    /**
+     * Decrypts and parses a poll frame.
+     *
+     * # Arguments
+     * `frame` - The 192 encrypted frame bytes.
+     *
+     * # Returns
+     * A Result containing the Tc66Reading or an ApplicationError if the frame is invalid.
+     */
+    pub fn parse(frame: &[u8]) -> Result<Self, ApplicationError> {
+        if frame.len() != TC66_FRAME_LENGTH {
+            return Err(ApplicationError::Command(format!(
+                "Invalid TC66 frame length {}, expected {}",
+                frame.len(),
+                TC66_FRAME_LENGTH
+            )));
+        }
+        let plain = aes::decrypt_ecb(&TC66_KEY, frame);
+        for (index, packet) in plain.chunks_exact(PACKET_LENGTH).enumerate() {
+            let tag = format!("pac{}", index + 1);
+            if &packet[..4] != tag.as_bytes() {
+                return Err(ApplicationError::Command(format!("TC66 packet {} not found", tag)));
+            }
+            let crc = crc16_modbus(&packet[..PACKET_CRC_OFFSET]) as u32;
+            if crc != word(packet, PACKET_CRC_OFFSET) {
+                debug!("TC66 packet {} CRC mismatch: computed {:#06x}, received {:#06x}", tag, crc, word(packet, PACKET_CRC_OFFSET));
+                return Err(ApplicationError::Command(format!("TC66 packet {} has an invalid CRC", tag)));
+            }
+            debug!("TC66 packet {} CRC {:#06x} ok", tag, crc);
+        }
+        let (pac1, pac2) = (&plain[..PACKET_LENGTH], &plain[PACKET_LENGTH..2 * PACKET_LENGTH]);
+        let text = |bytes: &[u8]| String::from_utf8_lossy(bytes).trim_end_matches('\0').to_string();
+        let temperature = word(pac2, 28) as i32;
+        Ok(Self {
+            product: text(&pac1[4..8]),
+            version: text(&pac1[8..12]),
+            serial: word(pac1, 12),
+            runs: word(pac1, 44),
+            voltage: word(pac1, 48) as f64 * 1e-4,
+            current: word(pac1, 52) as f64 * 1e-5,
+            power: word(pac1, 56) as f64 * 1e-4,
+            resistance: word(pac2, 4) as f64 * 1e-1,
+            group_mah: [word(pac2, 8), word(pac2, 16)],
+            group_mwh: [word(pac2, 12), word(pac2, 20)],
+            temperature: if word(pac2, 24) == 1 { -temperature } else { temperature },
+            dplus_voltage: word(pac2, 32) as f64 * 1e-2,
+            dminus_voltage: word(pac2, 36) as f64 * 1e-2,
+            original_bytes: frame.to_vec(),
+        })
+    }
+}
+
+impl Reading for Tc66Reading {
+    /**
+     * Returns the names of the CSV fields.
+     *
+     * # Returns
+     * A Result containing the CSV header fields or an ApplicationError.
+     */
+    fn get_csv_header(&self) -> Result<Vec<String>, ApplicationError> {
+        Ok(CSV_HEADER.iter().map(|field| field.to_string()).collect())
+    }
+
+    /**
+     * Returns the measurement data as CSV fields. Values are rounded to the resolution of the
+     * tester.
+     *
+     * # Returns
+     * A Result containing the CSV field values or an ApplicationError.
+     */
+    fn get_csv(&self) -> Result<Vec<String>, ApplicationError> {
+        Ok(vec![
+            self.product.clone(),
+            self.version.clone(),
+            self.serial.to_string(),
+            self.runs.to_string(),
+            format!("{:.4}", self.voltage),
+            format!("{:.5}", self.current),
+            format!("{:.4}", self.power),
+            format!("{:.1}", self.resistance),
+            self.group_mah[0].to_string(),
+            self.group_mwh[0].to_string(),
+            self.group_mah[1].to_string(),
+            self.group_mwh[1].to_string(),
+            self.temperature.to_string(),
+            format!("{:.2}", self.dplus_voltage),
+            format!("{:.2}", self.dminus_voltage),
+            self.get_measurement()
+                .map(|measurement| measurement.value.to_string())
+                .unwrap_or_default(),
+        ])
+    }
+
+    /**
+     * Returns the encrypted frame as a byte vector.
+     *
+     * # Returns
+     * A Result containing a byte vector with the raw data or an ApplicationError.
+     */
+    fn get_raw(&self) -> Result<Vec<u8>, ApplicationError> {
+        Ok(self.original_bytes.clone())
+    }
+
+    /**
+     * Returns voltage, current and power as a String.
+     *
+     * # Returns
+     * A Result containing a String with the values or an ApplicationError.
+     */
+    fn get_raw_string(&self) -> Result<String, ApplicationError> {
+        Ok(format!("{:.4} V {:.5} A {:.4} W", self.voltage, self.current, self.power))
+    }
+
+    /**
+     * Returns the typed measurement of the bus voltage.
+     *
+     * # Returns
+     * The measurement.
+     */
+    fn get_measurement(&self) -> Option<Measurement> {
+        Measurement::from_display(self.voltage, "V", Vec::new())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /**
+     * Builds an encrypted poll frame of 5.1234 V, 1.23456 A, 6.3254 W and 41.5 Ω, 1200 mAh and
+     * 6100 mWh in group 0, -3 °C and D+ / D- at 0.60 V / 0.55 V.
+     */
+    fn frame() -> Vec<u8> {
+        let mut plain = vec![0u8; TC66_FRAME_LENGTH];
+        let mut put = |offset: usize, value: u32| plain[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+        put(48, 51234);
+        put(52, 123456);
+        put(56, 63254);
+        put(12, 1234567);
+        put(44, 17);
+        put(64 + 4, 415);
+        put(64 + 8, 1200);
+        put(64 + 12, 6100);
+        put(64 + 24, 1);
+        put(64 + 28, 3);
+        put(64 + 32, 60);
+        put(64 + 36, 55);
+        plain[4..12].copy_from_slice(b"TC661.15");
+        for (index, packet) in plain.chunks_exact_mut(PACKET_LENGTH).enumerate() {
+            packet[..4].copy_from_slice(format!("pac{}", index + 1).as_bytes());
+            let crc = crc16_modbus(&packet[..PACKET_CRC_OFFSET]) as u32;
+            packet[PACKET_CRC_OFFSET..].copy_from_slice(&crc.to_le_bytes());
+        }
+        aes::encrypt_ecb(&TC66_KEY, &plain)
+    }
+
+    #[test]
+    fn test_parse() {
+        let reading = Tc66Reading::parse(&frame()).unwrap();
+        assert_eq!(reading.product, "TC66");
+        assert_eq!(reading.version, "1.15");
+        assert_eq!(reading.serial, 1234567);
+        assert_eq!(reading.runs, 17);
+        assert_eq!(reading.group_mah, [1200, 0]);
+        assert_eq!(reading.temperature, -3);
+        let csv = reading.get_csv().unwrap();
+        assert_eq!(csv[4..8], ["5.1234", "1.23456", "6.3254", "41.5"]);
+        assert_eq!(csv[13..15], ["0.60", "0.55"]);
+        assert_eq!(reading.get_raw_string().unwrap(), "5.1234 V 1.23456 A 6.3254 W");
+        assert!((reading.get_measurement().unwrap().value - 5.1234).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_invalid_frame() {
+        assert!(Tc66Reading::parse(&[0u8; 64]).is_err());
+        let mut frame = frame();
+        frame[100] ^= 0xff;
+        assert!(Tc66Reading::parse(&frame).is_err());
+    }
+}
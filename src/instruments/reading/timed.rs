@@ -0,0 +1,165 @@
+use std::time::Duration;
+
+use crate::{
+    error::ApplicationError,
+    instruments::reading::{paired::response_fields, Measurement, Reading, Samples},
+};
+
+/**
+ * Field with the round trip time of the command.
+ */
+pub const LATENCY_FIELD: &str = "latency_ms";
+
+/**
+ * A reading with the command it answers and the time the command took: the round trip from
+ * sending the command to receiving the reading and, if the transport measures them, the time
+ * until the command transfer completed and until the first response byte arrived. The fields
+ * `command`, `latency_ms`, `write_ms` and `first_byte_ms` are prepended to the fields of the
+ * reading. Readings without CSV fields, e.g. raw SCPI responses, give a `response` field.
+ */
+pub struct TimedReading {
+    pub command: String,
+    pub latency: Duration,
+    pub write: Option<Duration>,
+    pub first_byte: Option<Duration>,
+    pub reading: Box<dyn Reading>,
+}
+
+impl TimedReading {
+    /**
+     * Creates a new TimedReading.
+     *
+     * # Arguments
+     * `command` - The command the reading answers.
+     * `latency` - The round trip time of the command.
+     * `write` - The time until the command transfer completed, if measured.
+     * `first_byte` - The time until the first response byte arrived, if measured.
+     * `reading` - The reading.
+     *
+     * # Returns
+     * A new TimedReading instance.
+     */
+    pub fn new(
+        command: String,
+        latency: Duration,
+        write: Option<Duration>,
+        first_byte: Option<Duration>,
+        reading: Box<dyn Reading>,
+    ) -> Self {
+        Self {
+            command,
+            latency,
+            write,
+            first_byte,
+            reading,
+        }
+    }
+}
+
+/**
+ * Formats a duration as milliseconds with microsecond resolution.
+ *
+ * # Arguments
+ * `duration` - The duration.
+ *
+ * # Returns
+ * The milliseconds, e.g. `12.345`.
+ */
+fn milliseconds(duration: Duration) -> String {
+    format!("{:.3}", duration.as_secs_f64() * 1000.0)
+}
+
+impl Reading for TimedReading {
+    /**
+     * Returns the command and timing fields followed by the fields of the reading.
+     *
+     * # Returns
+     * A Result containing the CSV header fields or an ApplicationError.
+     */
+    fn get_csv_header(&self) -> Result<Vec<String>, ApplicationError> {
+        let mut header: Vec<String> = vec!["command".into(), LATENCY_FIELD.into(), "write_ms".into(), "first_byte_ms".into()];
+        header.extend(response_fields(self.reading.as_ref())?.0);
+        Ok(header)
+    }
+
+    /**
+     * Returns the command and timing values followed by the fields of the reading. Timings the
+     * transport does not measure are empty.
+     *
+     * # Returns
+     * A Result containing the CSV field values or an ApplicationError.
+     */
+    fn get_csv(&self) -> Result<Vec<String>, ApplicationError> {
+        let mut fields = vec![
+            self.command.clone(),
+            milliseconds(self.latency),
+            self.write.map(milliseconds).unwrap_or_default(),
+            self.first_byte.map(milliseconds).unwrap_or_default(),
+        ];
+        fields.extend(response_fields(self.reading.as_ref())?.1);
+        Ok(fields)
+    }
+
+    /**
+     * Returns the raw data of the reading as a byte vector.
+     *
+     * # Returns
+     * A Result containing a byte vector with the raw data or an ApplicationError.
+     */
+    fn get_raw(&self) -> Result<Vec<u8>, ApplicationError> {
+        self.reading.get_raw()
+    }
+
+    /**
+     * Returns the raw data of the reading as a String.
+     *
+     * # Returns
+     * A Result containing a String with the raw data or an ApplicationError.
+     */
+    fn get_raw_string(&self) -> Result<String, ApplicationError> {
+        self.reading.get_raw_string()
+    }
+
+    /**
+     * Returns the typed measurement of the reading.
+     *
+     * # Returns
+     * The measurement, if the reading has one.
+     */
+    fn get_measurement(&self) -> Option<Measurement> {
+        self.reading.get_measurement()
+    }
+
+    /**
+     * Returns the samples of the wrapped reading.
+     *
+     * # Returns
+     * The samples, if the wrapped reading is a waveform.
+     */
+    fn get_samples(&self) -> Option<Samples> {
+        self.reading.get_samples()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::instruments::reading::ScpiRawReading;
+
+    #[test]
+    fn test_timed_csv() {
+        let reading = TimedReading::new(
+            "MEAS:VOLT?".into(),
+            Duration::from_micros(12345),
+            Some(Duration::from_micros(250)),
+            None,
+            Box::new(ScpiRawReading::new(b"4.99\n".to_vec())),
+        );
+        assert_eq!(
+            reading.get_csv_header().unwrap(),
+            ["command", "latency_ms", "write_ms", "first_byte_ms", "response"]
+        );
+        assert_eq!(reading.get_csv().unwrap(), ["MEAS:VOLT?", "12.345", "0.250", "", "4.99"]);
+        assert_eq!(reading.get_raw_string().unwrap(), "4.99\n");
+    }
+}
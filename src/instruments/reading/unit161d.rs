@@ -1,4 +1,7 @@
-use crate::{error::ApplicationError, instruments::reading::Reading};
+use crate::{
+    error::ApplicationError,
+    instruments::reading::{measurement::parse_unit, Measurement, Reading},
+};
 
 // Decoded modes
 const MODE: [&str; 31] = [
@@ -10,9 +13,16 @@ const MODE: [&str; 31] = [
 // Strings that could mean overload
 const OVERLOAD: [&str; 8] = [".OL", "O.L", "OL.", "OL", "-.OL", "-O.L", "-OL.", "-OL"];
 
-// Strings that indicate level of voltage detected >=50Vrms (50-60Hz)
+// Strings that indicate level of voltage detected >=50Vrms (50-60Hz), by level from 0 to 5
 const NCV: [&str; 6] = ["EF", "-", "--", "---", "----", "-----"];
 
+// CSV field names, in the order returned by get_csv
+const CSV_HEADER: [&str; 21] = [
+    "mode", "range", "display_value", "overload", "ncv", "decimal_value", "display_unit",
+    "progres", "max", "min", "hold", "rel", "auto", "battery", "hwwarning", "dc", "peak_max",
+    "peak_min", "bar_polarity", "scaled_value", "ncv_level",
+];
+
 // Get unit based on mode and range
 fn get_unit(mode: &str, range: &str) -> Option<&'static str> {
     match (mode, range) {
@@ -134,6 +144,19 @@ fn is_ncv(value: &str) -> bool {
     NCV.contains(&value)
 }
 
+/**
+ * Gets the detected field strength level of an NCV display value: 0 for `EF` and the number
+ * of dashes for `-` to `-----`.
+ * # Arguments
+ * `value` - A string slice representing the display value.
+ *
+ * # Returns
+ * The level, or None if the value is not an NCV display.
+ */
+fn ncv_level(value: &str) -> Option<u8> {
+    NCV.iter().position(|ncv| *ncv == value).map(|level| level as u8)
+}
+
 /**
 * Represents a measurement taken by an instrument.
  */
@@ -145,6 +168,10 @@ pub struct Unit161dReading {
     pub display_value: String,
     pub overload: bool,
     pub ncv: bool,
+    /**
+     * Field strength level shown in NCV mode, from 0 (`EF`) to 5 (`-----`).
+     */
+    pub ncv_level: Option<u8>,
     pub decimal_value: Option<f64>, //Todo: Change number representation
     pub display_unit: String,
     pub progres: u16,
@@ -159,6 +186,10 @@ pub struct Unit161dReading {
     pub peak_max: bool,
     pub peak_min: bool,
     pub bar_polarity: bool,
+    /**
+     * Decimal value in the base unit, e.g. 123.4 mV as 0.1234 V. Temperatures are not converted.
+     */
+    pub scaled_value: Option<f64>,
 }
 
 impl Unit161dReading {
@@ -184,12 +215,16 @@ impl Unit161dReading {
         let display_value = String::from_utf8_lossy(&bytes[2..9]).trim().to_string();
         let overload = is_overload(&display_value);
         let ncv = is_ncv(&display_value);
+        let ncv_level = ncv_level(&display_value);
         let decimal_value = if overload || ncv {
             None
         } else {
             display_value.parse::<f64>().ok()
         };
         let display_unit = get_unit(&mode, &range).unwrap_or("Unknown").to_string();
+        let scaled_value = decimal_value.map(|value| {
+            value * parse_unit(&display_unit).map(|(_, factor)| factor).unwrap_or(1.0)
+        });
         let progres: u16 = bytes[9] as u16 * 10 + bytes[10] as u16;
         let max = bytes[11] & 8 > 0;
         let min = bytes[11] & 4 > 0;
@@ -210,6 +245,7 @@ impl Unit161dReading {
             display_value,
             overload,
             ncv,
+            ncv_level,
             decimal_value,
             display_unit,
             progres,
@@ -224,40 +260,52 @@ impl Unit161dReading {
             peak_max,
             peak_min,
             bar_polarity,
+            scaled_value,
         })
     }
 }
 
 impl Reading for Unit161dReading {
     /**
-     * Returns the measurement data in CSV format.
+     * Returns the names of the CSV fields.
      *
      * # Returns
-     * A Result containing a String in CSV format or an ApplicationError.
+     * A Result containing the CSV header fields or an ApplicationError.
      */
-    fn get_csv(&self) -> Result<String, ApplicationError> {
-        Ok(format!(
-            "{},{},{},{},{},{:?},{},{},{},{},{},{},{},{},{},{},{},{},{}",
-            self.mode,
-            self.range,
-            self.display_value,
-            self.overload,
-            self.ncv,
-            self.decimal_value,
-            self.display_unit,
-            self.progres,
-            self.max,
-            self.min,
-            self.hold,
-            self.rel,
-            self.auto,
-            self.battery,
-            self.hwwarning,
-            self.dc,
-            self.peak_max,
-            self.peak_min,
-            self.bar_polarity
-        ))
+    fn get_csv_header(&self) -> Result<Vec<String>, ApplicationError> {
+        Ok(CSV_HEADER.iter().map(|field| field.to_string()).collect())
+    }
+
+    /**
+     * Returns the measurement data as CSV fields.
+     *
+     * # Returns
+     * A Result containing the CSV field values or an ApplicationError.
+     */
+    fn get_csv(&self) -> Result<Vec<String>, ApplicationError> {
+        Ok(vec![
+            self.mode.clone(),
+            self.range.clone(),
+            self.display_value.clone(),
+            self.overload.to_string(),
+            self.ncv.to_string(),
+            self.decimal_value.map(|value| value.to_string()).unwrap_or_default(),
+            self.display_unit.clone(),
+            self.progres.to_string(),
+            self.max.to_string(),
+            self.min.to_string(),
+            self.hold.to_string(),
+            self.rel.to_string(),
+            self.auto.to_string(),
+            self.battery.to_string(),
+            self.hwwarning.to_string(),
+            self.dc.to_string(),
+            self.peak_max.to_string(),
+            self.peak_min.to_string(),
+            self.bar_polarity.to_string(),
+            self.scaled_value.map(|value| value.to_string()).unwrap_or_default(),
+            self.ncv_level.map(|level| level.to_string()).unwrap_or_default(),
+        ])
     }
 
     /**
@@ -281,6 +329,29 @@ impl Reading for Unit161dReading {
             ApplicationError::General(format!("Failed to convert raw bytes to string: {}", e))
         })
     }
+
+    /**
+     * Returns the decimal value as a typed measurement with the active flags.
+     *
+     * # Returns
+     * The measurement, or None for overload, NCV or unknown units.
+     */
+    fn get_measurement(&self) -> Option<Measurement> {
+        let flags = [
+            ("max", self.max),
+            ("min", self.min),
+            ("hold", self.hold),
+            ("rel", self.rel),
+            ("auto", self.auto),
+            ("peak_max", self.peak_max),
+            ("peak_min", self.peak_min),
+        ]
+        .iter()
+        .filter(|(_, active)| *active)
+        .map(|(flag, _)| flag.to_string())
+        .collect();
+        Measurement::from_display(self.decimal_value?, &self.display_unit, flags)
+    }
 }
 
 #[cfg(test)]
@@ -302,6 +373,7 @@ mod test {
         assert_eq!(reading.overload, false);
         assert_eq!(reading.ncv, false);
         assert_eq!(reading.decimal_value, Some(123.456));
+        assert_eq!(reading.scaled_value, Some(123.456));
         assert_eq!(reading.display_unit, "Unknown");
         assert_eq!(reading.progres, 50);
         assert_eq!(reading.max, true);
@@ -326,6 +398,7 @@ mod test {
             display_value: "123.456".to_string(),
             overload: false,
             ncv: false,
+            ncv_level: None,
             decimal_value: Some(123.456),
             display_unit: "V".to_string(),
             progres: 50,
@@ -340,11 +413,33 @@ mod test {
             peak_max: true,
             peak_min: true,
             bar_polarity: true,
+            scaled_value: Some(123.456),
         };
 
-        let csv = reading.get_csv().unwrap();
-        let expected_csv = "DCV,\0,123.456,false,false,Some(123.456),V,50,true,true,true,false,true,true,true,true,true,true,true";
+        let csv = reading.get_csv().unwrap().join(",");
+        let expected_csv = "DCV,\0,123.456,false,false,123.456,V,50,true,true,true,false,true,true,true,true,true,true,true,123.456,";
         assert_eq!(csv, expected_csv);
+        assert_eq!(reading.get_csv_header().unwrap().len(), reading.get_csv().unwrap().len());
+
+        let measurement = reading.get_measurement().unwrap();
+        assert_eq!(measurement.value, 123.456);
+        assert_eq!(measurement.flags, vec!["max", "min", "hold", "auto", "peak_max", "peak_min"]);
+    }
+
+    #[test]
+    fn test_unit161d_scaled_value() {
+        let millivolts = vec![3, b'0', b' ', b'1', b'2', b'3', b'.', b'4', b' ', 0, 0, 0, 0, 0];
+        let reading = Unit161dReading::parse(millivolts).unwrap();
+        assert_eq!(reading.display_unit, "mV");
+        assert!((reading.scaled_value.unwrap() - 0.1234).abs() < 1e-12);
+
+        let kiloohm = vec![6, b'1', b' ', b' ', b'2', b'.', b'5', b'0', b'0', 0, 0, 0, 0, 0];
+        let reading = Unit161dReading::parse(kiloohm).unwrap();
+        assert_eq!(reading.display_unit, "kΩ");
+        assert_eq!(reading.scaled_value, Some(2500.0));
+
+        let overload = vec![6, b'1', b' ', b' ', b' ', b'O', b'.', b'L', b' ', 0, 0, 0, 0, 0];
+        assert_eq!(Unit161dReading::parse(overload).unwrap().scaled_value, None);
     }
 
     #[test]
@@ -371,4 +466,20 @@ mod test {
             assert!(!is_ncv(value));
         }
     }
+
+    #[test]
+    fn test_ncv_level() {
+        assert_eq!(ncv_level("EF"), Some(0));
+        assert_eq!(ncv_level("---"), Some(3));
+        assert_eq!(ncv_level("-----"), Some(5));
+        assert_eq!(ncv_level("123.45"), None);
+
+        let ncv = vec![20, b'0', b' ', b' ', b'-', b'-', b'-', b'-', b' ', 0, 0, 0, 0, 0];
+        let reading = Unit161dReading::parse(ncv).unwrap();
+        assert_eq!(reading.mode, "NCV");
+        assert!(reading.ncv);
+        assert_eq!(reading.ncv_level, Some(4));
+        assert_eq!(reading.decimal_value, None);
+        assert_eq!(reading.get_csv().unwrap().last().unwrap(), "4");
+    }
 }
@@ -0,0 +1,337 @@
+#[cfg(feature = "serial")]
+use crate::instruments::command::ut325::{UT325_DOWNLOAD, UT325_FRAME_START};
+use crate::{
+    error::ApplicationError,
+    instruments::{
+        command::{
+            ut325::{UT325_MEASURE, UT325_RECORD},
+            Ut325Channel,
+        },
+        reading::{Measurement, Reading},
+    },
+};
+
+// Bits of the status byte
+const STATUS_FAHRENHEIT: u8 = 0x01;
+const STATUS_HOLD: u8 = 0x02;
+const STATUS_T1_OPEN: u8 = 0x04;
+const STATUS_T2_OPEN: u8 = 0x08;
+
+/**
+ * Thermocouple types in the order of the type byte.
+ */
+const THERMOCOUPLES: [&str; 7] = ["K", "J", "T", "E", "R", "S", "N"];
+
+// CSV field names, in the order returned by get_csv
+const CSV_HEADER: [&str; 8] = ["t1", "t2", "t1_t2", "unit", "thermocouple", "hold", "record", "elapsed_s"];
+
+/**
+ * Splits received data into the payloads of the complete frames, `AB CD <length> <payload>
+ * <sum>`. The length counts the payload and the two bytes of the 16 bit big endian sum of the
+ * preceding bytes. Bytes before a header are skipped and an incomplete last frame is left out.
+ *
+ * # Arguments
+ * `data` - The received data.
+ *
+ * # Returns
+ * A Result containing the payloads in order or an ApplicationError if a checksum is wrong.
+ */
+#[cfg(feature = "serial")]
+pub fn ut325_frames(data: &[u8]) -> Result<Vec<Vec<u8>>, ApplicationError> {
+    let mut payloads = Vec::new();
+    let mut rest = data;
+    while let Some(start) = rest.windows(2).position(|window| window == UT325_FRAME_START) {
+        let Some(&length) = rest.get(start + 2) else {
+            break;
+        };
+        let end = start + 3 + length as usize;
+        let Some(frame) = rest.get(start..end).filter(|_| length >= 2) else {
+            break;
+        };
+        let (body, sum) = frame.split_at(frame.len() - 2);
+        let computed = body.iter().map(|byte| *byte as u16).fold(0u16, u16::wrapping_add);
+        if computed.to_be_bytes() != sum {
+            return Err(ApplicationError::Command(format!("Checksum mismatch in UT325 frame {:02X?}", frame)));
+        }
+        payloads.push(body[3..].to_vec());
+        rest = &rest[end..];
+    }
+    Ok(payloads)
+}
+
+/**
+ * Decodes the first frame of a download: the number of records and the logging interval.
+ *
+ * # Arguments
+ * `payload` - The payload of the frame.
+ *
+ * # Returns
+ * A Result containing the number of records and the interval in seconds or an ApplicationError
+ * if the payload is not a download header.
+ */
+#[cfg(feature = "serial")]
+pub fn ut325_download_header(payload: &[u8]) -> Result<(u16, u16), ApplicationError> {
+    match payload {
+        [UT325_DOWNLOAD, count_low, count_high, interval_low, interval_high] => Ok((
+            u16::from_le_bytes([*count_low, *count_high]),
+            u16::from_le_bytes([*interval_low, *interval_high]),
+        )),
+        _ => Err(ApplicationError::Command(format!("Invalid UT325 download header {:02X?}", payload))),
+    }
+}
+
+/**
+ * Decodes a temperature in tenths of a degree.
+ *
+ * # Arguments
+ * `low` - The low byte.
+ * `high` - The high byte.
+ * `open` - Whether the thermocouple is open or not connected.
+ *
+ * # Returns
+ * The temperature, or None if the thermocouple is open.
+ */
+fn temperature(low: u8, high: u8, open: bool) -> Option<f64> {
+    (!open).then(|| i16::from_le_bytes([low, high]) as f64 / 10.0)
+}
+
+/**
+ * Represents a measurement or a logged record of a UNI-T UT325 dual thermocouple logger.
+ */
+#[derive(Debug)]
+pub struct Ut325Reading {
+    /**
+     * Temperature of T1 in the displayed unit, None if the thermocouple is open.
+     */
+    pub t1: Option<f64>,
+    /**
+     * Temperature of T2 in the displayed unit, None if the thermocouple is open.
+     */
+    pub t2: Option<f64>,
+    /**
+     * True if the meter displays °F, false for °C.
+     */
+    pub fahrenheit: bool,
+    /**
+     * Whether the display is held.
+     */
+    pub hold: bool,
+    /**
+     * Thermocouple type set on the meter, e.g. K.
+     */
+    pub thermocouple: String,
+    /**
+     * The value used as the typed measurement.
+     */
+    pub channel: Ut325Channel,
+    /**
+     * Index of a logged record, None for a live measurement.
+     */
+    pub record: Option<u16>,
+    /**
+     * Time of a logged record since logging started, if the interval is known.
+     */
+    pub elapsed_s: Option<u32>,
+    /**
+     * The payload as read.
+     */
+    pub payload: Vec<u8>,
+}
+
+impl Ut325Reading {
+    /**
+     * Parses the payload of a measurement or a logged record.
+     *
+     * # Arguments
+     * `payload` - The payload of the frame.
+     * `channel` - The value used as the typed measurement.
+     * `interval` - The logging interval in seconds of a download, None if it is not known.
+     *
+     * # Returns
+     * A Result containing the Ut325Reading or an ApplicationError if the payload is invalid.
+     */
+    pub fn parse(payload: &[u8], channel: Ut325Channel, interval: Option<u16>) -> Result<Self, ApplicationError> {
+        let (record, values) = match payload {
+            [UT325_MEASURE, values @ ..] => (None, values),
+            [UT325_RECORD, index_low, index_high, values @ ..] => (Some(u16::from_le_bytes([*index_low, *index_high])), values),
+            _ => return Err(ApplicationError::Command(format!("Invalid UT325 payload {:02X?}", payload))),
+        };
+        let [status, kind, t1_low, t1_high, t2_low, t2_high] = *values else {
+            return Err(ApplicationError::Command(format!("Invalid UT325 payload {:02X?}", payload)));
+        };
+        let thermocouple = THERMOCOUPLES
+            .get(kind as usize)
+            .ok_or_else(|| ApplicationError::Command(format!("Unknown UT325 thermocouple type {}", kind)))?;
+        Ok(Self {
+            t1: temperature(t1_low, t1_high, status & STATUS_T1_OPEN != 0),
+            t2: temperature(t2_low, t2_high, status & STATUS_T2_OPEN != 0),
+            fahrenheit: status & STATUS_FAHRENHEIT != 0,
+            hold: status & STATUS_HOLD != 0,
+            thermocouple: thermocouple.to_string(),
+            channel,
+            record,
+            elapsed_s: record.zip(interval).map(|(index, interval)| index as u32 * interval as u32),
+            payload: payload.to_vec(),
+        })
+    }
+
+    /**
+     * Returns the displayed unit.
+     *
+     * # Returns
+     * `°F` or `°C`.
+     */
+    fn unit(&self) -> &'static str {
+        if self.fahrenheit { "°F" } else { "°C" }
+    }
+
+    /**
+     * Returns the difference T1 - T2, rounded to the resolution of the meter.
+     *
+     * # Returns
+     * The difference, or None if a thermocouple is open.
+     */
+    fn difference(&self) -> Option<f64> {
+        Some(((self.t1? - self.t2?) * 10.0).round() / 10.0)
+    }
+}
+
+impl Reading for Ut325Reading {
+    /**
+     * Returns the names of the CSV fields.
+     *
+     * # Returns
+     * A Result containing the CSV header fields or an ApplicationError.
+     */
+    fn get_csv_header(&self) -> Result<Vec<String>, ApplicationError> {
+        Ok(CSV_HEADER.iter().map(|field| field.to_string()).collect())
+    }
+
+    /**
+     * Returns the temperatures as CSV fields. Open thermocouples are `OL`, the record fields
+     * are empty for live measurements.
+     *
+     * # Returns
+     * A Result containing the CSV field values or an ApplicationError.
+     */
+    fn get_csv(&self) -> Result<Vec<String>, ApplicationError> {
+        let value = |value: Option<f64>| value.map(|value| value.to_string()).unwrap_or_else(|| "OL".into());
+        Ok(vec![
+            value(self.t1),
+            value(self.t2),
+            value(self.difference()),
+            self.unit().to_string(),
+            self.thermocouple.clone(),
+            self.hold.to_string(),
+            self.record.map(|record| record.to_string()).unwrap_or_default(),
+            self.elapsed_s.map(|elapsed| elapsed.to_string()).unwrap_or_default(),
+        ])
+    }
+
+    /**
+     * Returns the payload as read.
+     *
+     * # Returns
+     * A Result containing a byte vector with the raw data or an ApplicationError.
+     */
+    fn get_raw(&self) -> Result<Vec<u8>, ApplicationError> {
+        Ok(self.payload.clone())
+    }
+
+    /**
+     * Returns the temperatures and the unit as a String.
+     *
+     * # Returns
+     * A Result containing a String with the values or an ApplicationError.
+     */
+    fn get_raw_string(&self) -> Result<String, ApplicationError> {
+        let value = |value: Option<f64>| value.map(|value| value.to_string()).unwrap_or_else(|| "OL".into());
+        Ok(format!(
+            "T1 {} T2 {} T1-T2 {} {} type {}",
+            value(self.t1),
+            value(self.t2),
+            value(self.difference()),
+            self.unit(),
+            self.thermocouple
+        ))
+    }
+
+    /**
+     * Returns the temperature of the channel as the measurement. The difference T1 - T2 is a
+     * temperature interval, so it is given in K rather than converted as a temperature.
+     *
+     * # Returns
+     * The measurement, or None if a thermocouple of the channel is open.
+     */
+    fn get_measurement(&self) -> Option<Measurement> {
+        let flags = if self.hold { vec!["hold".to_string()] } else { Vec::new() };
+        match self.channel {
+            Ut325Channel::T1 => Measurement::from_display(self.t1?, self.unit(), flags),
+            Ut325Channel::T2 => Measurement::from_display(self.t2?, self.unit(), flags),
+            Ut325Channel::Difference => {
+                let kelvin = if self.fahrenheit { self.difference()? * 5.0 / 9.0 } else { self.difference()? };
+                Measurement::from_display(kelvin, "K", flags)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[cfg(feature = "serial")]
+    fn frame(payload: &[u8]) -> Vec<u8> {
+        let mut frame = vec![0xAB, 0xCD, payload.len() as u8 + 2];
+        frame.extend_from_slice(payload);
+        let sum = frame.iter().map(|byte| *byte as u16).sum::<u16>();
+        frame.extend_from_slice(&sum.to_be_bytes());
+        frame
+    }
+
+    #[test]
+    #[cfg(feature = "serial")]
+    fn test_frames() {
+        let measure = [0x5E, 0x00, 0x00, 0xEB, 0x00, 0x9A, 0xFF];
+        let mut data = vec![0x00, 0x17];
+        data.extend(frame(&measure));
+        data.extend(&frame(&[0x60, 0x02, 0x00, 0x0A, 0x00])[..4]);
+        assert_eq!(ut325_frames(&data).unwrap(), vec![measure.to_vec()]);
+        let mut corrupt = frame(&measure);
+        corrupt[4] ^= 0x01;
+        assert!(ut325_frames(&corrupt).is_err());
+        assert_eq!(ut325_download_header(&[0x60, 0x02, 0x01, 0x0A, 0x00]).unwrap(), (258, 10));
+        assert!(ut325_download_header(&measure).is_err());
+    }
+
+    #[test]
+    fn test_parse_measurement() {
+        // T1 23.5 °C, T2 -10.2 °C, K type
+        let reading = Ut325Reading::parse(&[0x5E, 0x00, 0x00, 0xEB, 0x00, 0x9A, 0xFF], Ut325Channel::T1, None).unwrap();
+        assert_eq!(reading.get_csv().unwrap(), vec!["23.5", "-10.2", "33.7", "°C", "K", "false", "", ""]);
+        assert_eq!(reading.get_raw_string().unwrap(), "T1 23.5 T2 -10.2 T1-T2 33.7 °C type K");
+        assert!((reading.get_measurement().unwrap().value - 296.65).abs() < 1e-9);
+
+        // T1 212.0 °F held, T2 open, J type
+        let reading = Ut325Reading::parse(&[0x5E, 0x0B, 0x01, 0x48, 0x08, 0x00, 0x00], Ut325Channel::T2, None).unwrap();
+        assert_eq!(reading.get_csv().unwrap()[..6], ["212", "OL", "OL", "°F", "J", "true"]);
+        assert!(reading.get_measurement().is_none());
+        let reading = Ut325Reading::parse(&reading.payload, Ut325Channel::T1, None).unwrap();
+        let measurement = reading.get_measurement().unwrap();
+        assert!((measurement.value - 373.15).abs() < 1e-9);
+        assert_eq!(measurement.flags, vec!["hold"]);
+        assert!(Ut325Reading::parse(&[0x5E, 0x00, 0x09, 0, 0, 0, 0], Ut325Channel::T1, None).is_err());
+    }
+
+    #[test]
+    fn test_parse_record() {
+        // Record 3 of a download logged every 10 s, T1 50.0 °F, T2 41.0 °F
+        let payload = [0x61, 0x03, 0x00, 0x01, 0x00, 0xF4, 0x01, 0x9A, 0x01];
+        let reading = Ut325Reading::parse(&payload, Ut325Channel::Difference, Some(10)).unwrap();
+        assert_eq!(reading.get_csv().unwrap()[6..], ["3", "30"]);
+        // A difference of 9 °F is 5 K
+        assert!((reading.get_measurement().unwrap().value - 5.0).abs() < 1e-9);
+        assert_eq!(Ut325Reading::parse(&payload, Ut325Channel::T1, None).unwrap().elapsed_s, None);
+        assert!(Ut325Reading::parse(&payload[..8], Ut325Channel::T1, None).is_err());
+    }
+}
@@ -0,0 +1,288 @@
+use crate::{
+    error::ApplicationError,
+    instruments::reading::{Measurement, Reading},
+};
+
+/**
+ * Length of a UT71 frame including the CR LF terminator.
+ */
+pub const UT71_FRAME_LENGTH: usize = 11;
+
+// Status byte 7
+const STATUS_AC: u8 = 0x01;
+const STATUS_DC: u8 = 0x02;
+const STATUS_AUTO: u8 = 0x04;
+const STATUS_NEGATIVE: u8 = 0x08;
+
+// Status byte 8
+const STATUS_HOLD: u8 = 0x01;
+const STATUS_MAX: u8 = 0x02;
+const STATUS_MIN: u8 = 0x04;
+const STATUS_REL: u8 = 0x08;
+
+// CSV field names, in the order returned by get_csv
+const CSV_HEADER: [&str; 14] = [
+    "mode", "range", "display_value", "overload", "decimal_value", "display_unit", "ac", "dc", "auto", "hold",
+    "max", "min", "rel", "scaled_value",
+];
+
+/**
+ * Gets the mode name and the (decimals, unit) of each range of a function code.
+ *
+ * # Arguments
+ * `function` - The function code, the low nibble of byte 6.
+ * `ac` - Whether the AC flag is set, used by the current functions.
+ *
+ * # Returns
+ * Some((mode, ranges)) for known function codes, otherwise None.
+ */
+fn function(function: u8, ac: bool) -> Option<(&'static str, &'static [(usize, &'static str)])> {
+    let current = |dc_mode, ac_mode| if ac { ac_mode } else { dc_mode };
+    match function {
+        0x0 => Some(("%", &[(2, "%")])),
+        0x1 => Some(("ACmV", &[(2, "mV")])),
+        0x2 => Some(("DCV", &[(4, "V"), (3, "V"), (2, "V"), (1, "V")])),
+        0x3 => Some(("ACV", &[(4, "V"), (3, "V"), (2, "V"), (1, "V")])),
+        0x4 => Some(("DCmV", &[(2, "mV")])),
+        0x5 => Some(("OHM", &[(2, "Ω"), (4, "kΩ"), (3, "kΩ"), (2, "kΩ"), (4, "MΩ"), (3, "MΩ")])),
+        0x6 => Some((
+            "CAP",
+            &[(3, "nF"), (2, "nF"), (4, "µF"), (3, "µF"), (2, "µF"), (4, "mF"), (3, "mF")],
+        )),
+        0x7 => Some(("°C", &[(1, "°C")])),
+        0x8 => Some((current("DCuA", "ACuA"), &[(2, "µA"), (1, "µA")])),
+        0x9 => Some((current("DCmA", "ACmA"), &[(3, "mA"), (2, "mA")])),
+        0xa => Some((current("DCA", "ACA"), &[(4, "A"), (3, "A")])),
+        0xb => Some(("CONT", &[(2, "Ω")])),
+        0xc => Some(("DIODE", &[(4, "V")])),
+        0xd => Some((
+            "Hz",
+            &[(3, "Hz"), (2, "Hz"), (4, "kHz"), (3, "kHz"), (2, "kHz"), (4, "MHz"), (3, "MHz")],
+        )),
+        0xe => Some(("°F", &[(1, "°F")])),
+        _ => None,
+    }
+}
+
+/**
+ * Represents a measurement frame of a UNI-T UT71 series meter. Frames are 11 bytes:
+ * five display digits, range, function, two status bytes and CR LF. Only the low nibble
+ * of each byte carries data.
+ */
+#[derive(Debug)]
+pub struct Ut71Reading {
+    pub mode: String,
+    pub range: u8,
+    pub display_value: String,
+    pub overload: bool,
+    pub decimal_value: Option<f64>,
+    pub display_unit: String,
+    pub ac: bool,
+    pub dc: bool,
+    pub auto: bool,
+    pub hold: bool,
+    pub max: bool,
+    pub min: bool,
+    pub rel: bool,
+    pub original_bytes: Vec<u8>,
+}
+
+impl Ut71Reading {
+    /**
+     * Finds the first complete frame in a stream of received bytes. Frames start after a CR LF,
+     * as reading may have started in the middle of a frame.
+     *
+     * # Arguments
+     * `data` - The received bytes.
+     *
+     * # Returns
+     * Some(frame) once a complete frame has been received, otherwise None.
+     */
+    #[cfg(feature = "serial")]
+    pub fn find_frame(data: &[u8]) -> Option<&[u8]> {
+        let start = data.windows(2).position(|window| window == b"\r\n")? + 2;
+        let frame = data.get(start..start + UT71_FRAME_LENGTH)?;
+        frame.ends_with(b"\r\n").then_some(frame)
+    }
+
+    /**
+     * Parses a frame.
+     *
+     * # Arguments
+     * `frame` - The 11 frame bytes.
+     *
+     * # Returns
+     * A Result containing the Ut71Reading or an ApplicationError if the frame is invalid.
+     */
+    pub fn parse(frame: &[u8]) -> Result<Self, ApplicationError> {
+        if frame.len() != UT71_FRAME_LENGTH || !frame.ends_with(b"\r\n") {
+            return Err(ApplicationError::Command(format!("Invalid UT71 frame {:02x?}", frame)));
+        }
+        let nibbles: Vec<u8> = frame[..9].iter().map(|byte| byte & 0x0f).collect();
+        let (status, flags) = (nibbles[7], nibbles[8]);
+        let ac = status & STATUS_AC != 0;
+        let (mode, ranges) = function(nibbles[6], ac)
+            .ok_or_else(|| ApplicationError::Command(format!("Unknown UT71 function {:#x}", nibbles[6])))?;
+        let range = nibbles[5];
+        let (decimals, unit) = *ranges
+            .get(range as usize)
+            .ok_or_else(|| ApplicationError::Command(format!("Unknown UT71 range {} for {}", range, mode)))?;
+        let digits = &nibbles[..5];
+        let overload = digits.iter().any(|digit| *digit > 9);
+        let display_value = if overload {
+            "OL".to_string()
+        } else {
+            let digits: String = digits.iter().map(|digit| char::from(b'0' + digit)).collect();
+            let (integer, fraction) = digits.split_at(digits.len() - decimals);
+            let sign = if status & STATUS_NEGATIVE != 0 { "-" } else { "" };
+            match fraction {
+                "" => format!("{}{}", sign, integer),
+                _ => format!("{}{}.{}", sign, integer, fraction),
+            }
+        };
+        Ok(Self {
+            mode: mode.to_string(),
+            range,
+            decimal_value: if overload { None } else { display_value.parse().ok() },
+            display_value,
+            overload,
+            display_unit: unit.to_string(),
+            ac,
+            dc: status & STATUS_DC != 0,
+            auto: status & STATUS_AUTO != 0,
+            hold: flags & STATUS_HOLD != 0,
+            max: flags & STATUS_MAX != 0,
+            min: flags & STATUS_MIN != 0,
+            rel: flags & STATUS_REL != 0,
+            original_bytes: frame.to_vec(),
+        })
+    }
+}
+
+impl Reading for Ut71Reading {
+    /**
+     * Returns the names of the CSV fields.
+     *
+     * # Returns
+     * A Result containing the CSV header fields or an ApplicationError.
+     */
+    fn get_csv_header(&self) -> Result<Vec<String>, ApplicationError> {
+        Ok(CSV_HEADER.iter().map(|field| field.to_string()).collect())
+    }
+
+    /**
+     * Returns the measurement data as CSV fields.
+     *
+     * # Returns
+     * A Result containing the CSV field values or an ApplicationError.
+     */
+    fn get_csv(&self) -> Result<Vec<String>, ApplicationError> {
+        Ok(vec![
+            self.mode.clone(),
+            self.range.to_string(),
+            self.display_value.clone(),
+            self.overload.to_string(),
+            self.decimal_value.map(|value| value.to_string()).unwrap_or_default(),
+            self.display_unit.clone(),
+            self.ac.to_string(),
+            self.dc.to_string(),
+            self.auto.to_string(),
+            self.hold.to_string(),
+            self.max.to_string(),
+            self.min.to_string(),
+            self.rel.to_string(),
+            self.get_measurement()
+                .map(|measurement| measurement.value.to_string())
+                .unwrap_or_default(),
+        ])
+    }
+
+    /**
+     * Returns the raw frame as a byte vector.
+     *
+     * # Returns
+     * A Result containing a byte vector with the raw data or an ApplicationError.
+     */
+    fn get_raw(&self) -> Result<Vec<u8>, ApplicationError> {
+        Ok(self.original_bytes.clone())
+    }
+
+    /**
+     * Returns the displayed value and unit as a String.
+     *
+     * # Returns
+     * A Result containing a String with the value or an ApplicationError.
+     */
+    fn get_raw_string(&self) -> Result<String, ApplicationError> {
+        Ok(format!("{} {} {}", self.mode, self.display_value, self.display_unit))
+    }
+
+    /**
+     * Returns the typed measurement of the frame.
+     *
+     * # Returns
+     * The measurement, or None for overload.
+     */
+    fn get_measurement(&self) -> Option<Measurement> {
+        let flags = [
+            ("max", self.max),
+            ("min", self.min),
+            ("hold", self.hold),
+            ("rel", self.rel),
+            ("auto", self.auto),
+        ]
+        .iter()
+        .filter(|(_, active)| *active)
+        .map(|(flag, _)| flag.to_string())
+        .collect();
+        Measurement::from_display(self.decimal_value?, &self.display_unit, flags)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_dc_voltage() {
+        // 1.2345 V, DCV range 0, DC and auto
+        let reading = Ut71Reading::parse(b"1234502\x36\x30\r\n").unwrap();
+        assert_eq!(reading.mode, "DCV");
+        assert_eq!(reading.display_value, "1.2345");
+        assert_eq!(reading.display_unit, "V");
+        assert!(reading.dc && reading.auto && !reading.ac);
+        assert_eq!(reading.get_measurement().unwrap().value, 1.2345);
+    }
+
+    #[test]
+    fn test_parse_flags_and_ranges() {
+        // 123.45 kΩ in resistance range 3 with hold and rel
+        let reading = Ut71Reading::parse(b"1234535\x34\x39\r\n").unwrap();
+        assert_eq!(reading.mode, "OHM");
+        assert_eq!(reading.display_value, "123.45");
+        assert_eq!(reading.display_unit, "kΩ");
+        assert!(reading.hold && reading.rel && !reading.max);
+        assert!((reading.get_measurement().unwrap().value - 123450.0).abs() < 1e-6);
+        // Negative AC current in the mA range
+        let reading = Ut71Reading::parse(b"0012309\x39\x30\r\n").unwrap();
+        assert_eq!(reading.mode, "ACmA");
+        assert_eq!(reading.display_value, "-00.123");
+    }
+
+    #[test]
+    fn test_overload_and_invalid() {
+        let reading = Ut71Reading::parse(b":::::05\x30\x30\r\n").unwrap();
+        assert!(reading.overload);
+        assert!(reading.get_measurement().is_none());
+        assert!(Ut71Reading::parse(b"1234592\x30\x30\r\n").is_err());
+        assert!(Ut71Reading::parse(b"123450\x3f\x30\x30\r\n").is_err());
+        assert!(Ut71Reading::parse(b"1234502\x36\x30\r").is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "serial")]
+    fn test_find_frame() {
+        assert_eq!(Ut71Reading::find_frame(b"0\r\n1234502\x36\x30\r\n12"), Some(&b"1234502\x36\x30\r\n"[..]));
+        assert_eq!(Ut71Reading::find_frame(b"45026\x30\r\n12345"), None);
+    }
+}
@@ -0,0 +1,251 @@
+use crate::{
+    error::ApplicationError,
+    instruments::reading::{Reading, Samples},
+};
+
+// CSV field names, in the order returned by get_csv
+const CSV_HEADER: [&str; 5] = ["source", "points", "x_increment", "x_origin", "y_increment"];
+
+/**
+ * Waveform data format given by the preamble.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WaveformFormat {
+    /// One unsigned byte per point.
+    Byte,
+    /// Two bytes per point, little endian.
+    Word,
+}
+
+/**
+ * The `:WAV:PRE?` preamble describing how waveform points convert to time and voltage.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct WaveformPreamble {
+    pub format: WaveformFormat,
+    pub points: u64,
+    pub x_increment: f64,
+    pub x_origin: f64,
+    pub x_reference: f64,
+    pub y_increment: f64,
+    pub y_origin: f64,
+    pub y_reference: f64,
+}
+
+impl WaveformPreamble {
+    /**
+     * Parses a preamble of the form `format,type,points,count,xincrement,xorigin,xreference,
+     * yincrement,yorigin,yreference`.
+     *
+     * # Arguments
+     * `response` - The raw preamble response.
+     *
+     * # Returns
+     * A Result containing the WaveformPreamble or an ApplicationError.
+     */
+    pub fn parse(response: &[u8]) -> Result<Self, ApplicationError> {
+        let text = String::from_utf8_lossy(response);
+        let fields = text
+            .trim()
+            .split(',')
+            .map(|field| field.trim().parse::<f64>())
+            .collect::<Result<Vec<f64>, _>>()
+            .map_err(|e| ApplicationError::ParseFloat(format!("Invalid waveform preamble {}", text.trim()), e))?;
+        let [format, _, points, _, x_increment, x_origin, x_reference, y_increment, y_origin, y_reference] = fields[..]
+        else {
+            return Err(ApplicationError::Command(format!(
+                "Expected 10 preamble fields, got {}",
+                text.trim()
+            )));
+        };
+        let format = match format as u8 {
+            0 => WaveformFormat::Byte,
+            1 => WaveformFormat::Word,
+            _ => {
+                return Err(ApplicationError::Command(
+                    "Only BYTE and WORD waveform formats are supported".into(),
+                ))
+            }
+        };
+        Ok(Self {
+            format,
+            points: points as u64,
+            x_increment,
+            x_origin,
+            x_reference,
+            y_increment,
+            y_origin,
+            y_reference,
+        })
+    }
+}
+
+/**
+ * Represents a waveform captured from an oscilloscope.
+ */
+#[derive(Debug)]
+pub struct WaveformReading {
+    pub source: String,
+    pub preamble: WaveformPreamble,
+    /**
+     * Raw points as returned by the scope, in the preamble format.
+     */
+    pub data: Vec<u8>,
+}
+
+impl WaveformReading {
+    /**
+     * Creates a new WaveformReading.
+     *
+     * # Arguments
+     * `source` - The source channel.
+     * `preamble` - The preamble of the waveform.
+     * `data` - The raw points, the payloads of all data blocks concatenated.
+     *
+     * # Returns
+     * A new WaveformReading instance.
+     */
+    pub fn new(source: &str, preamble: WaveformPreamble, data: Vec<u8>) -> Self {
+        Self {
+            source: source.to_string(),
+            preamble,
+            data,
+        }
+    }
+
+    /**
+     * Converts the raw points to time and voltage pairs.
+     *
+     * # Returns
+     * The samples as (time in s, voltage in V).
+     */
+    pub fn samples(&self) -> Vec<(f64, f64)> {
+        let preamble = &self.preamble;
+        let points: Vec<f64> = match preamble.format {
+            WaveformFormat::Byte => self.data.iter().map(|point| *point as f64).collect(),
+            WaveformFormat::Word => self
+                .data
+                .chunks_exact(2)
+                .map(|point| u16::from_le_bytes([point[0], point[1]]) as f64)
+                .collect(),
+        };
+        points
+            .iter()
+            .enumerate()
+            .map(|(index, point)| {
+                (
+                    (index as f64 - preamble.x_reference) * preamble.x_increment + preamble.x_origin,
+                    (point - preamble.y_origin - preamble.y_reference) * preamble.y_increment,
+                )
+            })
+            .collect()
+    }
+}
+
+impl Reading for WaveformReading {
+    /**
+     * Returns the names of the CSV fields.
+     *
+     * # Returns
+     * A Result containing the CSV header fields or an ApplicationError.
+     */
+    fn get_csv_header(&self) -> Result<Vec<String>, ApplicationError> {
+        Ok(CSV_HEADER.iter().map(|field| field.to_string()).collect())
+    }
+
+    /**
+     * Returns a summary of the waveform as CSV fields. The samples are returned by get_raw_string.
+     *
+     * # Returns
+     * A Result containing the CSV field values or an ApplicationError.
+     */
+    fn get_csv(&self) -> Result<Vec<String>, ApplicationError> {
+        Ok(vec![
+            self.source.clone(),
+            self.samples().len().to_string(),
+            self.preamble.x_increment.to_string(),
+            self.preamble.x_origin.to_string(),
+            self.preamble.y_increment.to_string(),
+        ])
+    }
+
+    /**
+     * Returns the raw points as a byte vector.
+     *
+     * # Returns
+     * A Result containing a byte vector with the raw data or an ApplicationError.
+     */
+    fn get_raw(&self) -> Result<Vec<u8>, ApplicationError> {
+        Ok(self.data.clone())
+    }
+
+    /**
+     * Returns the samples as lines of time and voltage.
+     *
+     * # Returns
+     * A Result containing the samples or an ApplicationError.
+     */
+    fn get_raw_string(&self) -> Result<String, ApplicationError> {
+        Ok(self
+            .samples()
+            .iter()
+            .map(|(time, voltage)| format!("{},{}", time, voltage))
+            .collect::<Vec<String>>()
+            .join("\n"))
+    }
+
+    /**
+     * Returns the samples in volts, with the sample rate given by the x increment.
+     *
+     * # Returns
+     * The samples.
+     */
+    fn get_samples(&self) -> Option<Samples> {
+        Some(Samples {
+            sample_rate: 1.0 / self.preamble.x_increment,
+            points: self.samples(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const PREAMBLE: &[u8] = b"0,2,4,1,1.000000e-06,-2.000000e-06,0,1.000000e-01,-1.000000e+00,127\n";
+
+    #[test]
+    fn test_parse_preamble() {
+        let preamble = WaveformPreamble::parse(PREAMBLE).unwrap();
+        assert_eq!(preamble.format, WaveformFormat::Byte);
+        assert_eq!(preamble.points, 4);
+        assert_eq!(preamble.y_reference, 127.0);
+        assert!(WaveformPreamble::parse(b"0,2,4\n").is_err());
+        assert!(WaveformPreamble::parse(b"2,2,4,1,1,0,0,1,0,0\n").is_err());
+    }
+
+    #[test]
+    fn test_samples() {
+        let preamble = WaveformPreamble::parse(PREAMBLE).unwrap();
+        let reading = WaveformReading::new("CHAN1", preamble, vec![126, 136, 146, 126]);
+        let samples = reading.samples();
+        assert_eq!(samples.len(), 4);
+        assert!((samples[0].0 + 2e-6).abs() < 1e-12);
+        assert!((samples[1].0 + 1e-6).abs() < 1e-12);
+        assert!(samples[0].1.abs() < 1e-9);
+        assert!((samples[1].1 - 1.0).abs() < 1e-9);
+        assert_eq!(reading.get_csv().unwrap()[1], "4");
+        let exported = reading.get_samples().unwrap();
+        assert!((exported.sample_rate - 1e6).abs() < 1e-3);
+        assert_eq!(exported.points, samples);
+    }
+
+    #[test]
+    fn test_word_samples() {
+        let mut preamble = WaveformPreamble::parse(PREAMBLE).unwrap();
+        preamble.format = WaveformFormat::Word;
+        let reading = WaveformReading::new("CHAN1", preamble, vec![0x7e, 0x00, 0x88, 0x00]);
+        assert_eq!(reading.samples().len(), 2);
+        assert!((reading.samples()[1].1 - 1.0).abs() < 1e-9);
+    }
+}
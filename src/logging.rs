@@ -0,0 +1,68 @@
+use tracing::level_filters::LevelFilter;
+
+/**
+ * Gets the log level of a verbosity, the number of -v flags. Warnings are always shown, -v
+ * shows the device open and claim steps, -vv the bytes written and read and the checksum
+ * validation, and -vvv everything.
+ *
+ * # Arguments
+ * `verbosity` - The number of -v flags.
+ *
+ * # Returns
+ * The most verbose level to log.
+ */
+pub fn level(verbosity: u8) -> LevelFilter {
+    match verbosity {
+        0 => LevelFilter::WARN,
+        1 => LevelFilter::INFO,
+        2 => LevelFilter::DEBUG,
+        _ => LevelFilter::TRACE,
+    }
+}
+
+/**
+ * Logs to stderr, so the log does not mix with readings written to stdout.
+ *
+ * # Arguments
+ * `verbosity` - The number of -v flags.
+ */
+pub fn init(verbosity: u8) {
+    tracing_subscriber::fmt()
+        .with_max_level(level(verbosity))
+        .with_writer(std::io::stderr)
+        .init();
+}
+
+/**
+ * Formats bytes as space separated hex, e.g. `2a 49 44 4e 3f 0a`.
+ *
+ * # Arguments
+ * `bytes` - The bytes.
+ *
+ * # Returns
+ * The hex dump.
+ */
+#[cfg(any(feature = "usb", feature = "hid", feature = "serial", feature = "gpib", feature = "ble", all(feature = "network", feature = "modbus")))]
+pub fn hex_dump(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect::<Vec<String>>().join(" ")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_level() {
+        assert_eq!(level(0), LevelFilter::WARN);
+        assert_eq!(level(1), LevelFilter::INFO);
+        assert_eq!(level(2), LevelFilter::DEBUG);
+        assert_eq!(level(5), LevelFilter::TRACE);
+    }
+
+    #[cfg(any(feature = "usb", feature = "hid", feature = "serial", feature = "gpib", feature = "ble", all(feature = "network", feature = "modbus")))]
+    #[test]
+    fn test_hex_dump() {
+        assert_eq!(hex_dump(b"*IDN?\n"), "2a 49 44 4e 3f 0a");
+        assert_eq!(hex_dump(&[]), "");
+    }
+}
@@ -1,28 +1,385 @@
 mod arguments;
+mod capture;
+mod config;
+mod daemon;
 mod error;
 mod instruments;
+mod logging;
+mod output;
+mod shutdown;
+mod workflow;
 
-use crate::{error::ApplicationError, instruments::communication::Communication};
+use std::{io::Write, path::Path, process::ExitCode, time::Duration};
+
+use clap::ValueEnum;
+
+use crate::{
+    error::ApplicationError,
+    instruments::communication::registry::check_devices,
+    output::{
+        chainlog, color, json,
+        backlog::{Backlog, Ordering},
+        locale::Translator,
+        otlp::{OtlpEndpoint, OtlpExporter},
+        rotation::Rotation,
+        s3::{S3Target, Uploader},
+        shape::with_shape,
+        sink,
+        systemlog::SystemLog,
+        ChainLog, CsvWriter, Printer, WebSocketServer,
+    },
+    workflow::{
+        acquire::{acquire_all, OpenDevices},
+        assertion::Assertions,
+        dut::{self, DutId},
+        filter::Filter,
+        latency::LatencySummary,
+    },
+};
 use arguments::Args;
+use config::Config;
 
 /**
- * Main entry point for the hardware measurement application.
+ * Main entry point for the hardware measurement application. Errors are printed and exit with
+ * the code of their category, see `ApplicationError::exit_code`.
  */
 #[tokio::main]
-async fn main() -> Result<(), ApplicationError> {
+async fn main() -> ExitCode {
+    match run().await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            ExitCode::from(e.exit_code())
+        }
+    }
+}
+
+/**
+ * Runs the application with the command line arguments.
+ *
+ * # Returns
+ * A Result indicating success or an ApplicationError.
+ */
+async fn run() -> Result<(), ApplicationError> {
     let args = Args::parse_args();
-    let instrument: Box<dyn Communication> = instruments::communication::get_communication_device(&args).await?;
-    let reading = instrument
-        .command(args.clone().commands.to_vec())
-        .await?;
-    if let Some(reading) = reading {
-        for reading in reading {
-            match args.clone().format.unwrap_or(arguments::Format::Raw) {
-                arguments::Format::Csv => println!("{:?}", reading.get_csv()?),
-                arguments::Format::Raw => println!("{:?}", reading.get_raw()?),
-                arguments::Format::RawString => println!("{:?}", reading.get_raw_string()?),
+    logging::init(args.common.verbose);
+    if let Some(shell) = args.completions {
+        return workflow::completions::write_completions(shell, &mut std::io::stdout());
+    }
+    if args.man {
+        return workflow::completions::write_man_page(&mut std::io::stdout());
+    }
+    if let Some(path) = &args.connection.capture {
+        capture::open(path)?;
+    }
+    if let Some(path) = &args.verify_log {
+        let (records, _) = chainlog::verify(path)?;
+        println!("{}: {} records verified", path, records);
+        return Ok(());
+    }
+    if !args.validate.is_empty() {
+        return workflow::validate::validate(&args);
+    }
+    let config = Config::load(&args)?;
+    if args.list {
+        workflow::devices::list(&config).iter().for_each(|line| println!("{}", line));
+    }
+    #[cfg(feature = "network")]
+    if args.discovery.discover {
+        let browse_time = Duration::from_millis(args.discovery.discover_ms);
+        workflow::discover::discover(browse_time).await?.iter().for_each(|line| println!("{}", line));
+    }
+    #[cfg(not(feature = "network"))]
+    if args.discovery.discover {
+        return Err(ApplicationError::Network("Compiled without the network feature".into()));
+    }
+    if args.list || args.discovery.discover {
+        return Ok(());
+    }
+    let mut devices = config.devices(&args)?;
+    if let (true, [(_, device_args), ..]) = (args.info, devices.as_slice()) {
+        workflow::devices::info(device_args).await?.iter().for_each(|line| println!("{}", line));
+        return Ok(());
+    }
+    if let [(_, device_args @ Args { bench: Some(count), .. }), ..] = devices.as_slice() {
+        workflow::bench::run(device_args, *count).await?.iter().for_each(|line| println!("{}", line));
+        return Ok(());
+    }
+    check_devices(&mut devices, &args)?;
+    let shapes = &config.sinks;
+    let args = devices[0].1.clone();
+    if let Some(path) = &args.socket {
+        return daemon::SocketDaemon::open(&devices).await?.serve(path).await;
+    }
+    if let Some(address) = &args.serve {
+        return daemon::Daemon::new(&devices).serve(address).await;
+    }
+    let notation = args.printing.notation.unwrap_or_default();
+    let (sink, rotation) = open_output(&args)?;
+    let mut printer = Printer::new(
+        args.clone().printing.format.unwrap_or(arguments::Format::Raw),
+        CsvWriter::new(
+            args.printing.csv_delimiter,
+            args.printing.csv_header,
+            notation,
+            args.printing.decimal_separator.unwrap_or_default(),
+        ),
+        Translator::new(args.printing.language.unwrap_or_default()),
+        notation,
+        color::enabled(args.printing.color.unwrap_or_default(), args.printing.output.is_some()),
+        sink,
+        rotation,
+    );
+    if args.decode.is_some() {
+        let result = workflow::decode::run(&devices, &mut printer, shapes.output.as_ref());
+        printer.finish().await?;
+        return result;
+    }
+    if args.control.stdin {
+        let result = workflow::session::run(&config, &devices, &mut printer, shapes.output.as_ref()).await;
+        printer.finish().await?;
+        return result;
+    }
+    if args.sequence.profile_file.is_some() {
+        let result = workflow::ramp::run(&devices, &mut printer, shapes.output.as_ref()).await;
+        printer.finish().await?;
+        return result;
+    }
+    if args.sequence.discharge_current.is_some() {
+        let result = workflow::discharge::run(&devices, &mut printer, shapes.output.as_ref()).await;
+        printer.finish().await?;
+        return result;
+    }
+    if args.sequence.sweep_start.is_some() {
+        let result = workflow::sweep::run(&devices, &mut printer, shapes.output.as_ref()).await;
+        printer.finish().await?;
+        return result;
+    }
+    if args.monitoring.record {
+        let result = workflow::record::run(&devices, &mut printer, shapes.output.as_ref()).await;
+        printer.finish().await?;
+        return result;
+    }
+    let websocket = match &args.monitoring.websocket {
+        Some(address) => Some(
+            WebSocketServer::start(
+                address,
+                args.monitoring.websocket_buffer,
+                args.sinks.sink_overflow,
+                args.monitoring.websocket_max_rate,
+            )
+            .await?,
+        ),
+        None => None,
+    };
+    let mut chain_log = match &args.sinks.chain_log {
+        Some(path) => Some(ChainLog::open(path)?),
+        None => None,
+    };
+    let system_log = match args.sinks.log_to {
+        Some(log_to) => Some(SystemLog::open(
+            log_to,
+            args.sinks.syslog_address.as_deref(),
+            &args.sinks.log_identifier,
+            open_backlog(&args, "syslog", Ordering::AnyOrder)?,
+        )?),
+        None => None,
+    };
+    let otlp = match &args.sinks.otlp_endpoint {
+        Some(url) => Some(OtlpExporter::start(
+            OtlpEndpoint::parse(url)?,
+            Duration::from_millis(args.sinks.otlp_interval_ms),
+            &args.sinks.log_identifier,
+            &args
+                .connection.device
+                .as_ref()
+                .and_then(|device| device.to_possible_value())
+                .map(|value| value.get_name().to_string())
+                .unwrap_or_default(),
+            open_backlog(&args, "otlp", Ordering::InOrder)?,
+            args.sinks.otlp_queue,
+            args.sinks.sink_overflow,
+        )),
+        None => None,
+    };
+    let mut dut_id = DutId::new(&args);
+    let mut latency = args.measurement.latency.then(LatencySummary::default);
+    let mut assertions = Assertions::new(&args.measurement.assertions, args.measurement.alarm_hook.clone())?;
+    let mut filter = Filter::new(&args)?;
+    // Devices stay open between the measurement cycles
+    let mut opened: Vec<_> = devices.iter().map(|_| OpenDevices::default()).collect();
+    loop {
+        // A shutdown request ends the run between the records, so no partial records are written
+        let id = tokio::select! {
+            biased;
+            _ = shutdown::requested() => break,
+            id = dut_id.next() => id?,
+        };
+        let Some(id) = id else {
+            break;
+        };
+        let (readings, mut error) = tokio::select! {
+            biased;
+            _ = shutdown::requested() => break,
+            acquired = acquire_all(&devices, &mut opened) => acquired,
+        };
+        let readings = match &mut filter {
+            Some(filter) => {
+                let mut filtered = Vec::new();
+                for reading in readings {
+                    filtered.extend(filter.apply(reading)?);
+                }
+                filtered
+            }
+            None => readings,
+        };
+        let mut violation = None;
+        for reading in readings {
+            let reading = dut::tag(&id, reading);
+            if let Some(latency) = &mut latency {
+                latency.record(reading.as_ref())?;
             }
+            with_shape(reading.as_ref(), shapes.output.as_ref(), |reading| printer.print(reading))?;
+            if let Some(chain_log) = &mut chain_log {
+                with_shape(reading.as_ref(), shapes.chain_log.as_ref(), |reading| chain_log.append(reading))?;
+            }
+            if let Some(system_log) = &system_log {
+                with_shape(reading.as_ref(), shapes.syslog.as_ref(), |reading| system_log.log_reading(reading))?;
+            }
+            if let Some(otlp) = &otlp {
+                otlp.record(reading.as_ref()).await?;
+            }
+            if let Some(websocket) = &websocket {
+                let message = with_shape(reading.as_ref(), shapes.websocket.as_ref(), |reading| {
+                    json::format_reading(reading, notation)
+                })?;
+                websocket.publish(message).await;
+            }
+            violation = violation.or_else(|| assertions.check(reading.as_ref()));
+        }
+        if let (None, Some(violation)) = (&error, violation) {
+            error = Some(assertions.alarm(&violation).await);
+        }
+        if let Some(e) = error {
+            drop(opened);
+            print_latency(latency.as_ref());
+            print_dropped(websocket.as_ref(), otlp.as_ref());
+            if let Some(Err(log_error)) = system_log.as_ref().map(|system_log| system_log.log_error(&e)) {
+                eprintln!("Error: {}", log_error);
+            }
+            if let Some(otlp) = otlp {
+                otlp.finish().await;
+            }
+            if let Err(finish_error) = printer.finish().await {
+                eprintln!("Error: {}", finish_error);
+            }
+            return Err(e);
         }
+        match args.monitoring.interval_ms {
+            // Monitoring ends with Ctrl-C or SIGTERM so the sinks are finished
+            Some(interval_ms) => tokio::select! {
+                _ = tokio::time::sleep(Duration::from_millis(interval_ms)) => {}
+                _ = shutdown::requested() => break,
+            },
+            None if dut_id.is_prompt() => continue,
+            None => break,
+        }
+    }
+    // Release the devices, e.g. claimed USB interfaces, before the sinks are finished
+    drop(opened);
+    print_latency(latency.as_ref());
+    print_dropped(websocket.as_ref(), otlp.as_ref());
+    if let Some(otlp) = otlp {
+        otlp.finish().await;
     }
-    Ok(())
-}
\ No newline at end of file
+    printer.finish().await?;
+    assertions.finish()
+}
+
+/**
+ * Prints the latency summary to stderr, if latencies are recorded, so it does not mix with the
+ * records.
+ *
+ * # Arguments
+ * `latency` - The latency summary.
+ */
+fn print_latency(latency: Option<&LatencySummary>) {
+    for line in latency.map(LatencySummary::lines).unwrap_or_default() {
+        eprintln!("latency {}", line);
+    }
+}
+
+/**
+ * Prints the number of readings the streaming sinks dropped because their queues were full
+ * to stderr, for the sinks in use.
+ *
+ * # Arguments
+ * `websocket` - The WebSocket server.
+ * `otlp` - The OTLP exporter.
+ */
+fn print_dropped(websocket: Option<&WebSocketServer>, otlp: Option<&OtlpExporter>) {
+    if let Some(websocket) = websocket {
+        eprintln!("websocket dropped {} messages", websocket.dropped());
+    }
+    if let Some(otlp) = otlp {
+        eprintln!("otlp dropped {} readings", otlp.dropped());
+    }
+}
+
+/**
+ * Opens the output of the printed records: stdout, the output file, or its first segment if
+ * the output is rotated. Finished segments are uploaded if an S3 bucket is given.
+ *
+ * # Arguments
+ * `args` - The command line arguments.
+ *
+ * # Returns
+ * A Result containing the sink and the rotation, if enabled, or an ApplicationError.
+ */
+fn open_output(args: &Args) -> Result<(Box<dyn Write>, Option<Rotation>), ApplicationError> {
+    let output =
+        args.printing.output.as_deref().filter(|_| args.readings.reader != Some(arguments::Reader::ScreenshotReader));
+    let (Some(path), Some(rotate_seconds)) = (output, args.sinks.rotate_seconds) else {
+        return Ok((sink::open(output)?, None));
+    };
+    let uploader = match &args.sinks.s3_url {
+        Some(url) => {
+            let credential = |arg: &Option<String>, variable: &str| {
+                arg.clone()
+                    .or_else(|| std::env::var(variable).ok())
+                    .ok_or_else(|| ApplicationError::Config(format!("No S3 credentials, set {}", variable)))
+            };
+            let target = S3Target::parse(
+                url,
+                &args.sinks.s3_region,
+                &credential(&args.sinks.s3_access_key, "AWS_ACCESS_KEY_ID")?,
+                &credential(&args.sinks.s3_secret_key, "AWS_SECRET_ACCESS_KEY")?,
+            )?;
+            let spool_dir = match &args.sinks.spool_dir {
+                Some(spool_dir) => Path::new(spool_dir).to_path_buf(),
+                None => Path::new(path).parent().unwrap_or(Path::new("")).join("spool"),
+            };
+            Some(Uploader::start(target, &spool_dir)?)
+        }
+        None => None,
+    };
+    let (rotation, sink) = Rotation::start(path, Duration::from_secs(rotate_seconds), uploader)?;
+    Ok((sink, Some(rotation)))
+}
+
+/**
+ * Opens the backlog of a network sink, persisted in a subdirectory of the buffer directory if
+ * one is given.
+ *
+ * # Arguments
+ * `args` - The command line arguments.
+ * `sink` - The name of the sink and its subdirectory.
+ * `ordering` - How the sink accepts late records.
+ *
+ * # Returns
+ * A Result containing the Backlog or an ApplicationError.
+ */
+fn open_backlog(args: &Args, sink: &str, ordering: Ordering) -> Result<Backlog, ApplicationError> {
+    let directory = args.sinks.buffer_dir.as_ref().map(|directory| Path::new(directory).join(sink));
+    Backlog::open(directory.as_deref(), args.sinks.buffer_limit, ordering)
+}
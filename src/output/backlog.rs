@@ -0,0 +1,263 @@
+use std::{
+    collections::VecDeque,
+    path::{Path, PathBuf},
+};
+
+use tracing::{info, warn};
+
+use crate::error::ApplicationError;
+
+/**
+ * Extension of the record files of a persisted backlog.
+ */
+const RECORD_EXTENSION: &str = "rec";
+
+/**
+ * How a sink accepts records older than records it already received.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Ordering {
+    /**
+     * Records must arrive in the order they were taken, e.g. metric backends that reject
+     * out-of-order samples. New records wait behind the backlog.
+     */
+    InOrder,
+    /**
+     * Each record carries its own timestamp and may arrive late, e.g. syslog. New records are
+     * sent at once and the backlog is backfilled behind them.
+     */
+    AnyOrder,
+}
+
+/**
+ * Records a network sink could not deliver, oldest first, kept with their original timestamps
+ * until the sink is reachable again. The backlog is held in memory and, with a directory, also
+ * persisted as one file per record so it survives a restart. When the limit is reached the
+ * oldest records are dropped.
+ */
+pub struct Backlog {
+    /**
+     * The records with their sequence numbers.
+     */
+    records: VecDeque<(u64, Vec<u8>)>,
+    directory: Option<PathBuf>,
+    limit: usize,
+    ordering: Ordering,
+    next_sequence: u64,
+    /**
+     * Records dropped since the backlog was last empty.
+     */
+    dropped: u64,
+    /**
+     * Records backfilled since the backlog was last empty.
+     */
+    backfilled: u64,
+}
+
+impl Backlog {
+    /**
+     * Opens a backlog. Records persisted by an earlier run are loaded first.
+     *
+     * # Arguments
+     * `directory` - The directory the records are persisted in, memory only if None.
+     * `limit` - The largest number of records kept.
+     * `ordering` - How the sink accepts late records.
+     *
+     * # Returns
+     * A Result containing the Backlog or an ApplicationError if the directory cannot be read.
+     */
+    pub fn open(directory: Option<&Path>, limit: usize, ordering: Ordering) -> Result<Self, ApplicationError> {
+        let mut backlog = Self {
+            records: VecDeque::new(),
+            directory: directory.map(Path::to_path_buf),
+            limit: limit.max(1),
+            ordering,
+            next_sequence: 0,
+            dropped: 0,
+            backfilled: 0,
+        };
+        if let Some(directory) = directory {
+            let error = |e: std::io::Error| {
+                ApplicationError::Io(format!("Could not read backlog {}", directory.display()), e)
+            };
+            std::fs::create_dir_all(directory).map_err(error)?;
+            let mut sequences: Vec<u64> = std::fs::read_dir(directory)
+                .map_err(error)?
+                .filter_map(|entry| entry.ok())
+                .filter_map(|entry| {
+                    let path = entry.path();
+                    match path.extension().and_then(|extension| extension.to_str()) {
+                        Some(RECORD_EXTENSION) => path.file_stem()?.to_str()?.parse().ok(),
+                        _ => None,
+                    }
+                })
+                .collect();
+            sequences.sort();
+            for sequence in sequences {
+                let record = std::fs::read(backlog.record_path(sequence)).map_err(error)?;
+                backlog.records.push_back((sequence, record));
+                backlog.next_sequence = sequence + 1;
+            }
+        }
+        Ok(backlog)
+    }
+
+    /**
+     * Checks if records are waiting.
+     *
+     * # Returns
+     * A boolean indicating whether the backlog is empty.
+     */
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+
+    /**
+     * Returns the number of waiting records.
+     *
+     * # Returns
+     * The number of records.
+     */
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    /**
+     * Checks if a new record must be queued behind the backlog instead of being sent at once.
+     *
+     * # Returns
+     * A boolean indicating whether the record must wait.
+     */
+    pub fn must_wait(&self) -> bool {
+        self.ordering == Ordering::InOrder && !self.records.is_empty()
+    }
+
+    /**
+     * Adds an undelivered record at the end, dropping the oldest record if the backlog is full.
+     *
+     * # Arguments
+     * `record` - The record as it is sent to the sink.
+     *
+     * # Returns
+     * A Result indicating success or an ApplicationError if the record cannot be persisted.
+     */
+    pub fn push(&mut self, record: Vec<u8>) -> Result<(), ApplicationError> {
+        if self.records.len() >= self.limit {
+            self.pop();
+            self.dropped += 1;
+        }
+        let sequence = self.next_sequence;
+        self.next_sequence += 1;
+        if self.directory.is_some() {
+            let path = self.record_path(sequence);
+            std::fs::write(&path, &record).map_err(|e| {
+                ApplicationError::Io(format!("Could not write backlog record {}", path.display()), e)
+            })?;
+        }
+        self.records.push_back((sequence, record));
+        Ok(())
+    }
+
+    /**
+     * Returns the oldest record.
+     *
+     * # Returns
+     * The record, or None if the backlog is empty.
+     */
+    pub fn front(&self) -> Option<&[u8]> {
+        self.records.front().map(|(_, record)| record.as_slice())
+    }
+
+    /**
+     * Removes the oldest record after it was delivered. Reports the number of backfilled and
+     * dropped records once the backlog is empty.
+     */
+    pub fn delivered(&mut self) {
+        if self.pop() {
+            self.backfilled += 1;
+        }
+        if self.records.is_empty() && self.backfilled > 0 {
+            info!("Backfilled {} buffered records, {} dropped", self.backfilled, self.dropped);
+            self.backfilled = 0;
+            self.dropped = 0;
+        }
+    }
+
+    /**
+     * Removes the oldest record and its file.
+     *
+     * # Returns
+     * A boolean indicating whether a record was removed.
+     */
+    fn pop(&mut self) -> bool {
+        let Some((sequence, _)) = self.records.pop_front() else {
+            return false;
+        };
+        if self.directory.is_some() {
+            let path = self.record_path(sequence);
+            if let Err(e) = std::fs::remove_file(&path) {
+                warn!("Could not remove backlog record {}: {}", path.display(), e);
+            }
+        }
+        true
+    }
+
+    /**
+     * Returns the file of a record.
+     *
+     * # Arguments
+     * `sequence` - The sequence number of the record.
+     *
+     * # Returns
+     * The path; the file name sorts in sequence order.
+     */
+    fn record_path(&self, sequence: u64) -> PathBuf {
+        self.directory
+            .as_deref()
+            .unwrap_or(Path::new(""))
+            .join(format!("{:020}.{}", sequence, RECORD_EXTENSION))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_memory_backlog() {
+        let mut backlog = Backlog::open(None, 2, Ordering::InOrder).unwrap();
+        assert!(!backlog.must_wait());
+        for record in [b"a", b"b", b"c"] {
+            backlog.push(record.to_vec()).unwrap();
+        }
+        assert_eq!(backlog.len(), 2);
+        assert!(backlog.must_wait());
+        assert_eq!(backlog.front(), Some(&b"b"[..]));
+        backlog.delivered();
+        assert_eq!(backlog.front(), Some(&b"c"[..]));
+        backlog.delivered();
+        assert!(backlog.is_empty());
+        let backlog = Backlog::open(None, 2, Ordering::AnyOrder).unwrap();
+        assert!(!backlog.must_wait());
+    }
+
+    #[test]
+    fn test_persisted_backlog() {
+        let directory = std::env::temp_dir().join(format!("backlog-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&directory);
+        let mut backlog = Backlog::open(Some(&directory), 10, Ordering::InOrder).unwrap();
+        backlog.push(b"first".to_vec()).unwrap();
+        backlog.push(b"second".to_vec()).unwrap();
+        backlog.delivered();
+        drop(backlog);
+        let mut backlog = Backlog::open(Some(&directory), 10, Ordering::InOrder).unwrap();
+        assert_eq!(backlog.len(), 1);
+        assert_eq!(backlog.front(), Some(&b"second"[..]));
+        backlog.push(b"third".to_vec()).unwrap();
+        assert_eq!(backlog.records.back().unwrap().0, 2);
+        backlog.delivered();
+        backlog.delivered();
+        assert_eq!(std::fs::read_dir(&directory).unwrap().count(), 0);
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+}
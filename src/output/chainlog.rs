@@ -0,0 +1,195 @@
+use std::{
+    fs::{File, OpenOptions},
+    io::{BufRead, BufReader, Write},
+};
+
+use sha2::{Digest, Sha256};
+
+use crate::{
+    arguments::Notation,
+    error::ApplicationError,
+    instruments::reading::Reading,
+    output::json,
+};
+
+/**
+ * Previous hash of the first record.
+ */
+const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+/**
+ * Append-only log where every record carries a SHA-256 hash chained to the previous record,
+ * so editing, removing or reordering records is detected by `verify`. Each line is
+ * `sequence<TAB>previous hash<TAB>hash<TAB>JSON record`.
+ */
+pub struct ChainLog {
+    file: File,
+    /**
+     * Sequence number of the last record.
+     */
+    sequence: u64,
+    /**
+     * Hash of the last record.
+     */
+    previous: String,
+}
+
+impl ChainLog {
+    /**
+     * Opens a log for appending. An existing log is verified and continued.
+     *
+     * # Arguments
+     * `path` - The log file.
+     *
+     * # Returns
+     * A Result containing the ChainLog or an ApplicationError if the log cannot be opened or fails verification.
+     */
+    pub fn open(path: &str) -> Result<Self, ApplicationError> {
+        let (sequence, previous) = match std::fs::metadata(path) {
+            Ok(_) => verify(path)?,
+            Err(_) => (0, GENESIS_HASH.to_string()),
+        };
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .map_err(|e| ApplicationError::Io(format!("Could not open log {}", path), e))?;
+        Ok(Self {
+            file,
+            sequence,
+            previous,
+        })
+    }
+
+    /**
+     * Appends a reading as a JSON record.
+     *
+     * # Arguments
+     * `reading` - The reading to append.
+     *
+     * # Returns
+     * A Result indicating success or an ApplicationError.
+     */
+    pub fn append(&mut self, reading: &dyn Reading) -> Result<(), ApplicationError> {
+        let record = json::format_reading(reading, Notation::Plain)?;
+        let sequence = self.sequence + 1;
+        let hash = record_hash(sequence, &self.previous, &record);
+        writeln!(self.file, "{}\t{}\t{}\t{}", sequence, self.previous, hash, record)
+            .and_then(|_| self.file.flush())
+            .map_err(|e| ApplicationError::Io("Could not write log".into(), e))?;
+        self.sequence = sequence;
+        self.previous = hash;
+        Ok(())
+    }
+}
+
+/**
+ * Computes the hash of a record.
+ *
+ * # Arguments
+ * `sequence` - The sequence number of the record.
+ * `previous` - The hash of the previous record.
+ * `record` - The JSON record.
+ *
+ * # Returns
+ * The hash as hex digits.
+ */
+fn record_hash(sequence: u64, previous: &str, record: &str) -> String {
+    hex::encode(Sha256::digest(format!("{}\t{}\t{}", sequence, previous, record)))
+}
+
+/**
+ * Verifies the hash chain of a log.
+ *
+ * # Arguments
+ * `path` - The log file.
+ *
+ * # Returns
+ * A Result containing the number of records and the hash of the last record, or an
+ * ApplicationError naming the first line that fails verification.
+ */
+pub fn verify(path: &str) -> Result<(u64, String), ApplicationError> {
+    let file = File::open(path).map_err(|e| ApplicationError::Io(format!("Could not open log {}", path), e))?;
+    verify_lines(BufReader::new(file).lines().map(|line| {
+        line.map_err(|e| ApplicationError::Io(format!("Could not read log {}", path), e))
+    }))
+}
+
+/**
+ * Verifies the hash chain of log lines.
+ *
+ * # Arguments
+ * `lines` - The lines of the log.
+ *
+ * # Returns
+ * A Result containing the number of records and the hash of the last record, or an
+ * ApplicationError naming the first line that fails verification.
+ */
+fn verify_lines(
+    lines: impl Iterator<Item = Result<String, ApplicationError>>,
+) -> Result<(u64, String), ApplicationError> {
+    let mut sequence = 0;
+    let mut previous = GENESIS_HASH.to_string();
+    for (index, line) in lines.enumerate() {
+        let line = line?;
+        let invalid = |reason: &str| ApplicationError::General(format!("Log line {} {}", index + 1, reason));
+        let mut fields = line.splitn(4, '\t');
+        let (Some(line_sequence), Some(line_previous), Some(hash), Some(record)) =
+            (fields.next(), fields.next(), fields.next(), fields.next())
+        else {
+            return Err(invalid("is not a log record"));
+        };
+        if line_sequence.parse::<u64>().ok() != Some(sequence + 1) {
+            return Err(invalid("is out of sequence; records were removed or reordered"));
+        }
+        if line_previous != previous {
+            return Err(invalid("does not chain to the previous record"));
+        }
+        if record_hash(sequence + 1, &previous, record) != hash {
+            return Err(invalid("does not match its hash; the record was edited"));
+        }
+        sequence += 1;
+        previous = hash.to_string();
+    }
+    Ok((sequence, previous))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::instruments::reading::KoradReading;
+
+    fn write_log(path: &str) {
+        let _ = std::fs::remove_file(path);
+        let mut log = ChainLog::open(path).unwrap();
+        log.append(&KoradReading::setpoints(b"1.234", b"0.100")).unwrap();
+        log.append(&KoradReading::setpoints(b"2.345", b"0.100")).unwrap();
+        let mut log = ChainLog::open(path).unwrap();
+        log.append(&KoradReading::setpoints(b"3.456", b"0.100")).unwrap();
+    }
+
+    #[test]
+    fn test_append_and_verify() {
+        let path = std::env::temp_dir().join(format!("chainlog-{}.log", std::process::id()));
+        let path = path.to_str().unwrap();
+        write_log(path);
+        assert_eq!(verify(path).unwrap().0, 3);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_tampering_detected() {
+        let path = std::env::temp_dir().join(format!("chainlog-tamper-{}.log", std::process::id()));
+        let path = path.to_str().unwrap();
+        write_log(path);
+        let content = std::fs::read_to_string(path).unwrap();
+        let lines: Vec<&str> = content.lines().collect();
+
+        let edited = content.replace("2.345", "2.346");
+        let error = verify_lines(edited.lines().map(|line| Ok(line.to_string()))).unwrap_err();
+        assert!(error.to_string().contains("line 2"));
+        let removed = [lines[0], lines[2]];
+        assert!(verify_lines(removed.iter().map(|line| Ok(line.to_string()))).is_err());
+        std::fs::remove_file(path).unwrap();
+    }
+}
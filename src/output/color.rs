@@ -0,0 +1,128 @@
+use std::io::{stdout, IsTerminal};
+
+use crate::arguments::ColorChoice;
+
+const BOLD: &str = "\x1b[1m";
+const DIM: &str = "\x1b[2m";
+const NORMAL_INTENSITY: &str = "\x1b[22m";
+const RED: &str = "\x1b[31m";
+const YELLOW: &str = "\x1b[33m";
+const DEFAULT_COLOR: &str = "\x1b[39m";
+
+/**
+ * Flags marking a reading as out of range or faulty, shown in red.
+ */
+const ALARM_FIELDS: [&str; 2] = ["overload", "hwwarning"];
+
+/**
+ * Flags marking a reading as frozen on the display, shown in yellow.
+ */
+const HELD_FIELDS: [&str; 1] = ["hold"];
+
+/**
+ * Checks if the output is colored.
+ *
+ * # Arguments
+ * `choice` - The --color argument.
+ * `to_file` - Whether the output goes to a file instead of stdout.
+ *
+ * # Returns
+ * A boolean indicating whether ANSI colors are written.
+ */
+pub fn enabled(choice: ColorChoice, to_file: bool) -> bool {
+    decide(choice, !to_file && stdout().is_terminal(), std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty()))
+}
+
+/**
+ * Decides whether the output is colored. With auto, only terminals are colored, unless the
+ * NO_COLOR convention asks for plain output.
+ *
+ * # Arguments
+ * `choice` - The --color argument.
+ * `terminal` - Whether the output is a terminal.
+ * `no_color` - Whether NO_COLOR is set.
+ *
+ * # Returns
+ * A boolean indicating whether ANSI colors are written.
+ */
+fn decide(choice: ColorChoice, terminal: bool, no_color: bool) -> bool {
+    match choice {
+        ColorChoice::Always => true,
+        ColorChoice::Never => false,
+        ColorChoice::Auto => terminal && !no_color,
+    }
+}
+
+/**
+ * Styles the value of a field: measured values bold and units dimmed.
+ *
+ * # Arguments
+ * `name` - The canonical field name.
+ * `value` - The formatted value.
+ *
+ * # Returns
+ * The value with ANSI styles.
+ */
+pub fn style_value(name: &str, value: &str) -> String {
+    if name.ends_with("value") {
+        format!("{}{}{}", BOLD, value, NORMAL_INTENSITY)
+    } else if name.ends_with("unit") {
+        format!("{}{}{}", DIM, value, NORMAL_INTENSITY)
+    } else {
+        value.to_string()
+    }
+}
+
+/**
+ * Colors a line by the state of the reading: red with overload or a hardware warning, yellow
+ * while the display is held.
+ *
+ * # Arguments
+ * `line` - The formatted reading.
+ * `header` - The canonical field names.
+ * `fields` - The field values.
+ *
+ * # Returns
+ * The line, colored if the reading has a highlighted state.
+ */
+pub fn highlight(line: String, header: &[String], fields: &[String]) -> String {
+    let active = |names: &[&str]| {
+        header
+            .iter()
+            .zip(fields)
+            .any(|(name, value)| value == "true" && names.contains(&name.as_str()))
+    };
+    if active(&ALARM_FIELDS) {
+        format!("{}{}{}", RED, line, DEFAULT_COLOR)
+    } else if active(&HELD_FIELDS) {
+        format!("{}{}{}", YELLOW, line, DEFAULT_COLOR)
+    } else {
+        line
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_decide() {
+        assert!(decide(ColorChoice::Auto, true, false));
+        assert!(!decide(ColorChoice::Auto, false, false));
+        assert!(!decide(ColorChoice::Auto, true, true));
+        assert!(decide(ColorChoice::Always, false, true));
+        assert!(!decide(ColorChoice::Never, true, false));
+    }
+
+    #[test]
+    fn test_highlight() {
+        let header: Vec<String> = ["overload", "hold"].iter().map(|name| name.to_string()).collect();
+        let fields = |overload: bool, hold: bool| vec![overload.to_string(), hold.to_string()];
+        assert_eq!(highlight("OL".into(), &header, &fields(true, true)), "\x1b[31mOL\x1b[39m");
+        assert_eq!(highlight("1.0".into(), &header, &fields(false, true)), "\x1b[33m1.0\x1b[39m");
+        assert_eq!(highlight("1.0".into(), &header, &fields(false, false)), "1.0");
+        assert_eq!(style_value("display_value", "1.0"), "\x1b[1m1.0\x1b[22m");
+        assert_eq!(style_value("display_unit", "V"), "\x1b[2mV\x1b[22m");
+        assert_eq!(style_value("mode", "DCV"), "DCV");
+    }
+}
@@ -0,0 +1,254 @@
+use crate::{arguments::{DecimalSeparator, Notation}, error::ApplicationError, instruments::reading::Reading, output::{notation, tidy}};
+
+/**
+ * Column names of the waveform CSV format.
+ */
+const SAMPLES_HEADER: [&str; 2] = ["time", "value"];
+
+/**
+ * Writes readings as RFC 4180 CSV records with an optional header row.
+ */
+pub struct CsvWriter {
+    /**
+     * Field delimiter.
+     */
+    delimiter: char,
+    /**
+     * Whether a header row should be written before the first record.
+     */
+    header: bool,
+    /**
+     * Whether the header row has already been written.
+     */
+    header_written: bool,
+    /**
+     * Notation of non-integer numbers.
+     */
+    notation: Notation,
+    /**
+     * Decimal separator of non-integer numbers.
+     */
+    decimal_separator: DecimalSeparator,
+}
+
+impl CsvWriter {
+    /**
+     * Creates a new CsvWriter.
+     *
+     * # Arguments
+     * `delimiter` - The field delimiter. With a decimal comma a comma delimiter becomes `;`, as
+     * spreadsheets in those locales expect.
+     * `header` - Whether to write a header row before the first record.
+     * `notation` - The notation of non-integer numbers.
+     * `decimal_separator` - The decimal separator of non-integer numbers.
+     *
+     * # Returns
+     * A new CsvWriter instance.
+     */
+    pub fn new(delimiter: char, header: bool, notation: Notation, decimal_separator: DecimalSeparator) -> Self {
+        let delimiter = match (decimal_separator, delimiter) {
+            (DecimalSeparator::Comma, ',') => ';',
+            _ => delimiter,
+        };
+        Self {
+            delimiter,
+            header,
+            header_written: false,
+            notation,
+            decimal_separator,
+        }
+    }
+
+    /**
+     * Writes the header row again before the next record, e.g. at the start of a new output segment.
+     */
+    pub fn restart(&mut self) {
+        self.header_written = false;
+    }
+
+    /**
+     * Formats a reading as CSV. The header row is prepended to the first record if enabled.
+     *
+     * # Arguments
+     * `reading` - The reading to format.
+     *
+     * # Returns
+     * A Result containing the CSV line(s) without trailing newline or an ApplicationError.
+     */
+    pub fn format_reading(&mut self, reading: &dyn Reading) -> Result<String, ApplicationError> {
+        let mut fields = reading.get_csv()?;
+        if self.notation != Notation::Plain {
+            fields = notation::format_fields(&reading.get_csv_header()?, fields, self.notation);
+        }
+        let record = self.format_record(&fields);
+        if self.header && !self.header_written {
+            self.header_written = true;
+            return Ok(format!("{}\n{}", self.format_record(&reading.get_csv_header()?), record));
+        }
+        Ok(record)
+    }
+
+    /**
+     * Formats a reading in the long format, one (timestamp, device, channel, field, value)
+     * record per field. The header row is prepended to the first records if enabled.
+     *
+     * # Arguments
+     * `reading` - The reading to format.
+     *
+     * # Returns
+     * A Result containing the CSV lines without trailing newline or an ApplicationError.
+     */
+    pub fn format_tidy_reading(&mut self, reading: &dyn Reading) -> Result<String, ApplicationError> {
+        let mut records: Vec<String> = tidy::rows(reading, &tidy::now(), self.notation)?
+            .iter()
+            .map(|row| self.format_record(row))
+            .collect();
+        if self.header && !self.header_written {
+            self.header_written = true;
+            records.insert(0, self.format_record(&tidy::TIDY_HEADER.map(String::from)));
+        }
+        Ok(records.join("\n"))
+    }
+
+    /**
+     * Formats the samples of a waveform reading as (time, value) records. The header row is
+     * prepended to the first records if enabled.
+     *
+     * # Arguments
+     * `reading` - The reading to format.
+     *
+     * # Returns
+     * A Result containing the CSV lines without trailing newline or an ApplicationError if the
+     * reading is not a waveform.
+     */
+    pub fn format_samples(&mut self, reading: &dyn Reading) -> Result<String, ApplicationError> {
+        let samples = reading
+            .get_samples()
+            .ok_or_else(|| ApplicationError::General("The waveform CSV format needs waveform readings".into()))?;
+        let header = SAMPLES_HEADER.map(String::from);
+        let mut records: Vec<String> = samples
+            .points
+            .iter()
+            .map(|(time, value)| {
+                let fields = notation::format_fields(&header, vec![time.to_string(), value.to_string()], self.notation);
+                self.format_record(&fields)
+            })
+            .collect();
+        if self.header && !self.header_written {
+            self.header_written = true;
+            records.insert(0, self.format_record(&header));
+        }
+        Ok(records.join("\n"))
+    }
+
+    /**
+     * Formats a list of fields as a single CSV record.
+     *
+     * # Arguments
+     * `fields` - The field values.
+     *
+     * # Returns
+     * The CSV record.
+     */
+    pub fn format_record(&self, fields: &[String]) -> String {
+        fields
+            .iter()
+            .map(|field| match self.decimal_separator {
+                DecimalSeparator::Dot => self.escape(field),
+                DecimalSeparator::Comma => self.escape(&notation::decimal_comma(field)),
+            })
+            .collect::<Vec<String>>()
+            .join(&self.delimiter.to_string())
+    }
+
+    /**
+     * Quotes a field if it contains the delimiter, a quote or a line break. Embedded quotes are doubled.
+     *
+     * # Arguments
+     * `field` - The field value.
+     *
+     * # Returns
+     * The escaped field.
+     */
+    fn escape(&self, field: &str) -> String {
+        if field.contains(self.delimiter) || field.contains('"') || field.contains('\n') || field.contains('\r') {
+            format!("\"{}\"", field.replace('"', "\"\""))
+        } else {
+            field.to_string()
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::instruments::reading::{Unit161dReading, WaveformPreamble, WaveformReading};
+
+    #[test]
+    fn test_format_record_plain() {
+        let writer = CsvWriter::new(',', false, Notation::Plain, DecimalSeparator::Dot);
+        assert_eq!(writer.format_record(&["a".into(), "1.5".into(), "".into()]), "a,1.5,");
+    }
+
+    #[test]
+    fn test_format_record_quoting() {
+        let writer = CsvWriter::new(',', false, Notation::Plain, DecimalSeparator::Dot);
+        assert_eq!(
+            writer.format_record(&["a,b".into(), "say \"hi\"".into(), "line\nbreak".into()]),
+            "\"a,b\",\"say \"\"hi\"\"\",\"line\nbreak\""
+        );
+    }
+
+    #[test]
+    fn test_format_record_custom_delimiter() {
+        let writer = CsvWriter::new(';', false, Notation::Plain, DecimalSeparator::Dot);
+        assert_eq!(writer.format_record(&["1,5".into(), "a;b".into()]), "1,5;\"a;b\"");
+    }
+
+    #[test]
+    fn test_format_record_decimal_comma() {
+        let writer = CsvWriter::new(',', false, Notation::Plain, DecimalSeparator::Comma);
+        assert_eq!(
+            writer.format_record(&["DCV".into(), "1.234".into(), "-4.7e-6".into(), "12".into(), "v1.2.3".into()]),
+            "DCV;1,234;-4,7e-6;12;v1.2.3"
+        );
+        let writer = CsvWriter::new('\t', false, Notation::Plain, DecimalSeparator::Comma);
+        assert_eq!(writer.format_record(&["0.5".into(), "a".into()]), "0,5\ta");
+    }
+
+    #[test]
+    fn test_format_reading_header_once() {
+        let raw_data = vec![2, b'0', b' ', b' ', b'1', b'.', b'2', b'3', b'4', 0, 0, 0, 0, 0];
+        let reading = Unit161dReading::parse(raw_data).unwrap();
+        let mut writer = CsvWriter::new(',', true, Notation::Plain, DecimalSeparator::Dot);
+        let first = writer.format_reading(&reading).unwrap();
+        let second = writer.format_reading(&reading).unwrap();
+        assert!(first.starts_with("mode,range,display_value,"));
+        assert_eq!(first.lines().count(), 2);
+        assert_eq!(second.lines().count(), 1);
+        assert!(second.starts_with("DCV,0,1.234,false,false,1.234,V,"));
+    }
+
+    #[test]
+    fn test_format_tidy_reading() {
+        let raw_data = vec![2, b'0', b' ', b' ', b'1', b'.', b'2', b'3', b'4', 0, 0, 0, 0, 0];
+        let reading = Unit161dReading::parse(raw_data).unwrap();
+        let mut writer = CsvWriter::new(';', true, Notation::Plain, DecimalSeparator::Dot);
+        let first = writer.format_tidy_reading(&reading).unwrap();
+        let mut lines = first.lines();
+        assert_eq!(lines.next(), Some("timestamp;device;channel;field;value"));
+        assert!(lines.next().unwrap().ends_with(";;;mode;DCV"));
+        assert!(!writer.format_tidy_reading(&reading).unwrap().starts_with("timestamp"));
+    }
+
+    #[test]
+    fn test_format_samples() {
+        let preamble = WaveformPreamble::parse(b"0,2,2,1,1e-3,0,0,0.5,0,0\n").unwrap();
+        let reading = WaveformReading::new("CHAN1", preamble, vec![2, 4]);
+        let mut writer = CsvWriter::new(';', true, Notation::Plain, DecimalSeparator::Dot);
+        assert_eq!(writer.format_samples(&reading).unwrap(), "time;value\n0;1\n0.001;2");
+        assert_eq!(writer.format_samples(&reading).unwrap(), "0;1\n0.001;2");
+        let raw_data = vec![2, b'0', b' ', b' ', b'1', b'.', b'2', b'3', b'4', 0, 0, 0, 0, 0];
+        assert!(writer.format_samples(&Unit161dReading::parse(raw_data).unwrap()).is_err());
+    }
+}
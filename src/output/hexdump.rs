@@ -0,0 +1,74 @@
+use crate::{error::ApplicationError, instruments::reading::Reading};
+
+/**
+ * Number of bytes per line of the dump.
+ */
+const BYTES_PER_LINE: usize = 16;
+
+/**
+ * Formats the raw data of a reading as a hex and ASCII dump in the layout of `xxd`: the offset,
+ * 16 bytes in groups of two and the printable characters, other bytes shown as `.`.
+ *
+ * # Arguments
+ * `reading` - The reading to format.
+ *
+ * # Returns
+ * A Result containing the dump lines without trailing newline, empty for readings without
+ * data, or an ApplicationError.
+ */
+pub fn format_reading(reading: &dyn Reading) -> Result<String, ApplicationError> {
+    Ok(dump(&reading.get_raw()?))
+}
+
+/**
+ * Formats bytes as a hex and ASCII dump.
+ *
+ * # Arguments
+ * `bytes` - The bytes.
+ *
+ * # Returns
+ * The dump lines without trailing newline.
+ */
+fn dump(bytes: &[u8]) -> String {
+    bytes
+        .chunks(BYTES_PER_LINE)
+        .enumerate()
+        .map(|(index, line)| {
+            let hex: Vec<String> = line
+                .chunks(2)
+                .map(|group| group.iter().map(|byte| format!("{:02x}", byte)).collect())
+                .collect();
+            let ascii: String = line
+                .iter()
+                .map(|byte| match byte {
+                    0x20..=0x7e => *byte as char,
+                    _ => '.',
+                })
+                .collect();
+            format!("{:08x}: {:<39}  {}", index * BYTES_PER_LINE, hex.join(" "), ascii)
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::instruments::reading::ScpiRawReading;
+
+    #[test]
+    fn test_dump() {
+        assert_eq!(
+            dump(b"*IDN?\n"),
+            "00000000: 2a49 444e 3f0a                           *IDN?."
+        );
+        let reading = ScpiRawReading::new(b"RIGOL TECHNOLOGIES,DS1054Z,DS1ZA0000\n".to_vec());
+        assert_eq!(
+            format_reading(&reading).unwrap(),
+            "00000000: 5249 474f 4c20 5445 4348 4e4f 4c4f 4749  RIGOL TECHNOLOGI\n\
+             00000010: 4553 2c44 5331 3035 345a 2c44 5331 5a41  ES,DS1054Z,DS1ZA\n\
+             00000020: 3030 3030 0a                             0000."
+        );
+        assert_eq!(dump(&[]), "");
+    }
+}
@@ -0,0 +1,187 @@
+use std::io::{self, Write};
+
+use serde::Serialize;
+use serde_json::{
+    json,
+    ser::{CompactFormatter, Formatter},
+    Map, Serializer, Value,
+};
+
+use crate::{arguments::Notation, error::ApplicationError, instruments::reading::Reading, output::notation};
+
+/**
+ * Compact JSON formatter writing non-integer numbers in the selected notation. Numbers of
+ * `display_*` fields, which show the instrument display, are written as they are.
+ */
+struct NotationFormatter {
+    notation: Notation,
+    /**
+     * The object key being written, or the last one written.
+     */
+    key: String,
+    /**
+     * Whether an object key is being written.
+     */
+    in_key: bool,
+}
+
+impl Formatter for NotationFormatter {
+    fn begin_object_key<W: ?Sized + Write>(&mut self, writer: &mut W, first: bool) -> io::Result<()> {
+        self.key.clear();
+        self.in_key = true;
+        CompactFormatter.begin_object_key(writer, first)
+    }
+
+    fn begin_object_value<W: ?Sized + Write>(&mut self, writer: &mut W) -> io::Result<()> {
+        self.in_key = false;
+        CompactFormatter.begin_object_value(writer)
+    }
+
+    fn write_string_fragment<W: ?Sized + Write>(&mut self, writer: &mut W, fragment: &str) -> io::Result<()> {
+        if self.in_key {
+            self.key.push_str(fragment);
+        }
+        CompactFormatter.write_string_fragment(writer, fragment)
+    }
+
+    fn write_f64<W: ?Sized + Write>(&mut self, writer: &mut W, value: f64) -> io::Result<()> {
+        if self.notation == Notation::Plain || value.fract() == 0.0 || self.key.starts_with("display_") {
+            CompactFormatter.write_f64(writer, value)
+        } else {
+            writer.write_all(notation::format_value(value, self.notation).as_bytes())
+        }
+    }
+}
+
+/**
+ * Converts a field value to a JSON value. Booleans are emitted as such, empty values as null
+ * and everything else as strings, as written: the fields of a reading include identifiers such
+ * as DUT ids, serial numbers and firmware versions, e.g. `000123` or `1.10`, that must not be
+ * read as numbers. The values as numbers are in the `measurement` object.
+ *
+ * # Arguments
+ * `field` - The field value.
+ *
+ * # Returns
+ * The JSON value.
+ */
+fn to_value(field: &str) -> Value {
+    match field {
+        "" => Value::Null,
+        "true" => Value::Bool(true),
+        "false" => Value::Bool(false),
+        _ => Value::String(field.to_string()),
+    }
+}
+
+/**
+ * Formats a reading as a single line JSON object keyed by the CSV header fields.
+ * Readings with a typed measurement also get a `measurement` object with the SI normalized value
+ * and the value in the displayed unit as numbers.
+ *
+ * # Arguments
+ * `reading` - The reading to format.
+ * `notation` - The notation of non-integer numbers.
+ *
+ * # Returns
+ * A Result containing the JSON object or an ApplicationError.
+ */
+pub fn format_reading(reading: &dyn Reading, notation: Notation) -> Result<String, ApplicationError> {
+    let mut object: Map<String, Value> = reading
+        .get_csv_header()?
+        .into_iter()
+        .zip(reading.get_csv()?.iter().map(|field| to_value(field)))
+        .collect();
+    if let Some(measurement) = reading.get_measurement() {
+        object.insert(
+            "measurement".into(),
+            json!({
+                "quantity": measurement.quantity.to_string(),
+                "value": measurement.value,
+                "unit": measurement.unit(),
+                "display_value": measurement.display_value(),
+                "display_unit": measurement.display_unit,
+                "flags": measurement.flags,
+            }),
+        );
+    }
+    if notation == Notation::Plain {
+        return Ok(Value::Object(object).to_string());
+    }
+    let mut serializer = Serializer::with_formatter(
+        Vec::new(),
+        NotationFormatter {
+            notation,
+            key: String::new(),
+            in_key: false,
+        },
+    );
+    Value::Object(object)
+        .serialize(&mut serializer)
+        .map_err(|e| ApplicationError::General(format!("Failed to write JSON: {}", e)))?;
+    String::from_utf8(serializer.into_inner())
+        .map_err(|e| ApplicationError::General(format!("Failed to write JSON: {}", e)))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::instruments::reading::{TaggedReading, Unit161dReading};
+
+    #[test]
+    fn test_to_value() {
+        assert_eq!(to_value(""), Value::Null);
+        assert_eq!(to_value("true"), Value::Bool(true));
+        assert_eq!(to_value("1.5"), Value::String("1.5".into()));
+        assert_eq!(to_value("DCV"), Value::String("DCV".into()));
+        assert_eq!(to_value("NaN"), Value::String("NaN".into()));
+    }
+
+    #[test]
+    fn test_identifier_fields() {
+        // DUT ids, serial numbers and firmware versions keep their text
+        assert_eq!(to_value("000123"), Value::String("000123".into()));
+        assert_eq!(to_value("1e3"), Value::String("1e3".into()));
+        assert_eq!(to_value("1.10"), Value::String("1.10".into()));
+        let reading = TaggedReading::new(
+            vec![("dut_id".into(), "000123".into()), ("firmware".into(), "1.10".into())],
+            Box::new(Unit161dReading::parse(vec![2, b'0', b' ', b' ', b'1', b'.', b'2', b'3', b'4', 0, 0, 0, 0, 0]).unwrap()),
+        );
+        let json: Value = serde_json::from_str(&format_reading(&reading, Notation::Scientific).unwrap()).unwrap();
+        assert_eq!(json["dut_id"], "000123");
+        assert_eq!(json["firmware"], "1.10");
+    }
+
+    #[test]
+    fn test_format_reading() {
+        let raw_data = vec![3, b'0', b' ', b'1', b'2', b'3', b'.', b'4', b' ', 0, 0, 0, 0, 0];
+        let reading = Unit161dReading::parse(raw_data).unwrap();
+        let json: Value = serde_json::from_str(&format_reading(&reading, Notation::Plain).unwrap()).unwrap();
+        assert_eq!(json["mode"], "DCmV");
+        assert_eq!(json["decimal_value"], "123.4");
+        assert_eq!(json["measurement"]["quantity"], "voltage");
+        assert_eq!(json["measurement"]["value"], 0.1234);
+        assert_eq!(json["measurement"]["unit"], "V");
+        assert_eq!(json["measurement"]["display_value"], 123.4);
+        assert_eq!(json["measurement"]["display_unit"], "mV");
+
+        let raw_data = vec![2, b'0', b' ', b' ', b'1', b'.', b'2', b'3', b'4', 0, 0, 0, 0, 0];
+        let reading = Unit161dReading::parse(raw_data).unwrap();
+        let json: Value = serde_json::from_str(&format_reading(&reading, Notation::Plain).unwrap()).unwrap();
+        assert_eq!(json["mode"], "DCV");
+        assert_eq!(json["decimal_value"], "1.234");
+        assert_eq!(json["hold"], false);
+    }
+
+    #[test]
+    fn test_format_reading_notation() {
+        let raw_data = vec![3, b'0', b' ', b'1', b'2', b'3', b'.', b'4', b' ', 0, 0, 0, 0, 0];
+        let reading = Unit161dReading::parse(raw_data).unwrap();
+        let text = format_reading(&reading, Notation::Scientific).unwrap();
+        assert!(text.contains("\"value\":1.234") && text.contains("e-1}"));
+        assert!(text.contains("\"display_value\":123.4"));
+        let json: Value = serde_json::from_str(&text).unwrap();
+        assert_eq!(json["measurement"]["value"], 0.1234);
+        assert_eq!(json["range"], "0");
+    }
+}
@@ -0,0 +1,69 @@
+use crate::{arguments::Notation, error::ApplicationError, instruments::reading::Reading, output::notation};
+
+/**
+ * Quotes a value containing spaces, quotes or `=` so the pairs can be split again.
+ *
+ * # Arguments
+ * `value` - The field value.
+ *
+ * # Returns
+ * The value, quoted if needed.
+ */
+fn quote(value: &str) -> String {
+    if value.contains([' ', '"', '=']) {
+        format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/**
+ * Formats a reading as one line of labelled `name=value` pairs with every field of the
+ * reading, including empty fields and inactive flags. Readings without CSV support fall back
+ * to the raw string.
+ *
+ * # Arguments
+ * `reading` - The reading to format.
+ * `notation` - The notation of non-integer numbers.
+ *
+ * # Returns
+ * A Result containing the line or an ApplicationError.
+ */
+pub fn format_reading(reading: &dyn Reading, notation: Notation) -> Result<String, ApplicationError> {
+    let (Ok(header), Ok(fields)) = (reading.get_csv_header(), reading.get_csv()) else {
+        return reading.get_raw_string();
+    };
+    let fields = notation::format_fields(&header, fields, notation);
+    let pairs: Vec<String> = header
+        .iter()
+        .zip(fields.iter())
+        .map(|(name, value)| format!("{}={}", name, quote(value)))
+        .collect();
+    Ok(pairs.join(" "))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::instruments::reading::Unit161dReading;
+
+    #[test]
+    fn test_format_reading() {
+        let mut raw_data = vec![2, b'1', b' ', b' ', b'1', b'2', b'.', b'3', b'4', 0, 5];
+        raw_data.extend([0b1010, 0b0100, 0b1000]);
+        let reading = Unit161dReading::parse(raw_data).unwrap();
+        assert_eq!(
+            format_reading(&reading, Notation::Plain).unwrap(),
+            "mode=DCV range=1 display_value=12.34 overload=false ncv=false decimal_value=12.34 display_unit=V \
+             progres=5 max=true min=false hold=true rel=false auto=true battery=false hwwarning=false dc=true \
+             peak_max=false peak_min=false bar_polarity=false scaled_value=12.34 ncv_level="
+        );
+    }
+
+    #[test]
+    fn test_quote() {
+        assert_eq!(quote("DCV"), "DCV");
+        assert_eq!(quote("RIGOL TECHNOLOGIES"), "\"RIGOL TECHNOLOGIES\"");
+        assert_eq!(quote("a\"b"), "\"a\\\"b\"");
+    }
+}
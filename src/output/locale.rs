@@ -0,0 +1,123 @@
+use crate::arguments::Language;
+
+/**
+ * Translations of canonical mode, flag and field names: (canonical, English, German, Norwegian).
+ * Machine formats always use the canonical names.
+ */
+const TRANSLATIONS: &[(&str, &str, &str, &str)] = &[
+    // Quantities
+    ("voltage", "voltage", "Spannung", "spenning"),
+    ("current", "current", "Strom", "strøm"),
+    ("resistance", "resistance", "Widerstand", "motstand"),
+    ("capacitance", "capacitance", "Kapazität", "kapasitans"),
+    ("frequency", "frequency", "Frequenz", "frekvens"),
+    ("temperature", "temperature", "Temperatur", "temperatur"),
+    // Meter modes
+    ("ACV", "AC voltage", "Wechselspannung", "vekselspenning"),
+    ("ACmV", "AC millivolts", "Wechselspannung mV", "vekselspenning mV"),
+    ("DCV", "DC voltage", "Gleichspannung", "likespenning"),
+    ("DCmV", "DC millivolts", "Gleichspannung mV", "likespenning mV"),
+    ("OHM", "resistance", "Widerstand", "motstand"),
+    ("CONT", "continuity", "Durchgang", "kontinuitet"),
+    ("DIDOE", "diode", "Diode", "diode"),
+    ("CAP", "capacitance", "Kapazität", "kapasitans"),
+    ("DIODE", "diode", "Diode", "diode"),
+    ("NCV", "non-contact voltage", "berührungslose Spannung", "berøringsfri spenning"),
+    ("DCA", "DC current", "Gleichstrom", "likestrøm"),
+    ("DCmA", "DC milliamperes", "Gleichstrom mA", "likestrøm mA"),
+    ("DCuA", "DC microamperes", "Gleichstrom µA", "likestrøm µA"),
+    ("ACA", "AC current", "Wechselstrom", "vekselstrøm"),
+    ("ACmA", "AC milliamperes", "Wechselstrom mA", "vekselstrøm mA"),
+    ("ACuA", "AC microamperes", "Wechselstrom µA", "vekselstrøm µA"),
+    ("Hz", "frequency", "Frequenz", "frekvens"),
+    ("HFE", "transistor gain", "Transistorverstärkung", "transistorforsterkning"),
+    ("Live", "live wire", "Phase", "fase"),
+    ("LozV", "low impedance voltage", "Spannung niederohmig", "lavimpedans spenning"),
+    ("INRUSH", "inrush current", "Einschaltstrom", "innkoblingsstrøm"),
+    ("CV", "constant voltage", "Konstantspannung", "konstant spenning"),
+    ("CC", "constant current", "Konstantstrom", "konstant strøm"),
+    // Flags
+    ("max", "max", "Max", "maks"),
+    ("min", "min", "Min", "min"),
+    ("hold", "hold", "Halten", "hold"),
+    ("rel", "relative", "Relativ", "relativ"),
+    ("auto", "auto range", "Automatischer Bereich", "automatisk område"),
+    ("peak_max", "peak max", "Spitze max", "topp maks"),
+    ("peak_min", "peak min", "Spitze min", "topp min"),
+    ("overload", "overload", "Überlast", "overbelastning"),
+    ("dc", "DC", "DC", "DC"),
+    ("hwwarning", "high voltage warning", "Hochspannungswarnung", "høyspenningsvarsel"),
+    ("battery", "low battery", "Batterie schwach", "lavt batteri"),
+    ("output", "output on", "Ausgang ein", "utgang på"),
+    // Fields
+    ("mode", "mode", "Modus", "modus"),
+    ("range", "range", "Bereich", "område"),
+    ("display_value", "display", "Anzeige", "visning"),
+    ("display_unit", "unit", "Einheit", "enhet"),
+    ("decimal_value", "value", "Wert", "verdi"),
+    ("scaled_value", "scaled value", "skalierter Wert", "skalert verdi"),
+    ("ncv", "NCV", "NCV", "NCV"),
+    ("ncv_level", "NCV level", "NCV-Stufe", "NCV-nivå"),
+    ("progres", "bar graph", "Balkenanzeige", "søylediagram"),
+    ("bar_polarity", "bar polarity", "Balkenpolarität", "søylepolaritet"),
+    ("values", "values", "Werte", "verdier"),
+    ("status", "status", "Status", "status"),
+    ("device", "device", "Gerät", "enhet"),
+];
+
+/**
+ * Translates canonical names for human facing output.
+ */
+#[derive(Debug, Clone)]
+pub struct Translator {
+    language: Language,
+}
+
+impl Translator {
+    /**
+     * Creates a new Translator.
+     *
+     * # Arguments
+     * `language` - The output language.
+     *
+     * # Returns
+     * A new Translator instance.
+     */
+    pub fn new(language: Language) -> Self {
+        Self { language }
+    }
+
+    /**
+     * Translates a canonical name.
+     *
+     * # Arguments
+     * `term` - The canonical name, e.g. `DCV` or `hold`.
+     *
+     * # Returns
+     * The translation, or the term itself if it has none.
+     */
+    pub fn translate<'a>(&self, term: &'a str) -> &'a str {
+        TRANSLATIONS
+            .iter()
+            .find(|(canonical, ..)| *canonical == term)
+            .map(|(_, en, de, nb)| match self.language {
+                Language::En => *en,
+                Language::De => *de,
+                Language::Nb => *nb,
+            })
+            .unwrap_or(term)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_translate() {
+        assert_eq!(Translator::new(Language::De).translate("DCV"), "Gleichspannung");
+        assert_eq!(Translator::new(Language::Nb).translate("hold"), "hold");
+        assert_eq!(Translator::new(Language::En).translate("auto"), "auto range");
+        assert_eq!(Translator::new(Language::De).translate("1.234"), "1.234");
+    }
+}
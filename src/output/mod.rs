@@ -0,0 +1,29 @@
+pub mod backlog;
+pub mod chainlog;
+pub mod color;
+mod csv;
+mod hexdump;
+pub mod json;
+mod keyvalue;
+pub mod locale;
+pub mod notation;
+pub mod otlp;
+mod parquetfile;
+pub mod pretty;
+mod printer;
+pub mod queue;
+pub mod rotation;
+pub mod s3;
+pub mod shape;
+pub mod sink;
+pub mod systemlog;
+mod tidy;
+mod vcd;
+mod wav;
+pub mod time;
+mod websocket;
+
+pub use chainlog::ChainLog;
+pub use csv::CsvWriter;
+pub use printer::Printer;
+pub use websocket::WebSocketServer;
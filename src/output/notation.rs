@@ -0,0 +1,153 @@
+use crate::arguments::Notation;
+
+/**
+ * Formats a number in the given notation, e.g. 0.0000047 as `4.7e-6` (scientific) or
+ * 0.00047 as `470e-6` (engineering, exponent a multiple of 3).
+ *
+ * # Arguments
+ * `value` - The number.
+ * `notation` - The notation.
+ *
+ * # Returns
+ * The formatted number.
+ */
+pub fn format_value(value: f64, notation: Notation) -> String {
+    match notation {
+        Notation::Plain => value.to_string(),
+        Notation::Scientific => format!("{:e}", value),
+        Notation::Engineering => engineering(value),
+    }
+}
+
+/**
+ * Reformats a field value if it is a non-integer number. Integers such as ranges, counts and
+ * status bytes, and text fields, are returned unchanged.
+ *
+ * # Arguments
+ * `field` - The field value.
+ * `notation` - The notation.
+ *
+ * # Returns
+ * The formatted field.
+ */
+pub fn format_field(field: &str, notation: Notation) -> String {
+    if notation == Notation::Plain || field.parse::<i64>().is_ok() {
+        return field.to_string();
+    }
+    match field.parse::<f64>() {
+        Ok(value) if value.is_finite() => format_value(value, notation),
+        _ => field.to_string(),
+    }
+}
+
+/**
+ * Reformats the numeric fields of a reading. `display_*` fields show the instrument display
+ * as it is and are never reformatted.
+ *
+ * # Arguments
+ * `header` - The field names.
+ * `fields` - The field values.
+ * `notation` - The notation.
+ *
+ * # Returns
+ * The formatted fields.
+ */
+pub fn format_fields(header: &[String], fields: Vec<String>, notation: Notation) -> Vec<String> {
+    if notation == Notation::Plain {
+        return fields;
+    }
+    fields
+        .into_iter()
+        .enumerate()
+        .map(|(index, field)| match header.get(index) {
+            Some(name) if name.starts_with("display_") => field,
+            _ => format_field(&field, notation),
+        })
+        .collect()
+}
+
+/**
+ * Writes a non-integer number with a decimal comma, e.g. `1.5` as `1,5`. Other fields are
+ * returned unchanged.
+ *
+ * # Arguments
+ * `field` - The field value.
+ *
+ * # Returns
+ * The field with a decimal comma if it is a number.
+ */
+pub fn decimal_comma(field: &str) -> String {
+    match field.parse::<f64>() {
+        Ok(_) => field.replace('.', ","),
+        Err(_) => field.to_string(),
+    }
+}
+
+/**
+ * Formats a number with an exponent that is a multiple of 3, keeping the shortest digits
+ * that round trip.
+ *
+ * # Arguments
+ * `value` - The number.
+ *
+ * # Returns
+ * The formatted number.
+ */
+fn engineering(value: f64) -> String {
+    let scientific = format!("{:e}", value);
+    let Some((mantissa, exponent)) = scientific.split_once('e') else {
+        return scientific;
+    };
+    let Ok(exponent) = exponent.parse::<i32>() else {
+        return scientific;
+    };
+    let (sign, mantissa) = match mantissa.strip_prefix('-') {
+        Some(mantissa) => ("-", mantissa),
+        None => ("", mantissa),
+    };
+    let shift = exponent.rem_euclid(3);
+    let integer_digits = 1 + shift as usize;
+    let mut digits = mantissa.replace('.', "");
+    while digits.len() < integer_digits {
+        digits.push('0');
+    }
+    let (integer, fraction) = digits.split_at(integer_digits);
+    match fraction {
+        "" => format!("{}{}e{}", sign, integer, exponent - shift),
+        _ => format!("{}{}.{}e{}", sign, integer, fraction, exponent - shift),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_format_value() {
+        assert_eq!(format_value(0.0000047, Notation::Plain), "0.0000047");
+        assert_eq!(format_value(0.0000047, Notation::Scientific), "4.7e-6");
+        assert_eq!(format_value(0.0000047, Notation::Engineering), "4.7e-6");
+        assert_eq!(format_value(0.00047, Notation::Engineering), "470e-6");
+        assert_eq!(format_value(-12345.6, Notation::Engineering), "-12.3456e3");
+        assert_eq!(format_value(0.0, Notation::Engineering), "0e0");
+    }
+
+    #[test]
+    fn test_format_fields() {
+        let header: Vec<String> = ["mode", "range", "display_value", "decimal_value"].map(String::from).to_vec();
+        let fields: Vec<String> = ["DCV", "2", "0.0047", "0.0047"].map(String::from).to_vec();
+        assert_eq!(
+            format_fields(&header, fields, Notation::Scientific),
+            vec!["DCV", "2", "0.0047", "4.7e-3"]
+        );
+        assert_eq!(format_field("NaN", Notation::Engineering), "NaN");
+    }
+
+    #[test]
+    fn test_decimal_comma() {
+        assert_eq!(decimal_comma("0.0047"), "0,0047");
+        assert_eq!(decimal_comma("470e-6"), "470e-6");
+        assert_eq!(decimal_comma("DCV"), "DCV");
+        assert_eq!(decimal_comma("2026-10-16T08:00:00.5Z"), "2026-10-16T08:00:00.5Z");
+    }
+}
@@ -0,0 +1,621 @@
+use std::{
+    collections::BTreeMap,
+    sync::Arc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use serde_json::{json, Value};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpStream,
+    task::JoinHandle,
+};
+use tracing::warn;
+
+use crate::{
+    arguments::SinkOverflow,
+    error::ApplicationError,
+    instruments::reading::Reading,
+    output::{backlog::Backlog, queue::SinkQueue},
+};
+
+/**
+ * Time allowed for a single export, including the connection.
+ */
+const EXPORT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/**
+ * Path the metrics are posted to when the endpoint URL has none.
+ */
+const DEFAULT_METRICS_PATH: &str = "/v1/metrics";
+
+/**
+ * Prefix of the metric names, followed by the quantity, e.g. `hardware_measurement.voltage`.
+ */
+const METRIC_PREFIX: &str = "hardware_measurement";
+
+/**
+ * Reading fields exported as data point attributes.
+ */
+const ATTRIBUTE_FIELDS: [&str; 3] = ["device", "channel", "dut_id"];
+
+/**
+ * Aggregation temporality of the histograms: every export covers the values since the previous one.
+ */
+const AGGREGATION_TEMPORALITY_DELTA: u8 = 1;
+
+/**
+ * An OTLP/HTTP collector endpoint, e.g. `http://otel-collector:4318`.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct OtlpEndpoint {
+    /**
+     * Host and port of the collector.
+     */
+    pub host: String,
+    pub path: String,
+}
+
+impl OtlpEndpoint {
+    /**
+     * Parses the endpoint URL. Only plain HTTP is supported, as for the S3 upload.
+     *
+     * # Arguments
+     * `url` - The URL, `http://host[:port][/path]`. The path defaults to /v1/metrics.
+     *
+     * # Returns
+     * A Result containing the OtlpEndpoint or an ApplicationError if the URL is invalid.
+     */
+    pub fn parse(url: &str) -> Result<Self, ApplicationError> {
+        let rest = url.strip_prefix("http://").ok_or_else(|| {
+            ApplicationError::Config(format!("Only http:// OTLP endpoints are supported, got {}", url))
+        })?;
+        let (host, path) = match rest.find('/') {
+            Some(index) => rest.split_at(index),
+            None => (rest, ""),
+        };
+        if host.is_empty() {
+            return Err(ApplicationError::Config(format!("No host in OTLP endpoint {}", url)));
+        }
+        let host = if host.contains(':') { host.to_string() } else { format!("{}:4318", host) };
+        let path = match path.trim_end_matches('/') {
+            "" => DEFAULT_METRICS_PATH.to_string(),
+            path => path.to_string(),
+        };
+        Ok(Self { host, path })
+    }
+
+    /**
+     * Posts an export request.
+     *
+     * # Arguments
+     * `body` - The JSON encoded ExportMetricsServiceRequest.
+     *
+     * # Returns
+     * A Result indicating success or an ApplicationError if the collector cannot be reached or
+     * rejects the request.
+     */
+    async fn post(&self, body: &[u8]) -> Result<(), ApplicationError> {
+        let error = |e: std::io::Error| ApplicationError::General(format!("OTLP export to {} failed: {}", self.host, e));
+        let head = format!(
+            "POST {} HTTP/1.1\r\nHost: {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+            self.path,
+            self.host,
+            body.len()
+        );
+        let response = tokio::time::timeout(EXPORT_TIMEOUT, async {
+            let mut stream = TcpStream::connect(&self.host).await?;
+            stream.write_all(head.as_bytes()).await?;
+            stream.write_all(body).await?;
+            let mut response = Vec::new();
+            stream.read_to_end(&mut response).await?;
+            Ok(response)
+        })
+        .await
+        .map_err(|_| ApplicationError::Timeout(format!("OTLP export to {}", self.host)))?
+        .map_err(error)?;
+        let response = String::from_utf8_lossy(&response);
+        let status = response.lines().next().unwrap_or_default();
+        match status.split_whitespace().nth(1) {
+            Some(code) if code.starts_with('2') => Ok(()),
+            _ => Err(ApplicationError::General(format!("OTLP export to {} rejected: {}", self.host, status))),
+        }
+    }
+}
+
+/**
+ * A measured value with the metric it is recorded in.
+ */
+#[derive(Debug, Clone, PartialEq)]
+struct Sample {
+    series: Series,
+    value: f64,
+    time_nanos: u128,
+}
+
+/**
+ * Identifies a metric time series: metric name, unit and attributes.
+ */
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+struct Series {
+    name: String,
+    unit: String,
+    attributes: Vec<(String, String)>,
+}
+
+/**
+ * Values of a series since the previous export.
+ */
+#[derive(Debug, Clone, PartialEq)]
+struct Aggregate {
+    last: f64,
+    last_nanos: u128,
+    count: u64,
+    sum: f64,
+    min: f64,
+    max: f64,
+}
+
+/**
+ * Collects samples between exports and builds the export requests. Every series is exported
+ * as a gauge with the last value and a histogram with the count, sum, min and max of the
+ * values since the previous export.
+ */
+#[derive(Debug, Default)]
+struct Aggregator {
+    series: BTreeMap<Series, Aggregate>,
+    /**
+     * Start of the current export interval.
+     */
+    start_nanos: u128,
+}
+
+impl Aggregator {
+    /**
+     * Adds a sample to its series.
+     *
+     * # Arguments
+     * `sample` - The sample.
+     */
+    fn record(&mut self, sample: Sample) {
+        let aggregate = self.series.entry(sample.series).or_insert(Aggregate {
+            last: sample.value,
+            last_nanos: sample.time_nanos,
+            count: 0,
+            sum: 0.0,
+            min: sample.value,
+            max: sample.value,
+        });
+        aggregate.last = sample.value;
+        aggregate.last_nanos = sample.time_nanos;
+        aggregate.count += 1;
+        aggregate.sum += sample.value;
+        aggregate.min = aggregate.min.min(sample.value);
+        aggregate.max = aggregate.max.max(sample.value);
+    }
+
+    /**
+     * Builds the export request of the current interval and starts the next interval.
+     *
+     * # Arguments
+     * `resource` - The resource attributes.
+     * `now_nanos` - The end of the interval.
+     *
+     * # Returns
+     * The ExportMetricsServiceRequest in the OTLP JSON encoding, or None if nothing was recorded.
+     */
+    fn take(&mut self, resource: &[(String, String)], now_nanos: u128) -> Option<Value> {
+        if self.series.is_empty() {
+            self.start_nanos = now_nanos;
+            return None;
+        }
+        let start_nanos = std::mem::replace(&mut self.start_nanos, now_nanos);
+        let mut metrics = Vec::new();
+        for (series, aggregate) in std::mem::take(&mut self.series) {
+            let attributes = attributes(&series.attributes);
+            metrics.push(json!({
+                "name": series.name,
+                "unit": series.unit,
+                "gauge": { "dataPoints": [{
+                    "attributes": attributes,
+                    "timeUnixNano": aggregate.last_nanos.to_string(),
+                    "asDouble": aggregate.last,
+                }]},
+            }));
+            metrics.push(json!({
+                "name": format!("{}.histogram", series.name),
+                "unit": series.unit,
+                "histogram": {
+                    "aggregationTemporality": AGGREGATION_TEMPORALITY_DELTA,
+                    "dataPoints": [{
+                        "attributes": attributes,
+                        "startTimeUnixNano": start_nanos.to_string(),
+                        "timeUnixNano": now_nanos.to_string(),
+                        "count": aggregate.count.to_string(),
+                        "sum": aggregate.sum,
+                        "min": aggregate.min,
+                        "max": aggregate.max,
+                        "bucketCounts": [aggregate.count.to_string()],
+                        "explicitBounds": [],
+                    }],
+                },
+            }));
+        }
+        Some(json!({
+            "resourceMetrics": [{
+                "resource": { "attributes": attributes(resource) },
+                "scopeMetrics": [{
+                    "scope": { "name": env!("CARGO_PKG_NAME"), "version": env!("CARGO_PKG_VERSION") },
+                    "metrics": metrics,
+                }],
+            }],
+        }))
+    }
+}
+
+/**
+ * Encodes attributes as OTLP key values.
+ *
+ * # Arguments
+ * `attributes` - The attribute names and values.
+ *
+ * # Returns
+ * The JSON array of key values.
+ */
+fn attributes(attributes: &[(String, String)]) -> Value {
+    Value::Array(
+        attributes
+            .iter()
+            .map(|(key, value)| json!({ "key": key, "value": { "stringValue": value } }))
+            .collect(),
+    )
+}
+
+/**
+ * Returns the current time as nanoseconds since the Unix epoch.
+ *
+ * # Returns
+ * The nanoseconds.
+ */
+fn unix_nanos() -> u128 {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_nanos()
+}
+
+/**
+ * Converts a reading to a sample. Only readings with a typed measurement are exported; the
+ * metric is named after the quantity and the device, channel and DUT id fields of the reading
+ * become attributes.
+ *
+ * # Arguments
+ * `reading` - The reading.
+ * `device` - The device attribute used when the reading has no device field.
+ * `time_nanos` - The time of the reading.
+ *
+ * # Returns
+ * A Result containing the sample, None if the reading has no measurement, or an ApplicationError.
+ */
+fn sample(reading: &dyn Reading, device: &str, time_nanos: u128) -> Result<Option<Sample>, ApplicationError> {
+    let Some(measurement) = reading.get_measurement() else {
+        return Ok(None);
+    };
+    let mut attributes: Vec<(String, String)> = reading
+        .get_csv_header()?
+        .into_iter()
+        .zip(reading.get_csv()?)
+        .filter(|(name, value)| ATTRIBUTE_FIELDS.contains(&name.as_str()) && !value.is_empty())
+        .collect();
+    if !attributes.iter().any(|(name, _)| name == "device") {
+        attributes.push(("device".into(), device.to_string()));
+    }
+    attributes.sort();
+    Ok(Some(Sample {
+        series: Series {
+            name: format!("{}.{}", METRIC_PREFIX, measurement.quantity),
+            unit: measurement.unit().to_string(),
+            attributes,
+        },
+        value: measurement.value,
+        time_nanos,
+    }))
+}
+
+/**
+ * Exports readings as OpenTelemetry metrics to an OTLP/HTTP collector. Readings are queued for
+ * a background task, which aggregates them and exports every interval. Exports the collector cannot
+ * take are kept in a backlog with their original timestamps and backfilled in order before
+ * newer exports, as metric backends reject out-of-order samples.
+ */
+pub struct OtlpExporter {
+    /**
+     * The device attribute of readings without a device field.
+     */
+    device: String,
+    samples: Arc<SinkQueue<Sample>>,
+    task: JoinHandle<()>,
+}
+
+impl OtlpExporter {
+    /**
+     * Starts the export task.
+     *
+     * # Arguments
+     * `endpoint` - The collector.
+     * `interval` - The time between exports.
+     * `service_name` - The service.name resource attribute.
+     * `device` - The device attribute of readings without a device field.
+     * `backlog` - The backlog of exports the collector did not take, in order.
+     * `queue` - Number of readings queued for the export task before the overflow policy applies.
+     * `overflow` - What happens to new readings when the queue is full.
+     *
+     * # Returns
+     * A new OtlpExporter instance.
+     */
+    pub fn start(
+        endpoint: OtlpEndpoint,
+        interval: Duration,
+        service_name: &str,
+        device: &str,
+        backlog: Backlog,
+        queue: usize,
+        overflow: SinkOverflow,
+    ) -> Self {
+        let resource = vec![("service.name".to_string(), service_name.to_string())];
+        let samples = Arc::new(SinkQueue::new(queue, overflow, None));
+        let task = tokio::spawn(run(endpoint, interval, resource, samples.clone(), backlog));
+        Self {
+            device: device.to_string(),
+            samples,
+            task,
+        }
+    }
+
+    /**
+     * Queues the measurement of a reading for the next export. With SinkOverflow::Block this
+     * waits while the queue is full, e.g. during a slow export.
+     *
+     * # Arguments
+     * `reading` - The reading; readings without a typed measurement are ignored.
+     *
+     * # Returns
+     * A Result indicating success or an ApplicationError if the reading fields cannot be read.
+     */
+    pub async fn record(&self, reading: &dyn Reading) -> Result<(), ApplicationError> {
+        if let Some(sample) = sample(reading, &self.device, unix_nanos())? {
+            self.samples.push(sample).await;
+        }
+        Ok(())
+    }
+
+    /**
+     * Returns the number of readings dropped because the queue was full.
+     *
+     * # Returns
+     * The number of dropped readings.
+     */
+    pub fn dropped(&self) -> u64 {
+        self.samples.dropped()
+    }
+
+    /**
+     * Exports the remaining values and stops the export task. Exports still in a persisted
+     * backlog are backfilled by the next run.
+     */
+    pub async fn finish(self) {
+        self.samples.close();
+        let _ = tokio::time::timeout(EXPORT_TIMEOUT, self.task).await;
+    }
+}
+
+/**
+ * Sends an export, or queues it behind the backlog if the backlog is in order, and backfills
+ * the backlog, oldest first, until the collector fails.
+ *
+ * # Arguments
+ * `endpoint` - The collector.
+ * `backlog` - The exports waiting for the collector.
+ * `request` - The new export, if any.
+ */
+async fn export(endpoint: &OtlpEndpoint, backlog: &mut Backlog, request: Option<Vec<u8>>) {
+    if let Some(request) = request {
+        if backlog.must_wait() {
+            if let Err(e) = backlog.push(request) {
+                warn!("{}", e);
+            }
+        } else if let Err(e) = endpoint.post(&request).await {
+            warn!("{}, buffering", e);
+            if let Err(e) = backlog.push(request) {
+                warn!("{}", e);
+            }
+            return;
+        }
+    }
+    while let Some(request) = backlog.front() {
+        if let Err(e) = endpoint.post(request).await {
+            warn!("{}, {} exports buffered", e, backlog.len());
+            return;
+        }
+        backlog.delivered();
+    }
+}
+
+/**
+ * Export task: aggregates samples and exports them every interval. Returns after a last
+ * export once the OtlpExporter is finished.
+ *
+ * # Arguments
+ * `endpoint` - The collector.
+ * `interval` - The time between exports.
+ * `resource` - The resource attributes.
+ * `samples` - The queue of the recorded samples.
+ * `backlog` - The exports waiting for the collector.
+ */
+async fn run(
+    endpoint: OtlpEndpoint,
+    interval: Duration,
+    resource: Vec<(String, String)>,
+    samples: Arc<SinkQueue<Sample>>,
+    mut backlog: Backlog,
+) {
+    let mut aggregator = Aggregator {
+        start_nanos: unix_nanos(),
+        ..Default::default()
+    };
+    let mut ticks = tokio::time::interval(interval);
+    ticks.tick().await;
+    loop {
+        let closed = tokio::select! {
+            sample = samples.take() => match sample {
+                Some(sample) => {
+                    aggregator.record(sample);
+                    continue;
+                }
+                None => true,
+            },
+            _ = ticks.tick() => false,
+        };
+        let request = aggregator.take(&resource, unix_nanos());
+        export(&endpoint, &mut backlog, request.map(|request| request.to_string().into_bytes())).await;
+        if closed {
+            return;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::instruments::reading::{ScpiRawReading, TaggedReading, Ut71Reading};
+    use crate::output::backlog::Ordering;
+    use tokio::net::TcpListener;
+
+    fn voltage_reading() -> Box<dyn Reading> {
+        let reading = Ut71Reading::parse(b"1234502\x36\x30\r\n").unwrap();
+        Box::new(TaggedReading::new(vec![("channel".into(), "3".into())], Box::new(reading)))
+    }
+
+    #[test]
+    fn test_parse_endpoint() {
+        let endpoint = OtlpEndpoint::parse("http://collector").unwrap();
+        assert_eq!(endpoint.host, "collector:4318");
+        assert_eq!(endpoint.path, "/v1/metrics");
+        let endpoint = OtlpEndpoint::parse("http://collector:9000/otlp/v1/metrics/").unwrap();
+        assert_eq!(endpoint.host, "collector:9000");
+        assert_eq!(endpoint.path, "/otlp/v1/metrics");
+        assert!(OtlpEndpoint::parse("https://collector").is_err());
+        assert!(OtlpEndpoint::parse("http:///v1/metrics").is_err());
+    }
+
+    #[test]
+    fn test_sample() {
+        let reading = voltage_reading();
+        let sample = sample(reading.as_ref(), "ut71-serial", 5).unwrap().unwrap();
+        assert_eq!(sample.series.name, "hardware_measurement.voltage");
+        assert_eq!(sample.series.unit, "V");
+        assert_eq!(
+            sample.series.attributes,
+            vec![("channel".to_string(), "3".to_string()), ("device".to_string(), "ut71-serial".to_string())]
+        );
+        let raw = ScpiRawReading::new(b"1.0".to_vec());
+        assert!(super::sample(&raw, "ut71-serial", 5).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_aggregate() {
+        let series = Series {
+            name: "hardware_measurement.voltage".into(),
+            unit: "V".into(),
+            attributes: vec![("device".into(), "psu".into())],
+        };
+        let mut aggregator = Aggregator {
+            start_nanos: 100,
+            ..Default::default()
+        };
+        for (value, time_nanos) in [(2.0, 150), (1.0, 160), (4.0, 170)] {
+            aggregator.record(Sample { series: series.clone(), value, time_nanos });
+        }
+        let resource = vec![("service.name".to_string(), "bench".to_string())];
+        let request = aggregator.take(&resource, 200).unwrap();
+        let metrics = &request["resourceMetrics"][0]["scopeMetrics"][0]["metrics"];
+        assert_eq!(metrics[0]["gauge"]["dataPoints"][0]["asDouble"], 4.0);
+        assert_eq!(metrics[0]["gauge"]["dataPoints"][0]["timeUnixNano"], "170");
+        let histogram = &metrics[1]["histogram"]["dataPoints"][0];
+        assert_eq!(metrics[1]["name"], "hardware_measurement.voltage.histogram");
+        assert_eq!(histogram["count"], "3");
+        assert_eq!(histogram["sum"], 7.0);
+        assert_eq!(histogram["min"], 1.0);
+        assert_eq!(histogram["startTimeUnixNano"], "100");
+        assert_eq!(histogram["attributes"][0]["value"]["stringValue"], "psu");
+        assert!(aggregator.take(&resource, 300).is_none());
+        assert_eq!(aggregator.start_nanos, 300);
+    }
+
+    #[tokio::test]
+    async fn test_export() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let endpoint = OtlpEndpoint::parse(&format!("http://{}", listener.local_addr().unwrap())).unwrap();
+        let server = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut request = Vec::new();
+            let mut buffer = [0u8; 4096];
+            loop {
+                let length = stream.read(&mut buffer).await.unwrap();
+                request.extend_from_slice(&buffer[..length]);
+                let text = String::from_utf8_lossy(&request);
+                if length == 0 || (text.contains("\r\n\r\n") && text.ends_with('}')) {
+                    break;
+                }
+            }
+            stream.write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\n\r\n").await.unwrap();
+            String::from_utf8(request).unwrap()
+        });
+        let backlog = Backlog::open(None, 10, Ordering::InOrder).unwrap();
+        let exporter = OtlpExporter::start(
+            endpoint,
+            Duration::from_secs(3600),
+            "bench",
+            "ut71-serial",
+            backlog,
+            10,
+            SinkOverflow::DropOldest,
+        );
+        exporter.record(voltage_reading().as_ref()).await.unwrap();
+        assert_eq!(exporter.dropped(), 0);
+        exporter.finish().await;
+        let request = server.await.unwrap();
+        assert!(request.starts_with("POST /v1/metrics HTTP/1.1\r\n"));
+        assert!(request.contains("Content-Type: application/json"));
+        assert!(request.contains("\"hardware_measurement.voltage\""));
+    }
+
+    #[tokio::test]
+    async fn test_backfill_in_order() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        drop(listener);
+        let endpoint = OtlpEndpoint::parse(&format!("http://{}", address)).unwrap();
+        let mut backlog = Backlog::open(None, 10, Ordering::InOrder).unwrap();
+        export(&endpoint, &mut backlog, Some(b"{\"first\":1}".to_vec())).await;
+        export(&endpoint, &mut backlog, Some(b"{\"second\":2}".to_vec())).await;
+        assert_eq!(backlog.len(), 2);
+        let listener = TcpListener::bind(address).await.unwrap();
+        let server = tokio::spawn(async move {
+            let mut bodies = Vec::new();
+            for _ in 0..3 {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let mut request = Vec::new();
+                let mut buffer = [0u8; 4096];
+                while !String::from_utf8_lossy(&request).ends_with('}') {
+                    let length = stream.read(&mut buffer).await.unwrap();
+                    request.extend_from_slice(&buffer[..length]);
+                }
+                stream.write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\n\r\n").await.unwrap();
+                let request = String::from_utf8(request).unwrap();
+                bodies.push(request.split("\r\n\r\n").nth(1).unwrap().to_string());
+            }
+            bodies
+        });
+        export(&endpoint, &mut backlog, Some(b"{\"third\":3}".to_vec())).await;
+        assert!(backlog.is_empty());
+        assert_eq!(server.await.unwrap(), ["{\"first\":1}", "{\"second\":2}", "{\"third\":3}"]);
+    }
+}
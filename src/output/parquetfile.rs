@@ -0,0 +1,282 @@
+use std::{
+    io::{self, Write},
+    mem,
+    sync::{Arc, Mutex},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use parquet::{
+    basic::{Compression, ZstdLevel},
+    data_type::{ByteArray, ByteArrayType, DoubleType, Int64Type},
+    errors::ParquetError,
+    file::{properties::WriterProperties, writer::SerializedFileWriter},
+    schema::parser::parse_message_type,
+};
+
+use crate::{error::ApplicationError, instruments::reading::Reading};
+
+/**
+ * Columns of the Parquet output, one row per reading with a typed measurement.
+ */
+const SCHEMA: &str = "
+message reading {
+    required int64 timestamp (TIMESTAMP(MICROS,true));
+    optional binary device (STRING);
+    optional binary mode (STRING);
+    required double value;
+    required binary unit (STRING);
+    optional binary flags (STRING);
+}
+";
+
+/**
+ * Number of rows buffered before they are written as a row group.
+ */
+const ROW_GROUP_ROWS: usize = 10000;
+
+/**
+ * Buffer the Parquet writer writes to; the bytes are moved to the output after every row group.
+ * The writer needs a Send target, which the output is not. The writer keeps up to 8 KiB in its own
+ * buffer, which is written with the next row group or the footer.
+ */
+#[derive(Clone, Default)]
+struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+impl SharedBuffer {
+    /**
+     * Takes the bytes written so far.
+     *
+     * # Returns
+     * The bytes.
+     */
+    fn take(&self) -> Vec<u8> {
+        mem::take(&mut *self.0.lock().unwrap_or_else(|poisoned| poisoned.into_inner()))
+    }
+}
+
+impl Write for SharedBuffer {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).extend_from_slice(data);
+        Ok(data.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/**
+ * Rows buffered for the next row group. Optional columns keep their definition levels, 1 for a
+ * value and 0 for null, and the values that are present.
+ */
+#[derive(Default)]
+struct RowGroup {
+    timestamps: Vec<i64>,
+    devices: (Vec<i16>, Vec<ByteArray>),
+    modes: (Vec<i16>, Vec<ByteArray>),
+    values: Vec<f64>,
+    units: Vec<ByteArray>,
+    flags: (Vec<i16>, Vec<ByteArray>),
+}
+
+/**
+ * Adds a value to an optional column.
+ *
+ * # Arguments
+ * `column` - The definition levels and values of the column.
+ * `value` - The value, None for null.
+ */
+fn push_optional(column: &mut (Vec<i16>, Vec<ByteArray>), value: Option<String>) {
+    match value {
+        Some(value) => {
+            column.0.push(1);
+            column.1.push(ByteArray::from(value.as_str()));
+        }
+        None => column.0.push(0),
+    }
+}
+
+/**
+ * Maps a Parquet error to an ApplicationError.
+ *
+ * # Arguments
+ * `error` - The Parquet error.
+ *
+ * # Returns
+ * The ApplicationError.
+ */
+fn parquet_error(error: ParquetError) -> ApplicationError {
+    ApplicationError::General(format!("Could not write Parquet output: {}", error))
+}
+
+/**
+ * Writes readings as an Apache Parquet file with the columns timestamp, device, mode, value,
+ * unit and flags, compressed with zstd. Rows are written in row groups of ROW_GROUP_ROWS as
+ * they come in; the file is complete when it is closed. Readings without a typed measurement,
+ * e.g. events, are left out.
+ */
+#[derive(Default)]
+pub struct ParquetWriter {
+    writer: Option<SerializedFileWriter<SharedBuffer>>,
+    buffer: SharedBuffer,
+    rows: RowGroup,
+}
+
+impl ParquetWriter {
+    /**
+     * Creates a new ParquetWriter.
+     *
+     * # Returns
+     * A new ParquetWriter instance.
+     */
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /**
+     * Adds the measurement of a reading as a row, writing a row group when enough rows are
+     * buffered.
+     *
+     * # Arguments
+     * `reading` - The reading.
+     *
+     * # Returns
+     * A Result containing the bytes of the file to write to the output, empty until a row group
+     * is complete, or an ApplicationError.
+     */
+    pub fn write_reading(&mut self, reading: &dyn Reading) -> Result<Vec<u8>, ApplicationError> {
+        let Some(measurement) = reading.get_measurement() else {
+            return Ok(Vec::new());
+        };
+        let header = reading.get_csv_header()?;
+        let fields = reading.get_csv()?;
+        let field = |name: &str| {
+            header
+                .iter()
+                .zip(fields.iter())
+                .find(|(field, value)| field.as_str() == name && !value.is_empty())
+                .map(|(_, value)| value.clone())
+        };
+        let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_micros() as i64;
+        self.rows.timestamps.push(timestamp);
+        push_optional(&mut self.rows.devices, field("device"));
+        push_optional(&mut self.rows.modes, field("mode").or_else(|| Some(measurement.quantity.to_string())));
+        self.rows.values.push(measurement.display_value());
+        self.rows.units.push(ByteArray::from(measurement.display_unit.as_str()));
+        push_optional(&mut self.rows.flags, Some(measurement.flags.join(",")).filter(|flags| !flags.is_empty()));
+        if self.rows.timestamps.len() < ROW_GROUP_ROWS {
+            return Ok(Vec::new());
+        }
+        self.write_row_group()?;
+        Ok(self.buffer.take())
+    }
+
+    /**
+     * Writes the buffered rows as a row group, starting the file first if needed.
+     *
+     * # Returns
+     * A Result indicating success or an ApplicationError.
+     */
+    fn write_row_group(&mut self) -> Result<(), ApplicationError> {
+        let rows = mem::take(&mut self.rows);
+        if rows.timestamps.is_empty() {
+            return Ok(());
+        }
+        let writer = match &mut self.writer {
+            Some(writer) => writer,
+            None => {
+                let schema = Arc::new(parse_message_type(SCHEMA).map_err(parquet_error)?);
+                let properties = WriterProperties::builder()
+                    .set_compression(Compression::ZSTD(ZstdLevel::default()))
+                    .build();
+                let writer = SerializedFileWriter::new(self.buffer.clone(), schema, Arc::new(properties)).map_err(parquet_error)?;
+                self.writer.insert(writer)
+            }
+        };
+        let mut row_group = writer.next_row_group().map_err(parquet_error)?;
+        let mut index = 0;
+        while let Some(mut column) = row_group.next_column().map_err(parquet_error)? {
+            // The columns in the order of SCHEMA
+            let written = match index {
+                0 => column.typed::<Int64Type>().write_batch(&rows.timestamps, None, None),
+                1 => column.typed::<ByteArrayType>().write_batch(&rows.devices.1, Some(&rows.devices.0), None),
+                2 => column.typed::<ByteArrayType>().write_batch(&rows.modes.1, Some(&rows.modes.0), None),
+                3 => column.typed::<DoubleType>().write_batch(&rows.values, None, None),
+                4 => column.typed::<ByteArrayType>().write_batch(&rows.units, None, None),
+                _ => column.typed::<ByteArrayType>().write_batch(&rows.flags.1, Some(&rows.flags.0), None),
+            };
+            written.map_err(parquet_error)?;
+            column.close().map_err(parquet_error)?;
+            index += 1;
+        }
+        row_group.close().map(|_| ()).map_err(parquet_error)
+    }
+
+    /**
+     * Writes the buffered rows and the footer, completing the file. The next reading starts a
+     * new file, e.g. in the next output segment.
+     *
+     * # Returns
+     * A Result containing the remaining bytes of the file, empty if no reading was written, or
+     * an ApplicationError.
+     */
+    pub fn close(&mut self) -> Result<Vec<u8>, ApplicationError> {
+        self.write_row_group()?;
+        if let Some(writer) = self.writer.take() {
+            writer.close().map_err(parquet_error)?;
+        }
+        Ok(self.buffer.take())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs::{self, File};
+
+    use parquet::{
+        file::reader::{FileReader, SerializedFileReader},
+        record::Field,
+    };
+
+    use super::*;
+    use crate::instruments::{
+        command::DmmFunction,
+        reading::{EventKind, EventReading, KeysightDmmReading, TaggedReading},
+    };
+
+    fn volts(value: &str) -> Box<dyn Reading> {
+        Box::new(KeysightDmmReading::parse(DmmFunction::VoltageDc, value.as_bytes()).unwrap().remove(0))
+    }
+
+    #[test]
+    fn test_write_rows() {
+        let mut writer = ParquetWriter::new();
+        let mut file = Vec::new();
+        file.extend(writer.write_reading(volts("1.5").as_ref()).unwrap());
+        let tagged = TaggedReading::new(vec![("device".into(), "dmm".into())], volts("0.0025"));
+        file.extend(writer.write_reading(&tagged).unwrap());
+        file.extend(writer.write_reading(&EventReading::new(EventKind::Retry, "x".into())).unwrap());
+        assert!(file.is_empty());
+        file.extend(writer.close().unwrap());
+        assert!(file.starts_with(b"PAR1") && file.ends_with(b"PAR1"));
+
+        let path = std::env::temp_dir().join(format!("parquet-{}.parquet", std::process::id()));
+        fs::write(&path, &file).unwrap();
+        let reader = SerializedFileReader::new(File::open(&path).unwrap()).unwrap();
+        assert_eq!(reader.metadata().file_metadata().num_rows(), 2);
+        let rows: Vec<Vec<(String, Field)>> = reader
+            .get_row_iter(None)
+            .unwrap()
+            .map(|row| row.unwrap().get_column_iter().map(|(name, field)| (name.clone(), field.clone())).collect())
+            .collect();
+        assert!(matches!(rows[0][0].1, Field::TimestampMicros(_)));
+        assert_eq!(rows[0][1].1, Field::Null);
+        assert_eq!(rows[0][2].1, Field::Str("voltage".into()));
+        assert_eq!(rows[0][3].1, Field::Double(1.5));
+        assert_eq!(rows[0][4].1, Field::Str("V".into()));
+        assert_eq!(rows[1][1].1, Field::Str("dmm".into()));
+        assert_eq!(rows[1][5], ("flags".to_string(), Field::Null));
+        assert!(writer.close().unwrap().is_empty());
+        fs::remove_file(path).unwrap();
+    }
+}
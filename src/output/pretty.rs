@@ -0,0 +1,67 @@
+use crate::{
+    arguments::Notation,
+    error::ApplicationError,
+    instruments::reading::Reading,
+    output::{color, locale::Translator, notation},
+};
+
+/**
+ * Formats a reading as a human readable line of `name: value` pairs with translated field
+ * names and modes. Empty and false fields are left out and true fields are shown by name
+ * only. Readings without CSV support fall back to the raw string. With color, values are bold,
+ * units dimmed and readings with overload or a hardware warning red, held readings yellow.
+ *
+ * # Arguments
+ * `reading` - The reading to format.
+ * `translator` - Translates mode, flag and field names.
+ * `notation` - The notation of non-integer numbers.
+ * `colored` - Whether ANSI colors are added.
+ *
+ * # Returns
+ * A Result containing the line or an ApplicationError.
+ */
+pub fn format_reading(
+    reading: &dyn Reading,
+    translator: &Translator,
+    notation: Notation,
+    colored: bool,
+) -> Result<String, ApplicationError> {
+    let (Ok(header), Ok(fields)) = (reading.get_csv_header(), reading.get_csv()) else {
+        return reading.get_raw_string();
+    };
+    let fields = notation::format_fields(&header, fields, notation);
+    let style = |name: &str, value: &str| match colored {
+        true => color::style_value(name, value),
+        false => value.to_string(),
+    };
+    let pairs: Vec<String> = header
+        .iter()
+        .zip(fields.iter())
+        .filter(|(_, value)| !value.is_empty() && value.as_str() != "false")
+        .map(|(name, value)| match value.as_str() {
+            "true" => translator.translate(name).to_string(),
+            _ if name == "mode" => format!("{}: {}", translator.translate(name), translator.translate(value)),
+            _ => format!("{}: {}", translator.translate(name), style(name, value)),
+        })
+        .collect();
+    let line = pairs.join(", ");
+    Ok(match colored {
+        true => color::highlight(line, &header, &fields),
+        false => line,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{arguments::Language, instruments::reading::KoradReading};
+
+    #[test]
+    fn test_format_reading() {
+        let reading = KoradReading::measured(b"05.00", b"0.100", &[0x41]);
+        assert_eq!(
+            format_reading(&reading, &Translator::new(Language::De), Notation::Plain, false).unwrap(),
+            "Werte: output, Spannung: 5, Strom: 0.1, Status: 65, Modus: Konstantspannung, Ausgang ein"
+        );
+    }
+}
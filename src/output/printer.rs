@@ -0,0 +1,210 @@
+use std::io::{self, Write};
+
+use crate::{
+    arguments::{Format, Notation},
+    error::ApplicationError,
+    instruments::reading::Reading,
+    output::{
+        hexdump, json, keyvalue, locale::Translator, parquetfile::ParquetWriter, pretty, rotation::Rotation, sink, vcd::VcdWriter,
+        wav, CsvWriter,
+    },
+};
+
+/**
+ * Prints readings to stdout or an output file in the selected output format.
+ */
+pub struct Printer {
+    /**
+     * Output format.
+     */
+    format: Format,
+    /**
+     * Writer used for the CSV and tidy formats.
+     */
+    csv_writer: CsvWriter,
+    /**
+     * Writer used for the VCD format.
+     */
+    vcd_writer: VcdWriter,
+    /**
+     * Writer used for the Parquet format.
+     */
+    parquet_writer: ParquetWriter,
+    /**
+     * Translator used for the pretty format.
+     */
+    translator: Translator,
+    /**
+     * Notation of non-integer numbers in the JSON, pretty and key-value formats.
+     */
+    notation: Notation,
+    /**
+     * Whether the pretty format is written with ANSI colors.
+     */
+    colored: bool,
+    /**
+     * Stdout or the output file.
+     */
+    sink: Box<dyn Write>,
+    /**
+     * Whether a WAV file has been written to the sink, which holds a single one.
+     */
+    wav_written: bool,
+    /**
+     * Rotation of the output file into segments, if enabled.
+     */
+    rotation: Option<Rotation>,
+}
+
+impl Printer {
+    /**
+     * Creates a new Printer.
+     *
+     * # Arguments
+     * `format` - The output format.
+     * `csv_writer` - The writer used for the CSV and tidy formats.
+     * `translator` - The translator used for the pretty format.
+     * `notation` - The notation of non-integer numbers in the JSON, pretty and key-value formats.
+     * `colored` - Whether the pretty format is written with ANSI colors.
+     * `sink` - Stdout or the output file.
+     * `rotation` - The rotation of the output file, if enabled. The sink is its first segment.
+     *
+     * # Returns
+     * A new Printer instance.
+     */
+    pub fn new(
+        format: Format,
+        csv_writer: CsvWriter,
+        translator: Translator,
+        notation: Notation,
+        colored: bool,
+        sink: Box<dyn Write>,
+        rotation: Option<Rotation>,
+    ) -> Self {
+        Self {
+            format,
+            csv_writer,
+            vcd_writer: VcdWriter::new(),
+            parquet_writer: ParquetWriter::new(),
+            translator,
+            notation,
+            colored,
+            sink,
+            wav_written: false,
+            rotation,
+        }
+    }
+
+    /**
+     * Prints a reading.
+     *
+     * # Arguments
+     * `reading` - The reading to print.
+     */
+    pub fn print(&mut self, reading: &dyn Reading) -> Result<(), ApplicationError> {
+        if self.rotation.as_ref().is_some_and(Rotation::is_due) {
+            // Every segment is a complete Parquet file
+            let footer = self.parquet_writer.close()?;
+            self.write_bytes(&footer)?;
+        }
+        if let Some(rotation) = self.rotation.as_mut().filter(|rotation| rotation.is_due()) {
+            let sink = std::mem::replace(&mut self.sink, Box::new(io::sink()));
+            self.sink = rotation.rotate(sink)?;
+            self.csv_writer.restart();
+            self.vcd_writer.restart();
+            self.wav_written = false;
+        }
+        let line = match self.format {
+            Format::Csv => self.csv_writer.format_reading(reading)?,
+            Format::Json => json::format_reading(reading, self.notation)?,
+            Format::Raw => format!("{:?}", reading.get_raw()?),
+            Format::RawString => format!("{:?}", reading.get_raw_string()?),
+            Format::Hex => {
+                let dump = hexdump::format_reading(reading)?;
+                if dump.is_empty() {
+                    return Ok(());
+                }
+                dump
+            }
+            Format::Tidy => {
+                let records = self.csv_writer.format_tidy_reading(reading)?;
+                if records.is_empty() {
+                    return Ok(());
+                }
+                records
+            }
+            Format::Pretty => pretty::format_reading(reading, &self.translator, self.notation, self.colored)?,
+            Format::KeyValue => keyvalue::format_reading(reading, self.notation)?,
+            Format::WaveformCsv => self.csv_writer.format_samples(reading)?,
+            Format::Wav => return self.write_wav(reading),
+            Format::Parquet => {
+                let bytes = self.parquet_writer.write_reading(reading)?;
+                return self.write_bytes(&bytes);
+            }
+            Format::Vcd => {
+                let changes = self.vcd_writer.format_reading(reading)?;
+                if changes.is_empty() {
+                    return Ok(());
+                }
+                changes
+            }
+        };
+        sink::write_line(self.sink.as_mut(), &line)
+    }
+
+    /**
+     * Writes the samples of a waveform reading as WAV file.
+     *
+     * # Arguments
+     * `reading` - The waveform reading.
+     *
+     * # Returns
+     * A Result indicating success, or an ApplicationError if the reading is not a waveform or a
+     * WAV file has already been written.
+     */
+    fn write_wav(&mut self, reading: &dyn Reading) -> Result<(), ApplicationError> {
+        let samples = reading
+            .get_samples()
+            .ok_or_else(|| ApplicationError::General("The WAV format needs waveform readings".into()))?;
+        if self.wav_written {
+            return Err(ApplicationError::General("The WAV output holds a single waveform".into()));
+        }
+        self.wav_written = true;
+        self.write_bytes(&wav::encode(&samples)?)
+    }
+
+    /**
+     * Writes binary output, e.g. a WAV file or Parquet row groups, to the sink.
+     *
+     * # Arguments
+     * `bytes` - The bytes, nothing is written if empty.
+     *
+     * # Returns
+     * A Result indicating success or an ApplicationError.
+     */
+    fn write_bytes(&mut self, bytes: &[u8]) -> Result<(), ApplicationError> {
+        if bytes.is_empty() {
+            return Ok(());
+        }
+        self.sink
+            .write_all(bytes)
+            .and_then(|_| self.sink.flush())
+            .map_err(|e| ApplicationError::Io("Could not write output".into(), e))
+    }
+
+    /**
+     * Closes the output. The Parquet footer is written, the last segment of a rotated output is
+     * handed to the uploader and the pending uploads are awaited.
+     *
+     * # Returns
+     * A Result indicating success or an ApplicationError.
+     */
+    pub async fn finish(mut self) -> Result<(), ApplicationError> {
+        let footer = self.parquet_writer.close()?;
+        self.write_bytes(&footer)?;
+        match self.rotation {
+            Some(rotation) => rotation.finish(self.sink).await,
+            None => Ok(()),
+        }
+    }
+}
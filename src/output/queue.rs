@@ -0,0 +1,249 @@
+use std::{
+    collections::VecDeque,
+    sync::{Mutex, MutexGuard},
+    time::Duration,
+};
+
+use tokio::{sync::Notify, time::Instant};
+
+use crate::arguments::SinkOverflow;
+
+/**
+ * Items waiting for the consumer and the counters of the queue.
+ */
+struct QueueState<T> {
+    items: VecDeque<T>,
+    dropped: u64,
+    closed: bool,
+    /**
+     * Earliest time the next item is taken when the rate is limited.
+     */
+    next_take: Option<Instant>,
+}
+
+/**
+ * Bounded queue between the acquisition loop and the task feeding a streaming sink, so a
+ * stalled consumer does not hold up acquisition. When the queue is full the overflow policy
+ * drops the oldest item, keeps only the newest, or makes the producer wait for room. Dropped
+ * items are counted for the report at shutdown.
+ */
+pub struct SinkQueue<T> {
+    state: Mutex<QueueState<T>>,
+    capacity: usize,
+    overflow: SinkOverflow,
+    /**
+     * Shortest time between two items taken, None for no rate limit.
+     */
+    interval: Option<Duration>,
+    pushed: Notify,
+    taken: Notify,
+}
+
+impl<T> SinkQueue<T> {
+    /**
+     * Creates a new SinkQueue.
+     *
+     * # Arguments
+     * `capacity` - Largest number of items queued; at least one item is queued.
+     * `overflow` - What happens to new items when the queue is full.
+     * `max_rate` - Largest number of items taken per second, None for no limit.
+     *
+     * # Returns
+     * A new SinkQueue instance.
+     */
+    pub fn new(capacity: usize, overflow: SinkOverflow, max_rate: Option<f64>) -> Self {
+        Self {
+            state: Mutex::new(QueueState {
+                items: VecDeque::new(),
+                dropped: 0,
+                closed: false,
+                next_take: None,
+            }),
+            capacity: capacity.max(1),
+            overflow,
+            interval: max_rate.filter(|rate| *rate > 0.0).map(|rate| Duration::from_secs_f64(1.0 / rate)),
+            pushed: Notify::new(),
+            taken: Notify::new(),
+        }
+    }
+
+    /**
+     * Locks the state, also after a panic of another holder.
+     *
+     * # Returns
+     * The guard of the state.
+     */
+    fn lock(&self) -> MutexGuard<'_, QueueState<T>> {
+        self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /**
+     * Queues an item, applying the overflow policy if the queue is full. With
+     * SinkOverflow::Block this waits until the consumer has taken an item. Items pushed after
+     * the queue is closed are discarded.
+     *
+     * # Arguments
+     * `item` - The item.
+     */
+    pub async fn push(&self, item: T) {
+        loop {
+            // Registered before the state is checked so an item taken in between wakes us
+            let taken = self.taken.notified();
+            {
+                let mut state = self.lock();
+                if state.closed {
+                    return;
+                }
+                if state.items.len() >= self.capacity {
+                    match self.overflow {
+                        SinkOverflow::DropOldest => {
+                            state.items.pop_front();
+                            state.dropped += 1;
+                        }
+                        SinkOverflow::KeepLatest => {
+                            state.dropped += state.items.len() as u64;
+                            state.items.clear();
+                        }
+                        SinkOverflow::Block => {}
+                    }
+                }
+                if state.items.len() < self.capacity {
+                    state.items.push_back(item);
+                    drop(state);
+                    self.pushed.notify_waiters();
+                    return;
+                }
+            }
+            taken.await;
+        }
+    }
+
+    /**
+     * Takes the oldest item, waiting for one to be pushed and, with a rate limit, until the
+     * interval since the previous item has passed. Cancelling the wait loses no item.
+     *
+     * # Returns
+     * The item, or None once the queue is closed and empty.
+     */
+    pub async fn take(&self) -> Option<T> {
+        let next_take = self.lock().next_take;
+        if let Some(next_take) = next_take {
+            tokio::time::sleep_until(next_take).await;
+        }
+        loop {
+            let pushed = self.pushed.notified();
+            {
+                let mut state = self.lock();
+                if let Some(item) = state.items.pop_front() {
+                    state.next_take = self.interval.map(|interval| Instant::now() + interval);
+                    drop(state);
+                    self.taken.notify_waiters();
+                    return Some(item);
+                }
+                if state.closed {
+                    return None;
+                }
+            }
+            pushed.await;
+        }
+    }
+
+    /**
+     * Closes the queue: the consumer takes the remaining items and then gets None, and
+     * producers waiting for room return.
+     */
+    pub fn close(&self) {
+        self.lock().closed = true;
+        self.pushed.notify_waiters();
+        self.taken.notify_waiters();
+    }
+
+    /**
+     * Returns the number of items dropped by the overflow policy.
+     *
+     * # Returns
+     * The number of dropped items.
+     */
+    pub fn dropped(&self) -> u64 {
+        self.lock().dropped
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use super::*;
+
+    async fn drain(queue: &SinkQueue<u32>) -> Vec<u32> {
+        queue.close();
+        let mut items = Vec::new();
+        while let Some(item) = queue.take().await {
+            items.push(item);
+        }
+        items
+    }
+
+    #[tokio::test]
+    async fn test_drop_oldest() {
+        let queue = SinkQueue::new(3, SinkOverflow::DropOldest, None);
+        for item in 1..=5 {
+            queue.push(item).await;
+        }
+        assert_eq!(queue.dropped(), 2);
+        assert_eq!(drain(&queue).await, vec![3, 4, 5]);
+        queue.push(6).await;
+        assert!(queue.take().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_keep_latest() {
+        let queue = SinkQueue::new(3, SinkOverflow::KeepLatest, None);
+        for item in 1..=5 {
+            queue.push(item).await;
+        }
+        assert_eq!(queue.dropped(), 3);
+        assert_eq!(drain(&queue).await, vec![4, 5]);
+    }
+
+    #[tokio::test]
+    async fn test_block() {
+        let queue = Arc::new(SinkQueue::new(1, SinkOverflow::Block, None));
+        queue.push(1).await;
+        let producer = tokio::spawn({
+            let queue = queue.clone();
+            async move { queue.push(2).await }
+        });
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert!(!producer.is_finished());
+        assert_eq!(queue.take().await, Some(1));
+        producer.await.unwrap();
+        assert_eq!(queue.dropped(), 0);
+        assert_eq!(drain(&queue).await, vec![2]);
+    }
+
+    #[tokio::test]
+    async fn test_close_releases_blocked_producer() {
+        let queue = Arc::new(SinkQueue::new(1, SinkOverflow::Block, None));
+        queue.push(1).await;
+        let producer = tokio::spawn({
+            let queue = queue.clone();
+            async move { queue.push(2).await }
+        });
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        queue.close();
+        producer.await.unwrap();
+        assert_eq!(drain(&queue).await, vec![1]);
+    }
+
+    #[tokio::test]
+    async fn test_max_rate() {
+        let queue = SinkQueue::new(10, SinkOverflow::DropOldest, Some(20.0));
+        for item in 1..=3 {
+            queue.push(item).await;
+        }
+        let start = Instant::now();
+        assert_eq!(drain(&queue).await, vec![1, 2, 3]);
+        assert!(start.elapsed() >= Duration::from_millis(100));
+    }
+}
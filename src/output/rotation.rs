@@ -0,0 +1,180 @@
+use std::{
+    io::Write,
+    path::Path,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use crate::{
+    error::ApplicationError,
+    output::{s3::Uploader, sink},
+};
+
+/**
+ * Splits the output into segments, starting a new file every interval. Each segment is named
+ * after the output file with its start time inserted before the extensions, e.g.
+ * `capture-1700000000.csv.gz`. Finished segments are handed to the uploader, if any.
+ */
+pub struct Rotation {
+    /**
+     * The output file the segment names are derived from.
+     */
+    path: String,
+    interval: Duration,
+    /**
+     * Start of the current segment.
+     */
+    started: Instant,
+    /**
+     * File of the current segment.
+     */
+    segment: String,
+    uploader: Option<Uploader>,
+}
+
+impl Rotation {
+    /**
+     * Opens the first segment.
+     *
+     * # Arguments
+     * `path` - The output file.
+     * `interval` - The time after which a new segment is started.
+     * `uploader` - The uploader finished segments are handed to, if any.
+     *
+     * # Returns
+     * A Result containing the Rotation and the sink of the first segment, or an ApplicationError.
+     */
+    pub fn start(
+        path: &str,
+        interval: Duration,
+        uploader: Option<Uploader>,
+    ) -> Result<(Self, Box<dyn Write>), ApplicationError> {
+        let segment = segment_path(path, unix_seconds());
+        let sink = sink::open(Some(&segment))?;
+        Ok((
+            Self {
+                path: path.to_string(),
+                interval,
+                started: Instant::now(),
+                segment,
+                uploader,
+            },
+            sink,
+        ))
+    }
+
+    /**
+     * Checks if the current segment should be finished.
+     *
+     * # Returns
+     * A boolean indicating whether the interval has passed.
+     */
+    pub fn is_due(&self) -> bool {
+        self.started.elapsed() >= self.interval
+    }
+
+    /**
+     * Finishes the current segment and opens the next one.
+     *
+     * # Arguments
+     * `sink` - The sink of the current segment, closed before it is uploaded.
+     *
+     * # Returns
+     * A Result containing the sink of the next segment or an ApplicationError.
+     */
+    pub fn rotate(&mut self, sink: Box<dyn Write>) -> Result<Box<dyn Write>, ApplicationError> {
+        drop(sink);
+        self.submit()?;
+        self.segment = segment_path(&self.path, unix_seconds());
+        self.started = Instant::now();
+        sink::open(Some(&self.segment))
+    }
+
+    /**
+     * Finishes the last segment and waits for the uploads.
+     *
+     * # Arguments
+     * `sink` - The sink of the last segment.
+     *
+     * # Returns
+     * A Result indicating success or an ApplicationError if the segment cannot be spooled.
+     */
+    pub async fn finish(mut self, sink: Box<dyn Write>) -> Result<(), ApplicationError> {
+        drop(sink);
+        self.submit()?;
+        if let Some(uploader) = self.uploader.take() {
+            uploader.finish().await;
+        }
+        Ok(())
+    }
+
+    /**
+     * Hands the current segment to the uploader.
+     *
+     * # Returns
+     * A Result indicating success or an ApplicationError.
+     */
+    fn submit(&self) -> Result<(), ApplicationError> {
+        match &self.uploader {
+            Some(uploader) => uploader.submit(Path::new(&self.segment)),
+            None => Ok(()),
+        }
+    }
+}
+
+/**
+ * Returns the current time as whole seconds since the Unix epoch.
+ *
+ * # Returns
+ * The seconds.
+ */
+fn unix_seconds() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs()
+}
+
+/**
+ * Names a segment by inserting its start time before the extensions of the output file.
+ *
+ * # Arguments
+ * `path` - The output file.
+ * `seconds` - The start time as seconds since the Unix epoch.
+ *
+ * # Returns
+ * The segment file.
+ */
+fn segment_path(path: &str, seconds: u64) -> String {
+    let name_start = path.rfind('/').map(|index| index + 1).unwrap_or(0);
+    let (directory, name) = path.split_at(name_start);
+    match name.find('.').filter(|index| *index > 0) {
+        Some(index) => format!("{}{}-{}{}", directory, &name[..index], seconds, &name[index..]),
+        None => format!("{}{}-{}", directory, name, seconds),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_segment_path() {
+        assert_eq!(segment_path("logs/capture.csv.gz", 1700000000), "logs/capture-1700000000.csv.gz");
+        assert_eq!(segment_path("capture", 5), "capture-5");
+        assert_eq!(segment_path("./data.v2/.hidden", 5), "./data.v2/.hidden-5");
+    }
+
+    #[test]
+    fn test_rotate() {
+        let directory = std::env::temp_dir().join(format!("rotation-{}", std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+        let path = directory.join("capture.csv");
+        let (mut rotation, mut sink) = Rotation::start(path.to_str().unwrap(), Duration::ZERO, None).unwrap();
+        assert!(rotation.is_due());
+        sink::write_line(sink.as_mut(), "a,b").unwrap();
+        let first = rotation.segment.clone();
+        std::thread::sleep(Duration::from_millis(1100));
+        let sink = rotation.rotate(sink).unwrap();
+        assert_ne!(rotation.segment, first);
+        assert_eq!(std::fs::read_to_string(&first).unwrap(), "a,b\n");
+        drop(sink);
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+}
@@ -0,0 +1,525 @@
+use std::{
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use hmac::{Hmac, Mac};
+use reqwest::{Client, Url};
+use sha2::{Digest, Sha256};
+use tokio::{sync::mpsc, task::JoinHandle};
+use tracing::warn;
+
+use crate::{error::ApplicationError, output::time::utc};
+
+/**
+ * Time allowed for a single upload, including the connection.
+ */
+const UPLOAD_TIMEOUT: Duration = Duration::from_secs(60);
+
+/**
+ * Wait before the first retry of a failed upload. The wait doubles after every failure.
+ */
+const RETRY_INITIAL: Duration = Duration::from_secs(5);
+
+/**
+ * Longest wait between retries.
+ */
+const RETRY_MAX: Duration = Duration::from_secs(300);
+
+/**
+ * Time the uploader gets to upload the spooled segments at exit.
+ */
+const FINISH_TIMEOUT: Duration = Duration::from_secs(30);
+
+/**
+ * Headers included in the request signature.
+ */
+const SIGNED_HEADERS: &str = "host;x-amz-content-sha256;x-amz-date";
+
+/**
+ * An S3-compatible bucket addressed path style, e.g. `https://minio.local:9000/bench/logs`.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct S3Target {
+    /**
+     * Scheme, host and port of the endpoint.
+     */
+    pub endpoint: Url,
+    pub bucket: String,
+    /**
+     * Key prefix of the uploaded segments, without trailing slash.
+     */
+    pub prefix: String,
+    pub region: String,
+    pub access_key: String,
+    pub secret_key: String,
+}
+
+impl S3Target {
+    /**
+     * Parses the bucket URL.
+     *
+     * # Arguments
+     * `url` - The URL, `http[s]://host[:port]/bucket[/prefix]`.
+     * `region` - The signing region.
+     * `access_key` - The access key id.
+     * `secret_key` - The secret access key.
+     *
+     * # Returns
+     * A Result containing the S3Target or an ApplicationError if the URL is invalid.
+     */
+    pub fn parse(url: &str, region: &str, access_key: &str, secret_key: &str) -> Result<Self, ApplicationError> {
+        let invalid = || ApplicationError::Config(format!("Expected http[s]://host/bucket[/prefix], got {}", url));
+        let mut endpoint = Url::parse(url).map_err(|_| invalid())?;
+        if !matches!(endpoint.scheme(), "http" | "https") || endpoint.host_str().is_none() {
+            return Err(invalid());
+        }
+        let path = endpoint.path().trim_matches('/').to_string();
+        let (bucket, prefix) = path.split_once('/').unwrap_or((&path, ""));
+        if bucket.is_empty() {
+            return Err(invalid());
+        }
+        let (bucket, prefix) = (bucket.to_string(), prefix.to_string());
+        endpoint.set_path("");
+        Ok(Self {
+            endpoint,
+            bucket,
+            prefix,
+            region: region.to_string(),
+            access_key: access_key.to_string(),
+            secret_key: secret_key.to_string(),
+        })
+    }
+
+    /**
+     * Returns the Host header of the requests, the port included unless it is the default of the scheme.
+     *
+     * # Returns
+     * The host.
+     */
+    fn host(&self) -> String {
+        let host = self.endpoint.host_str().unwrap_or_default();
+        match self.endpoint.port() {
+            Some(port) => format!("{}:{}", host, port),
+            None => host.to_string(),
+        }
+    }
+
+    /**
+     * Returns the request path of an object.
+     *
+     * # Arguments
+     * `name` - The file name of the segment.
+     *
+     * # Returns
+     * The URI encoded path, `/bucket/prefix/name`.
+     */
+    fn object_path(&self, name: &str) -> String {
+        let key = match self.prefix.as_str() {
+            "" => name.to_string(),
+            prefix => format!("{}/{}", prefix, name),
+        };
+        format!("/{}/{}", uri_encode(&self.bucket), uri_encode(&key))
+    }
+
+    /**
+     * Builds the headers signing a PUT request (AWS Signature Version 4).
+     *
+     * # Arguments
+     * `name` - The file name of the segment.
+     * `body` - The object contents.
+     * `seconds` - The signing time as seconds since the Unix epoch.
+     *
+     * # Returns
+     * The header names and values.
+     */
+    fn signed_headers(&self, name: &str, body: &[u8], seconds: u64) -> [(&'static str, String); 3] {
+        let date = amz_date(seconds);
+        let day = &date[..8];
+        let payload_hash = hex::encode(Sha256::digest(body));
+        let canonical_request = format!(
+            "PUT\n{}\n\nhost:{}\nx-amz-content-sha256:{}\nx-amz-date:{}\n\n{}\n{}",
+            self.object_path(name),
+            self.host(),
+            payload_hash,
+            date,
+            SIGNED_HEADERS,
+            payload_hash
+        );
+        let scope = format!("{}/{}/s3/aws4_request", day, self.region);
+        let string_to_sign = format!(
+            "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+            date,
+            scope,
+            hex::encode(Sha256::digest(canonical_request))
+        );
+        let key = signing_key(&self.secret_key, day, &self.region, "s3");
+        let signature = hex::encode(hmac_sha256(&key, string_to_sign.as_bytes()));
+        let authorization = format!(
+            "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+            self.access_key, scope, SIGNED_HEADERS, signature
+        );
+        [
+            ("x-amz-content-sha256", payload_hash),
+            ("x-amz-date", date),
+            ("authorization", authorization),
+        ]
+    }
+
+    /**
+     * Uploads a file.
+     *
+     * # Arguments
+     * `client` - The HTTP client.
+     * `path` - The file to upload; its file name becomes the last part of the object key.
+     *
+     * # Returns
+     * A Result indicating success or an ApplicationError if the upload fails.
+     */
+    async fn upload(&self, client: &Client, path: &Path) -> Result<(), ApplicationError> {
+        let name = path.file_name().and_then(|name| name.to_str()).unwrap_or_default();
+        let body = tokio::fs::read(path)
+            .await
+            .map_err(|e| ApplicationError::Io(format!("Could not read {}", path.display()), e))?;
+        let seconds = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+        let url = self
+            .endpoint
+            .join(&self.object_path(name))
+            .map_err(|e| ApplicationError::General(format!("Invalid object key {}: {}", name, e)))?;
+        let mut request = client.put(url);
+        for (header, value) in self.signed_headers(name, &body, seconds) {
+            request = request.header(header, value);
+        }
+        let response = request.body(body).send().await.map_err(|e| {
+            if e.is_timeout() {
+                ApplicationError::Timeout(format!("Upload of {}", path.display()))
+            } else {
+                ApplicationError::Network(format!("Upload of {} failed: {}", path.display(), e))
+            }
+        })?;
+        if !response.status().is_success() {
+            return Err(ApplicationError::Network(format!(
+                "Upload of {} rejected: {}",
+                path.display(),
+                response.status()
+            )));
+        }
+        Ok(())
+    }
+}
+
+/**
+ * Uploads finished segments in the background. Segments are moved to a spool directory and
+ * removed from it once uploaded, so segments written while the bucket is unreachable are
+ * uploaded when it comes back, also by a later run.
+ */
+pub struct Uploader {
+    /**
+     * Directory of the segments waiting for upload.
+     */
+    spool_dir: PathBuf,
+    /**
+     * Wakes the upload task when a segment is spooled.
+     */
+    wake: mpsc::UnboundedSender<()>,
+    task: JoinHandle<()>,
+}
+
+impl Uploader {
+    /**
+     * Creates the spool directory and starts the upload task. Segments left in the spool by
+     * an earlier run are uploaded first.
+     *
+     * # Arguments
+     * `target` - The bucket.
+     * `spool_dir` - The spool directory.
+     *
+     * # Returns
+     * A Result containing the Uploader or an ApplicationError if the spool directory cannot be created.
+     */
+    pub fn start(target: S3Target, spool_dir: &Path) -> Result<Self, ApplicationError> {
+        std::fs::create_dir_all(spool_dir).map_err(|e| {
+            ApplicationError::Io(format!("Could not create spool directory {}", spool_dir.display()), e)
+        })?;
+        let (wake, wakeups) = mpsc::unbounded_channel();
+        let task = tokio::spawn(run(target, spool_dir.to_path_buf(), wakeups));
+        Ok(Self {
+            spool_dir: spool_dir.to_path_buf(),
+            wake,
+            task,
+        })
+    }
+
+    /**
+     * Moves a finished segment to the spool and wakes the upload task.
+     *
+     * # Arguments
+     * `path` - The segment file.
+     *
+     * # Returns
+     * A Result indicating success or an ApplicationError if the segment cannot be moved.
+     */
+    pub fn submit(&self, path: &Path) -> Result<(), ApplicationError> {
+        let name = path
+            .file_name()
+            .ok_or_else(|| ApplicationError::General(format!("Invalid segment {}", path.display())))?;
+        let spooled = self.spool_dir.join(name);
+        std::fs::rename(path, &spooled)
+            .or_else(|_| std::fs::copy(path, &spooled).and_then(|_| std::fs::remove_file(path)))
+            .map_err(|e| ApplicationError::Io(format!("Could not spool {}", path.display()), e))?;
+        let _ = self.wake.send(());
+        Ok(())
+    }
+
+    /**
+     * Stops the upload task after a last attempt to upload the spool. Segments that could not
+     * be uploaded stay in the spool for the next run.
+     */
+    pub async fn finish(self) {
+        drop(self.wake);
+        let _ = tokio::time::timeout(FINISH_TIMEOUT, self.task).await;
+    }
+}
+
+/**
+ * Upload task: uploads the spool whenever a segment is added, retrying with backoff while
+ * uploads fail. Returns after a last attempt once the Uploader is finished.
+ *
+ * # Arguments
+ * `target` - The bucket.
+ * `spool_dir` - The spool directory.
+ * `wakeups` - Signals that a segment was spooled.
+ */
+async fn run(target: S3Target, spool_dir: PathBuf, mut wakeups: mpsc::UnboundedReceiver<()>) {
+    let client = match Client::builder().timeout(UPLOAD_TIMEOUT).build() {
+        Ok(client) => client,
+        Err(e) => {
+            warn!("Could not create the S3 client, segments stay in {}: {}", spool_dir.display(), e);
+            return;
+        }
+    };
+    let mut backoff = RETRY_INITIAL;
+    loop {
+        let pending = upload_spool(&client, &target, &spool_dir).await;
+        let wait = if pending {
+            let wait = backoff;
+            backoff = (backoff * 2).min(RETRY_MAX);
+            wait
+        } else {
+            backoff = RETRY_INITIAL;
+            RETRY_MAX
+        };
+        tokio::select! {
+            wakeup = wakeups.recv() => {
+                if wakeup.is_none() {
+                    upload_spool(&client, &target, &spool_dir).await;
+                    return;
+                }
+            }
+            _ = tokio::time::sleep(wait) => {}
+        }
+    }
+}
+
+/**
+ * Uploads the spooled segments, oldest first, and removes the uploaded ones. Stops at the
+ * first failure.
+ *
+ * # Arguments
+ * `client` - The HTTP client.
+ * `target` - The bucket.
+ * `spool_dir` - The spool directory.
+ *
+ * # Returns
+ * Whether segments remain in the spool.
+ */
+async fn upload_spool(client: &Client, target: &S3Target, spool_dir: &Path) -> bool {
+    let mut segments: Vec<PathBuf> = match std::fs::read_dir(spool_dir) {
+        Ok(entries) => entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.is_file())
+            .collect(),
+        Err(e) => {
+            warn!("Could not read spool directory {}: {}", spool_dir.display(), e);
+            return true;
+        }
+    };
+    segments.sort();
+    for segment in segments {
+        if let Err(e) = target.upload(client, &segment).await {
+            warn!("{}", e);
+            return true;
+        }
+        if let Err(e) = std::fs::remove_file(&segment) {
+            warn!("Could not remove uploaded segment {}: {}", segment.display(), e);
+        }
+    }
+    false
+}
+
+/**
+ * Computes the HMAC-SHA256 of a message.
+ *
+ * # Arguments
+ * `key` - The key.
+ * `message` - The message.
+ *
+ * # Returns
+ * The MAC.
+ */
+fn hmac_sha256(key: &[u8], message: &[u8]) -> [u8; 32] {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts keys of any length");
+    mac.update(message);
+    mac.finalize().into_bytes().into()
+}
+
+/**
+ * Derives the Signature Version 4 signing key.
+ *
+ * # Arguments
+ * `secret_key` - The secret access key.
+ * `day` - The signing day, YYYYMMDD.
+ * `region` - The region.
+ * `service` - The service.
+ *
+ * # Returns
+ * The signing key.
+ */
+fn signing_key(secret_key: &str, day: &str, region: &str, service: &str) -> [u8; 32] {
+    let key = hmac_sha256(format!("AWS4{}", secret_key).as_bytes(), day.as_bytes());
+    let key = hmac_sha256(&key, region.as_bytes());
+    let key = hmac_sha256(&key, service.as_bytes());
+    hmac_sha256(&key, b"aws4_request")
+}
+
+/**
+ * Percent encodes an object key as required by Signature Version 4. Slashes are kept.
+ *
+ * # Arguments
+ * `key` - The object key.
+ *
+ * # Returns
+ * The encoded key.
+ */
+fn uri_encode(key: &str) -> String {
+    key.bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' => (byte as char).to_string(),
+            _ => format!("%{:02X}", byte),
+        })
+        .collect()
+}
+
+/**
+ * Formats a time as the ISO 8601 basic format used by Signature Version 4.
+ *
+ * # Arguments
+ * `seconds` - Seconds since the Unix epoch.
+ *
+ * # Returns
+ * The UTC time, e.g. `20130524T000000Z`.
+ */
+fn amz_date(seconds: u64) -> String {
+    let time = utc(seconds);
+    format!(
+        "{:04}{:02}{:02}T{:02}{:02}{:02}Z",
+        time.year, time.month, time.day, time.hour, time.minute, time.second
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    use super::*;
+
+    fn target() -> S3Target {
+        S3Target::parse("http://minio.local:9000/bench/logs/", "us-east-1", "AKIDEXAMPLE", "secret").unwrap()
+    }
+
+    #[test]
+    fn test_parse_url() {
+        let target = target();
+        assert_eq!(target.endpoint.as_str(), "http://minio.local:9000/");
+        assert_eq!(target.host(), "minio.local:9000");
+        assert_eq!(target.bucket, "bench");
+        assert_eq!(target.prefix, "logs");
+        assert_eq!(S3Target::parse("http://minio:80/bench", "r", "a", "s").unwrap().host(), "minio");
+        let target = S3Target::parse("https://s3.amazonaws.com/bench", "r", "a", "s").unwrap();
+        assert_eq!(target.endpoint.as_str(), "https://s3.amazonaws.com/");
+        assert_eq!(target.prefix, "");
+        assert!(S3Target::parse("http://minio.local:9000/", "r", "a", "s").is_err());
+        assert!(S3Target::parse("ftp://minio.local/bench", "r", "a", "s").is_err());
+        assert!(S3Target::parse("minio.local/bench", "r", "a", "s").is_err());
+    }
+
+    #[test]
+    fn test_signing_key() {
+        // Example from the AWS Signature Version 4 documentation
+        let key = signing_key("wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY", "20120215", "us-east-1", "iam");
+        assert_eq!(hex::encode(key), "f4780e2d9f65fa895f9c67b32ce1baf0b0d8a43505a000a1a9e090d414db404d");
+    }
+
+    #[test]
+    fn test_signed_headers() {
+        let target = target();
+        assert_eq!(target.object_path("capture 1.csv.gz"), "/bench/logs/capture%201.csv.gz");
+        let [(_, payload_hash), (_, date), (_, authorization)] =
+            target.signed_headers("capture 1.csv.gz", b"a,b\n", 1369353600);
+        assert_eq!(payload_hash, hex::encode(Sha256::digest(b"a,b\n")));
+        assert_eq!(date, "20130524T000000Z");
+        assert!(authorization.starts_with(
+            "AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/20130524/us-east-1/s3/aws4_request, \
+             SignedHeaders=host;x-amz-content-sha256;x-amz-date, Signature="
+        ));
+        assert_eq!(authorization.len() - authorization.rfind('=').unwrap() - 1, 64);
+    }
+
+    #[test]
+    fn test_amz_date() {
+        assert_eq!(amz_date(0), "19700101T000000Z");
+        assert_eq!(amz_date(1369353600), "20130524T000000Z");
+        assert_eq!(amz_date(1709210096), "20240229T123456Z");
+    }
+
+    #[tokio::test]
+    async fn test_upload_spool() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let target = S3Target::parse(
+            &format!("http://{}/bench/logs", listener.local_addr().unwrap()),
+            "us-east-1",
+            "AKIDEXAMPLE",
+            "secret",
+        )
+        .unwrap();
+        let server = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut request = Vec::new();
+            let mut buffer = [0; 4096];
+            while !request.ends_with(b"a,b\n") {
+                let length = stream.read(&mut buffer).await.unwrap();
+                if length == 0 {
+                    break;
+                }
+                request.extend_from_slice(&buffer[..length]);
+            }
+            stream.write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\n\r\n").await.unwrap();
+            String::from_utf8_lossy(&request).to_string()
+        });
+        let spool_dir = std::env::temp_dir().join(format!("spool-{}", std::process::id()));
+        std::fs::create_dir_all(&spool_dir).unwrap();
+        std::fs::write(spool_dir.join("capture-1.csv"), "a,b\n").unwrap();
+        let client = Client::new();
+        assert!(!upload_spool(&client, &target, &spool_dir).await);
+        let request = server.await.unwrap();
+        assert!(request.starts_with("PUT /bench/logs/capture-1.csv HTTP/1.1\r\n"));
+        assert!(request.contains(&format!("host: {}\r\n", target.host())));
+        assert!(request.contains("authorization: AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/"));
+        assert!(!spool_dir.join("capture-1.csv").exists());
+        // Nothing listens anymore, the segment stays spooled
+        std::fs::write(spool_dir.join("capture-2.csv"), "a,b\n").unwrap();
+        assert!(upload_spool(&client, &target, &spool_dir).await);
+        assert!(spool_dir.join("capture-2.csv").exists());
+        std::fs::remove_dir_all(&spool_dir).unwrap();
+    }
+}
@@ -0,0 +1,227 @@
+use std::collections::HashMap;
+
+use serde::Deserialize;
+
+use crate::{
+    error::ApplicationError,
+    instruments::reading::{Measurement, Reading, Samples},
+};
+
+/**
+ * Fields a sink receives and the names it receives them under, e.g. only `display_value` and
+ * `display_unit` renamed to `reading` and `unit`.
+ */
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FieldShape {
+    /**
+     * Fields passed to the sink, in this order. All fields if empty.
+     */
+    #[serde(default)]
+    pub fields: Vec<String>,
+    /**
+     * New names of fields, by original name.
+     */
+    #[serde(default)]
+    pub rename: HashMap<String, String>,
+}
+
+impl FieldShape {
+    /**
+     * Selects and renames the fields of a reading.
+     *
+     * # Arguments
+     * `header` - The field names.
+     * `values` - The field values.
+     *
+     * # Returns
+     * The shaped names and values. Selected fields the reading does not have are left out.
+     */
+    fn apply(&self, header: Vec<String>, values: Vec<String>) -> Vec<(String, String)> {
+        let fields: Vec<(String, String)> = header.into_iter().zip(values).collect();
+        let selected = match self.fields.is_empty() {
+            true => fields,
+            false => self
+                .fields
+                .iter()
+                .filter_map(|name| fields.iter().find(|(field, _)| field == name).cloned())
+                .collect(),
+        };
+        selected
+            .into_iter()
+            .map(|(name, value)| (self.rename.get(&name).cloned().unwrap_or(name), value))
+            .collect()
+    }
+}
+
+/**
+ * Field shapes of the sinks, from the `[sinks.<sink>]` tables of the configuration file.
+ */
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SinkShapes {
+    /**
+     * Stdout or the output file.
+     */
+    pub output: Option<FieldShape>,
+    pub websocket: Option<FieldShape>,
+    pub syslog: Option<FieldShape>,
+    pub chain_log: Option<FieldShape>,
+}
+
+/**
+ * A reading as seen by one sink: the fields of the wrapped reading selected and renamed by the
+ * shape of the sink. Raw data and the typed measurement are passed through.
+ */
+pub struct ShapedReading<'a> {
+    reading: &'a dyn Reading,
+    shape: &'a FieldShape,
+}
+
+impl<'a> ShapedReading<'a> {
+    /**
+     * Creates a new ShapedReading.
+     *
+     * # Arguments
+     * `reading` - The reading.
+     * `shape` - The field shape of the sink.
+     *
+     * # Returns
+     * A new ShapedReading instance.
+     */
+    pub fn new(reading: &'a dyn Reading, shape: &'a FieldShape) -> Self {
+        Self { reading, shape }
+    }
+
+    /**
+     * Returns the shaped fields.
+     *
+     * # Returns
+     * A Result containing the field names and values or an ApplicationError.
+     */
+    fn fields(&self) -> Result<Vec<(String, String)>, ApplicationError> {
+        Ok(self.shape.apply(self.reading.get_csv_header()?, self.reading.get_csv()?))
+    }
+}
+
+impl Reading for ShapedReading<'_> {
+    /**
+     * Returns the names of the shaped fields.
+     *
+     * # Returns
+     * A Result containing the CSV header fields or an ApplicationError.
+     */
+    fn get_csv_header(&self) -> Result<Vec<String>, ApplicationError> {
+        Ok(self.fields()?.into_iter().map(|(name, _)| name).collect())
+    }
+
+    /**
+     * Returns the values of the shaped fields.
+     *
+     * # Returns
+     * A Result containing the CSV field values or an ApplicationError.
+     */
+    fn get_csv(&self) -> Result<Vec<String>, ApplicationError> {
+        Ok(self.fields()?.into_iter().map(|(_, value)| value).collect())
+    }
+
+    /**
+     * Returns the raw data of the wrapped reading as a byte vector.
+     *
+     * # Returns
+     * A Result containing a byte vector with the raw data or an ApplicationError.
+     */
+    fn get_raw(&self) -> Result<Vec<u8>, ApplicationError> {
+        self.reading.get_raw()
+    }
+
+    /**
+     * Returns the raw data of the wrapped reading as a String.
+     *
+     * # Returns
+     * A Result containing a String with the raw data or an ApplicationError.
+     */
+    fn get_raw_string(&self) -> Result<String, ApplicationError> {
+        self.reading.get_raw_string()
+    }
+
+    /**
+     * Returns the typed measurement of the wrapped reading.
+     *
+     * # Returns
+     * The measurement, if the wrapped reading has one.
+     */
+    fn get_measurement(&self) -> Option<Measurement> {
+        self.reading.get_measurement()
+    }
+
+    /**
+     * Returns the samples of the wrapped reading.
+     *
+     * # Returns
+     * The samples, if the wrapped reading is a waveform.
+     */
+    fn get_samples(&self) -> Option<Samples> {
+        self.reading.get_samples()
+    }
+}
+
+/**
+ * Applies the shape of a sink, if it has one, to a reading.
+ *
+ * # Arguments
+ * `reading` - The reading.
+ * `shape` - The field shape of the sink.
+ * `sink` - Receives the reading as the sink sees it.
+ *
+ * # Returns
+ * The result of the sink.
+ */
+pub fn with_shape<T>(
+    reading: &dyn Reading,
+    shape: Option<&FieldShape>,
+    sink: impl FnOnce(&dyn Reading) -> T,
+) -> T {
+    match shape {
+        Some(shape) => sink(&ShapedReading::new(reading, shape)),
+        None => sink(reading),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::instruments::reading::Unit161dReading;
+
+    fn reading() -> Unit161dReading {
+        Unit161dReading::parse(vec![2, b'0', b' ', b' ', b'1', b'.', b'2', b'3', b'4', 0, 0, 0, 0, 0]).unwrap()
+    }
+
+    #[test]
+    fn test_select_and_rename() {
+        let reading = reading();
+        let shape = FieldShape {
+            fields: vec!["display_unit".into(), "display_value".into(), "missing".into()],
+            rename: HashMap::from([("display_value".to_string(), "reading".to_string())]),
+        };
+        let shaped = ShapedReading::new(&reading, &shape);
+        assert_eq!(shaped.get_csv_header().unwrap(), ["display_unit", "reading"]);
+        let values = reading.get_csv().unwrap();
+        assert_eq!(shaped.get_csv().unwrap(), [values[6].clone(), values[2].clone()]);
+        assert_eq!(shaped.get_measurement(), reading.get_measurement());
+    }
+
+    #[test]
+    fn test_rename_only() {
+        let reading = reading();
+        let shape = FieldShape {
+            fields: Vec::new(),
+            rename: HashMap::from([("mode".to_string(), "function".to_string())]),
+        };
+        let header = with_shape(&reading, Some(&shape), |reading| reading.get_csv_header().unwrap());
+        assert_eq!(header[0], "function");
+        assert_eq!(header.len(), reading.get_csv_header().unwrap().len());
+        let header = with_shape(&reading, None, |reading| reading.get_csv_header().unwrap());
+        assert_eq!(header[0], "mode");
+    }
+}
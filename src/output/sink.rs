@@ -0,0 +1,114 @@
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+};
+
+use flate2::{write::GzEncoder, Compression};
+
+use crate::error::ApplicationError;
+
+/**
+ * Compression level of zstd sinks.
+ */
+const ZSTD_LEVEL: i32 = 3;
+
+/**
+ * Opens the sink records are written to: stdout, or a file that is gzip or zstd compressed
+ * when its name ends in `.gz` or `.zst`. Compressed streams are finished when the sink is dropped.
+ *
+ * # Arguments
+ * `path` - The output file, or None for stdout.
+ *
+ * # Returns
+ * A Result containing the sink or an ApplicationError if the file cannot be created.
+ */
+pub fn open(path: Option<&str>) -> Result<Box<dyn Write>, ApplicationError> {
+    let Some(path) = path else {
+        return Ok(Box::new(io::stdout()));
+    };
+    let file = File::create(path)
+        .map(BufWriter::new)
+        .map_err(|e| ApplicationError::Io(format!("Could not create {}", path), e))?;
+    if path.ends_with(".gz") {
+        Ok(Box::new(GzEncoder::new(file, Compression::default())))
+    } else if path.ends_with(".zst") {
+        let encoder = zstd::Encoder::new(file, ZSTD_LEVEL)
+            .map_err(|e| ApplicationError::Io(format!("Could not start zstd stream for {}", path), e))?;
+        Ok(Box::new(encoder.auto_finish()))
+    } else {
+        Ok(Box::new(file))
+    }
+}
+
+/**
+ * Writes a line to a sink and flushes it, so compressed files can be read while they are
+ * being written and lose at most the last record if the process is killed.
+ *
+ * # Arguments
+ * `sink` - The sink.
+ * `line` - The line without trailing newline.
+ *
+ * # Returns
+ * A Result indicating success or an ApplicationError.
+ */
+pub fn write_line(sink: &mut dyn Write, line: &str) -> Result<(), ApplicationError> {
+    writeln!(sink, "{}", line)
+        .and_then(|_| sink.flush())
+        .map_err(|e| ApplicationError::Io("Could not write output".into(), e))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::io::Read;
+
+    fn temp_path(name: &str) -> String {
+        std::env::temp_dir()
+            .join(format!("sink-{}-{}", std::process::id(), name))
+            .to_str()
+            .unwrap()
+            .to_string()
+    }
+
+    #[test]
+    fn test_gzip_sink() {
+        let path = temp_path("capture.csv.gz");
+        let mut sink = open(Some(&path)).unwrap();
+        write_line(sink.as_mut(), "a,b").unwrap();
+        write_line(sink.as_mut(), "1,2").unwrap();
+        drop(sink);
+        let mut text = String::new();
+        flate2::read::GzDecoder::new(File::open(&path).unwrap())
+            .read_to_string(&mut text)
+            .unwrap();
+        assert_eq!(text, "a,b\n1,2\n");
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_zstd_sink() {
+        let path = temp_path("capture.csv.zst");
+        let mut sink = open(Some(&path)).unwrap();
+        write_line(sink.as_mut(), "a,b").unwrap();
+        // Readable after a flush, before the stream is finished
+        let mut flushed = [0; 4];
+        zstd::Decoder::new(File::open(&path).unwrap())
+            .unwrap()
+            .read_exact(&mut flushed)
+            .unwrap();
+        assert_eq!(&flushed, b"a,b\n");
+        drop(sink);
+        assert_eq!(zstd::decode_all(File::open(&path).unwrap()).unwrap(), b"a,b\n");
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_plain_sink() {
+        let path = temp_path("capture.csv");
+        let mut sink = open(Some(&path)).unwrap();
+        write_line(sink.as_mut(), "a,b").unwrap();
+        drop(sink);
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "a,b\n");
+        std::fs::remove_file(&path).unwrap();
+    }
+}
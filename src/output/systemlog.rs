@@ -0,0 +1,483 @@
+use std::{
+    cell::RefCell,
+    io,
+    net::UdpSocket,
+    os::unix::net::UnixDatagram,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use tracing::warn;
+
+use crate::{
+    arguments::LogTo,
+    error::ApplicationError,
+    instruments::reading::Reading,
+    output::{backlog::Backlog, time::utc},
+};
+
+/**
+ * Socket of the journald native protocol.
+ */
+const JOURNALD_SOCKET: &str = "/run/systemd/journal/socket";
+
+/**
+ * Default syslog socket.
+ */
+const DEFAULT_SYSLOG_ADDRESS: &str = "/dev/log";
+
+/**
+ * Syslog facility of the messages, user-level messages.
+ */
+const FACILITY_USER: u8 = 1;
+
+/**
+ * Structured data id of readings, with the enterprise number reserved for documentation.
+ */
+const SD_ID: &str = "reading@32473";
+
+/**
+ * Prefix of the reading fields in the journal, e.g. `READING_DISPLAY_VALUE`.
+ */
+const JOURNAL_FIELD_PREFIX: &str = "READING_";
+
+// Syslog severities
+const SEVERITY_ERROR: u8 = 3;
+const SEVERITY_WARNING: u8 = 4;
+const SEVERITY_NOTICE: u8 = 5;
+const SEVERITY_INFO: u8 = 6;
+
+/**
+ * Socket the messages are sent on.
+ */
+enum Transport {
+    Unix(UnixDatagram, String),
+    Udp(UdpSocket),
+}
+
+/**
+ * Sends readings and errors to syslog or journald with the reading fields as structured data,
+ * so they can be collected by the site log infrastructure. Measurements are logged with
+ * severity info, retries and reconnects with warning, script log statements with notice
+ * and errors with err. Messages that cannot be sent are kept in a backlog and sent when the
+ * socket accepts messages again; they carry their original timestamp, so they may arrive
+ * after newer messages.
+ */
+pub struct SystemLog {
+    log_to: LogTo,
+    transport: Transport,
+    /**
+     * Application name of the messages.
+     */
+    identifier: String,
+    hostname: String,
+    backlog: RefCell<Backlog>,
+}
+
+impl SystemLog {
+    /**
+     * Opens the log socket.
+     *
+     * # Arguments
+     * `log_to` - Whether to log to syslog or journald.
+     * `address` - The syslog socket path or UDP host:port, /dev/log if None. Ignored for journald.
+     * `identifier` - The application name of the messages.
+     * `backlog` - The backlog of messages that could not be sent.
+     *
+     * # Returns
+     * A Result containing the SystemLog or an ApplicationError if the socket cannot be opened.
+     */
+    pub fn open(
+        log_to: LogTo,
+        address: Option<&str>,
+        identifier: &str,
+        backlog: Backlog,
+    ) -> Result<Self, ApplicationError> {
+        let address = match log_to {
+            LogTo::Journald => JOURNALD_SOCKET,
+            LogTo::Syslog => address.unwrap_or(DEFAULT_SYSLOG_ADDRESS),
+        };
+        let error = |e: std::io::Error| ApplicationError::Io(format!("Could not open log socket {}", address), e);
+        let transport = if address.starts_with('/') {
+            Transport::Unix(UnixDatagram::unbound().map_err(error)?, address.to_string())
+        } else {
+            let socket = UdpSocket::bind("0.0.0.0:0").map_err(error)?;
+            socket.connect(address).map_err(error)?;
+            Transport::Udp(socket)
+        };
+        Ok(Self {
+            log_to,
+            transport,
+            identifier: identifier.to_string(),
+            hostname: hostname(),
+            backlog: RefCell::new(backlog),
+        })
+    }
+
+    /**
+     * Logs a reading.
+     *
+     * # Arguments
+     * `reading` - The reading.
+     *
+     * # Returns
+     * A Result indicating success or an ApplicationError if the message cannot be sent.
+     */
+    pub fn log_reading(&self, reading: &dyn Reading) -> Result<(), ApplicationError> {
+        let fields: Vec<(String, String)> = reading
+            .get_csv_header()?
+            .into_iter()
+            .zip(reading.get_csv()?)
+            .filter(|(_, value)| !value.is_empty())
+            .collect();
+        let severity = severity(&fields);
+        let message = reading.get_raw_string()?.replace(['\r', '\n'], " ");
+        self.send(severity, &message, &fields)
+    }
+
+    /**
+     * Logs an error.
+     *
+     * # Arguments
+     * `error` - The error.
+     *
+     * # Returns
+     * A Result indicating success or an ApplicationError if the message cannot be sent.
+     */
+    pub fn log_error(&self, error: &ApplicationError) -> Result<(), ApplicationError> {
+        self.send(SEVERITY_ERROR, &error.to_string(), &[])
+    }
+
+    /**
+     * Formats and sends a message, then backfills the backlog. A message that cannot be sent
+     * is added to the backlog.
+     *
+     * # Arguments
+     * `severity` - The syslog severity.
+     * `message` - The message text.
+     * `fields` - The structured fields.
+     *
+     * # Returns
+     * A Result indicating success or an ApplicationError if the message cannot be buffered.
+     */
+    fn send(&self, severity: u8, message: &str, fields: &[(String, String)]) -> Result<(), ApplicationError> {
+        let datagram = match self.log_to {
+            LogTo::Journald => journald_message(&self.identifier, severity, message, fields),
+            LogTo::Syslog => {
+                let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+                syslog_message(&self.hostname, &self.identifier, severity, timestamp.as_millis() as u64, message, fields)
+                    .into_bytes()
+            }
+        };
+        let mut backlog = self.backlog.borrow_mut();
+        if backlog.must_wait() {
+            backlog.push(datagram)?;
+        } else if let Err(e) = self.transmit(&datagram) {
+            if backlog.is_empty() {
+                warn!("Could not send log message, buffering: {}", e);
+            }
+            backlog.push(datagram)?;
+            return Ok(());
+        }
+        while let Some(record) = backlog.front() {
+            if self.transmit(record).is_err() {
+                break;
+            }
+            backlog.delivered();
+        }
+        Ok(())
+    }
+
+    /**
+     * Sends a datagram on the log socket.
+     *
+     * # Arguments
+     * `datagram` - The formatted message.
+     *
+     * # Returns
+     * An io::Result indicating whether the datagram was sent.
+     */
+    fn transmit(&self, datagram: &[u8]) -> io::Result<()> {
+        match &self.transport {
+            Transport::Unix(socket, path) => socket.send_to(datagram, path),
+            Transport::Udp(socket) => socket.send(datagram),
+        }
+        .map(|_| ())
+    }
+}
+
+/**
+ * Gets the severity of a reading from its fields. Events are logged by kind, other readings
+ * with severity info.
+ *
+ * # Arguments
+ * `fields` - The field names and values of the reading.
+ *
+ * # Returns
+ * The syslog severity.
+ */
+fn severity(fields: &[(String, String)]) -> u8 {
+    let field = |name: &str| {
+        fields
+            .iter()
+            .find(|(field, _)| field == name)
+            .map(|(_, value)| value.as_str())
+    };
+    if field("type") != Some("event") {
+        return SEVERITY_INFO;
+    }
+    match field("kind") {
+        Some("error") => SEVERITY_ERROR,
+        Some("retry") | Some("reconnect") => SEVERITY_WARNING,
+        _ => SEVERITY_NOTICE,
+    }
+}
+
+/**
+ * Formats an RFC 5424 message with the fields as structured data.
+ *
+ * # Arguments
+ * `hostname` - The host name.
+ * `identifier` - The application name.
+ * `severity` - The syslog severity.
+ * `timestamp_ms` - The time as milliseconds since the Unix epoch.
+ * `message` - The message text.
+ * `fields` - The structured fields.
+ *
+ * # Returns
+ * The message.
+ */
+fn syslog_message(
+    hostname: &str,
+    identifier: &str,
+    severity: u8,
+    timestamp_ms: u64,
+    message: &str,
+    fields: &[(String, String)],
+) -> String {
+    let time = utc(timestamp_ms / 1000);
+    let structured_data = match fields {
+        [] => "-".to_string(),
+        fields => format!(
+            "[{}{}]",
+            SD_ID,
+            fields
+                .iter()
+                .map(|(name, value)| format!(" {}=\"{}\"", sd_name(name), sd_escape(value)))
+                .collect::<String>()
+        ),
+    };
+    format!(
+        "<{}>1 {:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}Z {} {} {} - {} {}",
+        FACILITY_USER * 8 + severity,
+        time.year,
+        time.month,
+        time.day,
+        time.hour,
+        time.minute,
+        time.second,
+        timestamp_ms % 1000,
+        hostname,
+        identifier,
+        std::process::id(),
+        structured_data,
+        message
+    )
+}
+
+/**
+ * Formats a journald native protocol message. Values with line breaks use the length
+ * prefixed binary form.
+ *
+ * # Arguments
+ * `identifier` - The syslog identifier.
+ * `severity` - The syslog severity.
+ * `message` - The message text.
+ * `fields` - The structured fields, stored with the READING_ prefix.
+ *
+ * # Returns
+ * The datagram.
+ */
+fn journald_message(identifier: &str, severity: u8, message: &str, fields: &[(String, String)]) -> Vec<u8> {
+    let mut datagram = Vec::new();
+    let mut append = |name: &str, value: &str| {
+        datagram.extend_from_slice(name.as_bytes());
+        if value.contains('\n') {
+            datagram.push(b'\n');
+            datagram.extend_from_slice(&(value.len() as u64).to_le_bytes());
+        } else {
+            datagram.push(b'=');
+        }
+        datagram.extend_from_slice(value.as_bytes());
+        datagram.push(b'\n');
+    };
+    append("MESSAGE", message);
+    append("PRIORITY", &severity.to_string());
+    append("SYSLOG_IDENTIFIER", identifier);
+    for (name, value) in fields {
+        append(&journal_field_name(name), value);
+    }
+    datagram
+}
+
+/**
+ * Converts a field name to a journal field name: uppercase letters, digits and underscores.
+ *
+ * # Arguments
+ * `name` - The field name.
+ *
+ * # Returns
+ * The prefixed journal field name, at most 64 characters.
+ */
+fn journal_field_name(name: &str) -> String {
+    let name: String = name
+        .chars()
+        .map(|character| match character.to_ascii_uppercase() {
+            character @ ('A'..='Z' | '0'..='9') => character,
+            _ => '_',
+        })
+        .collect();
+    format!("{}{}", JOURNAL_FIELD_PREFIX, name).chars().take(64).collect()
+}
+
+/**
+ * Converts a field name to a structured data parameter name, at most 32 printable ASCII
+ * characters other than `=`, space, `]` and `"`.
+ *
+ * # Arguments
+ * `name` - The field name.
+ *
+ * # Returns
+ * The parameter name.
+ */
+fn sd_name(name: &str) -> String {
+    name.chars()
+        .map(|character| match character {
+            '!'..='~' if !matches!(character, '=' | ']' | '"') => character,
+            _ => '_',
+        })
+        .take(32)
+        .collect()
+}
+
+/**
+ * Escapes a structured data parameter value.
+ *
+ * # Arguments
+ * `value` - The value.
+ *
+ * # Returns
+ * The value with `"`, `\` and `]` escaped.
+ */
+fn sd_escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace(']', "\\]")
+}
+
+/**
+ * Gets the host name for syslog messages.
+ *
+ * # Returns
+ * The host name, or `-` if unknown.
+ */
+fn hostname() -> String {
+    std::fs::read_to_string("/proc/sys/kernel/hostname")
+        .ok()
+        .or_else(|| std::env::var("HOSTNAME").ok())
+        .map(|hostname| hostname.trim().to_string())
+        .filter(|hostname| !hostname.is_empty())
+        .unwrap_or_else(|| "-".to_string())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::output::backlog::Ordering;
+
+    fn fields(fields: &[(&str, &str)]) -> Vec<(String, String)> {
+        fields
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_severity() {
+        assert_eq!(severity(&fields(&[("display_value", "1.2")])), SEVERITY_INFO);
+        assert_eq!(severity(&fields(&[("type", "event"), ("kind", "error")])), SEVERITY_ERROR);
+        assert_eq!(severity(&fields(&[("type", "event"), ("kind", "retry")])), SEVERITY_WARNING);
+        assert_eq!(severity(&fields(&[("type", "event"), ("kind", "log")])), SEVERITY_NOTICE);
+    }
+
+    #[test]
+    fn test_syslog_message() {
+        let message = syslog_message(
+            "bench1",
+            "hardware-measurement",
+            SEVERITY_INFO,
+            1709210096789,
+            "DCV 1.2345 V",
+            &fields(&[("display_value", "1.2345"), ("mode name", "a\"b]")]),
+        );
+        assert_eq!(
+            message,
+            format!(
+                "<14>1 2024-02-29T12:34:56.789Z bench1 hardware-measurement {} - \
+                 [reading@32473 display_value=\"1.2345\" mode_name=\"a\\\"b\\]\"] DCV 1.2345 V",
+                std::process::id()
+            )
+        );
+        assert!(syslog_message("h", "i", SEVERITY_ERROR, 0, "failed", &[]).contains(" - - failed"));
+    }
+
+    #[test]
+    fn test_journald_message() {
+        let datagram = journald_message("hm", SEVERITY_ERROR, "a\nb", &fields(&[("display-value", "1.2")]));
+        let mut expected = b"MESSAGE\n".to_vec();
+        expected.extend_from_slice(&3u64.to_le_bytes());
+        expected.extend_from_slice(b"a\nb\nPRIORITY=3\nSYSLOG_IDENTIFIER=hm\nREADING_DISPLAY_VALUE=1.2\n");
+        assert_eq!(datagram, expected);
+    }
+
+    #[test]
+    fn test_unix_socket() {
+        let path = std::env::temp_dir().join(format!("systemlog-{}.sock", std::process::id()));
+        let path = path.to_str().unwrap();
+        let _ = std::fs::remove_file(path);
+        let receiver = UnixDatagram::bind(path).unwrap();
+        let backlog = Backlog::open(None, 10, Ordering::AnyOrder).unwrap();
+        let log = SystemLog::open(LogTo::Syslog, Some(path), "hm", backlog).unwrap();
+        log.log_error(&ApplicationError::Usb("Device not found".into())).unwrap();
+        let mut buffer = [0; 512];
+        let length = receiver.recv(&mut buffer).unwrap();
+        let message = String::from_utf8_lossy(&buffer[..length]).to_string();
+        assert!(message.starts_with("<11>1 "));
+        assert!(message.ends_with(" - - USB Error: Device not found"));
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_backfill() {
+        let path = std::env::temp_dir().join(format!("systemlog-backfill-{}.sock", std::process::id()));
+        let path = path.to_str().unwrap();
+        let _ = std::fs::remove_file(path);
+        let backlog = Backlog::open(None, 10, Ordering::AnyOrder).unwrap();
+        let log = SystemLog::open(LogTo::Syslog, Some(path), "hm", backlog).unwrap();
+        log.log_error(&ApplicationError::Usb("first".into())).unwrap();
+        assert_eq!(log.backlog.borrow().len(), 1);
+        let receiver = UnixDatagram::bind(path).unwrap();
+        log.log_error(&ApplicationError::Usb("second".into())).unwrap();
+        assert!(log.backlog.borrow().is_empty());
+        let mut buffer = [0; 512];
+        let messages: Vec<String> = (0..2)
+            .map(|_| {
+                let length = receiver.recv(&mut buffer).unwrap();
+                String::from_utf8_lossy(&buffer[..length]).to_string()
+            })
+            .collect();
+        assert!(messages[0].ends_with("USB Error: second"));
+        assert!(messages[1].ends_with("USB Error: first"));
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,87 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::{arguments::Notation, error::ApplicationError, instruments::reading::Reading, output::notation};
+
+/**
+ * Column names of the long format, one row per field of a reading.
+ */
+pub const TIDY_HEADER: [&str; 5] = ["timestamp", "device", "channel", "field", "value"];
+
+/**
+ * Fields that identify a reading and become columns instead of rows.
+ */
+const ID_FIELDS: [&str; 3] = ["timestamp", "device", "channel"];
+
+/**
+ * Returns the current time as seconds since the Unix epoch.
+ *
+ * # Returns
+ * The timestamp with millisecond resolution.
+ */
+pub fn now() -> String {
+    let since_epoch = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+    format!("{}.{:03}", since_epoch.as_secs(), since_epoch.subsec_millis())
+}
+
+/**
+ * Splits a reading into (timestamp, device, channel, field, value) rows. The `timestamp`,
+ * `device` and `channel` fields of the reading fill the identifying columns; empty fields are left out.
+ *
+ * # Arguments
+ * `reading` - The reading.
+ * `timestamp` - The timestamp used if the reading has none.
+ * `notation` - The notation of non-integer numbers.
+ *
+ * # Returns
+ * A Result containing the rows or an ApplicationError.
+ */
+pub fn rows(reading: &dyn Reading, timestamp: &str, notation: Notation) -> Result<Vec<Vec<String>>, ApplicationError> {
+    let header = reading.get_csv_header()?;
+    let fields = notation::format_fields(&header, reading.get_csv()?, notation);
+    let id = |name: &str| {
+        header
+            .iter()
+            .zip(fields.iter())
+            .find(|(field, _)| field.as_str() == name)
+            .map(|(_, value)| value.clone())
+            .unwrap_or_default()
+    };
+    let timestamp = match id("timestamp") {
+        value if value.is_empty() => timestamp.to_string(),
+        value => value,
+    };
+    let (device, channel) = (id("device"), id("channel"));
+    Ok(header
+        .iter()
+        .zip(fields.iter())
+        .filter(|(field, value)| !ID_FIELDS.contains(&field.as_str()) && !value.is_empty())
+        .map(|(field, value)| {
+            vec![
+                timestamp.clone(),
+                device.clone(),
+                channel.clone(),
+                field.clone(),
+                value.clone(),
+            ]
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::instruments::reading::{KoradReading, TaggedReading};
+
+    #[test]
+    fn test_rows() {
+        let reading = TaggedReading::new(
+            vec![("device".into(), "psu".into()), ("channel".into(), "2".into())],
+            Box::new(KoradReading::setpoints(b"05.00", b"0.100")),
+        );
+        let rows = rows(&reading, "1700000000.123", Notation::Plain).unwrap();
+        assert_eq!(rows.len(), 3);
+        assert_eq!(rows[0], vec!["1700000000.123", "psu", "2", "values", "setpoint"]);
+        assert_eq!(rows[1], vec!["1700000000.123", "psu", "2", "voltage", "5"]);
+        assert_eq!(rows[2], vec!["1700000000.123", "psu", "2", "current", "0.1"]);
+    }
+}
@@ -0,0 +1,59 @@
+/**
+ * A UTC calendar date and time of day.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UtcTime {
+    pub year: i64,
+    pub month: i64,
+    pub day: i64,
+    pub hour: u64,
+    pub minute: u64,
+    pub second: u64,
+}
+
+/**
+ * Converts seconds since the Unix epoch to a UTC date and time, using H. Hinnant's
+ * civil-from-days algorithm.
+ *
+ * # Arguments
+ * `seconds` - Seconds since the Unix epoch.
+ *
+ * # Returns
+ * The UTC date and time.
+ */
+pub fn utc(seconds: u64) -> UtcTime {
+    let days = (seconds / 86400) as i64 + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days - era * 146097;
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let month = if month_index < 10 { month_index + 3 } else { month_index - 9 };
+    let time = seconds % 86400;
+    UtcTime {
+        year: year_of_era + era * 400 + if month <= 2 { 1 } else { 0 },
+        month,
+        day: day_of_year - (153 * month_index + 2) / 5 + 1,
+        hour: time / 3600,
+        minute: time % 3600 / 60,
+        second: time % 60,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_utc() {
+        assert_eq!(
+            utc(0),
+            UtcTime { year: 1970, month: 1, day: 1, hour: 0, minute: 0, second: 0 }
+        );
+        assert_eq!(
+            utc(1709210096),
+            UtcTime { year: 2024, month: 2, day: 29, hour: 12, minute: 34, second: 56 }
+        );
+        assert_eq!(utc(951868800).month, 3);
+    }
+}
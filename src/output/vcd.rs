@@ -0,0 +1,222 @@
+use std::time::Instant;
+
+use crate::{error::ApplicationError, instruments::reading::Reading};
+
+/**
+ * Kind of a VCD variable.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum VariableKind {
+    /**
+     * Numeric field, an analog channel in PulseView.
+     */
+    Real,
+    /**
+     * Boolean field, a logic channel in PulseView.
+     */
+    Wire,
+}
+
+/**
+ * A field of the readings declared as VCD variable.
+ */
+#[derive(Debug, Clone)]
+struct Variable {
+    field: String,
+    kind: VariableKind,
+    identifier: String,
+    /**
+     * The value last written, to write changes only.
+     */
+    last: Option<String>,
+}
+
+/**
+ * Writes readings as a Value Change Dump, which sigrok and PulseView import next to logic
+ * captures. The numeric fields of the first reading become `real` variables and its boolean
+ * fields `wire` variables; fields of later readings that were not declared are left out. The
+ * time is the microseconds since the first reading was written, and only changed values are
+ * written.
+ */
+#[derive(Default)]
+pub struct VcdWriter {
+    variables: Option<Vec<Variable>>,
+    /**
+     * Whether the header must be written before the next values.
+     */
+    header_pending: bool,
+    start: Option<Instant>,
+}
+
+/**
+ * Gets the identifier code of the n-th variable from the printable ASCII characters.
+ *
+ * # Arguments
+ * `index` - The index of the variable.
+ *
+ * # Returns
+ * The identifier code.
+ */
+fn identifier(mut index: usize) -> String {
+    let mut code = String::new();
+    loop {
+        code.push((b'!' + (index % 94) as u8) as char);
+        index /= 94;
+        if index == 0 {
+            return code;
+        }
+        index -= 1;
+    }
+}
+
+impl VcdWriter {
+    /**
+     * Creates a new VcdWriter.
+     *
+     * # Returns
+     * A new VcdWriter instance.
+     */
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /**
+     * Writes the header and every value again before the next values, e.g. at the start of a
+     * new output segment. The time keeps counting from the first reading.
+     */
+    pub fn restart(&mut self) {
+        self.header_pending = true;
+        for variable in self.variables.iter_mut().flatten() {
+            variable.last = None;
+        }
+    }
+
+    /**
+     * Formats a reading as the VCD value changes at the current time, preceded by the header
+     * for the first reading.
+     *
+     * # Arguments
+     * `reading` - The reading to format.
+     *
+     * # Returns
+     * A Result containing the VCD lines without trailing newline, empty if no value changed,
+     * or an ApplicationError.
+     */
+    pub fn format_reading(&mut self, reading: &dyn Reading) -> Result<String, ApplicationError> {
+        self.format_reading_at(reading, Instant::now())
+    }
+
+    /**
+     * Formats a reading as the VCD value changes at the given time.
+     *
+     * # Arguments
+     * `reading` - The reading to format.
+     * `now` - The time of the reading.
+     *
+     * # Returns
+     * A Result containing the VCD lines without trailing newline, empty if no value changed,
+     * or an ApplicationError.
+     */
+    fn format_reading_at(&mut self, reading: &dyn Reading, now: Instant) -> Result<String, ApplicationError> {
+        let header = reading.get_csv_header()?;
+        let fields = reading.get_csv()?;
+        let variables = self.variables.get_or_insert_with(|| {
+            header
+                .iter()
+                .zip(fields.iter())
+                .filter_map(|(field, value)| match value.as_str() {
+                    "true" | "false" => Some((field, VariableKind::Wire)),
+                    value if value.parse::<f64>().is_ok() => Some((field, VariableKind::Real)),
+                    _ => None,
+                })
+                .enumerate()
+                .map(|(index, (field, kind))| Variable {
+                    field: field.clone(),
+                    kind,
+                    identifier: identifier(index),
+                    last: None,
+                })
+                .collect()
+        });
+        let mut lines = Vec::new();
+        if std::mem::take(&mut self.header_pending) || self.start.is_none() {
+            lines.push("$timescale 1 us $end".to_string());
+            lines.push("$scope module hardware_measurement $end".to_string());
+            for variable in variables.iter() {
+                let (kind, size) = match variable.kind {
+                    VariableKind::Real => ("real", 64),
+                    VariableKind::Wire => ("wire", 1),
+                };
+                lines.push(format!("$var {} {} {} {} $end", kind, size, variable.identifier, variable.field));
+            }
+            lines.push("$upscope $end".to_string());
+            lines.push("$enddefinitions $end".to_string());
+        }
+        let start = *self.start.get_or_insert(now);
+        let mut changes = Vec::new();
+        for variable in variables.iter_mut() {
+            let Some(value) = header.iter().position(|field| *field == variable.field).map(|index| &fields[index]) else {
+                continue;
+            };
+            let value = match (variable.kind, value.as_str()) {
+                (VariableKind::Wire, "true") => "1".to_string(),
+                (VariableKind::Wire, "false") => "0".to_string(),
+                (VariableKind::Wire, _) => "x".to_string(),
+                (VariableKind::Real, value) => match value.parse::<f64>() {
+                    Ok(number) => number.to_string(),
+                    // Overloads and other values without a number are left out
+                    Err(_) => continue,
+                },
+            };
+            if variable.last.as_ref() == Some(&value) {
+                continue;
+            }
+            changes.push(match variable.kind {
+                VariableKind::Real => format!("r{} {}", value, variable.identifier),
+                VariableKind::Wire => format!("{}{}", value, variable.identifier),
+            });
+            variable.last = Some(value);
+        }
+        if !changes.is_empty() {
+            lines.push(format!("#{}", now.duration_since(start).as_micros()));
+            lines.extend(changes);
+        }
+        Ok(lines.join("\n"))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::instruments::reading::KoradReading;
+
+    #[test]
+    fn test_identifier() {
+        assert_eq!(identifier(0), "!");
+        assert_eq!(identifier(93), "~");
+        assert_eq!(identifier(94), "!!");
+        assert_eq!(identifier(95), "\"!");
+    }
+
+    #[test]
+    fn test_format_readings() {
+        let mut writer = VcdWriter::new();
+        let start = Instant::now();
+        let reading = KoradReading::measured(b"05.00", b"0.100", &[0x41]);
+        assert_eq!(
+            writer.format_reading_at(&reading, start).unwrap(),
+            "$timescale 1 us $end\n$scope module hardware_measurement $end\n$var real 64 ! voltage $end\n\
+             $var real 64 \" current $end\n$var real 64 # status $end\n$var wire 1 $ output $end\n$upscope $end\n\
+             $enddefinitions $end\n#0\nr5 !\nr0.1 \"\nr65 #\n1$"
+        );
+        assert_eq!(writer.format_reading_at(&reading, start + Duration::from_millis(10)).unwrap(), "");
+        let changed = KoradReading::measured(b"05.50", b"0.100", &[0x41]);
+        assert_eq!(writer.format_reading_at(&changed, start + Duration::from_millis(20)).unwrap(), "#20000\nr5.5 !");
+        writer.restart();
+        let restarted = writer.format_reading_at(&changed, start + Duration::from_millis(30)).unwrap();
+        assert!(restarted.starts_with("$timescale 1 us $end\n"));
+        assert!(restarted.ends_with("$enddefinitions $end\n#30000\nr5.5 !\nr0.1 \"\nr65 #\n1$"));
+    }
+}
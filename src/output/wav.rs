@@ -0,0 +1,73 @@
+use crate::{error::ApplicationError, instruments::reading::Samples};
+
+/**
+ * Encodes samples as a mono 16-bit PCM WAV file. The values are scaled so that the largest
+ * magnitude reaches full scale, as audio tools expect values from -1 to 1.
+ *
+ * # Arguments
+ * `samples` - The samples.
+ *
+ * # Returns
+ * A Result containing the WAV file or an ApplicationError if the sample rate does not fit.
+ */
+pub fn encode(samples: &Samples) -> Result<Vec<u8>, ApplicationError> {
+    let sample_rate = samples.sample_rate.round();
+    if !(1.0..=u32::MAX as f64).contains(&sample_rate) {
+        return Err(ApplicationError::General(format!(
+            "Sample rate {} is not supported by WAV",
+            samples.sample_rate
+        )));
+    }
+    let sample_rate = sample_rate as u32;
+    let peak = samples.points.iter().fold(0.0f64, |peak, (_, value)| peak.max(value.abs()));
+    let scale = if peak > 0.0 { i16::MAX as f64 / peak } else { 0.0 };
+    let data_size = samples.points.len() as u32 * 2;
+    let mut wav = Vec::with_capacity(44 + data_size as usize);
+    wav.extend(b"RIFF");
+    wav.extend((36 + data_size).to_le_bytes());
+    wav.extend(b"WAVEfmt ");
+    wav.extend(16u32.to_le_bytes());
+    // PCM, mono
+    wav.extend(1u16.to_le_bytes());
+    wav.extend(1u16.to_le_bytes());
+    wav.extend(sample_rate.to_le_bytes());
+    wav.extend((sample_rate * 2).to_le_bytes());
+    wav.extend(2u16.to_le_bytes());
+    wav.extend(16u16.to_le_bytes());
+    wav.extend(b"data");
+    wav.extend(data_size.to_le_bytes());
+    for (_, value) in &samples.points {
+        wav.extend(((value * scale).round() as i16).to_le_bytes());
+    }
+    Ok(wav)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_encode() {
+        let samples = Samples {
+            sample_rate: 1e6,
+            points: vec![(0.0, 0.0), (1e-6, 2.0), (2e-6, -1.0)],
+        };
+        let wav = encode(&samples).unwrap();
+        assert_eq!(wav.len(), 44 + 6);
+        assert_eq!(&wav[..4], b"RIFF");
+        assert_eq!(u32::from_le_bytes(wav[4..8].try_into().unwrap()), 42);
+        assert_eq!(u32::from_le_bytes(wav[24..28].try_into().unwrap()), 1_000_000);
+        assert_eq!(&wav[36..40], b"data");
+        assert_eq!(i16::from_le_bytes([wav[46], wav[47]]), i16::MAX);
+        assert_eq!(i16::from_le_bytes([wav[48], wav[49]]), -16384);
+    }
+
+    #[test]
+    fn test_unsupported_sample_rate() {
+        let samples = Samples {
+            sample_rate: 1e10,
+            points: Vec::new(),
+        };
+        assert!(encode(&samples).is_err());
+    }
+}
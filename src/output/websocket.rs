@@ -0,0 +1,175 @@
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc, Mutex,
+};
+
+use futures_util::SinkExt;
+use tokio::net::{TcpListener, TcpStream};
+use tokio_tungstenite::tungstenite::Message;
+
+use crate::{arguments::SinkOverflow, error::ApplicationError, output::queue::SinkQueue};
+
+/**
+ * Connected clients with their queues, and the messages dropped for clients that disconnected.
+ */
+#[derive(Default)]
+struct Clients {
+    queues: Mutex<Vec<Arc<SinkQueue<String>>>>,
+    dropped: AtomicU64,
+}
+
+impl Clients {
+    /**
+     * Returns the queues of the connected clients.
+     *
+     * # Returns
+     * The queues.
+     */
+    fn queues(&self) -> Vec<Arc<SinkQueue<String>>> {
+        self.queues.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).clone()
+    }
+
+    /**
+     * Removes the queue of a disconnected client, keeping its count of dropped messages.
+     *
+     * # Arguments
+     * `queue` - The queue of the client.
+     */
+    fn remove(&self, queue: &Arc<SinkQueue<String>>) {
+        queue.close();
+        self.queues
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .retain(|client| !Arc::ptr_eq(client, queue));
+        self.dropped.fetch_add(queue.dropped(), Ordering::Relaxed);
+    }
+}
+
+/**
+ * WebSocket server pushing every reading as a JSON message to all connected clients.
+ * Every client has a bounded queue so a slow client does not hold up acquisition, unless
+ * --sink-overflow=block asks for it; a client that falls behind is told how many messages
+ * were dropped.
+ */
+pub struct WebSocketServer {
+    clients: Arc<Clients>,
+}
+
+impl WebSocketServer {
+    /**
+     * Binds the listener and starts accepting clients in the background.
+     *
+     * # Arguments
+     * `address` - The address to listen on, e.g. `127.0.0.1:9001`.
+     * `buffer` - Number of messages buffered per client before the overflow policy applies.
+     * `overflow` - What happens to new messages when a client's buffer is full.
+     * `max_rate` - Largest number of messages sent to a client per second, None for no limit.
+     *
+     * # Returns
+     * A Result containing the WebSocketServer or an ApplicationError.
+     */
+    pub async fn start(
+        address: &str,
+        buffer: usize,
+        overflow: SinkOverflow,
+        max_rate: Option<f64>,
+    ) -> Result<Self, ApplicationError> {
+        let listener = TcpListener::bind(address)
+            .await
+            .map_err(|e| ApplicationError::Io(format!("Could not listen on {}", address), e))?;
+        let clients = Arc::new(Clients::default());
+        let accept_clients = clients.clone();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let queue = Arc::new(SinkQueue::new(buffer, overflow, max_rate));
+                tokio::spawn(Self::serve(stream, accept_clients.clone(), queue));
+            }
+        });
+        Ok(Self { clients })
+    }
+
+    /**
+     * Publishes a JSON message to all connected clients. Messages are discarded if no client is
+     * connected. With SinkOverflow::Block this waits until every client has room.
+     *
+     * # Arguments
+     * `message` - The JSON message.
+     */
+    pub async fn publish(&self, message: String) {
+        for queue in self.clients.queues() {
+            queue.push(message.clone()).await;
+        }
+    }
+
+    /**
+     * Returns the number of messages dropped for all clients, connected or not.
+     *
+     * # Returns
+     * The number of dropped messages.
+     */
+    pub fn dropped(&self) -> u64 {
+        let connected: u64 = self.clients.queues().iter().map(|queue| queue.dropped()).sum();
+        self.clients.dropped.load(Ordering::Relaxed) + connected
+    }
+
+    /**
+     * Performs the WebSocket handshake and forwards queued messages to the client until it disconnects.
+     *
+     * # Arguments
+     * `stream` - The accepted TCP connection.
+     * `clients` - The connected clients, which the client joins after the handshake.
+     * `queue` - The client's queue.
+     */
+    async fn serve(stream: TcpStream, clients: Arc<Clients>, queue: Arc<SinkQueue<String>>) {
+        let Ok(mut websocket) = tokio_tungstenite::accept_async(stream).await else {
+            return;
+        };
+        clients.queues.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).push(queue.clone());
+        let mut reported = 0;
+        while let Some(message) = queue.take().await {
+            let dropped = queue.dropped();
+            if dropped > reported {
+                let lagged = format!("{{\"type\":\"event\",\"kind\":\"lagged\",\"dropped\":{}}}", dropped - reported);
+                reported = dropped;
+                if websocket.send(Message::Text(lagged)).await.is_err() {
+                    break;
+                }
+            }
+            if websocket.send(Message::Text(message)).await.is_err() {
+                break;
+            }
+        }
+        clients.remove(&queue);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use futures_util::StreamExt;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_lagged_client() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        drop(listener);
+        let server = WebSocketServer::start(&address, 2, SinkOverflow::DropOldest, None).await.unwrap();
+        server.publish("{\"before\":0}".into()).await;
+        let (mut client, _) = tokio_tungstenite::connect_async(format!("ws://{}", address)).await.unwrap();
+        while server.clients.queues().is_empty() {
+            tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+        }
+        // Published without yielding to the client's task, so the messages pile up in its queue
+        for value in 1..=4 {
+            server.publish(format!("{{\"value\":{}}}", value)).await;
+        }
+        let mut received = Vec::new();
+        while received.len() < 3 {
+            received.push(client.next().await.unwrap().unwrap().into_text().unwrap());
+        }
+        assert_eq!(received[0], "{\"type\":\"event\",\"kind\":\"lagged\",\"dropped\":2}");
+        assert_eq!(received[1..], ["{\"value\":3}", "{\"value\":4}"]);
+        assert_eq!(server.dropped(), 2);
+    }
+}
@@ -0,0 +1,168 @@
+use std::{io, process, sync::OnceLock};
+
+#[cfg(unix)]
+use tokio::signal::unix::{signal, Signal, SignalKind};
+use tokio::sync::watch;
+use tracing::warn;
+
+/**
+ * Exit code when a second signal ends the process without finishing the sinks.
+ */
+const FORCED_EXIT_CODE: i32 = 130;
+
+/**
+ * Set once SIGINT or SIGTERM has been received.
+ */
+static REQUESTED: OnceLock<watch::Receiver<bool>> = OnceLock::new();
+
+/**
+ * The signals requesting a shutdown, SIGINT and SIGTERM.
+ */
+#[cfg(unix)]
+struct Signals {
+    interrupt: Signal,
+    terminate: Signal,
+}
+
+#[cfg(unix)]
+impl Signals {
+    /**
+     * Installs the handlers of SIGINT and SIGTERM.
+     *
+     * # Returns
+     * A Result containing the Signals or the io::Error of a handler that could not be installed.
+     */
+    fn install() -> io::Result<Self> {
+        Ok(Self {
+            interrupt: signal(SignalKind::interrupt())?,
+            terminate: signal(SignalKind::terminate())?,
+        })
+    }
+
+    /**
+     * Waits for the next SIGINT or SIGTERM.
+     */
+    async fn recv(&mut self) {
+        tokio::select! {
+            _ = self.interrupt.recv() => {}
+            _ = self.terminate.recv() => {}
+        }
+    }
+}
+
+/**
+ * The signal requesting a shutdown, Ctrl-C, on platforms without Unix signals.
+ */
+#[cfg(not(unix))]
+struct Signals;
+
+#[cfg(not(unix))]
+impl Signals {
+    /**
+     * Installs nothing; the Ctrl-C handler is installed by the first wait.
+     *
+     * # Returns
+     * A Result containing the Signals.
+     */
+    fn install() -> io::Result<Self> {
+        Ok(Self)
+    }
+
+    /**
+     * Waits for the next Ctrl-C. Waits forever if the handler cannot be installed.
+     */
+    async fn recv(&mut self) {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            warn!("Could not install the Ctrl-C handler: {}", e);
+            std::future::pending::<()>().await;
+        }
+    }
+}
+
+/**
+ * Installs the signal handlers. The first signal requests a shutdown, which long-running modes
+ * wait for to flush and close their outputs and release the devices; the second one ends the
+ * process right away.
+ *
+ * # Returns
+ * The receiver of the shutdown request.
+ */
+fn listen() -> watch::Receiver<bool> {
+    let (sender, receiver) = watch::channel(false);
+    match Signals::install() {
+        Ok(mut signals) => {
+            tokio::spawn(async move {
+                for received in 0.. {
+                    signals.recv().await;
+                    if received > 0 {
+                        process::exit(FORCED_EXIT_CODE);
+                    }
+                    warn!("Shutting down, send the signal again to exit right away");
+                    let _ = sender.send(true);
+                }
+            });
+        }
+        Err(e) => warn!("Could not install signal handlers: {}", e),
+    }
+    receiver
+}
+
+/**
+ * Waits until a shutdown is requested on a channel. Waits forever if the sender is gone, which
+ * only happens when no signal handlers could be installed.
+ *
+ * # Arguments
+ * `receiver` - The receiver of the shutdown request.
+ */
+async fn wait(mut receiver: watch::Receiver<bool>) {
+    if receiver.wait_for(|requested| *requested).await.is_err() {
+        std::future::pending::<()>().await;
+    }
+}
+
+/**
+ * Waits until a shutdown is requested with SIGINT (Ctrl-C) or SIGTERM, or Ctrl-C on platforms
+ * without Unix signals. The handlers are installed by the first call, so signals end the process
+ * as usual until a mode waits for them; requests arriving while nobody waits are not lost.
+ */
+pub async fn requested() {
+    wait(REQUESTED.get_or_init(listen).clone()).await
+}
+
+/**
+ * Creates a shutdown request channel for tests, waited for like `requested`.
+ *
+ * # Returns
+ * The sender requesting the shutdown and the future waiting for it.
+ */
+#[cfg(test)]
+pub fn channel() -> (watch::Sender<bool>, impl std::future::Future<Output = ()>) {
+    let (sender, receiver) = watch::channel(false);
+    (sender, wait(receiver))
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_wait_for_request() {
+        let (sender, receiver) = watch::channel(false);
+        let waiting = tokio::spawn(wait(receiver.clone()));
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert!(!waiting.is_finished());
+        sender.send(true).unwrap();
+        tokio::time::timeout(Duration::from_secs(1), waiting).await.unwrap().unwrap();
+        // A request made while nobody waits is seen by a later wait
+        tokio::time::timeout(Duration::from_secs(1), wait(receiver)).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_wait_without_sender() {
+        let (sender, receiver) = watch::channel(false);
+        drop(sender);
+        assert!(tokio::time::timeout(Duration::from_millis(20), wait(receiver)).await.is_err());
+    }
+}
@@ -0,0 +1,327 @@
+use futures_util::future::join_all;
+
+use crate::{
+    arguments::Args,
+    error::ApplicationError,
+    instruments::{
+        communication::{get_communication_device, Communication},
+        reading::{EventKind, EventReading, Reading, TaggedReading},
+    },
+    workflow,
+};
+
+/**
+ * The devices of an acquisition, kept open from one acquisition to the next.
+ */
+#[derive(Default)]
+pub struct OpenDevices {
+    /**
+     * The measurement device.
+     */
+    pub instrument: Option<Box<dyn Communication>>,
+    /**
+     * The switch of a scan.
+     */
+    pub switch: Option<Box<dyn Communication>>,
+}
+
+/**
+ * Returns the open device, opening it unless it is still open from an earlier acquisition.
+ *
+ * # Arguments
+ * `args` - The arguments of the device.
+ * `device` - The open device, if any.
+ *
+ * # Returns
+ * A Result containing the device or an ApplicationError.
+ */
+pub async fn open<'a>(
+    args: &Args,
+    device: &'a mut Option<Box<dyn Communication>>,
+) -> Result<&'a dyn Communication, ApplicationError> {
+    let instrument = match device {
+        Some(instrument) => instrument,
+        None => device.insert(get_communication_device(args).await?),
+    };
+    Ok(&**instrument)
+}
+
+/**
+ * Sends the commands. With --samples the commands are sent that many times and the readings are
+ * numbered in a `sample` field.
+ *
+ * # Arguments
+ * `args` - The arguments of the device.
+ * `device` - The open device, if any.
+ *
+ * # Returns
+ * A Result containing the readings or an ApplicationError.
+ */
+async fn measure(
+    args: &Args,
+    device: &mut Option<Box<dyn Communication>>,
+) -> Result<Option<Vec<Box<dyn Reading>>>, ApplicationError> {
+    let instrument = open(args, device).await?;
+    let Some(samples) = args.measurement.samples else {
+        return instrument.command(args.control.commands.clone()).await;
+    };
+    if samples == 0 {
+        return Err(ApplicationError::Config("--samples needs at least one sample".into()));
+    }
+    let mut readings: Vec<Box<dyn Reading>> = Vec::new();
+    for sample in 1..=samples {
+        let sample_readings = instrument.command(args.control.commands.clone()).await?;
+        readings.extend(sample_readings.unwrap_or_default().into_iter().map(|reading| {
+            Box::new(TaggedReading::new(vec![("sample".into(), sample.to_string())], reading)) as Box<dyn Reading>
+        }));
+    }
+    Ok(Some(readings))
+}
+
+/**
+ * Runs the scan, the script or the commands on the devices, opening the devices that are not
+ * still open from an earlier acquisition. The devices are closed when the acquisition fails, so
+ * the next attempt opens them again.
+ *
+ * # Arguments
+ * `args` - The arguments of the device.
+ * `opened` - The open devices.
+ *
+ * # Returns
+ * A Result containing the readings or an ApplicationError.
+ */
+async fn run(args: &Args, opened: &mut OpenDevices) -> Result<Option<Vec<Box<dyn Reading>>>, ApplicationError> {
+    let result = if !args.sequence.scan_channels.is_empty() {
+        workflow::scan::run(args, &mut opened.switch, &mut opened.instrument).await
+    } else if args.control.script.is_some() {
+        workflow::script::run(args, &mut opened.instrument).await
+    } else {
+        measure(args, &mut opened.instrument).await
+    };
+    if result.is_err() {
+        *opened = OpenDevices::default();
+    }
+    result
+}
+
+/**
+ * Runs the commands, retrying and reporting events as configured.
+ *
+ * # Arguments
+ * `args` - The arguments of the device.
+ * `opened` - The open devices, kept open for the next acquisition.
+ * `events` - Event records are appended here when events are enabled.
+ *
+ * # Returns
+ * A Result containing the readings or the ApplicationError of the last attempt.
+ */
+pub async fn acquire(
+    args: &Args,
+    opened: &mut OpenDevices,
+    events: &mut Vec<Box<dyn Reading>>,
+) -> Result<Vec<Box<dyn Reading>>, ApplicationError> {
+    let mut attempt = 0;
+    loop {
+        match run(args, opened).await {
+            Ok(readings) => return Ok(readings.unwrap_or_default()),
+            Err(e) => {
+                if args.control.events {
+                    events.push(Box::new(EventReading::new(EventKind::Error, e.to_string())));
+                }
+                if attempt >= args.control.retries {
+                    return Err(e);
+                }
+                attempt += 1;
+                if args.control.events {
+                    events.push(Box::new(EventReading::new(
+                        EventKind::Retry,
+                        format!("Attempt {} of {}", attempt, args.control.retries),
+                    )));
+                    events.push(Box::new(EventReading::new(
+                        EventKind::Reconnect,
+                        format!("Reopening {:?}", args.connection.device),
+                    )));
+                }
+            }
+        }
+    }
+}
+
+/**
+ * Acquires from all devices concurrently, so the readings are taken as close to simultaneously
+ * as possible. With several devices a failing device does not stop the others; its error is
+ * reported as an event record labelled with the device. With --metadata every record also
+ * carries the provenance of its device.
+ *
+ * # Arguments
+ * `devices` - The device labels (None for a single device) with their arguments.
+ * `opened` - The open devices in device order, kept open across acquisitions.
+ *
+ * # Returns
+ * The event records and readings of all devices in device order, and the ApplicationError of a
+ * single device that failed.
+ */
+pub async fn acquire_all(
+    devices: &[(Option<String>, Args)],
+    opened: &mut [OpenDevices],
+) -> (Vec<Box<dyn Reading>>, Option<ApplicationError>) {
+    let results = join_all(devices.iter().zip(opened.iter_mut()).map(|((label, args), opened)| async move {
+        let mut events = Vec::new();
+        let result = acquire(args, opened, &mut events).await;
+        let metadata = args.measurement.metadata.then(|| {
+            workflow::metadata::metadata(args, opened.instrument.as_ref().and_then(|device| device.device_info()))
+        });
+        (label, args, events, result, metadata)
+    }))
+    .await;
+    let mut readings: Vec<Box<dyn Reading>> = Vec::new();
+    let mut error = None;
+    for (label, args, mut events, result, metadata) in results {
+        match result {
+            Ok(device_readings) => events.extend(device_readings),
+            Err(e) if devices.len() > 1 => {
+                if !args.control.events {
+                    events.push(Box::new(EventReading::new(EventKind::Error, e.to_string())));
+                }
+            }
+            Err(e) => error = Some(e),
+        }
+        readings.extend(events.into_iter().map(|reading| match label {
+            Some(label) => Box::new(TaggedReading::new(vec![("device".into(), label.clone())], workflow::metadata::tag(&metadata, reading))),
+            None => workflow::metadata::tag(&metadata, reading),
+        }));
+    }
+    (readings, error)
+}
+
+#[cfg(all(test, feature = "network", feature = "modbus"))]
+mod test {
+    use std::{cell::RefCell, rc::Rc};
+
+    use async_trait::async_trait;
+
+    use super::*;
+    use crate::instruments::communication::LoopbackModbus;
+
+    /**
+     * Switch logging the commands it receives.
+     */
+    struct Switch(Rc<RefCell<Vec<String>>>);
+
+    #[async_trait(?Send)]
+    impl Communication for Switch {
+        async fn command(&self, commands: Vec<String>) -> Result<Option<Vec<Box<dyn Reading>>>, ApplicationError> {
+            self.0.borrow_mut().extend(commands);
+            Ok(None)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_device_kept_open() {
+        let server = LoopbackModbus::start();
+        let args = server.args(&["--command", "Measure", "--init", "Measure"]);
+        let mut opened = OpenDevices::default();
+        for _ in 0..3 {
+            assert_eq!(acquire(&args, &mut opened, &mut Vec::new()).await.unwrap().len(), 1);
+        }
+        assert_eq!(server.connections(), 1);
+        // The initialization commands run once, when the device is opened
+        assert_eq!(server.requests(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_device_reopened_after_failure() {
+        let server = LoopbackModbus::start();
+        let args = server.args(&["--command", "Measure"]);
+        let mut opened = OpenDevices::default();
+        acquire(&args, &mut opened, &mut Vec::new()).await.unwrap();
+        let failing = server.args(&["--command", "Read:current"]);
+        assert!(acquire(&failing, &mut opened, &mut Vec::new()).await.is_err());
+        assert!(opened.instrument.is_none());
+        acquire(&args, &mut opened, &mut Vec::new()).await.unwrap();
+        assert_eq!(server.connections(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_samples() {
+        let server = LoopbackModbus::start();
+        let args = server.args(&["--command", "Measure", "--samples=3"]);
+        let readings = acquire(&args, &mut OpenDevices::default(), &mut Vec::new()).await.unwrap();
+        let samples: Vec<String> = readings.iter().map(|reading| reading.get_csv().unwrap()[0].clone()).collect();
+        assert_eq!(samples, ["1", "2", "3"]);
+        assert_eq!(server.requests(), 3);
+        assert_eq!(server.connections(), 1);
+
+        let args = server.args(&["--command", "Measure", "--samples=0"]);
+        let result = acquire(&args, &mut OpenDevices::default(), &mut Vec::new()).await;
+        assert!(matches!(result, Err(ApplicationError::Config(_))));
+    }
+
+    #[tokio::test]
+    async fn test_retries_with_events() {
+        let server = LoopbackModbus::start();
+        let args = server.args(&["--command", "Read:current", "--retries=1", "--events"]);
+        let mut events = Vec::new();
+        assert!(acquire(&args, &mut OpenDevices::default(), &mut events).await.is_err());
+        let kinds: Vec<String> = events.iter().map(|event| event.get_csv().unwrap()[2].clone()).collect();
+        assert_eq!(kinds, ["error", "retry", "reconnect", "error"]);
+    }
+
+    #[tokio::test]
+    async fn test_acquire_all_isolates_failures() {
+        let server = LoopbackModbus::start();
+        let devices = vec![
+            (Some("meter".to_string()), server.args(&["--command", "Measure"])),
+            (Some("broken".to_string()), server.args(&["--command", "Read:current"])),
+        ];
+        let mut opened: Vec<OpenDevices> = devices.iter().map(|_| OpenDevices::default()).collect();
+        let (readings, error) = acquire_all(&devices, &mut opened).await;
+        assert!(error.is_none());
+        assert_eq!(readings.len(), 2);
+        assert_eq!(readings[0].get_csv().unwrap()[..2], ["meter", "0"]);
+        let event = readings[1].get_csv().unwrap();
+        assert_eq!([&event[0], &event[1], &event[3]], ["broken", "event", "error"]);
+        assert!(event[4].contains("No register current"));
+        assert!(opened[0].instrument.is_some());
+        assert!(opened[1].instrument.is_none());
+
+        // A single device reports its error instead of an event
+        let (readings, error) = acquire_all(&devices[1..], &mut opened[1..]).await;
+        assert!(readings.is_empty());
+        assert!(matches!(error, Some(ApplicationError::Command(_))));
+    }
+
+    #[tokio::test]
+    async fn test_script_kept_open() {
+        let path = std::env::temp_dir().join(format!("hm-acquire-script-{}", std::process::id()));
+        std::fs::write(&path, "Measure\nlog measured\nMeasure\n").unwrap();
+        let server = LoopbackModbus::start();
+        let script = format!("--script={}", path.display());
+        let args = server.args(&[script.as_str()]);
+        let mut opened = OpenDevices::default();
+        for _ in 0..3 {
+            assert_eq!(acquire(&args, &mut opened, &mut Vec::new()).await.unwrap().len(), 3);
+        }
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(server.connections(), 1);
+        assert_eq!(server.requests(), 6);
+    }
+
+    #[tokio::test]
+    async fn test_scan_kept_open() {
+        let server = LoopbackModbus::start();
+        let args = server.args(&["--command", "Measure", "--scan-channels=1,2", "--switch-device=hid-relay"]);
+        let switched = Rc::new(RefCell::new(Vec::new()));
+        let mut opened = OpenDevices {
+            instrument: None,
+            switch: Some(Box::new(Switch(switched.clone()))),
+        };
+        for _ in 0..2 {
+            assert_eq!(acquire(&args, &mut opened, &mut Vec::new()).await.unwrap().len(), 2);
+        }
+        assert_eq!(server.connections(), 1);
+        assert_eq!(switched.borrow()[..5], ["Open:all", "Close:1", "Open:all", "Close:2", "Open:all"]);
+        // Both scans used the switch that was open before the first one
+        assert_eq!(switched.borrow().len(), 10);
+    }
+}
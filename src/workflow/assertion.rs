@@ -0,0 +1,234 @@
+use tracing::warn;
+
+use crate::{error::ApplicationError, instruments::reading::Reading};
+
+/**
+ * Field of the typed measurement value, in the base unit of the quantity.
+ */
+const VALUE_FIELD: &str = "value";
+
+/**
+ * A bound on a field of the readings, e.g. `value within 4.95..5.05`. Either end of the range
+ * may be left out, e.g. `current within ..0.5`.
+ */
+#[derive(Debug, Clone, PartialEq)]
+struct Assertion {
+    /**
+     * The assertion as given, used in messages.
+     */
+    text: String,
+    field: String,
+    low: Option<f64>,
+    high: Option<f64>,
+}
+
+impl Assertion {
+    /**
+     * Parses an assertion.
+     *
+     * # Arguments
+     * `text` - The assertion, `<field> within <low>..<high>`.
+     *
+     * # Returns
+     * A Result containing the Assertion or an ApplicationError if it is invalid.
+     */
+    fn parse(text: &str) -> Result<Self, ApplicationError> {
+        let invalid = || ApplicationError::Config(format!("Expected <field> within <low>..<high>, got {}", text));
+        let (field, range) = text.trim().split_once(" within ").ok_or_else(invalid)?;
+        let (low, high) = range.trim().split_once("..").ok_or_else(invalid)?;
+        let bound = |bound: &str| match bound.trim() {
+            "" => Ok(None),
+            bound => bound.parse::<f64>().map(Some).map_err(|_| invalid()),
+        };
+        let assertion = Self {
+            text: text.trim().to_string(),
+            field: field.trim().to_string(),
+            low: bound(low)?,
+            high: bound(high)?,
+        };
+        if assertion.field.is_empty() || (assertion.low.is_none() && assertion.high.is_none()) {
+            return Err(invalid());
+        }
+        Ok(assertion)
+    }
+
+    /**
+     * Gets the value of the field of a reading. `value` is the typed measurement value if the
+     * reading has one; other fields are CSV fields.
+     *
+     * # Arguments
+     * `reading` - The reading.
+     *
+     * # Returns
+     * The value, or None if the reading has no numeric value for the field.
+     */
+    fn value(&self, reading: &dyn Reading) -> Option<f64> {
+        if self.field == VALUE_FIELD {
+            if let Some(measurement) = reading.get_measurement() {
+                return Some(measurement.value);
+            }
+        }
+        let (header, fields) = (reading.get_csv_header().ok()?, reading.get_csv().ok()?);
+        let index = header.iter().position(|name| *name == self.field)?;
+        fields.get(index)?.trim().parse::<f64>().ok()
+    }
+
+    /**
+     * Checks if a value is within the bounds.
+     *
+     * # Arguments
+     * `value` - The value.
+     *
+     * # Returns
+     * A boolean indicating whether the value is within the bounds.
+     */
+    fn holds(&self, value: f64) -> bool {
+        self.low.is_none_or(|low| value >= low) && self.high.is_none_or(|high| value <= high)
+    }
+}
+
+/**
+ * The --assert bounds of a run. A reading violating a bound raises an alarm: the hook command,
+ * if any, is run and the run fails. The run also fails if no reading had the field of an
+ * assertion, so a misspelled field does not pass silently.
+ */
+#[derive(Default)]
+pub struct Assertions {
+    assertions: Vec<Assertion>,
+    /**
+     * Whether a reading had the field of each assertion.
+     */
+    checked: Vec<bool>,
+    hook: Option<String>,
+}
+
+impl Assertions {
+    /**
+     * Parses the assertions.
+     *
+     * # Arguments
+     * `assertions` - The --assert arguments.
+     * `hook` - The command run when an assertion is violated.
+     *
+     * # Returns
+     * A Result containing the Assertions or an ApplicationError if an assertion is invalid.
+     */
+    pub fn new(assertions: &[String], hook: Option<String>) -> Result<Self, ApplicationError> {
+        let assertions = assertions
+            .iter()
+            .map(|assertion| Assertion::parse(assertion))
+            .collect::<Result<Vec<Assertion>, ApplicationError>>()?;
+        Ok(Self {
+            checked: vec![false; assertions.len()],
+            assertions,
+            hook,
+        })
+    }
+
+    /**
+     * Checks a reading against the assertions on its fields.
+     *
+     * # Arguments
+     * `reading` - The reading.
+     *
+     * # Returns
+     * The violation, e.g. `value 5.12 violates value within 4.95..5.05`, or None.
+     */
+    pub fn check(&mut self, reading: &dyn Reading) -> Option<String> {
+        for (assertion, checked) in self.assertions.iter().zip(self.checked.iter_mut()) {
+            let Some(value) = assertion.value(reading) else {
+                continue;
+            };
+            *checked = true;
+            if !assertion.holds(value) {
+                return Some(format!("{} {} violates {}", assertion.field, value, assertion.text));
+            }
+        }
+        None
+    }
+
+    /**
+     * Raises an alarm: runs the hook command with the violation in `HM_ALARM`.
+     *
+     * # Arguments
+     * `violation` - The violation.
+     *
+     * # Returns
+     * The error failing the run.
+     */
+    pub async fn alarm(&self, violation: &str) -> ApplicationError {
+        if let Some(hook) = &self.hook {
+            let status = tokio::process::Command::new("sh")
+                .arg("-c")
+                .arg(hook)
+                .env("HM_ALARM", violation)
+                .status()
+                .await;
+            match status {
+                Ok(status) if status.success() => {}
+                Ok(status) => warn!("Alarm hook {} failed with {}", hook, status),
+                Err(e) => warn!("Could not run alarm hook {}: {}", hook, e),
+            }
+        }
+        ApplicationError::Assertion(violation.to_string())
+    }
+
+    /**
+     * Checks that every assertion was checked against a reading.
+     *
+     * # Returns
+     * A Result indicating success or an ApplicationError naming an assertion no reading had
+     * the field of.
+     */
+    pub fn finish(&self) -> Result<(), ApplicationError> {
+        match self.assertions.iter().zip(&self.checked).find(|(_, checked)| !**checked) {
+            Some((assertion, _)) => Err(ApplicationError::Assertion(format!(
+                "No reading had a numeric {} to check {}",
+                assertion.field, assertion.text
+            ))),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::instruments::{command::XdmFunction, reading::OwonXdmReading};
+
+    #[test]
+    fn test_parse() {
+        let assertion = Assertion::parse("value within 4.95..5.05").unwrap();
+        assert_eq!((assertion.field.as_str(), assertion.low, assertion.high), ("value", Some(4.95), Some(5.05)));
+        assert_eq!(Assertion::parse("current within ..0.5").unwrap().low, None);
+        assert!(Assertion::parse("value within ..").is_err());
+        assert!(Assertion::parse("value 4.95..5.05").is_err());
+        assert!(Assertion::parse("value within 4.95-5.05").is_err());
+        assert!(Assertion::parse(" within 1..2").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_check() {
+        let mut assertions = Assertions::new(&["value within 4.95..5.05".into(), "overload within ..0".into()], None).unwrap();
+        let reading = OwonXdmReading::parse(XdmFunction::VoltageDc, b"5.01E+00\n").unwrap();
+        assert_eq!(assertions.check(&reading), None);
+        let reading = OwonXdmReading::parse(XdmFunction::VoltageDc, b"5.12E+00\n").unwrap();
+        let violation = assertions.check(&reading).unwrap();
+        assert_eq!(violation, "value 5.12 violates value within 4.95..5.05");
+        // The overload field is not numeric
+        assert!(assertions.finish().is_err());
+        assert_eq!(assertions.alarm(&violation).await.to_string(), "Assertion Error: value 5.12 violates value within 4.95..5.05");
+    }
+
+    #[tokio::test]
+    async fn test_alarm_hook() {
+        let directory = std::env::temp_dir().join(format!("hm-alarm-{}", std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+        let path = directory.join("alarm");
+        let hook = format!("printf %s \"$HM_ALARM\" > {}", path.display());
+        let assertions = Assertions::new(&[], Some(hook)).unwrap();
+        assertions.alarm("value 5.12 violates value within 4.95..5.05").await;
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "value 5.12 violates value within 4.95..5.05");
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+}
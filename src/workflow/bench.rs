@@ -0,0 +1,160 @@
+use std::time::{Duration, Instant};
+
+use crate::{
+    arguments::Args,
+    error::ApplicationError,
+    instruments::communication::{common::get_communication_device, registry::driver},
+    shutdown,
+};
+
+/**
+ * Percentiles of the round trip times in the summary.
+ */
+const PERCENTILES: [u8; 3] = [50, 90, 99];
+
+/**
+ * Gets the query of the benchmark: the first --command, or the identity query of the device,
+ * or Measure for devices without one.
+ *
+ * # Arguments
+ * `args` - The arguments of the device.
+ *
+ * # Returns
+ * A Result containing the query or an ApplicationError if no device is given.
+ */
+fn query(args: &Args) -> Result<String, ApplicationError> {
+    if let Some(command) = args.control.commands.first() {
+        return Ok(command.clone());
+    }
+    let device = args.connection.device.as_ref().ok_or_else(|| ApplicationError::Config("No device given".into()))?;
+    Ok(driver(device)?.identify.unwrap_or("Measure").to_string())
+}
+
+/**
+ * Gets a percentile of sorted round trip times with the nearest rank method.
+ *
+ * # Arguments
+ * `sorted` - The round trip times in ascending order, not empty.
+ * `percentile` - The percentile, 1 to 100.
+ *
+ * # Returns
+ * The round trip time.
+ */
+fn percentile(sorted: &[Duration], percentile: u8) -> Duration {
+    let rank = (sorted.len() * percentile as usize).div_ceil(100).max(1);
+    sorted[rank - 1]
+}
+
+/**
+ * Formats a duration in milliseconds.
+ *
+ * # Arguments
+ * `duration` - The duration.
+ *
+ * # Returns
+ * The milliseconds with three decimals.
+ */
+fn milliseconds(duration: Duration) -> String {
+    format!("{:.3} ms", duration.as_secs_f64() * 1000.0)
+}
+
+/**
+ * Formats the results of a benchmark, e.g.
+ * `latency: min 1.234 ms, p50 1.500 ms, p90 1.900 ms, p99 2.100 ms, max 2.345 ms`.
+ *
+ * # Arguments
+ * `latencies` - The round trip time of each counted query.
+ * `elapsed` - The time the counted queries took together.
+ *
+ * # Returns
+ * The lines of the summary.
+ */
+fn summary(mut latencies: Vec<Duration>, elapsed: Duration) -> Vec<String> {
+    latencies.sort();
+    let (Some(min), Some(max)) = (latencies.first(), latencies.last()) else {
+        return vec!["count: 0".to_string()];
+    };
+    let percentiles: Vec<String> = PERCENTILES
+        .iter()
+        .map(|p| format!("p{} {}", p, milliseconds(percentile(&latencies, *p))))
+        .collect();
+    vec![
+        format!("count: {}", latencies.len()),
+        format!("latency: min {}, {}, max {}", milliseconds(*min), percentiles.join(", "), milliseconds(*max)),
+        format!("rate: {:.1} samples/s", latencies.len() as f64 / elapsed.as_secs_f64().max(f64::EPSILON)),
+    ]
+}
+
+/**
+ * Benchmarks the connection to a device: opens it, sends a cheap query, by default `*IDN?` or
+ * Measure, as often as --bench or --count says, back to back, and reports the round trip
+ * percentiles and the sample rate the device sustains. The first query is reported apart, as
+ * it may include setting up the transfers. A shutdown request ends the benchmark early.
+ *
+ * # Arguments
+ * `args` - The arguments of the device.
+ * `count` - The number of queries to count.
+ *
+ * # Returns
+ * A Result containing the lines to print or an ApplicationError if a query fails.
+ */
+pub async fn run(args: &Args, count: usize) -> Result<Vec<String>, ApplicationError> {
+    if count == 0 {
+        return Err(ApplicationError::Config("The benchmark needs at least one query".into()));
+    }
+    let query = query(args)?;
+    let start = Instant::now();
+    let device = get_communication_device(args).await?;
+    let mut lines = vec![format!("query: {}", query), format!("open: {}", milliseconds(start.elapsed()))];
+    let start = Instant::now();
+    device.command(vec![query.clone()]).await?;
+    lines.push(format!("first: {}", milliseconds(start.elapsed())));
+    let mut latencies = Vec::with_capacity(count);
+    let start = Instant::now();
+    for _ in 0..count {
+        let sent = Instant::now();
+        tokio::select! {
+            biased;
+            _ = shutdown::requested() => break,
+            result = device.command(vec![query.clone()]) => result?,
+        };
+        latencies.push(sent.elapsed());
+    }
+    lines.extend(summary(latencies, start.elapsed()));
+    Ok(lines)
+}
+
+#[cfg(test)]
+mod test {
+    use clap::Parser;
+
+    use super::*;
+
+    #[test]
+    fn test_query() {
+        let args = |line: &[&str]| Args::parse_from([&["hardware-measurement"], line].concat());
+        assert_eq!(query(&args(&["--device=generic-scpi-usb"])).unwrap(), "*IDN?");
+        assert_eq!(query(&args(&["--device=hid-relay"])).unwrap(), "Measure");
+        assert_eq!(query(&args(&["--device=generic-scpi-usb", "--command=MEAS:VOLT?"])).unwrap(), "MEAS:VOLT?");
+    }
+
+    #[test]
+    fn test_summary() {
+        let latencies: Vec<Duration> = (1..=10).rev().map(Duration::from_millis).collect();
+        assert_eq!(
+            summary(latencies, Duration::from_millis(55)),
+            [
+                "count: 10",
+                "latency: min 1.000 ms, p50 5.000 ms, p90 9.000 ms, p99 10.000 ms, max 10.000 ms",
+                "rate: 181.8 samples/s",
+            ]
+        );
+        assert_eq!(summary(Vec::new(), Duration::ZERO), ["count: 0"]);
+    }
+
+    #[tokio::test]
+    async fn test_no_queries() {
+        let args = Args::parse_from(["hardware-measurement", "--device=generic-scpi-usb"]);
+        assert!(matches!(run(&args, 0).await, Err(ApplicationError::Config(_))));
+    }
+}
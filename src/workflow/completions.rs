@@ -0,0 +1,73 @@
+use std::io::Write;
+
+use clap_complete::Shell;
+
+use crate::{arguments::Args, error::ApplicationError};
+
+/**
+ * Name of the binary the completions and the man page are written for.
+ */
+const BINARY_NAME: &str = "hardware-measurement";
+
+/**
+ * Writes the completion script of a shell. The values of devices, readers, formats and the other
+ * enums complete as well as the options and subcommands.
+ *
+ * # Arguments
+ * `shell` - The shell.
+ * `output` - Where the script is written, stdout when run.
+ *
+ * # Returns
+ * A Result indicating success or an ApplicationError if the script cannot be written.
+ */
+pub fn write_completions(shell: Shell, output: &mut dyn Write) -> Result<(), ApplicationError> {
+    // The generator panics on write errors, so the script is written once it is complete
+    let mut script = Vec::new();
+    clap_complete::generate(shell, &mut Args::command_with_modes(), BINARY_NAME, &mut script);
+    output
+        .write_all(&script)
+        .map_err(|e| ApplicationError::Io("Failed to write the completion script".into(), e))
+}
+
+/**
+ * Writes the man page in roff format, with the options and subcommands of --help.
+ *
+ * # Arguments
+ * `output` - Where the page is written, stdout when run.
+ *
+ * # Returns
+ * A Result indicating success or an ApplicationError if the page cannot be written.
+ */
+pub fn write_man_page(output: &mut dyn Write) -> Result<(), ApplicationError> {
+    clap_mangen::Man::new(Args::command_with_modes().name(BINARY_NAME))
+        .render(output)
+        .map_err(|e| ApplicationError::Io("Failed to write the man page".into(), e))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_completions_list_values() {
+        let mut script = Vec::new();
+        write_completions(Shell::Bash, &mut script).unwrap();
+        let script = String::from_utf8(script).unwrap();
+        assert!(script.contains("--device"));
+        assert!(script.contains("keysight-dmm-serial"));
+        assert!(script.contains("monitor"));
+        let mut script = Vec::new();
+        write_completions(Shell::Fish, &mut script).unwrap();
+        assert!(String::from_utf8(script).unwrap().contains("complete -c hardware-measurement"));
+    }
+
+    #[test]
+    fn test_man_page() {
+        let mut page = Vec::new();
+        write_man_page(&mut page).unwrap();
+        let page = String::from_utf8(page).unwrap();
+        assert!(page.starts_with(".ie"));
+        assert!(page.contains(".TH hardware-measurement 1"));
+        assert!(page.contains("\\-\\-interval\\-ms"));
+    }
+}
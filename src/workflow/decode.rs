@@ -0,0 +1,95 @@
+use tracing::warn;
+
+use crate::{
+    arguments::Args,
+    error::ApplicationError,
+    instruments::{command::raw::parse_hex, communication::decode_frame},
+    output::{
+        shape::{with_shape, FieldShape},
+        Printer,
+    },
+};
+
+/**
+ * Parses a line of a capture file: a frame as written by `--format=raw`, e.g. `[2, 48, 32]`,
+ * or as hex digits, e.g. `02 30 20` as in the debug log. Empty lines and lines starting with
+ * `#` are skipped.
+ *
+ * # Arguments
+ * `line` - The line.
+ *
+ * # Returns
+ * A Result containing the frame, None for lines without one, or an ApplicationError.
+ */
+fn parse_frame(line: &str) -> Result<Option<Vec<u8>>, ApplicationError> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return Ok(None);
+    }
+    let Some(list) = line.strip_prefix('[').and_then(|line| line.strip_suffix(']')) else {
+        return parse_hex(line).map(Some);
+    };
+    if list.trim().is_empty() {
+        return Ok(Some(Vec::new()));
+    }
+    list.split(',')
+        .map(|byte| {
+            byte.trim()
+                .parse::<u8>()
+                .map_err(|e| ApplicationError::ParseInt(format!("Invalid byte {}", byte.trim()), e))
+        })
+        .collect::<Result<Vec<u8>, ApplicationError>>()
+        .map(Some)
+}
+
+/**
+ * Decodes the frames of a capture file as the device would have when reading them live and
+ * prints the readings. Frames the device reading rejects are skipped with a warning, as they
+ * are when recording.
+ *
+ * # Arguments
+ * `devices` - The device with its arguments.
+ * `printer` - Prints the readings.
+ * `shape` - The field shape of the output, if any.
+ *
+ * # Returns
+ * A Result indicating success, or an ApplicationError if the file cannot be read or has a line
+ * that is not a frame.
+ */
+pub fn run(devices: &[(Option<String>, Args)], printer: &mut Printer, shape: Option<&FieldShape>) -> Result<(), ApplicationError> {
+    let [(_, args)] = devices else {
+        return Err(ApplicationError::Config("--decode takes a single device".into()));
+    };
+    let path = args.decode.as_deref().unwrap_or_default();
+    let capture =
+        std::fs::read_to_string(path).map_err(|e| ApplicationError::Io(format!("Could not read capture {}", path), e))?;
+    for (index, line) in capture.lines().enumerate() {
+        let frame = parse_frame(line)
+            .map_err(|e| ApplicationError::Config(format!("{} line {}: {}", path, index + 1, e)))?;
+        let Some(frame) = frame else {
+            continue;
+        };
+        match decode_frame(args, frame) {
+            Ok(reading) => with_shape(reading.as_ref(), shape, |reading| printer.print(reading))?,
+            Err(e @ ApplicationError::Config(_)) => return Err(e),
+            Err(e) => warn!("Skipping {} line {}: {}", path, index + 1, e),
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_frame() {
+        assert_eq!(parse_frame("[2, 48, 32]").unwrap(), Some(vec![2, 48, 32]));
+        assert_eq!(parse_frame("[]").unwrap(), Some(Vec::new()));
+        assert_eq!(parse_frame("2a 49 44 4e 3f 0a").unwrap(), Some(b"*IDN?\n".to_vec()));
+        assert_eq!(parse_frame("  # UT161D, DCV").unwrap(), None);
+        assert_eq!(parse_frame("").unwrap(), None);
+        assert!(parse_frame("[2, 256]").is_err());
+        assert!(parse_frame("not hex").is_err());
+    }
+}
@@ -0,0 +1,139 @@
+use crate::{
+    arguments::Args,
+    config::Config,
+    error::ApplicationError,
+    instruments::communication::{
+        common::DeviceInfo,
+        get_communication_device,
+        registry::{driver, drivers, Driver},
+    },
+};
+
+/**
+ * Describes what a driver needs and can do, e.g. `korad-psu  --serial  measure, drive an output`.
+ *
+ * # Arguments
+ * `driver` - The driver.
+ *
+ * # Returns
+ * The device name, the options it cannot be opened without and its capabilities.
+ */
+fn describe(driver: &Driver) -> (String, String, String) {
+    let requires = match driver.requires {
+        [] => "-".to_string(),
+        requires => requires.iter().map(|requirement| requirement.option()).collect::<Vec<&str>>().join(" "),
+    };
+    (driver.name(), requires, driver.capabilities.descriptions().join(", "))
+}
+
+/**
+ * Lists the supported devices, and the profiles and groups of the configuration file.
+ *
+ * # Arguments
+ * `config` - The configuration file.
+ *
+ * # Returns
+ * The lines to print.
+ */
+pub fn list(config: &Config) -> Vec<String> {
+    let mut lines: Vec<String> = drivers()
+        .iter()
+        .map(|driver| {
+            let (name, requires, capabilities) = describe(driver);
+            format!("device {:<20} {:<9} {}", name, requires, capabilities)
+        })
+        .collect();
+    let mut profiles: Vec<(&String, String)> = config
+        .profiles
+        .iter()
+        .map(|(name, profile)| (name, profile.device.as_ref().and_then(|device| driver(device).ok()).map(Driver::name).unwrap_or_default()))
+        .collect();
+    profiles.sort();
+    lines.extend(profiles.into_iter().map(|(name, device)| format!("profile {} {}", name, device).trim_end().to_string()));
+    let mut groups: Vec<(&String, &Vec<String>)> = config.groups.iter().collect();
+    groups.sort();
+    lines.extend(groups.into_iter().map(|(name, members)| format!("group {} {}", name, members.join(", "))));
+    lines
+}
+
+/**
+ * Lists the USB descriptor strings the hardware has, e.g. `serial number: 0001`.
+ *
+ * # Arguments
+ * `device_info` - The descriptor strings.
+ *
+ * # Returns
+ * A line per descriptor string.
+ */
+fn descriptors(device_info: DeviceInfo) -> Vec<String> {
+    [
+        ("manufacturer", device_info.manufacturer),
+        ("product", device_info.product),
+        ("serial number", device_info.serial_number),
+    ]
+    .into_iter()
+    .filter_map(|(name, value)| value.map(|value| format!("{}: {}", name, value)))
+    .collect()
+}
+
+/**
+ * Describes the device of the arguments and, if the options it needs are given, opens it and
+ * reads the manufacturer, product and serial number of the hardware and, if it has an identity
+ * query such as `*IDN?`, its identity.
+ *
+ * # Arguments
+ * `args` - The arguments of the device.
+ *
+ * # Returns
+ * A Result containing the lines to print or an ApplicationError if the device could not be read.
+ */
+pub async fn info(args: &Args) -> Result<Vec<String>, ApplicationError> {
+    let device = args.connection.device.as_ref().ok_or_else(|| ApplicationError::Config("No device given".into()))?;
+    let driver = driver(device)?;
+    let (name, requires, capabilities) = describe(driver);
+    let mut lines = vec![format!("device: {}", name), format!("requires: {}", requires), format!("capabilities: {}", capabilities)];
+    if !driver.requires.iter().all(|requirement| requirement.argument(args).is_ok()) {
+        return Ok(lines);
+    }
+    let device = get_communication_device(args).await?;
+    lines.extend(descriptors(device.device_info().unwrap_or_default()));
+    if let Some(identify) = driver.identify {
+        for reading in device.command(vec![identify.to_string()]).await?.unwrap_or_default() {
+            lines.push(format!("identity: {}", reading.get_raw_string()?.trim()));
+        }
+    }
+    Ok(lines)
+}
+
+#[cfg(test)]
+mod test {
+    use clap::Parser;
+
+    use super::*;
+
+    #[test]
+    fn test_list() {
+        let config = Config::parse("[profiles.psu]\ndevice = \"korad-psu\"\n[groups]\nbench = [\"psu\"]\n").unwrap();
+        let lines = list(&config);
+        assert!(lines.contains(&"device korad-psu            --serial  measure, drive an output".to_string()));
+        assert!(lines.contains(&"device audio-in             -         measure".to_string()));
+        assert_eq!(lines[lines.len() - 2..], ["profile psu korad-psu", "group bench psu"]);
+    }
+
+    #[tokio::test]
+    async fn test_info_without_connection() {
+        let args = Args::parse_from(["hardware-measurement", "--device=hid-relay"]);
+        assert_eq!(info(&args).await.unwrap(), ["device: hid-relay", "requires: --hid", "capabilities: switch channels"]);
+    }
+
+    #[test]
+    fn test_descriptors() {
+        let device_info = DeviceInfo {
+            manufacturer: Some("Silicon Labs".into()),
+            product: None,
+            serial_number: Some("0001".into()),
+        };
+        assert_eq!(descriptors(device_info), ["manufacturer: Silicon Labs", "serial number: 0001"]);
+        assert!(descriptors(DeviceInfo::default()).is_empty());
+    }
+}
@@ -0,0 +1,217 @@
+use std::{pin::pin, time::Duration};
+
+use tokio::time::Instant;
+
+use crate::{
+    arguments::Args,
+    error::ApplicationError,
+    instruments::{
+        communication::{get_communication_device, Communication},
+        reading::{DischargeReading, Quantity, Reading, TaggedReading},
+    },
+    output::{
+        shape::{with_shape, FieldShape},
+        Printer,
+    },
+    shutdown,
+};
+
+/**
+ * A device taking part in a discharge test.
+ */
+struct DischargeDevice {
+    label: Option<String>,
+    device: Box<dyn Communication>,
+    commands: Vec<String>,
+}
+
+/**
+ * Gets the voltage of a reading: the typed measurement if it is a voltage, else the `voltage`
+ * field, e.g. the readback of a power supply.
+ *
+ * # Arguments
+ * `reading` - The reading.
+ *
+ * # Returns
+ * A Result containing the voltage in V, if the reading has one, or an ApplicationError.
+ */
+fn voltage(reading: &dyn Reading) -> Result<Option<f64>, ApplicationError> {
+    if let Some(measurement) = reading.get_measurement() {
+        return Ok((measurement.quantity == Quantity::Voltage).then_some(measurement.value));
+    }
+    let fields = reading.get_csv_header()?.into_iter().zip(reading.get_csv()?);
+    Ok(fields.into_iter().find(|(name, _)| name == "voltage").and_then(|(_, value)| value.parse().ok()))
+}
+
+/**
+ * Runs a battery discharge test: the first device is the load, which is set to the discharge
+ * current given with `--discharge-current` and switched on. Every interval all devices run their
+ * commands and the readings are printed, tagged with the elapsed time and the capacity drawn.
+ * The battery voltage is taken from the last device reporting one, so a DMM listed after the
+ * load is preferred over the readback of the load. The test stops when the voltage reaches the
+ * cutoff voltage, or on Ctrl-C or SIGTERM; the load is switched off and the capacity and energy
+ * are printed as a summary.
+ *
+ * # Arguments
+ * `devices` - The device labels (None for a single device) with their arguments.
+ * `printer` - Prints the readings.
+ * `shape` - The field shape of the output, if any.
+ *
+ * # Returns
+ * A Result indicating success or an ApplicationError.
+ */
+pub async fn run(
+    devices: &[(Option<String>, Args)],
+    printer: &mut Printer,
+    shape: Option<&FieldShape>,
+) -> Result<(), ApplicationError> {
+    let args = &devices[0].1;
+    let (Some(current), Some(cutoff)) = (args.sequence.discharge_current, args.sequence.cutoff_voltage) else {
+        return Err(ApplicationError::Config("A discharge test needs --discharge-current and --cutoff-voltage".into()));
+    };
+    if !(current.is_finite() && current > 0.0) {
+        return Err(ApplicationError::Config("The discharge current must be positive".into()));
+    }
+    let mut opened = Vec::new();
+    for (label, args) in devices {
+        opened.push(DischargeDevice {
+            label: label.clone(),
+            device: get_communication_device(args).await?,
+            commands: args.control.commands.clone(),
+        });
+    }
+    let load = &opened[0].device;
+    load.command(vec![
+        args.sequence.current_command.replace("{value}", &format!("{:.3}", current)),
+        args.sequence.load_on_command.clone(),
+    ])
+    .await?;
+    let mut summary = DischargeReading::new(current);
+    let interval = Duration::from_millis(args.sequence.discharge_interval_ms);
+    let result = {
+        let stop = pin!(shutdown::requested());
+        let test = discharge(&opened, interval, cutoff, &mut summary, |reading| {
+            with_shape(reading, shape, |reading| printer.print(reading))
+        });
+        tokio::select! {
+            biased;
+            _ = stop => Ok(()),
+            result = test => result,
+        }
+    };
+    // The load is switched off also when the test failed
+    let off = load.command(vec![args.sequence.load_off_command.clone()]).await;
+    result?;
+    off?;
+    with_shape(&summary, shape, |reading| printer.print(reading))
+}
+
+/**
+ * Logs the readings of every interval until the voltage reaches the cutoff voltage.
+ *
+ * # Arguments
+ * `devices` - The load followed by the measuring devices.
+ * `interval` - The interval of the readings.
+ * `cutoff` - The cutoff voltage in V.
+ * `summary` - Receives the voltages.
+ * `print` - Receives the tagged readings.
+ *
+ * # Returns
+ * A Result indicating success or an ApplicationError.
+ */
+async fn discharge(
+    devices: &[DischargeDevice],
+    interval: Duration,
+    cutoff: f64,
+    summary: &mut DischargeReading,
+    mut print: impl FnMut(&dyn Reading) -> Result<(), ApplicationError>,
+) -> Result<(), ApplicationError> {
+    let start = Instant::now();
+    let mut ticks = tokio::time::interval(interval.max(Duration::from_millis(1)));
+    loop {
+        ticks.tick().await;
+        let elapsed = start.elapsed();
+        let mut readings = Vec::new();
+        for device in devices {
+            readings.push((device, device.device.command(device.commands.clone()).await?));
+        }
+        let mut battery = None;
+        for (_, device_readings) in &readings {
+            for reading in device_readings.iter().flatten() {
+                battery = voltage(reading.as_ref())?.or(battery);
+            }
+        }
+        if let Some(battery) = battery {
+            summary.add(elapsed, battery);
+        }
+        for (device, device_readings) in readings {
+            for reading in device_readings.unwrap_or_default() {
+                let mut tags = vec![
+                    ("elapsed".to_string(), format!("{:.3}", elapsed.as_secs_f64())),
+                    ("capacity_mah".to_string(), format!("{:.3}", summary.capacity_mah())),
+                ];
+                if let Some(label) = &device.label {
+                    tags.push(("device".to_string(), label.clone()));
+                }
+                print(&TaggedReading::new(tags, reading))?;
+            }
+        }
+        if battery.is_some_and(|battery| battery <= cutoff) {
+            summary.cutoff_reached = true;
+            return Ok(());
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{cell::RefCell, rc::Rc};
+
+    use async_trait::async_trait;
+
+    use super::*;
+    use crate::instruments::reading::KoradReading;
+
+    /**
+     * Load answering Measure with the next voltage of a discharge curve.
+     */
+    struct Battery(Rc<RefCell<Vec<&'static [u8]>>>);
+
+    #[async_trait(?Send)]
+    impl Communication for Battery {
+        async fn command(&self, commands: Vec<String>) -> Result<Option<Vec<Box<dyn Reading>>>, ApplicationError> {
+            let mut voltages = self.0.borrow_mut();
+            Ok(Some(
+                commands
+                    .iter()
+                    .filter(|command| *command == "Measure")
+                    .map(|_| Box::new(KoradReading::measured(voltages.remove(0), b"0.500", &[0x41])) as Box<dyn Reading>)
+                    .collect(),
+            ))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_discharge() {
+        let voltages: Vec<&'static [u8]> = vec![b"04.20", b"03.70", b"02.95", b"02.90"];
+        let devices = vec![DischargeDevice {
+            label: None,
+            device: Box::new(Battery(Rc::new(RefCell::new(voltages)))),
+            commands: vec!["Measure".into()],
+        }];
+        let mut summary = DischargeReading::new(0.5);
+        let mut records = Vec::new();
+        discharge(&devices, Duration::from_millis(10), 3.0, &mut summary, |reading| {
+            assert_eq!(reading.get_csv_header()?[..2], ["elapsed", "capacity_mah"]);
+            records.push(reading.get_csv()?[3].clone());
+            Ok(())
+        })
+        .await
+        .unwrap();
+        assert_eq!(records, vec!["4.2", "3.7", "2.95"]);
+        assert_eq!(summary.samples, 3);
+        assert_eq!(summary.end_voltage, Some(2.95));
+        assert!(summary.cutoff_reached);
+        assert!(summary.capacity_mah() > 0.0);
+    }
+}
@@ -0,0 +1,444 @@
+use std::{
+    collections::BTreeMap,
+    net::{IpAddr, Ipv4Addr, SocketAddr},
+    time::Duration,
+};
+
+use futures_util::future::join_all;
+use tokio::{
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+    net::{TcpStream, UdpSocket},
+    time::{timeout, Instant},
+};
+use tracing::debug;
+
+use crate::{error::ApplicationError, instruments::reading::Identity};
+
+/**
+ * Services browsed for: LXI instruments and instruments with a raw SCPI socket.
+ */
+const SERVICES: [&str; 2] = ["_lxi._tcp.local", "_scpi-raw._tcp.local"];
+/**
+ * Service whose port is the raw SCPI socket.
+ */
+const SCPI_RAW_SERVICE: &str = "_scpi-raw._tcp.local";
+/**
+ * Raw SCPI port of LXI instruments not advertising `_scpi-raw._tcp`.
+ */
+const SCPI_RAW_PORT: u16 = 5025;
+/**
+ * mDNS multicast group and port.
+ */
+const MDNS_ADDRESS: SocketAddr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(224, 0, 0, 251)), 5353);
+/**
+ * Time to wait for an instrument to answer `*IDN?`.
+ */
+const PROBE_TIMEOUT: Duration = Duration::from_secs(2);
+/**
+ * Largest number of compression pointers followed in one name, against pointer loops.
+ */
+const MAX_POINTERS: usize = 32;
+
+// DNS record types
+const TYPE_A: u16 = 1;
+const TYPE_PTR: u16 = 12;
+const TYPE_TXT: u16 = 16;
+const TYPE_SRV: u16 = 33;
+
+/**
+ * Class IN with the unicast-response bit, so responders answer the querying port directly.
+ */
+const CLASS_IN_UNICAST: u16 = 0x8001;
+
+/**
+ * A resource record of an mDNS response.
+ */
+#[derive(Debug, Clone, PartialEq)]
+enum Record {
+    /// An instance of a service.
+    Ptr { name: String, instance: String },
+    /// The host and port of an instance.
+    Srv { name: String, host: String, port: u16 },
+    /// The address of a host.
+    A { name: String, address: Ipv4Addr },
+    /// The key/value pairs of an instance.
+    Txt { name: String, entries: Vec<String> },
+}
+
+/**
+ * An instrument found on the network.
+ */
+#[derive(Debug, Clone, PartialEq)]
+struct Instrument {
+    /**
+     * Name of the instance, e.g. `RIGOL DS1104Z-Plus`.
+     */
+    instance: String,
+    /**
+     * Address of the raw SCPI socket.
+     */
+    address: SocketAddr,
+    /**
+     * Manufacturer, model and serial number from the LXI TXT record, used if `*IDN?` fails.
+     */
+    manufacturer: Option<String>,
+    model: Option<String>,
+    serial_number: Option<String>,
+}
+
+/**
+ * Builds the mDNS query for the PTR records of the services.
+ *
+ * # Returns
+ * The query packet.
+ */
+fn query() -> Vec<u8> {
+    let mut packet = vec![0, 0, 0, 0, 0, SERVICES.len() as u8, 0, 0, 0, 0, 0, 0];
+    for service in SERVICES {
+        for label in service.split('.') {
+            packet.push(label.len() as u8);
+            packet.extend_from_slice(label.as_bytes());
+        }
+        packet.push(0);
+        packet.extend_from_slice(&TYPE_PTR.to_be_bytes());
+        packet.extend_from_slice(&CLASS_IN_UNICAST.to_be_bytes());
+    }
+    packet
+}
+
+/**
+ * Reads a big endian u16.
+ *
+ * # Arguments
+ * `packet` - The packet.
+ * `offset` - The offset of the value.
+ *
+ * # Returns
+ * The value, or None past the end of the packet.
+ */
+fn u16_at(packet: &[u8], offset: usize) -> Option<u16> {
+    Some(u16::from_be_bytes([*packet.get(offset)?, *packet.get(offset + 1)?]))
+}
+
+/**
+ * Reads a possibly compressed domain name.
+ *
+ * # Arguments
+ * `packet` - The packet.
+ * `offset` - The offset of the name.
+ *
+ * # Returns
+ * The name without trailing dot and the offset after it, or None for a malformed name.
+ */
+fn read_name(packet: &[u8], mut offset: usize) -> Option<(String, usize)> {
+    let mut labels = Vec::new();
+    let mut end = None;
+    for _ in 0..MAX_POINTERS {
+        loop {
+            let length = *packet.get(offset)? as usize;
+            match length {
+                0 => return Some((labels.join("."), end.unwrap_or(offset + 1))),
+                length if length & 0xC0 == 0xC0 => {
+                    end.get_or_insert(offset + 2);
+                    offset = (u16_at(packet, offset)? & 0x3FFF) as usize;
+                    break;
+                }
+                length => {
+                    let label = packet.get(offset + 1..offset + 1 + length)?;
+                    labels.push(String::from_utf8_lossy(label).to_string());
+                    offset += 1 + length;
+                }
+            }
+        }
+    }
+    None
+}
+
+/**
+ * Parses the PTR, SRV, A and TXT records of an mDNS response. Other records are skipped.
+ *
+ * # Arguments
+ * `packet` - The response packet.
+ *
+ * # Returns
+ * The records, or None for a malformed packet.
+ */
+fn parse_records(packet: &[u8]) -> Option<Vec<Record>> {
+    let questions = u16_at(packet, 4)?;
+    let records = (6..12).step_by(2).map(|offset| u16_at(packet, offset).map(u32::from)).sum::<Option<u32>>()?;
+    let mut offset = 12;
+    for _ in 0..questions {
+        offset = read_name(packet, offset)?.1 + 4;
+    }
+    let mut parsed = Vec::new();
+    for _ in 0..records {
+        let (name, after_name) = read_name(packet, offset)?;
+        let record_type = u16_at(packet, after_name)?;
+        let length = u16_at(packet, after_name + 8)? as usize;
+        let data = after_name + 10;
+        let rdata = packet.get(data..data + length)?;
+        match record_type {
+            TYPE_PTR => parsed.push(Record::Ptr { name, instance: read_name(packet, data)?.0 }),
+            TYPE_SRV => parsed.push(Record::Srv {
+                name,
+                port: u16_at(packet, data + 4)?,
+                host: read_name(packet, data + 6)?.0,
+            }),
+            TYPE_A if length == 4 => parsed.push(Record::A {
+                name,
+                address: Ipv4Addr::new(rdata[0], rdata[1], rdata[2], rdata[3]),
+            }),
+            TYPE_TXT => {
+                let mut entries = Vec::new();
+                let mut position = 0;
+                while let Some(length) = rdata.get(position).map(|length| *length as usize) {
+                    entries.push(String::from_utf8_lossy(rdata.get(position + 1..position + 1 + length)?).to_string());
+                    position += 1 + length;
+                }
+                parsed.push(Record::Txt { name, entries });
+            }
+            _ => {}
+        }
+        offset = data + length;
+    }
+    Some(parsed)
+}
+
+/**
+ * Combines the records of the responses into instruments, one per address. The port of
+ * `_scpi-raw._tcp` is used if advertised, else the standard raw SCPI port.
+ *
+ * # Arguments
+ * `responses` - The records of each response with the address it came from.
+ *
+ * # Returns
+ * The instruments, ordered by address.
+ */
+fn instruments(responses: &[(IpAddr, Vec<Record>)]) -> Vec<Instrument> {
+    let records: Vec<(IpAddr, &Record)> = responses
+        .iter()
+        .flat_map(|(source, records)| records.iter().map(move |record| (*source, record)))
+        .collect();
+    let srv = |instance: &str| {
+        records.iter().find_map(|(_, record)| match record {
+            Record::Srv { name, host, port } if name.eq_ignore_ascii_case(instance) => Some((host.clone(), *port)),
+            _ => None,
+        })
+    };
+    let address = |host: &str| {
+        records.iter().find_map(|(_, record)| match record {
+            Record::A { name, address } if name.eq_ignore_ascii_case(host) => Some(IpAddr::V4(*address)),
+            _ => None,
+        })
+    };
+    let txt = |instance: &str, key: &str| {
+        records.iter().find_map(|(_, record)| match record {
+            Record::Txt { name, entries } if name.eq_ignore_ascii_case(instance) => entries
+                .iter()
+                .find_map(|entry| entry.split_once('=').filter(|(name, _)| name.eq_ignore_ascii_case(key)))
+                .map(|(_, value)| value.to_string()),
+            _ => None,
+        })
+    };
+    let mut found: BTreeMap<IpAddr, Instrument> = BTreeMap::new();
+    for (source, record) in &records {
+        let Record::Ptr { name: service, instance } = record else {
+            continue;
+        };
+        let Some(service) = SERVICES.iter().find(|known| known.eq_ignore_ascii_case(service)) else {
+            continue;
+        };
+        let (host, port) = srv(instance).unzip();
+        let ip = host.as_deref().and_then(address).unwrap_or(*source);
+        let port = port.filter(|_| *service == SCPI_RAW_SERVICE).unwrap_or(SCPI_RAW_PORT);
+        let instrument = found.entry(ip).or_insert_with(|| Instrument {
+            instance: instance.split('.').next().unwrap_or_default().to_string(),
+            address: SocketAddr::new(ip, port),
+            manufacturer: None,
+            model: None,
+            serial_number: None,
+        });
+        if *service == SCPI_RAW_SERVICE {
+            instrument.address = SocketAddr::new(ip, port);
+        }
+        instrument.manufacturer = instrument.manufacturer.take().or_else(|| txt(instance, "Manufacturer"));
+        instrument.model = instrument.model.take().or_else(|| txt(instance, "Model"));
+        instrument.serial_number = instrument.serial_number.take().or_else(|| txt(instance, "SerialNumber"));
+    }
+    found.into_values().collect()
+}
+
+/**
+ * Asks an instrument for its identity on its raw SCPI socket.
+ *
+ * # Arguments
+ * `address` - The address of the socket.
+ *
+ * # Returns
+ * The identity, or None if the instrument did not answer in time.
+ */
+async fn probe(address: SocketAddr) -> Option<Identity> {
+    let identify = async {
+        let mut stream = TcpStream::connect(address).await.ok()?;
+        stream.write_all(b"*IDN?\n").await.ok()?;
+        let mut response = Vec::new();
+        BufReader::new(stream).read_until(b'\n', &mut response).await.ok()?;
+        Identity::parse(&response)
+    };
+    timeout(PROBE_TIMEOUT, identify).await.ok().flatten()
+}
+
+/**
+ * Describes an instrument with its identity, or with its LXI TXT record if it did not answer.
+ *
+ * # Arguments
+ * `instrument` - The instrument.
+ * `identity` - The `*IDN?` identity, if it answered.
+ *
+ * # Returns
+ * The line to print.
+ */
+fn describe(instrument: &Instrument, identity: Option<Identity>) -> String {
+    let (manufacturer, model, serial_number) = match identity {
+        Some(identity) => (Some(identity.manufacturer), Some(identity.model), Some(identity.serial_number)),
+        None => (instrument.manufacturer.clone(), instrument.model.clone(), instrument.serial_number.clone()),
+    };
+    let mut line = format!("instrument {}", instrument.address);
+    match (manufacturer, model) {
+        (Some(manufacturer), Some(model)) => line.push_str(&format!(" {} {}", manufacturer, model)),
+        (manufacturer, model) => line.push_str(&format!(" {}", model.or(manufacturer).unwrap_or_else(|| instrument.instance.clone()))),
+    }
+    if let Some(serial_number) = serial_number.filter(|serial_number| !serial_number.is_empty()) {
+        line.push_str(&format!(" serial {}", serial_number));
+    }
+    line
+}
+
+/**
+ * Browses the local network for LXI and raw SCPI instruments with mDNS and identifies them
+ * with `*IDN?` on their raw SCPI socket.
+ *
+ * # Arguments
+ * `browse_time` - How long to collect mDNS responses.
+ *
+ * # Returns
+ * A Result containing a line per instrument with its SCPI address, model and serial number,
+ * or an ApplicationError if the query could not be sent.
+ */
+pub async fn discover(browse_time: Duration) -> Result<Vec<String>, ApplicationError> {
+    let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0))
+        .await
+        .map_err(|e| ApplicationError::Io("Could not open an mDNS socket".into(), e))?;
+    socket
+        .send_to(&query(), MDNS_ADDRESS)
+        .await
+        .map_err(|e| ApplicationError::Io("Could not send the mDNS query".into(), e))?;
+    let deadline = Instant::now() + browse_time;
+    let mut responses = Vec::new();
+    let mut buffer = [0u8; 9000];
+    while let Ok(received) = tokio::time::timeout_at(deadline, socket.recv_from(&mut buffer)).await {
+        let (length, source) = received.map_err(|e| ApplicationError::Io("Could not receive mDNS responses".into(), e))?;
+        match parse_records(&buffer[..length]) {
+            Some(records) => responses.push((source.ip(), records)),
+            None => debug!("Malformed mDNS response from {}", source),
+        }
+    }
+    let instruments = instruments(&responses);
+    let identities = join_all(instruments.iter().map(|instrument| probe(instrument.address))).await;
+    Ok(instruments.iter().zip(identities).map(|(instrument, identity)| describe(instrument, identity)).collect())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /**
+     * Appends a resource record to a packet.
+     */
+    fn record(packet: &mut Vec<u8>, name: &[u8], record_type: u16, data: &[u8]) {
+        packet.extend_from_slice(name);
+        packet.extend_from_slice(&record_type.to_be_bytes());
+        packet.extend_from_slice(&[0x80, 0x01, 0, 0, 0x11, 0x94]);
+        packet.extend_from_slice(&(data.len() as u16).to_be_bytes());
+        packet.extend_from_slice(data);
+    }
+
+    fn response() -> Vec<u8> {
+        let mut packet = vec![0, 0, 0x84, 0, 0, 0, 0, 2, 0, 0, 0, 3];
+        // Offset 12: _scpi-raw._tcp.local
+        record(&mut packet, b"\x09_scpi-raw\x04_tcp\x05local\x00", TYPE_PTR, b"\x07DS1104Z\xc0\x0c");
+        // Offset 44: DS1104Z._scpi-raw._tcp.local, the PTR target
+        record(&mut packet, b"\xc0\x2c", TYPE_SRV, b"\x00\x00\x00\x00\x13\x8b\x05scope\xc0\x1b");
+        record(&mut packet, b"\x05scope\xc0\x1b", TYPE_A, &[192, 168, 1, 23]);
+        record(&mut packet, b"\x07DS1104Z\x04_lxi\xc0\x16", TYPE_TXT, b"\x0aModel=DS1Z\x0dSerialNumber=");
+        record(&mut packet, b"\xc0\x2c", 28, &[0; 16]);
+        packet
+    }
+
+    #[test]
+    fn test_query() {
+        let packet = query();
+        assert_eq!(packet[..12], [0, 0, 0, 0, 0, 2, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(read_name(&packet, 12).unwrap(), ("_lxi._tcp.local".to_string(), 29));
+        assert_eq!(u16_at(&packet, 29), Some(TYPE_PTR));
+        assert_eq!(read_name(&packet, 33).unwrap().0, "_scpi-raw._tcp.local");
+    }
+
+    #[test]
+    fn test_read_name_loop() {
+        assert_eq!(read_name(&[0xc0, 0x00], 0), None);
+        assert_eq!(read_name(&[0x05, b'a'], 0), None);
+    }
+
+    #[test]
+    fn test_parse_records() {
+        let records = parse_records(&response()).unwrap();
+        assert_eq!(
+            records,
+            [
+                Record::Ptr {
+                    name: "_scpi-raw._tcp.local".into(),
+                    instance: "DS1104Z._scpi-raw._tcp.local".into()
+                },
+                Record::Srv {
+                    name: "DS1104Z._scpi-raw._tcp.local".into(),
+                    host: "scope.local".into(),
+                    port: 5003
+                },
+                Record::A { name: "scope.local".into(), address: Ipv4Addr::new(192, 168, 1, 23) },
+                Record::Txt {
+                    name: "DS1104Z._lxi._tcp.local".into(),
+                    entries: vec!["Model=DS1Z".into(), "SerialNumber=".into()]
+                },
+            ]
+        );
+        assert_eq!(parse_records(&response()[..40]), None);
+    }
+
+    #[test]
+    fn test_instruments() {
+        let source = IpAddr::V4(Ipv4Addr::new(192, 168, 1, 23));
+        let lxi = vec![
+            Record::Ptr { name: "_lxi._tcp.local".into(), instance: "MSO5074._lxi._tcp.local".into() },
+            Record::Txt {
+                name: "MSO5074._lxi._tcp.local".into(),
+                entries: vec!["Manufacturer=RIGOL TECHNOLOGIES".into(), "Model=MSO5074".into(), "SerialNumber=MS5A1".into()],
+            },
+            Record::Ptr { name: "_lxi._tcp.local".into(), instance: "Meter._lxi._tcp.local".into() },
+        ];
+        let responses = [(source, parse_records(&response()).unwrap()), (IpAddr::V4(Ipv4Addr::new(192, 168, 1, 40)), lxi)];
+        let found = instruments(&responses);
+        assert_eq!(found.len(), 2);
+        assert_eq!(found[0].address, "192.168.1.23:5003".parse().unwrap());
+        assert_eq!(found[0].instance, "DS1104Z");
+        assert_eq!(found[0].model, None);
+        assert_eq!(found[1].address, "192.168.1.40:5025".parse().unwrap());
+        assert_eq!(describe(&found[1], None), "instrument 192.168.1.40:5025 RIGOL TECHNOLOGIES MSO5074 serial MS5A1");
+        assert_eq!(describe(&found[0], None), "instrument 192.168.1.23:5003 DS1104Z");
+        let identity = Identity::parse(b"RIGOL TECHNOLOGIES,DS1104Z,DS1ZA2,00.04.04").unwrap();
+        assert_eq!(
+            describe(&found[0], Some(identity)),
+            "instrument 192.168.1.23:5003 RIGOL TECHNOLOGIES DS1104Z serial DS1ZA2"
+        );
+    }
+}
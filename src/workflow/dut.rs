@@ -0,0 +1,107 @@
+use tokio::io::{stdin, AsyncBufReadExt, BufReader, Lines, Stdin};
+
+use crate::{
+    arguments::Args,
+    error::ApplicationError,
+    instruments::reading::{Reading, TaggedReading},
+};
+
+/**
+ * Source of the DUT (device under test) identifier recorded with every reading.
+ * The identifier is either fixed with `--dut-id` or read from standard input before each
+ * measurement cycle with `--prompt-dut-id`, e.g. from a barcode scanner acting as a keyboard.
+ */
+pub enum DutId {
+    None,
+    Fixed(String),
+    Prompt(Lines<BufReader<Stdin>>),
+}
+
+impl DutId {
+    /**
+     * Creates the DUT identifier source from the command line arguments.
+     *
+     * # Arguments
+     * `args` - The command line arguments.
+     *
+     * # Returns
+     * A new DutId instance.
+     */
+    pub fn new(args: &Args) -> Self {
+        match (&args.measurement.dut_id, args.measurement.prompt_dut_id) {
+            (_, true) => DutId::Prompt(BufReader::new(stdin()).lines()),
+            (Some(id), false) => DutId::Fixed(id.clone()),
+            (None, false) => DutId::None,
+        }
+    }
+
+    /**
+     * Checks if the identifier is read before each cycle.
+     *
+     * # Returns
+     * A boolean indicating whether the user is prompted.
+     */
+    pub fn is_prompt(&self) -> bool {
+        matches!(self, DutId::Prompt(_))
+    }
+
+    /**
+     * Returns the identifier for the next measurement cycle. When prompting, empty lines are
+     * skipped and the prompt is written to stderr so it does not mix with the records.
+     *
+     * # Returns
+     * A Result containing Some(identifier) (None in the inner Option if no identifier is used),
+     * None when standard input is closed, or an ApplicationError.
+     */
+    pub async fn next(&mut self) -> Result<Option<Option<String>>, ApplicationError> {
+        match self {
+            DutId::None => Ok(Some(None)),
+            DutId::Fixed(id) => Ok(Some(Some(id.clone()))),
+            DutId::Prompt(lines) => loop {
+                eprint!("DUT id: ");
+                let line = lines
+                    .next_line()
+                    .await
+                    .map_err(|e| ApplicationError::Io("Could not read DUT id".into(), e))?;
+                match line {
+                    Some(line) if line.trim().is_empty() => continue,
+                    Some(line) => return Ok(Some(Some(line.trim().to_string()))),
+                    None => return Ok(None),
+                }
+            },
+        }
+    }
+}
+
+/**
+ * Tags a reading with the DUT identifier.
+ *
+ * # Arguments
+ * `dut_id` - The DUT identifier, if any.
+ * `reading` - The reading.
+ *
+ * # Returns
+ * The tagged reading, or the reading itself if there is no identifier.
+ */
+pub fn tag(dut_id: &Option<String>, reading: Box<dyn Reading>) -> Box<dyn Reading> {
+    match dut_id {
+        Some(id) => Box::new(TaggedReading::new(vec![("dut_id".into(), id.clone())], reading)),
+        None => reading,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::instruments::reading::{EventKind, EventReading};
+
+    #[tokio::test]
+    async fn test_fixed_dut_id() {
+        let mut dut_id = DutId::Fixed("SN-0042".into());
+        assert!(!dut_id.is_prompt());
+        let id = dut_id.next().await.unwrap().unwrap();
+        let reading = tag(&id, Box::new(EventReading::new(EventKind::Retry, "x".into())));
+        assert_eq!(reading.get_csv_header().unwrap()[0], "dut_id");
+        assert_eq!(reading.get_csv().unwrap()[0], "SN-0042");
+    }
+}
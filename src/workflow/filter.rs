@@ -0,0 +1,252 @@
+use std::collections::VecDeque;
+
+use crate::{
+    arguments::{Args, AverageKind},
+    error::ApplicationError,
+    instruments::reading::{FilteredReading, Measurement, Reading, TaggedReading},
+};
+
+/**
+ * Fields telling the streams of readings apart, besides the quantity of their measurement.
+ */
+const STREAM_FIELDS: [&str; 2] = ["device", "channel"];
+
+/**
+ * Moving average and decimation state of one stream of readings.
+ */
+struct Stream {
+    /**
+     * The device, channel and quantity of the readings.
+     */
+    key: String,
+    /**
+     * Values of the boxcar average in the base unit, oldest first.
+     */
+    values: VecDeque<f64>,
+    /**
+     * Value of the exponential average in the base unit.
+     */
+    average: f64,
+    /**
+     * Number of readings in the average, up to the window.
+     */
+    samples: usize,
+    /**
+     * Number of readings seen, for the decimation.
+     */
+    seen: usize,
+}
+
+/**
+ * Filters the readings with --average and --decimate, so a fast polled device writes fewer and
+ * less noisy records. Readings with a typed measurement are filtered per device, channel and
+ * quantity; other readings, e.g. events and overloads, pass through unchanged.
+ */
+pub struct Filter {
+    window: Option<usize>,
+    kind: AverageKind,
+    decimate: usize,
+    keep_raw: bool,
+    streams: Vec<Stream>,
+}
+
+impl Filter {
+    /**
+     * Creates the filter of the arguments.
+     *
+     * # Arguments
+     * `args` - The command line arguments.
+     *
+     * # Returns
+     * A Result containing the filter, None if neither --average nor --decimate is given, or an
+     * ApplicationError if either is zero.
+     */
+    pub fn new(args: &Args) -> Result<Option<Self>, ApplicationError> {
+        if args.measurement.average.is_none() && args.measurement.decimate.is_none() {
+            return Ok(None);
+        }
+        if args.measurement.average == Some(0) || args.measurement.decimate == Some(0) {
+            return Err(ApplicationError::Config("--average and --decimate need at least one reading".into()));
+        }
+        Ok(Some(Self {
+            window: args.measurement.average,
+            kind: args.measurement.average_kind.unwrap_or_default(),
+            decimate: args.measurement.decimate.unwrap_or(1),
+            keep_raw: args.measurement.keep_raw,
+            streams: Vec::new(),
+        }))
+    }
+
+    /**
+     * Gets the stream of a reading, adding it the first time.
+     *
+     * # Arguments
+     * `reading` - The reading.
+     * `measurement` - The measurement of the reading.
+     *
+     * # Returns
+     * The stream.
+     */
+    fn stream(&mut self, reading: &dyn Reading, measurement: &Measurement) -> &mut Stream {
+        let header = reading.get_csv_header().unwrap_or_default();
+        let fields = reading.get_csv().unwrap_or_default();
+        let mut key: Vec<String> = STREAM_FIELDS
+            .iter()
+            .filter_map(|name| header.iter().position(|field| field == name).and_then(|index| fields.get(index).cloned()))
+            .collect();
+        key.push(measurement.quantity.to_string());
+        let key = key.join(" ");
+        let index = match self.streams.iter().position(|stream| stream.key == key) {
+            Some(index) => index,
+            None => {
+                self.streams.push(Stream {
+                    key,
+                    values: VecDeque::new(),
+                    average: 0.0,
+                    samples: 0,
+                    seen: 0,
+                });
+                self.streams.len() - 1
+            }
+        };
+        &mut self.streams[index]
+    }
+
+    /**
+     * Filters a reading. With --average every reading is added to the moving average of its
+     * stream and the average is written; with --decimate only every Nth of a stream is written.
+     * With --keep-raw the reading is also written as taken, with empty average fields, and the
+     * records are told apart by a `stream` field.
+     *
+     * # Arguments
+     * `reading` - The reading.
+     *
+     * # Returns
+     * A Result containing the records to write, none if the reading is decimated away, or an
+     * ApplicationError.
+     */
+    pub fn apply(&mut self, reading: Box<dyn Reading>) -> Result<Vec<Box<dyn Reading>>, ApplicationError> {
+        let Some(measurement) = reading.get_measurement() else {
+            return Ok(vec![reading]);
+        };
+        let (window, kind, decimate, keep_raw) = (self.window, self.kind, self.decimate, self.keep_raw);
+        let stream = self.stream(reading.as_ref(), &measurement);
+        stream.seen += 1;
+        let written = stream.seen.is_multiple_of(decimate);
+        let Some(window) = window else {
+            return Ok(if written { vec![reading] } else { Vec::new() });
+        };
+        let average = match kind {
+            AverageKind::Boxcar => {
+                stream.values.push_back(measurement.value);
+                if stream.values.len() > window {
+                    stream.values.pop_front();
+                }
+                stream.values.iter().sum::<f64>() / stream.values.len() as f64
+            }
+            AverageKind::Exponential => {
+                // The smoothing factor of an N sample average, 2 / (N + 1)
+                let alpha = 2.0 / (window as f64 + 1.0);
+                stream.average = match stream.samples {
+                    0 => measurement.value,
+                    _ => stream.average + alpha * (measurement.value - stream.average),
+                };
+                stream.average
+            }
+        };
+        stream.samples = (stream.samples + 1).min(window);
+        let mut records: Vec<Box<dyn Reading>> = Vec::new();
+        if written {
+            let average = Measurement { value: average, ..measurement };
+            let filtered: Box<dyn Reading> = Box::new(FilteredReading::new(average, stream.samples, reading.as_ref())?);
+            records.push(match keep_raw {
+                true => Box::new(TaggedReading::new(vec![("stream".into(), "filtered".into())], filtered)),
+                false => filtered,
+            });
+        }
+        if keep_raw {
+            let raw = TaggedReading::new(vec![("stream".into(), "raw".into())], reading);
+            records.insert(0, Box::new(raw.with_appended(FilteredReading::empty_fields())));
+        }
+        Ok(records)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::instruments::{
+        command::DmmFunction,
+        reading::{EventKind, EventReading, KeysightDmmReading},
+    };
+
+    fn parse(args: &[&str]) -> Args {
+        <Args as clap::Parser>::parse_from([&["hardware-measurement", "--device=keysight-dmm-serial"], args].concat())
+    }
+
+    fn volts(value: &str) -> Box<dyn Reading> {
+        Box::new(KeysightDmmReading::parse(DmmFunction::VoltageDc, value.as_bytes()).unwrap().remove(0))
+    }
+
+    fn averages(filter: &mut Filter, values: &[&str]) -> Vec<String> {
+        values
+            .iter()
+            .flat_map(|value| filter.apply(volts(value)).unwrap())
+            .map(|record| record.get_csv().unwrap().join(","))
+            .collect()
+    }
+
+    #[test]
+    fn test_boxcar_average() {
+        let mut filter = Filter::new(&parse(&["--average=3"])).unwrap().unwrap();
+        assert_eq!(
+            averages(&mut filter, &["1", "2", "3", "7"]),
+            ["VoltageDc,1,V,false,1,1", "VoltageDc,2,V,false,1.5,2", "VoltageDc,3,V,false,2,3", "VoltageDc,7,V,false,4,3"]
+        );
+    }
+
+    #[test]
+    fn test_block_average() {
+        let mut filter = Filter::new(&parse(&["--average=2", "--decimate=2"])).unwrap().unwrap();
+        assert_eq!(averages(&mut filter, &["1", "3", "5", "9"]), ["VoltageDc,3,V,false,2,2", "VoltageDc,9,V,false,7,2"]);
+    }
+
+    #[test]
+    fn test_exponential_average() {
+        let mut filter = Filter::new(&parse(&["--average=3", "--average-kind=exponential"])).unwrap().unwrap();
+        assert_eq!(averages(&mut filter, &["4", "8", "8"])[1..], ["VoltageDc,8,V,false,6,2", "VoltageDc,8,V,false,7,3"]);
+    }
+
+    #[test]
+    fn test_decimate_and_keep_raw() {
+        let mut filter = Filter::new(&parse(&["--decimate=3"])).unwrap().unwrap();
+        assert_eq!(averages(&mut filter, &["1", "2", "3", "4", "5", "6"]), ["VoltageDc,3,V,false", "VoltageDc,6,V,false"]);
+        let mut filter = Filter::new(&parse(&["--average=2", "--decimate=2", "--keep-raw"])).unwrap().unwrap();
+        assert_eq!(
+            averages(&mut filter, &["1", "3"]),
+            ["raw,VoltageDc,1,V,false,,", "raw,VoltageDc,3,V,false,,", "filtered,VoltageDc,3,V,false,2,2"]
+        );
+    }
+
+    #[test]
+    fn test_keep_raw_columns() {
+        let mut filter = Filter::new(&parse(&["--average=2", "--keep-raw"])).unwrap().unwrap();
+        let records = filter.apply(volts("1")).unwrap();
+        let header = records[0].get_csv_header().unwrap();
+        assert_eq!(header, ["stream", "function", "value", "unit", "overload", "average", "average_samples"]);
+        for record in &records {
+            assert_eq!(record.get_csv_header().unwrap(), header);
+            assert_eq!(record.get_csv().unwrap().len(), header.len());
+        }
+    }
+
+    #[test]
+    fn test_unmeasured_readings_pass() {
+        let mut filter = Filter::new(&parse(&["--average=4", "--decimate=4"])).unwrap().unwrap();
+        assert_eq!(filter.apply(Box::new(EventReading::new(EventKind::Retry, "x".into()))).unwrap().len(), 1);
+        assert!(filter.apply(volts("+9.90000000E+37")).unwrap().len() == 1);
+        assert!(filter.apply(volts("1")).unwrap().is_empty());
+        assert!(Filter::new(&parse(&[])).unwrap().is_none());
+        assert!(Filter::new(&parse(&["--decimate=0"])).is_err());
+    }
+}
@@ -0,0 +1,139 @@
+use crate::{
+    error::ApplicationError,
+    instruments::reading::{Reading, LATENCY_FIELD},
+};
+
+/**
+ * Round trip statistics of one command.
+ */
+struct CommandLatency {
+    /**
+     * The command, prefixed with the device label if the readings have one.
+     */
+    command: String,
+    count: usize,
+    min: f64,
+    max: f64,
+    total: f64,
+}
+
+/**
+ * Summary of the round trip times recorded with --latency, printed at exit. The times are taken
+ * from the `latency_ms` fields of the readings, also when they are numbered in paired records.
+ */
+#[derive(Default)]
+pub struct LatencySummary {
+    commands: Vec<CommandLatency>,
+}
+
+impl LatencySummary {
+    /**
+     * Adds the round trip times of a reading to the summary.
+     *
+     * # Arguments
+     * `reading` - The reading.
+     *
+     * # Returns
+     * A Result indicating success or an ApplicationError.
+     */
+    pub fn record(&mut self, reading: &dyn Reading) -> Result<(), ApplicationError> {
+        let (Ok(header), Ok(fields)) = (reading.get_csv_header(), reading.get_csv()) else {
+            return Ok(());
+        };
+        let field = |name: &str| header.iter().position(|field| field == name).map(|index| fields[index].clone());
+        for name in &header {
+            let Some(suffix) = name.strip_prefix(LATENCY_FIELD) else {
+                continue;
+            };
+            let (Some(latency), Some(command)) = (field(name), field(&format!("command{}", suffix))) else {
+                continue;
+            };
+            let latency: f64 = latency
+                .parse()
+                .map_err(|_| ApplicationError::General(format!("Invalid latency {:?}", latency)))?;
+            let command = match field("device") {
+                Some(device) => format!("{} {}", device, command),
+                None => command,
+            };
+            match self.commands.iter_mut().find(|existing| existing.command == command) {
+                Some(existing) => {
+                    existing.count += 1;
+                    existing.min = existing.min.min(latency);
+                    existing.max = existing.max.max(latency);
+                    existing.total += latency;
+                }
+                None => self.commands.push(CommandLatency {
+                    command,
+                    count: 1,
+                    min: latency,
+                    max: latency,
+                    total: latency,
+                }),
+            }
+        }
+        Ok(())
+    }
+
+    /**
+     * Formats the summary, one line per command in the order they were first seen, e.g.
+     * `MEAS:VOLT?: count 10, min 1.234 ms, avg 1.500 ms, max 2.345 ms`.
+     *
+     * # Returns
+     * The lines of the summary.
+     */
+    pub fn lines(&self) -> Vec<String> {
+        self.commands
+            .iter()
+            .map(|latency| {
+                format!(
+                    "{}: count {}, min {:.3} ms, avg {:.3} ms, max {:.3} ms",
+                    latency.command,
+                    latency.count,
+                    latency.min,
+                    latency.total / latency.count as f64,
+                    latency.max
+                )
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::instruments::reading::{PairedReading, ScpiRawReading, TaggedReading, TimedReading};
+
+    fn timed(command: &str, milliseconds: u64) -> Box<dyn Reading> {
+        Box::new(TimedReading::new(
+            command.into(),
+            Duration::from_millis(milliseconds),
+            None,
+            None,
+            Box::new(ScpiRawReading::new(b"1\n".to_vec())),
+        ))
+    }
+
+    #[test]
+    fn test_summary() {
+        let mut summary = LatencySummary::default();
+        summary.record(timed("MEAS:VOLT?", 2).as_ref()).unwrap();
+        summary.record(timed("MEAS:VOLT?", 4).as_ref()).unwrap();
+        summary
+            .record(&TaggedReading::new(vec![("device".into(), "psu".into())], timed("MEAS:CURR?", 3)))
+            .unwrap();
+        summary
+            .record(&PairedReading::new(vec![("VOLT".into(), "1".into())], vec![timed("A?", 1), timed("B?", 5)]))
+            .unwrap();
+        assert_eq!(
+            summary.lines(),
+            [
+                "MEAS:VOLT?: count 2, min 2.000 ms, avg 3.000 ms, max 4.000 ms",
+                "psu MEAS:CURR?: count 1, min 3.000 ms, avg 3.000 ms, max 3.000 ms",
+                "A?: count 1, min 1.000 ms, avg 1.000 ms, max 1.000 ms",
+                "B?: count 1, min 5.000 ms, avg 5.000 ms, max 5.000 ms",
+            ]
+        );
+    }
+}
@@ -0,0 +1,100 @@
+use clap::ValueEnum;
+
+use crate::{
+    arguments::Args,
+    instruments::{
+        communication::common::DeviceInfo,
+        reading::{Reading, TaggedReading},
+    },
+};
+
+/**
+ * Names of the provenance fields added with `--metadata`.
+ */
+const METADATA_FIELDS: [&str; 5] = ["manufacturer", "model", "serial_number", "reader", "commands"];
+
+/**
+ * Gets the provenance of the readings of a device: the manufacturer, model and serial number
+ * reported by the hardware, the reader and the commands. Unknown values are empty, so every
+ * record of a device has the same fields.
+ *
+ * # Arguments
+ * `args` - The arguments of the device.
+ * `device_info` - The device info of the open device, if any.
+ *
+ * # Returns
+ * The field names and values.
+ */
+pub fn metadata(args: &Args, device_info: Option<DeviceInfo>) -> Vec<(String, String)> {
+    let device_info = device_info.unwrap_or_default();
+    let reader = args
+        .readings.reader
+        .as_ref()
+        .and_then(|reader| reader.to_possible_value())
+        .map(|value| value.get_name().to_string());
+    let values = [
+        device_info.manufacturer,
+        device_info.product,
+        device_info.serial_number,
+        reader,
+        Some(args.control.commands.join(";")),
+    ];
+    METADATA_FIELDS
+        .iter()
+        .zip(values)
+        .map(|(name, value)| (name.to_string(), value.unwrap_or_default()))
+        .collect()
+}
+
+/**
+ * Tags a reading with the provenance of its device.
+ *
+ * # Arguments
+ * `metadata` - The provenance fields, if enabled.
+ * `reading` - The reading.
+ *
+ * # Returns
+ * The tagged reading, or the reading itself if metadata is not enabled.
+ */
+pub fn tag(metadata: &Option<Vec<(String, String)>>, reading: Box<dyn Reading>) -> Box<dyn Reading> {
+    match metadata {
+        Some(tags) => Box::new(TaggedReading::new(tags.clone(), reading)),
+        None => reading,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use clap::Parser;
+
+    use super::*;
+    use crate::instruments::reading::KoradReading;
+
+    #[test]
+    fn test_metadata() {
+        let args = Args::parse_from([
+            "test_program",
+            "--device=generic-scpi-usb",
+            "--command=CONF:VOLT:DC",
+            "--command=READ?",
+            "--reader=scpi-idn-reader",
+        ]);
+        let device_info = DeviceInfo {
+            manufacturer: Some("Keysight".into()),
+            product: Some("34461A".into()),
+            serial_number: None,
+        };
+        let metadata = Some(metadata(&args, Some(device_info)));
+        let reading = tag(&metadata, Box::new(KoradReading::measured(b"05.00", b"0.100", &[0x41])));
+        assert_eq!(
+            reading.get_csv_header().unwrap()[..5],
+            ["manufacturer", "model", "serial_number", "reader", "commands"]
+        );
+        assert_eq!(
+            reading.get_csv().unwrap()[..5],
+            ["Keysight", "34461A", "", "scpi-idn-reader", "CONF:VOLT:DC;READ?"]
+        );
+        let plain = tag(&None, Box::new(KoradReading::measured(b"05.00", b"0.100", &[0x41])));
+        assert_eq!(plain.get_csv_header().unwrap().len() + 5, reading.get_csv_header().unwrap().len());
+    }
+}
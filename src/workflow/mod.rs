@@ -0,0 +1,20 @@
+pub mod acquire;
+pub mod assertion;
+pub mod bench;
+pub mod completions;
+pub mod decode;
+pub mod devices;
+pub mod discharge;
+#[cfg(feature = "network")]
+pub mod discover;
+pub mod dut;
+pub mod filter;
+pub mod latency;
+pub mod metadata;
+pub mod ramp;
+pub mod record;
+pub mod scan;
+pub mod script;
+pub mod session;
+pub mod sweep;
+pub mod validate;
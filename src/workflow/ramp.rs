@@ -0,0 +1,320 @@
+use std::time::Duration;
+
+use tokio::time::Instant;
+
+use crate::{
+    arguments::Args,
+    error::ApplicationError,
+    instruments::{
+        communication::{get_communication_device, Communication},
+        reading::{Reading, TaggedReading},
+    },
+    output::{
+        shape::{with_shape, FieldShape},
+        Printer,
+    },
+    shutdown,
+    workflow::script::parse_duration,
+};
+
+/**
+ * A point of a setpoint profile: the voltage and current set at a time after the start.
+ * Quantities left out keep their earlier value.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct RampPoint {
+    pub at: Duration,
+    pub voltage: Option<f64>,
+    pub current: Option<f64>,
+}
+
+/**
+ * A step of a ramp: the setpoints sent at a time after the start.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct RampStep {
+    pub at: Duration,
+    pub voltage: Option<f64>,
+    pub current: Option<f64>,
+}
+
+/**
+ * Parses a setpoint profile such as `0s 0V; 10s 5V 1A; 60s 5V; 65s 0V`. Points are separated
+ * by `;` or line breaks and give a time followed by a voltage (`V` or `mV`) and/or a current
+ * (`A` or `mA`). Text after `#` is a comment.
+ *
+ * # Arguments
+ * `profile` - The profile text.
+ *
+ * # Returns
+ * A Result containing the points or an ApplicationError naming the offending point.
+ */
+pub fn parse(profile: &str) -> Result<Vec<RampPoint>, ApplicationError> {
+    let mut points: Vec<RampPoint> = Vec::new();
+    let entries = profile
+        .lines()
+        .map(|line| line.split('#').next().unwrap_or_default())
+        .flat_map(|line| line.split(';'))
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty());
+    for entry in entries {
+        let error = |message: &str| ApplicationError::Command(format!("{} in profile point {}", message, entry));
+        let mut tokens = entry.split_whitespace();
+        let at = parse_duration(tokens.next().unwrap_or_default()).map_err(|_| error("Invalid time"))?;
+        if points.last().is_some_and(|last| at < last.at) {
+            return Err(error("Time going backwards"));
+        }
+        let mut point = RampPoint { at, voltage: None, current: None };
+        for token in tokens {
+            let (number, quantity, scale) = [("mV", 'V', 1e-3), ("V", 'V', 1.0), ("mA", 'A', 1e-3), ("A", 'A', 1.0)]
+                .iter()
+                .find_map(|(unit, quantity, scale)| token.strip_suffix(unit).map(|number| (number, *quantity, *scale)))
+                .ok_or_else(|| error("Expected a voltage or current"))?;
+            let value = number
+                .parse::<f64>()
+                .ok()
+                .filter(|value| value.is_finite() && *value >= 0.0)
+                .ok_or_else(|| error("Invalid value"))?
+                * scale;
+            match quantity {
+                'V' => point.voltage = Some(value),
+                _ => point.current = Some(value),
+            }
+        }
+        if point.voltage.is_none() && point.current.is_none() {
+            return Err(error("Expected a voltage or current"));
+        }
+        points.push(point);
+    }
+    if points.is_empty() {
+        return Err(ApplicationError::Command("The profile has no points".into()));
+    }
+    Ok(points)
+}
+
+/**
+ * Interpolates a quantity between two points.
+ *
+ * # Arguments
+ * `from` - The value at the earlier point, if set.
+ * `to` - The value at the later point, if set.
+ * `fraction` - The position between the points, from 0 to 1.
+ *
+ * # Returns
+ * The value, or None if the quantity is not set yet.
+ */
+fn interpolate(from: Option<f64>, to: Option<f64>, fraction: f64) -> Option<f64> {
+    match (from, to) {
+        (Some(from), Some(to)) => Some(from + (to - from) * fraction),
+        (from, _) => from,
+    }
+}
+
+/**
+ * Computes the steps of a profile: the setpoints change linearly between the points, in steps
+ * of the step interval.
+ *
+ * # Arguments
+ * `points` - The points of the profile.
+ * `step` - The interval of the steps.
+ *
+ * # Returns
+ * The steps.
+ */
+pub fn schedule(points: &[RampPoint], step: Duration) -> Vec<RampStep> {
+    // Quantities left out keep their earlier value
+    let mut filled: Vec<RampPoint> = Vec::new();
+    for point in points {
+        let previous = filled.last();
+        filled.push(RampPoint {
+            at: point.at,
+            voltage: point.voltage.or(previous.and_then(|previous| previous.voltage)),
+            current: point.current.or(previous.and_then(|previous| previous.current)),
+        });
+    }
+    let step = step.max(Duration::from_millis(1));
+    let mut steps = Vec::new();
+    for pair in filled.windows(2) {
+        let (from, to) = (&pair[0], &pair[1]);
+        let length = to.at - from.at;
+        let mut at = from.at;
+        while at < to.at {
+            let fraction = (at - from.at).as_secs_f64() / length.as_secs_f64();
+            steps.push(RampStep {
+                at,
+                voltage: interpolate(from.voltage, to.voltage, fraction),
+                current: interpolate(from.current, to.current, fraction),
+            });
+            at += step;
+        }
+    }
+    if let Some(last) = filled.last() {
+        steps.push(RampStep {
+            at: last.at,
+            voltage: last.voltage,
+            current: last.current,
+        });
+    }
+    steps
+}
+
+/**
+ * Runs the setpoint profile given with `--profile-file` on a power supply: at every step the
+ * changed setpoints are sent, followed by the commands of the device, e.g. a readback
+ * measurement. The steps are timed from the start so delays do not add up. Readings are printed
+ * as they are taken, tagged with the elapsed time and the setpoints.
+ *
+ * # Arguments
+ * `devices` - The device labels (None for a single device) with their arguments.
+ * `printer` - Prints the readings.
+ * `shape` - The field shape of the output, if any.
+ *
+ * # Returns
+ * A Result indicating success or an ApplicationError.
+ */
+pub async fn run(
+    devices: &[(Option<String>, Args)],
+    printer: &mut Printer,
+    shape: Option<&FieldShape>,
+) -> Result<(), ApplicationError> {
+    let [(None, args)] = devices else {
+        return Err(ApplicationError::Config("--profile-file takes a single device".into()));
+    };
+    let path = args.sequence.profile_file.as_deref().unwrap_or_default();
+    let profile = std::fs::read_to_string(path)
+        .map_err(|e| ApplicationError::Io(format!("Could not read profile {}", path), e))?;
+    let steps = schedule(&parse(&profile)?, Duration::from_millis(args.sequence.ramp_step_ms));
+    let device = get_communication_device(args).await?;
+    let ramp = ramp(device.as_ref(), &steps, args, |reading| {
+        with_shape(reading, shape, |reading| printer.print(reading))
+    });
+    // A shutdown request ends the profile at the current setpoints, the printed readings are kept
+    tokio::select! {
+        biased;
+        _ = shutdown::requested() => Ok(()),
+        result = ramp => result,
+    }
+}
+
+/**
+ * Executes the steps of a profile.
+ *
+ * # Arguments
+ * `device` - The power supply.
+ * `steps` - The steps.
+ * `args` - The arguments with the setpoint command templates and the readback commands.
+ * `print` - Receives the tagged readings.
+ *
+ * # Returns
+ * A Result indicating success or an ApplicationError.
+ */
+async fn ramp(
+    device: &dyn Communication,
+    steps: &[RampStep],
+    args: &Args,
+    mut print: impl FnMut(&dyn Reading) -> Result<(), ApplicationError>,
+) -> Result<(), ApplicationError> {
+    let start = Instant::now();
+    let (mut voltage, mut current) = (None, None);
+    for step in steps {
+        tokio::time::sleep_until(start + step.at).await;
+        let mut commands = Vec::new();
+        if step.voltage != voltage {
+            let command = |value: f64| args.sequence.voltage_command.replace("{value}", &format!("{:.3}", value));
+            commands.extend(step.voltage.map(command));
+        }
+        if step.current != current {
+            let command = |value: f64| args.sequence.current_command.replace("{value}", &format!("{:.3}", value));
+            commands.extend(step.current.map(command));
+        }
+        (voltage, current) = (step.voltage, step.current);
+        commands.extend(args.control.commands.iter().cloned());
+        let elapsed = start.elapsed();
+        for reading in device.command(commands).await?.unwrap_or_default() {
+            let setpoint = |value: Option<f64>| value.map(|value| format!("{:.3}", value)).unwrap_or_default();
+            let tags = vec![
+                ("elapsed".to_string(), format!("{:.3}", elapsed.as_secs_f64())),
+                ("setpoint_voltage".to_string(), setpoint(voltage)),
+                ("setpoint_current".to_string(), setpoint(current)),
+            ];
+            print(&TaggedReading::new(tags, reading))?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use std::{cell::RefCell, rc::Rc};
+
+    use async_trait::async_trait;
+    use clap::Parser;
+
+    use super::*;
+    use crate::instruments::reading::KoradReading;
+
+    /**
+     * Power supply logging the commands it receives and answering Measure with a reading.
+     */
+    struct Logging(Rc<RefCell<Vec<String>>>);
+
+    #[async_trait(?Send)]
+    impl Communication for Logging {
+        async fn command(&self, commands: Vec<String>) -> Result<Option<Vec<Box<dyn Reading>>>, ApplicationError> {
+            self.0.borrow_mut().extend(commands.iter().cloned());
+            Ok(Some(
+                commands
+                    .iter()
+                    .filter(|command| *command == "Measure")
+                    .map(|_| Box::new(KoradReading::measured(b"05.00", b"0.100", &[0x41])) as Box<dyn Reading>)
+                    .collect(),
+            ))
+        }
+    }
+
+    #[test]
+    fn test_parse() {
+        let points = parse("0s 0V; 10s 5V 1A # start\n60s 5V\n65s 0V 500mA\n").unwrap();
+        assert_eq!(points.len(), 4);
+        assert_eq!(points[1], RampPoint { at: Duration::from_secs(10), voltage: Some(5.0), current: Some(1.0) });
+        assert_eq!(points[3].current, Some(0.5));
+        assert!(parse("10s 5V; 5s 0V").is_err());
+        assert!(parse("0s 5W").is_err());
+        assert!(parse("0s").is_err());
+        assert!(parse("soon 5V").is_err());
+        assert!(parse("# nothing").is_err());
+    }
+
+    #[test]
+    fn test_schedule() {
+        let points = parse("0s 0V 1A; 2s 4V; 3s 4V; 3s 0V").unwrap();
+        let steps = schedule(&points, Duration::from_millis(500));
+        let voltages: Vec<Option<f64>> = steps.iter().map(|step| step.voltage).collect();
+        assert_eq!(
+            voltages,
+            vec![Some(0.0), Some(1.0), Some(2.0), Some(3.0), Some(4.0), Some(4.0), Some(0.0)]
+        );
+        assert!(steps.iter().all(|step| step.current == Some(1.0)));
+        assert_eq!(steps[5].at, Duration::from_millis(2500));
+        assert_eq!(steps[6].at, Duration::from_secs(3));
+    }
+
+    #[tokio::test]
+    async fn test_ramp() {
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let args = Args::parse_from(["test_program", "--device=korad-psu", "--command=Measure", "--profile-file=p.txt"]);
+        let steps = schedule(&parse("0s 0V 1A; 50ms 2V; 100ms 2V").unwrap(), Duration::from_millis(50));
+        let mut records = Vec::new();
+        ramp(&Logging(log.clone()), &steps, &args, |reading| {
+            records.push(reading.get_csv()?[1..3].join(","));
+            Ok(())
+        })
+        .await
+        .unwrap();
+        assert_eq!(
+            *log.borrow(),
+            vec!["Voltage:0.000", "Current:1.000", "Measure", "Voltage:2.000", "Measure", "Measure"]
+        );
+        assert_eq!(records, vec!["0.000,1.000", "2.000,1.000", "2.000,1.000"]);
+    }
+}
@@ -0,0 +1,168 @@
+use std::{future::Future, pin::pin, time::Duration};
+
+use futures_util::StreamExt;
+
+use crate::{
+    arguments::{Args, Device},
+    error::ApplicationError,
+    instruments::communication::{get_communication_device, Communication},
+    output::{
+        shape::{with_shape, FieldShape},
+        Printer,
+    },
+    shutdown,
+};
+
+/**
+ * Records the measurements of a UT161D: the device is opened once and the commands, Measure by
+ * default, are sent in a tight loop with the readings printed as they arrive, until Ctrl-C or SIGTERM.
+ * Responses with a bad checksum are skipped by the device.
+ *
+ * # Arguments
+ * `devices` - The device labels (None for a single device) with their arguments.
+ * `printer` - Prints the readings.
+ * `shape` - The field shape of the output, if any.
+ *
+ * # Returns
+ * A Result indicating success or the ApplicationError that ended the recording.
+ */
+pub async fn run(
+    devices: &[(Option<String>, Args)],
+    printer: &mut Printer,
+    shape: Option<&FieldShape>,
+) -> Result<(), ApplicationError> {
+    let [(None, args)] = devices else {
+        return Err(ApplicationError::Config("--record takes a single device".into()));
+    };
+    if args.connection.device != Some(Device::Unit161d) {
+        return Err(ApplicationError::Config("--record is supported for the unit161d".into()));
+    }
+    let device = get_communication_device(args).await?;
+    let commands = match args.control.commands.is_empty() {
+        true => vec!["Measure".to_string()],
+        false => args.control.commands.clone(),
+    };
+    record(device.as_ref(), &commands, printer, shape, shutdown::requested()).await
+}
+
+/**
+ * Sends the commands until a shutdown is requested and prints the readings. The request is
+ * checked between readings, so the reading being printed is always written completely.
+ *
+ * # Arguments
+ * `device` - The open device.
+ * `commands` - The commands of a cycle.
+ * `printer` - Prints the readings.
+ * `shape` - The field shape of the output, if any.
+ * `stop` - Completes when a shutdown is requested.
+ *
+ * # Returns
+ * A Result indicating success or the ApplicationError that ended the recording.
+ */
+async fn record(
+    device: &dyn Communication,
+    commands: &[String],
+    printer: &mut Printer,
+    shape: Option<&FieldShape>,
+    stop: impl Future<Output = ()>,
+) -> Result<(), ApplicationError> {
+    // Created once so a shutdown request during a cycle ends the recording after it
+    let mut stop = pin!(stop);
+    let mut readings = device.readings(commands.to_vec(), Duration::ZERO);
+    loop {
+        let reading = tokio::select! {
+            biased;
+            _ = &mut stop => return Ok(()),
+            reading = readings.next() => reading,
+        };
+        let Some(reading) = reading else {
+            return Ok(());
+        };
+        with_shape(reading?.as_ref(), shape, |reading| printer.print(reading))?;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{
+        cell::{Cell, RefCell},
+        io::Write,
+        rc::Rc,
+    };
+
+    use async_trait::async_trait;
+    use tokio::sync::watch;
+
+    use super::*;
+    use crate::{
+        arguments::Format,
+        instruments::reading::{Reading, ScpiRawReading},
+        output::{locale::Translator, CsvWriter},
+    };
+
+    /**
+     * Device answering every command with the command itself, requesting a shutdown in its
+     * second cycle.
+     */
+    struct Meter {
+        cycles: Cell<usize>,
+        shutdown: watch::Sender<bool>,
+    }
+
+    #[async_trait(?Send)]
+    impl Communication for Meter {
+        async fn command(&self, commands: Vec<String>) -> Result<Option<Vec<Box<dyn Reading>>>, ApplicationError> {
+            self.cycles.set(self.cycles.get() + 1);
+            if self.cycles.get() == 2 {
+                self.shutdown.send(true).unwrap();
+            }
+            Ok(Some(
+                commands
+                    .into_iter()
+                    .map(|command| Box::new(ScpiRawReading::new(command.into_bytes())) as Box<dyn Reading>)
+                    .collect(),
+            ))
+        }
+    }
+
+    /**
+     * Output shared with the test.
+     */
+    #[derive(Clone, Default)]
+    struct Output(Rc<RefCell<Vec<u8>>>);
+
+    impl Write for Output {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.borrow_mut().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_stops_between_records() {
+        let (sender, stop) = shutdown::channel();
+        let meter = Meter {
+            cycles: Cell::new(0),
+            shutdown: sender,
+        };
+        let output = Output::default();
+        let mut printer = Printer::new(
+            Format::RawString,
+            CsvWriter::new(',', false, Default::default(), Default::default()),
+            Translator::new(Default::default()),
+            Default::default(),
+            false,
+            Box::new(output.clone()),
+            None,
+        );
+        let commands = vec!["T1".to_string(), "T2".to_string()];
+        record(&meter, &commands, &mut printer, None, stop).await.unwrap();
+        // The shutdown requested in the second cycle ends the recording after its first record
+        assert_eq!(meter.cycles.get(), 2);
+        let printed = String::from_utf8(output.0.borrow().clone()).unwrap();
+        assert_eq!(printed, "\"T1\"\n\"T2\"\n\"T1\"\n");
+    }
+}
@@ -0,0 +1,67 @@
+use std::time::Duration;
+
+use crate::{
+    arguments::Args,
+    error::ApplicationError,
+    instruments::{
+        communication::{registry::driver, Communication},
+        reading::{Reading, TaggedReading},
+    },
+    workflow::acquire::open,
+};
+
+/**
+ * Runs a scan: for every channel in the scan list the switch closes only that channel, waits for
+ * the settle time and the measurement device runs the commands. Readings are tagged with the channel.
+ * All channels are opened when the scan ends, also on error. The switch and the measurement
+ * device are opened unless they are still open from an earlier scan.
+ *
+ * # Arguments
+ * `args` - The command line arguments. `device` is the measurement device, `switch_device` the switch.
+ * `switch` - The open switch, if any.
+ * `instrument` - The open measurement device, if any.
+ *
+ * # Returns
+ * A Result containing the channel tagged readings or an ApplicationError.
+ */
+pub async fn run(
+    args: &Args,
+    switch: &mut Option<Box<dyn Communication>>,
+    instrument: &mut Option<Box<dyn Communication>>,
+) -> Result<Option<Vec<Box<dyn Reading>>>, ApplicationError> {
+    let switch_device = args
+        .sequence.switch_device
+        .clone()
+        .ok_or_else(|| ApplicationError::Command("Scanning requires a switch device".into()))?;
+    driver(&switch_device)?.require_switch("--switch-device")?;
+    let mut switch_args = args.clone();
+    switch_args.connection.device = Some(switch_device);
+    switch_args.connection.hid = args.sequence.switch_hid.clone();
+    switch_args.connection.usb = args.sequence.switch_usb.clone();
+    switch_args.connection.init_commands = Vec::new();
+    let switch = open(&switch_args, switch).await?;
+    let instrument = open(args, instrument).await?;
+
+    let mut readings: Vec<Box<dyn Reading>> = Vec::new();
+    let mut result = Ok(());
+    for channel in &args.sequence.scan_channels {
+        result = async {
+            switch.command(vec!["Open:all".to_string(), format!("Close:{}", channel)]).await?;
+            tokio::time::sleep(Duration::from_millis(args.sequence.settle_ms)).await;
+            for reading in instrument.command(args.control.commands.clone()).await?.unwrap_or_default() {
+                readings.push(Box::new(TaggedReading::new(
+                    vec![("channel".to_string(), channel.to_string())],
+                    reading,
+                )));
+            }
+            Ok::<(), ApplicationError>(())
+        }
+        .await;
+        if result.is_err() {
+            break;
+        }
+    }
+    switch.command(vec!["Open:all".to_string()]).await?;
+    result?;
+    Ok(Some(readings))
+}
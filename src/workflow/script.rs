@@ -0,0 +1,228 @@
+use std::{collections::HashMap, time::Duration};
+
+use crate::{
+    arguments::Args,
+    config::expand,
+    error::ApplicationError,
+    instruments::{
+        communication::Communication,
+        reading::{EventKind, EventReading, Reading},
+    },
+    workflow::acquire::open,
+};
+
+/**
+ * Enum representing a statement of a measurement script.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub enum Statement {
+    /// An instrument command.
+    Command(String),
+    /// `delay 500ms` - waits before the next statement.
+    Delay(Duration),
+    /// `repeat 10 { ... }` - runs the block a number of times.
+    Repeat(u32, Vec<Statement>),
+    /// `expect <text>` - fails the script unless the last reading contains the text.
+    Expect(String),
+    /// `log <message>` - writes an event record.
+    Log(String),
+}
+
+/**
+ * Parses a duration such as `500ms`, `2s` or `1.5s`. A number without unit is milliseconds.
+ *
+ * # Arguments
+ * `value` - The duration string.
+ *
+ * # Returns
+ * A Result containing the Duration or an ApplicationError.
+ */
+pub fn parse_duration(value: &str) -> Result<Duration, ApplicationError> {
+    let value = value.trim();
+    let (number, scale) = if let Some(number) = value.strip_suffix("ms") {
+        (number, 0.001)
+    } else if let Some(number) = value.strip_suffix('s') {
+        (number, 1.0)
+    } else {
+        (value, 0.001)
+    };
+    number
+        .trim()
+        .parse::<f64>()
+        .ok()
+        .filter(|number| number.is_finite() && *number >= 0.0)
+        .map(|number| Duration::from_secs_f64(number * scale))
+        .ok_or_else(|| ApplicationError::Command(format!("Invalid duration: {}", value)))
+}
+
+/**
+ * Parses a measurement script. Every line is a command or a directive; empty lines and lines
+ * starting with `#` are ignored.
+ *
+ * # Arguments
+ * `script` - The script text.
+ *
+ * # Returns
+ * A Result containing the statements or an ApplicationError with the offending line number.
+ */
+pub fn parse(script: &str) -> Result<Vec<Statement>, ApplicationError> {
+    // Blocks being parsed; the first entry is the script itself
+    let mut blocks: Vec<(u32, Vec<Statement>)> = vec![(1, Vec::new())];
+    for (index, line) in script.lines().enumerate() {
+        let line = line.trim();
+        let error = |message: String| ApplicationError::Command(format!("Line {}: {}", index + 1, message));
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let (keyword, rest) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+        let rest = rest.trim();
+        let statement = match keyword {
+            "}" if rest.is_empty() => {
+                if blocks.len() == 1 {
+                    return Err(error("Unexpected }".into()));
+                }
+                let (count, block) = blocks.pop().unwrap_or_default();
+                Statement::Repeat(count, block)
+            }
+            "repeat" => {
+                let count = rest
+                    .strip_suffix('{')
+                    .and_then(|count| count.trim().parse::<u32>().ok())
+                    .ok_or_else(|| error(format!("Expected repeat <count> {{, got {}", line)))?;
+                blocks.push((count, Vec::new()));
+                continue;
+            }
+            "delay" => Statement::Delay(parse_duration(rest).map_err(|e| error(e.to_string()))?),
+            "expect" if !rest.is_empty() => Statement::Expect(rest.to_string()),
+            "log" => Statement::Log(rest.to_string()),
+            _ => Statement::Command(line.to_string()),
+        };
+        if let Some((_, block)) = blocks.last_mut() {
+            block.push(statement);
+        }
+    }
+    if blocks.len() > 1 {
+        return Err(ApplicationError::Command("Missing } at end of script".into()));
+    }
+    Ok(blocks.pop().map(|(_, statements)| statements).unwrap_or_default())
+}
+
+/**
+ * Executes statements against an instrument. Macros in the commands are expanded as they are sent.
+ *
+ * # Arguments
+ * `instrument` - The instrument.
+ * `statements` - The statements to execute.
+ * `macros` - The command macros.
+ * `readings` - The readings and log records produced so far.
+ * `last` - The raw string of the last instrument reading, used by expect.
+ *
+ * # Returns
+ * A Result indicating success or an ApplicationError.
+ */
+async fn execute(
+    instrument: &dyn Communication,
+    statements: &[Statement],
+    macros: &HashMap<String, Vec<String>>,
+    readings: &mut Vec<Box<dyn Reading>>,
+    last: &mut Option<String>,
+) -> Result<(), ApplicationError> {
+    for statement in statements {
+        match statement {
+            Statement::Command(command) => {
+                let mut commands = Vec::new();
+                expand(macros, command, &mut Vec::new(), &mut commands)?;
+                for command in commands {
+                    for reading in instrument.command(vec![command]).await?.unwrap_or_default() {
+                        *last = reading.get_raw_string().ok();
+                        readings.push(reading);
+                    }
+                }
+            }
+            Statement::Delay(delay) => tokio::time::sleep(*delay).await,
+            Statement::Repeat(count, block) => {
+                for _ in 0..*count {
+                    Box::pin(execute(instrument, block, macros, readings, last)).await?;
+                }
+            }
+            Statement::Expect(text) => match last {
+                Some(value) if value.contains(text.as_str()) => {}
+                _ => {
+                    return Err(ApplicationError::Command(format!(
+                        "Expected {} in {:?}",
+                        text,
+                        last.as_deref().unwrap_or("no reading")
+                    )))
+                }
+            },
+            Statement::Log(message) => readings.push(Box::new(EventReading::new(EventKind::Log, message.clone()))),
+        }
+    }
+    Ok(())
+}
+
+/**
+ * Runs the script file given with `--script` on the device, opening it unless it is still open
+ * from an earlier run.
+ *
+ * # Arguments
+ * `args` - The command line arguments.
+ * `device` - The open device, if any.
+ *
+ * # Returns
+ * A Result containing the readings and log records or an ApplicationError.
+ */
+pub async fn run(
+    args: &Args,
+    device: &mut Option<Box<dyn Communication>>,
+) -> Result<Option<Vec<Box<dyn Reading>>>, ApplicationError> {
+    let path = args
+        .control.script
+        .as_ref()
+        .ok_or_else(|| ApplicationError::Command("No script given".into()))?;
+    let script = std::fs::read_to_string(path)
+        .map_err(|e| ApplicationError::Command(format!("Could not read script {}: {}", path, e)))?;
+    let statements = parse(&script)?;
+    let instrument = open(args, device).await?;
+    let mut readings: Vec<Box<dyn Reading>> = Vec::new();
+    execute(instrument, &statements, &args.control.script_macros, &mut readings, &mut None).await?;
+    Ok(Some(readings))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("500ms").unwrap(), Duration::from_millis(500));
+        assert_eq!(parse_duration("1.5s").unwrap(), Duration::from_millis(1500));
+        assert_eq!(parse_duration("20").unwrap(), Duration::from_millis(20));
+        assert!(parse_duration("soon").is_err());
+    }
+
+    #[test]
+    fn test_parse_script() {
+        let script = "# Configure the generator\nAPPL:SIN 1000,1\ndelay 500ms\nrepeat 3 {\n  MEAS:VOLT?\n  expect +\n}\nlog done\n";
+        assert_eq!(
+            parse(script).unwrap(),
+            vec![
+                Statement::Command("APPL:SIN 1000,1".into()),
+                Statement::Delay(Duration::from_millis(500)),
+                Statement::Repeat(
+                    3,
+                    vec![Statement::Command("MEAS:VOLT?".into()), Statement::Expect("+".into())]
+                ),
+                Statement::Log("done".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_script_errors() {
+        assert!(parse("repeat 3 {\nMeasure\n").is_err());
+        assert!(parse("Measure\n}\n").is_err());
+        assert!(parse("repeat many {\n}\n").is_err());
+        assert!(parse("delay later\n").is_err());
+    }
+}
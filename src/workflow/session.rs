@@ -0,0 +1,168 @@
+use tokio::io::{stdin, AsyncBufRead, AsyncBufReadExt, BufReader};
+use tracing::error;
+
+use crate::{
+    arguments::Args,
+    config::Config,
+    error::ApplicationError,
+    instruments::communication::{get_communication_device, Communication},
+    output::{
+        shape::{with_shape, FieldShape},
+        Printer,
+    },
+};
+
+/**
+ * Runs a session with the commands read from stdin: the device is opened once and every line is
+ * sent as a command as soon as it is read, with its readings printed before the next line is
+ * read. Empty lines and lines starting with `#` are skipped, and macros are expanded. A failing
+ * command is logged as an error and does not end the session; the session ends at the end of
+ * stdin.
+ *
+ * # Arguments
+ * `config` - The configuration file with the macros.
+ * `devices` - The device labels (None for a single device) with their arguments.
+ * `printer` - Prints the readings.
+ * `shape` - The field shape of the output, if any.
+ *
+ * # Returns
+ * A Result indicating success, or the ApplicationError of the last failing command.
+ */
+pub async fn run(
+    config: &Config,
+    devices: &[(Option<String>, Args)],
+    printer: &mut Printer,
+    shape: Option<&FieldShape>,
+) -> Result<(), ApplicationError> {
+    let [(None, args)] = devices else {
+        return Err(ApplicationError::Config("--stdin takes a single device".into()));
+    };
+    let device = get_communication_device(args).await?;
+    run_lines(config, args, device.as_ref(), BufReader::new(stdin()), printer, shape).await
+}
+
+/**
+ * Sends every line of the input as a command and prints the readings.
+ *
+ * # Arguments
+ * `config` - The configuration file with the macros.
+ * `args` - The arguments of the device.
+ * `device` - The open device.
+ * `input` - The command lines.
+ * `printer` - Prints the readings.
+ * `shape` - The field shape of the output, if any.
+ *
+ * # Returns
+ * A Result indicating success, or the ApplicationError of the last failing command.
+ */
+async fn run_lines(
+    config: &Config,
+    args: &Args,
+    device: &dyn Communication,
+    input: impl AsyncBufRead + Unpin,
+    printer: &mut Printer,
+    shape: Option<&FieldShape>,
+) -> Result<(), ApplicationError> {
+    let mut lines = input.lines();
+    let mut error = None;
+    while let Some(line) = lines
+        .next_line()
+        .await
+        .map_err(|e| ApplicationError::Io("Could not read commands".into(), e))?
+    {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let mut command_args = args.clone();
+        command_args.control.commands = vec![line.to_string()];
+        let result = match config.expand_macros(&mut command_args) {
+            Ok(()) => device.command(command_args.control.commands).await,
+            Err(e) => Err(e),
+        };
+        match result {
+            Ok(readings) => {
+                for reading in readings.unwrap_or_default() {
+                    with_shape(reading.as_ref(), shape, |reading| printer.print(reading))?;
+                }
+            }
+            Err(e) => {
+                error!("{}: {}", line, e);
+                error = Some(e);
+            }
+        }
+    }
+    error.map_or(Ok(()), Err)
+}
+
+#[cfg(test)]
+mod test {
+    use std::{cell::RefCell, io::Write, rc::Rc};
+
+    use async_trait::async_trait;
+    use clap::Parser;
+
+    use super::*;
+    use crate::{
+        arguments::Format,
+        instruments::reading::{Reading, ScpiRawReading},
+        output::{locale::Translator, CsvWriter},
+    };
+
+    /**
+     * Device answering every command with the command itself, failing on `FAIL`.
+     */
+    struct Echo;
+
+    #[async_trait(?Send)]
+    impl Communication for Echo {
+        async fn command(&self, commands: Vec<String>) -> Result<Option<Vec<Box<dyn Reading>>>, ApplicationError> {
+            if commands.iter().any(|command| command == "FAIL") {
+                return Err(ApplicationError::Command("failed".into()));
+            }
+            Ok(Some(
+                commands
+                    .into_iter()
+                    .map(|command| Box::new(ScpiRawReading::new(command.into_bytes())) as Box<dyn Reading>)
+                    .collect(),
+            ))
+        }
+    }
+
+    /**
+     * Output shared with the test.
+     */
+    #[derive(Clone, Default)]
+    struct Output(Rc<RefCell<Vec<u8>>>);
+
+    impl Write for Output {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.borrow_mut().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_run_lines() {
+        let config = Config::parse("[macros]\nsetup = [\"*RST\", \"*CLS\"]\n").unwrap();
+        let args = Args::parse_from(["test_program", "--device=generic-scpi-usb", "--stdin"]);
+        let output = Output::default();
+        let mut printer = Printer::new(
+            Format::RawString,
+            CsvWriter::new(',', false, Default::default(), Default::default()),
+            Translator::new(Default::default()),
+            Default::default(),
+            false,
+            Box::new(output.clone()),
+            None,
+        );
+        let input: &[u8] = b"*IDN?\n\n# comment\nsetup\nFAIL\nMEAS?\n";
+        let result = run_lines(&config, &args, &Echo, input, &mut printer, None).await;
+        assert!(result.is_err());
+        let printed = String::from_utf8(output.0.borrow().clone()).unwrap();
+        assert_eq!(printed, "\"*IDN?\"\n\"*RST\"\n\"*CLS\"\n\"MEAS?\"\n");
+    }
+}
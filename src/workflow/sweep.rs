@@ -0,0 +1,213 @@
+use std::time::Duration;
+
+use crate::{
+    arguments::Args,
+    error::ApplicationError,
+    instruments::{
+        communication::{get_communication_device, Communication},
+        reading::{Reading, TaggedReading},
+    },
+    output::{
+        shape::{with_shape, FieldShape},
+        Printer,
+    },
+    shutdown,
+};
+
+/**
+ * Computes the frequencies of a sweep from start to stop, both included.
+ *
+ * # Arguments
+ * `start` - The start frequency in Hz.
+ * `stop` - The stop frequency in Hz.
+ * `points` - The number of frequencies.
+ * `log` - Whether the frequencies are spaced logarithmically instead of linearly.
+ *
+ * # Returns
+ * A Result containing the frequencies or an ApplicationError if the sweep is invalid.
+ */
+pub fn frequencies(start: f64, stop: f64, points: u32, log: bool) -> Result<Vec<f64>, ApplicationError> {
+    if points == 0 {
+        return Err(ApplicationError::Config("A sweep needs at least one point".into()));
+    }
+    if log && (start <= 0.0 || stop <= 0.0) {
+        return Err(ApplicationError::Config("A logarithmic sweep needs positive frequencies".into()));
+    }
+    let steps = (points - 1).max(1) as f64;
+    Ok((0..points)
+        .map(|point| {
+            let fraction = point as f64 / steps;
+            match log {
+                true => start * (stop / start).powf(fraction),
+                false => start + (stop - start) * fraction,
+            }
+        })
+        .collect())
+}
+
+/**
+ * A device taking part in a sweep.
+ */
+struct SweepDevice {
+    label: Option<String>,
+    device: Box<dyn Communication>,
+    commands: Vec<String>,
+}
+
+/**
+ * Runs a frequency sweep: the first device is the generator and receives the sweep command
+ * with each frequency, followed by its own commands. After the dwell time the other devices
+ * run their commands. The readings of every point are printed as they are taken, tagged with
+ * the frequency and, with several devices, the device label.
+ *
+ * # Arguments
+ * `devices` - The device labels (None for a single device) with their arguments.
+ * `printer` - Prints the readings.
+ * `shape` - The field shape of the output, if any.
+ *
+ * # Returns
+ * A Result indicating success or an ApplicationError.
+ */
+pub async fn run(
+    devices: &[(Option<String>, Args)],
+    printer: &mut Printer,
+    shape: Option<&FieldShape>,
+) -> Result<(), ApplicationError> {
+    let args = &devices[0].1;
+    let (Some(start), Some(stop)) = (args.sequence.sweep_start, args.sequence.sweep_stop) else {
+        return Err(ApplicationError::Config("A sweep needs --sweep-start and --sweep-stop".into()));
+    };
+    let frequencies = frequencies(start, stop, args.sequence.sweep_points, args.sequence.sweep_log)?;
+    let mut opened = Vec::new();
+    for (label, args) in devices {
+        opened.push(SweepDevice {
+            label: label.clone(),
+            device: get_communication_device(args).await?,
+            commands: args.control.commands.clone(),
+        });
+    }
+    let dwell = Duration::from_millis(args.sequence.dwell_ms);
+    let sweep = sweep(&opened, &frequencies, &args.sequence.sweep_command, dwell, |reading| {
+        with_shape(reading, shape, |reading| printer.print(reading))
+    });
+    // A shutdown request ends the sweep between the readings, the printed readings are kept
+    tokio::select! {
+        biased;
+        _ = shutdown::requested() => Ok(()),
+        result = sweep => result,
+    }
+}
+
+/**
+ * Steps the generator through the frequencies and takes the readings of every point.
+ *
+ * # Arguments
+ * `devices` - The generator followed by the measuring devices.
+ * `frequencies` - The frequencies in Hz.
+ * `sweep_command` - The generator command with `{frequency}` for the frequency.
+ * `dwell` - The time to wait after setting a frequency.
+ * `print` - Receives the tagged readings.
+ *
+ * # Returns
+ * A Result indicating success or an ApplicationError.
+ */
+async fn sweep(
+    devices: &[SweepDevice],
+    frequencies: &[f64],
+    sweep_command: &str,
+    dwell: Duration,
+    mut print: impl FnMut(&dyn Reading) -> Result<(), ApplicationError>,
+) -> Result<(), ApplicationError> {
+    let Some((generator, meters)) = devices.split_first() else {
+        return Ok(());
+    };
+    for frequency in frequencies {
+        let mut commands = vec![sweep_command.replace("{frequency}", &frequency.to_string())];
+        commands.extend(generator.commands.iter().cloned());
+        let mut readings = vec![(generator, generator.device.command(commands).await?)];
+        tokio::time::sleep(dwell).await;
+        for meter in meters {
+            readings.push((meter, meter.device.command(meter.commands.clone()).await?));
+        }
+        for (device, device_readings) in readings {
+            for reading in device_readings.unwrap_or_default() {
+                let mut tags = vec![("frequency".to_string(), frequency.to_string())];
+                if let Some(label) = &device.label {
+                    tags.push(("device".to_string(), label.clone()));
+                }
+                print(&TaggedReading::new(tags, reading))?;
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use std::{cell::RefCell, rc::Rc};
+
+    use async_trait::async_trait;
+
+    use super::*;
+    use crate::instruments::reading::KoradReading;
+
+    /**
+     * Device logging the commands it receives and answering queries with a reading.
+     */
+    struct Logging(Rc<RefCell<Vec<String>>>);
+
+    #[async_trait(?Send)]
+    impl Communication for Logging {
+        async fn command(&self, commands: Vec<String>) -> Result<Option<Vec<Box<dyn Reading>>>, ApplicationError> {
+            self.0.borrow_mut().extend(commands.iter().cloned());
+            Ok(Some(
+                commands
+                    .into_iter()
+                    .filter(|command| command.ends_with('?'))
+                    .map(|_| Box::new(KoradReading::measured(b"01.00", b"0.100", &[0x41])) as Box<dyn Reading>)
+                    .collect(),
+            ))
+        }
+    }
+
+    #[test]
+    fn test_frequencies() {
+        assert_eq!(frequencies(100.0, 400.0, 4, false).unwrap(), vec![100.0, 200.0, 300.0, 400.0]);
+        let log = frequencies(10.0, 1000.0, 3, true).unwrap();
+        assert!((log[1] - 100.0).abs() < 1e-9);
+        assert!((log[2] - 1000.0).abs() < 1e-9);
+        assert_eq!(frequencies(50.0, 60.0, 1, false).unwrap(), vec![50.0]);
+        assert!(frequencies(0.0, 1000.0, 3, true).is_err());
+        assert!(frequencies(1.0, 2.0, 0, false).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_sweep() {
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let devices = vec![
+            SweepDevice {
+                label: Some("generator".into()),
+                device: Box::new(Logging(log.clone())),
+                commands: vec!["OUTP ON".into()],
+            },
+            SweepDevice {
+                label: Some("dmm".into()),
+                device: Box::new(Logging(log.clone())),
+                commands: vec!["MEAS:VOLT:AC?".into()],
+            },
+        ];
+        let mut records = Vec::new();
+        sweep(&devices, &[100.0, 1000.0], "FREQ {frequency}", Duration::ZERO, |reading| {
+            assert_eq!(reading.get_csv_header()?[..2], ["frequency", "device"]);
+            records.push(reading.get_csv()?[..2].join(","));
+            Ok(())
+        })
+        .await
+        .unwrap();
+        assert_eq!(records, vec!["100,dmm", "1000,dmm"]);
+        assert_eq!(
+            *log.borrow(),
+            vec!["FREQ 100", "OUTP ON", "MEAS:VOLT:AC?", "FREQ 1000", "OUTP ON", "MEAS:VOLT:AC?"]
+        );
+    }
+}
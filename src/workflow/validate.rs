@@ -0,0 +1,374 @@
+use std::collections::HashMap;
+
+use serde::Deserialize;
+use toml::Spanned;
+
+use crate::{
+    arguments::{Args, Device},
+    config::{self, Config, Profile},
+    error::ApplicationError,
+    instruments::communication::registry::driver,
+    output::shape::SinkShapes,
+    workflow::script,
+};
+
+/**
+ * Sections of a configuration file with the positions of their entries, used to report
+ * problems with line numbers.
+ */
+#[derive(Deserialize)]
+struct Layout {
+    #[serde(default)]
+    profiles: HashMap<String, Spanned<toml::Value>>,
+    #[serde(default)]
+    macros: HashMap<String, Spanned<toml::Value>>,
+    #[serde(default)]
+    groups: HashMap<String, Spanned<Vec<Spanned<String>>>>,
+    // Not spanned, as nested tables inside a spanned value do not deserialize
+    sinks: Option<toml::Value>,
+}
+
+/**
+ * A problem found in a file.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct Problem {
+    /**
+     * Line number, starting at 1.
+     */
+    pub line: usize,
+    pub message: String,
+}
+
+/**
+ * Returns the line number of a byte offset.
+ *
+ * # Arguments
+ * `content` - The file content.
+ * `offset` - The byte offset.
+ *
+ * # Returns
+ * The line number, starting at 1.
+ */
+fn line_of(content: &str, offset: usize) -> usize {
+    content[..offset.min(content.len())].matches('\n').count() + 1
+}
+
+/**
+ * Returns the message of an error without the error kind.
+ *
+ * # Arguments
+ * `error` - The error.
+ *
+ * # Returns
+ * The message.
+ */
+fn message(error: ApplicationError) -> String {
+    match error {
+        ApplicationError::Usb(message)
+        | ApplicationError::Hid(message)
+        | ApplicationError::Command(message)
+        | ApplicationError::Audio(message)
+        | ApplicationError::Serial(message)
+        | ApplicationError::Bluetooth(message)
+        | ApplicationError::Gpib(message)
+        | ApplicationError::Network(message)
+        | ApplicationError::Config(message)
+        | ApplicationError::General(message)
+        | ApplicationError::Timeout(message)
+        | ApplicationError::Assertion(message) => message,
+        #[cfg(has_discovery)]
+        ApplicationError::NotFound(message) => message,
+        ApplicationError::Io(message, e) => format!("{}: {}", message, e),
+        #[cfg(feature = "usb")]
+        ApplicationError::Nusb(message, e) => format!("{}: {}", message, e),
+        #[cfg(feature = "usb")]
+        ApplicationError::Transfer(message, e) => format!("{}: {}", message, e),
+        #[cfg(feature = "hid")]
+        ApplicationError::HidApi(message, e) => format!("{}: {}", message, e),
+        #[cfg(feature = "serial")]
+        ApplicationError::SerialPort(message, e) => format!("{}: {}", message, e),
+        ApplicationError::ParseInt(message, e) => format!("{}: {}", message, e),
+        ApplicationError::ParseFloat(message, e) => format!("{}: {}", message, e),
+        ApplicationError::Toml(e) => format!("Invalid configuration: {}", e),
+    }
+}
+
+/**
+ * Checks a command against the typed commands of a device without opening it. Commands of
+ * SCPI devices are sent as is and always pass.
+ *
+ * # Arguments
+ * `device` - The device.
+ * `command` - The command, after macro expansion.
+ *
+ * # Returns
+ * A Result indicating success or an ApplicationError if the device does not accept the command.
+ */
+pub fn check_command(device: &Device, command: &str) -> Result<(), ApplicationError> {
+    (driver(device)?.check_command)(command)
+}
+
+/**
+ * Checks that the device of a profile can act on the limits of the profile.
+ *
+ * # Arguments
+ * `device` - The device of the profile.
+ * `profile` - The profile.
+ *
+ * # Returns
+ * A Result indicating success or an ApplicationError naming the setting the device cannot act on.
+ */
+fn check_profile_capabilities(device: &Device, profile: &Profile) -> Result<(), ApplicationError> {
+    let driver = driver(device)?;
+    let limits = [
+        (profile.max_voltage.is_some(), "max_voltage"),
+        (profile.max_current.is_some(), "max_current"),
+        (profile.dangerous_output_voltage.is_some(), "dangerous_output_voltage"),
+    ];
+    for (_, setting) in limits.iter().filter(|(given, _)| *given) {
+        driver.require_source_control(setting)?;
+    }
+    Ok(())
+}
+
+/**
+ * Checks a configuration file: the syntax, every profile, macro, group and sink shape, that
+ * groups name existing profiles, that macros are not recursive and that the commands of each
+ * profile are accepted by its device.
+ *
+ * # Arguments
+ * `content` - The TOML content.
+ *
+ * # Returns
+ * The problems found, ordered by line.
+ */
+pub fn check_config(content: &str) -> Vec<Problem> {
+    let layout: Layout = match toml::from_str(content) {
+        Ok(layout) => layout,
+        Err(e) => {
+            return vec![Problem {
+                line: e.span().map(|span| line_of(content, span.start)).unwrap_or(1),
+                message: e.message().to_string(),
+            }]
+        }
+    };
+    let mut problems = Vec::new();
+    let mut problem = |offset: usize, message: String| problems.push(Problem { line: line_of(content, offset), message });
+    let mut macros = HashMap::new();
+    for (name, value) in &layout.macros {
+        match value.get_ref().clone().try_into::<Vec<String>>() {
+            Ok(commands) => {
+                macros.insert(name.clone(), commands);
+            }
+            Err(e) => problem(value.span().start, format!("Macro {}: {}", name, e.message())),
+        }
+    }
+    for (name, value) in &layout.macros {
+        if let Err(e) = config::expand(&macros, name, &mut Vec::new(), &mut Vec::new()) {
+            problem(value.span().start, message(e));
+        }
+    }
+    for (name, value) in &layout.profiles {
+        let profile = match value.get_ref().clone().try_into::<Profile>() {
+            Ok(profile) => profile,
+            Err(e) => {
+                problem(value.span().start, format!("Profile {}: {}", name, e.message()));
+                continue;
+            }
+        };
+        let Some(device) = &profile.device else {
+            continue;
+        };
+        if let Err(e) = check_profile_capabilities(device, &profile) {
+            problem(value.span().start, format!("Profile {}: {}", name, message(e)));
+        }
+        for command in profile.init.iter().chain(&profile.commands) {
+            let mut expanded = Vec::new();
+            if config::expand(&macros, command, &mut Vec::new(), &mut expanded).is_err() {
+                continue;
+            }
+            for command in expanded {
+                if let Err(e) = check_command(device, &command) {
+                    problem(value.span().start, format!("Profile {}: {}", name, message(e)));
+                }
+            }
+        }
+    }
+    for (name, members) in &layout.groups {
+        if members.get_ref().is_empty() {
+            problem(members.span().start, format!("Group {} has no profiles", name));
+        }
+        for member in members.get_ref() {
+            if !layout.profiles.contains_key(member.get_ref()) {
+                problem(member.span().start, format!("Group {}: unknown profile {}", name, member.get_ref()));
+            }
+        }
+    }
+    if let Some(sinks) = &layout.sinks {
+        if let Err(e) = sinks.clone().try_into::<SinkShapes>() {
+            let line = content.lines().position(|line| line.trim_start().starts_with("[sinks")).unwrap_or_default();
+            problems.push(Problem {
+                line: line + 1,
+                message: format!("Sinks: {}", e.message()),
+            });
+        }
+    }
+    if problems.is_empty() {
+        // Anything the sections above do not cover, e.g. a misspelled section
+        if let Err(e) = Config::parse(content) {
+            problems.push(Problem { line: 1, message: message(e) });
+        }
+    }
+    problems.sort_by(|a, b| (a.line, &a.message).cmp(&(b.line, &b.message)));
+    problems
+}
+
+/**
+ * Checks a measurement script: the directives and blocks and, if the device is known, that
+ * every command is accepted by the device.
+ *
+ * # Arguments
+ * `content` - The script text.
+ * `device` - The device the script is run on, if known.
+ * `macros` - The macros of the configuration file.
+ *
+ * # Returns
+ * The problems found, ordered by line.
+ */
+pub fn check_script(content: &str, device: Option<&Device>, macros: &HashMap<String, Vec<String>>) -> Vec<Problem> {
+    let mut problems = Vec::new();
+    if let Err(e) = script::parse(content) {
+        let message = message(e);
+        // Block errors are reported as "Line <n>: <message>"
+        let line = message
+            .strip_prefix("Line ")
+            .and_then(|rest| rest.split_once(':'))
+            .and_then(|(line, _)| line.parse().ok())
+            .unwrap_or_else(|| content.lines().count().max(1));
+        problems.push(Problem { line, message });
+    }
+    let Some(device) = device else {
+        return problems;
+    };
+    for (index, line) in content.lines().enumerate() {
+        let line = line.trim();
+        let (keyword, rest) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+        let directive = matches!(keyword, "repeat" | "delay" | "log")
+            || (keyword == "}" && rest.trim().is_empty())
+            || (keyword == "expect" && !rest.trim().is_empty());
+        if line.is_empty() || line.starts_with('#') || directive {
+            continue;
+        }
+        let mut expanded = Vec::new();
+        let result = config::expand(macros, line, &mut Vec::new(), &mut expanded)
+            .and_then(|_| expanded.iter().try_for_each(|command| check_command(device, command)));
+        if let Err(e) = result {
+            problems.push(Problem { line: index + 1, message: message(e) });
+        }
+    }
+    problems.sort_by_key(|problem| problem.line);
+    problems
+}
+
+/**
+ * Checks the files given with `--validate` and prints every problem as `file:line: message`.
+ * Files ending in `.toml` are configuration files, all others are scripts. Script commands are
+ * checked against the device of `--device` or `--profile`, using the macros of the
+ * configuration file.
+ *
+ * # Arguments
+ * `args` - The command line arguments.
+ *
+ * # Returns
+ * A Result indicating success or an ApplicationError with the number of problems found.
+ */
+pub fn validate(args: &Args) -> Result<(), ApplicationError> {
+    let config = Config::load(args).unwrap_or_default();
+    let mut device_args = args.clone();
+    if let Some(name) = args.common.profiles.first() {
+        config.apply_profile(name, &mut device_args)?;
+    }
+    let mut count = 0;
+    for path in &args.validate {
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| ApplicationError::Config(format!("Could not read {}: {}", path, e)))?;
+        let problems = match path.ends_with(".toml") {
+            true => check_config(&content),
+            false => check_script(&content, device_args.connection.device.as_ref(), &config.macros),
+        };
+        for problem in &problems {
+            println!("{}:{}: {}", path, problem.line, problem.message);
+        }
+        if problems.is_empty() {
+            println!("{}: ok", path);
+        }
+        count += problems.len();
+    }
+    match count {
+        0 => Ok(()),
+        count => Err(ApplicationError::Config(format!("{} problems found", count))),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const CONFIG: &str = r#"
+[profiles.psu]
+device = "korad-psu"
+serial = "/dev/ttyACM0"
+commands = ["setup", "Ocp:Maybe"]
+
+[profiles.dmm]
+device = "unit161d"
+reader = "no-such-reader"
+
+[macros]
+setup = ["Voltage:5", "Output:On"]
+loop-a = ["loop-b"]
+loop-b = ["loop-a"]
+
+[groups]
+bench = ["psu", "scope"]
+"#;
+
+    #[test]
+    fn test_check_config() {
+        let problems = check_config(CONFIG);
+        let lines: Vec<usize> = problems.iter().map(|problem| problem.line).collect();
+        assert_eq!(problems.len(), 5, "{:?}", problems);
+        assert!(problems[0].message.starts_with("Profile psu: "));
+        assert!(problems[1].message.starts_with("Profile dmm: "));
+        assert!(problems[2].message.starts_with("Recursive macro"));
+        assert!(problems[4].message.contains("unknown profile scope"));
+        assert_eq!(lines[4], 17);
+        assert!(lines[0] <= 5 && lines[1] >= 7 && lines[1] <= 9);
+    }
+
+    #[test]
+    fn test_check_config_valid() {
+        assert_eq!(check_config("[profiles.psu]\ndevice = \"riden-psu\"\ncommands = [\"Measure\"]\n"), Vec::new());
+        assert_eq!(check_config("[sinks.output]\nfields = [\"value\"]\n"), Vec::new());
+        let problems = check_config("[profiles.psu\ndevice = 1\n");
+        assert_eq!(problems.len(), 1);
+        assert_eq!(problems[0].line, 1);
+        let problems = check_config("[profiles.dmm]\ndevice = \"fluke-serial\"\nmax_voltage = 5.0\n");
+        assert_eq!(problems.len(), 1);
+        assert_eq!(problems[0].message, "Profile dmm: max_voltage needs a device that can drive an output, fluke-serial cannot");
+    }
+
+    #[test]
+    fn test_check_script() {
+        let macros = HashMap::from([("setup".to_string(), vec!["Voltage:5".to_string()])]);
+        let script = "# Setup\nsetup\ndelay 100ms\nrepeat 2 {\n    Measure\n    Volts:3\n}\nOcp:Maybe\n";
+        assert_eq!(check_script(script, None, &macros), Vec::new());
+        let problems = check_script(script, Some(&Device::KoradPsu), &macros);
+        assert_eq!(problems.iter().map(|problem| problem.line).collect::<Vec<_>>(), [6, 8]);
+        let problems = check_script("delay soon\n}\n", None, &macros);
+        assert_eq!(problems.len(), 1);
+        assert_eq!(problems[0].line, 1);
+        assert_eq!(check_command(&Device::GenericScpiUsb, "MEAS:VOLT?").ok(), Some(()));
+    }
+}
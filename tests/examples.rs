@@ -0,0 +1,317 @@
+//! Runs the flows of the `examples/` directory against the built binary. Nothing here needs
+//! hardware: the configuration files and scripts are checked with `--validate`, and the serial
+//! instruments of the battery test and the daemon are simulated on a pseudo terminal. The sweep
+//! runs on USB and is driven against scripted pipes in the tests of `scpiusb`. There is no MQTT
+//! sink, so there is no MQTT flow to run.
+
+use std::{
+    io::{ErrorKind, Read, Write},
+    net::{TcpListener, TcpStream},
+    process::{Child, Command, Output},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    thread::{sleep, spawn, JoinHandle},
+    time::{Duration, Instant},
+};
+
+use serialport::{SerialPort, TTYPort};
+
+/**
+ * Time to wait for the daemon to listen.
+ */
+const STARTUP_TIMEOUT: Duration = Duration::from_secs(10);
+
+/**
+ * Time to wait for a simulator to read what the binary sent.
+ */
+const RECEIVE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/**
+ * Runs the binary to completion.
+ *
+ * # Arguments
+ * `args` - The command line arguments.
+ *
+ * # Returns
+ * The output of the process.
+ */
+fn run(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_hardware-measurement"))
+        .args(args)
+        .output()
+        .expect("Could not run hardware-measurement")
+}
+
+/**
+ * An instrument simulated on a pseudo terminal. Every query, text up to a `?`, is answered with
+ * the response of the first query suffix it ends with; unknown queries are not answered.
+ */
+struct Simulator {
+    /**
+     * The port the binary opens.
+     */
+    port: TTYPort,
+    /**
+     * Everything the binary sent.
+     */
+    received: Arc<Mutex<String>>,
+    /**
+     * Set to stop the simulator thread.
+     */
+    stop: Arc<AtomicBool>,
+    /**
+     * The simulator thread.
+     */
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Simulator {
+    /**
+     * Starts answering queries on a new pseudo terminal.
+     *
+     * # Arguments
+     * `responses` - The query suffixes and their responses.
+     *
+     * # Returns
+     * A new Simulator instance.
+     */
+    fn start(responses: &'static [(&'static str, &'static [u8])]) -> Self {
+        let (mut instrument, port) = TTYPort::pair().expect("Could not open a pseudo terminal");
+        let received = Arc::new(Mutex::new(String::new()));
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread = {
+            let (received, stop) = (received.clone(), stop.clone());
+            spawn(move || {
+                let mut query = String::new();
+                let mut buffer = [0u8; 64];
+                while !stop.load(Ordering::Relaxed) {
+                    let length = match instrument.read(&mut buffer) {
+                        Ok(length) => length,
+                        Err(e) if e.kind() == ErrorKind::TimedOut => continue,
+                        Err(e) => panic!("Simulator failed to read: {}", e),
+                    };
+                    received.lock().unwrap().extend(buffer[..length].iter().map(|&byte| byte as char));
+                    for &byte in &buffer[..length] {
+                        query.push(byte as char);
+                        if byte != b'?' {
+                            continue;
+                        }
+                        if let Some((_, response)) = responses.iter().find(|(suffix, _)| query.ends_with(suffix)) {
+                            instrument.write_all(response).unwrap();
+                        }
+                        query.clear();
+                    }
+                }
+            })
+        };
+        Self {
+            port,
+            received,
+            stop,
+            thread: Some(thread),
+        }
+    }
+
+    /**
+     * Returns the path of the port.
+     *
+     * # Returns
+     * The path of the pseudo terminal.
+     */
+    fn path(&self) -> String {
+        self.port.name().expect("The pseudo terminal has no name")
+    }
+
+    /**
+     * Checks everything the binary sent, waiting for the simulator to read what is still in the
+     * pseudo terminal.
+     *
+     * # Arguments
+     * `expected` - The expected text.
+     */
+    fn assert_received(&self, expected: &str) {
+        let start = Instant::now();
+        while *self.received.lock().unwrap() != expected && start.elapsed() < RECEIVE_TIMEOUT {
+            sleep(Duration::from_millis(10));
+        }
+        assert_eq!(*self.received.lock().unwrap(), expected);
+    }
+}
+
+impl Drop for Simulator {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+/**
+ * Kills the daemon when the test ends, also when it fails.
+ */
+struct Daemon(Child);
+
+impl Drop for Daemon {
+    fn drop(&mut self) {
+        let _ = self.0.kill();
+        let _ = self.0.wait();
+    }
+}
+
+/**
+ * Sends a GET request to the daemon.
+ *
+ * # Arguments
+ * `address` - The address of the daemon.
+ * `path` - The request path.
+ *
+ * # Returns
+ * The status line and the body of the response.
+ */
+fn get(address: &str, path: &str) -> (String, serde_json::Value) {
+    let mut stream = TcpStream::connect(address).expect("Could not connect to the daemon");
+    write!(stream, "GET {} HTTP/1.1\r\nHost: {}\r\n\r\n", path, address).unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    let (head, body) = response.split_once("\r\n\r\n").expect("Malformed response");
+    (head.lines().next().unwrap_or_default().to_string(), serde_json::from_str(body).unwrap())
+}
+
+#[test]
+fn test_example_configurations_are_valid() {
+    let output = run(&[
+        "--validate",
+        "examples/battery-test/config.toml",
+        "examples/sweep/config.toml",
+        "examples/daemon/config.toml",
+    ]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stdout));
+}
+
+#[test]
+fn test_battery_test_script_is_valid() {
+    let output = run(&[
+        "--config=examples/battery-test/config.toml",
+        "--profile=charger",
+        "--validate",
+        "examples/battery-test/charge.txt",
+    ]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stdout));
+}
+
+#[cfg(feature = "serial")]
+#[test]
+fn test_battery_test_charges_the_cell() {
+    let korad: &[(&str, &[u8])] = &[
+        ("VOUT1?", b"4.20"),
+        ("IOUT1?", b"0.498"),
+        // Constant voltage with the output on
+        ("STATUS?", &[0x41]),
+    ];
+    let simulator = Simulator::start(korad);
+    // Three steps without the waits of the example
+    let script = std::fs::read_to_string("examples/battery-test/charge.txt")
+        .unwrap()
+        .replace("repeat 60", "repeat 3")
+        .replace("delay 2s", "delay 10ms")
+        .replace("delay 10s", "delay 10ms");
+    let path = std::env::temp_dir().join(format!("charge-{}.txt", std::process::id()));
+    std::fs::write(&path, script).unwrap();
+
+    let output = run(&[
+        "--config=examples/battery-test/config.toml",
+        "--profile=charger",
+        &format!("--serial={}", simulator.path()),
+        &format!("--script={}", path.display()),
+    ]);
+    let _ = std::fs::remove_file(&path);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "{}{}", stdout, String::from_utf8_lossy(&output.stderr));
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert_eq!(lines[..3], ["output,4.2,0.498,65,CV,true"; 3]);
+    assert!(lines[3].starts_with("event,") && lines[3].ends_with(",log,Charge test done"), "{}", lines[3]);
+    assert_eq!(lines.len(), 4);
+    // The init commands of the profile, the cc-cv macro, three measurements and the stop macro
+    simulator.assert_received(&format!("OUT0OVP1OCP1VSET1:4.20ISET1:0.500OUT1{}OUT0", "VOUT1?IOUT1?STATUS?".repeat(3)));
+}
+
+#[test]
+fn test_sweep_script_is_valid() {
+    let output = run(&[
+        "--config=examples/sweep/config.toml",
+        "--profile=generator",
+        "--validate",
+        "examples/sweep/steps.txt",
+    ]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stdout));
+}
+
+#[test]
+fn test_invalid_script_is_reported() {
+    // The sweep steps are not Korad commands
+    let output = run(&[
+        "--config=examples/battery-test/config.toml",
+        "--profile=charger",
+        "--validate",
+        "examples/sweep/steps.txt",
+    ]);
+    assert_eq!(output.status.code(), Some(5));
+    assert!(String::from_utf8_lossy(&output.stdout).contains("examples/sweep/steps.txt:2: "));
+}
+
+#[test]
+fn test_invalid_argument_exit_code() {
+    let output = run(&["--device=oscilloscope"]);
+    assert_eq!(output.status.code(), Some(5));
+}
+
+#[test]
+fn test_daemon_rest_client() {
+    let owon: &[(&str, &[u8])] = &[("MEAS?", b"1.2345E+00\n")];
+    let simulator = Simulator::start(owon);
+    let address = TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().to_string();
+    let _daemon = Daemon(
+        Command::new(env!("CARGO_BIN_EXE_hardware-measurement"))
+            .args([
+                "--config=examples/daemon/config.toml",
+                "--profile=input",
+                "--profile=output",
+                &format!("--serial={}", simulator.path()),
+                &format!("--serve={}", address),
+            ])
+            .spawn()
+            .expect("Could not start the daemon"),
+    );
+    let start = Instant::now();
+    while TcpStream::connect(&address).is_err() {
+        assert!(start.elapsed() < STARTUP_TIMEOUT, "The daemon did not start");
+        sleep(Duration::from_millis(50));
+    }
+
+    let (status, body) = get(&address, "/devices");
+    assert_eq!(status, "HTTP/1.1 200 OK");
+    assert_eq!(body, serde_json::json!(["input", "output"]));
+
+    // The output meter is simulated: the profile selects DC volts and measures
+    #[cfg(feature = "serial")]
+    {
+        let (status, body) = get(&address, "/devices/output/reading?max_age=60s");
+        assert_eq!(status, "HTTP/1.1 200 OK");
+        assert_eq!(body["cached"], false);
+        assert_eq!(body["readings"][0]["function"], "VoltageDc");
+        assert_eq!(body["readings"][0]["measurement"]["value"], 1.2345);
+        let (_, body) = get(&address, "/devices/output/reading?max_age=60s");
+        assert_eq!(body["cached"], true);
+    }
+    // The reading is cached, so the meter is asked only once
+    simulator.assert_received(if cfg!(feature = "serial") { "CONF:VOLT:DC\nMEAS?\n" } else { "" });
+
+    let (status, _) = get(&address, "/devices/scope/reading");
+    assert_eq!(status, "HTTP/1.1 404 Not Found");
+    let (status, _) = get(&address, "/devices/input/reading?max_age=soon");
+    assert_eq!(status, "HTTP/1.1 400 Bad Request");
+}
+